@@ -0,0 +1,70 @@
+/// Maximum length of a `CALL` instruction recognized by [`call_length`].
+///
+/// The longest form is `CALL [base + index * scale + disp32]` (7 bytes),
+/// optionally preceded by a REX prefix and a segment override or branch
+/// hint prefix.
+pub(crate) const MAX_CALL_LENGTH: usize = 9;
+
+/// Decodes a near `CALL` instruction and returns its length.
+///
+/// Recognizes the direct form `CALL rel32` (`E8 cd`) and the indirect form
+/// `CALL r/m64` (`FF /2`). Returns `None` if the bytes do not start with
+/// one of these instructions.
+pub(crate) fn call_length(bytes: &[u8]) -> Option<usize> {
+    let mut length = 0;
+
+    // Segment override / branch hint prefix (CS, SS, DS, ES, FS, GS).
+    if let Some(0x2E | 0x36 | 0x3E | 0x26 | 0x64 | 0x65) = bytes.first() {
+        length += 1;
+    }
+
+    // REX prefix.
+    if let Some(0x40..=0x4F) = bytes.get(length) {
+        length += 1;
+    }
+
+    match bytes.get(length)? {
+        // CALL rel32
+        0xE8 => Some(length + 5),
+
+        // CALL r/m64
+        0xFF => {
+            let modrm = *bytes.get(length + 1)?;
+            let mod_ = modrm >> 6;
+            let reg = (modrm >> 3) & 0b111;
+            let rm = modrm & 0b111;
+
+            if reg != 2 {
+                return None;
+            }
+
+            // Opcode + ModRM.
+            length += 2;
+
+            if mod_ == 0b11 {
+                return Some(length);
+            }
+
+            // SIB byte.
+            if rm == 0b100 {
+                let sib = *bytes.get(length)?;
+                length += 1;
+
+                // No base register, disp32 follows.
+                if mod_ == 0b00 && sib & 0b111 == 0b101 {
+                    return Some(length + 4);
+                }
+            }
+
+            match mod_ {
+                // RIP-relative, disp32 follows.
+                0b00 if rm == 0b101 => Some(length + 4),
+                0b00 => Some(length),
+                0b01 => Some(length + 1),
+                _ => Some(length + 4),
+            }
+        }
+
+        _ => None,
+    }
+}
@@ -0,0 +1,179 @@
+use vmi_core::{CpuidResult, VcpuId, VmiCore, VmiDriver, VmiError};
+
+use crate::Amd64;
+
+bitflags::bitflags! {
+    /// CPU features reported by `CPUID` leaves 1 and 7.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct CpuFeatures: u64 {
+        // Leaf 1, EDX
+        /// Time Stamp Counter.
+        const TSC          = 1 << 0;
+        /// Physical Address Extension.
+        const PAE          = 1 << 1;
+        /// Page Global Enable.
+        const PGE          = 1 << 2;
+        /// Page Attribute Table.
+        const PAT          = 1 << 3;
+        /// `SSE` extensions.
+        const SSE          = 1 << 4;
+        /// `SSE2` extensions.
+        const SSE2         = 1 << 5;
+
+        // Leaf 1, ECX
+        /// `SSE3` extensions.
+        const SSE3         = 1 << 6;
+        /// `PCLMULQDQ` instruction.
+        const PCLMULQDQ    = 1 << 7;
+        /// Virtual Machine Extensions.
+        const VMX          = 1 << 8;
+        /// `SSSE3` extensions.
+        const SSSE3        = 1 << 9;
+        /// Fused multiply-add.
+        const FMA          = 1 << 10;
+        /// Process-context identifiers.
+        const PCID         = 1 << 11;
+        /// `SSE4.1` extensions.
+        const SSE4_1       = 1 << 12;
+        /// `SSE4.2` extensions.
+        const SSE4_2       = 1 << 13;
+        /// x2APIC.
+        const X2APIC       = 1 << 14;
+        /// `POPCNT` instruction.
+        const POPCNT       = 1 << 15;
+        /// AES instructions.
+        const AES          = 1 << 16;
+        /// `XSAVE`/`XRSTOR` instructions.
+        const XSAVE        = 1 << 17;
+        /// `XSAVE` enabled by the OS (`CR4.OSXSAVE`).
+        const OSXSAVE      = 1 << 18;
+        /// Advanced Vector Extensions.
+        const AVX          = 1 << 19;
+        /// `RDRAND` instruction.
+        const RDRAND       = 1 << 20;
+        /// Running under a hypervisor.
+        const HYPERVISOR   = 1 << 21;
+
+        // Leaf 7, subleaf 0, EBX
+        /// `RDFSBASE`/`WRFSBASE` instructions.
+        const FSGSBASE     = 1 << 22;
+        /// Bit manipulation instruction set 1.
+        const BMI1         = 1 << 23;
+        /// Advanced Vector Extensions 2.
+        const AVX2         = 1 << 24;
+        /// Supervisor-mode execution prevention.
+        const SMEP         = 1 << 25;
+        /// Bit manipulation instruction set 2.
+        const BMI2         = 1 << 26;
+        /// `INVPCID` instruction.
+        const INVPCID      = 1 << 27;
+        /// Restricted transactional memory.
+        const RTM          = 1 << 28;
+        /// AVX-512 foundation.
+        const AVX512F      = 1 << 29;
+        /// `RDSEED` instruction.
+        const RDSEED       = 1 << 30;
+        /// Supervisor-mode access prevention.
+        const SMAP         = 1 << 31;
+        /// SHA extensions.
+        const SHA          = 1 << 32;
+
+        // Leaf 7, subleaf 0, ECX
+        /// User-mode instruction prevention.
+        const UMIP         = 1 << 33;
+        /// Protection keys for user-mode pages.
+        const PKU          = 1 << 34;
+        /// CET shadow stack.
+        const CET_SS       = 1 << 35;
+        /// 5-level paging.
+        const LA57         = 1 << 36;
+
+        // Leaf 7, subleaf 0, EDX
+        /// CET indirect branch tracking.
+        const CET_IBT      = 1 << 37;
+    }
+}
+
+impl CpuFeatures {
+    /// Parses the features from the results of `CPUID` leaf 1 and leaf 7
+    /// (subleaf 0).
+    pub fn from_cpuid(leaf1: CpuidResult, leaf7: CpuidResult) -> Self {
+        #[rustfmt::skip]
+        let bits = [
+            (leaf1.edx, 4, Self::TSC),
+            (leaf1.edx, 6, Self::PAE),
+            (leaf1.edx, 13, Self::PGE),
+            (leaf1.edx, 16, Self::PAT),
+            (leaf1.edx, 25, Self::SSE),
+            (leaf1.edx, 26, Self::SSE2),
+
+            (leaf1.ecx, 0, Self::SSE3),
+            (leaf1.ecx, 1, Self::PCLMULQDQ),
+            (leaf1.ecx, 5, Self::VMX),
+            (leaf1.ecx, 9, Self::SSSE3),
+            (leaf1.ecx, 12, Self::FMA),
+            (leaf1.ecx, 17, Self::PCID),
+            (leaf1.ecx, 19, Self::SSE4_1),
+            (leaf1.ecx, 20, Self::SSE4_2),
+            (leaf1.ecx, 21, Self::X2APIC),
+            (leaf1.ecx, 23, Self::POPCNT),
+            (leaf1.ecx, 25, Self::AES),
+            (leaf1.ecx, 26, Self::XSAVE),
+            (leaf1.ecx, 27, Self::OSXSAVE),
+            (leaf1.ecx, 28, Self::AVX),
+            (leaf1.ecx, 30, Self::RDRAND),
+            (leaf1.ecx, 31, Self::HYPERVISOR),
+
+            (leaf7.ebx, 0, Self::FSGSBASE),
+            (leaf7.ebx, 3, Self::BMI1),
+            (leaf7.ebx, 5, Self::AVX2),
+            (leaf7.ebx, 7, Self::SMEP),
+            (leaf7.ebx, 8, Self::BMI2),
+            (leaf7.ebx, 10, Self::INVPCID),
+            (leaf7.ebx, 11, Self::RTM),
+            (leaf7.ebx, 16, Self::AVX512F),
+            (leaf7.ebx, 18, Self::RDSEED),
+            (leaf7.ebx, 20, Self::SMAP),
+            (leaf7.ebx, 29, Self::SHA),
+
+            (leaf7.ecx, 2, Self::UMIP),
+            (leaf7.ecx, 3, Self::PKU),
+            (leaf7.ecx, 7, Self::CET_SS),
+            (leaf7.ecx, 16, Self::LA57),
+
+            (leaf7.edx, 20, Self::CET_IBT),
+        ];
+
+        let mut result = Self::empty();
+        for (register, bit, feature) in bits {
+            if register >> bit & 1 != 0 {
+                result |= feature;
+            }
+        }
+
+        result
+    }
+
+    /// Retrieves the features the guest observes on a specific virtual CPU.
+    ///
+    /// Leaf 7 is queried only if leaf 0 reports it as supported.
+    ///
+    /// # Notes
+    ///
+    /// The features report what the guest is allowed to use, not whether
+    /// the guest enabled them. For example, whether SMAP is actually in
+    /// effect is determined by `CR4.SMAP`.
+    pub fn read<Driver>(vmi: &VmiCore<Driver>, vcpu: VcpuId) -> Result<Self, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let max_leaf = vmi.guest_cpuid(vcpu, 0, 0)?.eax;
+        let leaf1 = vmi.guest_cpuid(vcpu, 1, 0)?;
+        let leaf7 = match max_leaf >= 7 {
+            true => vmi.guest_cpuid(vcpu, 7, 0)?,
+            false => CpuidResult::default(),
+        };
+
+        Ok(Self::from_cpuid(leaf1, leaf7))
+    }
+}
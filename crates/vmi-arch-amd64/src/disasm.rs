@@ -0,0 +1,111 @@
+use iced_x86::{Decoder, DecoderError, DecoderOptions, Formatter as _, IntelFormatter};
+use vmi_core::{Architecture as _, Registers as _, Va, VmiCore, VmiDriver, VmiError};
+
+use crate::{Amd64, Registers};
+
+/// The maximum length of an AMD64 instruction.
+const MAX_INSTRUCTION_LENGTH: usize = 15;
+
+/// A disassembled instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    /// The address of the instruction.
+    pub address: Va,
+
+    /// The encoded instruction.
+    pub bytes: Vec<u8>,
+
+    /// The instruction in Intel syntax (e.g., `mov rax,[rcx+8]`).
+    pub text: String,
+}
+
+impl DisassembledInstruction {
+    /// Returns the length of the instruction in bytes.
+    #[expect(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns the address of the instruction that follows.
+    ///
+    /// This is the value the instruction pointer is advanced to in order
+    /// to skip the instruction.
+    pub fn next_address(&self) -> Va {
+        self.address + self.bytes.len() as u64
+    }
+}
+
+impl Amd64 {
+    /// Disassembles the instruction at the current instruction pointer.
+    pub fn disassemble_current<Driver>(
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+    ) -> Result<DisassembledInstruction, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>,
+    {
+        Self::disassemble(vmi, registers, Va(registers.rip))
+    }
+
+    /// Disassembles the instruction at the given address.
+    ///
+    /// The address is translated using the translation root of the
+    /// registers, and the instruction is decoded in the mode the VCPU
+    /// currently executes in (64-bit or compatibility mode).
+    ///
+    /// # Implementation Details
+    ///
+    /// Up to 15 bytes are read. If the instruction crosses a page boundary
+    /// and the next page is not present, only the bytes up to the end of
+    /// the page are decoded. The page fault is returned only if the
+    /// instruction turns out to be longer.
+    pub fn disassemble<Driver>(
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        address: Va,
+    ) -> Result<DisassembledInstruction, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>,
+    {
+        let bitness = match registers.effective_address_width() {
+            8 => 64,
+            4 => 32,
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let in_page = (Self::PAGE_SIZE - Self::va_offset(address)) as usize;
+        let mut buffer = [0u8; MAX_INSTRUCTION_LENGTH];
+
+        let mut length = MAX_INSTRUCTION_LENGTH.min(in_page);
+        vmi.read(registers.address_context(address), &mut buffer[..length])?;
+
+        let mut next_page_error = None;
+        if length < MAX_INSTRUCTION_LENGTH {
+            let next_page = address + length as u64;
+            match vmi.read(registers.address_context(next_page), &mut buffer[length..]) {
+                Ok(()) => length = MAX_INSTRUCTION_LENGTH,
+                Err(err) => next_page_error = Some(err),
+            }
+        }
+
+        let mut decoder =
+            Decoder::with_ip(bitness, &buffer[..length], address.0, DecoderOptions::NONE);
+        let instruction = decoder.decode();
+
+        if instruction.is_invalid() {
+            return match (decoder.last_error(), next_page_error) {
+                (DecoderError::NoMoreBytes, Some(err)) => Err(err),
+                _ => Err(VmiError::Other("Invalid instruction")),
+            };
+        }
+
+        let mut text = String::new();
+        IntelFormatter::new().format(&instruction, &mut text);
+
+        Ok(DisassembledInstruction {
+            address,
+            bytes: buffer[..instruction.len()].to_vec(),
+            text,
+        })
+    }
+}
@@ -1,6 +1,8 @@
-use vmi_core::{Gfn, MemoryAccess, Pa, Va};
+use vmi_core::{
+    Gfn, MemoryAccess, Pa, Registers as _, Va, VmiCore, VmiDriver, VmiError, VmiEventResponse,
+};
 
-use crate::{ControlRegister, ExceptionVector, Interrupt};
+use crate::{Amd64, ControlRegister, ExceptionVector, Interrupt, Registers};
 
 bitflags::bitflags! {
     /// Flags describing a memory access event.
@@ -103,6 +105,123 @@ pub struct EventIo {
     pub string: bool,
 }
 
+impl EventIo {
+    /// Creates a response that completes an `IN` instruction with the
+    /// given input value.
+    ///
+    /// The value is truncated to the access size and written to the
+    /// `AL`/`AX`/`EAX` register, and the instruction pointer is advanced
+    /// past the instruction.
+    ///
+    /// # Notes
+    ///
+    /// When an I/O event is delivered, the hypervisor does not perform the
+    /// port access itself. The response is expected to complete the
+    /// instruction. String I/O instructions are not supported.
+    pub fn respond_in<Driver>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        value: u32,
+    ) -> Result<VmiEventResponse<Amd64>, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        if !matches!(self.direction, EventIoDirection::In) {
+            return Err(VmiError::Other("I/O event is not an IN instruction"));
+        }
+
+        let mut registers = self.complete(vmi, registers)?;
+        registers.rax = match self.length {
+            1 => registers.rax & !0xff | u64::from(value as u8),
+            2 => registers.rax & !0xffff | u64::from(value as u16),
+            // 32-bit register writes are zero-extended.
+            _ => u64::from(value),
+        };
+
+        Ok(VmiEventResponse::set_registers(registers.gp_registers()))
+    }
+
+    /// Creates a response that completes an `OUT` instruction.
+    ///
+    /// The instruction pointer is advanced past the instruction, effectively
+    /// discarding the output value.
+    ///
+    /// # Notes
+    ///
+    /// String I/O instructions are not supported.
+    pub fn respond_out<Driver>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+    ) -> Result<VmiEventResponse<Amd64>, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        if !matches!(self.direction, EventIoDirection::Out) {
+            return Err(VmiError::Other("I/O event is not an OUT instruction"));
+        }
+
+        let registers = self.complete(vmi, registers)?;
+        Ok(VmiEventResponse::set_registers(registers.gp_registers()))
+    }
+
+    /// Advances the instruction pointer past the I/O instruction.
+    ///
+    /// The I/O event does not carry the instruction length, so it is
+    /// decoded from the instruction bytes.
+    fn complete<Driver>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+    ) -> Result<Registers, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        if self.string {
+            return Err(VmiError::NotSupported);
+        }
+
+        // The longest valid instruction is 15 bytes. The bytes are read one
+        // at a time, so that a short instruction at the end of a page doesn't
+        // fail because the next page is not mapped.
+        let mut instruction_length = 0;
+        let opcode = loop {
+            if instruction_length == 15 {
+                return Err(VmiError::Other("unexpected I/O instruction"));
+            }
+
+            let va = Va(registers.rip.wrapping_add(instruction_length));
+            let byte = vmi.read_u8(registers.address_context(va))?;
+            instruction_length += 1;
+
+            match byte {
+                // Legacy prefixes: LOCK, REPNE, REP, segment overrides,
+                // operand-size and address-size overrides.
+                0xf0 | 0xf2 | 0xf3 | 0x26 | 0x2e | 0x36 | 0x3e | 0x64 | 0x65 | 0x66 | 0x67 => {}
+
+                // REX prefix. Outside of 64-bit mode these are INC/DEC,
+                // which never cause an I/O event.
+                0x40..=0x4f => {}
+
+                opcode => break opcode,
+            }
+        };
+
+        match opcode {
+            // IN AL/eAX, imm8; OUT imm8, AL/eAX
+            0xe4..=0xe7 => instruction_length += 1,
+            // IN AL/eAX, DX; OUT DX, AL/eAX
+            0xec..=0xef => {}
+            _ => return Err(VmiError::Other("unexpected I/O instruction")),
+        }
+
+        let mut registers = *registers;
+        registers.rip += instruction_length;
+        Ok(registers)
+    }
+}
+
 /// Reason for an event.
 #[derive(Debug, Clone, Copy)]
 pub enum EventReason {
@@ -174,6 +293,15 @@ impl EventReason {
         }
     }
 
+    /// Checks whether the event reason is a singlestep event.
+    ///
+    /// Useful for handlers that arm singlestepping on a VCPU and need to
+    /// tell the completion of the step apart from other events delivered
+    /// on the same VCPU.
+    pub fn is_singlestep(&self) -> bool {
+        matches!(self, Self::Singlestep(_))
+    }
+
     /// Returns the CPUID instruction event.
     ///
     /// # Panics
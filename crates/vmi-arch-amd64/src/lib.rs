@@ -1,8 +1,12 @@
 //! AMD64 architecture definitions.
 
 mod address;
+mod call;
+mod cpuid;
 mod cr;
 mod descriptor;
+#[cfg(feature = "disasm")]
+mod disasm;
 mod dr;
 mod efer;
 mod event;
@@ -18,7 +22,10 @@ use vmi_core::{
 };
 use zerocopy::FromBytes;
 
+#[cfg(feature = "disasm")]
+pub use self::disasm::DisassembledInstruction;
 pub use self::{
+    cpuid::CpuFeatures,
     cr::{ControlRegister, Cr0, Cr2, Cr3, Cr4},
     descriptor::{Gdtr, Idtr},
     dr::{Dr0, Dr1, Dr2, Dr3, Dr6, Dr7},
@@ -32,7 +39,7 @@ pub use self::{
     registers::{GpRegisters, Registers},
     rflags::Rflags,
     segment::{
-        DescriptorTable, DescriptorType, Granularity, OperationSize, SegmentAccess,
+        DescriptorTable, DescriptorType, Granularity, OperationSize, Segment, SegmentAccess,
         SegmentDescriptor, Selector,
     },
     translation::{TranslationEntries, TranslationEntry, VaTranslation},
@@ -51,8 +58,10 @@ impl Architecture for Amd64 {
 
     type Registers = Registers;
     type PageTableLevel = PageTableLevel;
+    type PageTableEntry = PageTableEntry;
     type Interrupt = Interrupt;
     type SpecialRegister = ControlRegister;
+    type Segment = Segment;
 
     type EventMonitor = EventMonitor;
     type EventReason = EventReason;
@@ -102,75 +111,150 @@ impl Architecture for Amd64 {
         }
     }
 
+    fn is_kernel_address(va: Va) -> bool {
+        va.0 & (1 << 63) != 0
+    }
+
+    fn segment_base(registers: &Registers, segment: Segment) -> Va {
+        // In 64-bit mode, the processor treats the segment base of CS, DS,
+        // ES and SS as zero. FS and GS are the only segments whose base is
+        // used, and their hidden bases are loaded from MSR_FS_BASE and
+        // MSR_GS_BASE.
+        let long_mode = registers.cs.access.long_mode();
+
+        match segment {
+            Segment::Cs if !long_mode => Va(registers.cs.base),
+            Segment::Ds if !long_mode => Va(registers.ds.base),
+            Segment::Es if !long_mode => Va(registers.es.base),
+            Segment::Ss if !long_mode => Va(registers.ss.base),
+            Segment::Cs | Segment::Ds | Segment::Es | Segment::Ss => Va(0),
+            Segment::Fs => Va(registers.fs.base),
+            Segment::Gs => Va(registers.gs.base),
+            Segment::KernelGs => Va(registers.shadow_gs),
+        }
+    }
+
     fn translate_address<Driver>(vmi: &VmiCore<Driver>, va: Va, root: Pa) -> Result<Pa, VmiError>
     where
         Driver: VmiDriver<Architecture = Self>,
     {
-        // Read the PML4 table
-        let buffer = vmi.read_page(Self::gfn_from_pa(root))?;
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
+        Self::translate_address_ex(vmi, va, root).map(|(pa, _)| pa)
+    }
 
+    fn translate_address_ex<Driver>(
+        vmi: &VmiCore<Driver>,
+        va: Va,
+        root: Pa,
+    ) -> Result<(Pa, PageTableEntry), VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>,
+    {
+        Self::translate_address_walk(vmi, va, root, Ok)
+    }
+
+    fn translate_address_mapped<Driver>(
+        vmi: &VmiCore<Driver>,
+        va: Va,
+        root: Pa,
+        map: impl FnMut(Pa) -> Result<Pa, VmiError>,
+    ) -> Result<Pa, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>,
+    {
+        Self::translate_address_walk(vmi, va, root, map).map(|(pa, _)| pa)
+    }
+}
+
+impl Amd64 {
+    /// Performs a page table walk, passing the physical address of every
+    /// paging structure through `map` before it is read.
+    fn translate_address_walk<Driver>(
+        vmi: &VmiCore<Driver>,
+        va: Va,
+        root: Pa,
+        mut map: impl FnMut(Pa) -> Result<Pa, VmiError>,
+    ) -> Result<(Pa, PageTableEntry), VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>,
+    {
+        let mut read_table = |pa: Pa| vmi.read_page(Self::gfn_from_pa(map(pa)?));
+
+        // Read the PML4 table
+        let buffer = read_table(root)?;
         let pml4i = Self::va_index_for(va, PageTableLevel::Pml4) as usize;
-        let pml4e = page_table[pml4i];
+        let pml4e = Self::page_table_entry(&buffer, pml4i)?;
 
         if !pml4e.present() {
             return Err(VmiError::page_fault((va, root)));
         }
 
         if pml4e.large() {
-            return Ok(
-                Self::pa_from_gfn(pml4e.pfn()) + Self::va_offset_for(va, PageTableLevel::Pml4)
-            );
+            return Ok((
+                Self::pa_from_gfn(pml4e.pfn()) + Self::va_offset_for(va, PageTableLevel::Pml4),
+                pml4e,
+            ));
         }
 
         // Read the PDPT table
-        let buffer = vmi.read_page(pml4e.pfn())?;
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
-
+        let buffer = read_table(Self::pa_from_gfn(pml4e.pfn()))?;
         let pdpti = Self::va_index_for(va, PageTableLevel::Pdpt) as usize;
-        let pdpte = page_table[pdpti];
+        let pdpte = Self::page_table_entry(&buffer, pdpti)?;
 
         if !pdpte.present() {
             return Err(VmiError::page_fault((va, root)));
         }
 
         if pdpte.large() {
-            return Ok(
-                Self::pa_from_gfn(pdpte.pfn()) + Self::va_offset_for(va, PageTableLevel::Pdpt)
-            );
+            return Ok((
+                Self::pa_from_gfn(pdpte.pfn()) + Self::va_offset_for(va, PageTableLevel::Pdpt),
+                pdpte,
+            ));
         }
 
         // Read the PD table
-        let buffer = vmi.read_page(pdpte.pfn())?;
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
-
+        let buffer = read_table(Self::pa_from_gfn(pdpte.pfn()))?;
         let pdi = Self::va_index_for(va, PageTableLevel::Pd) as usize;
-        let pde = page_table[pdi];
+        let pde = Self::page_table_entry(&buffer, pdi)?;
 
         if !pde.present() {
             return Err(VmiError::page_fault((va, root)));
         }
 
         if pde.large() {
-            return Ok(Self::pa_from_gfn(pde.pfn()) + Self::va_offset_for(va, PageTableLevel::Pd));
+            return Ok((
+                Self::pa_from_gfn(pde.pfn()) + Self::va_offset_for(va, PageTableLevel::Pd),
+                pde,
+            ));
         }
 
         // Read the PT table
-        let buffer = vmi.read_page(pde.pfn())?;
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
-
+        let buffer = read_table(Self::pa_from_gfn(pde.pfn()))?;
         let pti = Self::va_index_for(va, PageTableLevel::Pt) as usize;
-        let pte = page_table[pti];
+        let pte = Self::page_table_entry(&buffer, pti)?;
 
         if !pte.present() {
             return Err(VmiError::page_fault((va, root)));
         }
 
-        Ok(Self::pa_from_gfn(pte.pfn()) + Self::va_offset_for(va, PageTableLevel::Pt))
+        Ok((
+            Self::pa_from_gfn(pte.pfn()) + Self::va_offset_for(va, PageTableLevel::Pt),
+            pte,
+        ))
+    }
+
+    /// Reads an entry of a page table.
+    ///
+    /// The entry is copied out of the page rather than cast in place, so
+    /// the page content doesn't need to be aligned. Drivers are free to
+    /// back mapped pages with any buffer.
+    fn page_table_entry(page: &[u8], index: usize) -> Result<PageTableEntry, VmiError> {
+        let offset = index * size_of::<PageTableEntry>();
+
+        page.get(offset..offset + size_of::<PageTableEntry>())
+            .and_then(|entry| PageTableEntry::read_from_bytes(entry).ok())
+            .ok_or(VmiError::OutOfBounds)
     }
-}
 
-impl Amd64 {
     /// Canonicalize a virtual address.
     pub fn va_canonical(va: Va) -> Va {
         const BITS: u64 = 48;
@@ -227,6 +311,70 @@ impl Amd64 {
         vmi.read_struct::<Idt>((idtr_base, registers.cr3.into()))
     }
 
+    /// Locates the `CALL` instruction that precedes a return address.
+    ///
+    /// Return addresses found on the stack point to the instruction
+    /// following the call. Symbolizing the call site instead gives more
+    /// accurate stack traces, e.g., when the call is the last instruction of
+    /// a function or of a source line.
+    ///
+    /// # Implementation Details
+    ///
+    /// The bytes preceding the return address are decoded as a near
+    /// `CALL rel32` (`E8`) or `CALL r/m64` (`FF /2`) instruction that ends
+    /// exactly at the return address. The direct form is tried first, then
+    /// the indirect forms from the shortest to the longest.
+    ///
+    /// Returns [`VmiError::Other`] if no call instruction precedes the
+    /// return address.
+    pub fn call_site_for_return<Driver>(
+        vmi: &VmiCore<Driver>,
+        return_address: impl Into<AddressContext>,
+    ) -> Result<Va, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>,
+    {
+        const CALL_REL32_LENGTH: usize = 5;
+
+        let return_address = return_address.into();
+
+        // The call may start on the preceding page, which might not be
+        // present. In that case, only the calls that fit on the page of the
+        // return address can be recognized.
+        let mut buffer = [0u8; call::MAX_CALL_LENGTH];
+        let mut length = call::MAX_CALL_LENGTH;
+        let ctx = AddressContext::new(return_address.va - length as u64, return_address.root);
+        if let Err(err) = vmi.read(ctx, &mut buffer) {
+            length = length.min(Self::va_offset(return_address.va) as usize);
+            if length == 0 {
+                return Err(err);
+            }
+
+            let ctx = AddressContext::new(return_address.va - length as u64, return_address.root);
+            vmi.read(ctx, &mut buffer[call::MAX_CALL_LENGTH - length..])?;
+        }
+
+        let buffer = &buffer[call::MAX_CALL_LENGTH - length..];
+
+        let candidates = std::iter::once(CALL_REL32_LENGTH)
+            .chain((2..=length).filter(|&candidate| candidate != CALL_REL32_LENGTH));
+
+        for candidate in candidates {
+            if candidate > length {
+                continue;
+            }
+
+            let bytes = &buffer[length - candidate..];
+            if call::call_length(bytes) == Some(candidate) {
+                return Ok(return_address.va - candidate as u64);
+            }
+        }
+
+        Err(VmiError::Other(
+            "no call instruction precedes the return address",
+        ))
+    }
+
     /// Performs a page table walk to translate a virtual address to a physical
     /// address.
     ///
@@ -278,9 +426,11 @@ impl Amd64 {
             Ok(buffer) => buffer,
             Err(_) => return VaTranslation { entries, pa: None },
         };
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
         let pml4i = Self::va_index_for(va, PageTableLevel::Pml4) as usize;
-        let pml4e = page_table[pml4i];
+        let pml4e = match Self::page_table_entry(&buffer, pml4i) {
+            Ok(entry) => entry,
+            Err(_) => return VaTranslation { entries, pa: None },
+        };
 
         entries.push(TranslationEntry {
             level: PageTableLevel::Pml4,
@@ -306,9 +456,11 @@ impl Amd64 {
             Ok(buffer) => buffer,
             Err(_) => return VaTranslation { entries, pa: None },
         };
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
         let pdpti = Self::va_index_for(va, PageTableLevel::Pdpt) as usize;
-        let pdpte = page_table[pdpti];
+        let pdpte = match Self::page_table_entry(&buffer, pdpti) {
+            Ok(entry) => entry,
+            Err(_) => return VaTranslation { entries, pa: None },
+        };
 
         entries.push(TranslationEntry {
             level: PageTableLevel::Pdpt,
@@ -335,9 +487,11 @@ impl Amd64 {
             Ok(buffer) => buffer,
             Err(_) => return VaTranslation { entries, pa: None },
         };
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
         let pdi = Self::va_index_for(va, PageTableLevel::Pd) as usize;
-        let pde = page_table[pdi];
+        let pde = match Self::page_table_entry(&buffer, pdi) {
+            Ok(entry) => entry,
+            Err(_) => return VaTranslation { entries, pa: None },
+        };
 
         entries.push(TranslationEntry {
             level: PageTableLevel::Pd,
@@ -364,9 +518,11 @@ impl Amd64 {
             Ok(buffer) => buffer,
             Err(_) => return VaTranslation { entries, pa: None },
         };
-        let page_table = <[PageTableEntry]>::ref_from_bytes(&buffer).unwrap();
         let pti = Self::va_index_for(va, PageTableLevel::Pt) as usize;
-        let pte = page_table[pti];
+        let pte = match Self::page_table_entry(&buffer, pti) {
+            Ok(entry) => entry,
+            Err(_) => return VaTranslation { entries, pa: None },
+        };
 
         entries.push(TranslationEntry {
             level: PageTableLevel::Pt,
@@ -124,6 +124,11 @@ impl PageTableEntry {
         self.0 >> 8 & 1 != 0
     }
 
+    /// Checks if instruction fetches from the page are disallowed (NX).
+    pub fn execute_disable(self) -> bool {
+        self.0 >> 63 & 1 != 0
+    }
+
     /// Extracts the page frame number from the entry.
     pub fn pfn(self) -> Gfn {
         const BITS: u64 = 40;
@@ -144,6 +149,7 @@ impl std::fmt::Debug for PageTableEntry {
             .field("dirty", &self.dirty())
             .field("large", &self.large())
             .field("global", &self.global())
+            .field("execute_disable", &self.execute_disable())
             .field("pfn", &self.pfn())
             .finish()
     }
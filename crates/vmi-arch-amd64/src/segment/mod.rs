@@ -4,6 +4,41 @@ pub use self::descriptor::SegmentDescriptor;
 mod selector;
 pub use self::selector::{DescriptorTable, Selector};
 
+/// A segment register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    /// The code segment.
+    Cs,
+
+    /// The data segment.
+    Ds,
+
+    /// The extra segment.
+    Es,
+
+    /// The `FS` segment.
+    ///
+    /// Points to the TEB in 32-bit user mode on Windows and to the
+    /// thread-local storage on Linux.
+    Fs,
+
+    /// The `GS` segment.
+    ///
+    /// Points to the TEB in 64-bit user mode and to the `KPCR` in kernel
+    /// mode on Windows, and to the per-CPU data in kernel mode on Linux.
+    Gs,
+
+    /// The stack segment.
+    Ss,
+
+    /// The inactive `GS` base (`MSR_KERNEL_GS_BASE`).
+    ///
+    /// The `SWAPGS` instruction exchanges it with the `GS` base on
+    /// transitions between user mode and kernel mode. When the guest runs
+    /// in user mode, it holds the kernel `GS` base, and vice versa.
+    KernelGs,
+}
+
 /// Determines the type of segment descriptor.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DescriptorType {
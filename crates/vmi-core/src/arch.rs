@@ -56,6 +56,9 @@ pub trait Architecture {
     /// - **AMD64**: PML5, PML4, PDPT, PD, PT
     type PageTableLevel: Debug + Clone + Copy;
 
+    /// An entry in the architecture's paging structures.
+    type PageTableEntry: Debug + Clone + Copy;
+
     /// Various types of interrupts that can occur in the architecture.
     type Interrupt: Debug + Clone + Copy;
 
@@ -67,6 +70,14 @@ pub trait Architecture {
     ///   `CR4`
     type SpecialRegister: Debug + Clone + Copy;
 
+    /// Segment registers that can be used as a base for memory accesses.
+    ///
+    /// # Architecture-specific
+    ///
+    /// - **AMD64**: `CS`, `DS`, `ES`, `FS`, `GS`, `SS` and the inactive `GS`
+    ///   base (`MSR_KERNEL_GS_BASE`)
+    type Segment: Debug + Clone + Copy;
+
     /// Options for monitoring.
     type EventMonitor;
 
@@ -117,11 +128,62 @@ pub trait Architecture {
     /// hierarchy for a given virtual address.
     fn va_index_for(va: Va, level: Self::PageTableLevel) -> u64;
 
+    /// Checks whether a virtual address lies in the part of the address
+    /// space reserved for the kernel.
+    ///
+    /// # Architecture-specific
+    ///
+    /// - **AMD64**: The upper half of the canonical address space, i.e.,
+    ///   addresses with bit 63 set.
+    fn is_kernel_address(va: Va) -> bool;
+
+    /// Returns the base address of a segment.
+    ///
+    /// # Architecture-specific
+    ///
+    /// - **AMD64**: The base of the hidden part of the segment register.
+    ///   In 64-bit mode, the bases of `CS`, `DS`, `ES` and `SS` are treated
+    ///   as zero, and the bases of `FS` and `GS` correspond to `MSR_FS_BASE`
+    ///   and `MSR_GS_BASE`.
+    fn segment_base(registers: &Self::Registers, segment: Self::Segment) -> Va;
+
     /// Performs a full page table walk to translate a virtual address to a
     /// physical address.
     fn translate_address<Driver>(vmi: &VmiCore<Driver>, va: Va, root: Pa) -> Result<Pa, VmiError>
     where
         Driver: VmiDriver<Architecture = Self>;
+
+    /// Performs a full page table walk to translate a virtual address to a
+    /// physical address, returning the leaf page table entry alongside
+    /// the physical address.
+    ///
+    /// The leaf entry is the entry that maps the page, which may be
+    /// a large page entry at a higher level of the paging structures.
+    fn translate_address_ex<Driver>(
+        vmi: &VmiCore<Driver>,
+        va: Va,
+        root: Pa,
+    ) -> Result<(Pa, Self::PageTableEntry), VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>;
+
+    /// Performs a full page table walk to translate a virtual address to a
+    /// physical address, with the paging structures located in another
+    /// address space.
+    ///
+    /// The physical address of every paging structure, starting with the
+    /// `root`, is passed through `map` before the structure is read. This
+    /// allows walking the page tables of a nested guest, whose physical
+    /// addresses have to be translated by an outer stage first. The
+    /// returned address is not passed through `map`.
+    fn translate_address_mapped<Driver>(
+        vmi: &VmiCore<Driver>,
+        va: Va,
+        root: Pa,
+        map: impl FnMut(Pa) -> Result<Pa, VmiError>,
+    ) -> Result<Pa, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Self>;
 }
 
 /// Complete set of CPU registers for a specific architecture.
@@ -0,0 +1,113 @@
+use std::{
+    hash::Hash,
+    num::NonZeroUsize,
+    ops::{Deref, DerefMut},
+};
+
+use lru::LruCache;
+
+/// Hit, miss and eviction counters of a cache.
+///
+/// The counters are accumulated since the cache was created or since the
+/// last call to [`VmiCore::reset_cache_metrics`].
+///
+/// [`VmiCore::reset_cache_metrics`]: crate::VmiCore::reset_cache_metrics
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CacheMetrics {
+    /// The number of lookups that found the entry in the cache.
+    pub hits: u64,
+
+    /// The number of lookups that didn't find the entry in the cache.
+    pub misses: u64,
+
+    /// The number of entries removed to make room for new ones.
+    pub evictions: u64,
+}
+
+impl CacheMetrics {
+    /// Returns the ratio of hits to all lookups, or `None` if there were
+    /// no lookups.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let lookups = self.hits + self.misses;
+        (lookups != 0).then(|| self.hits as f64 / lookups as f64)
+    }
+}
+
+/// An LRU cache that counts its hits, misses and evictions.
+///
+/// The counters live next to the cache, so they are updated under the same
+/// borrow as the cache itself. Operations that don't affect the counters are
+/// available through [`Deref`].
+pub(crate) struct MeteredCache<K, V> {
+    cache: LruCache<K, V>,
+    metrics: CacheMetrics,
+}
+
+impl<K, V> MeteredCache<K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates a new cache with the given capacity.
+    pub fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            cache: LruCache::new(capacity),
+            metrics: CacheMetrics::default(),
+        }
+    }
+
+    /// Returns the counters.
+    pub fn metrics(&self) -> CacheMetrics {
+        self.metrics
+    }
+
+    /// Resets the counters to zero.
+    pub fn reset_metrics(&mut self) {
+        self.metrics = CacheMetrics::default();
+    }
+
+    /// Looks up an entry, counting a hit or a miss.
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        match self.cache.get(key) {
+            Some(value) => {
+                self.metrics.hits += 1;
+                Some(value)
+            }
+            None => {
+                self.metrics.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Inserts an entry, counting an eviction if the cache was full.
+    pub fn put(&mut self, key: K, value: V) {
+        if let Some((evicted, _)) = self.cache.push(key, value) {
+            // The entry is returned even if it was replaced, rather than
+            // evicted.
+            if !self.cache.contains(&evicted) {
+                self.metrics.evictions += 1;
+            }
+        }
+    }
+
+    /// Resizes the cache, counting the entries that didn't fit as evicted.
+    pub fn resize(&mut self, capacity: NonZeroUsize) {
+        let len = self.cache.len();
+        self.cache.resize(capacity);
+        self.metrics.evictions += (len - self.cache.len()) as u64;
+    }
+}
+
+impl<K, V> Deref for MeteredCache<K, V> {
+    type Target = LruCache<K, V>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cache
+    }
+}
+
+impl<K, V> DerefMut for MeteredCache<K, V> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cache
+    }
+}
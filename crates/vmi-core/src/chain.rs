@@ -0,0 +1,85 @@
+use std::collections::HashSet;
+
+use crate::{AddressContext, Pa, Va, VmiCore, VmiDriver, VmiError};
+
+/// An iterator over the nodes of a singly-linked list.
+///
+/// Each node holds a pointer to the next node at a fixed offset. The
+/// iteration starts with the first node and ends when the pointer to the
+/// next node equals the sentinel (usually NULL).
+///
+/// The iteration also ends when a node is visited twice, or after
+/// [`MAX_LENGTH`] nodes, so that corrupted or concurrently modified lists
+/// can't cause an endless loop. If a node can't be read, the error is
+/// yielded and the iteration ends.
+///
+/// Created by [`VmiCore::follow_chain`].
+///
+/// [`MAX_LENGTH`]: Self::MAX_LENGTH
+pub struct ChainIterator<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    vmi: &'a VmiCore<Driver>,
+    root: Pa,
+    next: Option<Va>,
+    next_offset: u64,
+    sentinel: Va,
+    address_width: usize,
+    visited: HashSet<Va>,
+}
+
+impl<'a, Driver> ChainIterator<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// The maximum number of nodes yielded by the iterator.
+    pub const MAX_LENGTH: usize = 0x10000;
+
+    /// Creates a new iterator starting at the given node.
+    pub(crate) fn new(
+        vmi: &'a VmiCore<Driver>,
+        start: AddressContext,
+        next_offset: u64,
+        sentinel: Va,
+        address_width: usize,
+    ) -> Self {
+        Self {
+            vmi,
+            root: start.root,
+            next: Some(start.va),
+            next_offset,
+            sentinel,
+            address_width,
+            visited: HashSet::new(),
+        }
+    }
+}
+
+impl<Driver> Iterator for ChainIterator<'_, Driver>
+where
+    Driver: VmiDriver,
+{
+    type Item = Result<Va, VmiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+
+        if node == self.sentinel
+            || self.visited.len() >= Self::MAX_LENGTH
+            || !self.visited.insert(node)
+        {
+            return None;
+        }
+
+        match self
+            .vmi
+            .read_va((node + self.next_offset, self.root), self.address_width)
+        {
+            Ok(next) => self.next = Some(next),
+            Err(err) => return Some(Err(err)),
+        }
+
+        Some(Ok(node))
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::BTreeMap;
+
+use crate::{Gfn, MemoryAccess, Va, View, VmiCore, VmiDriver, VmiError};
+
+/// Pages of code protected against writes in a view.
+///
+/// Records the original access permissions of every protected page, so
+/// that they can be restored with [`restore`]. The protection doesn't
+/// hold a reference to the [`VmiCore`], so it can be stored in the state
+/// of an event handler and consulted when a memory access event arrives
+/// (see [`contains`]).
+///
+/// Created by [`VmiCore::protect_module_code`].
+///
+/// # Notes
+///
+/// The permissions are not restored when the protection is dropped. Call
+/// [`restore`] explicitly.
+///
+/// The protection covers the physical pages the code was mapped to at the
+/// time of its creation. If the guest pages the code out and back in,
+/// the new pages aren't protected.
+///
+/// [`restore`]: Self::restore
+/// [`contains`]: Self::contains
+#[derive(Debug)]
+pub struct CodeProtection {
+    view: View,
+    pages: BTreeMap<Gfn, MemoryAccess>,
+    skipped: Vec<Va>,
+}
+
+impl CodeProtection {
+    /// Creates a new empty protection.
+    pub(crate) fn new(view: View) -> Self {
+        Self {
+            view,
+            pages: BTreeMap::new(),
+            skipped: Vec::new(),
+        }
+    }
+
+    /// Records the original access permissions of a protected page.
+    pub(crate) fn insert(&mut self, gfn: Gfn, access: MemoryAccess) {
+        self.pages.insert(gfn, access);
+    }
+
+    /// Records a page that was not protected.
+    pub(crate) fn skip(&mut self, va: Va) {
+        self.skipped.push(va);
+    }
+
+    /// Returns the view the protection applies to.
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    /// Checks whether the guest frame is protected.
+    pub fn contains(&self, gfn: Gfn) -> bool {
+        self.pages.contains_key(&gfn)
+    }
+
+    /// Returns the protected guest frames.
+    pub fn gfns(&self) -> impl Iterator<Item = Gfn> + '_ {
+        self.pages.keys().copied()
+    }
+
+    /// Returns the virtual addresses of the pages that were not protected
+    /// because they were not present in memory.
+    pub fn skipped(&self) -> &[Va] {
+        &self.skipped
+    }
+
+    /// Restores the original access permissions of the protected pages.
+    ///
+    /// If a page fails to be restored, the error is returned and the
+    /// remaining pages are still restored.
+    pub fn restore<Driver>(self, vmi: &VmiCore<Driver>) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let mut result = Ok(());
+
+        for (gfn, access) in self.pages {
+            if let Err(err) = vmi.set_memory_access(gfn, self.view, access) {
+                tracing::error!(?gfn, %err, "failed to restore memory access");
+                result = result.and(Err(err));
+            }
+        }
+
+        result
+    }
+}
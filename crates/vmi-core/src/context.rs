@@ -4,10 +4,10 @@ use indexmap::IndexSet;
 use zerocopy::{FromBytes, Immutable, IntoBytes};
 
 use crate::{
-    os::VmiOs,
+    os::{ProcessObject, ThreadObject, VmiOs},
     session::{VmiSession, VmiSessionProber},
-    Architecture, Pa, PageFault, PageFaults, Registers as _, Va, VmiCore, VmiDriver, VmiError,
-    VmiEvent,
+    Architecture, ChainIterator, Pa, PageFault, PageFaults, Registers as _, Va, VmiCore, VmiDriver,
+    VmiError, VmiEvent,
 };
 
 /// A VMI context.
@@ -99,11 +99,40 @@ where
         self.registers().return_address(self.core())
     }
 
+    /// Returns the process that was running when the event occurred.
+    ///
+    /// Shorthand for `self.os().current_process()`.
+    pub fn current_process(&self) -> Result<ProcessObject, VmiError> {
+        self.underlying_os()
+            .current_process(self.core(), self.registers())
+    }
+
+    /// Returns the thread that was running when the event occurred.
+    ///
+    /// Shorthand for `self.os().current_thread()`.
+    pub fn current_thread(&self) -> Result<ThreadObject, VmiError> {
+        self.underlying_os()
+            .current_thread(self.core(), self.registers())
+    }
+
     /// Reads memory from the virtual machine.
     pub fn read(&self, address: Va, buffer: &mut [u8]) -> Result<(), VmiError> {
         self.core().read(self.access_context(address), buffer)
     }
 
+    /// Reads memory from the address space of a process.
+    ///
+    /// Shorthand for `self.session().read_process_memory(self.registers(), ...)`.
+    pub fn read_process_memory(
+        &self,
+        process: ProcessObject,
+        address: Va,
+        buffer: &mut [u8],
+    ) -> Result<(), VmiError> {
+        self.session()
+            .read_process_memory(self.registers(), process, address, buffer)
+    }
+
     /// Writes memory to the virtual machine.
     pub fn write(&self, address: Va, buffer: &[u8]) -> Result<(), VmiError> {
         self.core().write(self.access_context(address), buffer)
@@ -129,12 +158,58 @@ where
         self.core().read_u64(self.access_context(address))
     }
 
+    /// Reads a 64-bit unsigned integer at an offset from a segment base.
+    ///
+    /// This is useful for accessing thread-local and per-CPU data, e.g.,
+    /// the TEB or the `KPCR` on Windows.
+    pub fn read_u64_seg(
+        &self,
+        segment: <Driver::Architecture as Architecture>::Segment,
+        offset: u64,
+    ) -> Result<u64, VmiError> {
+        self.read_u64(Driver::Architecture::segment_base(self.registers(), segment) + offset)
+    }
+
+    /// Reads a virtual address at an offset from a segment base.
+    pub fn read_va_seg(
+        &self,
+        segment: <Driver::Architecture as Architecture>::Segment,
+        offset: u64,
+    ) -> Result<Va, VmiError> {
+        self.read_va(Driver::Architecture::segment_base(self.registers(), segment) + offset)
+    }
+
     /// Reads a virtual address from the virtual machine.
+    ///
+    /// The size of the address is the effective address width of the
+    /// current execution mode. Structures that don't belong to the current
+    /// mode (e.g., kernel structures read while a 32-bit process runs in
+    /// compatibility mode) should be read with [`read_va_native`] or
+    /// [`read_va_for_mode`] instead.
+    ///
+    /// [`read_va_native`]: Self::read_va_native
+    /// [`read_va_for_mode`]: Self::read_va_for_mode
     pub fn read_va(&self, address: Va) -> Result<Va, VmiError> {
-        self.core().read_va(
-            self.access_context(address),
-            self.registers().effective_address_width(),
-        )
+        self.read_va_for_mode(address, self.registers().effective_address_width())
+    }
+
+    /// Reads a virtual address of the native address width from the
+    /// virtual machine.
+    ///
+    /// Unlike [`read_va`], the result doesn't depend on whether the event
+    /// occurred in compatibility mode, which makes this method suitable
+    /// for reading kernel structures.
+    ///
+    /// [`read_va`]: Self::read_va
+    pub fn read_va_native(&self, address: Va) -> Result<Va, VmiError> {
+        self.read_va_for_mode(address, self.registers().address_width())
+    }
+
+    /// Reads a virtual address of the given width (in bytes) from the
+    /// virtual machine.
+    pub fn read_va_for_mode(&self, address: Va, address_width: usize) -> Result<Va, VmiError> {
+        self.core()
+            .read_va(self.access_context(address), address_width)
     }
 
     /// Reads a 32-bit virtual address from the virtual machine.
@@ -147,6 +222,23 @@ where
         self.core().read_va64(self.access_context(address))
     }
 
+    /// Follows a singly-linked list, starting at the given node.
+    ///
+    /// See [`VmiCore::follow_chain`] for details.
+    pub fn follow_chain(
+        &self,
+        start: Va,
+        next_offset: u64,
+        sentinel: Va,
+    ) -> ChainIterator<'_, Driver> {
+        self.core().follow_chain(
+            self.access_context(start),
+            next_offset,
+            sentinel,
+            self.registers().effective_address_width(),
+        )
+    }
+
     /// Reads a null-terminated string of bytes from the virtual machine.
     pub fn read_string_bytes(&self, address: Va) -> Result<Vec<u8>, VmiError> {
         self.core().read_string_bytes(self.access_context(address))
@@ -387,6 +479,22 @@ where
         self.check_result(self.context.read_va(address))
     }
 
+    /// Reads a virtual address of the native address width from the
+    /// virtual machine.
+    pub fn read_va_native(&self, address: Va) -> Result<Option<Va>, VmiError> {
+        self.check_result(self.context.read_va_native(address))
+    }
+
+    /// Reads a virtual address of the given width (in bytes) from the
+    /// virtual machine.
+    pub fn read_va_for_mode(
+        &self,
+        address: Va,
+        address_width: usize,
+    ) -> Result<Option<Va>, VmiError> {
+        self.check_result(self.context.read_va_for_mode(address, address_width))
+    }
+
     /// Reads a 32-bit virtual address from the virtual machine.
     pub fn read_va32(&self, address: Va) -> Result<Option<Va>, VmiError> {
         self.check_result(self.context.read_va32(address))
@@ -85,6 +85,28 @@ impl AccessContext {
             },
         }
     }
+
+    /// Returns the number of bytes from the address to the next page
+    /// boundary.
+    ///
+    /// If the address is page-aligned, the full page size is returned.
+    /// The `page_size` must be a power of two.
+    pub fn page_remaining(&self, page_size: u64) -> u64 {
+        debug_assert!(page_size.is_power_of_two());
+        page_size - (self.address & (page_size - 1))
+    }
+
+    /// Returns a copy of the context with the address aligned down to the
+    /// page boundary.
+    ///
+    /// The `page_size` must be a power of two.
+    pub fn aligned_down(&self, page_size: u64) -> Self {
+        debug_assert!(page_size.is_power_of_two());
+        Self {
+            address: self.address & !(page_size - 1),
+            ..*self
+        }
+    }
 }
 
 impl From<Pa> for AccessContext {
@@ -0,0 +1,15 @@
+/// Result of the `CPUID` instruction for a leaf and subleaf.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CpuidResult {
+    /// Value of the `EAX` register.
+    pub eax: u32,
+
+    /// Value of the `EBX` register.
+    pub ebx: u32,
+
+    /// Value of the `ECX` register.
+    pub ecx: u32,
+
+    /// Value of the `EDX` register.
+    pub edx: u32,
+}
@@ -1,3 +1,5 @@
+use std::time::SystemTime;
+
 use serde::{Deserialize, Serialize};
 
 use crate::Gfn;
@@ -16,4 +18,19 @@ pub struct VmiInfo {
 
     /// The number of virtual CPUs.
     pub vcpus: u16,
+
+    /// The frequency of the guest's time-stamp counter in Hz.
+    ///
+    /// `None` if the driver cannot determine the frequency.
+    pub tsc_frequency: Option<u64>,
+
+    /// The host wall-clock time at which the guest's time-stamp counter
+    /// was zero.
+    ///
+    /// Together with [`tsc_frequency`], this allows correlating guest TSC
+    /// values with the host clock. `None` if the driver cannot determine
+    /// the offset.
+    ///
+    /// [`tsc_frequency`]: Self::tsc_frequency
+    pub tsc_epoch: Option<SystemTime>,
 }
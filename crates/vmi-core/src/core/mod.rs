@@ -1,5 +1,6 @@
 mod access_context;
 mod address_context;
+mod cpuid;
 mod hex;
 mod info;
 pub(crate) mod macros;
@@ -10,9 +11,10 @@ mod view;
 pub use self::{
     access_context::{AccessContext, Gfn, Pa, TranslationMechanism, Va},
     address_context::AddressContext,
+    cpuid::CpuidResult,
     hex::Hex,
     info::VmiInfo,
     memory_access::MemoryAccess,
     vcpu_id::VcpuId,
-    view::View,
+    view::{View, ViewInfo},
 };
@@ -1,3 +1,5 @@
+use super::MemoryAccess;
+
 /// A physical memory view identifier.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct View(pub u16);
@@ -7,3 +9,21 @@ impl std::fmt::Display for View {
         write!(f, "{}", self.0)
     }
 }
+
+/// Statistics of the modifications made to a view.
+///
+/// Created by [`VmiCore::view_info`].
+///
+/// [`VmiCore::view_info`]: crate::VmiCore::view_info
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ViewInfo {
+    /// The access permissions of the GFNs that were not modified.
+    pub default_access: MemoryAccess,
+
+    /// The number of GFNs mapped to a different GFN in the view.
+    pub remapped_gfns: usize,
+
+    /// The number of GFNs whose access permissions differ from the
+    /// default access permissions of the view.
+    pub restricted_gfns: usize,
+}
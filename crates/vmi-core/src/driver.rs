@@ -1,10 +1,25 @@
 use std::time::Duration;
 
 use crate::{
-    Architecture, Gfn, MemoryAccess, VcpuId, View, VmiError, VmiEvent, VmiEventResponse, VmiInfo,
-    VmiMappedPage,
+    Architecture, CpuidResult, Gfn, MemoryAccess, VcpuId, View, ViewInfo, VmiError, VmiEvent,
+    VmiEventResponse, VmiInfo, VmiMappedPage,
 };
 
+bitflags::bitflags! {
+    /// Optional capabilities supported by a VMI driver.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct DriverCapabilities: u32 {
+        /// Pages returned by [`VmiDriver::read_page`] are mapped directly
+        /// from the guest memory rather than copied into a local buffer.
+        const DIRECT_MAP = 0b00000001;
+
+        /// The driver supports multiple memory views, and
+        /// [`VmiDriver::max_views`] reports how many of them can exist
+        /// at once.
+        const VIEWS = 0b00000010;
+    }
+}
+
 /// A trait for implementing a VMI driver.
 pub trait VmiDriver {
     /// The architecture supported by the driver.
@@ -13,6 +28,11 @@ pub trait VmiDriver {
     /// Retrieves information about the virtual machine.
     fn info(&self) -> Result<VmiInfo, VmiError>;
 
+    /// Returns the optional capabilities supported by the driver.
+    fn capabilities(&self) -> DriverCapabilities {
+        DriverCapabilities::empty()
+    }
+
     /// Pauses the virtual machine.
     fn pause(&self) -> Result<(), VmiError>;
 
@@ -32,6 +52,39 @@ pub trait VmiDriver {
         registers: <Self::Architecture as Architecture>::Registers,
     ) -> Result<(), VmiError>;
 
+    /// Retrieves the `CPUID` result the guest observes on a specific
+    /// virtual CPU.
+    ///
+    /// Drivers that can't query the guest CPUID policy return
+    /// [`VmiError::NotSupported`].
+    fn guest_cpuid(&self, vcpu: VcpuId, leaf: u32, subleaf: u32) -> Result<CpuidResult, VmiError> {
+        let _ = (vcpu, leaf, subleaf);
+        Err(VmiError::NotSupported)
+    }
+
+    /// Retrieves the task priority of a specific virtual CPU.
+    ///
+    /// Drivers that can't query the task priority return
+    /// [`VmiError::NotSupported`].
+    fn task_priority(&self, vcpu: VcpuId) -> Result<u8, VmiError> {
+        let _ = vcpu;
+        Err(VmiError::NotSupported)
+    }
+
+    /// Invalidates the cached address translations of the guest.
+    ///
+    /// If `vcpu` is `None`, the translations of all virtual CPUs are
+    /// invalidated.
+    ///
+    /// Drivers that can't invalidate the translations return
+    /// [`VmiError::NotSupported`]. Drivers without a running guest (e.g.,
+    /// read-only memory dumps) have nothing to invalidate and should
+    /// return `Ok(())`.
+    fn flush_guest_tlb(&self, vcpu: Option<VcpuId>) -> Result<(), VmiError> {
+        let _ = vcpu;
+        Err(VmiError::NotSupported)
+    }
+
     /// Retrieves the memory access permissions for a specific GFN.
     fn memory_access(&self, gfn: Gfn, view: View) -> Result<MemoryAccess, VmiError>;
 
@@ -42,6 +95,15 @@ pub trait VmiDriver {
     /// Reads a page of memory from the virtual machine.
     fn read_page(&self, gfn: Gfn) -> Result<VmiMappedPage, VmiError>;
 
+    /// Checks whether a GFN is backed by guest memory.
+    ///
+    /// The default implementation attempts to read the page and reports
+    /// any failure as an invalid GFN. Drivers that know the memory layout
+    /// of the guest should override this with a cheaper check.
+    fn gfn_valid(&self, gfn: Gfn) -> Result<bool, VmiError> {
+        Ok(self.read_page(gfn).is_ok())
+    }
+
     /// Writes data to a page of memory in the virtual machine.
     fn write_page(&self, gfn: Gfn, offset: u64, content: &[u8]) -> Result<VmiMappedPage, VmiError>;
 
@@ -54,6 +116,14 @@ pub trait VmiDriver {
     /// Returns the default view for the virtual machine.
     fn default_view(&self) -> View;
 
+    /// Returns the maximum number of views that can exist at once,
+    /// including the default view.
+    ///
+    /// Drivers that don't support views return [`VmiError::NotSupported`].
+    fn max_views(&self) -> Result<u16, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
     /// Creates a new view with the specified default access permissions.
     fn create_view(&self, default_access: MemoryAccess) -> Result<View, VmiError>;
 
@@ -69,6 +139,54 @@ pub trait VmiDriver {
     /// Resets the mapping of a GFN in a specific view to its original state.
     fn reset_view_gfn(&self, view: View, gfn: Gfn) -> Result<(), VmiError>;
 
+    /// Returns statistics of the modifications made to a view.
+    ///
+    /// The default implementation returns [`VmiError::NotSupported`].
+    fn view_info(&self, view: View) -> Result<ViewInfo, VmiError> {
+        let _ = view;
+        Err(VmiError::NotSupported)
+    }
+
+    /// Enables tracking of GFNs written by the guest.
+    ///
+    /// The tracking covers the whole VM, regardless of the view the guest
+    /// runs in.
+    ///
+    /// The default implementation returns [`VmiError::NotSupported`].
+    fn enable_dirty_tracking(&self) -> Result<(), VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    /// Disables tracking of GFNs written by the guest.
+    ///
+    /// The default implementation returns [`VmiError::NotSupported`].
+    fn disable_dirty_tracking(&self) -> Result<(), VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    /// Returns the GFNs written by the guest since the tracking was enabled
+    /// or last cleared.
+    ///
+    /// The default implementation returns [`VmiError::NotSupported`].
+    fn dirty_gfns(&self) -> Result<Vec<Gfn>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    /// Clears the set of GFNs written by the guest.
+    ///
+    /// The default implementation returns [`VmiError::NotSupported`].
+    fn clear_dirty(&self) -> Result<(), VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    /// Returns the GFNs written by the guest since the tracking was enabled
+    /// or last cleared, and clears the set in the same operation.
+    ///
+    /// The default implementation returns [`VmiError::NotSupported`].
+    fn take_dirty_gfns(&self) -> Result<Vec<Gfn>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
     /// Enables monitoring of specific events.
     fn monitor_enable(
         &self,
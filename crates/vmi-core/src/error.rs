@@ -47,6 +47,15 @@ pub enum VmiError {
     #[error("Operation timed out.")]
     Timeout,
 
+    /// A structure read from the guest has inconsistent fields.
+    #[error("Corrupted structure: {0}")]
+    CorruptedStruct(&'static str),
+
+    /// An event handler waited for events in a way the re-entrancy policy
+    /// doesn't allow.
+    #[error("Re-entrant event handler invocation")]
+    ReentrantEvent,
+
     /// The view was not found.
     #[error("The view was not found.")]
     ViewNotFound,
@@ -159,6 +159,19 @@ where
         Self::default().and_set_registers(registers)
     }
 
+    /// Creates a response that lets the instruction that caused a memory
+    /// access event execute in the given view.
+    ///
+    /// The view should not restrict the access. Single-stepping is enabled,
+    /// so that a singlestep event is received right after the instruction
+    /// executes. Its handler is expected to switch back to the restricted
+    /// view and disable single-stepping (i.e., respond with
+    /// `toggle_singlestep().and_set_view(restricted_view)`), so that the
+    /// protection stays armed for subsequent accesses.
+    pub fn allow_once(view: View) -> Self {
+        Self::toggle_singlestep().and_set_view(view)
+    }
+
     /// Adds the reinject interrupt flag to the response.
     pub fn and_reinject_interrupt(self) -> Self {
         Self {
@@ -0,0 +1,216 @@
+//! Decoding of the SMBIOS tables of the guest firmware.
+//!
+//! The SMBIOS entry point is located by scanning the legacy BIOS area
+//! (`0xF0000` - `0xFFFFF`) on 16-byte boundaries for the `_SM3_` (64-bit)
+//! or `_SM_` (32-bit) anchor. The entry point holds the physical address
+//! of the structure table, which consists of variable-length structures,
+//! each followed by a set of NUL-terminated strings.
+//!
+//! # References
+//!
+//! - [DMTF DSP0134 - System Management BIOS (SMBIOS) Reference Specification](https://www.dmtf.org/standards/smbios)
+
+use crate::{Pa, VmiCore, VmiDriver, VmiError};
+
+/// The start of the area the entry point is searched in.
+const SCAN_START: u64 = 0xf0000;
+
+/// The size of the area the entry point is searched in.
+const SCAN_SIZE: usize = 0x10000;
+
+/// The largest structure table accepted.
+///
+/// The 64-bit entry point allows tables up to 4 GiB, anything beyond a few
+/// kilobytes is a sign of corrupted memory.
+const MAX_TABLE_SIZE: usize = 0x10_0000;
+
+/// BIOS Information (Type 0).
+const TYPE_BIOS: u8 = 0;
+
+/// System Information (Type 1).
+const TYPE_SYSTEM: u8 = 1;
+
+/// Baseboard Information (Type 2).
+const TYPE_BASEBOARD: u8 = 2;
+
+/// End-of-Table (Type 127).
+const TYPE_END_OF_TABLE: u8 = 127;
+
+/// Information from the SMBIOS tables of the guest.
+///
+/// Strings that are not present in the tables are `None`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct FirmwareTables {
+    /// The SMBIOS version (major, minor) from the entry point.
+    pub smbios_version: (u8, u8),
+
+    /// The physical address of the structure table.
+    pub table_address: Pa,
+
+    /// The `Vendor` string of the BIOS Information (Type 0).
+    pub bios_vendor: Option<String>,
+
+    /// The `BIOS Version` string of the BIOS Information (Type 0).
+    pub bios_version: Option<String>,
+
+    /// The `Manufacturer` string of the System Information (Type 1).
+    pub system_manufacturer: Option<String>,
+
+    /// The `Product Name` string of the System Information (Type 1).
+    pub system_product: Option<String>,
+
+    /// The `Version` string of the System Information (Type 1).
+    pub system_version: Option<String>,
+
+    /// The `Serial Number` string of the System Information (Type 1).
+    pub system_serial_number: Option<String>,
+
+    /// The `Manufacturer` string of the Baseboard Information (Type 2).
+    pub baseboard_manufacturer: Option<String>,
+
+    /// The `Product` string of the Baseboard Information (Type 2).
+    pub baseboard_product: Option<String>,
+}
+
+/// The location of the structure table, as described by the entry point.
+struct EntryPoint {
+    version: (u8, u8),
+    table_address: u64,
+    table_size: usize,
+}
+
+impl FirmwareTables {
+    /// Reads the SMBIOS tables from the guest physical memory.
+    pub(crate) fn read<Driver>(vmi: &VmiCore<Driver>) -> Result<Self, VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let mut area = vec![0u8; SCAN_SIZE];
+        vmi.read(Pa(SCAN_START), &mut area)?;
+
+        let entry_point = match find_entry_point(&area) {
+            Some(entry_point) => entry_point,
+            None => return Err(VmiError::Other("SMBIOS entry point not found")),
+        };
+
+        if entry_point.table_size > MAX_TABLE_SIZE {
+            return Err(VmiError::CorruptedStruct("SMBIOS entry point"));
+        }
+
+        let mut table = vec![0u8; entry_point.table_size];
+        vmi.read(Pa(entry_point.table_address), &mut table)?;
+
+        let mut result = Self {
+            smbios_version: entry_point.version,
+            table_address: Pa(entry_point.table_address),
+            ..Self::default()
+        };
+
+        let mut position = 0;
+        while let Some(&[typ, length]) = table.get(position..position + 2) {
+            let length = length as usize;
+            if length < 4 {
+                return Err(VmiError::CorruptedStruct("SMBIOS structure"));
+            }
+
+            let formatted = match table.get(position..position + length) {
+                Some(formatted) => formatted,
+                None => break,
+            };
+
+            //
+            // The strings follow the formatted area and are terminated by
+            // a double NUL. A structure without strings is followed by two
+            // NULs as well.
+            //
+
+            let strings_start = position + length;
+            let strings_end = match table[strings_start.min(table.len())..]
+                .windows(2)
+                .position(|pair| pair == [0, 0])
+            {
+                Some(end) => strings_start + end,
+                None => break,
+            };
+
+            let strings = &table[strings_start..strings_end];
+            let string = |offset: usize| {
+                let index = *formatted.get(offset)? as usize;
+                if index == 0 {
+                    return None;
+                }
+
+                let string = strings.split(|&byte| byte == 0).nth(index - 1)?;
+                Some(String::from_utf8_lossy(string).trim().to_owned())
+            };
+
+            match typ {
+                TYPE_BIOS => {
+                    result.bios_vendor = string(0x04);
+                    result.bios_version = string(0x05);
+                }
+                TYPE_SYSTEM => {
+                    result.system_manufacturer = string(0x04);
+                    result.system_product = string(0x05);
+                    result.system_version = string(0x06);
+                    result.system_serial_number = string(0x07);
+                }
+                TYPE_BASEBOARD => {
+                    result.baseboard_manufacturer = string(0x04);
+                    result.baseboard_product = string(0x05);
+                }
+                TYPE_END_OF_TABLE => break,
+                _ => {}
+            }
+
+            position = strings_end + 2;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Finds the entry point in the legacy BIOS area.
+///
+/// The 64-bit entry point is preferred, as the 32-bit one can't describe
+/// tables above 4 GiB. Candidates with an invalid checksum are ignored.
+fn find_entry_point(area: &[u8]) -> Option<EntryPoint> {
+    let mut result = None;
+
+    for offset in (0..area.len()).step_by(16) {
+        let candidate = &area[offset..];
+
+        if candidate.starts_with(b"_SM3_") {
+            let length = *candidate.get(0x06)? as usize;
+            if length < 0x18 || !checksum_valid(candidate.get(..length)?) {
+                continue;
+            }
+
+            return Some(EntryPoint {
+                version: (candidate[0x07], candidate[0x08]),
+                table_address: u64::from_le_bytes(candidate[0x10..0x18].try_into().ok()?),
+                table_size: u32::from_le_bytes(candidate[0x0c..0x10].try_into().ok()?) as usize,
+            });
+        }
+
+        if candidate.starts_with(b"_SM_") && result.is_none() {
+            let length = *candidate.get(0x05)? as usize;
+            if length < 0x1f || !checksum_valid(candidate.get(..length)?) {
+                continue;
+            }
+
+            result = Some(EntryPoint {
+                version: (candidate[0x06], candidate[0x07]),
+                table_address: u32::from_le_bytes(candidate[0x18..0x1c].try_into().ok()?) as u64,
+                table_size: u16::from_le_bytes(candidate[0x16..0x18].try_into().ok()?) as usize,
+            });
+        }
+    }
+
+    result
+}
+
+/// Checks whether the bytes of an entry point add up to zero.
+fn checksum_valid(data: &[u8]) -> bool {
+    data.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte)) == 0
+}
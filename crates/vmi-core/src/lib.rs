@@ -1,44 +1,61 @@
 //! Core VMI functionality.
 
 pub mod arch;
+mod cache;
+mod chain;
+mod code_protection;
 mod context;
 mod core;
 mod driver;
 mod error;
 mod event;
+mod firmware;
 mod handler;
+mod memory_map;
 pub mod os;
 mod page;
+mod reentrancy;
 mod session;
+mod write_buffer;
 
 use std::{
     cell::RefCell,
+    collections::BTreeMap,
+    fs::File,
+    io::{Read as _, Seek as _, SeekFrom},
     num::NonZeroUsize,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
-use lru::LruCache;
 use zerocopy::{FromBytes, Immutable, IntoBytes};
 
 pub use self::{
     arch::{Architecture, Registers},
+    cache::CacheMetrics,
+    chain::ChainIterator,
+    code_protection::CodeProtection,
     context::{VmiContext, VmiContextProber, VmiOsContext, VmiOsContextProber},
     core::{
-        AccessContext, AddressContext, Gfn, Hex, MemoryAccess, Pa, TranslationMechanism, Va,
-        VcpuId, View, VmiInfo,
+        AccessContext, AddressContext, CpuidResult, Gfn, Hex, MemoryAccess, Pa,
+        TranslationMechanism, Va, VcpuId, View, ViewInfo, VmiInfo,
     },
-    driver::VmiDriver,
+    driver::{DriverCapabilities, VmiDriver},
     error::{PageFault, PageFaults, VmiError},
     event::{VmiEvent, VmiEventFlags, VmiEventResponse, VmiEventResponseFlags},
+    firmware::FirmwareTables,
     handler::VmiHandler,
+    memory_map::MemoryMap,
     os::VmiOs,
     page::VmiMappedPage,
+    reentrancy::VmiReentrancy,
     session::{VmiOsSession, VmiOsSessionProber, VmiSession, VmiSessionProber},
+    write_buffer::WriteBuffer,
 };
+use self::{cache::MeteredCache, reentrancy::ActiveHandlers};
 
 struct Cache {
-    gfn: RefCell<LruCache<Gfn, VmiMappedPage>>,
-    v2p: RefCell<LruCache<AccessContext, Pa>>,
+    gfn: RefCell<MeteredCache<Gfn, VmiMappedPage>>,
+    v2p: RefCell<MeteredCache<AccessContext, Pa>>,
 }
 
 impl Cache {
@@ -46,10 +63,10 @@ impl Cache {
 
     pub fn new() -> Self {
         Self {
-            gfn: RefCell::new(LruCache::new(
+            gfn: RefCell::new(MeteredCache::new(
                 NonZeroUsize::new(Self::DEFAULT_SIZE).unwrap(),
             )),
-            v2p: RefCell::new(LruCache::new(
+            v2p: RefCell::new(MeteredCache::new(
                 NonZeroUsize::new(Self::DEFAULT_SIZE).unwrap(),
             )),
         }
@@ -68,6 +85,12 @@ where
     translate_access_context_fn: fn(&Self, AccessContext) -> Result<Pa, VmiError>,
 
     read_string_length_limit: RefCell<Option<usize>>,
+    gfn_cache_enabled: bool,
+    readahead: RefCell<usize>,
+    reentrancy: RefCell<VmiReentrancy>,
+    active_handlers: ActiveHandlers,
+    pagefiles: RefCell<BTreeMap<u8, File>>,
+    tsc_info: RefCell<Option<(u64, SystemTime)>>,
     created: Instant,
 }
 
@@ -86,6 +109,12 @@ where
             read_page_fn: Self::read_page_cache,
             translate_access_context_fn: Self::translate_access_context_cache,
             read_string_length_limit: RefCell::new(None),
+            gfn_cache_enabled: true,
+            readahead: RefCell::new(0),
+            reentrancy: RefCell::new(VmiReentrancy::default()),
+            active_handlers: ActiveHandlers::new(),
+            pagefiles: RefCell::new(BTreeMap::new()),
+            tsc_info: RefCell::new(None),
             created: Instant::now(),
         })
     }
@@ -108,10 +137,11 @@ where
     pub fn with_gfn_cache(self, size: usize) -> Self {
         Self {
             cache: Cache {
-                gfn: RefCell::new(LruCache::new(NonZeroUsize::new(size).unwrap())),
+                gfn: RefCell::new(MeteredCache::new(NonZeroUsize::new(size).unwrap())),
                 ..self.cache
             },
             read_page_fn: Self::read_page_cache,
+            gfn_cache_enabled: true,
             ..self
         }
     }
@@ -123,6 +153,7 @@ where
     /// [`with_gfn_cache`]: Self::with_gfn_cache
     pub fn enable_gfn_cache(&mut self) {
         self.read_page_fn = Self::read_page_cache;
+        self.gfn_cache_enabled = true;
     }
 
     /// Disables the GFN cache.
@@ -133,6 +164,7 @@ where
     /// [`read_page`]: Self::read_page
     pub fn disable_gfn_cache(&mut self) {
         self.read_page_fn = Self::read_page_nocache;
+        self.gfn_cache_enabled = false;
     }
 
     /// Resizes the GFN cache.
@@ -165,14 +197,73 @@ where
         self.cache.gfn.borrow_mut().clear();
     }
 
-    ///// Retrieves metrics about the GFN cache.
-    //pub fn gfn_cache_metrics(&self) -> CacheMetrics {
-    //    let cache = self.cache.gfn.borrow();
-    //    CacheMetrics {
-    //        hits: ...,
-    //        misses: ...,
-    //    }
-    //}
+    /// Enables read-ahead for [`read`].
+    ///
+    /// When [`read`] misses the GFN cache, the next `pages` virtually
+    /// contiguous pages are also fetched and stored in the GFN cache,
+    /// as long as they are physically contiguous as well. Prefetching stops
+    /// at the first page that is not present, that is not physically
+    /// adjacent to the previous one, or that fails to be read.
+    ///
+    /// This speeds up sequential workloads, such as hashing or scanning
+    /// a memory region, at the cost of reading pages that might not be
+    /// needed. Prefetched pages are stored in the GFN cache, so read-ahead
+    /// is skipped while the GFN cache is disabled.
+    ///
+    /// Passing `0` disables read-ahead.
+    ///
+    /// [`read`]: Self::read
+    pub fn enable_readahead(&self, pages: usize) {
+        *self.readahead.borrow_mut() = pages;
+    }
+
+    /// Disables read-ahead.
+    ///
+    /// See [`enable_readahead`] for more details.
+    ///
+    /// [`enable_readahead`]: Self::enable_readahead
+    pub fn disable_readahead(&self) {
+        *self.readahead.borrow_mut() = 0;
+    }
+
+    /// Returns the policy for event handlers that wait for events
+    /// themselves.
+    pub fn reentrancy(&self) -> VmiReentrancy {
+        *self.reentrancy.borrow()
+    }
+
+    /// Sets the policy for event handlers that wait for events themselves.
+    ///
+    /// See [`VmiReentrancy`] for more details.
+    pub fn set_reentrancy(&self, reentrancy: VmiReentrancy) {
+        *self.reentrancy.borrow_mut() = reentrancy;
+    }
+
+    /// Attaches a copy of a page file (or swap device) of the guest.
+    ///
+    /// Pages evicted from memory can't be read from the virtual machine.
+    /// With a copy of the page file, OS-specific code that decodes the
+    /// location of an evicted page from its non-present page table entry
+    /// can read the page with [`read_pagefile`] instead.
+    ///
+    /// The `index` identifies the page file the same way the guest does
+    /// (e.g., the `PageFileLow` field of Windows software PTEs, where
+    /// `pagefile.sys` usually has index 0). A page file previously attached
+    /// with the same index is replaced.
+    ///
+    /// [`read_pagefile`]: Self::read_pagefile
+    pub fn set_pagefile(&self, index: u8, file: File) {
+        self.pagefiles.borrow_mut().insert(index, file);
+    }
+
+    /// Retrieves metrics about the GFN cache.
+    ///
+    /// Only lookups made while the cache is enabled are counted. Pages
+    /// prefetched by read-ahead count as evictions if they push other pages
+    /// out of the cache, but not as misses.
+    pub fn gfn_cache_metrics(&self) -> CacheMetrics {
+        self.cache.gfn.borrow().metrics()
+    }
 
     /// Enables the virtual-to-physical (V2P) address translation cache.
     ///
@@ -193,7 +284,7 @@ where
     pub fn with_v2p_cache(self, size: usize) -> Self {
         Self {
             cache: Cache {
-                v2p: RefCell::new(LruCache::new(NonZeroUsize::new(size).unwrap())),
+                v2p: RefCell::new(MeteredCache::new(NonZeroUsize::new(size).unwrap())),
                 ..self.cache
             },
             translate_access_context_fn: Self::translate_access_context_cache,
@@ -259,14 +350,20 @@ where
         self.cache.v2p.borrow_mut().clear();
     }
 
-    ///// Retrieves metrics about the V2P cache.
-    //pub fn v2p_cache_metrics(&self) -> CacheMetrics {
-    //    let cache = self.cache.v2p.borrow();
-    //    CacheMetrics {
-    //        hits: ...,
-    //        misses: ...,
-    //    }
-    //}
+    /// Retrieves metrics about the V2P cache.
+    ///
+    /// Only lookups made while the cache is enabled are counted.
+    pub fn v2p_cache_metrics(&self) -> CacheMetrics {
+        self.cache.v2p.borrow().metrics()
+    }
+
+    /// Resets the metrics of both the GFN cache and the V2P cache.
+    ///
+    /// The cached entries are kept.
+    pub fn reset_cache_metrics(&self) {
+        self.cache.gfn.borrow_mut().reset_metrics();
+        self.cache.v2p.borrow_mut().reset_metrics();
+    }
 
     /// Sets a limit on the length of strings read by the `read_string` methods.
     /// If the limit is reached, the string will be truncated.
@@ -314,6 +411,48 @@ where
         self.driver.info()
     }
 
+    /// Converts a guest time-stamp counter value to the host wall-clock time.
+    ///
+    /// The conversion uses the TSC frequency and epoch reported by the
+    /// driver in [`VmiInfo`]. Both are retrieved on the first conversion
+    /// and cached for the lifetime of this `VmiCore` instance, so the result
+    /// does not account for TSC scaling changes after that (e.g., after
+    /// live migration). The result is only as accurate as the driver's
+    /// view of the guest TSC.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the driver does not provide
+    /// the TSC frequency or epoch.
+    pub fn tsc_to_systemtime(&self, tsc: u64) -> Result<SystemTime, VmiError> {
+        let (frequency, epoch) = self.tsc_info()?;
+
+        let nanos = u128::from(tsc) * 1_000_000_000 / u128::from(frequency);
+        let elapsed = Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        );
+
+        epoch
+            .checked_add(elapsed)
+            .ok_or(VmiError::Other("TSC value out of range"))
+    }
+
+    /// Returns the cached TSC frequency and epoch, retrieving them from the
+    /// driver on first use.
+    fn tsc_info(&self) -> Result<(u64, SystemTime), VmiError> {
+        if let Some(tsc_info) = *self.tsc_info.borrow() {
+            return Ok(tsc_info);
+        }
+
+        let info = self.driver.info()?;
+        let tsc_info = match (info.tsc_frequency, info.tsc_epoch) {
+            (Some(frequency), Some(epoch)) if frequency != 0 => (frequency, epoch),
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        *self.tsc_info.borrow_mut() = Some(tsc_info);
+        Ok(tsc_info)
+    }
+
     /// Pauses the virtual machine.
     pub fn pause(&self) -> Result<(), VmiError> {
         self.driver.pause()
@@ -330,6 +469,22 @@ where
         VmiPauseGuard::new(&self.driver)
     }
 
+    /// Pauses the virtual machine, runs the closure and resumes the virtual
+    /// machine.
+    ///
+    /// The virtual machine is resumed even if the closure fails or panics.
+    /// Useful for operations that require a consistent view of the guest
+    /// state, such as walking kernel data structures that the guest might
+    /// concurrently modify.
+    ///
+    /// Calls can be nested if the driver counts pause requests (as Xen
+    /// does); the virtual machine then runs again only after the outermost
+    /// call returns.
+    pub fn with_paused<R>(&self, f: impl FnOnce() -> Result<R, VmiError>) -> Result<R, VmiError> {
+        let _pause_guard = self.pause_guard()?;
+        f()
+    }
+
     /// Retrieves the current state of CPU registers for a specified virtual
     /// CPU.
     ///
@@ -359,6 +514,55 @@ where
         self.driver.set_registers(vcpu, registers)
     }
 
+    /// Invalidates the cached address translations (TLB) of the guest.
+    ///
+    /// Call this after modifying the guest page tables with [`write`], so
+    /// that the guest observes the change immediately instead of using
+    /// stale translations. If `vcpu` is `None`, the translations of all
+    /// virtual CPUs are invalidated.
+    ///
+    /// A flush is only needed when a present entry is changed or removed;
+    /// the processor doesn't cache non-present entries. Flushing may be
+    /// expensive, so prefer flushing the single vCPU that needs it.
+    ///
+    /// [`write`]: Self::write
+    pub fn flush_guest_tlb(&self, vcpu: Option<VcpuId>) -> Result<(), VmiError> {
+        self.driver.flush_guest_tlb(vcpu)
+    }
+
+    /// Retrieves the `CPUID` result the guest observes on a specific
+    /// virtual CPU.
+    ///
+    /// The result reflects the CPUID policy of the guest, i.e., the features
+    /// the guest believes it has, rather than the features of the host.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the driver can't query the
+    /// guest CPUID policy.
+    pub fn guest_cpuid(
+        &self,
+        vcpu: VcpuId,
+        leaf: u32,
+        subleaf: u32,
+    ) -> Result<CpuidResult, VmiError> {
+        self.driver.guest_cpuid(vcpu, leaf, subleaf)
+    }
+
+    /// Retrieves the task priority of a specific virtual CPU.
+    ///
+    /// The task priority is the priority class below which the virtual CPU
+    /// doesn't accept interrupts.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the driver can't query the
+    /// task priority.
+    ///
+    /// # Architecture-specific
+    ///
+    /// - **AMD64**: The value of the `CR8` register, i.e., bits 7:4 of the
+    ///   task priority register (TPR) of the local APIC.
+    pub fn task_priority(&self, vcpu: VcpuId) -> Result<u8, VmiError> {
+        self.driver.task_priority(vcpu)
+    }
+
     /// Retrieves the memory access permissions for a specific guest frame
     /// number (GFN).
     ///
@@ -382,6 +586,65 @@ where
         self.driver.set_memory_access(gfn, view, access)
     }
 
+    /// Protects the code of a module against writes in a view.
+    ///
+    /// Removes the write permission from every page of the range starting
+    /// at `ctx` and spanning `size` bytes, while keeping the read and
+    /// execute permissions. Any write to the code then triggers a memory
+    /// access event, which allows detecting inline patching. The original
+    /// permissions are restored with [`CodeProtection::restore`].
+    ///
+    /// Pages that are not present in memory are skipped (see
+    /// [`CodeProtection::skipped`]). If the permissions of a page can't be
+    /// changed, the pages protected so far are restored and the error is
+    /// returned.
+    ///
+    /// Returns [`VmiError::OutOfBounds`] if the range wraps around the end
+    /// of the address space.
+    pub fn protect_module_code(
+        &self,
+        view: View,
+        ctx: impl Into<AddressContext>,
+        size: u64,
+    ) -> Result<CodeProtection, VmiError> {
+        let ctx = ctx.into();
+        let mut protection = CodeProtection::new(view);
+
+        let page_size = Driver::Architecture::PAGE_SIZE;
+        let end = Va(ctx.va.0.checked_add(size).ok_or(VmiError::OutOfBounds)?);
+        let mut va = Va(ctx.va.0 & Driver::Architecture::PAGE_MASK);
+
+        while va < end {
+            let result = self.translate_address((va, ctx.root)).and_then(|pa| {
+                let gfn = Driver::Architecture::gfn_from_pa(pa);
+                if protection.contains(gfn) {
+                    return Ok(());
+                }
+
+                let access = self.memory_access(gfn, view)?;
+                self.set_memory_access(gfn, view, access - MemoryAccess::W)?;
+                protection.insert(gfn, access);
+                Ok(())
+            });
+
+            match result {
+                Ok(()) => {}
+                Err(VmiError::PageFault(_)) => protection.skip(va),
+                Err(err) => {
+                    let _ = protection.restore(self);
+                    return Err(err);
+                }
+            }
+
+            va = match va.0.checked_add(page_size) {
+                Some(next) => Va(next),
+                None => break,
+            };
+        }
+
+        Ok(protection)
+    }
+
     /// Allocates the next available guest frame number (GFN).
     ///
     /// This method finds and allocates the next free GFN after the current
@@ -420,6 +683,17 @@ where
         self.driver.default_view()
     }
 
+    /// Returns the maximum number of views that can exist at once,
+    /// including the default view.
+    ///
+    /// Tools creating several views can check this limit upfront instead of
+    /// relying on [`create_view`] to fail.
+    ///
+    /// [`create_view`]: Self::create_view
+    pub fn max_views(&self) -> Result<u16, VmiError> {
+        self.driver.max_views()
+    }
+
     /// Creates a new view with the specified default access permissions.
     ///
     /// Views allow for creating different perspectives of the VM's memory,
@@ -493,6 +767,91 @@ where
         self.driver.reset_view_gfn(view, gfn)
     }
 
+    /// Returns statistics of the modifications made to a view.
+    ///
+    /// Reports how many GFNs are remapped (see [`change_view_gfn`]) and how
+    /// many have access permissions other than the default ones (see
+    /// [`set_memory_access`]). This helps to find forgotten remappings or
+    /// restrictions when a view behaves unexpectedly.
+    ///
+    /// Only the modifications made through the driver are counted.
+    /// Drivers that don't track the modifications return
+    /// [`VmiError::NotSupported`].
+    ///
+    /// [`change_view_gfn`]: Self::change_view_gfn
+    /// [`set_memory_access`]: Self::set_memory_access
+    pub fn view_info(&self, view: View) -> Result<ViewInfo, VmiError> {
+        self.driver.view_info(view)
+    }
+
+    /// Enables tracking of GFNs written by the guest.
+    ///
+    /// Once enabled, [`take_dirty_gfns`] returns the GFNs the guest wrote to,
+    /// which allows a snapshot to be updated by copying only the changed
+    /// pages. The tracking adds overhead to guest writes and should be
+    /// disabled with [`disable_dirty_tracking`] when no longer needed.
+    ///
+    /// The tracking is domain-wide: writes are tracked regardless of the
+    /// view the guest runs in, and there is a single set of dirty GFNs
+    /// shared by all users of this `VmiCore`.
+    ///
+    /// # Platform-specific
+    ///
+    /// - **Xen**: Uses the log-dirty mode of the domain. Fails if the mode
+    ///   is already enabled by someone else, e.g., by the toolstack during
+    ///   a live migration.
+    ///
+    /// [`take_dirty_gfns`]: Self::take_dirty_gfns
+    /// [`disable_dirty_tracking`]: Self::disable_dirty_tracking
+    pub fn enable_dirty_tracking(&self) -> Result<(), VmiError> {
+        self.driver.enable_dirty_tracking()
+    }
+
+    /// Disables tracking of GFNs written by the guest.
+    ///
+    /// Does nothing if the tracking was not enabled with
+    /// [`enable_dirty_tracking`].
+    ///
+    /// [`enable_dirty_tracking`]: Self::enable_dirty_tracking
+    pub fn disable_dirty_tracking(&self) -> Result<(), VmiError> {
+        self.driver.disable_dirty_tracking()
+    }
+
+    /// Returns the GFNs written by the guest since the tracking was enabled
+    /// or last cleared, and clears the set.
+    ///
+    /// Reading and clearing the set is a single atomic operation, so no
+    /// write is lost between the two. This is the preferred way to poll
+    /// the written GFNs periodically.
+    ///
+    /// As the set is shared, this affects every user of the dirty tracking.
+    pub fn take_dirty_gfns(&self) -> Result<Vec<Gfn>, VmiError> {
+        self.driver.take_dirty_gfns()
+    }
+
+    /// Returns the GFNs written by the guest since the tracking was enabled
+    /// or last cleared, without clearing the set.
+    ///
+    /// Writes between this call and [`clear_dirty`] are lost, unless the VM
+    /// is paused in between. Use [`take_dirty_gfns`] to read and clear the
+    /// set atomically.
+    ///
+    /// [`clear_dirty`]: Self::clear_dirty
+    /// [`take_dirty_gfns`]: Self::take_dirty_gfns
+    pub fn dirty_gfns(&self) -> Result<Vec<Gfn>, VmiError> {
+        self.driver.dirty_gfns()
+    }
+
+    /// Clears the set of GFNs written by the guest.
+    ///
+    /// As the set is shared, this affects every user of the dirty tracking.
+    /// Use [`take_dirty_gfns`] to read and clear the set atomically.
+    ///
+    /// [`take_dirty_gfns`]: Self::take_dirty_gfns
+    pub fn clear_dirty(&self) -> Result<(), VmiError> {
+        self.driver.clear_dirty()
+    }
+
     /// Enables monitoring of specific events.
     pub fn monitor_enable(
         &self,
@@ -623,12 +982,48 @@ where
     /// This method blocks until an event occurs or the specified timeout is
     /// reached. When an event occurs, it is passed to the provided callback
     /// function for processing.
+    ///
+    /// # Re-entrancy
+    ///
+    /// The handler must not rely on being able to wait for events itself.
+    /// By default, calling this method from within the handler fails with
+    /// [`VmiError::ReentrantEvent`]. Nested waiting for events of other
+    /// VCPUs can be allowed with [`set_reentrancy`], but an event of
+    /// a VCPU whose handler is still running is never dispatched: it is
+    /// answered with an empty response, and this method returns
+    /// [`VmiError::ReentrantEvent`] once the driver is done waiting.
+    ///
+    /// [`set_reentrancy`]: Self::set_reentrancy
     pub fn wait_for_event(
         &self,
         timeout: Duration,
-        handler: impl FnMut(&VmiEvent<Driver::Architecture>) -> VmiEventResponse<Driver::Architecture>,
+        mut handler: impl FnMut(
+            &VmiEvent<Driver::Architecture>,
+        ) -> VmiEventResponse<Driver::Architecture>,
     ) -> Result<(), VmiError> {
-        self.driver.wait_for_event(timeout, handler)
+        if self.reentrancy() == VmiReentrancy::Reject && !self.active_handlers.is_empty() {
+            return Err(VmiError::ReentrantEvent);
+        }
+
+        let mut reentrant = false;
+        self.driver.wait_for_event(timeout, |event| {
+            let _guard = match self.active_handlers.enter(event.vcpu_id()) {
+                Some(guard) => guard,
+                None => {
+                    tracing::error!(vcpu = %event.vcpu_id(), "re-entrant event");
+                    reentrant = true;
+                    return VmiEventResponse::default();
+                }
+            };
+
+            handler(event)
+        })?;
+
+        if reentrant {
+            return Err(VmiError::ReentrantEvent);
+        }
+
+        Ok(())
     }
 
     /// Resets the state of the VMI system.
@@ -648,14 +1043,25 @@ where
         let mut position = 0usize;
         let mut remaining = buffer.len();
 
+        let readahead = match self.gfn_cache_enabled {
+            true => *self.readahead.borrow(),
+            false => 0,
+        };
+
         while remaining > 0 {
             let address = self.translate_access_context(ctx + position as u64)?;
             let gfn = Driver::Architecture::gfn_from_pa(address);
             let offset = Driver::Architecture::pa_offset(address) as usize;
 
+            let miss = readahead > 0 && !self.cache.gfn.borrow().contains(&gfn);
+
             let page = self.read_page(gfn)?;
             let page = &page[offset..];
 
+            if miss {
+                self.read_ahead(ctx + position as u64, gfn, readahead);
+            }
+
             let size = std::cmp::min(remaining, page.len());
             buffer[position..position + size].copy_from_slice(&page[..size]);
 
@@ -666,7 +1072,88 @@ where
         Ok(())
     }
 
+    /// Reads memory from the virtual machine, bypassing the GFN cache.
+    ///
+    /// The pages are always read from the virtual machine, even if they
+    /// are cached, so that structures the guest might have modified since
+    /// they were cached are read fresh. Pages that are already in the GFN
+    /// cache are replaced with the fresh content; other pages are not
+    /// added to the cache. The rest of the cache is left untouched.
+    ///
+    /// # Notes
+    ///
+    /// Only the pages holding the data are bypassed. The address is
+    /// translated as usual, using the V2P cache and the cached page tables.
+    pub fn read_uncached(
+        &self,
+        ctx: impl Into<AccessContext>,
+        buffer: &mut [u8],
+    ) -> Result<(), VmiError> {
+        let ctx = ctx.into();
+        let mut position = 0usize;
+        let mut remaining = buffer.len();
+
+        while remaining > 0 {
+            let address = self.translate_access_context(ctx + position as u64)?;
+            let gfn = Driver::Architecture::gfn_from_pa(address);
+            let offset = Driver::Architecture::pa_offset(address) as usize;
+
+            let page = self.read_page_nocache(gfn)?;
+            if let Some(cached) = self.cache.gfn.borrow_mut().peek_mut(&gfn) {
+                *cached = page.clone();
+            }
+
+            let page = &page[offset..];
+
+            let size = std::cmp::min(remaining, page.len());
+            buffer[position..position + size].copy_from_slice(&page[..size]);
+
+            position += size;
+            remaining -= size;
+        }
+
+        Ok(())
+    }
+
+    /// Reads data from a page file attached with [`set_pagefile`].
+    ///
+    /// Returns [`VmiError::NotSupported`] if no page file with the given
+    /// index is attached.
+    ///
+    /// [`set_pagefile`]: Self::set_pagefile
+    pub fn read_pagefile(&self, index: u8, offset: u64, buffer: &mut [u8]) -> Result<(), VmiError> {
+        let mut pagefiles = self.pagefiles.borrow_mut();
+        let file = match pagefiles.get_mut(&index) {
+            Some(file) => file,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        file.seek(SeekFrom::Start(offset))?;
+        file.read_exact(buffer)?;
+
+        Ok(())
+    }
+
+    /// Reads the SMBIOS tables of the guest firmware.
+    ///
+    /// Returns the strings describing the BIOS, the system and the
+    /// baseboard, as seen by the guest. Hypervisors usually identify
+    /// themselves in these strings (e.g., `Xen` or `innotek GmbH`), which
+    /// is what software detecting virtualization looks for.
+    ///
+    /// # Notes
+    ///
+    /// Only the entry point in the legacy BIOS area (`0xF0000` - `0xFFFFF`)
+    /// is found. Guests booted with UEFI firmware that publishes the entry
+    /// point only through the EFI configuration table are not supported.
+    pub fn firmware_tables(&self) -> Result<FirmwareTables, VmiError> {
+        FirmwareTables::read(self)
+    }
+
     /// Writes memory to the virtual machine.
+    ///
+    /// The write is performed on the guest physical memory, so it succeeds
+    /// even if the guest maps the memory as read-only.
     pub fn write(&self, ctx: impl Into<AccessContext>, buffer: &[u8]) -> Result<(), VmiError> {
         let ctx = ctx.into();
         let mut position = 0usize;
@@ -765,6 +1252,25 @@ where
         Ok(Va(self.read_address64(ctx)?))
     }
 
+    /// Follows a singly-linked list, starting at the given node.
+    ///
+    /// Each node holds a pointer to the next node at `next_offset`. The
+    /// returned iterator yields the addresses of the nodes until the
+    /// pointer to the next node equals `sentinel` (usually NULL).
+    ///
+    /// This is the counterpart of walking a doubly-linked `LIST_ENTRY`
+    /// list, for structures that are linked only forward (e.g., hash
+    /// bucket chains). See [`ChainIterator`] for the termination rules.
+    pub fn follow_chain(
+        &self,
+        start: impl Into<AddressContext>,
+        next_offset: u64,
+        sentinel: Va,
+        address_width: usize,
+    ) -> ChainIterator<'_, Driver> {
+        ChainIterator::new(self, start.into(), next_offset, sentinel, address_width)
+    }
+
     /// Reads a null-terminated string of bytes from the virtual machine with a
     /// specified limit.
     pub fn read_string_bytes_limited(
@@ -775,11 +1281,7 @@ where
         let mut ctx = ctx.into();
 
         // read until the end of page
-        let mut buffer = vec![
-            0u8;
-            (Driver::Architecture::PAGE_SIZE - (ctx.address & !Driver::Architecture::PAGE_MASK))
-                as usize
-        ];
+        let mut buffer = vec![0u8; ctx.page_remaining(Driver::Architecture::PAGE_SIZE) as usize];
         self.read(ctx, &mut buffer)?;
 
         // try to find the null terminator
@@ -790,9 +1292,10 @@ where
             return Ok(buffer);
         }
 
-        let mut page = [0u8; 4096_usize]; // FIXME: Driver::Architecture::PAGE_SIZE
+        let mut page = vec![0u8; Driver::Architecture::PAGE_SIZE as usize];
+        ctx = ctx.aligned_down(Driver::Architecture::PAGE_SIZE);
         loop {
-            ctx.address += buffer.len() as u64;
+            ctx += Driver::Architecture::PAGE_SIZE;
             self.read(ctx, &mut page)?;
 
             let position = page.iter().position(|&b| b == 0);
@@ -836,11 +1339,7 @@ where
         let mut ctx = ctx.into();
 
         // read until the end of page
-        let mut buffer = vec![
-            0u8;
-            (Driver::Architecture::PAGE_SIZE - (ctx.address & !Driver::Architecture::PAGE_MASK))
-                as usize
-        ];
+        let mut buffer = vec![0u8; ctx.page_remaining(Driver::Architecture::PAGE_SIZE) as usize];
         self.read(ctx, &mut buffer)?;
 
         // try to find the null terminator
@@ -856,9 +1355,10 @@ where
                 .collect());
         }
 
-        let mut page = [0u8; 4096_usize]; // FIXME: Driver::Architecture::PAGE_SIZE
+        let mut page = vec![0u8; Driver::Architecture::PAGE_SIZE as usize];
+        ctx = ctx.aligned_down(Driver::Architecture::PAGE_SIZE);
         loop {
-            ctx.address += buffer.len() as u64;
+            ctx += Driver::Architecture::PAGE_SIZE;
             self.read(ctx, &mut page)?;
 
             let position = page
@@ -973,11 +1473,84 @@ where
         self.write(ctx, value.as_bytes())
     }
 
+    /// Creates a write-combining buffer for many small writes.
+    ///
+    /// The writes made through the returned [`WriteBuffer`] are accumulated
+    /// per page and written to the virtual machine when the buffer is
+    /// committed. This reduces the number of driver writes when building
+    /// larger structures piece by piece. Pending writes are also committed
+    /// when the buffer is dropped.
+    pub fn write_buffered(&self) -> WriteBuffer<'_, Driver> {
+        WriteBuffer::new(self)
+    }
+
     /// Translates a virtual address to a physical address.
     pub fn translate_address(&self, ctx: impl Into<AddressContext>) -> Result<Pa, VmiError> {
         self.translate_access_context(AccessContext::from(ctx.into()))
     }
 
+    /// Translates a virtual address to a physical address, returning the
+    /// leaf page table entry alongside the physical address.
+    ///
+    /// The leaf entry provides the properties of the page (e.g., writable,
+    /// accessed or dirty bits). Because the V2P cache stores only physical
+    /// addresses, this method always performs a full page table walk.
+    pub fn translate_address_ex(
+        &self,
+        ctx: impl Into<AddressContext>,
+    ) -> Result<(Pa, <Driver::Architecture as Architecture>::PageTableEntry), VmiError> {
+        let ctx = ctx.into();
+        <Driver::Architecture as Architecture>::translate_address_ex(self, ctx.va, ctx.root)
+    }
+
+    /// Translates a virtual address through a chain of translation stages.
+    ///
+    /// Each root in the chain is a translation stage. The first root is the
+    /// innermost stage (e.g., the page tables of a nested L2 guest), and
+    /// each following root translates the physical addresses of the stage
+    /// before it. The last stage produces a physical address of the virtual
+    /// machine.
+    ///
+    /// An empty chain performs no translation. A chain with a single root
+    /// is equivalent to [`translate_address`].
+    ///
+    /// # Notes
+    ///
+    /// Both the paging structures and the resulting address of each stage
+    /// are translated through all outer stages, so a single translation
+    /// takes several page table walks per stage. The V2P cache is not
+    /// used.
+    ///
+    /// Every stage is walked as guest paging structures of the
+    /// architecture, the same way [`translate_address`] walks them.
+    /// Second-level translation structures of a nested hypervisor are not
+    /// supported.
+    ///
+    /// # Architecture-specific
+    ///
+    /// - **AMD64**: Every root must point to IA-32e (4-level) page tables.
+    ///   Extended page tables (EPT) use a different entry format, in which
+    ///   bits 0-2 are the read, write and execute permissions. Walking them
+    ///   as IA-32e page tables treats execute-only entries as not present
+    ///   and ignores the other permissions.
+    ///
+    /// [`translate_address`]: Self::translate_address
+    pub fn translate_nested(&self, va: Va, roots: &[Pa]) -> Result<Pa, VmiError> {
+        let (root, outer) = match roots.split_first() {
+            Some((root, outer)) => (*root, outer),
+            None => return Ok(Pa(va.0)),
+        };
+
+        let pa = <Driver::Architecture as Architecture>::translate_address_mapped(
+            self,
+            va,
+            root,
+            |pa| self.translate_nested(Va(pa.0), outer),
+        )?;
+
+        self.translate_nested(Va(pa.0), outer)
+    }
+
     /// Translates an access context to a physical address.
     pub fn translate_access_context(&self, ctx: AccessContext) -> Result<Pa, VmiError> {
         (self.translate_access_context_fn)(self, ctx)
@@ -988,6 +1561,111 @@ where
         (self.read_page_fn)(self, gfn)
     }
 
+    /// Maps a range of guest physical memory as a read-only, file-like
+    /// object.
+    ///
+    /// The returned [`MemoryMap`] implements [`std::io::Read`] and
+    /// [`std::io::Seek`] over `count` pages starting at `start`. Pages are
+    /// read lazily, so mapping a range does not access the guest memory.
+    ///
+    /// Returns an error if the range exceeds the guest physical address
+    /// space.
+    pub fn map_physical_readonly(
+        &self,
+        start: Gfn,
+        count: u64,
+    ) -> Result<MemoryMap<'_, Driver>, VmiError> {
+        let end = start.0.checked_add(count).ok_or(VmiError::OutOfBounds)?;
+
+        if count > 0 && Gfn(end - 1) > self.info()?.max_gfn {
+            return Err(VmiError::OutOfBounds);
+        }
+
+        Ok(MemoryMap::new(self, start, count))
+    }
+
+    /// Checks whether a guest frame number (GFN) is backed by guest memory.
+    ///
+    /// Pages present in the GFN cache are considered valid without
+    /// querying the driver. This allows physical memory scanners to skip
+    /// MMIO holes and unbacked frames without issuing failing reads.
+    pub fn gfn_valid(&self, gfn: Gfn) -> Result<bool, VmiError> {
+        if self.cache.gfn.borrow().contains(&gfn) {
+            return Ok(true);
+        }
+
+        self.driver.gfn_valid(gfn)
+    }
+
+    /// Calls the provided closure with the contents of a page of memory.
+    ///
+    /// Unlike [`read`], the page contents are not copied into an intermediate
+    /// buffer. If the driver supports [`DriverCapabilities::DIRECT_MAP`],
+    /// a page already in the GFN cache is used as is, and any other page is
+    /// mapped directly without being inserted into the cache. This avoids
+    /// evicting cached pages during large sequential reads (e.g.,
+    /// full-memory dumps). Otherwise, the page is obtained via [`read_page`].
+    ///
+    /// [`read`]: Self::read
+    /// [`read_page`]: Self::read_page
+    pub fn with_mapped_page<R>(&self, gfn: Gfn, f: impl FnOnce(&[u8]) -> R) -> Result<R, VmiError> {
+        let page = if self
+            .driver
+            .capabilities()
+            .contains(DriverCapabilities::DIRECT_MAP)
+        {
+            // Peeking doesn't change the order of the cached pages.
+            let cached = match self.gfn_cache_enabled {
+                true => self.cache.gfn.borrow().peek(&gfn).cloned(),
+                false => None,
+            };
+
+            match cached {
+                Some(page) => page,
+                None => self.read_page_nocache(gfn)?,
+            }
+        }
+        else {
+            self.read_page(gfn)?
+        };
+
+        Ok(f(&page))
+    }
+
+    /// Prefetches up to `pages` pages following the page at `ctx` into the
+    /// GFN cache.
+    ///
+    /// Stops at the first page that cannot be translated or read, or whose
+    /// GFN doesn't immediately follow the previous one.
+    fn read_ahead(&self, ctx: AccessContext, gfn: Gfn, pages: usize) {
+        let page_size = Driver::Architecture::PAGE_SIZE;
+        let page = ctx.aligned_down(page_size);
+
+        for index in 1..=pages as u64 {
+            let next_gfn = gfn + index;
+
+            let address = match self.translate_access_context(page + index * page_size) {
+                Ok(address) => address,
+                Err(_) => break,
+            };
+
+            if Driver::Architecture::gfn_from_pa(address) != next_gfn {
+                break;
+            }
+
+            if self.cache.gfn.borrow().contains(&next_gfn) {
+                continue;
+            }
+
+            match self.read_page_nocache(next_gfn) {
+                Ok(content) => {
+                    self.cache.gfn.borrow_mut().put(next_gfn, content);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
     /// Reads a page of memory from the virtual machine without using the cache.
     fn read_page_nocache(&self, gfn: Gfn) -> Result<VmiMappedPage, VmiError> {
         self.driver.read_page(gfn)
@@ -996,11 +1674,14 @@ where
     /// Reads a page of memory from the virtual machine, using the cache if
     /// enabled.
     fn read_page_cache(&self, gfn: Gfn) -> Result<VmiMappedPage, VmiError> {
-        let mut cache = self.cache.gfn.borrow_mut();
-        let value = cache.try_get_or_insert(gfn, || self.read_page_nocache(gfn))?;
-
         // Mapped pages are reference counted, so cloning it is cheap.
-        Ok(value.clone())
+        if let Some(value) = self.cache.gfn.borrow_mut().get(&gfn) {
+            return Ok(value.clone());
+        }
+
+        let value = self.read_page_nocache(gfn)?;
+        self.cache.gfn.borrow_mut().put(gfn, value.clone());
+        Ok(value)
     }
 
     /// Translates an access context to a physical address without using the
@@ -1028,9 +1709,13 @@ where
     /// Translates an access context to a physical address, using the cache if
     /// enabled.
     fn translate_access_context_cache(&self, ctx: AccessContext) -> Result<Pa, VmiError> {
-        let mut cache = self.cache.v2p.borrow_mut();
-        let value = cache.try_get_or_insert(ctx, || self.translate_access_context_nocache(ctx))?;
-        Ok(*value)
+        if let Some(value) = self.cache.v2p.borrow_mut().get(&ctx) {
+            return Ok(*value);
+        }
+
+        let value = self.translate_access_context_nocache(ctx)?;
+        self.cache.v2p.borrow_mut().put(ctx, value);
+        Ok(value)
     }
 }
 
@@ -0,0 +1,135 @@
+use std::io::{Error, ErrorKind, Read, Result, Seek, SeekFrom};
+
+use crate::{Architecture, Gfn, VmiCore, VmiDriver, VmiMappedPage};
+
+/// A read-only, file-like view of a range of guest physical memory.
+///
+/// Implements [`Read`] and [`Seek`], so that the guest memory can be
+/// consumed by tools that expect a file-like interface (e.g., disassemblers
+/// or debuggers). Positions are relative to the start of the range.
+///
+/// Pages are read lazily through [`VmiCore::read_page`] as the position
+/// advances, and the most recently read page is kept, so that sequential
+/// reads within a page are served without querying the core again.
+///
+/// Created by [`VmiCore::map_physical_readonly`].
+pub struct MemoryMap<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    vmi: &'a VmiCore<Driver>,
+    start: Gfn,
+    count: u64,
+    position: u64,
+    page: Option<(Gfn, VmiMappedPage)>,
+}
+
+impl<'a, Driver> MemoryMap<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// Creates a new view of `count` pages starting at `start`.
+    pub(crate) fn new(vmi: &'a VmiCore<Driver>, start: Gfn, count: u64) -> Self {
+        Self {
+            vmi,
+            start,
+            count,
+            position: 0,
+            page: None,
+        }
+    }
+
+    /// Returns the first guest frame number of the range.
+    pub fn start(&self) -> Gfn {
+        self.start
+    }
+
+    /// Returns the number of pages in the range.
+    pub fn page_count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the size of the range, in bytes.
+    pub fn len(&self) -> u64 {
+        self.count << Driver::Architecture::PAGE_SHIFT
+    }
+
+    /// Checks whether the range is empty.
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Returns the current position within the range.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the page with the given guest frame number, reading it if
+    /// it is not the current page.
+    fn page(&mut self, gfn: Gfn) -> Result<VmiMappedPage> {
+        if let Some((current, page)) = &self.page {
+            if *current == gfn {
+                return Ok(page.clone());
+            }
+        }
+
+        let page = self
+            .vmi
+            .read_page(gfn)
+            .map_err(|err| Error::other(err.to_string()))?;
+
+        self.page = Some((gfn, page.clone()));
+        Ok(page)
+    }
+}
+
+impl<Driver> Read for MemoryMap<'_, Driver>
+where
+    Driver: VmiDriver,
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let len = self.len();
+        if buf.is_empty() || self.position >= len {
+            return Ok(0);
+        }
+
+        let gfn = self.start + (self.position >> Driver::Architecture::PAGE_SHIFT);
+        let offset = (self.position & !Driver::Architecture::PAGE_MASK) as usize;
+
+        let remaining = len - self.position;
+        let page = self.page(gfn)?;
+        let size = buf
+            .len()
+            .min(page.len() - offset)
+            .min(remaining.try_into().unwrap_or(usize::MAX));
+
+        buf[..size].copy_from_slice(&page[offset..offset + size]);
+        self.position += size as u64;
+
+        Ok(size)
+    }
+}
+
+impl<Driver> Seek for MemoryMap<'_, Driver>
+where
+    Driver: VmiDriver,
+{
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+
+        match position {
+            Some(position) => {
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+}
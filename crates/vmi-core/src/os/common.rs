@@ -140,6 +140,13 @@ pub enum OsArchitecture {
 }
 
 /// Represents information about a kernel module in the target system.
+///
+/// Also used for user-mode modules of a process, see
+/// [`VmiOs::process_modules`]. The fields are then filled from the
+/// `LDR_DATA_TABLE_ENTRY` on Windows, and from the file mappings of the
+/// process on Linux.
+///
+/// [`VmiOs::process_modules`]: crate::os::VmiOs::process_modules
 #[derive(Debug, Serialize, Deserialize)]
 pub struct OsModule {
     /// The base address of the module.
@@ -227,6 +227,23 @@ where
         process: ProcessObject,
     ) -> Result<Vec<OsRegion>, VmiError>;
 
+    /// Retrieves a list of user-mode modules loaded in a given process.
+    ///
+    /// # Platform-specific
+    ///
+    /// - **Windows**: Retrieves information from the loader module list
+    ///   (`PEB.Ldr.InLoadOrderModuleList`), including the 32-bit list of
+    ///   WoW64 processes.
+    /// - **Linux**: Retrieves the files mapped with execute permission into
+    ///   the address space of the process. Each module spans all mappings
+    ///   of its file.
+    fn process_modules(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<OsModule>, VmiError>;
+
     /// Checks if a given virtual address is valid in a given process.
     fn process_address_is_valid(
         &self,
@@ -341,6 +358,7 @@ where
     ///
     /// - **Windows 7**: Enumerates a `MMADDRESS_NODE` structure.
     /// - **Windows 10+**: Enumerates a `RTL_BALANCED_NODE` structure.
+    /// - **Linux**: Enumerates a `struct rb_root` structure, in order.
     fn enumerate_tree(
         &self,
         vmi: &VmiCore<Driver>,
@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+
+use crate::VcpuId;
+
+/// Policy for event handlers that wait for events themselves.
+///
+/// An event handler that calls [`VmiCore::wait_for_event`] (directly, or
+/// through a session) dispatches further events while it is still
+/// running. The nested handler invocation then works on state the outer
+/// invocation is in the middle of modifying.
+///
+/// Regardless of the policy, an event of a VCPU whose handler is still
+/// running is never dispatched. It is answered with an empty response and
+/// the wait fails with [`VmiError::ReentrantEvent`].
+///
+/// [`VmiCore::wait_for_event`]: crate::VmiCore::wait_for_event
+/// [`VmiError::ReentrantEvent`]: crate::VmiError::ReentrantEvent
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum VmiReentrancy {
+    /// Waiting for events from within an event handler fails with
+    /// [`VmiError::ReentrantEvent`] before any event is dispatched.
+    ///
+    /// [`VmiError::ReentrantEvent`]: crate::VmiError::ReentrantEvent
+    #[default]
+    Reject,
+
+    /// Waiting for events from within an event handler is allowed, as long
+    /// as the nested events come from other VCPUs.
+    AllowOtherVcpus,
+}
+
+/// The VCPUs whose events are being handled.
+pub(crate) struct ActiveHandlers {
+    vcpus: RefCell<Vec<VcpuId>>,
+}
+
+impl ActiveHandlers {
+    /// Creates a new empty set.
+    pub fn new() -> Self {
+        Self {
+            vcpus: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Checks whether an event of any VCPU is being handled.
+    pub fn is_empty(&self) -> bool {
+        self.vcpus.borrow().is_empty()
+    }
+
+    /// Marks the VCPU as being handled until the returned guard is
+    /// dropped.
+    ///
+    /// Returns `None` if an event of the VCPU is already being handled.
+    pub fn enter(&self, vcpu: VcpuId) -> Option<ActiveHandlerGuard<'_>> {
+        let mut vcpus = self.vcpus.borrow_mut();
+        if vcpus.contains(&vcpu) {
+            return None;
+        }
+
+        vcpus.push(vcpu);
+        Some(ActiveHandlerGuard {
+            handlers: self,
+            vcpu,
+        })
+    }
+}
+
+/// Unmarks the VCPU when dropped, even if the handler panics.
+pub(crate) struct ActiveHandlerGuard<'a> {
+    handlers: &'a ActiveHandlers,
+    vcpu: VcpuId,
+}
+
+impl Drop for ActiveHandlerGuard<'_> {
+    fn drop(&mut self) {
+        self.handlers
+            .vcpus
+            .borrow_mut()
+            .retain(|vcpu| *vcpu != self.vcpu);
+    }
+}
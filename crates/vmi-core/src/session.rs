@@ -4,8 +4,10 @@ use indexmap::IndexSet;
 use zerocopy::{FromBytes, IntoBytes};
 
 use crate::{
-    context::VmiContext, os::VmiOs, AccessContext, Architecture, PageFault, PageFaults,
-    TranslationMechanism, Va, VmiCore, VmiDriver, VmiError, VmiHandler,
+    context::VmiContext,
+    os::{ProcessObject, VmiOs},
+    AccessContext, Architecture, PageFault, PageFaults, TranslationMechanism, Va, VmiCore,
+    VmiDriver, VmiError, VmiHandler,
 };
 
 /// A VMI session.
@@ -70,11 +72,62 @@ where
         VmiSessionProber::new(self, restricted)
     }
 
+    /// Reads memory from the address space of a process.
+    ///
+    /// The address is translated using the translation root of the given
+    /// process, rather than the one of the process that is currently
+    /// running on the VCPU.
+    ///
+    /// # Implementation Details
+    ///
+    /// Kernel addresses (see [`Architecture::is_kernel_address`]) are read
+    /// using the kernel translation root of the process, and user addresses
+    /// using its user translation root. With KPTI, only the kernel
+    /// translation root maps the kernel.
+    ///
+    /// If the OS can't provide the user translation root (i.e., it returns
+    /// [`VmiError::NotSupported`]), the kernel translation root is used for
+    /// user addresses as well, as it maps the user part of the address
+    /// space too.
+    ///
+    /// # Notes
+    ///
+    /// WoW64 processes share a single translation root for both their
+    /// 32-bit and 64-bit parts, so no special handling is needed.
+    pub fn read_process_memory(
+        &self,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        address: Va,
+        buffer: &mut [u8],
+    ) -> Result<(), VmiError> {
+        let root = if Driver::Architecture::is_kernel_address(address) {
+            self.os
+                .process_translation_root(self.core, registers, process)?
+        }
+        else {
+            match self
+                .os
+                .process_user_translation_root(self.core, registers, process)
+            {
+                Err(VmiError::NotSupported) => self
+                    .os
+                    .process_translation_root(self.core, registers, process)?,
+                result => result?,
+            }
+        };
+
+        self.core.read((address, root), buffer)
+    }
+
     /// Waits for an event to occur and processes it with the provided handler.
     ///
     /// This method blocks until an event occurs or the specified timeout is
     /// reached. When an event occurs, it is passed to the provided callback
     /// function for processing.
+    ///
+    /// See [`VmiCore::wait_for_event`] for the rules on calling this method
+    /// from within a handler.
     pub fn wait_for_event(
         &self,
         timeout: Duration,
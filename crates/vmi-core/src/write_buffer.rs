@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+
+use zerocopy::{Immutable, IntoBytes};
+
+use crate::{AccessContext, Architecture, Gfn, VmiCore, VmiDriver, VmiError};
+
+/// Pending writes to a single page.
+struct PageWrites {
+    /// The written bytes, at their offsets within the page.
+    data: Vec<u8>,
+
+    /// Whether the byte at the same offset has been written.
+    dirty: Vec<bool>,
+}
+
+/// A write-combining buffer for many small writes.
+///
+/// Writes are translated immediately, but the data is accumulated per
+/// guest page and written to the virtual machine only when the buffer is
+/// committed with [`commit`] (or flushed with [`flush`]). Each contiguous
+/// run of written bytes within a page results in a single driver write,
+/// regardless of how many writes contributed to it. When writes overlap,
+/// the later write wins.
+///
+/// Pending writes are committed when the buffer is dropped, but errors
+/// can then only be logged. Prefer calling [`commit`] explicitly, and
+/// call [`discard`] first if the pending writes should not reach the
+/// virtual machine.
+///
+/// Created by [`VmiCore::write_buffered`].
+///
+/// # Notes
+///
+/// Reads through the [`VmiCore`] do not observe writes that have not been
+/// flushed yet.
+///
+/// [`commit`]: Self::commit
+/// [`discard`]: Self::discard
+/// [`flush`]: Self::flush
+pub struct WriteBuffer<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    vmi: &'a VmiCore<Driver>,
+    pages: BTreeMap<Gfn, PageWrites>,
+    max_pages: Option<usize>,
+}
+
+impl<'a, Driver> WriteBuffer<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// Creates a new empty buffer.
+    pub(crate) fn new(vmi: &'a VmiCore<Driver>) -> Self {
+        Self {
+            vmi,
+            pages: BTreeMap::new(),
+            max_pages: None,
+        }
+    }
+
+    /// Sets the maximum number of pages held by the buffer.
+    ///
+    /// When a write touches a page that would exceed the limit, the buffer
+    /// is flushed first. By default, the number of pages is not limited.
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Returns the number of pages with pending writes.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Checks whether there are no pending writes.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Buffers a write to the virtual machine.
+    pub fn write(&mut self, ctx: impl Into<AccessContext>, buffer: &[u8]) -> Result<(), VmiError> {
+        let ctx = ctx.into();
+        let mut position = 0usize;
+        let mut remaining = buffer.len();
+
+        let page_size = Driver::Architecture::PAGE_SIZE;
+
+        while remaining > 0 {
+            let address = self.vmi.translate_access_context(ctx + position as u64)?;
+            let gfn = Driver::Architecture::gfn_from_pa(address);
+            let offset = Driver::Architecture::pa_offset(address) as usize;
+
+            let size = std::cmp::min(remaining, page_size as usize - offset);
+            let content = &buffer[position..position + size];
+
+            if let Some(max_pages) = self.max_pages {
+                if !self.pages.contains_key(&gfn) && self.pages.len() >= max_pages {
+                    self.flush()?;
+                }
+            }
+
+            let page = self.pages.entry(gfn).or_insert_with(|| PageWrites {
+                data: vec![0; page_size as usize],
+                dirty: vec![false; page_size as usize],
+            });
+
+            page.data[offset..offset + size].copy_from_slice(content);
+            page.dirty[offset..offset + size].fill(true);
+
+            position += size;
+            remaining -= size;
+        }
+
+        Ok(())
+    }
+
+    /// Buffers a write of a single byte.
+    pub fn write_u8(&mut self, ctx: impl Into<AccessContext>, value: u8) -> Result<(), VmiError> {
+        self.write(ctx, &value.to_le_bytes())
+    }
+
+    /// Buffers a write of a 16-bit unsigned integer.
+    pub fn write_u16(&mut self, ctx: impl Into<AccessContext>, value: u16) -> Result<(), VmiError> {
+        self.write(ctx, &value.to_le_bytes())
+    }
+
+    /// Buffers a write of a 32-bit unsigned integer.
+    pub fn write_u32(&mut self, ctx: impl Into<AccessContext>, value: u32) -> Result<(), VmiError> {
+        self.write(ctx, &value.to_le_bytes())
+    }
+
+    /// Buffers a write of a 64-bit unsigned integer.
+    pub fn write_u64(&mut self, ctx: impl Into<AccessContext>, value: u64) -> Result<(), VmiError> {
+        self.write(ctx, &value.to_le_bytes())
+    }
+
+    /// Buffers a write of a struct.
+    pub fn write_struct<T>(
+        &mut self,
+        ctx: impl Into<AccessContext>,
+        value: T,
+    ) -> Result<(), VmiError>
+    where
+        T: IntoBytes + Immutable,
+    {
+        self.write(ctx, value.as_bytes())
+    }
+
+    /// Writes all pending data to the virtual machine.
+    ///
+    /// Pages are written in the order of their guest frame numbers. If
+    /// a write fails, the failed page and the pages that follow it remain
+    /// buffered.
+    pub fn flush(&mut self) -> Result<(), VmiError> {
+        while let Some((gfn, page)) = self.pages.pop_first() {
+            if let Err(err) = self.flush_page(gfn, &page) {
+                self.pages.insert(gfn, page);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Writes all pending data to the virtual machine and consumes the
+    /// buffer.
+    ///
+    /// If a write fails, the remaining pending writes are discarded.
+    pub fn commit(mut self) -> Result<(), VmiError> {
+        let result = self.flush();
+        self.discard();
+        result
+    }
+
+    /// Discards all pending writes.
+    pub fn discard(&mut self) {
+        self.pages.clear();
+    }
+
+    /// Writes every contiguous run of written bytes of a page.
+    fn flush_page(&self, gfn: Gfn, page: &PageWrites) -> Result<(), VmiError> {
+        let mut offset = 0;
+
+        while offset < page.dirty.len() {
+            if !page.dirty[offset] {
+                offset += 1;
+                continue;
+            }
+
+            let end = page.dirty[offset..]
+                .iter()
+                .position(|&dirty| !dirty)
+                .map_or(page.dirty.len(), |length| offset + length);
+
+            self.vmi
+                .driver
+                .write_page(gfn, offset as u64, &page.data[offset..end])?;
+
+            offset = end;
+        }
+
+        Ok(())
+    }
+}
+
+impl<Driver> Drop for WriteBuffer<'_, Driver>
+where
+    Driver: VmiDriver,
+{
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            tracing::error!(
+                ?err,
+                pages = self.pages.len(),
+                "failed to commit buffered writes"
+            );
+        }
+    }
+}
@@ -3,7 +3,8 @@ mod registers;
 
 use vmi_arch_amd64::{Amd64, ControlRegister, EventMonitor, EventReason, ExceptionVector};
 use vmi_core::{
-    Registers as _, VcpuId, View, VmiEvent, VmiEventFlags, VmiEventResponse, VmiEventResponseFlags,
+    CpuidResult, Registers as _, VcpuId, View, VmiEvent, VmiEventFlags, VmiEventResponse,
+    VmiEventResponseFlags,
 };
 use xen::ctrl::{
     VmEvent, VmEventData, VmEventFastSinglestep, VmEventFlag, VmEventFlagOptions, VmEventRegs,
@@ -28,6 +29,87 @@ impl ArchAdapter for Amd64 {
             .set_context_cpu(vcpu.into_ext(), registers.into_ext())?)
     }
 
+    fn guest_cpuid(
+        driver: &XenDriver<Self>,
+        vcpu: VcpuId,
+        leaf: u32,
+        subleaf: u32,
+    ) -> Result<CpuidResult, Error> {
+        const CPUID_HYPERVISOR_LEAVES: std::ops::RangeInclusive<u32> = 0x4000_0000..=0x4fff_ffff;
+
+        const CPUID_1_ECX_XSAVE: u32 = 1 << 26;
+        const CPUID_1_ECX_OSXSAVE: u32 = 1 << 27;
+        const CPUID_7_ECX_PKU: u32 = 1 << 3;
+        const CPUID_7_ECX_OSPKE: u32 = 1 << 4;
+
+        // The hypervisor leaves are generated by Xen on each `CPUID` and
+        // are not part of the domain CPU policy.
+        if CPUID_HYPERVISOR_LEAVES.contains(&leaf) {
+            return Err(Error::NotSupported);
+        }
+
+        // Leaves that are not in the policy read as zero.
+        let mut result = driver
+            .cpuid_policy()?
+            .iter()
+            .find(|entry| {
+                entry.leaf == leaf
+                    && (entry.subleaf == subleaf || entry.subleaf == xen_sys::XEN_CPUID_NO_SUBLEAF)
+            })
+            .map(|entry| CpuidResult {
+                eax: entry.a,
+                ebx: entry.b,
+                ecx: entry.c,
+                edx: entry.d,
+            })
+            .unwrap_or_default();
+
+        //
+        // The policy is shared by all vCPUs. Xen fills in the APIC ID and
+        // the bits reflecting the guest CR4 when the guest executes `CPUID`,
+        // so the same is done here.
+        //
+
+        let apic_id = u32::from(vcpu.0) * 2;
+
+        match leaf {
+            0x1 => {
+                result.ebx = (result.ebx & 0x00ff_ffff) | (apic_id << 24);
+
+                if result.ecx & CPUID_1_ECX_XSAVE != 0
+                    && Self::registers(driver, vcpu)?.cr4.os_xsave()
+                {
+                    result.ecx |= CPUID_1_ECX_OSXSAVE;
+                }
+            }
+            0x7 if subleaf == 0
+                && result.ecx & CPUID_7_ECX_PKU != 0
+                && Self::registers(driver, vcpu)?
+                    .cr4
+                    .protection_key_for_user_mode_enable() =>
+            {
+                result.ecx |= CPUID_7_ECX_OSPKE;
+            }
+            0xb => {
+                result.ecx = (result.ecx & !0xff) | (subleaf & 0xff);
+                result.edx = apic_id;
+            }
+            _ => {}
+        }
+
+        Ok(result)
+    }
+
+    fn task_priority(driver: &XenDriver<Self>, vcpu: VcpuId) -> Result<u8, Error> {
+        // Offset of the task priority register (TPR) in the APIC page.
+        const APIC_TASKPRI: usize = 0x80;
+
+        // CR8 is not part of the saved CPU context, but it is an alias of
+        // the priority class (bits 7:4) of the local APIC TPR.
+        let lapic = driver.domain.get_context_lapic_regs(vcpu.into_ext())?;
+        Ok(lapic.data[APIC_TASKPRI] >> 4)
+    }
+
     fn monitor_enable(driver: &XenDriver<Self>, option: Self::EventMonitor) -> Result<(), Error> {
         const ENABLE: bool = true;
         const SYNC: bool = true;
@@ -217,7 +299,9 @@ impl ArchAdapter for Amd64 {
         let _ = driver.monitor_disable(EventMonitor::Register(ControlRegister::Cr4));
         let _ = driver.monitor_disable(EventMonitor::Register(ControlRegister::Cr3));
         let _ = driver.monitor_disable(EventMonitor::Register(ControlRegister::Cr0));
-        let _ = driver.altp2m.reset_view();
+        if let Some(altp2m) = &driver.altp2m {
+            let _ = altp2m.reset_view();
+        }
         driver.views.borrow_mut().clear();
 
         Ok(())
@@ -1,6 +1,6 @@
 mod amd64;
 
-use vmi_core::{Architecture, VcpuId, VmiEvent, VmiEventResponse};
+use vmi_core::{Architecture, CpuidResult, VcpuId, VmiEvent, VmiEventResponse};
 use xen::{ctrl::VmEvent, Architecture as XenArchitecture};
 
 use crate::{Error, XenDriver};
@@ -17,6 +17,15 @@ pub trait ArchAdapter: Architecture + Sized {
         registers: Self::Registers,
     ) -> Result<(), Error>;
 
+    fn guest_cpuid(
+        driver: &XenDriver<Self>,
+        vcpu: VcpuId,
+        leaf: u32,
+        subleaf: u32,
+    ) -> Result<CpuidResult, Error>;
+
+    fn task_priority(driver: &XenDriver<Self>, vcpu: VcpuId) -> Result<u8, Error>;
+
     fn monitor_enable(driver: &XenDriver<Self>, option: Self::EventMonitor) -> Result<(), Error>;
 
     fn monitor_disable(driver: &XenDriver<Self>, option: Self::EventMonitor) -> Result<(), Error>;
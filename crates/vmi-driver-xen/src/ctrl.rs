@@ -0,0 +1,114 @@
+use std::os::raw::{c_char, c_int, c_ulong};
+
+use xen_sys::{
+    __va_list_tag, xc_interface, xen_cpuid_leaf_t, xen_msr_entry_t, xentoollog_level,
+    xentoollog_logger,
+};
+
+use crate::Error;
+
+// The CPU policy functions live in `libxenguest`, which `libxen-sys`
+// doesn't provide bindings for.
+#[link(name = "xenguest")]
+extern "C" {
+    pub(crate) fn xc_get_cpu_policy_size(
+        xch: *mut xc_interface,
+        nr_leaves: *mut u32,
+        nr_msrs: *mut u32,
+    ) -> c_int;
+
+    pub(crate) fn xc_get_domain_cpu_policy(
+        xch: *mut xc_interface,
+        domid: u32,
+        nr_leaves: *mut u32,
+        leaves: *mut xen_cpuid_leaf_t,
+        nr_msrs: *mut u32,
+        msrs: *mut xen_msr_entry_t,
+    ) -> c_int;
+}
+
+/// A `libxc` control interface handle.
+///
+/// `libxen` doesn't expose the handle it uses internally, so the driver
+/// opens one of its own for the `libxc` calls that `libxen` doesn't wrap
+/// (e.g., `xc_hvm_param_get` or `xc_shadow_control`).
+///
+/// Without a logger, `libxc` prints failures to stderr. The handle is
+/// opened with a logger that discards them instead; the failures are
+/// reported through `errno` as well, which ends up in the returned errors.
+pub(crate) struct XenControlHandle {
+    xch: *mut xc_interface,
+
+    /// The logger is referenced by the handle, so it must outlive it.
+    _logger: Box<xentoollog_logger>,
+}
+
+impl XenControlHandle {
+    /// Opens a new control interface handle.
+    pub fn new() -> Result<Self, Error> {
+        let mut logger = Box::new(xentoollog_logger {
+            vmessage: Some(discard_message),
+            progress: Some(discard_progress),
+            destroy: None,
+        });
+
+        let xch = unsafe { xen_sys::xc_interface_open(&mut *logger, &mut *logger, 0) };
+
+        if xch.is_null() {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+
+        Ok(Self {
+            xch,
+            _logger: logger,
+        })
+    }
+
+    /// Returns the raw handle.
+    pub fn as_ptr(&self) -> *mut xc_interface {
+        self.xch
+    }
+
+    /// Converts the return code of a `libxc` call into a result.
+    ///
+    /// Must be called right after the call, before any cleanup (e.g.,
+    /// freeing a hypercall buffer) has a chance to overwrite `errno`.
+    pub fn check<T>(rc: T) -> Result<T, Error>
+    where
+        T: PartialOrd + Default,
+    {
+        if rc < T::default() {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+
+        Ok(rc)
+    }
+}
+
+impl Drop for XenControlHandle {
+    fn drop(&mut self) {
+        unsafe {
+            xen_sys::xc_interface_close(self.xch);
+        }
+    }
+}
+
+unsafe extern "C" fn discard_message(
+    _logger: *mut xentoollog_logger,
+    _level: xentoollog_level,
+    _errnoval: c_int,
+    _context: *const c_char,
+    _format: *const c_char,
+    _al: *mut __va_list_tag,
+) {
+}
+
+unsafe extern "C" fn discard_progress(
+    _logger: *mut xentoollog_logger,
+    _context: *const c_char,
+    _doing_what: *const c_char,
+    _percent: c_int,
+    _done: c_ulong,
+    _total: c_ulong,
+) {
+}
@@ -1,13 +1,13 @@
 use std::{
-    cell::RefCell,
-    collections::HashMap,
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
     os::fd::AsRawFd as _,
-    time::{Duration, Instant},
+    time::{Duration, Instant, SystemTime},
 };
 
 use vmi_core::{
-    Architecture, Gfn, MemoryAccess, VcpuId, View, VmiEvent, VmiEventResponse, VmiInfo,
-    VmiMappedPage,
+    Architecture, CpuidResult, Gfn, MemoryAccess, VcpuId, View, ViewInfo, VmiError, VmiEvent,
+    VmiEventResponse, VmiInfo, VmiMappedPage,
 };
 use xen::{
     ctrl::VmEventRing, XenAltP2M, XenAltP2MView, XenControl, XenDeviceModel, XenDomain,
@@ -16,7 +16,65 @@ use xen::{
 };
 
 use super::arch::ArchAdapter;
-use crate::{Error, IntoExt as _};
+use crate::{
+    ctrl::{self, XenControlHandle},
+    Error, IntoExt as _,
+};
+
+/// Number of altp2m views supported by Xen (`MAX_ALTP2M`), including the
+/// default view.
+///
+/// This is the default limit of Xen. Newer hypervisors allow a different
+/// limit per domain (the `altp2m.nr` domain creation parameter), but it
+/// can't be queried through the control interface, so the default limit is
+/// assumed.
+const MAX_ALTP2M: u16 = 10;
+
+/// Modifications made to a view through the driver.
+///
+/// Xen doesn't report which GFNs of a view were remapped or restricted, so
+/// the driver keeps track of them to be able to provide [`ViewInfo`].
+#[derive(Default)]
+pub(crate) struct XenViewModifications {
+    /// GFNs mapped to a different GFN.
+    remapped: HashSet<Gfn>,
+
+    /// GFNs with access permissions other than the default ones.
+    restricted: HashMap<Gfn, MemoryAccess>,
+}
+
+impl XenViewModifications {
+    /// Records a change of the access permissions of a GFN.
+    fn set_access(&mut self, gfn: Gfn, access: MemoryAccess, default_access: MemoryAccess) {
+        if access == default_access {
+            self.restricted.remove(&gfn);
+        }
+        else {
+            self.restricted.insert(gfn, access);
+        }
+    }
+
+    /// Returns the statistics of the modifications.
+    fn info(&self, default_access: MemoryAccess) -> ViewInfo {
+        ViewInfo {
+            default_access,
+            remapped_gfns: self.remapped.len(),
+            restricted_gfns: self.restricted.len(),
+        }
+    }
+}
+
+/// An altp2m view created by the driver.
+pub(crate) struct XenView {
+    /// The altp2m view.
+    inner: XenAltP2MView,
+
+    /// The access permissions the view was created with.
+    default_access: MemoryAccess,
+
+    /// The modifications made to the view.
+    modifications: XenViewModifications,
+}
 
 /// VMI driver for Xen hypervisor.
 pub struct XenDriver<Arch>
@@ -26,14 +84,23 @@ where
     pub(crate) domain: XenDomain<Arch::XenArch>,
     pub(crate) devicemodel: XenDeviceModel,
     pub(crate) monitor: XenMonitor,
-    pub(crate) altp2m: XenAltP2M,
+    pub(crate) altp2m: Option<XenAltP2M>,
     pub(crate) evtchn: XenEventChannelPort,
     pub(crate) foreign_memory: XenForeignMemory,
     pub(crate) info: XenDomainInfo,
+    pub(crate) xc: XenControlHandle,
+
+    /// Guest TSC frequency and epoch, retrieved once at creation.
+    pub(crate) tsc_frequency: Option<u64>,
+    pub(crate) tsc_epoch: Option<SystemTime>,
 
     pub(crate) ring: RefCell<VmEventRing>,
-    pub(crate) views: RefCell<HashMap<u16, XenAltP2MView>>,
+    pub(crate) views: RefCell<HashMap<u16, XenView>>,
+    pub(crate) default_view_modifications: RefCell<XenViewModifications>,
     pub(crate) event_processing_overhead: RefCell<Duration>,
+
+    /// Whether the log-dirty mode was enabled by the driver.
+    pub(crate) dirty_tracking: Cell<bool>,
 }
 
 impl<Arch> Drop for XenDriver<Arch>
@@ -46,6 +113,10 @@ where
         let _ = self.domain.set_max_mem(max_memkb);
         let _ = self.monitor.emul_unimplemented(false);
         let _ = self.monitor.inguest_pagefault(false);
+
+        if self.dirty_tracking.get() {
+            let _ = self.shadow_control(xen_sys::XEN_DOMCTL_SHADOW_OP_OFF);
+        }
     }
 }
 
@@ -53,14 +124,23 @@ impl<Arch> XenDriver<Arch>
 where
     Arch: Architecture + ArchAdapter,
 {
-    pub fn new(domain_id: XenDomainId) -> Result<Self, Error> {
+    pub fn new(domain_id: XenDomainId, enable_altp2m: bool) -> Result<Self, Error> {
+        let xc_handle = XenControlHandle::new()?;
+
+        if enable_altp2m {
+            Self::check_altp2m(&xc_handle, domain_id)?;
+        }
+
         let xc = XenControl::new()?;
         let domain = xc.domain(domain_id)?;
         domain.set_max_mem(u64::MAX)?;
 
         let devicemodel = domain.device_model()?;
         let (monitor, ring) = domain.monitor()?;
-        let altp2m = domain.altp2m()?;
+        let altp2m = match enable_altp2m {
+            true => Some(domain.altp2m()?),
+            false => None,
+        };
         let evtchn = monitor.channel()?;
         let foreign_memory = XenForeignMemory::new()?;
         let info = domain.info()?;
@@ -68,6 +148,14 @@ where
         monitor.inguest_pagefault(true)?;
         monitor.emul_unimplemented(true)?;
 
+        let (tsc_frequency, tsc_epoch) = match Self::tsc_info(&xc_handle, domain_id) {
+            Ok(tsc_info) => tsc_info,
+            Err(err) => {
+                tracing::warn!(err = %VmiError::from(err), "failed to retrieve TSC info");
+                (None, None)
+            }
+        };
+
         Ok(Self {
             domain,
             devicemodel,
@@ -76,21 +164,258 @@ where
             evtchn,
             foreign_memory,
             info,
+            xc: xc_handle,
+            tsc_frequency,
+            tsc_epoch,
             ring: RefCell::new(ring),
             views: RefCell::new(HashMap::new()),
+            default_view_modifications: RefCell::new(XenViewModifications::default()),
             event_processing_overhead: RefCell::new(Duration::from_millis(0)),
+            dirty_tracking: Cell::new(false),
         })
     }
 
+    /// Checks that altp2m is enabled for the domain.
+    ///
+    /// Enabling altp2m on a domain that was created without it fails in
+    /// `xc_altp2m_set_domain_state` without any details, so the
+    /// `HVM_PARAM_ALTP2M` parameter is inspected upfront.
+    fn check_altp2m(xc: &XenControlHandle, domain_id: XenDomainId) -> Result<(), Error> {
+        let mut mode = 0;
+
+        let rc = unsafe {
+            xen_sys::xc_hvm_param_get(
+                xc.as_ptr(),
+                domain_id.into(),
+                xen_sys::HVM_PARAM_ALTP2M,
+                &mut mode,
+            )
+        };
+
+        // The parameter is not available, e.g., for PV domains.
+        if rc < 0 {
+            tracing::debug!(
+                err = %std::io::Error::last_os_error(),
+                "failed to query HVM_PARAM_ALTP2M"
+            );
+            return Err(Error::AltP2MUnavailable);
+        }
+
+        if mode == u64::from(xen_sys::XEN_ALTP2M_disabled) {
+            return Err(Error::AltP2MUnavailable);
+        }
+
+        Ok(())
+    }
+
     pub fn info(&self) -> Result<VmiInfo, Error> {
         Ok(VmiInfo {
             page_size: Arch::PAGE_SIZE,
             page_shift: Arch::PAGE_SHIFT,
             max_gfn: Gfn::new(self.domain.maximum_gpfn()?),
             vcpus: self.info.max_vcpu_id + 1,
+            tsc_frequency: self.tsc_frequency,
+            tsc_epoch: self.tsc_epoch,
         })
     }
 
+    /// Retrieves the guest TSC frequency (in Hz) and the host wall-clock
+    /// time at which the guest TSC was zero.
+    ///
+    /// With an emulated (or scaled) TSC, Xen reports the guest TSC frequency.
+    /// With a native TSC, Xen reports zero instead, and the guest TSC runs
+    /// at the host TSC frequency.
+    ///
+    /// The epoch is derived from the current guest TSC of the first vCPU.
+    /// The elapsed time Xen reports is not used for it, because with
+    /// a native TSC it is either zero or measured with the host TSC, which
+    /// is offset from the guest TSC.
+    fn tsc_info(
+        xc: &XenControlHandle,
+        domain_id: XenDomainId,
+    ) -> Result<(Option<u64>, Option<SystemTime>), Error> {
+        let mut tsc_mode = 0;
+        let mut elapsed_nsec = 0;
+        let mut gtsc_khz = 0;
+        let mut incarnation = 0;
+
+        let rc = unsafe {
+            xen_sys::xc_domain_get_tsc_info(
+                xc.as_ptr(),
+                domain_id.into(),
+                &mut tsc_mode,
+                &mut elapsed_nsec,
+                &mut gtsc_khz,
+                &mut incarnation,
+            )
+        };
+
+        XenControlHandle::check(rc)?;
+
+        let frequency = match gtsc_khz {
+            0 => Self::host_tsc_khz(xc)?,
+            gtsc_khz => gtsc_khz,
+        };
+
+        let frequency = match frequency {
+            0 => return Ok((None, None)),
+            frequency => u64::from(frequency) * 1000,
+        };
+
+        let now = SystemTime::now();
+        let tsc = Self::guest_tsc(xc, domain_id, VcpuId(0))?;
+        let nanos = u128::from(tsc) * 1_000_000_000 / u128::from(frequency);
+        let elapsed = Duration::from_nanos(u64::try_from(nanos).map_err(|_| Error::OutOfBounds)?);
+
+        let epoch = now.checked_sub(elapsed).ok_or(Error::OutOfBounds)?;
+
+        Ok((Some(frequency), Some(epoch)))
+    }
+
+    /// Retrieves the TSC frequency of the host (in kHz).
+    fn host_tsc_khz(xc: &XenControlHandle) -> Result<u32, Error> {
+        let mut physinfo = xen_sys::xc_physinfo_t::default();
+        let rc = unsafe { xen_sys::xc_physinfo(xc.as_ptr(), &mut physinfo) };
+        XenControlHandle::check(rc)?;
+        Ok(physinfo.cpu_khz)
+    }
+
+    /// Retrieves the current guest TSC of a vCPU from its HVM context.
+    fn guest_tsc(
+        xc: &XenControlHandle,
+        domain_id: XenDomainId,
+        vcpu: VcpuId,
+    ) -> Result<u64, Error> {
+        let mut context = xen_sys::hvm_hw_cpu::default();
+
+        // The length of the `c` array is the save type code (HVM_SAVE_CODE).
+        let typecode = size_of_val(&xen_sys::__HVM_SAVE_TYPE_CPU::default().c) as u16;
+
+        let rc = unsafe {
+            xen_sys::xc_domain_hvm_getcontext_partial(
+                xc.as_ptr(),
+                domain_id.into(),
+                typecode,
+                vcpu.0,
+                &mut context as *mut _ as *mut _,
+                size_of_val(&context) as u32,
+            )
+        };
+
+        XenControlHandle::check(rc)?;
+        Ok(context.tsc)
+    }
+
+    /// Retrieves the virtual NUMA node of the domain that contains the
+    /// given GFN.
+    pub fn gfn_vnuma_node(&self, gfn: Gfn) -> Result<u32, Error> {
+        let address = u64::from(gfn) << Arch::PAGE_SHIFT;
+
+        self.vnuma_memory_ranges()?
+            .iter()
+            .find(|range| range.start <= address && address < range.end)
+            .map(|range| range.nid)
+            .ok_or(Error::OutOfBounds)
+    }
+
+    /// Retrieves the memory ranges of the virtual NUMA nodes of the domain.
+    ///
+    /// The first call queries the number of nodes, ranges and vCPUs, the
+    /// second one fills the buffers.
+    fn vnuma_memory_ranges(&self) -> Result<Vec<xen_sys::xen_vmemrange_t>, Error> {
+        let mut nr_vnodes = 0;
+        let mut nr_vmemranges = 0;
+        let mut nr_vcpus = 0;
+
+        let mut vmemranges = Vec::new();
+        let mut vdistance = Vec::new();
+        let mut vcpu_to_vnode = Vec::new();
+
+        // Query the sizes of the buffers. Without the buffers, the call
+        // fails with `ENOBUFS`, but the sizes are filled in. Domains without
+        // a virtual NUMA topology fail with `EOPNOTSUPP`.
+        let rc = unsafe {
+            xen_sys::xc_domain_getvnuma(
+                self.xc.as_ptr(),
+                self.domain.id().into(),
+                &mut nr_vnodes,
+                &mut nr_vmemranges,
+                &mut nr_vcpus,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+            )
+        };
+
+        if rc < 0 {
+            let err = std::io::Error::last_os_error();
+            match err.raw_os_error() {
+                Some(libc::ENOBUFS) => {}
+                Some(libc::EOPNOTSUPP) => return Err(Error::NotSupported),
+                _ => return Err(Error::Io(err)),
+            }
+        }
+
+        if nr_vnodes == 0 {
+            return Err(Error::NotSupported);
+        }
+
+        vmemranges.resize(nr_vmemranges as usize, Default::default());
+        vdistance.resize((nr_vnodes * nr_vnodes) as usize, 0);
+        vcpu_to_vnode.resize(nr_vcpus as usize, 0);
+
+        let rc = unsafe {
+            xen_sys::xc_domain_getvnuma(
+                self.xc.as_ptr(),
+                self.domain.id().into(),
+                &mut nr_vnodes,
+                &mut nr_vmemranges,
+                &mut nr_vcpus,
+                vmemranges.as_mut_ptr(),
+                vdistance.as_mut_ptr(),
+                vcpu_to_vnode.as_mut_ptr(),
+            )
+        };
+
+        XenControlHandle::check(rc)?;
+
+        vmemranges.truncate(nr_vmemranges as usize);
+        Ok(vmemranges)
+    }
+
+    /// Retrieves the CPUID leaves of the domain CPU policy.
+    ///
+    /// The buffers are sized for the largest policy Xen can report. The
+    /// MSRs are not needed, but Xen refuses to serialize the policy into
+    /// a buffer that is too small for them.
+    pub(crate) fn cpuid_policy(&self) -> Result<Vec<xen_sys::xen_cpuid_leaf_t>, Error> {
+        let xch = self.xc.as_ptr();
+
+        let mut nr_leaves = 0;
+        let mut nr_msrs = 0;
+
+        let rc = unsafe { ctrl::xc_get_cpu_policy_size(xch, &mut nr_leaves, &mut nr_msrs) };
+        XenControlHandle::check(rc)?;
+
+        let mut leaves = vec![xen_sys::xen_cpuid_leaf_t::default(); nr_leaves as usize];
+        let mut msrs = vec![xen_sys::xen_msr_entry_t::default(); nr_msrs as usize];
+
+        let rc = unsafe {
+            ctrl::xc_get_domain_cpu_policy(
+                xch,
+                self.domain.id().into(),
+                &mut nr_leaves,
+                leaves.as_mut_ptr(),
+                &mut nr_msrs,
+                msrs.as_mut_ptr(),
+            )
+        };
+        XenControlHandle::check(rc)?;
+
+        leaves.truncate(nr_leaves as usize);
+        Ok(leaves)
+    }
+
     pub fn pause(&self) -> Result<(), Error> {
         Ok(self.domain.pause()?)
     }
@@ -107,13 +432,50 @@ where
         Arch::set_registers(self, vcpu, registers)
     }
 
+    pub fn guest_cpuid(&self, vcpu: VcpuId, leaf: u32, subleaf: u32) -> Result<CpuidResult, Error> {
+        Arch::guest_cpuid(self, vcpu, leaf, subleaf)
+    }
+
+    pub fn task_priority(&self, vcpu: VcpuId) -> Result<u8, Error> {
+        Arch::task_priority(self, vcpu)
+    }
+
+    pub fn flush_guest_tlb(&self, vcpu: Option<VcpuId>) -> Result<(), Error> {
+        // Xen has no domctl to flush the TLB of a foreign domain. However,
+        // loading a vCPU context makes Xen recompute the paging mode of the
+        // vCPU, which flushes its tagged translations (ASID/VPID).
+        //
+        // Reloading the context is not cheap, callers should pass the vCPU
+        // that needs the flush instead of `None` whenever they know it.
+        //
+        // The domain is paused for the whole sequence, otherwise a running
+        // vCPU would be reset to the context read before it moved on.
+        // Pausing is reference counted, so this is safe even if the domain
+        // is already paused.
+        let mut vcpus = match vcpu {
+            Some(vcpu) => vcpu.0..vcpu.0 + 1,
+            None => 0..self.info()?.vcpus,
+        };
+
+        self.pause()?;
+
+        let result = vcpus.try_for_each(|vcpu| {
+            let vcpu = VcpuId(vcpu);
+            let registers = self.registers(vcpu)?;
+            self.set_registers(vcpu, registers)
+        });
+
+        let resumed = self.resume();
+        result.and(resumed)
+    }
+
     pub fn memory_access(&self, gfn: Gfn, view: View) -> Result<MemoryAccess, Error> {
         if view.0 == 0 {
             return Ok(self.domain.get_mem_access(gfn.0)?.into_ext());
         }
 
         match self.views.borrow().get(&view.0) {
-            Some(view) => Ok(view.get_mem_access(gfn.0)?.into_ext()),
+            Some(view) => Ok(view.inner.get_mem_access(gfn.0)?.into_ext()),
             None => Err(Error::ViewNotFound),
         }
     }
@@ -127,11 +489,21 @@ where
         tracing::trace!(%gfn, %view, %access, "set memory access");
 
         if view.0 == 0 {
-            return Ok(self.domain.set_mem_access(gfn.into(), access.into_ext())?);
+            self.domain.set_mem_access(gfn.into(), access.into_ext())?;
+            // The host p2m of the domain has full access by default.
+            self.default_view_modifications
+                .borrow_mut()
+                .set_access(gfn, access, MemoryAccess::RWX);
+            return Ok(());
         }
 
-        match self.views.borrow().get(&view.0) {
-            Some(view) => Ok(view.set_mem_access(gfn.into(), access.into_ext())?),
+        match self.views.borrow_mut().get_mut(&view.0) {
+            Some(view) => {
+                view.inner.set_mem_access(gfn.into(), access.into_ext())?;
+                view.modifications
+                    .set_access(gfn, access, view.default_access);
+                Ok(())
+            }
             None => Err(Error::ViewNotFound),
         }
     }
@@ -182,11 +554,44 @@ where
         View(0)
     }
 
+    /// Returns the maximum number of altp2m views, including the default
+    /// view.
+    ///
+    /// The limit is not queried from the hypervisor. The default limit of
+    /// Xen (`MAX_ALTP2M`, 10 views) is assumed, even if the domain was
+    /// created with a different one. Without altp2m, only the default view
+    /// exists.
+    pub fn max_altp2m_views(&self) -> u16 {
+        match self.altp2m {
+            Some(_) => MAX_ALTP2M,
+            None => 1,
+        }
+    }
+
     pub fn create_view(&self, default_access: MemoryAccess) -> Result<View, Error> {
-        let view = self.altp2m.create_view(default_access.into_ext())?;
+        let altp2m = match &self.altp2m {
+            Some(altp2m) => altp2m,
+            None => return Err(Error::NotSupported),
+        };
+
+        // The hypercall fails with a generic error when no altp2m slot is
+        // left, so report the limit explicitly.
+        if self.views.borrow().len() + 1 >= usize::from(MAX_ALTP2M) {
+            tracing::debug!(max = MAX_ALTP2M, "altp2m view limit reached");
+            return Err(Error::OutOfBounds);
+        }
+
+        let view = altp2m.create_view(default_access.into_ext())?;
 
         let id = view.id();
-        self.views.borrow_mut().insert(id, view);
+        self.views.borrow_mut().insert(
+            id,
+            XenView {
+                inner: view,
+                default_access,
+                modifications: XenViewModifications::default(),
+            },
+        );
 
         Ok(View(id))
     }
@@ -204,12 +609,18 @@ where
     }
 
     pub fn switch_to_view(&self, view: View) -> Result<(), Error> {
+        let altp2m = match &self.altp2m {
+            Some(altp2m) => altp2m,
+            None if view.0 == 0 => return Ok(()),
+            None => return Err(Error::NotSupported),
+        };
+
         if view.0 == 0 {
-            return Ok(self.altp2m.reset_view()?);
+            return Ok(altp2m.reset_view()?);
         }
 
         match self.views.borrow().get(&view.0) {
-            Some(view) => Ok(view.switch()?),
+            Some(view) => Ok(view.inner.switch()?),
             None => Err(Error::ViewNotFound),
         }
     }
@@ -219,9 +630,17 @@ where
             return Ok(());
         }
 
-        match self.views.borrow().get(&view.0) {
+        if self.altp2m.is_none() {
+            return Err(Error::NotSupported);
+        }
+
+        match self.views.borrow_mut().get_mut(&view.0) {
             // WARNING: This will change access permissions of the GFN!
-            Some(view) => Ok(view.change_gfn(old_gfn.into(), new_gfn.into())?),
+            Some(view) => {
+                view.inner.change_gfn(old_gfn.into(), new_gfn.into())?;
+                view.modifications.remapped.insert(old_gfn);
+                Ok(())
+            }
             None => Err(Error::ViewNotFound),
         }
     }
@@ -231,13 +650,155 @@ where
             return Ok(());
         }
 
-        match self.views.borrow().get(&view.0) {
+        if self.altp2m.is_none() {
+            return Err(Error::NotSupported);
+        }
+
+        match self.views.borrow_mut().get_mut(&view.0) {
             // WARNING: This will change access permissions of the GFN!
-            Some(view) => Ok(view.change_gfn(gfn.into(), u64::MAX)?),
+            Some(view) => {
+                view.inner.change_gfn(gfn.into(), u64::MAX)?;
+                view.modifications.remapped.remove(&gfn);
+                Ok(())
+            }
+            None => Err(Error::ViewNotFound),
+        }
+    }
+
+    pub fn view_info(&self, view: View) -> Result<ViewInfo, Error> {
+        if view.0 == 0 {
+            return Ok(self
+                .default_view_modifications
+                .borrow()
+                .info(MemoryAccess::RWX));
+        }
+
+        match self.views.borrow().get(&view.0) {
+            Some(view) => Ok(view.modifications.info(view.default_access)),
             None => Err(Error::ViewNotFound),
         }
     }
 
+    pub fn enable_dirty_tracking(&self) -> Result<(), Error> {
+        if self.dirty_tracking.get() {
+            return Ok(());
+        }
+
+        // Xen refuses to enable the log-dirty mode if it is already enabled,
+        // e.g., by the toolstack during a live migration.
+        self.shadow_control(xen_sys::XEN_DOMCTL_SHADOW_OP_ENABLE_LOGDIRTY)?;
+        self.dirty_tracking.set(true);
+        Ok(())
+    }
+
+    pub fn disable_dirty_tracking(&self) -> Result<(), Error> {
+        // The log-dirty mode is only turned off if the driver turned it on,
+        // so that other users of the mode are not affected.
+        if !self.dirty_tracking.get() {
+            return Ok(());
+        }
+
+        self.shadow_control(xen_sys::XEN_DOMCTL_SHADOW_OP_OFF)?;
+        self.dirty_tracking.set(false);
+        Ok(())
+    }
+
+    pub fn dirty_gfns(&self) -> Result<Vec<Gfn>, Error> {
+        let bitmap = self.logdirty_bitmap(xen_sys::XEN_DOMCTL_SHADOW_OP_PEEK)?;
+        Ok(Self::bitmap_gfns(&bitmap))
+    }
+
+    pub fn clear_dirty(&self) -> Result<(), Error> {
+        self.logdirty_bitmap(xen_sys::XEN_DOMCTL_SHADOW_OP_CLEAN)?;
+        Ok(())
+    }
+
+    pub fn take_dirty_gfns(&self) -> Result<Vec<Gfn>, Error> {
+        // SHADOW_OP_CLEAN returns the bitmap and clears it in a single
+        // hypercall, so no write is lost in between.
+        let bitmap = self.logdirty_bitmap(xen_sys::XEN_DOMCTL_SHADOW_OP_CLEAN)?;
+        Ok(Self::bitmap_gfns(&bitmap))
+    }
+
+    /// Converts a log-dirty bitmap to the list of dirty GFNs.
+    fn bitmap_gfns(bitmap: &[u8]) -> Vec<Gfn> {
+        bitmap
+            .iter()
+            .enumerate()
+            .filter(|(_, byte)| **byte != 0)
+            .flat_map(|(index, byte)| {
+                (0..8)
+                    .filter(move |bit| byte & (1 << bit) != 0)
+                    .map(move |bit| Gfn::new(index as u64 * 8 + bit))
+            })
+            .collect()
+    }
+
+    /// Performs a paging operation that doesn't transfer the dirty bitmap.
+    fn shadow_control(&self, sop: u32) -> Result<(), Error> {
+        let rc = unsafe {
+            xen_sys::xc_shadow_control(
+                self.xc.as_ptr(),
+                self.domain.id().into(),
+                sop,
+                std::ptr::null_mut(),
+                0,
+            )
+        };
+
+        XenControlHandle::check(rc)?;
+        Ok(())
+    }
+
+    /// Retrieves the log-dirty bitmap of the domain, one bit per GFN.
+    ///
+    /// The bitmap must be passed in a hypercall buffer, which is what the
+    /// `DECLARE_HYPERCALL_BUFFER` and `xc_hypercall_buffer_alloc_pages`
+    /// macros of `libxc` set up.
+    fn logdirty_bitmap(&self, sop: u32) -> Result<Vec<u8>, Error> {
+        let pages = self.domain.maximum_gpfn()? + 1;
+        let size = pages.div_ceil(8) as usize;
+        let nr_pages = size.div_ceil(Arch::PAGE_SIZE as usize) as i32;
+
+        let xch = self.xc.as_ptr();
+
+        // HYPERCALL_BUFFER_INIT_NO_BOUNCE
+        let mut buffer = xen_sys::xc_hypercall_buffer_t {
+            hbuf: std::ptr::null_mut(),
+            param_shadow: std::ptr::null_mut(),
+            dir: 0,
+            ubuf: usize::MAX as *mut _,
+            sz: 0,
+        };
+
+        let data = unsafe { xen_sys::xc__hypercall_buffer_alloc_pages(xch, &mut buffer, nr_pages) };
+        if data.is_null() {
+            return Err(Error::Io(std::io::Error::last_os_error()));
+        }
+
+        let mut stats = xen_sys::xc_shadow_op_stats_t::default();
+        let rc = unsafe {
+            xen_sys::xc_logdirty_control(
+                xch,
+                self.domain.id().into(),
+                sop,
+                &mut buffer,
+                pages as _,
+                0,
+                &mut stats,
+            )
+        };
+
+        let result = XenControlHandle::check(rc).map(|_| {
+            let mut bitmap = vec![0u8; size];
+            unsafe { std::ptr::copy_nonoverlapping(data as *const u8, bitmap.as_mut_ptr(), size) };
+            bitmap
+        });
+
+        unsafe { xen_sys::xc__hypercall_buffer_free_pages(xch, &mut buffer, nr_pages) };
+        result
+    }
+
     pub fn monitor_enable(&self, option: Arch::EventMonitor) -> Result<(), Error> {
         Arch::monitor_enable(self, option)
     }
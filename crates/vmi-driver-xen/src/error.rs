@@ -6,6 +6,9 @@ pub enum Error {
     /// An I/O error occurred.
     Io(std::io::Error),
 
+    /// altp2m is not enabled for the domain.
+    AltP2MUnavailable,
+
     /// The given timeout is invalid.
     InvalidTimeout,
 
@@ -33,6 +36,10 @@ impl From<Error> for vmi_core::VmiError {
         match error {
             Error::Xen(error) => Self::Driver(Box::new(error)),
             Error::Io(error) => Self::Io(error),
+            Error::AltP2MUnavailable => Self::Other(
+                "altp2m is not enabled for the domain, \
+                 create the domain with `altp2m=1` or `altp2m=\"external\"`",
+            ),
             Error::InvalidTimeout => Self::InvalidTimeout,
             Error::NotSupported => Self::NotSupported,
             Error::OutOfBounds => Self::OutOfBounds,
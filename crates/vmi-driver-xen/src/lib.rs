@@ -3,14 +3,15 @@
 mod arch;
 mod convert;
 mod core;
+mod ctrl;
 mod driver;
 mod error;
 
 use std::time::Duration;
 
 use vmi_core::{
-    Architecture, Gfn, MemoryAccess, VcpuId, View, VmiDriver, VmiError, VmiEvent, VmiEventResponse,
-    VmiInfo, VmiMappedPage,
+    Architecture, CpuidResult, DriverCapabilities, Gfn, MemoryAccess, VcpuId, View, ViewInfo,
+    VmiDriver, VmiError, VmiEvent, VmiEventResponse, VmiInfo, VmiMappedPage,
 };
 use xen::XenDomainId;
 
@@ -29,15 +30,97 @@ where
     inner: XenDriver<Arch>,
 }
 
+/// A builder for [`VmiXenDriver`].
+///
+/// Created by [`VmiXenDriver::builder`].
+#[derive(Debug)]
+pub struct VmiXenDriverBuilder<Arch>
+where
+    Arch: Architecture + ArchAdapter,
+{
+    domain_id: XenDomainId,
+    altp2m: bool,
+    _marker: std::marker::PhantomData<Arch>,
+}
+
+impl<Arch> VmiXenDriverBuilder<Arch>
+where
+    Arch: Architecture + ArchAdapter,
+{
+    /// Sets whether altp2m should be enabled for the domain.
+    ///
+    /// Enabled by default. Without altp2m, only the default view is
+    /// available and view operations fail with [`VmiError::NotSupported`],
+    /// but the driver can be used with domains created without altp2m
+    /// support.
+    pub fn with_altp2m(self, altp2m: bool) -> Self {
+        Self { altp2m, ..self }
+    }
+
+    /// Creates the driver.
+    pub fn build(self) -> Result<VmiXenDriver<Arch>, VmiError> {
+        Ok(VmiXenDriver {
+            inner: XenDriver::new(self.domain_id, self.altp2m)?,
+        })
+    }
+}
+
 impl<Arch> VmiXenDriver<Arch>
 where
     Arch: Architecture + ArchAdapter,
 {
     /// Creates a new VMI driver for Xen hypervisor.
+    ///
+    /// The domain must be created with altp2m enabled (`altp2m=1` or
+    /// `altp2m="external"` in the domain configuration). Otherwise,
+    /// an error explaining this is returned.
     pub fn new(domain_id: XenDomainId) -> Result<Self, VmiError> {
-        Ok(Self {
-            inner: XenDriver::new(domain_id)?,
-        })
+        Self::builder(domain_id).build()
+    }
+
+    /// Creates a new VMI driver for Xen hypervisor without setting up
+    /// altp2m.
+    ///
+    /// Useful for tools that don't need views, or for domains created
+    /// without altp2m. Only the default view is available; creating
+    /// a view fails with [`VmiError::NotSupported`].
+    pub fn new_without_altp2m(domain_id: XenDomainId) -> Result<Self, VmiError> {
+        Self::builder(domain_id).with_altp2m(false).build()
+    }
+
+    /// Returns a builder for configuring the driver.
+    pub fn builder(domain_id: XenDomainId) -> VmiXenDriverBuilder<Arch> {
+        VmiXenDriverBuilder {
+            domain_id,
+            altp2m: true,
+            _marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Retrieves the virtual NUMA node of the domain that contains the
+    /// given guest frame number (GFN).
+    ///
+    /// The node is looked up in the virtual NUMA topology of the domain,
+    /// so this is the node as seen by the guest, not the host NUMA node
+    /// that backs the GFN. Xen doesn't report which host node a virtual
+    /// node is placed on; that mapping is only known from the domain
+    /// configuration (`vnuma` `pnode` option).
+    ///
+    /// Returns [`VmiError::NotSupported`] if the domain has no virtual NUMA
+    /// topology and [`VmiError::OutOfBounds`] if the GFN is not part of any
+    /// node.
+    pub fn gfn_vnuma_node(&self, gfn: Gfn) -> Result<u32, VmiError> {
+        Ok(self.inner.gfn_vnuma_node(gfn)?)
+    }
+
+    /// Retrieves the maximum number of altp2m views of the domain,
+    /// including the default view.
+    ///
+    /// Xen doesn't expose the per-domain limit, so the default limit of 10
+    /// views is assumed. Domains created with a different `altp2m.nr`
+    /// may accept more or fewer views.
+    pub fn max_altp2m_views(&self) -> Result<u16, VmiError> {
+        Ok(self.inner.max_altp2m_views())
     }
 }
 
@@ -51,6 +134,17 @@ where
         Ok(self.inner.info()?)
     }
 
+    fn capabilities(&self) -> DriverCapabilities {
+        // Pages are foreign-mapped from the guest memory.
+        let mut capabilities = DriverCapabilities::DIRECT_MAP;
+
+        if self.inner.altp2m.is_some() {
+            capabilities |= DriverCapabilities::VIEWS;
+        }
+
+        capabilities
+    }
+
     fn pause(&self) -> Result<(), VmiError> {
         Ok(self.inner.pause()?)
     }
@@ -67,6 +161,18 @@ where
         Ok(self.inner.set_registers(vcpu, registers)?)
     }
 
+    fn guest_cpuid(&self, vcpu: VcpuId, leaf: u32, subleaf: u32) -> Result<CpuidResult, VmiError> {
+        Ok(self.inner.guest_cpuid(vcpu, leaf, subleaf)?)
+    }
+
+    fn task_priority(&self, vcpu: VcpuId) -> Result<u8, VmiError> {
+        Ok(self.inner.task_priority(vcpu)?)
+    }
+
+    fn flush_guest_tlb(&self, vcpu: Option<VcpuId>) -> Result<(), VmiError> {
+        Ok(self.inner.flush_guest_tlb(vcpu)?)
+    }
+
     fn memory_access(&self, gfn: Gfn, view: View) -> Result<MemoryAccess, VmiError> {
         Ok(self.inner.memory_access(gfn, view)?)
     }
@@ -100,6 +206,10 @@ where
         self.inner.default_view()
     }
 
+    fn max_views(&self) -> Result<u16, VmiError> {
+        Ok(self.inner.max_altp2m_views())
+    }
+
     fn create_view(&self, default_access: MemoryAccess) -> Result<View, VmiError> {
         Ok(self.inner.create_view(default_access)?)
     }
@@ -120,6 +230,30 @@ where
         Ok(self.inner.reset_view_gfn(view, gfn)?)
     }
 
+    fn view_info(&self, view: View) -> Result<ViewInfo, VmiError> {
+        Ok(self.inner.view_info(view)?)
+    }
+
+    fn enable_dirty_tracking(&self) -> Result<(), VmiError> {
+        Ok(self.inner.enable_dirty_tracking()?)
+    }
+
+    fn disable_dirty_tracking(&self) -> Result<(), VmiError> {
+        Ok(self.inner.disable_dirty_tracking()?)
+    }
+
+    fn dirty_gfns(&self) -> Result<Vec<Gfn>, VmiError> {
+        Ok(self.inner.dirty_gfns()?)
+    }
+
+    fn clear_dirty(&self) -> Result<(), VmiError> {
+        Ok(self.inner.clear_dirty()?)
+    }
+
+    fn take_dirty_gfns(&self) -> Result<Vec<Gfn>, VmiError> {
+        Ok(self.inner.take_dirty_gfns()?)
+    }
+
     fn monitor_enable(&self, option: Arch::EventMonitor) -> Result<(), VmiError> {
         Ok(self.inner.monitor_enable(option)?)
     }
@@ -15,7 +15,9 @@ pub trait ItemFnExt {
     /// Returns the visibility of the function.
     fn vis(&self) -> Option<&Visibility>;
 
-    /// Returns the documentation attributes of the function.
+    /// Returns the documentation and `cfg` attributes of the function.
+    ///
+    /// These are forwarded to the generated trait methods.
     fn doc(&self) -> impl Iterator<Item = &Attribute>;
 
     /// Returns the signature of the function.
@@ -37,7 +39,9 @@ impl ItemFnExt for &ImplItemFn {
     }
 
     fn doc(&self) -> impl Iterator<Item = &Attribute> {
-        self.attrs.iter().filter(|attr| attr.path().is_ident("doc"))
+        self.attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("cfg"))
     }
 
     fn sig(&self) -> &Signature {
@@ -60,7 +64,9 @@ impl ItemFnExt for &TraitItemFn {
     }
 
     fn doc(&self) -> impl Iterator<Item = &Attribute> {
-        self.attrs.iter().filter(|attr| attr.path().is_ident("doc"))
+        self.attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc") || attr.path().is_ident("cfg"))
     }
 
     fn sig(&self) -> &Signature {
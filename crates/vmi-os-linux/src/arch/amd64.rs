@@ -11,20 +11,18 @@ where
 {
     fn syscall_argument(
         _os: &LinuxOs<Driver>,
-        vmi: &VmiCore<Driver>,
+        _vmi: &VmiCore<Driver>,
         registers: &Registers,
         index: u64,
     ) -> Result<u64, VmiError> {
         match index {
-            0 => Ok(registers.r10),
-            1 => Ok(registers.rdx),
-            2 => Ok(registers.r8),
-            3 => Ok(registers.r9),
-            _ => {
-                let index = index + 1;
-                let stack = registers.rsp + index * size_of::<u64>() as u64;
-                vmi.read_u64(registers.address_context(stack.into()))
-            }
+            0 => Ok(registers.rdi),
+            1 => Ok(registers.rsi),
+            2 => Ok(registers.rdx),
+            3 => Ok(registers.r10),
+            4 => Ok(registers.r8),
+            5 => Ok(registers.r9),
+            _ => Err(VmiError::OutOfBounds),
         }
     }
 
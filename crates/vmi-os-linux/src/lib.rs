@@ -18,7 +18,57 @@ mod maple_tree;
 pub use self::maple_tree::MapleTree;
 
 mod offsets;
-pub use self::offsets::{Offsets, Symbols};
+pub use self::offsets::{
+    ModuleOffsets, Offsets, PrintkLogOffsets, PrintkOffsets, PrintkRingbufferOffsets, Symbols,
+};
+
+mod printk;
+
+mod rb_tree;
+pub use self::rb_tree::RbTreeIterator;
+
+mod security;
+pub use self::security::{LinuxCapabilities, LinuxSeccompMode};
+
+mod taint;
+pub use self::taint::LinuxTaint;
+
+mod xarray;
+pub use self::xarray::XArray;
+
+/// Size of the `comm` field of `task_struct`, including the terminating
+/// null byte.
+const TASK_COMM_LEN: usize = 16;
+
+/// A loadable kernel module.
+#[derive(Debug)]
+pub struct LinuxModule {
+    /// The address of the `struct module`.
+    pub module: Va,
+
+    /// The `name` field of the module.
+    pub name: String,
+
+    /// The `taints` field of the module.
+    ///
+    /// The taint flags the module caused when it was loaded.
+    pub taints: LinuxTaint,
+}
+
+/// The capability sets of a task, from its `struct cred`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LinuxTaskCapabilities {
+    /// The `cap_effective` set.
+    ///
+    /// The capabilities the kernel checks when the task performs
+    /// a privileged operation.
+    pub effective: LinuxCapabilities,
+
+    /// The `cap_permitted` set.
+    ///
+    /// The capabilities the task may add to its effective set.
+    pub permitted: LinuxCapabilities,
+}
 
 /// VMI operations for the Linux operating system.
 ///
@@ -31,6 +81,8 @@ where
 {
     offsets: Offsets,
     symbols: Symbols,
+    printk: Option<PrintkOffsets>,
+    module: Option<ModuleOffsets>,
     kernel_image_base: RefCell<Option<Va>>,
     kaslr_offset: RefCell<Option<u64>>,
 
@@ -48,6 +100,8 @@ where
         Ok(Self {
             offsets: Offsets::new(profile)?,
             symbols: Symbols::new(profile)?,
+            printk: PrintkOffsets::new(profile),
+            module: ModuleOffsets::new(profile).ok(),
             kernel_image_base: RefCell::new(None),
             kaslr_offset: RefCell::new(None),
             _marker: std::marker::PhantomData,
@@ -192,9 +246,7 @@ where
         let id = vmi.read_u32(registers.address_context(process.0 + __task_struct.tgid.offset))?;
         let name = match self.process_image_path(vmi, registers, process) {
             Ok(Some(name)) => name,
-            _ => {
-                vmi.read_string(registers.address_context(process.0 + __task_struct.comm.offset))?
-            }
+            _ => self.process_filename(vmi, registers, process)?,
         };
         let translation_root = self.process_pgd(vmi, registers, process)?;
 
@@ -206,6 +258,55 @@ where
         })
     }
 
+    /// Finds a process by its PID (thread group ID).
+    ///
+    /// Returns `None` if no process with the given PID exists.
+    pub fn find_process_by_pid(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        pid: ProcessId,
+    ) -> Result<Option<OsProcess>, VmiError> {
+        Ok(self
+            .processes(vmi, registers)?
+            .into_iter()
+            .find(|process| process.id == pid))
+    }
+
+    /// Finds the first process with the given executable name.
+    ///
+    /// The name is compared against `task_struct.comm`. Since `comm` is
+    /// truncated to `TASK_COMM_LEN - 1` (15) bytes, only that many bytes
+    /// of `name` are compared, e.g., `"systemd-journald"` matches
+    /// a process whose `comm` is `"systemd-journal"`.
+    ///
+    /// Returns `None` if no such process exists.
+    pub fn find_process_by_comm(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        name: &str,
+    ) -> Result<Option<OsProcess>, VmiError> {
+        let name = name.as_bytes();
+        let name = &name[..name.len().min(TASK_COMM_LEN - 1)];
+
+        for process in self.processes(vmi, registers)? {
+            let comm = match self.process_filename(vmi, registers, process.object) {
+                Ok(comm) => comm,
+                Err(err) => {
+                    tracing::debug!(?err, pid = %process.id, "failed to read comm");
+                    continue;
+                }
+            };
+
+            if comm.as_bytes() == name {
+                return Ok(Some(process));
+            }
+        }
+
+        Ok(None)
+    }
+
     /// Gets the process flags from a `task_struct`.
     ///
     /// Process flags in Linux include information about the process state,
@@ -227,6 +328,82 @@ where
         vmi.read_u32(registers.address_context(process.0 + __task_struct.flags.offset))
     }
 
+    /// Gets the capabilities of a process.
+    ///
+    /// Reads the effective and permitted capability sets from the
+    /// subjective credentials of the task.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return { task->cred->cap_effective, task->cred->cap_permitted };
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// Before Linux 6.3, `kernel_cap_t` was an array of two 32-bit words.
+    /// Its layout in memory is the same as the single 64-bit word used
+    /// since then, so both are read the same way.
+    pub fn process_capabilities(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<LinuxTaskCapabilities, VmiError> {
+        let __task_struct = &self.offsets.task_struct;
+        let __cred = &self.offsets.cred;
+
+        let cred = vmi.read_va(
+            registers.address_context(process.0 + __task_struct.cred.offset),
+            registers.address_width(),
+        )?;
+
+        if cred.is_null() {
+            return Err(VmiError::Other("Task has no credentials"));
+        }
+
+        let effective =
+            vmi.read_u64(registers.address_context(cred + __cred.cap_effective.offset))?;
+        let permitted =
+            vmi.read_u64(registers.address_context(cred + __cred.cap_permitted.offset))?;
+
+        Ok(LinuxTaskCapabilities {
+            effective: LinuxCapabilities::from_bits_retain(effective),
+            permitted: LinuxCapabilities::from_bits_retain(permitted),
+        })
+    }
+
+    /// Gets the seccomp mode of a process.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the kernel was built without
+    /// `CONFIG_SECCOMP`.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return task->seccomp.mode;
+    /// ```
+    pub fn process_seccomp_mode(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<LinuxSeccompMode, VmiError> {
+        let __task_struct = &self.offsets.task_struct;
+        let __seccomp = &self.offsets.seccomp;
+
+        let mode = match &__seccomp.mode {
+            Some(mode) => mode,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let result = vmi.read_u32(
+            registers.address_context(process.0 + __task_struct.seccomp.offset + mode.offset),
+        )?;
+
+        Ok(LinuxSeccompMode::from(result))
+    }
+
     /// Gets the address of `mm_struct` from a `task_struct`.
     ///
     /// The `mm_struct` contains the memory management information for a process.
@@ -457,6 +634,222 @@ where
             kind,
         })
     }
+
+    /// Returns an iterator over the nodes of a red-black tree, in order.
+    ///
+    /// The `root` is the address of the `struct rb_root`, and `node_offset`
+    /// is the offset of the `struct rb_node` within the container
+    /// structure. The iterator yields the addresses of the containers.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// for (node = rb_first(root); node; node = rb_next(node)) {
+    ///     yield (void *)node - node_offset;
+    /// }
+    /// ```
+    pub fn rb_tree_iter<'a>(
+        &'a self,
+        vmi: &'a VmiCore<Driver>,
+        registers: &'a <Driver::Architecture as Architecture>::Registers,
+        root: Va, // struct rb_root*
+        node_offset: u64,
+    ) -> RbTreeIterator<'a, Driver> {
+        RbTreeIterator::new(vmi, registers, &self.offsets, root, node_offset)
+    }
+
+    /// Enumerates the entries of an XArray (or a radix tree).
+    ///
+    /// The `xa` is the address of the `struct xarray` (or `struct
+    /// radix_tree_root`). The callback receives the index and the entry;
+    /// the enumeration stops when it returns `false`.
+    ///
+    /// See [`XArray::enumerate`] for details.
+    pub fn enumerate_xarray(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        xa: Va, // struct xarray*
+        callback: impl FnMut(u64, Va) -> bool,
+    ) -> Result<(), VmiError> {
+        XArray::new(vmi, registers, &self.offsets).enumerate(xa, callback)
+    }
+
+    /// Returns the taint flags of the kernel.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `tainted_mask` symbol is
+    /// not available.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return tainted_mask;
+    /// ```
+    pub fn kernel_taint(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<LinuxTaint, VmiError> {
+        let tainted_mask = match self.symbols.tainted_mask {
+            Some(tainted_mask) => Va(tainted_mask) + self.kaslr_offset(vmi, registers)?,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let result = vmi.read_u64(registers.address_context(tainted_mask))?;
+        Ok(LinuxTaint::from_bits_retain(result))
+    }
+
+    /// Retrieves the messages of the kernel log buffer (`dmesg`).
+    ///
+    /// Each message is formatted as `[seconds.micros] text`, ordered from
+    /// the oldest to the newest.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the profile doesn't describe
+    /// the log buffer of the kernel.
+    ///
+    /// # Implementation Details
+    ///
+    /// The layout of the log buffer is chosen based on the profile:
+    ///
+    /// - Linux 5.10+: the lockless ring buffer referenced by `prb`
+    ///   (`struct printk_ringbuffer`).
+    /// - Linux 3.5 - 5.9: the `struct printk_log` records in `log_buf`
+    ///   (which points to `__log_buf`, unless the buffer was resized with
+    ///   the `log_buf_len=` boot parameter).
+    ///
+    /// The whole buffer is read at once. Messages that are being written
+    /// at the time of the read are skipped.
+    pub fn kernel_log(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<String>, VmiError> {
+        let kaslr_offset = self.kaslr_offset(vmi, registers)?;
+        let address_width = registers.address_width();
+
+        match (&self.printk, &self.symbols) {
+            (Some(PrintkOffsets::Ringbuffer(offsets)), Symbols { prb: Some(prb), .. }) => {
+                let prb = vmi.read_va(
+                    registers.address_context(Va(prb + kaslr_offset)),
+                    address_width,
+                )?;
+
+                printk::read_ringbuffer(vmi, registers, offsets, prb)
+            }
+            (
+                Some(PrintkOffsets::Log(offsets)),
+                Symbols {
+                    log_buf: Some(log_buf),
+                    log_buf_len: Some(log_buf_len),
+                    log_first_idx: Some(log_first_idx),
+                    log_next_idx: Some(log_next_idx),
+                    ..
+                },
+            ) => {
+                let log_buf = vmi.read_va(
+                    registers.address_context(Va(log_buf + kaslr_offset)),
+                    address_width,
+                )?;
+                let log_buf_len =
+                    vmi.read_u32(registers.address_context(Va(log_buf_len + kaslr_offset)))?;
+                let log_first_idx =
+                    vmi.read_u32(registers.address_context(Va(log_first_idx + kaslr_offset)))?;
+                let log_next_idx =
+                    vmi.read_u32(registers.address_context(Va(log_next_idx + kaslr_offset)))?;
+
+                printk::read_log_buf(
+                    vmi,
+                    registers,
+                    offsets,
+                    log_buf,
+                    log_buf_len,
+                    log_first_idx,
+                    log_next_idx,
+                )
+            }
+            _ => Err(VmiError::NotSupported),
+        }
+    }
+
+    /// Retrieves the loaded kernel modules.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `modules` symbol or
+    /// the `struct module` type is not available, e.g., on kernels built
+    /// without `CONFIG_MODULES`.
+    ///
+    /// # Implementation Details
+    ///
+    /// The modules are enumerated by walking the `modules` list, linked
+    /// through the `module.list` field. Modules that fail to be read are
+    /// skipped.
+    ///
+    /// # Notes
+    ///
+    /// A module that has unlinked itself from the list (a common rootkit
+    /// technique) is not returned. Its taint is still reflected in the
+    /// kernel taint flags (see [`kernel_taint`]).
+    ///
+    /// [`kernel_taint`]: Self::kernel_taint
+    pub fn kernel_modules(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<LinuxModule>, VmiError> {
+        let (modules, __module) = match (self.symbols.modules, &self.module) {
+            (Some(modules), Some(offsets)) => (modules, &offsets.module),
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let modules = Va(modules) + self.kaslr_offset(vmi, registers)?;
+
+        let mut result = Vec::new();
+        self.enumerate_list(vmi, registers, modules, |entry| {
+            let module = entry - __module.list.offset;
+
+            match self.kernel_module(vmi, registers, module) {
+                Ok(module) => result.push(module),
+                Err(err) => tracing::warn!(?err, ?module, "Failed to read module"),
+            }
+
+            true
+        })?;
+
+        Ok(result)
+    }
+
+    /// Reads a `struct module`.
+    fn kernel_module(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        module: Va,
+    ) -> Result<LinuxModule, VmiError> {
+        let __module = match &self.module {
+            Some(offsets) => &offsets.module,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let mut name = vec![0u8; __module.name.size as usize];
+        vmi.read(
+            registers.address_context(module + __module.name.offset),
+            &mut name,
+        )?;
+
+        if let Some(length) = memchr::memchr(0, &name) {
+            name.truncate(length);
+        }
+
+        let taints = match __module.taints {
+            Some(taints) => vmi.read_u64(registers.address_context(module + taints.offset))?,
+            None => 0,
+        };
+
+        Ok(LinuxModule {
+            module,
+            name: String::from_utf8_lossy(&name).into(),
+            taints: LinuxTaint::from_bits_retain(taints),
+        })
+    }
 }
 
 #[allow(non_snake_case, unused_variables)]
@@ -638,7 +1031,10 @@ where
         registers: &<<Driver as VmiDriver>::Architecture as Architecture>::Registers,
         process: ProcessObject,
     ) -> Result<Pa, VmiError> {
-        unimplemented!()
+        match self.process_pgd(vmi, registers, process)? {
+            Some(pgd) => Ok(pgd),
+            None => Err(VmiError::Other("Process has no address space")),
+        }
     }
 
     fn process_user_translation_root(
@@ -647,7 +1043,9 @@ where
         registers: &<<Driver as VmiDriver>::Architecture as Architecture>::Registers,
         process: ProcessObject,
     ) -> Result<Pa, VmiError> {
-        unimplemented!()
+        // With KPTI, the user PGD is the page following the kernel one, but
+        // whether KPTI is enabled is not detected yet.
+        Err(VmiError::NotSupported)
     }
 
     fn process_filename(
@@ -656,9 +1054,14 @@ where
         registers: &<Driver::Architecture as Architecture>::Registers,
         process: ProcessObject,
     ) -> Result<String, VmiError> {
-        let task_struct_comm_offset = 0xBC0;
+        let __task_struct = &self.offsets.task_struct;
 
-        vmi.read_string(registers.address_context(process.0 + task_struct_comm_offset))
+        // The name is truncated by the kernel to `TASK_COMM_LEN - 1`
+        // characters.
+        vmi.read_string_limited(
+            registers.address_context(process.0 + __task_struct.comm.offset),
+            TASK_COMM_LEN,
+        )
     }
 
     fn process_image_base(
@@ -702,6 +1105,55 @@ where
         Ok(result)
     }
 
+    fn process_modules(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<OsModule>, VmiError> {
+        //
+        // A shared object is mapped as several VMAs (headers, code, data),
+        // each with different permissions. Only files with at least one
+        // executable mapping are considered modules, which excludes mapped
+        // data files.
+        //
+
+        let mut result = Vec::<(String, Va, Va, bool)>::new();
+
+        for region in self.process_regions(vmi, registers, process)? {
+            let path = match region.kind {
+                OsRegionKind::Mapped(OsMapped {
+                    path: Ok(Some(path)),
+                }) => path,
+                _ => continue,
+            };
+
+            let executable = region.protection.contains(MemoryAccess::X);
+
+            match result.iter_mut().find(|(p, ..)| *p == path) {
+                Some((_, start, end, exec)) => {
+                    *start = (*start).min(region.start);
+                    *end = (*end).max(region.end);
+                    *exec |= executable;
+                }
+                None => result.push((path, region.start, region.end, executable)),
+            }
+        }
+
+        Ok(result
+            .into_iter()
+            .filter(|(_, _, _, executable)| *executable)
+            .map(|(path, start, end, _)| OsModule {
+                base_address: start,
+                size: end.0 - start.0,
+                name: match path.rsplit_once('/') {
+                    Some((_, name)) => name.to_owned(),
+                    None => path,
+                },
+            })
+            .collect())
+    }
+
     fn process_address_is_valid(
         &self,
         vmi: &VmiCore<Driver>,
@@ -740,6 +1192,12 @@ where
         unimplemented!()
     }
 
+    /// Retrieves a system call argument according to the Linux system call
+    /// ABI.
+    ///
+    /// On AMD64, arguments `0..=5` are taken from `rdi`, `rsi`, `rdx`,
+    /// `r10`, `r8` and `r9`. Linux system calls take at most six arguments,
+    /// so any other index returns [`VmiError::OutOfBounds`].
     fn syscall_argument(
         &self,
         vmi: &VmiCore<Driver>,
@@ -805,11 +1263,17 @@ where
 
     fn enumerate_tree(
         &self,
-        _vmi: &VmiCore<Driver>,
-        _registers: &<Driver::Architecture as Architecture>::Registers,
-        _root: Va,
-        _callback: impl FnMut(Va) -> bool,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        root: Va,
+        mut callback: impl FnMut(Va) -> bool,
     ) -> Result<(), VmiError> {
-        unimplemented!()
+        for node in self.rb_tree_iter(vmi, registers, root, 0) {
+            if !callback(node?) {
+                break;
+            }
+        }
+
+        Ok(())
     }
 }
@@ -15,7 +15,10 @@
 #![allow(dead_code)]
 use vmi_core::{Architecture, Registers as _, Va, VmiCore, VmiDriver, VmiError};
 
-use crate::Offsets;
+use crate::{
+    xarray::{xa_is_internal, xa_is_node, xa_is_value, xa_is_zero, xa_to_internal, xa_to_value},
+    Offsets,
+};
 
 /// Represents different node types in a Maple Tree.
 #[derive(Debug)]
@@ -33,73 +36,6 @@ enum MapleType {
     Arange64,
 }
 
-/// Create an internal entry.
-/// @v: Value to turn into an internal entry.
-///
-/// Internal entries are used for a number of purposes.  Entries 0-255 are
-/// used for sibling entries (only 0-62 are used by the current code).  256
-/// is used for the retry entry.  257 is used for the reserved / zero entry.
-/// Negative internal entries are used to represent errnos.  Node pointers
-/// are also tagged as internal entries in some situations.
-///
-/// Context: Any context.
-/// Return: An XArray internal entry corresponding to this value.
-const fn xa_mk_internal(v: u64) -> Va {
-    Va::new((v << 2) | 2)
-}
-
-/// Extract the value from an internal entry.
-/// @entry: XArray entry.
-///
-/// Context: Any context.
-/// Return: The value which was stored in the internal entry.
-const fn xa_to_internal(entry: Va) -> u64 {
-    entry.0 >> 2
-}
-
-/// Is the entry an internal entry?
-/// @entry: XArray entry.
-///
-/// Context: Any context.
-/// Return: %true if the entry is an internal entry.
-const fn xa_is_internal(entry: Va) -> bool {
-    (entry.0 & 3) == 2
-}
-
-/// Is the entry a zero entry?
-/// @entry: Entry retrieved from the XArray
-///
-/// The normal API will return NULL as the contents of a slot containing
-/// a zero entry.  You can only see zero entries by using the advanced API.
-///
-/// Return: %true if the entry is a zero entry.
-const fn xa_is_zero(entry: Va) -> bool {
-    const XA_ZERO_ENTRY: Va = xa_mk_internal(257);
-    entry.0 == XA_ZERO_ENTRY.0
-}
-
-/// Get value stored in an XArray entry.
-/// @entry: XArray entry.
-///
-/// Context: Any context.
-/// Return: The value stored in the XArray entry.
-const fn xa_to_value(entry: Va) -> u64 {
-    entry.0 >> 1
-}
-
-/// Determine if an entry is a value.
-/// @entry: XArray entry.
-///
-/// Context: Any context.
-/// Return: True if the entry is a value, false if it is a pointer.
-const fn xa_is_value(entry: Va) -> bool {
-    (entry.0 & 1) == 1
-}
-
-const fn xa_is_node(entry: Va) -> bool {
-    xa_is_internal(entry) && entry.0 > 4096
-}
-
 const fn mt_flags_height(ma_flags: u32) -> u32 {
     const MT_FLAGS_HEIGHT_OFFSET: u32 = 0x02;
     const MT_FLAGS_HEIGHT_MASK: u32 = 0x7C;
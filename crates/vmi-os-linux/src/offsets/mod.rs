@@ -2,6 +2,9 @@
 
 use isr_macros::{offsets, symbols, Field};
 
+mod printk;
+pub use self::printk::{PrintkLogOffsets, PrintkOffsets, PrintkRingbufferOffsets};
+
 symbols! {
     #[derive(Debug)]
     pub struct Symbols {
@@ -9,6 +12,17 @@ symbols! {
         init_task: u64,
         entry_SYSCALL_64: u64,
         pcpu_hot: u64,
+        modules: Option<u64>,
+        tainted_mask: Option<u64>,
+
+        // Linux 5.10+
+        prb: Option<u64>,           // struct printk_ringbuffer *prb;
+
+        // Linux 3.5 - 5.9
+        log_buf: Option<u64>,       // char *log_buf;
+        log_buf_len: Option<u64>,   // u32 log_buf_len;
+        log_first_idx: Option<u64>, // u32 log_first_idx;
+        log_next_idx: Option<u64>,  // u32 log_next_idx;
 
         __bad_area_nosemaphore: u64,
     }
@@ -60,6 +74,18 @@ offsets! {
             tgid: Field,
             comm: Field,
             fs: Field,
+            cred: Field,            // const struct cred __rcu *cred;
+            seccomp: Field,         // struct seccomp seccomp;
+        }
+
+        struct cred {
+            cap_permitted: Field,   // kernel_cap_t cap_permitted;
+            cap_effective: Field,   // kernel_cap_t cap_effective;
+        }
+
+        // Empty without CONFIG_SECCOMP.
+        struct seccomp {
+            mode: Option<Field>,    // int mode;
         }
 
         struct dentry {
@@ -85,6 +111,25 @@ offsets! {
             len: Field,
         }
 
+        struct rb_root {
+            rb_node: Field,             // struct rb_node *rb_node;
+        }
+
+        struct rb_node {
+            __rb_parent_color: Field,   // unsigned long  __rb_parent_color;
+            rb_right: Field,            // struct rb_node *rb_right;
+            rb_left: Field,             // struct rb_node *rb_left;
+        }
+
+        struct xarray {
+            xa_head: Field,             // void __rcu *xa_head;
+        }
+
+        struct xa_node {
+            shift: Field,               // unsigned char shift;
+            slots: Field,               // void __rcu *slots[XA_CHUNK_SIZE];
+        }
+
         struct maple_tree {
             ma_flags: Field, // unsigned int ma_flags;
             ma_root: Field,  // void __rcu *ma_root;
@@ -108,3 +153,17 @@ offsets! {
         }
     }
 }
+
+offsets! {
+    /// Offsets of loadable kernel modules.
+    ///
+    /// Not present on kernels built without `CONFIG_MODULES`.
+    #[derive(Debug)]
+    pub struct ModuleOffsets {
+        struct module {
+            list: Field,            // struct list_head list;
+            name: Field,            // char name[MODULE_NAME_LEN];
+            taints: Option<Field>,  // unsigned long taints;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use isr_core::Profile;
+use isr_macros::{offsets, Field};
+
+offsets! {
+    /// Offsets of the lockless printk ring buffer (Linux 5.10+).
+    #[derive(Debug)]
+    pub struct PrintkRingbufferOffsets {
+        struct printk_ringbuffer {
+            desc_ring: Field,       // struct prb_desc_ring desc_ring;
+            text_data_ring: Field,  // struct prb_data_ring text_data_ring;
+        }
+
+        struct prb_desc_ring {
+            count_bits: Field,      // unsigned int count_bits;
+            descs: Field,           // struct prb_desc *descs;
+            infos: Field,           // struct printk_info *infos;
+            head_id: Field,         // atomic_long_t head_id;
+            tail_id: Field,         // atomic_long_t tail_id;
+        }
+
+        struct prb_data_ring {
+            size_bits: Field,       // unsigned int size_bits;
+            data: Field,            // char *data;
+        }
+
+        struct prb_desc {
+            state_var: Field,       // atomic_long_t state_var;
+            text_blk_lpos: Field,   // struct prb_data_blk_lpos text_blk_lpos;
+        }
+
+        struct prb_data_blk_lpos {
+            begin: Field,           // unsigned long begin;
+            next: Field,            // unsigned long next;
+        }
+
+        struct printk_info {
+            seq: Field,             // u64 seq;
+            ts_nsec: Field,         // u64 ts_nsec;
+            text_len: Field,        // u16 text_len;
+        }
+    }
+}
+
+offsets! {
+    /// Offsets of the record-based log buffer (Linux 3.5 - 5.9).
+    #[derive(Debug)]
+    pub struct PrintkLogOffsets {
+        struct printk_log {
+            ts_nsec: Field,         // u64 ts_nsec;
+            len: Field,             // u16 len;
+            text_len: Field,        // u16 text_len;
+        }
+    }
+}
+
+/// Offsets of the kernel log buffer.
+///
+/// The layout of the buffer was replaced by a lockless ring buffer in
+/// Linux 5.10.
+#[derive(Debug)]
+pub enum PrintkOffsets {
+    /// The lockless ring buffer (`struct printk_ringbuffer`).
+    Ringbuffer(Box<PrintkRingbufferOffsets>),
+
+    /// The record-based log buffer (`struct printk_log`).
+    Log(PrintkLogOffsets),
+}
+
+impl PrintkOffsets {
+    /// Creates a new `PrintkOffsets` instance, or returns `None` if the
+    /// profile describes neither layout.
+    pub fn new(profile: &Profile) -> Option<Self> {
+        if let Ok(offsets) = PrintkRingbufferOffsets::new(profile) {
+            Some(Self::Ringbuffer(Box::new(offsets)))
+        }
+        else if let Ok(offsets) = PrintkLogOffsets::new(profile) {
+            Some(Self::Log(offsets))
+        }
+        else {
+            None
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Decoding of the kernel log buffer (`dmesg`).
+//!
+//! Two layouts of the buffer are supported:
+//!
+//! - Linux 5.10+ stores the messages in a lockless ring buffer
+//!   (`struct printk_ringbuffer`), referenced by the `prb` pointer.
+//!   Record metadata lives in the descriptor ring (`descs` and `infos`
+//!   arrays), while the text is stored in blocks of a separate data ring.
+//! - Linux 3.5 - 5.9 store variable-length `struct printk_log` records
+//!   followed by their text in a single buffer, referenced by the
+//!   `log_buf` pointer and delimited by `log_first_idx` and `log_next_idx`.
+//!
+//! # References
+//!
+//! - [Linux Kernel Source - printk_ringbuffer.c](https://elixir.bootlin.com/linux/v6.10.5/source/kernel/printk/printk_ringbuffer.c)
+//! - [Linux Kernel Source - printk_ringbuffer.h](https://elixir.bootlin.com/linux/v6.10.5/source/kernel/printk/printk_ringbuffer.h)
+//! - [Linux Kernel Source - printk.c (5.9)](https://elixir.bootlin.com/linux/v5.9.16/source/kernel/printk/printk.c)
+
+use isr_macros::Field;
+use vmi_core::{Architecture, Registers as _, Va, VmiCore, VmiDriver, VmiError};
+
+use crate::{PrintkLogOffsets, PrintkRingbufferOffsets};
+
+/// `desc_committed`: the record is complete, but may still be extended.
+const DESC_COMMITTED: u64 = 1;
+
+/// `desc_finalized`: the record is complete and can't be modified.
+const DESC_FINALIZED: u64 = 2;
+
+/// `LOG_BUF_LEN_MAX`: the largest size of the log buffer.
+///
+/// `CONFIG_LOG_BUF_SHIFT` only sets the default size, which can be raised
+/// up to this limit by the `log_buf_len=` boot parameter.
+const LOG_BUF_LEN_MAX: u64 = 1 << 31;
+
+/// `PRB_AVGBITS`: the number of bits of the average size of a record.
+///
+/// The descriptor ring has one descriptor for every `1 << PRB_AVGBITS`
+/// bytes of the text data ring, i.e., `count_bits` is `size_bits` minus
+/// `PRB_AVGBITS`.
+const PRB_AVGBITS: u64 = 5;
+
+/// Reads the ring buffer of Linux 5.10+.
+///
+/// The records are read from the oldest (`tail_id`) to the newest
+/// (`head_id`) descriptor. Descriptors that are reserved (being written)
+/// or reusable (already recycled) are skipped, as are records without
+/// text.
+pub(crate) fn read_ringbuffer<Driver>(
+    vmi: &VmiCore<Driver>,
+    registers: &<Driver::Architecture as Architecture>::Registers,
+    offsets: &PrintkRingbufferOffsets,
+    prb: Va, // struct printk_ringbuffer*
+) -> Result<Vec<String>, VmiError>
+where
+    Driver: VmiDriver,
+{
+    let __printk_ringbuffer = &offsets.printk_ringbuffer;
+    let __prb_desc_ring = &offsets.prb_desc_ring;
+    let __prb_data_ring = &offsets.prb_data_ring;
+    let __prb_desc = &offsets.prb_desc;
+    let __prb_data_blk_lpos = &offsets.prb_data_blk_lpos;
+    let __printk_info = &offsets.printk_info;
+
+    let address_width = registers.address_width();
+
+    //
+    // The two most significant bits of `state_var` hold the state of the
+    // descriptor, the remaining bits hold its ID.
+    //
+
+    let flags_shift = address_width as u64 * 8 - 2;
+    let id_mask = (1u64 << flags_shift) - 1;
+
+    //
+    // Read the descriptor ring.
+    //
+
+    let desc_ring = prb + __printk_ringbuffer.desc_ring.offset;
+    let count_bits = vmi
+        .read_u32(registers.address_context(desc_ring + __prb_desc_ring.count_bits.offset))?
+        as u64;
+    let descs = vmi.read_va(
+        registers.address_context(desc_ring + __prb_desc_ring.descs.offset),
+        address_width,
+    )?;
+    let infos = vmi.read_va(
+        registers.address_context(desc_ring + __prb_desc_ring.infos.offset),
+        address_width,
+    )?;
+    let head_id = vmi.read_address(
+        registers.address_context(desc_ring + __prb_desc_ring.head_id.offset),
+        address_width,
+    )? & id_mask;
+    let tail_id = vmi.read_address(
+        registers.address_context(desc_ring + __prb_desc_ring.tail_id.offset),
+        address_width,
+    )? & id_mask;
+
+    //
+    // Read the text data ring.
+    //
+
+    let data_ring = prb + __printk_ringbuffer.text_data_ring.offset;
+    let size_bits = vmi
+        .read_u32(registers.address_context(data_ring + __prb_data_ring.size_bits.offset))?
+        as u64;
+    let data = vmi.read_va(
+        registers.address_context(data_ring + __prb_data_ring.data.offset),
+        address_width,
+    )?;
+
+    if 1u64
+        .checked_shl(size_bits as u32)
+        .map_or(true, |size| size > LOG_BUF_LEN_MAX)
+        || count_bits + PRB_AVGBITS > size_bits
+    {
+        return Err(VmiError::Other("Invalid printk ring buffer"));
+    }
+
+    let count = 1u64 << count_bits;
+    let size = 1u64 << size_bits;
+
+    let mut desc_buffer = vec![0u8; count as usize * __prb_desc.len()];
+    let mut info_buffer = vec![0u8; count as usize * __printk_info.len()];
+    let mut data_buffer = vec![0u8; size as usize];
+    vmi.read(registers.address_context(descs), &mut desc_buffer)?;
+    vmi.read(registers.address_context(infos), &mut info_buffer)?;
+    vmi.read(registers.address_context(data), &mut data_buffer)?;
+
+    //
+    // Walk the descriptors from the oldest to the newest.
+    //
+
+    let mut result = Vec::new();
+    let mut id = tail_id;
+
+    for _ in 0..count {
+        let index = (id & (count - 1)) as usize;
+        let desc = &desc_buffer[index * __prb_desc.len()..][..__prb_desc.len()];
+        let info = &info_buffer[index * __printk_info.len()..][..__printk_info.len()];
+
+        let state_var = field_value(desc, __prb_desc.state_var);
+        let state = state_var >> flags_shift;
+
+        if state_var & id_mask == id && matches!(state, DESC_COMMITTED | DESC_FINALIZED) {
+            let text_blk_lpos = &desc[__prb_desc.text_blk_lpos.offset as usize..];
+            let begin = field_value(text_blk_lpos, __prb_data_blk_lpos.begin);
+            let next = field_value(text_blk_lpos, __prb_data_blk_lpos.next);
+
+            if let Some(text) = data_block(&data_buffer, size_bits, begin, next, address_width) {
+                let text_len = field_value(info, __printk_info.text_len) as usize;
+                let ts_nsec = field_value(info, __printk_info.ts_nsec);
+                result.push(format_record(ts_nsec, &text[..text.len().min(text_len)]));
+            }
+        }
+
+        if id == head_id {
+            break;
+        }
+
+        id = (id + 1) & id_mask;
+    }
+
+    Ok(result)
+}
+
+/// Reads the log buffer of Linux 3.5 - 5.9.
+///
+/// The records are read from `log_first_idx` until `log_next_idx` is
+/// reached. A record with zero length marks the end of the buffer, the
+/// next record is then at the beginning of the buffer.
+pub(crate) fn read_log_buf<Driver>(
+    vmi: &VmiCore<Driver>,
+    registers: &<Driver::Architecture as Architecture>::Registers,
+    offsets: &PrintkLogOffsets,
+    log_buf: Va, // char*
+    log_buf_len: u32,
+    log_first_idx: u32,
+    log_next_idx: u32,
+) -> Result<Vec<String>, VmiError>
+where
+    Driver: VmiDriver,
+{
+    let __printk_log = &offsets.printk_log;
+
+    if log_buf_len as u64 > LOG_BUF_LEN_MAX {
+        return Err(VmiError::Other("Invalid printk log buffer"));
+    }
+
+    let mut buffer = vec![0u8; log_buf_len as usize];
+    vmi.read(registers.address_context(log_buf), &mut buffer)?;
+
+    let mut result = Vec::new();
+    let mut index = log_first_idx as usize;
+    let mut wrapped = false;
+
+    while index != log_next_idx as usize {
+        let header = match buffer.get(index..index + __printk_log.len()) {
+            Some(header) => header,
+            None => break,
+        };
+
+        let len = field_value(header, __printk_log.len) as usize;
+        if len == 0 {
+            //
+            // The rest of the buffer is unused, continue at the beginning.
+            //
+
+            if wrapped {
+                break;
+            }
+
+            wrapped = true;
+            index = 0;
+            continue;
+        }
+
+        let text_len = field_value(header, __printk_log.text_len) as usize;
+        let ts_nsec = field_value(header, __printk_log.ts_nsec);
+
+        let text_start = index + __printk_log.len();
+        let text = match buffer.get(text_start..text_start + text_len) {
+            Some(text) => text,
+            None => break,
+        };
+
+        result.push(format_record(ts_nsec, text));
+        index += len;
+
+        if index >= buffer.len() {
+            if wrapped {
+                break;
+            }
+
+            wrapped = true;
+            index = 0;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns the contents of a data block of the text data ring
+/// (`get_data`).
+///
+/// Each block starts with the ID of its descriptor, which is not part of
+/// the returned contents. A block that doesn't fit at the end of the ring
+/// is stored at its beginning instead.
+fn data_block(data: &[u8], size_bits: u64, begin: u64, next: u64, id_size: usize) -> Option<&[u8]> {
+    let size = 1u64 << size_bits;
+    let wraps = |lpos: u64| lpos >> size_bits;
+    let index = |lpos: u64| (lpos & (size - 1)) as usize;
+
+    //
+    // Data-less records (e.g., empty lines) have the lowest bit set.
+    //
+
+    if begin & 1 != 0 && next & 1 != 0 {
+        return None;
+    }
+
+    let (start, length) = if wraps(begin) == wraps(next) && begin < next {
+        (index(begin), (next - begin) as usize)
+    }
+    else if wraps(begin.wrapping_add(size)) == wraps(next) {
+        (0, index(next))
+    }
+    else {
+        return None;
+    };
+
+    data.get(start + id_size..start + length)
+}
+
+/// Formats a record the way `dmesg` does, i.e., `[seconds.micros] text`.
+fn format_record(ts_nsec: u64, text: &[u8]) -> String {
+    let seconds = ts_nsec / 1_000_000_000;
+    let micros = (ts_nsec % 1_000_000_000) / 1_000;
+
+    format!(
+        "[{seconds:5}.{micros:06}] {}",
+        String::from_utf8_lossy(text).trim_end_matches('\n')
+    )
+}
+
+/// Extracts the value of a field of at most 8 bytes from a buffer holding
+/// the structure.
+fn field_value(buffer: &[u8], field: Field) -> u64 {
+    let offset = field.offset as usize;
+    let size = (field.size as usize).min(8);
+
+    let mut bytes = [0u8; 8];
+    if let Some(value) = buffer.get(offset..offset + size) {
+        bytes[..size].copy_from_slice(value);
+    }
+
+    u64::from_le_bytes(bytes)
+}
@@ -0,0 +1,216 @@
+//! A red-black tree iterator based on the Linux kernel's data structure.
+//!
+//! Red-black trees are used throughout the Linux kernel, e.g., for the
+//! VMA tree of older kernels, timers, the CFS run queue or the epoll
+//! interest list. The nodes (`struct rb_node`) are embedded in the
+//! container structures.
+//!
+//! # References
+//!
+//! - [Linux Kernel Source - rbtree.c](https://elixir.bootlin.com/linux/v6.10.5/source/lib/rbtree.c)
+//! - [Linux Kernel Source - rbtree_types.h](https://elixir.bootlin.com/linux/v6.10.5/source/include/linux/rbtree_types.h)
+
+use vmi_core::{Architecture, Registers as _, Va, VmiCore, VmiDriver, VmiError};
+
+use crate::Offsets;
+
+/// Extracts the parent pointer from the `__rb_parent_color` field.
+///
+/// The lowest bit holds the color of the node.
+const fn rb_parent(parent_color: u64) -> Va {
+    Va::new(parent_color & !3)
+}
+
+/// An iterator over the nodes of a red-black tree, in order.
+///
+/// Yields the address of the container of each node, i.e., the address
+/// of the `struct rb_node` minus the offset of the node within its
+/// container.
+///
+/// The traversal follows the parent pointers (the same way as `rb_next`
+/// does), so no additional state needs to be kept. It ends after
+/// [`MAX_LENGTH`] nodes, and walks up or down the tree at most
+/// [`MAX_DEPTH`] levels at a time, so that corrupted or concurrently
+/// modified trees can't cause an endless loop. If a node can't be read,
+/// or the tree is deeper than [`MAX_DEPTH`], the error is yielded and the
+/// iteration ends.
+///
+/// Created by [`LinuxOs::rb_tree_iter`].
+///
+/// [`MAX_LENGTH`]: Self::MAX_LENGTH
+/// [`MAX_DEPTH`]: Self::MAX_DEPTH
+/// [`LinuxOs::rb_tree_iter`]: crate::LinuxOs::rb_tree_iter
+pub struct RbTreeIterator<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// The VMI core.
+    vmi: &'a VmiCore<Driver>,
+
+    /// The CPU register state.
+    regs: &'a <Driver::Architecture as Architecture>::Registers,
+
+    /// Offsets for the red-black tree data structure.
+    offsets: &'a Offsets,
+
+    /// The `struct rb_root` of the tree.
+    root: Va,
+
+    /// The offset of the `struct rb_node` within the container.
+    node_offset: u64,
+
+    /// The next node to be yielded, or `None` before the first node was
+    /// looked up.
+    next: Option<Va>,
+
+    /// The number of yielded nodes.
+    count: usize,
+
+    /// Whether the iteration has ended.
+    done: bool,
+}
+
+impl<'a, Driver> RbTreeIterator<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// The maximum number of nodes yielded by the iterator.
+    pub const MAX_LENGTH: usize = 0x100000;
+
+    /// The maximum depth of the tree.
+    ///
+    /// The depth of a red-black tree is at most `2 * log2(n + 1)`, which is
+    /// 42 for a tree of [`MAX_LENGTH`] nodes.
+    ///
+    /// [`MAX_LENGTH`]: Self::MAX_LENGTH
+    pub const MAX_DEPTH: usize = 64;
+
+    /// Creates a new iterator over the tree with the given root.
+    pub(crate) fn new(
+        vmi: &'a VmiCore<Driver>,
+        regs: &'a <Driver::Architecture as Architecture>::Registers,
+        offsets: &'a Offsets,
+        root: Va, // struct rb_root*
+        node_offset: u64,
+    ) -> Self {
+        Self {
+            vmi,
+            regs,
+            offsets,
+            root,
+            node_offset,
+            next: None,
+            count: 0,
+            done: false,
+        }
+    }
+
+    /// Returns the first node of the tree (`rb_first`).
+    fn first(&self) -> Result<Va, VmiError> {
+        let __rb_root = &self.offsets.rb_root;
+
+        let node = self.read_va(self.root + __rb_root.rb_node.offset)?;
+        self.leftmost(node)
+    }
+
+    /// Returns the leftmost node of the subtree.
+    fn leftmost(&self, mut node: Va) -> Result<Va, VmiError> {
+        let __rb_node = &self.offsets.rb_node;
+
+        if node.is_null() {
+            return Ok(node);
+        }
+
+        for _ in 0..Self::MAX_DEPTH {
+            let left = self.read_va(node + __rb_node.rb_left.offset)?;
+            if left.is_null() {
+                return Ok(node);
+            }
+
+            node = left;
+        }
+
+        Err(VmiError::Other("Red-black tree too deep"))
+    }
+
+    /// Returns the node that follows the given node (`rb_next`).
+    fn successor(&self, mut node: Va) -> Result<Va, VmiError> {
+        let __rb_node = &self.offsets.rb_node;
+
+        //
+        // If the node has a right child, the successor is the leftmost
+        // node of the right subtree.
+        //
+
+        let right = self.read_va(node + __rb_node.rb_right.offset)?;
+        if !right.is_null() {
+            return self.leftmost(right);
+        }
+
+        //
+        // Otherwise, go up the tree until we come from a left child.
+        //
+
+        for _ in 0..Self::MAX_DEPTH {
+            let parent = rb_parent(self.read_u64(node + __rb_node.__rb_parent_color.offset)?);
+            if parent.is_null() {
+                return Ok(parent);
+            }
+
+            if self.read_va(parent + __rb_node.rb_right.offset)? != node {
+                return Ok(parent);
+            }
+
+            node = parent;
+        }
+
+        Err(VmiError::Other("Red-black tree too deep"))
+    }
+
+    /// Reads a 64-bit value from virtual memory.
+    fn read_u64(&self, va: Va) -> Result<u64, VmiError> {
+        self.vmi.read_u64((va, self.regs.translation_root(va)))
+    }
+
+    /// Reads a virtual address from virtual memory.
+    fn read_va(&self, va: Va) -> Result<Va, VmiError> {
+        self.vmi.read_va(
+            (va, self.regs.translation_root(va)),
+            self.regs.address_width(),
+        )
+    }
+}
+
+impl<Driver> Iterator for RbTreeIterator<'_, Driver>
+where
+    Driver: VmiDriver,
+{
+    type Item = Result<Va, VmiError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.count >= Self::MAX_LENGTH {
+            return None;
+        }
+
+        let node = match self.next {
+            Some(node) => self.successor(node),
+            None => self.first(),
+        };
+
+        match node {
+            Ok(node) if node.is_null() => {
+                self.done = true;
+                None
+            }
+            Ok(node) => {
+                self.next = Some(node);
+                self.count += 1;
+                Some(Ok(node - self.node_offset))
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
+        }
+    }
+}
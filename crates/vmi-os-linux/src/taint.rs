@@ -0,0 +1,104 @@
+use std::fmt;
+
+bitflags::bitflags! {
+    /// Taint flags of the kernel or of a module (`TAINT_*`).
+    ///
+    /// The kernel taints itself when something happens that might make
+    /// its behavior untrustworthy, such as loading a proprietary,
+    /// out-of-tree or unsigned module. The flags are never cleared.
+    ///
+    /// The [`Display`] implementation prints the flags in the format of
+    /// `/proc/sys/kernel/tainted` reporting tools (e.g., `POE`).
+    ///
+    /// [`Display`]: fmt::Display
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct LinuxTaint: u64 {
+        /// `TAINT_PROPRIETARY_MODULE` (`P`): a module with a non-GPL
+        /// license was loaded.
+        const PROPRIETARY_MODULE = 1 << 0;
+
+        /// `TAINT_FORCED_MODULE` (`F`): a module was force-loaded,
+        /// bypassing the version checks.
+        const FORCED_MODULE = 1 << 1;
+
+        /// `TAINT_CPU_OUT_OF_SPEC` (`S`): the kernel runs on a CPU out of
+        /// its specification.
+        const CPU_OUT_OF_SPEC = 1 << 2;
+
+        /// `TAINT_FORCED_RMMOD` (`R`): a module was force-unloaded.
+        const FORCED_RMMOD = 1 << 3;
+
+        /// `TAINT_MACHINE_CHECK` (`M`): a machine check exception occurred.
+        const MACHINE_CHECK = 1 << 4;
+
+        /// `TAINT_BAD_PAGE` (`B`): a bad page was referenced.
+        const BAD_PAGE = 1 << 5;
+
+        /// `TAINT_USER` (`U`): taint was requested by user space.
+        const USER = 1 << 6;
+
+        /// `TAINT_DIE` (`D`): the kernel oopsed.
+        const DIE = 1 << 7;
+
+        /// `TAINT_OVERRIDDEN_ACPI_TABLE` (`A`): an ACPI table was
+        /// overridden.
+        const OVERRIDDEN_ACPI_TABLE = 1 << 8;
+
+        /// `TAINT_WARN` (`W`): the kernel issued a warning.
+        const WARN = 1 << 9;
+
+        /// `TAINT_CRAP` (`C`): a staging driver was loaded.
+        const CRAP = 1 << 10;
+
+        /// `TAINT_FIRMWARE_WORKAROUND` (`I`): a workaround for a firmware
+        /// bug was applied.
+        const FIRMWARE_WORKAROUND = 1 << 11;
+
+        /// `TAINT_OOT_MODULE` (`O`): an out-of-tree module was loaded.
+        const OOT_MODULE = 1 << 12;
+
+        /// `TAINT_UNSIGNED_MODULE` (`E`): an unsigned module was loaded.
+        const UNSIGNED_MODULE = 1 << 13;
+
+        /// `TAINT_SOFTLOCKUP` (`L`): a soft lockup occurred.
+        const SOFTLOCKUP = 1 << 14;
+
+        /// `TAINT_LIVEPATCH` (`K`): the kernel was live patched.
+        const LIVEPATCH = 1 << 15;
+
+        /// `TAINT_AUX` (`X`): reserved for distributions.
+        const AUX = 1 << 16;
+
+        /// `TAINT_RANDSTRUCT` (`T`): the kernel was built with the
+        /// structure randomization plugin.
+        const RANDSTRUCT = 1 << 17;
+
+        /// `TAINT_TEST` (`N`): a test module was loaded.
+        const TEST = 1 << 18;
+    }
+}
+
+impl LinuxTaint {
+    /// Flags that indicate that foreign code was loaded into the kernel,
+    /// bypassing the usual module checks.
+    ///
+    /// Rootkits are commonly loaded as out-of-tree, unsigned or
+    /// force-loaded modules.
+    pub const SUSPICIOUS_MODULE: Self = Self::FORCED_MODULE
+        .union(Self::OOT_MODULE)
+        .union(Self::UNSIGNED_MODULE);
+}
+
+impl fmt::Display for LinuxTaint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const LETTERS: &[u8] = b"PFSRMBUDAWCIOELKXTN";
+
+        for (index, letter) in LETTERS.iter().enumerate() {
+            if self.bits() & (1 << index) != 0 {
+                write!(f, "{}", *letter as char)?;
+            }
+        }
+
+        Ok(())
+    }
+}
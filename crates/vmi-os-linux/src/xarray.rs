@@ -0,0 +1,242 @@
+//! An XArray implementation based on the Linux kernel's data structure.
+//!
+//! The XArray is a radix tree of pointers, indexed by an unsigned long.
+//! Since Linux 4.20, it also backs the radix tree API (`struct
+//! radix_tree_root` is an alias of `struct xarray`), which is used e.g. by
+//! the page cache and the IDR allocator.
+//!
+//! # References
+//!
+//! - [Linux Kernel Source - xarray.c](https://elixir.bootlin.com/linux/v6.10.5/source/lib/xarray.c)
+//! - [Linux Kernel Source - xarray.h](https://elixir.bootlin.com/linux/v6.10.5/source/include/linux/xarray.h)
+//! - [Kernel Documentation - XArray](https://docs.kernel.org/core-api/xarray.html)
+
+use vmi_core::{Architecture, Registers as _, Va, VmiCore, VmiDriver, VmiError};
+
+use crate::Offsets;
+
+/// Create an internal entry.
+/// @v: Value to turn into an internal entry.
+///
+/// Internal entries are used for a number of purposes.  Entries 0-255 are
+/// used for sibling entries (only 0-62 are used by the current code).  256
+/// is used for the retry entry.  257 is used for the reserved / zero entry.
+/// Negative internal entries are used to represent errnos.  Node pointers
+/// are also tagged as internal entries in some situations.
+///
+/// Context: Any context.
+/// Return: An XArray internal entry corresponding to this value.
+pub(crate) const fn xa_mk_internal(v: u64) -> Va {
+    Va::new((v << 2) | 2)
+}
+
+/// Extract the value from an internal entry.
+/// @entry: XArray entry.
+///
+/// Context: Any context.
+/// Return: The value which was stored in the internal entry.
+pub(crate) const fn xa_to_internal(entry: Va) -> u64 {
+    entry.0 >> 2
+}
+
+/// Is the entry an internal entry?
+/// @entry: XArray entry.
+///
+/// Context: Any context.
+/// Return: %true if the entry is an internal entry.
+pub(crate) const fn xa_is_internal(entry: Va) -> bool {
+    (entry.0 & 3) == 2
+}
+
+/// Is the entry a zero entry?
+/// @entry: Entry retrieved from the XArray
+///
+/// The normal API will return NULL as the contents of a slot containing
+/// a zero entry.  You can only see zero entries by using the advanced API.
+///
+/// Return: %true if the entry is a zero entry.
+pub(crate) const fn xa_is_zero(entry: Va) -> bool {
+    const XA_ZERO_ENTRY: Va = xa_mk_internal(257);
+    entry.0 == XA_ZERO_ENTRY.0
+}
+
+/// Get value stored in an XArray entry.
+/// @entry: XArray entry.
+///
+/// Context: Any context.
+/// Return: The value stored in the XArray entry.
+pub(crate) const fn xa_to_value(entry: Va) -> u64 {
+    entry.0 >> 1
+}
+
+/// Determine if an entry is a value.
+/// @entry: XArray entry.
+///
+/// Context: Any context.
+/// Return: True if the entry is a value, false if it is a pointer.
+pub(crate) const fn xa_is_value(entry: Va) -> bool {
+    (entry.0 & 1) == 1
+}
+
+pub(crate) const fn xa_is_node(entry: Va) -> bool {
+    xa_is_internal(entry) && entry.0 > 4096
+}
+
+/// Is the entry a sibling entry?
+/// @entry: Entry retrieved from the XArray
+///
+/// Sibling entries refer to the canonical slot of a multi-index entry.
+const fn xa_is_sibling(entry: Va, chunk_size: u64) -> bool {
+    xa_is_internal(entry) && entry.0 < xa_mk_internal(chunk_size - 1).0
+}
+
+/// Is the entry a retry entry?
+/// @entry: Entry retrieved from the XArray
+const fn xa_is_retry(entry: Va) -> bool {
+    const XA_RETRY_ENTRY: Va = xa_mk_internal(256);
+    entry.0 == XA_RETRY_ENTRY.0
+}
+
+/// Converts a node entry to the address of the `struct xa_node`.
+const fn xa_to_node(entry: Va) -> Va {
+    Va::new(entry.0 - 2)
+}
+
+/// An XArray traversal implementation.
+///
+/// This struct provides methods for traversing Linux kernel XArrays and
+/// radix trees.
+pub struct XArray<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// The VMI core.
+    vmi: &'a VmiCore<Driver>,
+
+    /// The CPU register state.
+    regs: &'a <Driver::Architecture as Architecture>::Registers,
+
+    /// Offsets for the XArray data structure.
+    offsets: &'a Offsets,
+}
+
+impl<'a, Driver> XArray<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// The maximum depth of the tree.
+    ///
+    /// With 6 bits per level, 11 levels cover the 64-bit index space.
+    const MAX_DEPTH: usize = 11;
+
+    /// Creates a new XArray instance.
+    pub fn new(
+        vmi: &'a VmiCore<Driver>,
+        regs: &'a <Driver::Architecture as Architecture>::Registers,
+        offsets: &'a Offsets,
+    ) -> Self {
+        Self { vmi, regs, offsets }
+    }
+
+    /// Enumerates all present entries in the XArray.
+    ///
+    /// Calls the provided callback with the index and the entry, in
+    /// ascending order of indices. Multi-index entries are reported once,
+    /// at their first index. The enumeration stops when the callback
+    /// returns `false`.
+    ///
+    /// Value entries (`xa_mk_value`) are passed as-is; use their lowest
+    /// bit to tell them apart from pointers.
+    pub fn enumerate(
+        &self,
+        xa: Va, // struct xarray*
+        mut callback: impl FnMut(u64, Va) -> bool,
+    ) -> Result<(), VmiError> {
+        let __xarray = &self.offsets.xarray;
+
+        let head = self.read_va(xa + __xarray.xa_head.offset)?;
+
+        if xa_is_node(head) {
+            self.enumerate_node(xa_to_node(head), 0, 0, &mut callback)?;
+        }
+        else if !head.is_null() && !xa_is_internal(head) {
+            callback(0, head);
+        }
+
+        Ok(())
+    }
+
+    /// Enumerates the entries of a `struct xa_node`.
+    ///
+    /// Returns `false` if the enumeration was stopped by the callback.
+    fn enumerate_node(
+        &self,
+        node: Va,
+        base: u64,
+        depth: usize,
+        callback: &mut impl FnMut(u64, Va) -> bool,
+    ) -> Result<bool, VmiError> {
+        let __xa_node = &self.offsets.xa_node;
+
+        if depth >= Self::MAX_DEPTH {
+            tracing::warn!(?node, "XArray is too deep");
+            return Ok(true);
+        }
+
+        let shift = self.read_u8(node + __xa_node.shift.offset)? as u32;
+        let chunk_size = __xa_node.slots.size / self.regs.address_width() as u64;
+
+        // The shift is a multiple of `XA_CHUNK_SHIFT` and never exceeds
+        // `BITS_PER_LONG`.
+        let chunk_shift = chunk_size.trailing_zeros();
+        let bits_per_long = self.regs.address_width() as u32 * 8;
+        if chunk_shift == 0 || shift > bits_per_long || shift % chunk_shift != 0 {
+            return Err(VmiError::CorruptedStruct("xa_node"));
+        }
+
+        for offset in 0..chunk_size {
+            let slot = self.read_va(
+                node + __xa_node.slots.offset + offset * self.regs.address_width() as u64,
+            )?;
+
+            if slot.is_null()
+                || xa_is_sibling(slot, chunk_size)
+                || xa_is_retry(slot)
+                || xa_is_zero(slot)
+            {
+                continue;
+            }
+
+            let index = offset
+                .checked_shl(shift)
+                .and_then(|index| base.checked_add(index))
+                .ok_or(VmiError::CorruptedStruct("xa_node"))?;
+
+            let more = if xa_is_node(slot) {
+                self.enumerate_node(xa_to_node(slot), index, depth + 1, callback)?
+            }
+            else {
+                callback(index, slot)
+            };
+
+            if !more {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Reads an 8-bit value from virtual memory.
+    fn read_u8(&self, va: Va) -> Result<u8, VmiError> {
+        self.vmi.read_u8((va, self.regs.translation_root(va)))
+    }
+
+    /// Reads a virtual address from virtual memory.
+    fn read_va(&self, va: Va) -> Result<Va, VmiError> {
+        self.vmi.read_va(
+            (va, self.regs.translation_root(va)),
+            self.regs.address_width(),
+        )
+    }
+}
@@ -1,31 +1,75 @@
 use object::{FileKind, LittleEndian as LE};
-use vmi_arch_amd64::{Amd64, PageTableEntry, PageTableLevel, Registers};
+use vmi_arch_amd64::{Amd64, Cr3, GpRegisters, PageTableEntry, PageTableLevel, Registers};
 use vmi_core::{
-    os::ProcessObject, Architecture as _, Registers as _, Va, VmiCore, VmiDriver, VmiError,
+    os::{ProcessObject, StructReader, ThreadObject, VmiOs as _},
+    Architecture as _, Gfn, Pa, Registers as _, Va, VcpuId, VmiCore, VmiDriver, VmiError,
 };
+use zerocopy::FromBytes as _;
 
 use super::ArchAdapter;
 use crate::{
-    pe::codeview::codeview_from_pe, PeLite32, PeLite64, WindowsKernelInformation, WindowsOs,
+    offsets::OffsetsCommon, pe::codeview::codeview_from_pe, PeLite32, PeLite64, PeUnwindCode,
+    PeUnwindOperation, WindowsKernelInformation, WindowsOs, WindowsResolvedPage,
+    WindowsStackBounds, WindowsStackFrame,
 };
 
 /// An extension trait for [`PageTableEntry`] that provides access to
 /// Windows-specific fields.
+///
+/// The bit positions of the fields are taken from the `_MMPTE_SOFTWARE`
+/// and `_MMPTE_TRANSITION` structures, because they differ between Windows
+/// versions.
 trait WindowsPageTableEntry {
     /// Returns whether the page is a prototype.
-    fn windows_prototype(self) -> bool;
+    fn windows_prototype(self, offsets: &OffsetsCommon) -> bool;
 
     /// Returns whether the page is in transition.
-    fn windows_transition(self) -> bool;
+    fn windows_transition(self, offsets: &OffsetsCommon) -> bool;
+
+    /// Returns the `PageFrameNumber` field of a transition PTE.
+    fn windows_transition_pfn(self, offsets: &OffsetsCommon) -> Gfn;
+
+    /// Returns the `ProtoAddress` field of a prototype PTE, or the
+    /// `SubsectionAddress` field of a subsection PTE.
+    fn windows_proto_address(self) -> Va;
+
+    /// Returns the `Protection` field of a software PTE.
+    fn windows_protection(self, offsets: &OffsetsCommon) -> u8;
+
+    /// Returns the `PageFileLow` field of a software PTE.
+    fn windows_page_file_low(self, offsets: &OffsetsCommon) -> u8;
+
+    /// Returns the `PageFileHigh` field of a software PTE.
+    fn windows_page_file_high(self, offsets: &OffsetsCommon) -> u64;
 }
 
 impl WindowsPageTableEntry for PageTableEntry {
-    fn windows_prototype(self) -> bool {
-        self.0 >> 10 & 1 != 0
+    fn windows_prototype(self, offsets: &OffsetsCommon) -> bool {
+        offsets._MMPTE_SOFTWARE.Prototype.value_from(self.0) != 0
+    }
+
+    fn windows_transition(self, offsets: &OffsetsCommon) -> bool {
+        offsets._MMPTE_TRANSITION.Transition.value_from(self.0) != 0
+    }
+
+    fn windows_transition_pfn(self, offsets: &OffsetsCommon) -> Gfn {
+        Gfn(offsets._MMPTE_TRANSITION.PageFrameNumber.value_from(self.0))
     }
 
-    fn windows_transition(self) -> bool {
-        self.0 >> 11 & 1 != 0
+    fn windows_proto_address(self) -> Va {
+        Amd64::va_canonical(Va(self.0 >> 16))
+    }
+
+    fn windows_protection(self, offsets: &OffsetsCommon) -> u8 {
+        offsets._MMPTE_SOFTWARE.Protection.value_from(self.0) as u8
+    }
+
+    fn windows_page_file_low(self, offsets: &OffsetsCommon) -> u8 {
+        offsets._MMPTE_SOFTWARE.PageFileLow.value_from(self.0) as u8
+    }
+
+    fn windows_page_file_high(self, offsets: &OffsetsCommon) -> u64 {
+        offsets._MMPTE_SOFTWARE.PageFileHigh.value_from(self.0)
     }
 }
 
@@ -213,7 +257,9 @@ where
                     // The address is valid if the page is present.
                     return Ok(Some(true));
                 }
-                else if entry.entry.windows_transition() && !entry.entry.windows_prototype() {
+                else if entry.entry.windows_transition(&os.offsets.common)
+                    && !entry.entry.windows_prototype(&os.offsets.common)
+                {
                     // The Transition bit being 1 indicates that the page is in a transitional
                     // state. This means the page is not currently in the process's working
                     // set, but it's still resident in physical memory.
@@ -269,6 +315,352 @@ where
         ))
     }
 
+    fn present_pages(
+        _os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        start: Va,
+        end: Va,
+    ) -> Vec<(Va, bool)> {
+        let mut result = Vec::new();
+        collect_present_pages(
+            vmi,
+            root,
+            PageTableLevel::Pml4,
+            start,
+            end,
+            true,
+            &mut result,
+        );
+        result
+    }
+
+    fn process_resolve_paged_va(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        process: ProcessObject,
+        address: Va,
+    ) -> Result<Option<WindowsResolvedPage>, VmiError> {
+        //
+        // The hardware PTE is examined first:
+        // - A valid PTE or a PTE in transition points to a resident page.
+        // - A prototype PTE points to the prototype PTE shared by all
+        //   processes mapping the same section. The special value
+        //   `MiProtoAddressForVad` means that the prototype PTE has to be
+        //   looked up through the VAD.
+        // - A zero PTE (or a PTE with `PageFileHigh` set to
+        //   `MI_PTE_LOOKUP_NEEDED`) has never been accessed and the VAD
+        //   describes what backs it.
+        // - Any other PTE is a software PTE pointing to a paging file, or
+        //   describing a demand-zero page.
+        //
+        // References:
+        // - MiDispatchFault
+        // - MiResolveProtoPteFault
+        // - MiCheckVirtualAddress
+        //
+
+        const MM_PROTO_ADDRESS_FOR_VAD: u64 = 0xffff_ffff_0000;
+        const MI_PTE_LOOKUP_NEEDED: u64 = 0xffff_ffff;
+
+        let root = os.process_translation_root(vmi, registers, process)?;
+        let translation = Amd64::translation(vmi, address, root);
+        if let Some(pa) = translation.pa() {
+            return Ok(Some(WindowsResolvedPage::Physical(pa)));
+        }
+
+        let offset = Amd64::va_offset(address);
+
+        // If a higher-level entry is not present, the page table itself is
+        // not resident and the VAD is the only source of information.
+        let pte = match translation.entries().last() {
+            Some(entry) if entry.level == PageTableLevel::Pt => entry.entry,
+            _ => PageTableEntry(0),
+        };
+
+        let offsets = &os.offsets.common;
+
+        if pte.windows_transition(offsets) && !pte.windows_prototype(offsets) {
+            return Ok(Some(WindowsResolvedPage::Physical(
+                Amd64::pa_from_gfn(pte.windows_transition_pfn(offsets)) + offset,
+            )));
+        }
+
+        if pte.windows_prototype(offsets) && pte.0 >> 16 != MM_PROTO_ADDRESS_FOR_VAD {
+            return resolve_prototype_pte(os, vmi, registers, pte.windows_proto_address(), offset);
+        }
+
+        if !pte.windows_prototype(offsets) && pte.0 != 0 {
+            let software_pte = PageTableEntry(pte.0 ^ os.invalid_pte_mask(vmi, registers)?);
+            if software_pte.windows_page_file_high(offsets) != MI_PTE_LOOKUP_NEEDED {
+                return resolve_software_pte(os, vmi, registers, software_pte, offset);
+            }
+        }
+
+        let vad_va = match os.find_process_vad(vmi, registers, process, address)? {
+            Some(vad_va) => vad_va,
+            None => return Ok(None),
+        };
+
+        let vad = os.vad(vmi, registers, vad_va)?;
+        if vad.private_memory {
+            if vad.mem_commit {
+                return Ok(Some(WindowsResolvedPage::DemandZero));
+            }
+
+            return Ok(None);
+        }
+
+        let MMVAD = &os.offsets.common._MMVAD;
+
+        let first_prototype_pte = vmi.read_va(
+            registers.address_context(vad_va + MMVAD.FirstPrototypePte.offset),
+            registers.address_width(),
+        )?;
+
+        if first_prototype_pte.is_null() {
+            return Ok(None);
+        }
+
+        let index = (address.0 >> Amd64::PAGE_SHIFT) - vad.starting_vpn;
+        let prototype_pte = first_prototype_pte + index * size_of::<PageTableEntry>() as u64;
+        resolve_prototype_pte(os, vmi, registers, prototype_pte, offset)
+    }
+
+    fn thread_saved_context(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        thread: ThreadObject,
+    ) -> Result<Registers, VmiError> {
+        let KTHREAD = &os.offsets.common._KTHREAD;
+        let KPROCESS = &os.offsets.common._KPROCESS;
+        let KSWITCH_FRAME = &os.offsets.common._KSWITCH_FRAME;
+
+        //
+        // The kernel stack of a thread that is not running is only accessible
+        // through the kernel address space. With KVA shadowing enabled, the
+        // current translation root might be the user one, so use the kernel
+        // translation root of the thread's process instead.
+        //
+
+        let process = vmi.read_va(
+            registers.address_context(thread.0 + KTHREAD.Process.offset),
+            registers.address_width(),
+        )?;
+
+        let cr3 = Cr3::from(u64::from(vmi.read_va(
+            registers.address_context(process + KPROCESS.DirectoryTableBase.offset),
+            registers.address_width(),
+        )?));
+        let root = Pa::from(cr3);
+
+        let kernel_stack = vmi.read_va(
+            registers.address_context(thread.0 + KTHREAD.KernelStack.offset),
+            registers.address_width(),
+        )?;
+
+        //
+        // `SwapContext` saves the context of the outgoing thread into the
+        // `_KSWITCH_FRAME` located at `KTHREAD.KernelStack`. When the thread
+        // is resumed, the frame is popped and execution continues at the
+        // `Return` address.
+        //
+
+        let rip = vmi.read_u64((kernel_stack + KSWITCH_FRAME.Return.offset, root))?;
+        let rbp = match KSWITCH_FRAME.Rbp {
+            Some(Rbp) => vmi.read_u64((kernel_stack + Rbp.offset, root))?,
+            None => 0,
+        };
+        let rsp = kernel_stack + KSWITCH_FRAME.len() as u64;
+
+        let mut result = *registers;
+        result.set_gp_registers(&GpRegisters {
+            rip,
+            rsp: rsp.0,
+            rbp,
+            ..Default::default()
+        });
+        result.cr3 = cr3;
+
+        Ok(result)
+    }
+
+    fn thread_user_context(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        thread: ThreadObject,
+        is_current: bool,
+    ) -> Result<Registers, VmiError> {
+        let KTHREAD = &os.offsets.common._KTHREAD;
+        let KTRAP_FRAME = &os.offsets.common._KTRAP_FRAME;
+
+        //
+        // A thread that is currently executing user-mode code has its
+        // user-mode context loaded in the virtual CPU.
+        //
+
+        if is_current && registers.cs.selector.request_privilege_level() != 0 {
+            return Ok(*registers);
+        }
+
+        let process = ProcessObject(vmi.read_va(
+            registers.address_context(thread.0 + KTHREAD.Process.offset),
+            registers.address_width(),
+        )?);
+
+        let is_user_trap_frame = |trap_frame: Va| -> Result<bool, VmiError> {
+            let seg_cs =
+                vmi.read_u16(registers.address_context(trap_frame + KTRAP_FRAME.SegCs.offset))?;
+            Ok(seg_cs & 3 != 0)
+        };
+
+        //
+        // When the thread entered the kernel from user-mode, `KTHREAD.TrapFrame`
+        // points to the trap frame holding the user-mode context. If the
+        // thread was interrupted again while in kernel-mode, it points to a
+        // nested kernel-mode trap frame instead, and the user-mode trap frame
+        // is the first one on the kernel stack, right below
+        // `KTHREAD.InitialStack`.
+        //
+
+        let trap_frame = vmi.read_va(
+            registers.address_context(thread.0 + KTHREAD.TrapFrame.offset),
+            registers.address_width(),
+        )?;
+
+        let trap_frame = if !trap_frame.is_null() && is_user_trap_frame(trap_frame)? {
+            trap_frame
+        }
+        else {
+            let initial_stack = vmi.read_va(
+                registers.address_context(thread.0 + KTHREAD.InitialStack.offset),
+                registers.address_width(),
+            )?;
+
+            let trap_frame = initial_stack - KTRAP_FRAME.len() as u64;
+            if !is_user_trap_frame(trap_frame)? {
+                return Err(VmiError::Other("Thread has no user-mode context"));
+            }
+
+            trap_frame
+        };
+
+        let trap_frame = StructReader::new(
+            vmi,
+            registers.address_context(trap_frame),
+            KTRAP_FRAME.effective_len(),
+        )?;
+
+        //
+        // The system call entry saves only the volatile registers and a few
+        // non-volatile ones into the trap frame. The remaining non-volatile
+        // registers (R12-R15) are preserved somewhere on the kernel stack and
+        // can't be recovered without unwinding it.
+        //
+
+        let mut result = *registers;
+        result.set_gp_registers(&GpRegisters {
+            rax: trap_frame.read(KTRAP_FRAME.Rax)?,
+            rbx: trap_frame.read(KTRAP_FRAME.Rbx)?,
+            rcx: trap_frame.read(KTRAP_FRAME.Rcx)?,
+            rdx: trap_frame.read(KTRAP_FRAME.Rdx)?,
+            rbp: trap_frame.read(KTRAP_FRAME.Rbp)?,
+            rsi: trap_frame.read(KTRAP_FRAME.Rsi)?,
+            rdi: trap_frame.read(KTRAP_FRAME.Rdi)?,
+            rsp: trap_frame.read(KTRAP_FRAME.Rsp)?,
+            r8: trap_frame.read(KTRAP_FRAME.R8)?,
+            r9: trap_frame.read(KTRAP_FRAME.R9)?,
+            r10: trap_frame.read(KTRAP_FRAME.R10)?,
+            r11: trap_frame.read(KTRAP_FRAME.R11)?,
+            rip: trap_frame.read(KTRAP_FRAME.Rip)?,
+            rflags: trap_frame.read(KTRAP_FRAME.EFlags)?.into(),
+            ..Default::default()
+        });
+        result.cs.selector = (trap_frame.read(KTRAP_FRAME.SegCs)? as u16).into();
+        result.ss.selector = (trap_frame.read(KTRAP_FRAME.SegSs)? as u16).into();
+        result.cr3 = Cr3::from(u64::from(
+            os.process_user_translation_root(vmi, registers, process)?,
+        ));
+
+        Ok(result)
+    }
+
+    fn stack_trace_unwind(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        context: &Registers,
+        process: ProcessObject,
+        bounds: Option<WindowsStackBounds>,
+    ) -> Result<Vec<WindowsStackFrame>, VmiError> {
+        const MAX_FRAMES: usize = 256;
+
+        let highest_user_address = os.highest_user_address(vmi, registers)?;
+
+        let mut context = *context;
+        let mut kernel_modules = None;
+        let mut result = Vec::new();
+
+        while result.len() < MAX_FRAMES {
+            let rip = Va(context.rip);
+            let rsp = Va(context.rsp);
+
+            if rip.is_null() {
+                break;
+            }
+
+            if let Some(bounds) = bounds {
+                if !bounds.contains(rsp) {
+                    break;
+                }
+            }
+
+            let image_base = if rip > highest_user_address {
+                let modules = match &kernel_modules {
+                    Some(modules) => modules,
+                    None => kernel_modules.insert(os.modules(vmi, registers)?),
+                };
+
+                modules
+                    .iter()
+                    .find(|module| {
+                        module.base_address <= rip && rip < module.base_address + module.size
+                    })
+                    .map(|module| module.base_address)
+            }
+            else {
+                user_image_base(os, vmi, registers, process, rip)?
+            };
+
+            result.push(WindowsStackFrame {
+                instruction_pointer: rip,
+                stack_pointer: rsp,
+                image_base,
+            });
+
+            let image_base = match image_base {
+                Some(image_base) => image_base,
+                None => break,
+            };
+
+            if let Err(err) = unwind_frame(os, vmi, &mut context, image_base) {
+                tracing::debug!(%rip, %rsp, %err, "failed to unwind frame");
+                break;
+            }
+
+            // The stack pointer of the caller is always above the stack
+            // pointer of the callee. Anything else means a corrupted stack.
+            if context.rsp <= rsp.0 {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
     fn current_kpcr(_os: &WindowsOs<Driver>, _vmi: &VmiCore<Driver>, registers: &Registers) -> Va {
         if registers.cs.selector.request_privilege_level() != 0
             || (registers.gs.base & (1 << 47)) == 0
@@ -279,6 +671,439 @@ where
             registers.gs.base.into()
         }
     }
+
+    fn interrupt_stacks(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        prcb: Va,
+    ) -> Result<Vec<Va>, VmiError> {
+        let KPCR = &os.offsets.common._KPCR;
+        let KTSS64 = &os.offsets.common._KTSS64;
+
+        // The `_KPRCB` is embedded in the `_KPCR`.
+        let kpcr = prcb - KPCR.Prcb.offset;
+        let tss = vmi.read_va64(registers.address_context(kpcr + KPCR.TssBase.offset))?;
+
+        if tss.is_null() {
+            return Ok(Vec::new());
+        }
+
+        // The first entry of the Interrupt Stack Table is reserved, an IST
+        // index of zero in the IDT means that no stack switch is performed.
+        let mut result = Vec::new();
+        for index in 1..8 {
+            let ist =
+                vmi.read_va64(registers.address_context(tss + KTSS64.Ist.offset + index * 8))?;
+
+            if !ist.is_null() {
+                result.push(ist);
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn current_irql(
+        _os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        vcpu: VcpuId,
+    ) -> Result<u8, VmiError> {
+        vmi.task_priority(vcpu)
+    }
+
+    fn interrupts_enabled(
+        _os: &WindowsOs<Driver>,
+        _vmi: &VmiCore<Driver>,
+        registers: &Registers,
+    ) -> bool {
+        registers.rflags.interrupt_enable()
+    }
+}
+
+/// Collects the present pages in `[start, end)` of the paging structure at
+/// `table`, together with whether they are executable.
+///
+/// The paging structures are walked top-down, so that the whole range
+/// covered by a non-present entry is skipped with a single read. A page is
+/// executable only if none of the entries on its path has the NX bit set.
+fn collect_present_pages<Driver>(
+    vmi: &VmiCore<Driver>,
+    table: Pa,
+    level: PageTableLevel,
+    start: Va,
+    end: Va,
+    executable: bool,
+    result: &mut Vec<(Va, bool)>,
+) where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    let page = match vmi.read_page(Amd64::gfn_from_pa(table)) {
+        Ok(page) => page,
+        Err(err) => {
+            tracing::trace!(?err, %table, "failed to read paging structure");
+            return;
+        }
+    };
+
+    // Size of the range covered by a single entry.
+    let entry_size = Amd64::va_offset_for(Va(u64::MAX), level) + 1;
+
+    let mut va = start;
+    while va < end {
+        let index = Amd64::va_index_for(va, level) as usize;
+        let offset = index * size_of::<PageTableEntry>();
+        let entry = match page
+            .get(offset..offset + size_of::<PageTableEntry>())
+            .and_then(|entry| PageTableEntry::read_from_bytes(entry).ok())
+        {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        let next = match (va.0 & !(entry_size - 1)).checked_add(entry_size) {
+            Some(next) => Va(next.min(end.0)),
+            None => end,
+        };
+
+        if entry.present() {
+            let executable = executable && !entry.execute_disable();
+
+            match level.next() {
+                Some(next_level) if !entry.large() || level == PageTableLevel::Pml4 => {
+                    collect_present_pages(
+                        vmi,
+                        Amd64::pa_from_gfn(entry.pfn()),
+                        next_level,
+                        va,
+                        next,
+                        executable,
+                        result,
+                    );
+                }
+                _ => {
+                    let mut page_va = va;
+                    while page_va < next {
+                        result.push((page_va, executable));
+                        page_va = Va((page_va.0 & !(Amd64::PAGE_SIZE - 1)) + Amd64::PAGE_SIZE);
+                    }
+                }
+            }
+        }
+
+        va = next;
+    }
+}
+
+/// Resolves the page described by a prototype PTE.
+///
+/// A prototype PTE is either valid, in transition, a software PTE pointing
+/// to a paging file, or a subsection PTE pointing to the `_SUBSECTION` that
+/// maps the page from a file.
+fn resolve_prototype_pte<Driver>(
+    os: &WindowsOs<Driver>,
+    vmi: &VmiCore<Driver>,
+    registers: &Registers,
+    prototype_pte: Va,
+    offset: u64,
+) -> Result<Option<WindowsResolvedPage>, VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    const MMSECTOR_SHIFT: u64 = 9;
+
+    let pte = PageTableEntry(vmi.read_u64(registers.address_context(prototype_pte))?);
+
+    let offsets = &os.offsets.common;
+
+    if pte.present() {
+        return Ok(Some(WindowsResolvedPage::Physical(
+            Amd64::pa_from_gfn(pte.pfn()) + offset,
+        )));
+    }
+
+    if pte.windows_transition(offsets) && !pte.windows_prototype(offsets) {
+        return Ok(Some(WindowsResolvedPage::Physical(
+            Amd64::pa_from_gfn(pte.windows_transition_pfn(offsets)) + offset,
+        )));
+    }
+
+    if !pte.windows_prototype(offsets) {
+        let software_pte = PageTableEntry(pte.0 ^ os.invalid_pte_mask(vmi, registers)?);
+        return resolve_software_pte(os, vmi, registers, software_pte, offset);
+    }
+
+    //
+    // Subsection PTE.
+    //
+    // Equivalent of the `MiStartingOffset()` macro:
+    //
+    // ((PrototypePte - Subsection->SubsectionBase) << PAGE_SHIFT) +
+    // (Subsection->StartingSector << MMSECTOR_SHIFT)
+    //
+
+    let SUBSECTION = &os.offsets.common._SUBSECTION;
+
+    let subsection = pte.windows_proto_address();
+    let subsection_reader = StructReader::new(
+        vmi,
+        registers.address_context(subsection),
+        SUBSECTION.effective_len(),
+    )?;
+    let control_area = Va(subsection_reader.read(SUBSECTION.ControlArea)?);
+    let subsection_base = Va(subsection_reader.read(SUBSECTION.SubsectionBase)?);
+    let starting_sector = subsection_reader.read(SUBSECTION.StartingSector)?;
+
+    let index = (prototype_pte - subsection_base).0 / size_of::<PageTableEntry>() as u64;
+
+    Ok(Some(WindowsResolvedPage::File {
+        subsection,
+        control_area,
+        offset: (index << Amd64::PAGE_SHIFT) + (starting_sector << MMSECTOR_SHIFT) + offset,
+    }))
+}
+
+/// Resolves the page described by a software PTE.
+///
+/// The PTE must already be deobfuscated with the
+/// [`WindowsOs::invalid_pte_mask`].
+///
+/// Returns `None` if the PTE describes a decommitted or inaccessible page.
+///
+/// A software PTE pointing to the virtual store paging file describes a page
+/// compressed in the store (see [`WindowsOs::virtual_store_pagefile`]).
+fn resolve_software_pte<Driver>(
+    os: &WindowsOs<Driver>,
+    vmi: &VmiCore<Driver>,
+    registers: &Registers,
+    pte: PageTableEntry,
+    offset: u64,
+) -> Result<Option<WindowsResolvedPage>, VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    const MM_ZERO_ACCESS: u8 = 0;
+    const MM_DECOMMIT: u8 = 0x10;
+
+    let offsets = &os.offsets.common;
+    let page_file_low = pte.windows_page_file_low(offsets);
+    let page_file_high = pte.windows_page_file_high(offsets);
+
+    if page_file_high == 0 {
+        return Ok(match pte.windows_protection(offsets) {
+            MM_ZERO_ACCESS | MM_DECOMMIT => None,
+            _ => Some(WindowsResolvedPage::DemandZero),
+        });
+    }
+
+    if os.virtual_store_pagefile(vmi, registers)? == Some(page_file_low) {
+        // The store key is formed in the same way as by `SmPageKey()`.
+        let key = (page_file_low as u32) << 28 | (page_file_high as u32 & 0x0fff_ffff);
+
+        return Ok(Some(WindowsResolvedPage::Store { key, offset }));
+    }
+
+    Ok(Some(WindowsResolvedPage::PageFile {
+        index: page_file_low,
+        offset: (page_file_high << Amd64::PAGE_SHIFT) + offset,
+    }))
+}
+
+/// Finds the base address of the image mapped at a user-mode address in
+/// a process.
+fn user_image_base<Driver>(
+    os: &WindowsOs<Driver>,
+    vmi: &VmiCore<Driver>,
+    registers: &Registers,
+    process: ProcessObject,
+    address: Va,
+) -> Result<Option<Va>, VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    const VadImageMap: u8 = 2;
+
+    let vad = match os.find_process_vad(vmi, registers, process, address)? {
+        Some(vad) => os.vad(vmi, registers, vad)?,
+        None => return Ok(None),
+    };
+
+    if vad.vad_type != VadImageMap {
+        return Ok(None);
+    }
+
+    Ok(Some(Va(vad.starting_vpn << Amd64::PAGE_SHIFT)))
+}
+
+/// Unwinds a single frame, restoring the registers of the caller.
+///
+/// The registers are restored in place, following the algorithm of
+/// `RtlVirtualUnwind`.
+fn unwind_frame<Driver>(
+    os: &WindowsOs<Driver>,
+    vmi: &VmiCore<Driver>,
+    context: &mut Registers,
+    image_base: Va,
+) -> Result<(), VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    // The same limit as the one used by `RtlpUnwindPrologue`.
+    const MAX_CHAINED_UNWIND_INFO: usize = 32;
+
+    let read_u64 = |context: &Registers, address: u64| -> Result<u64, VmiError> {
+        vmi.read_u64(context.address_context(Va(address)))
+    };
+
+    let rva = (context.rip - image_base.0) as u32;
+    let mut function = match os.image_runtime_function(vmi, context, image_base, rva)? {
+        Some(function) => function,
+        None => {
+            // Leaf function, the return address is at the top of the stack.
+            context.rip = read_u64(context, context.rsp)?;
+            context.rsp += 8;
+            return Ok(());
+        }
+    };
+
+    //
+    // Operations of the prolog that has not been executed yet must not be
+    // undone. This only applies to the primary unwind information; chained
+    // unwind information describes code that has already been executed.
+    //
+
+    let mut prolog_offset = Some(rva - function.begin_address);
+    let mut frame = None;
+    let mut machine_frame = false;
+
+    for depth in 0.. {
+        if depth > MAX_CHAINED_UNWIND_INFO {
+            return Err(VmiError::Other("Too many chained unwind infos"));
+        }
+
+        let info =
+            os.image_unwind_info_at(vmi, context, image_base, function.unwind_info_address)?;
+
+        let is_executed = |code: &PeUnwindCode| match prolog_offset {
+            Some(prolog_offset) => code.code_offset as u32 <= prolog_offset,
+            None => true,
+        };
+
+        //
+        // Registers saved with a MOV are addressed relative to the frame
+        // base: the frame pointer (adjusted by the frame offset) if it has
+        // been established, or the stack pointer otherwise.
+        //
+
+        let frame = match frame {
+            Some(frame) => frame,
+            None => {
+                let frame_established = info.frame_register != 0
+                    && info.codes.iter().any(|code| {
+                        code.operation == PeUnwindOperation::SetFpreg && is_executed(code)
+                    });
+
+                let base = match frame_established {
+                    true => gp_register(context, info.frame_register)
+                        .checked_sub(info.frame_offset as u64 * 16)
+                        .ok_or(VmiError::Other("Invalid frame register"))?,
+                    false => context.rsp,
+                };
+
+                *frame.insert(base)
+            }
+        };
+
+        for code in info.codes.iter().filter(|code| is_executed(code)) {
+            match code.operation {
+                PeUnwindOperation::PushNonvol { register } => {
+                    let value = read_u64(context, context.rsp)?;
+                    set_gp_register(context, register, value);
+                    context.rsp += 8;
+                }
+                PeUnwindOperation::Alloc { size } => {
+                    context.rsp += size as u64;
+                }
+                PeUnwindOperation::SetFpreg => {
+                    context.rsp = frame;
+                }
+                PeUnwindOperation::SaveNonvol { register, offset } => {
+                    let value = read_u64(context, frame + offset as u64)?;
+                    set_gp_register(context, register, value);
+                }
+                PeUnwindOperation::PushMachframe { error_code } => {
+                    if error_code {
+                        context.rsp += 8;
+                    }
+
+                    // RIP, CS, RFLAGS, RSP, SS
+                    context.rip = read_u64(context, context.rsp)?;
+                    context.rsp = read_u64(context, context.rsp + 24)?;
+                    machine_frame = true;
+                }
+                PeUnwindOperation::SaveXmm128 { .. } | PeUnwindOperation::Epilog => {}
+            }
+        }
+
+        match info.chained {
+            Some(chained) => {
+                function = chained;
+                prolog_offset = None;
+            }
+            None => break,
+        }
+    }
+
+    if !machine_frame {
+        context.rip = read_u64(context, context.rsp)?;
+        context.rsp += 8;
+    }
+
+    Ok(())
+}
+
+/// Returns the value of a general-purpose register by its x64 encoding.
+fn gp_register(registers: &Registers, register: u8) -> u64 {
+    match register {
+        0 => registers.rax,
+        1 => registers.rcx,
+        2 => registers.rdx,
+        3 => registers.rbx,
+        4 => registers.rsp,
+        5 => registers.rbp,
+        6 => registers.rsi,
+        7 => registers.rdi,
+        8 => registers.r8,
+        9 => registers.r9,
+        10 => registers.r10,
+        11 => registers.r11,
+        12 => registers.r12,
+        13 => registers.r13,
+        14 => registers.r14,
+        _ => registers.r15,
+    }
+}
+
+/// Sets the value of a general-purpose register by its x64 encoding.
+fn set_gp_register(registers: &mut Registers, register: u8, value: u64) {
+    match register {
+        0 => registers.rax = value,
+        1 => registers.rcx = value,
+        2 => registers.rdx = value,
+        3 => registers.rbx = value,
+        4 => registers.rsp = value,
+        5 => registers.rbp = value,
+        6 => registers.rsi = value,
+        7 => registers.rdi = value,
+        8 => registers.r8 = value,
+        9 => registers.r9 = value,
+        10 => registers.r10 = value,
+        11 => registers.r11 = value,
+        12 => registers.r12 = value,
+        13 => registers.r13 = value,
+        14 => registers.r14 = value,
+        _ => registers.r15 = value,
+    }
 }
 
 fn function_argument_x86<Driver>(
@@ -1,8 +1,13 @@
 mod amd64;
 
-use vmi_core::{os::ProcessObject, Architecture, Va, VmiCore, VmiDriver, VmiError};
+use vmi_core::{
+    os::{ProcessObject, ThreadObject},
+    Architecture, Pa, Va, VcpuId, VmiCore, VmiDriver, VmiError,
+};
 
-use crate::{WindowsKernelInformation, WindowsOs};
+use crate::{
+    WindowsKernelInformation, WindowsOs, WindowsResolvedPage, WindowsStackBounds, WindowsStackFrame,
+};
 
 /// Architecture-specific Windows functionality.
 pub trait ArchAdapter<Driver>: Architecture
@@ -48,9 +53,68 @@ where
         address: Va,
     ) -> Result<Option<bool>, VmiError>;
 
+    fn present_pages(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        start: Va,
+        end: Va,
+    ) -> Vec<(Va, bool)>;
+
+    fn process_resolve_paged_va(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        address: Va,
+    ) -> Result<Option<WindowsResolvedPage>, VmiError>;
+
+    fn thread_saved_context(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<<Driver::Architecture as Architecture>::Registers, VmiError>;
+
+    fn thread_user_context(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+        is_current: bool,
+    ) -> Result<<Driver::Architecture as Architecture>::Registers, VmiError>;
+
+    fn stack_trace_unwind(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        context: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        bounds: Option<WindowsStackBounds>,
+    ) -> Result<Vec<WindowsStackFrame>, VmiError>;
+
     fn current_kpcr(
         os: &WindowsOs<Driver>,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
     ) -> Va;
+
+    fn interrupt_stacks(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        prcb: Va,
+    ) -> Result<Vec<Va>, VmiError>;
+
+    fn current_irql(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        vcpu: VcpuId,
+    ) -> Result<u8, VmiError>;
+
+    fn interrupts_enabled(
+        os: &WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> bool;
 }
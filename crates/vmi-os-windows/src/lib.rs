@@ -48,24 +48,32 @@
     non_upper_case_globals, // example: StandbyPageList
 )]
 
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    net::IpAddr,
+};
 
 use ::object::{
     pe::{
-        ImageNtHeaders32, ImageNtHeaders64, IMAGE_DIRECTORY_ENTRY_EXPORT,
-        IMAGE_NT_OPTIONAL_HDR32_MAGIC, IMAGE_NT_OPTIONAL_HDR64_MAGIC,
+        ImageNtHeaders32, ImageNtHeaders64, IMAGE_DIRECTORY_ENTRY_EXCEPTION,
+        IMAGE_DIRECTORY_ENTRY_EXPORT, IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG,
+        IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK, IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT,
+        IMAGE_GUARD_CF_INSTRUMENTED, IMAGE_GUARD_RF_INSTRUMENTED, IMAGE_NT_OPTIONAL_HDR32_MAGIC,
+        IMAGE_NT_OPTIONAL_HDR64_MAGIC,
     },
-    read::pe::{optional_header_magic, ExportTarget, ImageNtHeaders},
+    read::pe::{optional_header_magic, ExportTarget, ImageNtHeaders, ImageOptionalHeader as _},
     LittleEndian as LE,
 };
 use isr_core::Profile;
+use isr_macros::Field;
 use vmi_arch_amd64::{Amd64, Cr3};
 use vmi_core::{
     os::{
         OsArchitecture, OsExt, OsImageExportedSymbol, OsMapped, OsModule, OsProcess, OsRegion,
         OsRegionKind, ProcessId, ProcessObject, StructReader, ThreadId, ThreadObject, VmiOs,
     },
-    AccessContext, Architecture, Gfn, Hex, MemoryAccess, Pa, Registers as _, Va, VmiCore,
+    AccessContext, Architecture, Gfn, Hex, MemoryAccess, Pa, Registers as _, Va, VcpuId, VmiCore,
     VmiDriver, VmiError,
 };
 use vmi_macros::derive_trait_from_impl;
@@ -75,12 +83,39 @@ mod arch;
 use self::arch::ArchAdapter;
 
 mod pe;
-pub use self::pe::{CodeView, PeError, PeLite, PeLite32, PeLite64};
+pub use self::pe::{
+    CodeView, PeError, PeGuardConfig, PeLite, PeLite32, PeLite64, PeRuntimeFunction, PeUnwindCode,
+    PeUnwindInfo, PeUnwindOperation,
+};
+
+mod net;
+pub use self::net::{
+    TcpipSymbols, WindowsTcpConnection, WindowsTcpListener, WindowsTcpState, WindowsTcpip,
+    WindowsTcpipLayout, WindowsUdpEndpoint,
+};
 
 mod offsets;
 use self::offsets::{v1, v2};
 pub use self::offsets::{Offsets, OffsetsExt, Symbols}; // TODO: make private + remove offsets() & symbols() methods
 
+mod registry;
+pub use self::registry::WindowsRegistryValue;
+use self::registry::{
+    BigData, KeyIndex, KeyNode, KeyValue, CM_KEY_VALUE_BIG, HBASE_BLOCK_MINOR,
+    HBASE_BLOCK_ROOT_CELL, HCELL_HEADER_SIZE, HCELL_NIL, HSYS_WHISTLER_BETA1,
+};
+
+mod store;
+pub use self::store::{
+    WindowsCompressionFormat, WindowsSmKmLayout, WindowsSmKmStoreLocator, WindowsStoreLocator,
+    WindowsStorePage,
+};
+
+mod xpress;
+
+#[cfg(feature = "encoding")]
+pub use encoding_rs;
+
 /// VMI operations for the Windows operating system.
 ///
 /// `WindowsOs` provides methods and utilities for introspecting a Windows-based
@@ -199,9 +234,14 @@ where
 
     ki_kva_shadow: RefCell<Option<bool>>,
     mm_pfn_database: RefCell<Option<Va>>,
+    virtual_store_pagefile: RefCell<Option<u8>>,
+    invalid_pte_mask: RefCell<Option<u64>>,
     nt_build_lab: RefCell<Option<String>>,
     nt_build_lab_ex: RefCell<Option<String>>,
 
+    hal_dispatch_layout: Vec<(String, u64)>,
+    hal_private_dispatch_layout: Option<Vec<(String, u64)>>,
+
     _marker: std::marker::PhantomData<Driver>,
 }
 
@@ -299,6 +339,89 @@ pub struct WindowsPeb {
     pub command_line: String,
 }
 
+/// A user-mode module loaded in a process.
+///
+/// Represents a `_LDR_DATA_TABLE_ENTRY` of the loader module list of the
+/// process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsUserModule {
+    /// The `DllBase` field of the loader entry.
+    pub base_address: Va,
+
+    /// The `SizeOfImage` field of the loader entry.
+    pub size: u64,
+
+    /// The `BaseDllName` field of the loader entry.
+    pub name: String,
+
+    /// The `FullDllName` field of the loader entry.
+    pub full_name: String,
+
+    /// Whether the module is loaded in the 32-bit address space of
+    /// a WoW64 process.
+    pub wow64: bool,
+}
+
+/// An image loaded in one or more processes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsLoadedImage {
+    /// The full path of the image, as found in the first process it was
+    /// encountered in.
+    pub path: String,
+
+    /// The processes the image is loaded in.
+    pub instances: Vec<WindowsLoadedImageInstance>,
+}
+
+/// An instance of a [`WindowsLoadedImage`] in a process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsLoadedImageInstance {
+    /// The process the image is loaded in.
+    pub process: ProcessObject,
+
+    /// The ID of the process.
+    pub process_id: ProcessId,
+
+    /// The base address of the image in the process.
+    ///
+    /// Differs between processes if the image was relocated (e.g., due to
+    /// ASLR).
+    pub base_address: Va,
+
+    /// The size of the image.
+    pub size: u64,
+}
+
+/// A PE image found in private memory of a process.
+///
+/// Images loaded by the Windows loader are mapped from a section backed
+/// by the image file. An image found in private memory was copied there
+/// by other means, which is typical for reflective DLL injection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsPrivateImage {
+    /// The address of the `MZ` header.
+    pub base_address: Va,
+
+    /// The `SizeOfImage` field of the PE optional header.
+    pub size: u64,
+
+    /// The architecture of the image.
+    pub architecture: OsArchitecture,
+
+    /// The start of the VAD the image was found in.
+    pub region_start: Va,
+
+    /// The end of the VAD the image was found in.
+    pub region_end: Va,
+
+    /// Whether the image is listed in the loader module list of the
+    /// process.
+    ///
+    /// An image that is not listed is hidden from tools that rely on
+    /// the module list.
+    pub linked: bool,
+}
+
 /// Identifies the type of a Windows kernel object.
 ///
 /// Windows uses a object-based kernel architecture where various system
@@ -309,7 +432,7 @@ pub struct WindowsPeb {
 /// Each variant corresponds to a specific object type string used internally
 /// by the Windows kernel. For example, "Process" for process objects,
 /// "Thread" for thread objects, etc.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WindowsObjectType {
     /// ALPC Port object.
     ///
@@ -323,6 +446,12 @@ pub enum WindowsObjectType {
     /// Has `DebugObject` type name.
     DebugObject,
 
+    /// Desktop object.
+    ///
+    /// Represented by `tagDESKTOP` structure (owned by `win32k`).
+    /// Has `Desktop` type name.
+    Desktop,
+
     /// Device object.
     ///
     /// Represented by `_DEVICE_OBJECT` structure.
@@ -418,6 +547,48 @@ pub enum WindowsObjectType {
     /// Represented by `_OBJECT_TYPE` structure.
     /// Has `Type` type name.
     Type,
+
+    /// Window station object.
+    ///
+    /// Represented by `tagWINDOWSTATION` structure (owned by `win32k`).
+    /// Has `WindowStation` type name.
+    WindowStation,
+}
+
+/// The object counters of an object type.
+///
+/// Represents an `_OBJECT_TYPE` structure from the `ObTypeIndexTable`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsObjectTypeInfo {
+    /// The address of the `_OBJECT_TYPE` structure.
+    pub address: Va,
+
+    /// The `Index` field of the object type.
+    ///
+    /// The index into the `ObTypeIndexTable`.
+    pub index: u8,
+
+    /// The `Name` field of the object type.
+    pub name: String,
+
+    /// The object type, if it is one of the known types.
+    pub typ: Option<WindowsObjectType>,
+
+    /// The `TotalNumberOfObjects` field of the object type.
+    ///
+    /// The number of objects of this type that currently exist.
+    pub total_objects: u32,
+
+    /// The `TotalNumberOfHandles` field of the object type.
+    ///
+    /// The number of open handles to objects of this type.
+    pub total_handles: u32,
+
+    /// The `HighWaterNumberOfObjects` field of the object type.
+    pub high_water_objects: u32,
+
+    /// The `HighWaterNumberOfHandles` field of the object type.
+    pub high_water_handles: u32,
 }
 
 /// A Windows object name.
@@ -442,6 +613,19 @@ pub enum WindowsObject {
 
     /// Section object.
     Section(WindowsSectionObject),
+
+    /// Symbolic link object.
+    SymbolicLink(WindowsSymbolicLink),
+}
+
+impl WindowsObject {
+    /// Returns the symbolic link, if the object is a symbolic link.
+    pub fn as_symbolic_link(&self) -> Option<&WindowsSymbolicLink> {
+        match self {
+            Self::SymbolicLink(link) => Some(link),
+            _ => None,
+        }
+    }
 }
 
 /// A Windows file object.
@@ -456,6 +640,24 @@ pub struct WindowsFileObject {
     pub filename: String,
 }
 
+/// A Windows symbolic link object.
+#[derive(Debug)]
+pub struct WindowsSymbolicLink {
+    /// The `LinkTarget` field of the `_OBJECT_SYMBOLIC_LINK` structure.
+    ///
+    /// The object path the link points to, e.g., `\Device\HarddiskVolume3`,
+    /// or `None` if the link is resolved by a callback.
+    pub target: Option<String>,
+}
+
+impl WindowsSymbolicLink {
+    /// Returns the object path the link points to, or `None` if the link
+    /// is resolved by a callback.
+    pub fn target(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+}
+
 /// A Windows section object.
 #[derive(Debug)]
 pub struct WindowsSectionObject {
@@ -498,2379 +700,8959 @@ pub struct WindowsVad {
     pub right_child: Va,
 }
 
-//
-// Private types
-//
+/// The list a physical page is on, as recorded in `_MMPFN.u3.e1.PageLocation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsPageLocation {
+    /// The page is free and zeroed (`ZeroedPageList`).
+    Zeroed,
 
-/// The address space type in a WoW64 process.
-enum WindowsWow64Kind {
-    /// Native address space
-    Native = 0,
+    /// The page is free (`FreePageList`).
+    Free,
 
-    /// x86 (32-bit) address space under WoW64
-    X86 = 1,
-    // Arm32 = 2,
-    // Amd64 = 3,
-    // ChpeX86 = 4,
-    // VsmEnclave = 5,
-}
+    /// The page is on the standby list (`StandbyPageList`).
+    Standby,
 
-/// A 32-bit or 64-bit virtual address in WoW64 processes.
-struct WindowsWow64Va {
-    /// The virtual address.
-    va: Va,
+    /// The page is on the modified list (`ModifiedPageList`).
+    Modified,
 
-    /// The kind of the virtual address.
-    kind: WindowsWow64Kind,
-}
+    /// The page is on the modified no-write list (`ModifiedNoWritePageList`).
+    ModifiedNoWrite,
 
-impl WindowsWow64Va {
-    fn native(va: Va) -> Self {
-        Self {
-            va,
-            kind: WindowsWow64Kind::Native,
-        }
-    }
+    /// The page is bad (`BadPageList`).
+    Bad,
 
-    fn x86(va: Va) -> Self {
-        Self {
-            va,
-            kind: WindowsWow64Kind::X86,
+    /// The page is in use (`ActiveAndValid`).
+    Active,
+
+    /// The page is in transition (`TransitionPage`).
+    Transition,
+}
+
+impl From<u8> for WindowsPageLocation {
+    fn from(value: u8) -> Self {
+        match value & 7 {
+            0 => Self::Zeroed,
+            1 => Self::Free,
+            2 => Self::Standby,
+            3 => Self::Modified,
+            4 => Self::ModifiedNoWrite,
+            5 => Self::Bad,
+            6 => Self::Active,
+            _ => Self::Transition,
         }
     }
 }
 
-#[derive_trait_from_impl(
-    os_session_name = WindowsOsSessionExt,
-    os_context_name = WindowsOsExt,
-    os_session_prober_name = WindowsOsSessionProberExt,
-    os_context_prober_name = WindowsOsProberExt
-)]
-#[allow(non_snake_case, non_upper_case_globals)]
-impl<Driver> WindowsOs<Driver>
-where
-    Driver: VmiDriver,
-    Driver::Architecture: Architecture + ArchAdapter<Driver>,
-{
-    /// 32-bit current process pseudo-handle (-1).
-    pub const NtCurrentProcess32: u64 = 0xffff_ffff;
+/// The location of the data backing a virtual address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsResolvedPage {
+    /// The data is resident in physical memory at the given address.
+    ///
+    /// This includes pages in transition, which are not part of any working
+    /// set but have not been repurposed yet.
+    Physical(Pa),
 
-    /// 64-bit current process pseudo-handle (-1).
-    pub const NtCurrentProcess64: u64 = 0xffff_ffff_ffff_ffff;
+    /// The data is stored in a paging file.
+    PageFile {
+        /// The index of the paging file (`PageFileLow`).
+        index: u8,
 
-    /// 32-bit current thread pseudo-handle (-2).
-    pub const NtCurrentThread32: u64 = 0xffff_fffe;
+        /// The offset of the data within the paging file.
+        offset: u64,
+    },
 
-    /// 64-bit current thread pseudo-handle (-2).
-    pub const NtCurrentThread64: u64 = 0xffff_ffff_ffff_fffe;
+    /// The data is backed by a mapped file (or image).
+    File {
+        /// The address of the `_SUBSECTION` structure.
+        subsection: Va,
 
-    /// Creates a new `WindowsOs` instance.
-    pub fn new(profile: &Profile) -> Result<Self, VmiError> {
-        Ok(Self {
-            offsets: Offsets::new(profile)?,
-            symbols: Symbols::new(profile)?,
-            kernel_image_base: RefCell::new(None),
-            highest_user_address: RefCell::new(None),
-            object_header_cookie: RefCell::new(None),
-            object_type_cache: RefCell::new(HashMap::new()),
-            ki_kva_shadow: RefCell::new(None),
-            mm_pfn_database: RefCell::new(None),
-            nt_build_lab: RefCell::new(None),
-            nt_build_lab_ex: RefCell::new(None),
-            _marker: std::marker::PhantomData,
-        })
-    }
+        /// The address of the `_CONTROL_AREA` structure.
+        ///
+        /// The name of the file can be retrieved with
+        /// [`WindowsOs::control_area_to_filename`].
+        control_area: Va,
 
-    /// Returns a reference to the Windows-specific memory offsets.
-    pub fn offsets(&self) -> &Offsets {
-        &self.offsets
-    }
+        /// The offset of the data within the file.
+        offset: u64,
+    },
 
-    /// Returns a reference to the Windows-specific symbols.
-    pub fn symbols(&self) -> &Symbols {
-        &self.symbols
-    }
+    /// The page is committed, but it has not been accessed yet and will be
+    /// filled with zeros on first access.
+    DemandZero,
 
-    #[expect(clippy::only_used_in_recursion)]
-    fn enumerate_tree_node_v1(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        node: Va,
-        callback: &mut impl FnMut(Va) -> bool,
-        offsets: &v1::Offsets,
-    ) -> Result<(), VmiError> {
-        let MMADDRESS_NODE = &offsets._MMADDRESS_NODE;
+    /// The data is compressed in the store of the memory manager.
+    ///
+    /// The page can be read with [`WindowsOs::read_compressed_page`].
+    Store {
+        /// The key identifying the page in the store (`SM_PAGE_KEY`).
+        key: u32,
 
-        let balanced_node = StructReader::new(
-            vmi,
-            registers.address_context(node),
-            MMADDRESS_NODE.effective_len(),
-        )?;
+        /// The offset of the data within the page.
+        offset: u64,
+    },
+}
 
-        let left = Va(balanced_node.read(MMADDRESS_NODE.LeftChild)?);
-        if !left.is_null() {
-            self.enumerate_tree_node_v1(vmi, registers, left, callback, offsets)?;
-        }
+/// Represents an entry of the PFN database (`_MMPFN`).
+#[derive(Debug)]
+pub struct WindowsPfn {
+    /// The page frame number.
+    pub pfn: Gfn,
 
-        if !callback(node) {
-            return Ok(());
-        }
+    /// The address of the `_MMPFN` structure.
+    pub address: Va,
 
-        let right = Va(balanced_node.read(MMADDRESS_NODE.RightChild)?);
-        if !right.is_null() {
-            self.enumerate_tree_node_v1(vmi, registers, right, callback, offsets)?;
-        }
+    /// The list the page is on.
+    pub location: WindowsPageLocation,
 
-        Ok(())
-    }
+    /// The `ReferenceCount` field.
+    pub reference_count: u16,
 
-    #[expect(clippy::only_used_in_recursion)]
-    fn enumerate_tree_node_v2(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        node: Va,
-        callback: &mut impl FnMut(Va) -> bool,
-        offsets: &v2::Offsets,
-    ) -> Result<(), VmiError> {
-        let RTL_BALANCED_NODE = &offsets._RTL_BALANCED_NODE;
+    /// The `ShareCount` field.
+    ///
+    /// Only meaningful for active pages, where it holds the number of page
+    /// table entries referencing the page.
+    pub share_count: u64,
 
-        let balanced_node = StructReader::new(
-            vmi,
-            registers.address_context(node),
-            RTL_BALANCED_NODE.effective_len(),
-        )?;
+    /// The virtual address of the PTE that maps the page (`PteAddress`).
+    ///
+    /// For active pages mapped through the page table self-map, the virtual
+    /// address mapped by the page can be reconstructed from this value.
+    pub pte_address: Va,
 
-        let left = Va(balanced_node.read(RTL_BALANCED_NODE.Left)?);
-        if !left.is_null() {
-            self.enumerate_tree_node_v2(vmi, registers, left, callback, offsets)?;
-        }
+    /// The PFN of the page table page containing the PTE (`u4.PteFrame`).
+    ///
+    /// Following the `PteFrame` chain up to the top-level page table leads
+    /// to the translation root of the owning process.
+    pub pte_frame: Gfn,
+}
 
-        if !callback(node) {
-            return Ok(());
-        }
+/// A snapshot of the system memory counters of the memory manager.
+///
+/// All counts are in pages. A counter is `None` if the kernel doesn't
+/// provide it.
+///
+/// Windows 7 keeps the counters in global variables (named below), while
+/// Windows 10+ keeps them in the system memory partition
+/// (`MiSystemPartition`), in fields of the same names.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsMemoryStatistics {
+    /// The number of pages available for allocation (`MmAvailablePages`).
+    ///
+    /// This is the sum of the zeroed, free and standby pages.
+    pub available: Option<u64>,
 
-        let right = Va(balanced_node.read(RTL_BALANCED_NODE.Right)?);
-        if !right.is_null() {
-            self.enumerate_tree_node_v2(vmi, registers, right, callback, offsets)?;
-        }
+    /// The number of pages that can still be made resident
+    /// (`MmResidentAvailablePages`).
+    ///
+    /// Low values indicate that the system is running out of memory
+    /// for non-pageable allocations.
+    pub resident_available: Option<u64>,
 
-        Ok(())
-    }
+    /// The number of pages on the zeroed list (`MmZeroedPageListHead`).
+    pub zeroed: Option<u64>,
 
-    fn enumerate_tree_v1(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        root: Va,
-        mut callback: impl FnMut(Va) -> bool,
-        offsets: &v1::Offsets,
-    ) -> Result<(), VmiError> {
-        let MM_AVL_TABLE = &offsets._MM_AVL_TABLE;
-        let MMADDRESS_NODE = &offsets._MMADDRESS_NODE;
+    /// The number of pages on the free list (`MmFreePageListHead`).
+    pub free: Option<u64>,
 
-        // NumberGenericTableElements is a ULONG_PTR, which is the same size
-        // as a pointer.
-        let count = vmi.read_va(
-            registers.address_context(root + MM_AVL_TABLE.NumberGenericTableElements.offset),
-            registers.address_width(),
-        )?;
+    /// The number of pages on the standby lists
+    /// (`MmStandbyPageListByPriority`).
+    ///
+    /// This is the sum of the eight lists, one for each page priority.
+    ///
+    /// Standby pages hold cached data that can be repurposed without
+    /// writing them out first.
+    pub standby: Option<u64>,
 
-        let count = MM_AVL_TABLE.NumberGenericTableElements.value_from(count.0);
-        if count == 0 {
-            return Ok(());
-        }
+    /// The number of pages on the modified list (`MmModifiedPageListHead`).
+    ///
+    /// On Windows 10+, this is the sum of the `ModifiedPageListNoReservation`
+    /// and `ModifiedPageListByReservation` lists.
+    ///
+    /// Modified pages have to be written to the paging file or a mapped
+    /// file before they can be repurposed.
+    pub modified: Option<u64>,
 
-        // Table->BalancedRoot.RightChild
-        let root = vmi.read_va(
-            registers.address_context(
-                root + MM_AVL_TABLE.BalancedRoot.offset + MMADDRESS_NODE.RightChild.offset,
-            ),
-            registers.address_width(),
-        )?;
+    /// The number of pages on the modified no-write list
+    /// (`MmModifiedNoWritePageListHead`).
+    pub modified_no_write: Option<u64>,
+}
 
-        self.enumerate_tree_node_v1(vmi, registers, root, &mut callback, offsets)
-    }
+/// The scheduling state of a thread (`_KTHREAD.State`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsThreadState {
+    /// The thread is being created (`Initialized`).
+    Initialized,
 
-    fn enumerate_tree_v2(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        root: Va,
-        mut callback: impl FnMut(Va) -> bool,
-        offsets: &v2::Offsets,
-    ) -> Result<(), VmiError> {
-        self.enumerate_tree_node_v2(vmi, registers, root, &mut callback, offsets)
-    }
+    /// The thread is ready to run (`Ready`).
+    Ready,
 
-    fn image_exported_symbols_generic<Pe>(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        image_base: Va,
-    ) -> Result<Vec<OsImageExportedSymbol>, VmiError>
-    where
-        Pe: ImageNtHeaders,
-    {
-        let mut data = [0u8; Amd64::PAGE_SIZE as usize];
-        vmi.read(registers.address_context(image_base), &mut data)?;
+    /// The thread is running on a processor (`Running`).
+    Running,
 
-        let pe = PeLite::<Pe>::parse(&data).map_err(|err| VmiError::Os(err.into()))?;
-        let entry = pe.data_directories[IMAGE_DIRECTORY_ENTRY_EXPORT];
+    /// The thread is selected to run next on a processor (`Standby`).
+    Standby,
 
-        let mut data = vec![0u8; entry.size.get(LE) as usize];
-        vmi.read(
-            registers.address_context(image_base + entry.virtual_address.get(LE) as u64),
-            &mut data,
-        )?;
+    /// The thread has exited (`Terminated`).
+    Terminated,
 
-        let exports = pe.exports(&data).map_err(|err| VmiError::Os(err.into()))?;
-        Ok(exports
-            .iter()
-            .filter_map(|export| match export.target {
-                ExportTarget::Address(address) => Some(OsImageExportedSymbol {
-                    name: String::from_utf8_lossy(export.name?).to_string(),
-                    address: image_base + address as u64,
-                }),
-                _ => None,
-            })
-            .collect())
-    }
+    /// The thread is waiting (`Waiting`).
+    ///
+    /// The reason is recorded in `_KTHREAD.WaitReason`.
+    Waiting,
 
-    // region: File
+    /// The thread is ready to run, but its kernel stack is paged out
+    /// (`Transition`).
+    Transition,
 
-    /// Extracts the `FileName` from a `FILE_OBJECT` structure.
-    ///
-    /// # Equivalent C pseudo-code
-    ///
-    /// ```c
-    /// UNICODE_STRING FileName = FileObject->FileName;
-    /// return FileName;
-    /// ```
-    ///
-    /// # Notes
-    ///
-    /// This operation might fail as the filename is allocated from paged pool.
-    pub fn file_object_to_filename(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        file_object: Va,
-    ) -> Result<String, VmiError> {
-        let FILE_OBJECT = &self.offsets.common._FILE_OBJECT;
+    /// The thread is ready to run, but a processor hasn't been selected
+    /// yet (`DeferredReady`).
+    DeferredReady,
 
-        // Note that filename is allocated from paged pool,
-        // so this read might fail.
-        self.read_unicode_string(
-            vmi,
-            registers.address_context(file_object + FILE_OBJECT.FileName.offset),
-        )
-    }
+    /// Obsolete gate wait (`GateWaitObsolete`).
+    GateWaitObsolete,
 
-    /// Constructs the full path of a file from its `FILE_OBJECT`.
-    ///
-    /// This function first reads the `DeviceObject` field of the `FILE_OBJECT`
-    /// structure. Then it reads the `ObjectNameInfo` of the `DeviceObject`
-    /// and its directory. Finally, it concatenates the device directory
-    /// name, device name, and file name.
-    ///
-    /// # Equivalent C pseudo-code
-    ///
-    /// ```c
-    /// PDEVICE_OBJECT DeviceObject = FileObject->DeviceObject;
-    ///
-    /// POBJECT_HEADER_NAME_INFO DeviceNameInfo = ObjectNameInfo(DeviceObject);
-    /// POBJECT_HEADER_NAME_INFO DeviceDirectoryNameInfo = DeviceNameInfo->Directory
-    ///     ? ObjectNameInfo(DeviceNameInfo->Directory)
-    ///     : NULL;
-    ///
-    /// if (DeviceDirectoryNameInfo->Name != NULL) {
-    ///     FullPath += '\\' + DeviceDirectoryNameInfo->Name;
-    /// }
-    ///
-    /// if (DeviceNameInfo->Name != NULL) {
-    ///     FullPath += '\\' + DeviceNameInfo->Name;
-    /// }
-    ///
-    /// FullPath += FileObject->FileName;
-    ///
-    /// return FullPath;
-    /// ```
-    ///
-    /// # Panics
-    /// Panics if the provided object is not a file object.
-    pub fn file_object_to_full_path(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        file_object: Va,
-    ) -> Result<String, VmiError> {
-        let file = match self.parse_file_object(vmi, registers, file_object)? {
-            WindowsObject::File(file) => file,
-            _ => panic!("Not a file object"),
-        };
+    /// The thread is waiting for its process to be swapped in
+    /// (`WaitingForProcessInSwap`).
+    WaitingForProcessInSwap,
 
-        let device = self.object_name(vmi, registers, file.device_object)?;
-        let directory = match &device {
-            Some(device) => self.object_name(vmi, registers, device.directory)?,
-            None => None,
-        };
+    /// Unknown state.
+    Other(u8),
+}
 
-        let mut result = String::new();
-        if let Some(directory) = directory {
-            result.push('\\');
-            result.push_str(&directory.name);
+impl From<u8> for WindowsThreadState {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Initialized,
+            1 => Self::Ready,
+            2 => Self::Running,
+            3 => Self::Standby,
+            4 => Self::Terminated,
+            5 => Self::Waiting,
+            6 => Self::Transition,
+            7 => Self::DeferredReady,
+            8 => Self::GateWaitObsolete,
+            9 => Self::WaitingForProcessInSwap,
+            _ => Self::Other(value),
         }
+    }
+}
 
-        if let Some(device) = device {
-            result.push('\\');
-            result.push_str(&device.name);
-        }
+/// The reason a thread is waiting (`_KTHREAD.WaitReason`, `_KWAIT_REASON`).
+///
+/// The `Wr*` reasons are used for waits requested by the kernel itself,
+/// the others mirror the `KWAIT_REASON` values passed to the wait
+/// functions by drivers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsWaitReason {
+    /// Waiting on behalf of a kernel component.
+    Executive,
 
-        result.push_str(&file.filename);
+    /// Waiting for a free page.
+    FreePage,
 
-        Ok(result)
-    }
+    /// Waiting for a page to be read in.
+    PageIn,
 
-    /// Extracts the filename from a `CONTROL_AREA` structure.
-    ///
-    /// This function first reads the `FilePointer` field of the `CONTROL_AREA`
-    /// structure, then reads the `FileName` field of the `FILE_OBJECT`
-    /// structure pointed by the `FilePointer`.
-    ///
-    /// # Equivalent C pseudo-code
-    ///
-    /// ```c
-    /// PFILE_OBJECT FileObject = ControlArea->FilePointer;
-    /// UNICODE_STRING FileName = FileObject->FileName;
-    /// return FileName;
-    /// ```
-    pub fn control_area_to_filename(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        control_area: Va,
-    ) -> Result<String, VmiError> {
-        let EX_FAST_REF = &self.offsets.common._EX_FAST_REF;
-        let CONTROL_AREA = &self.offsets.common._CONTROL_AREA;
+    /// Waiting for a pool allocation.
+    PoolAllocation,
 
-        let file_pointer = vmi.read_va(
-            registers.address_context(control_area + CONTROL_AREA.FilePointer.offset),
-            registers.address_width(),
-        )?;
+    /// Sleeping (e.g., `KeDelayExecutionThread`).
+    DelayExecution,
 
-        // The file pointer is in fact an `_EX_FAST_REF` structure,
-        // where the low bits are used to store the reference count.
-        debug_assert_eq!(EX_FAST_REF.RefCnt.offset, 0);
-        debug_assert_eq!(EX_FAST_REF.RefCnt.bit_position, 0);
-        let file_pointer = file_pointer & !((1 << EX_FAST_REF.RefCnt.bit_length) - 1);
-        //let file_pointer = file_pointer & !0xf;
+    /// Suspended.
+    Suspended,
 
-        self.file_object_to_filename(vmi, registers, file_pointer)
-    }
+    /// Waiting on a request from user mode (e.g., `WaitForSingleObject`).
+    UserRequest,
 
-    // endregion: File
+    /// Kernel wait on behalf of a kernel component.
+    WrExecutive,
 
-    // region: Handle
+    /// Kernel wait for a free page.
+    WrFreePage,
 
-    /// Checks if the given handle is a kernel handle.
-    pub fn is_kernel_handle(
-        &self,
-        _vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        handle: u64,
-    ) -> Result<bool, VmiError> {
-        const KERNEL_HANDLE_MASK32: u64 = 0x8000_0000;
-        const KERNEL_HANDLE_MASK64: u64 = 0xffff_ffff_8000_0000;
+    /// Kernel wait for a page to be read in.
+    WrPageIn,
 
-        match registers.effective_address_width() {
-            4 => Ok(handle & KERNEL_HANDLE_MASK32 == KERNEL_HANDLE_MASK32),
-            8 => Ok(handle & KERNEL_HANDLE_MASK64 == KERNEL_HANDLE_MASK64),
-            _ => panic!("Unsupported address width"),
-        }
-    }
+    /// Kernel wait for a pool allocation.
+    WrPoolAllocation,
 
-    /// Retrieves the handle table for a given process.
-    pub fn handle_table(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<WindowsHandleTable, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
-        let HANDLE_TABLE = &self.offsets.common._HANDLE_TABLE;
+    /// Kernel sleep.
+    WrDelayExecution,
 
-        let handle_table = vmi.read_va(
-            registers.address_context(process.0 + EPROCESS.ObjectTable.offset),
-            registers.address_width(),
-        )?;
+    /// Suspended by the kernel.
+    WrSuspended,
 
-        let table_code = vmi.read_address(
-            registers.address_context(handle_table + HANDLE_TABLE.TableCode.offset),
-            registers.address_width(),
-        )?;
+    /// Kernel wait on a request from user mode.
+    WrUserRequest,
 
-        Ok(WindowsHandleTable { table_code })
-    }
+    /// Waiting on an event pair (`WrSpare0` on newer versions).
+    WrEventPair,
 
-    /// Looks up a specific handle table entry for a given process and handle.
-    pub fn handle_table_entry(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        handle: u64,
-    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
-        let mut process = process;
-        let mut handle = handle;
+    /// Waiting on a queue (e.g., a worker thread or an I/O completion port).
+    WrQueue,
 
-        if self.is_kernel_handle(vmi, registers, handle)? {
-            process = self.system_process(vmi, registers)?;
-            handle &= 0x7fff_ffff;
-        }
+    /// Waiting to receive an LPC/ALPC message.
+    WrLpcReceive,
 
-        let handle_table = self.handle_table(vmi, registers, process)?;
-        let entry_address =
-            self.handle_table_entry_lookup(vmi, registers, &handle_table, handle)?;
-        self.parse_handle_table_entry(vmi, registers, entry_address)
-    }
+    /// Waiting for a reply to an LPC/ALPC message.
+    WrLpcReply,
 
-    /// Performs a lookup in the handle table to find the address of a handle
-    /// table entry.
-    ///
-    /// Implements the multi-level handle table lookup algorithm used by
-    /// Windows. Returns the virtual address of the handle table entry.
-    pub fn handle_table_entry_lookup(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        handle_table: &WindowsHandleTable,
-        handle: u64,
-    ) -> Result<Va, VmiError> {
-        const SIZEOF_POINTER: u64 = 8;
-        const SIZEOF_HANDLE_TABLE_ENTRY: u64 = 16;
+    /// Waiting for virtual memory to be allocated.
+    WrVirtualMemory,
 
-        const LOWLEVEL_COUNT: u64 = 256; // (TABLE_PAGE_SIZE / sizeof(HANDLE_TABLE_ENTRY))
-        const MIDLEVEL_COUNT: u64 = 512; // (PAGE_SIZE / sizeof(PHANDLE_TABLE_ENTRY))
+    /// Waiting for a page to be written out.
+    WrPageOut,
 
-        const LEVEL_CODE_MASK: u64 = 3;
-        const HANDLE_VALUE_INC: u64 = 4;
+    /// Waiting at a rendezvous point.
+    WrRendezvous,
 
-        let level = handle_table.table_code & LEVEL_CODE_MASK;
-        let table = Va(handle_table.table_code - level);
+    /// Waiting on a keyed event.
+    WrKeyedEvent,
 
-        //
-        // The 2 least significant bits of a handle are available to the
-        // application and are ignored by the system.
-        //
+    /// Waiting for the thread to be terminated.
+    WrTerminated,
 
-        let mut index = handle & !0b11;
+    /// Waiting for the process to be swapped in.
+    WrProcessInSwap,
 
-        match level {
-            0 => Ok(table + index * (SIZEOF_HANDLE_TABLE_ENTRY / HANDLE_VALUE_INC)),
+    /// Throttled by CPU rate control.
+    WrCpuRateControl,
 
-            1 => {
-                let table2 = table;
-                let i = index % (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+    /// Waiting for a kernel stack to be expanded.
+    WrCalloutStack,
 
-                index -= i;
-                let j = index / (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+    /// Waiting in the kernel for an unspecified reason.
+    WrKernel,
 
-                let table1 = vmi.read_va(
-                    registers.address_context(table2 + j * SIZEOF_POINTER),
-                    registers.address_width(),
-                )?;
+    /// Waiting on an executive resource (`ERESOURCE`).
+    WrResource,
 
-                Ok(table1 + i * (SIZEOF_HANDLE_TABLE_ENTRY / HANDLE_VALUE_INC))
-            }
+    /// Waiting on a push lock.
+    WrPushLock,
 
-            2 => {
-                let table3 = table;
-                let i = index % (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+    /// Waiting on a mutex.
+    WrMutex,
 
-                index -= i;
-                let mut k = index / (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+    /// The quantum of the thread ended.
+    WrQuantumEnd,
 
-                let j = k % MIDLEVEL_COUNT;
-                k -= j;
-                k /= MIDLEVEL_COUNT;
+    /// Rescheduled by a dispatch interrupt.
+    WrDispatchInt,
 
-                let table2 = vmi.read_va(
-                    registers.address_context(table3 + k * SIZEOF_POINTER),
-                    registers.address_width(),
-                )?;
-                let table1 = vmi.read_va(
-                    registers.address_context(table2 + j * SIZEOF_POINTER),
-                    registers.address_width(),
-                )?;
+    /// Preempted by a higher priority thread.
+    WrPreempted,
 
-                Ok(table1 + i * (SIZEOF_HANDLE_TABLE_ENTRY / HANDLE_VALUE_INC))
-            }
+    /// Yielded its execution.
+    WrYieldExecution,
 
-            _ => unreachable!(),
-        }
-    }
+    /// Waiting on a fast mutex.
+    WrFastMutex,
 
-    /// Converts a handle to the virtual address of the corresponding object.
-    ///
-    /// Uses the handle table entry lookup to find the object address for a
-    /// given handle.
-    pub fn handle_to_object_address(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        handle: u64,
-    ) -> Result<Option<Va>, VmiError> {
-        Ok(self
-            .handle_table_entry(vmi, registers, process, handle)?
-            .map(|entry| entry.object))
-    }
+    /// Waiting on a guarded mutex.
+    WrGuardedMutex,
 
-    /// Retrieves the WindowsObject corresponding to a given handle in a
-    /// process.
-    pub fn handle_to_object(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        handle: u64,
-    ) -> Result<Option<WindowsObject>, VmiError> {
-        match self.handle_to_object_address(vmi, registers, process, handle)? {
-            Some(object) => self.object_from_address(vmi, registers, object),
-            None => Ok(None),
-        }
-    }
+    /// Waiting for a rundown protection to be released.
+    WrRundown,
 
-    /// Parses a Windows object from its memory address.
-    ///
-    /// Determines the object type and calls the appropriate parsing method.
-    /// Currently supports File and Section object types.
-    pub fn object_from_address(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-    ) -> Result<Option<WindowsObject>, VmiError> {
-        match self.object_type(vmi, registers, object)? {
-            Some(WindowsObjectType::File) => {
-                Ok(Some(self.parse_file_object(vmi, registers, object)?))
-            }
-            Some(WindowsObjectType::Section) => self.parse_section_object(vmi, registers, object),
-            _ => Ok(None),
+    /// Waiting for an alert by thread ID (e.g., an `SRWLOCK` or a critical
+    /// section).
+    WrAlertByThreadId,
+
+    /// Preemption deferred.
+    WrDeferredPreempt,
+
+    /// Waiting for a physical page fault to be resolved.
+    WrPhysicalFault,
+
+    /// Waiting on an I/O ring.
+    WrIoRing,
+
+    /// Waiting on the MDL cache.
+    WrMdlCache,
+
+    /// Waiting for an RCU grace period.
+    WrRcu,
+
+    /// Unknown wait reason.
+    Other(u8),
+}
+
+impl From<u8> for WindowsWaitReason {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Executive,
+            1 => Self::FreePage,
+            2 => Self::PageIn,
+            3 => Self::PoolAllocation,
+            4 => Self::DelayExecution,
+            5 => Self::Suspended,
+            6 => Self::UserRequest,
+            7 => Self::WrExecutive,
+            8 => Self::WrFreePage,
+            9 => Self::WrPageIn,
+            10 => Self::WrPoolAllocation,
+            11 => Self::WrDelayExecution,
+            12 => Self::WrSuspended,
+            13 => Self::WrUserRequest,
+            14 => Self::WrEventPair,
+            15 => Self::WrQueue,
+            16 => Self::WrLpcReceive,
+            17 => Self::WrLpcReply,
+            18 => Self::WrVirtualMemory,
+            19 => Self::WrPageOut,
+            20 => Self::WrRendezvous,
+            21 => Self::WrKeyedEvent,
+            22 => Self::WrTerminated,
+            23 => Self::WrProcessInSwap,
+            24 => Self::WrCpuRateControl,
+            25 => Self::WrCalloutStack,
+            26 => Self::WrKernel,
+            27 => Self::WrResource,
+            28 => Self::WrPushLock,
+            29 => Self::WrMutex,
+            30 => Self::WrQuantumEnd,
+            31 => Self::WrDispatchInt,
+            32 => Self::WrPreempted,
+            33 => Self::WrYieldExecution,
+            34 => Self::WrFastMutex,
+            35 => Self::WrGuardedMutex,
+            36 => Self::WrRundown,
+            37 => Self::WrAlertByThreadId,
+            38 => Self::WrDeferredPreempt,
+            39 => Self::WrPhysicalFault,
+            40 => Self::WrIoRing,
+            41 => Self::WrMdlCache,
+            42 => Self::WrRcu,
+            _ => Self::Other(value),
         }
     }
+}
 
-    /// Parses a `FILE_OBJECT` structure.
+/// Represents an asynchronous procedure call queued to a thread (`_KAPC`).
+#[derive(Debug)]
+pub struct WindowsApc {
+    /// The address of the `_KAPC` structure.
+    pub address: Va,
+
+    /// Whether the APC is queued in the user-mode APC list.
+    pub user_mode: bool,
+
+    /// The `KernelRoutine` field.
+    pub kernel_routine: Va,
+
+    /// The `RundownRoutine` field.
+    pub rundown_routine: Va,
+
+    /// The `NormalRoutine` field.
     ///
-    /// Extracts the device object and filename from the `FILE_OBJECT`.
-    /// Returns a [`WindowsObject::File`] variant.
-    fn parse_file_object(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-    ) -> Result<WindowsObject, VmiError> {
-        let FILE_OBJECT = &self.offsets.common._FILE_OBJECT;
+    /// For user-mode APCs, this is the user-mode function that will be
+    /// called when the APC is delivered. APCs queued to WoW64 threads
+    /// store the routine in an encoded form.
+    pub normal_routine: Va,
 
-        let device_object = vmi.read_va(
-            registers.address_context(object + FILE_OBJECT.DeviceObject.offset),
-            registers.address_width(),
-        )?;
+    /// The `NormalContext` field.
+    pub normal_context: Va,
 
-        let filename = self.file_object_to_filename(vmi, registers, object)?;
-        Ok(WindowsObject::File(WindowsFileObject {
-            device_object,
-            filename,
-        }))
+    /// The `SystemArgument1` field.
+    pub system_argument1: Va,
+
+    /// The `SystemArgument2` field.
+    pub system_argument2: Va,
+}
+
+/// A guard that keeps a physical page locked for as long as it is alive.
+///
+/// Created by [`WindowsOs::pfn_guard`]. The reference count of the page is
+/// incremented when the guard is created and decremented when the guard is
+/// dropped, even if the analysis returns early or panics.
+pub struct WindowsPfnGuard<'a, Driver>
+where
+    Driver: VmiDriver,
+    Driver::Architecture: Architecture + ArchAdapter<Driver>,
+{
+    os: &'a WindowsOs<Driver>,
+    vmi: &'a VmiCore<Driver>,
+    registers: <Driver::Architecture as Architecture>::Registers,
+    pfn: Gfn,
+    reference_count: Option<u16>,
+}
+
+impl<Driver> WindowsPfnGuard<'_, Driver>
+where
+    Driver: VmiDriver,
+    Driver::Architecture: Architecture + ArchAdapter<Driver>,
+{
+    /// Returns the page frame number of the locked page.
+    pub fn pfn(&self) -> Gfn {
+        self.pfn
     }
 
-    /// Parses a Windows section object.
+    /// Checks whether the page was locked.
     ///
-    /// Delegates to version-specific parsing methods based on the available
-    /// offsets. Currently supports `SECTION_OBJECT` and `SECTION` structures.
-    /// Returns a [`WindowsObject::Section`] variant.
-    fn parse_section_object(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-    ) -> Result<Option<WindowsObject>, VmiError> {
-        match &self.offsets.ext {
-            Some(OffsetsExt::V1(offsets)) => Ok(Some(
-                self.parse_section_object_v1(vmi, registers, object, offsets)?,
-            )),
-            Some(OffsetsExt::V2(offsets)) => Ok(Some(
-                self.parse_section_object_v2(vmi, registers, object, offsets)?,
-            )),
-            None => panic!("OffsetsExt not set"),
+    /// The page is not locked if it was not in a valid state when the guard
+    /// was created.
+    pub fn is_locked(&self) -> bool {
+        self.reference_count.is_some()
+    }
+
+    /// Returns the reference count of the page after it was locked.
+    pub fn reference_count(&self) -> Option<u16> {
+        self.reference_count
+    }
+}
+
+impl<Driver> Drop for WindowsPfnGuard<'_, Driver>
+where
+    Driver: VmiDriver,
+    Driver::Architecture: Architecture + ArchAdapter<Driver>,
+{
+    fn drop(&mut self) {
+        if self.reference_count.is_none() {
+            return;
+        }
+
+        // `unlock_pfn` re-checks the state of the page and leaves the
+        // reference count untouched if the page is no longer valid.
+        match self.os.unlock_pfn(self.vmi, &self.registers, self.pfn) {
+            Ok(Some(_)) => {}
+            Ok(None) => tracing::warn!(pfn = %self.pfn, "Page is no longer valid"),
+            Err(err) => tracing::error!(pfn = %self.pfn, ?err, "Failed to unlock the page"),
         }
     }
+}
 
-    fn parse_section_object_v1(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-        offsets: &v1::Offsets,
-    ) -> Result<WindowsObject, VmiError> {
-        let SECTION_OBJECT = &offsets._SECTION_OBJECT;
-        let SEGMENT_OBJECT = &offsets._SEGMENT_OBJECT;
-        let MMSECTION_FLAGS = &self.offsets.common._MMSECTION_FLAGS;
+/// The boot loader parameter block.
+///
+/// Built by the boot loader and handed over to the kernel. It is only
+/// available early in the boot, before the kernel frees it at the end of
+/// phase 1 initialization.
+#[derive(Debug)]
+pub struct WindowsLoaderBlock {
+    /// The address of the `_LOADER_PARAMETER_BLOCK` structure.
+    pub address: Va,
 
-        let section = StructReader::new(
-            vmi,
-            registers.address_context(object),
-            SECTION_OBJECT.effective_len(),
-        )?;
-        let starting_vpn = section.read(SECTION_OBJECT.StartingVa)?;
-        let ending_vpn = section.read(SECTION_OBJECT.EndingVa)?;
-        let segment = section.read(SECTION_OBJECT.Segment)?;
+    /// The boot options (`LoadOptions`), e.g., `NOEXECUTE=OPTIN DEBUG`.
+    pub load_options: String,
 
-        let segment = StructReader::new(
-            vmi,
-            registers.address_context(segment.into()),
-            SEGMENT_OBJECT.effective_len(),
-        )?;
-        let size = segment.read(SEGMENT_OBJECT.SizeOfSegment)?;
-        let flags = segment.read(SEGMENT_OBJECT.MmSectionFlags)?;
-        let flags = vmi.read_u32(registers.address_context(flags.into()))? as u64;
+    /// The ARC name of the boot device (`ArcBootDeviceName`).
+    pub arc_boot_device_name: String,
 
-        let file = MMSECTION_FLAGS.File.value_from(flags) != 0;
-        let _image = MMSECTION_FLAGS.Image.value_from(flags) != 0;
+    /// The modules loaded by the boot loader (`LoadOrderListHead`).
+    pub modules: Vec<OsModule>,
 
-        let kind = if file {
-            let control_area = vmi.read_va(
-                registers.address_context(object + SEGMENT_OBJECT.ControlArea.offset),
-                registers.address_width(),
-            )?;
+    /// The boot drivers (`BootDriverListHead`).
+    pub boot_drivers: Vec<WindowsBootDriver>,
 
-            let path = self.control_area_to_filename(vmi, registers, control_area);
+    /// The address of the NLS data (`NlsData`, `_NLS_DATA_BLOCK*`).
+    pub nls_data: Va,
+}
 
-            OsRegionKind::Mapped(OsMapped {
-                path: path.map(Some),
-            })
-        }
-        else {
-            OsRegionKind::Private
-        };
+/// A boot driver listed in the loader parameter block.
+#[derive(Debug)]
+pub struct WindowsBootDriver {
+    /// The path of the driver file (`FilePath`).
+    pub file_path: String,
 
-        Ok(WindowsObject::Section(WindowsSectionObject {
-            region: OsRegion {
-                start: Va(starting_vpn << 12),
-                end: Va((ending_vpn + 1) << 12),
-                protection: MemoryAccess::default(),
-                kind,
-            },
-            size,
-        }))
-    }
+    /// The registry path of the driver service (`RegistryPath`).
+    pub registry_path: String,
 
-    fn parse_section_object_v2(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-        offsets: &v2::Offsets,
-    ) -> Result<WindowsObject, VmiError> {
-        let SECTION = &offsets._SECTION;
-        let MMSECTION_FLAGS = &self.offsets.common._MMSECTION_FLAGS;
+    /// The address of the loader entry of the driver (`LdrEntry`), if the
+    /// driver was loaded.
+    pub loader_entry: Option<Va>,
+}
 
-        let section = StructReader::new(
-            vmi,
-            registers.address_context(object),
-            SECTION.effective_len(),
-        )?;
-        let starting_vpn = section.read(SECTION.StartingVpn)?;
-        let ending_vpn = section.read(SECTION.EndingVpn)?;
-        let size = section.read(SECTION.SizeOfSection)?;
-        let flags = section.read(SECTION.Flags)?;
+/// A system service dispatch table (`KiServiceTable` or `W32pServiceTable`).
+#[derive(Debug)]
+pub struct WindowsServiceTable {
+    /// The address of the table (`_KSERVICE_TABLE_DESCRIPTOR.Base`).
+    pub address: Va,
 
-        let file = MMSECTION_FLAGS.File.value_from(flags) != 0;
-        let _image = MMSECTION_FLAGS.Image.value_from(flags) != 0;
+    /// The entries of the table, indexed by the system service number.
+    pub entries: Vec<WindowsServiceTableEntry>,
+}
 
-        //
-        // We have to distinguish between FileObject and ControlArea.
-        // Here's an excerpt from _SECTION:
-        //
-        //     union {
-        //       union {
-        //         PCONTROL_AREA ControlArea;
-        //         PFILE_OBJECT FileObject;
-        //         struct {
-        //           ULONG_PTR RemoteImageFileObject : 1;
-        //           ULONG_PTR RemoteDataFileObject : 1;
-        //         };
-        //       };
-        //     };
-        //
-        // Based on information from Geoff Chappell's website, we can determine whether
-        // ControlArea is in fact FileObject by checking the lowest 2 bits of the
-        // pointer.
-        //
-        // ref: https://www.geoffchappell.com/studies/windows/km/ntoskrnl/inc/ntos/mi/section.htm
-        //
+impl WindowsServiceTable {
+    /// Returns an iterator over the entries that point outside of the
+    /// expected modules.
+    pub fn hooked(&self) -> impl Iterator<Item = &WindowsServiceTableEntry> {
+        self.entries.iter().filter(|entry| entry.hooked)
+    }
+}
 
-        let kind = if file {
-            let control_area = vmi.read_va(
-                registers.address_context(object + SECTION.ControlArea.offset),
-                registers.address_width(),
-            )?;
+/// An entry of a system service dispatch table.
+#[derive(Debug)]
+pub struct WindowsServiceTableEntry {
+    /// The system service number, relative to the table.
+    pub index: u32,
 
-            let path = if u64::from(control_area) & 0x3 != 0 {
-                let file_object = control_area;
-                self.file_object_to_filename(vmi, registers, file_object)
-            }
-            else {
-                self.control_area_to_filename(vmi, registers, control_area)
-            };
+    /// The resolved address of the service routine.
+    pub target: Va,
 
-            OsRegionKind::Mapped(OsMapped {
-                path: path.map(Some),
-            })
-        }
-        else {
-            OsRegionKind::Private
-        };
+    /// The name of the kernel module containing the service routine.
+    pub module: Option<String>,
 
-        Ok(WindowsObject::Section(WindowsSectionObject {
-            region: OsRegion {
-                start: Va(starting_vpn << 12),
-                end: Va((ending_vpn + 1) << 12),
-                protection: MemoryAccess::default(),
-                kind,
-            },
-            size,
-        }))
-    }
+    /// Whether the service routine lies outside of the module that owns
+    /// the table (`ntoskrnl.exe` or `win32k*.sys`).
+    pub hooked: bool,
+}
 
-    fn parse_handle_table_entry(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        entry: Va,
-    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
-        match &self.offsets.ext {
-            Some(OffsetsExt::V1(offsets)) => {
-                self.parse_handle_table_entry_v1(vmi, registers, entry, offsets)
-            }
-            Some(OffsetsExt::V2(offsets)) => {
-                self.parse_handle_table_entry_v2(vmi, registers, entry, offsets)
-            }
-            None => panic!("OffsetsExt not set"),
-        }
+/// A HAL dispatch table (`HalDispatchTable` or `HalPrivateDispatchTable`).
+#[derive(Debug)]
+pub struct WindowsHalDispatchTable {
+    /// The address of the table.
+    pub address: Va,
+
+    /// The `Version` field of the table.
+    pub version: u32,
+
+    /// The function pointers of the table.
+    pub entries: Vec<WindowsHalDispatchEntry>,
+}
+
+impl WindowsHalDispatchTable {
+    /// Returns an iterator over the entries that point outside of the
+    /// expected modules.
+    pub fn hooked(&self) -> impl Iterator<Item = &WindowsHalDispatchEntry> {
+        self.entries.iter().filter(|entry| entry.hooked)
     }
+}
 
-    fn parse_handle_table_entry_v1(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        entry: Va,
-        offsets: &v1::Offsets,
-    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
-        const OBJ_PROTECT_CLOSE: u64 = 0x00000001;
-        const OBJ_INHERIT: u64 = 0x00000002;
-        const OBJ_AUDIT_OBJECT_CLOSE: u64 = 0x00000004;
-        const OBJ_HANDLE_ATTRIBUTES: u64 = OBJ_PROTECT_CLOSE | OBJ_INHERIT | OBJ_AUDIT_OBJECT_CLOSE;
+/// An entry of a HAL dispatch table.
+#[derive(Debug)]
+pub struct WindowsHalDispatchEntry {
+    /// The name of the field (e.g., `HalQuerySystemInformation`).
+    pub name: String,
 
-        let HANDLE_TABLE_ENTRY = &offsets._HANDLE_TABLE_ENTRY;
-        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
+    /// The offset of the field within the table.
+    pub offset: u64,
 
-        let handle_table_entry = StructReader::new(
-            vmi,
-            registers.address_context(entry),
-            HANDLE_TABLE_ENTRY.effective_len(),
-        )?;
-        let object = handle_table_entry.read(HANDLE_TABLE_ENTRY.Object)?;
-        let attributes = handle_table_entry.read(HANDLE_TABLE_ENTRY.ObAttributes)?;
-        let granted_access = handle_table_entry.read(HANDLE_TABLE_ENTRY.GrantedAccess)? as u32;
+    /// The function pointer.
+    pub target: Va,
 
-        let object = Va(object & !OBJ_HANDLE_ATTRIBUTES);
-        let object = object + OBJECT_HEADER.Body.offset;
+    /// The name of the kernel module containing the function.
+    pub module: Option<String>,
 
-        let attributes = (attributes & OBJ_HANDLE_ATTRIBUTES) as u32;
+    /// Whether the function lies outside of `ntoskrnl.exe` and `hal.dll`.
+    ///
+    /// NULL entries are never considered hooked.
+    pub hooked: bool,
+}
 
-        Ok(Some(WindowsHandleTableEntry {
-            object,
-            attributes,
-            granted_access,
-        }))
-    }
+/// A bugcheck in progress, from `KiBugCheckData`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsBugCheck {
+    /// The bugcheck (stop) code (e.g., `0x0000003B` for
+    /// `SYSTEM_SERVICE_EXCEPTION`).
+    pub code: u32,
 
-    fn parse_handle_table_entry_v2(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        entry: Va,
-        offsets: &v2::Offsets,
-    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
-        let HANDLE_TABLE_ENTRY = &offsets._HANDLE_TABLE_ENTRY;
-        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
+    /// The four bugcheck parameters.
+    ///
+    /// Their meaning depends on the bugcheck code.
+    pub parameters: [u64; 4],
+}
 
-        #[repr(C)]
-        #[derive(Debug, Copy, Clone, FromBytes, IntoBytes)]
-        #[allow(non_camel_case_types, non_snake_case)]
-        struct _HANDLE_TABLE_ENTRY {
-            LowValue: u64,
-            HighValue: u64,
-        }
+/// The `KiWaitNever` and `KiWaitAlways` values.
+#[derive(Debug, Clone, Copy)]
+struct WindowsWaitKeys {
+    wait_never: u64,
+    wait_always: u64,
+}
 
-        // Fetch the handle table entry
-        let handle_table_entry =
-            vmi.read_struct::<_HANDLE_TABLE_ENTRY>(registers.address_context(entry))?;
+/// The kernel debugger data block (`KDDEBUGGER_DATA64`).
+///
+/// Only the most commonly used fields are decoded. Other fields can be
+/// read from the decoded contents with [`read_u64`].
+///
+/// [`read_u64`]: Self::read_u64
+#[derive(Debug, Clone)]
+pub struct WindowsKdDebuggerData {
+    /// The address of the block.
+    pub address: Va,
 
-        // Parse the handle table entry
-        let object_pointer_bits = HANDLE_TABLE_ENTRY
-            .ObjectPointerBits
-            .value_from(handle_table_entry.LowValue);
+    /// Whether the block was encoded in the guest memory.
+    pub encoded: bool,
 
-        let attributes = HANDLE_TABLE_ENTRY
-            .Attributes
-            .value_from(handle_table_entry.LowValue) as u32;
+    /// The `KernBase` field.
+    pub kernel_base: Va,
 
-        let granted_access = HANDLE_TABLE_ENTRY
-            .GrantedAccessBits
-            .value_from(handle_table_entry.HighValue) as u32;
+    /// The `PsLoadedModuleList` field.
+    pub ps_loaded_module_list: Va,
 
-        let object = Va(0xffff_0000_0000_0000 | object_pointer_bits << 4);
-        let object = object + OBJECT_HEADER.Body.offset;
+    /// The `PsActiveProcessHead` field.
+    pub ps_active_process_head: Va,
 
-        Ok(Some(WindowsHandleTableEntry {
-            object,
-            attributes,
-            granted_access,
-        }))
-    }
+    /// The `PspCidTable` field.
+    pub psp_cid_table: Va,
 
-    // endregion: Handle
+    /// The `ObpRootDirectoryObject` field.
+    pub obp_root_directory_object: Va,
 
-    // region: Kernel
+    /// The `MmPfnDatabase` field.
+    pub mm_pfn_database: Va,
 
-    /// Locates the Windows kernel in memory based on the CPU registers.
-    /// This function is architecture-specific.
+    /// The `MmHighestUserAddress` field.
+    pub mm_highest_user_address: Va,
+
+    /// The `NtBuildLab` field.
+    pub nt_build_lab: Va,
+
+    /// The `KiProcessorBlock` field.
+    pub ki_processor_block: Va,
+
+    /// The decoded contents of the block, as declared by the `Size` field
+    /// of its header.
+    pub data: Vec<u8>,
+}
+
+impl WindowsKdDebuggerData {
+    /// Reads a 64-bit field at the given offset of the decoded block.
     ///
-    /// On AMD64, the kernel is located by taking the `MSR_LSTAR` value and
-    /// reading the virtual memory page by page backwards until the `MZ` header
-    /// is found.
-    pub fn find_kernel(
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-    ) -> Result<Option<WindowsKernelInformation>, VmiError> {
-        Driver::Architecture::find_kernel(vmi, registers)
+    /// Returns `None` if the offset is past the end of the block.
+    pub fn read_u64(&self, offset: usize) -> Option<u64> {
+        let bytes = self.data.get(offset..offset + size_of::<u64>())?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
     }
+}
 
-    /// Retrieves the kernel information string.
+/// A registry hive loaded by the configuration manager.
+#[derive(Debug)]
+pub struct WindowsRegistryHive {
+    /// The address of the `_CMHIVE`.
+    pub hive: Va,
+
+    /// The `BaseBlock` field of the hive.
     ///
-    /// # Implementation Details
+    /// The address of the `_HBASE_BLOCK`, the in-memory copy of the first
+    /// page of the hive file.
+    pub base_block: Va,
+
+    /// The `FileFullPath` field of the hive.
     ///
-    /// The kernel information string is located by reading the `NtBuildLabEx`
-    /// symbol from the kernel image.
-    pub fn kernel_information_string_ex(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<<Driver as VmiDriver>::Architecture as Architecture>::Registers,
-    ) -> Result<Option<String>, VmiError> {
-        let NtBuildLabEx = match self.symbols.NtBuildLabEx {
-            Some(offset) => offset,
-            None => return Ok(None),
-        };
+    /// The path of the hive file (e.g.,
+    /// `\Device\HarddiskVolume2\Windows\System32\config\SYSTEM`).
+    /// Empty for volatile hives.
+    pub file_full_path: String,
 
-        if let Some(nt_build_lab_ex) = self.nt_build_lab_ex.borrow().as_ref() {
-            return Ok(Some(nt_build_lab_ex.clone()));
-        }
+    /// The `FileUserName` field of the hive.
+    ///
+    /// The path of the hive file as it was requested by the caller that
+    /// loaded the hive (e.g., `\SystemRoot\System32\Config\SOFTWARE`).
+    pub file_user_name: String,
 
-        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
-        let nt_build_lab_ex =
-            vmi.read_string(registers.address_context(kernel_image_base + NtBuildLabEx))?;
-        *self.nt_build_lab_ex.borrow_mut() = Some(nt_build_lab_ex.clone());
+    /// The `HiveRootPath` field of the hive.
+    ///
+    /// The registry path the hive is mounted at (e.g.,
+    /// `\REGISTRY\MACHINE\SYSTEM`).
+    pub root_path: Option<String>,
+}
 
-        Ok(Some(nt_build_lab_ex))
-    }
+/// The guest's view of the hypervisor it runs under.
+#[derive(Debug, Clone, Default)]
+pub struct WindowsHypervisorInformation {
+    /// Whether the hypervisor-present bit (`CPUID.1:ECX[31]`) is set in the
+    /// guest CPUID policy.
+    ///
+    /// `None` if the driver can't query the guest CPUID policy.
+    pub cpuid_hypervisor_present: Option<bool>,
 
-    /// Retrieves information about a kernel module from a pointer to
-    /// `KLDR_DATA_TABLE_ENTRY`
-    pub fn kernel_module(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        addr: Va, // _KLDR_DATA_TABLE_ENTRY*
-    ) -> Result<OsModule, VmiError> {
-        let KLDR_DATA_TABLE_ENTRY = &self.offsets.common._KLDR_DATA_TABLE_ENTRY;
+    /// The hypervisor vendor signature reported by `CPUID.40000000h`
+    /// (e.g., `Microsoft Hv` or `XenVMMXenVMM`).
+    ///
+    /// `None` if the driver can't query the guest CPUID policy or if no
+    /// hypervisor is advertised.
+    pub vendor: Option<String>,
 
-        let base_address = vmi.read_va(
-            registers.address_context(addr + KLDR_DATA_TABLE_ENTRY.DllBase.offset),
-            registers.address_width(),
-        )?;
+    /// The `HvlHypervisorConnected` variable.
+    ///
+    /// Whether the kernel has connected to the hypervisor, i.e., whether it
+    /// runs as an enlightened guest.
+    pub connected: Option<bool>,
 
-        let size = vmi
-            .read_u32(registers.address_context(addr + KLDR_DATA_TABLE_ENTRY.SizeOfImage.offset))?
-            as u64;
+    /// The `HvlEnlightenments` variable.
+    ///
+    /// The enlightenments recommended by the hypervisor (cached
+    /// `CPUID.40000004h:EAX`).
+    pub enlightenments: Option<u32>,
 
-        let name = self.read_unicode_string(
-            vmi,
-            registers.address_context(addr + KLDR_DATA_TABLE_ENTRY.BaseDllName.offset),
-        )?;
+    /// The `HvlpFlags` variable.
+    pub flags: Option<u32>,
 
-        Ok(OsModule {
-            base_address,
-            size,
-            name,
-        })
+    /// The `HvlpHypercallCodeVa` variable.
+    ///
+    /// The virtual address of the hypercall page, `None` if the symbol is
+    /// not available or if the page is not mapped.
+    pub hypercall_page: Option<Va>,
+}
+
+impl WindowsHypervisorInformation {
+    /// Checks whether the kernel uses the hypervisor enlightenments.
+    ///
+    /// This is the case if the kernel has connected to the hypervisor or
+    /// has mapped the hypercall page.
+    pub fn is_enlightened(&self) -> bool {
+        self.connected == Some(true) || self.hypercall_page.is_some()
     }
+}
 
-    // endregion: Kernel
+/// A kernel timer found in the per-processor timer tables.
+#[derive(Debug)]
+pub struct WindowsTimer {
+    /// The address of the `_KTIMER`.
+    pub timer: Va,
 
-    // region: Memory
+    /// The index of the processor whose timer table holds the timer.
+    pub processor: u32,
 
-    /// Retrieves information about a Virtual Address Descriptor (VAD) for a
-    /// given address.
+    /// The `DueTime` field of the timer.
     ///
-    /// This method extracts details such as the starting and ending virtual
-    /// page numbers, VAD type, memory protection, and other flags
-    /// associated with the specified VAD.
-    pub fn vad(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        vad: Va,
-    ) -> Result<WindowsVad, VmiError> {
-        let MMVAD_FLAGS = &self.offsets.common._MMVAD_FLAGS;
-        let MMVAD_SHORT = &self.offsets.common._MMVAD_SHORT;
+    /// The absolute interrupt time, in 100-nanosecond units, at which the
+    /// timer expires.
+    pub due_time: u64,
 
-        let mmvad = StructReader::new(
-            vmi,
-            registers.address_context(vad),
-            MMVAD_SHORT.effective_len(),
-        )?;
-        let starting_vpn_low = mmvad.read(MMVAD_SHORT.StartingVpn)?;
-        let ending_vpn_low = mmvad.read(MMVAD_SHORT.EndingVpn)?;
-        let starting_vpn_high = match MMVAD_SHORT.StartingVpnHigh {
-            Some(StartingVpnHigh) => mmvad.read(StartingVpnHigh)?,
-            None => 0,
-        };
-        let ending_vpn_high = match MMVAD_SHORT.EndingVpnHigh {
-            Some(EndingVpnHigh) => mmvad.read(EndingVpnHigh)?,
-            None => 0,
-        };
+    /// The `Period` field of the timer, in milliseconds.
+    ///
+    /// Zero for one-shot timers.
+    pub period: u32,
 
-        let starting_vpn = (starting_vpn_high << 32) | starting_vpn_low;
-        let ending_vpn = (ending_vpn_high << 32) | ending_vpn_low;
+    /// The address of the `_KDPC` queued when the timer expires.
+    ///
+    /// Null if the timer has no DPC associated.
+    pub dpc: Va,
 
-        let vad_flags = mmvad.read(MMVAD_SHORT.VadFlags)?;
-        let vad_type = MMVAD_FLAGS.VadType.value_from(vad_flags) as u8;
-        let protection = MMVAD_FLAGS.Protection.value_from(vad_flags) as u8;
-        let private_memory = MMVAD_FLAGS.PrivateMemory.value_from(vad_flags) != 0;
+    /// The `DeferredRoutine` of the DPC.
+    pub routine: Va,
 
-        // If `MMVAD_FLAGS.MemCommit` is present (Windows 7), then we fetch the
-        // value from it. Otherwise, we load the `VadFlags1` field from the VAD
-        // and fetch it from there.
-        let mem_commit = match MMVAD_FLAGS.MemCommit {
-            // `MemCommit` is present in `MMVAD_FLAGS`
-            Some(MemCommit) => MemCommit.value_from(vad_flags) != 0,
+    /// The `DeferredContext` of the DPC.
+    pub context: Va,
 
-            None => match (&self.offsets.ext, MMVAD_SHORT.VadFlags1) {
-                // `MemCommit` is present in `MMVAD_FLAGS1`
-                (Some(OffsetsExt::V2(offsets)), Some(VadFlags1)) => {
-                    let MMVAD_FLAGS1 = &offsets._MMVAD_FLAGS1;
-                    let vad_flags1 = mmvad.read(VadFlags1)?;
-                    MMVAD_FLAGS1.MemCommit.value_from(vad_flags1) != 0
-                }
-                _ => {
-                    panic!("Failed to read MemCommit from VAD");
-                }
-            },
-        };
+    /// The name of the kernel module containing the DPC routine.
+    pub module: Option<String>,
 
-        let left_child = Va(mmvad.read(MMVAD_SHORT.Left)?);
-        let right_child = Va(mmvad.read(MMVAD_SHORT.Right)?);
+    /// Whether the DPC routine lies outside of all loaded kernel modules.
+    pub outside_modules: bool,
+}
 
-        Ok(WindowsVad {
-            starting_vpn,
-            ending_vpn,
-            vad_type,
-            protection,
-            private_memory,
-            mem_commit,
-            left_child,
-            right_child,
-        })
-    }
+/// A large pool allocation tracked in the big page table.
+#[derive(Debug, Clone, Copy)]
+pub struct WindowsBigPoolAllocation {
+    /// The address of the allocation.
+    pub address: Va,
 
-    /// Locates the `VadRoot` for a given process. Returns the address of the
-    /// root node.
-    ///
-    /// # Equivalent C pseudo-code
-    ///
-    /// ```c
-    /// // For Windows 7:
-    /// return Process->VadRoot->BalancedRoot;
-    ///
-    /// // For Windows 8.1 and later:
-    /// return Process->VadRoot->Root;
-    /// ```
-    pub fn vad_root(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<Va, VmiError> {
-        match &self.offsets.ext {
-            Some(OffsetsExt::V1(offsets)) => self.vad_root_v1(vmi, registers, process, offsets),
-            Some(OffsetsExt::V2(offsets)) => self.vad_root_v2(vmi, registers, process, offsets),
-            None => panic!("OffsetsExt not set"),
-        }
-    }
+    /// The pool tag of the allocation.
+    pub tag: [u8; 4],
 
-    fn vad_root_v1(
-        &self,
-        _vmi: &VmiCore<Driver>,
-        _registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        offsets: &v1::Offsets,
-    ) -> Result<Va, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
-        let MM_AVL_TABLE = &offsets._MM_AVL_TABLE;
+    /// The size of the allocation in bytes.
+    pub size: u64,
+}
 
-        // The `_MM_AVL_TABLE::BalancedRoot` field is of `_MMADDRESS_NODE` type,
-        // which represents the root.
-        let vad_root = process.0 + EPROCESS.VadRoot.offset + MM_AVL_TABLE.BalancedRoot.offset;
+/// The memory counters of a process.
+///
+/// Sizes are in pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsMemoryCounters {
+    /// The `PageFaultCount` field of the working set.
+    ///
+    /// The number of page faults, both hard and soft, the process has
+    /// incurred.
+    pub page_fault_count: u32,
 
-        Ok(vad_root)
-    }
+    /// The `WorkingSetSize` field of the working set.
+    pub working_set_size: u64,
 
-    fn vad_root_v2(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        offsets: &v2::Offsets,
-    ) -> Result<Va, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
-        let RTL_AVL_TREE = &offsets._RTL_AVL_TREE;
+    /// The `PeakWorkingSetSize` field of the working set.
+    pub peak_working_set_size: u64,
+}
 
-        // The `RTL_AVL_TREE::Root` field is of pointer type (`_RTL_BALANCED_NODE*`),
-        // thus we need to dereference it to get the actual node.
-        let vad_root = vmi.read_va(
-            registers
-                .address_context(process.0 + EPROCESS.VadRoot.offset + RTL_AVL_TREE.Root.offset),
-            registers.address_width(),
-        )?;
+/// The bounds of a stack.
+///
+/// Stacks grow downwards, so every valid stack address lies in the range
+/// `limit..base`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsStackBounds {
+    /// The highest address of the stack (exclusive).
+    pub base: Va,
+
+    /// The lowest address of the stack.
+    pub limit: Va,
+}
 
-        Ok(vad_root)
+impl WindowsStackBounds {
+    /// Checks whether the address lies within the stack.
+    pub fn contains(&self, address: Va) -> bool {
+        self.limit <= address && address < self.base
     }
+}
 
-    /// Retrieves the `VadHint` for a given process. Returns the address of the
-    /// hint node in the VAD tree.
-    ///
-    /// The VAD hint is an optimization used by Windows to speed up VAD lookups.
-    /// This method returns the address of the hint node in the VAD tree.
+/// A frame of a stack trace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsStackFrame {
+    /// The instruction pointer of the frame.
     ///
-    /// # Equivalent C pseudo-code
+    /// For every frame but the first one, this is the return address.
+    pub instruction_pointer: Va,
+
+    /// The stack pointer of the frame.
+    pub stack_pointer: Va,
+
+    /// The base address of the image containing the instruction pointer.
     ///
-    /// ```c
-    /// // For Windows 7:
-    /// return Process->VadRoot->NodeHint;
+    /// `None` if the instruction pointer does not belong to any known image.
+    pub image_base: Option<Va>,
+}
+
+/// A window station found in the object namespace.
+#[derive(Debug)]
+pub struct WindowsWindowStation {
+    /// The address of the window station object.
+    pub object: Va,
+
+    /// The name of the window station (e.g., `WinSta0`).
+    pub name: String,
+
+    /// The session the window station belongs to.
+    pub session_id: u32,
+}
+
+/// A terminal services session (`_MM_SESSION_SPACE`).
+///
+/// Created by [`WindowsOs::sessions`].
+#[derive(Debug)]
+pub struct WindowsSession {
+    address: Va,
+    id: u32,
+    processes: Vec<ProcessObject>,
+}
+
+impl WindowsSession {
+    /// Returns the address of the `_MM_SESSION_SPACE` structure.
     ///
-    /// // For Windows 8.1 and later:
-    /// return Process->VadRoot->Hint;
-    /// ```
-    pub fn vad_hint(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<Va, VmiError> {
-        match &self.offsets.ext {
-            Some(OffsetsExt::V1(offsets)) => self.vad_hint_v1(vmi, registers, process, offsets),
-            Some(OffsetsExt::V2(offsets)) => self.vad_hint_v2(vmi, registers, process, offsets),
-            None => panic!("OffsetsExt not set"),
-        }
+    /// The structure resides in session space, which is mapped only in
+    /// the address spaces of the processes of the session.
+    pub fn address(&self) -> Va {
+        self.address
     }
 
-    fn vad_hint_v1(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        offsets: &v1::Offsets,
-    ) -> Result<Va, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
-        let MM_AVL_TABLE = &offsets._MM_AVL_TABLE;
-
-        let vad_hint = vmi.read_va(
-            registers.address_context(
-                process.0 + EPROCESS.VadRoot.offset + MM_AVL_TABLE.NodeHint.offset,
-            ),
-            registers.address_width(),
-        )?;
+    /// Returns the ID of the session (`SessionId`).
+    ///
+    /// Session 0 hosts the services, interactive and remote desktop
+    /// logons get sessions 1 and above.
+    pub fn id(&self) -> u32 {
+        self.id
+    }
 
-        Ok(vad_hint)
+    /// Returns the processes of the session, from the `ProcessList` of
+    /// the session.
+    pub fn processes(&self) -> &[ProcessObject] {
+        &self.processes
     }
+}
 
-    fn vad_hint_v2(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        _offsets: &v2::Offsets,
-    ) -> Result<Va, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
+/// Represents an ETW logger session (`_WMI_LOGGER_CONTEXT`).
+#[derive(Debug)]
+pub struct WindowsEtwSession {
+    /// The address of the `_WMI_LOGGER_CONTEXT` structure.
+    pub address: Va,
 
-        let VadHint = EPROCESS
-            .VadHint
-            .expect("VadHint is not present in common offsets");
+    /// The `LoggerId` field.
+    pub id: u32,
 
-        let vad_hint = vmi.read_va(
-            registers.address_context(process.0 + VadHint.offset),
-            registers.address_width(),
-        )?;
+    /// The `LoggerName` field.
+    pub name: String,
 
-        Ok(vad_hint)
-    }
+    /// The `LoggerMode` field (`EVENT_TRACE_*_MODE` flags).
+    pub mode: u32,
 
-    /// Converts a VAD (Virtual Address Descriptor) to an [`OsRegion`] structure.
+    /// The `EnableFlags` field.
     ///
-    /// This method extracts information from a VAD and creates an `OsRegion`
-    /// object, which includes details about the memory region's address range,
-    /// protection, and mapping type.
-    pub fn vad_to_region(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        vad: Va,
-    ) -> Result<OsRegion, VmiError> {
-        let MMVAD = &self.offsets.common._MMVAD;
-        let SUBSECTION = &self.offsets.common._SUBSECTION;
+    /// For the kernel logger, this is the mask of enabled kernel providers
+    /// (`EVENT_TRACE_FLAG_*`).
+    pub enable_flags: u32,
 
-        let mmvad = self.vad(vmi, registers, vad)?;
-        let start = Va(mmvad.starting_vpn << 12);
-        let end = Va((mmvad.ending_vpn + 1) << 12);
+    /// Whether the session accepts new events (`AcceptNewEvents`).
+    ///
+    /// A session that exists but doesn't accept new events is effectively
+    /// disabled.
+    pub enabled: bool,
+}
 
-        const MM_ZERO_ACCESS: u8 = 0; // this value is not used.
-        const MM_READONLY: u8 = 1;
-        const MM_EXECUTE: u8 = 2;
-        const MM_EXECUTE_READ: u8 = 3;
-        const MM_READWRITE: u8 = 4; // bit 2 is set if this is writable.
-        const MM_WRITECOPY: u8 = 5;
-        const MM_EXECUTE_READWRITE: u8 = 6;
-        const MM_EXECUTE_WRITECOPY: u8 = 7;
+/// Enable parameters of an ETW provider (`_TRACE_ENABLE_INFO`).
+#[derive(Debug, Clone, Copy)]
+pub struct WindowsEtwEnableInfo {
+    /// Whether the provider is enabled (`IsEnabled`).
+    pub enabled: bool,
 
-        let protection = match mmvad.protection {
-            MM_ZERO_ACCESS => MemoryAccess::default(),
-            MM_READONLY => MemoryAccess::R,
-            MM_EXECUTE => MemoryAccess::X,
-            MM_EXECUTE_READ => MemoryAccess::RX,
-            MM_READWRITE => MemoryAccess::RW,
-            MM_WRITECOPY => MemoryAccess::RW, // REVIEW: is this correct?
-            MM_EXECUTE_READWRITE => MemoryAccess::RWX,
-            MM_EXECUTE_WRITECOPY => MemoryAccess::RWX, // REVIEW: is this correct?
-            _ => MemoryAccess::default(),
-        };
+    /// The `Level` field (`TRACE_LEVEL_*`).
+    pub level: u8,
 
-        const VadImageMap: u8 = 2;
+    /// The `LoggerId` field.
+    ///
+    /// Matches [`WindowsEtwSession::id`] of the session the parameters
+    /// belong to.
+    pub logger_id: u16,
 
-        if mmvad.vad_type != VadImageMap {
-            return Ok(OsRegion {
-                start,
-                end,
-                protection,
-                kind: OsRegionKind::Private,
-            });
-        }
+    /// The `EnableProperty` field (`EVENT_ENABLE_PROPERTY_*` flags).
+    pub enable_property: u32,
 
-        let subsection = vmi.read_va(
-            registers.address_context(vad + MMVAD.Subsection.offset),
-            registers.address_width(),
-        )?;
+    /// The `MatchAnyKeyword` field.
+    pub match_any_keyword: u64,
 
-        let control_area = vmi.read_va(
-            registers.address_context(subsection + SUBSECTION.ControlArea.offset),
-            registers.address_width(),
-        )?;
+    /// The `MatchAllKeyword` field.
+    pub match_all_keyword: u64,
+}
 
-        // Note that filename is allocated from paged pool,
-        // so this read might fail.
-        let path = self.control_area_to_filename(vmi, registers, control_area);
+/// Represents a registered ETW provider (`_ETW_GUID_ENTRY`).
+#[derive(Debug)]
+pub struct WindowsEtwProvider {
+    /// The address of the `_ETW_GUID_ENTRY` structure.
+    pub address: Va,
 
-        Ok(OsRegion {
-            start,
-            end,
-            protection,
-            kind: OsRegionKind::Mapped(OsMapped {
-                path: path.map(Some),
-            }),
-        })
-    }
+    /// The provider GUID, formatted as
+    /// `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`.
+    pub guid: String,
 
-    /// Retrieves all memory regions associated with a process's VAD tree.
+    /// The combined enable parameters of all sessions
+    /// (`ProviderEnableInfo`).
     ///
-    /// This method traverses the entire VAD tree of a process and converts
-    /// each VAD into an [`OsRegion`], providing a comprehensive view of the
-    /// process's virtual address space.
-    pub fn vad_root_to_regions(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        vad_root: Va,
-    ) -> Result<Vec<OsRegion>, VmiError> {
-        let mut result = Vec::new();
+    /// The kernel checks these before it delivers an event to any session,
+    /// so clearing them disables the provider even though its sessions
+    /// still list it.
+    pub enable_info: WindowsEtwEnableInfo,
 
-        self.enumerate_tree(vmi, registers, vad_root, |vad| {
-            match self.vad_to_region(vmi, registers, vad) {
-                Ok(region) => result.push(region),
-                Err(err) => tracing::warn!(?err, ?vad, "Failed to convert VAD to region"),
-            }
+    /// The enable parameters of each session the provider is enabled for
+    /// (`EnableInfo`).
+    pub sessions: Vec<WindowsEtwEnableInfo>,
+}
 
-            true
-        })?;
+/// Control Flow Guard (CFG) information of a PE image.
+///
+/// The information is static, as declared by the load configuration
+/// directory of the image. It doesn't reflect the CFG bitmap the kernel
+/// maintains at runtime; see [`WindowsOs::process_cfg_valid_target`] for
+/// that.
+#[derive(Debug)]
+pub struct WindowsImageCfg {
+    /// The base address of the image.
+    pub image_base: Va,
 
-        Ok(result)
-    }
+    /// The `GuardFlags` field of the load configuration directory.
+    pub guard_flags: u32,
 
-    /// Locates the VAD that encompasses a specific virtual address in a process.
+    /// The CFG check function.
     ///
-    /// This method efficiently searches the VAD tree to find the VAD node that
-    /// corresponds to the given virtual address within the process's address
-    /// space. Its functionality is similar to the Windows kernel's internal
-    /// `MiLocateAddress()` function.
-    ///
-    /// Returns virtual address of the matching VAD if found, or `None` if the
-    /// address is not within any VAD.
+    /// The target of the `GuardCFCheckFunctionPointer` pointer.
+    pub check_function: Va,
+
+    /// The CFG dispatch function.
     ///
-    pub fn find_process_vad(
+    /// The target of the `GuardCFDispatchFunctionPointer` pointer.
+    pub dispatch_function: Va,
+
+    /// Sorted RVAs of valid call targets from the `GuardCFFunctionTable`.
+    pub functions: Vec<u32>,
+}
+
+impl WindowsImageCfg {
+    /// Checks whether the image is instrumented with Control Flow Guard.
+    pub fn is_cf_instrumented(&self) -> bool {
+        self.guard_flags & IMAGE_GUARD_CF_INSTRUMENTED != 0
+    }
+
+    /// Checks whether the image is instrumented with Return Flow Guard.
+    pub fn is_rf_instrumented(&self) -> bool {
+        self.guard_flags & IMAGE_GUARD_RF_INSTRUMENTED != 0
+    }
+
+    /// Checks whether the given address is listed in the
+    /// `GuardCFFunctionTable` of the image.
+    ///
+    /// Only the static function table is consulted. Call targets that are
+    /// added or removed at runtime (e.g., by `SetProcessValidCallTargets`
+    /// or for dynamically generated code) are not reflected, so this is not
+    /// the same check that the CFG check function performs. Use
+    /// [`WindowsOs::process_cfg_valid_target`] for that.
+    pub fn is_function_table_target(&self, va: Va) -> bool {
+        let rva = match u32::try_from(va.0.wrapping_sub(self.image_base.0)) {
+            Ok(rva) => rva,
+            Err(_) => return false,
+        };
+
+        self.functions.binary_search(&rva).is_ok()
+    }
+}
+
+//
+// Private types
+//
+
+/// The address space type in a WoW64 process.
+enum WindowsWow64Kind {
+    /// Native address space
+    Native = 0,
+
+    /// x86 (32-bit) address space under WoW64
+    X86 = 1,
+    // Arm32 = 2,
+    // Amd64 = 3,
+    // ChpeX86 = 4,
+    // VsmEnclave = 5,
+}
+
+/// A 32-bit or 64-bit virtual address in WoW64 processes.
+struct WindowsWow64Va {
+    /// The virtual address.
+    va: Va,
+
+    /// The kind of the virtual address.
+    kind: WindowsWow64Kind,
+}
+
+impl WindowsWow64Va {
+    fn native(va: Va) -> Self {
+        Self {
+            va,
+            kind: WindowsWow64Kind::Native,
+        }
+    }
+
+    fn x86(va: Va) -> Self {
+        Self {
+            va,
+            kind: WindowsWow64Kind::X86,
+        }
+    }
+}
+
+#[derive_trait_from_impl(
+    os_session_name = WindowsOsSessionExt,
+    os_context_name = WindowsOsExt,
+    os_session_prober_name = WindowsOsSessionProberExt,
+    os_context_prober_name = WindowsOsProberExt
+)]
+#[allow(non_snake_case, non_upper_case_globals)]
+impl<Driver> WindowsOs<Driver>
+where
+    Driver: VmiDriver,
+    Driver::Architecture: Architecture + ArchAdapter<Driver>,
+{
+    /// 32-bit current process pseudo-handle (-1).
+    pub const NtCurrentProcess32: u64 = 0xffff_ffff;
+
+    /// 64-bit current process pseudo-handle (-1).
+    pub const NtCurrentProcess64: u64 = 0xffff_ffff_ffff_ffff;
+
+    /// 32-bit current thread pseudo-handle (-2).
+    pub const NtCurrentThread32: u64 = 0xffff_fffe;
+
+    /// 64-bit current thread pseudo-handle (-2).
+    pub const NtCurrentThread64: u64 = 0xffff_ffff_ffff_fffe;
+
+    /// Creates a new `WindowsOs` instance.
+    pub fn new(profile: &Profile) -> Result<Self, VmiError> {
+        Ok(Self {
+            offsets: Offsets::new(profile)?,
+            symbols: Symbols::new(profile)?,
+            kernel_image_base: RefCell::new(None),
+            highest_user_address: RefCell::new(None),
+            object_header_cookie: RefCell::new(None),
+            object_type_cache: RefCell::new(HashMap::new()),
+            ki_kva_shadow: RefCell::new(None),
+            mm_pfn_database: RefCell::new(None),
+            virtual_store_pagefile: RefCell::new(None),
+            invalid_pte_mask: RefCell::new(None),
+            nt_build_lab: RefCell::new(None),
+            nt_build_lab_ex: RefCell::new(None),
+            hal_dispatch_layout: hal_dispatch_layout(profile, "_HAL_DISPATCH")
+                .unwrap_or_else(|| hal_dispatch_layout_fallback(profile.pointer_size())),
+            hal_private_dispatch_layout: hal_dispatch_layout(profile, "_HAL_PRIVATE_DISPATCH"),
+            _marker: std::marker::PhantomData,
+        })
+    }
+
+    /// Returns a reference to the Windows-specific memory offsets.
+    pub fn offsets(&self) -> &Offsets {
+        &self.offsets
+    }
+
+    /// Returns a reference to the Windows-specific symbols.
+    pub fn symbols(&self) -> &Symbols {
+        &self.symbols
+    }
+
+    #[expect(clippy::only_used_in_recursion)]
+    fn enumerate_tree_node_v1(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        address: Va,
-    ) -> Result<Option<Va>, VmiError> {
-        let mut vad_va = self.vad_hint(vmi, registers, process)?;
+        node: Va,
+        callback: &mut impl FnMut(Va) -> bool,
+        offsets: &v1::Offsets,
+    ) -> Result<(), VmiError> {
+        let MMADDRESS_NODE = &offsets._MMADDRESS_NODE;
 
-        if vad_va.is_null() {
-            return Ok(None);
+        let balanced_node = StructReader::new(
+            vmi,
+            registers.address_context(node),
+            MMADDRESS_NODE.effective_len(),
+        )?;
+
+        let left = Va(balanced_node.read(MMADDRESS_NODE.LeftChild)?);
+        if !left.is_null() {
+            self.enumerate_tree_node_v1(vmi, registers, left, callback, offsets)?;
         }
 
-        let vpn = address.0 >> 12;
+        if !callback(node) {
+            return Ok(());
+        }
 
-        let vad = self.vad(vmi, registers, vad_va)?;
-        if vpn >= vad.starting_vpn && vpn <= vad.ending_vpn {
-            return Ok(Some(vad_va));
+        let right = Va(balanced_node.read(MMADDRESS_NODE.RightChild)?);
+        if !right.is_null() {
+            self.enumerate_tree_node_v1(vmi, registers, right, callback, offsets)?;
         }
 
-        vad_va = self.vad_root(vmi, registers, process)?;
+        Ok(())
+    }
 
-        while !vad_va.is_null() {
-            let vad = self.vad(vmi, registers, vad_va)?;
+    #[expect(clippy::only_used_in_recursion)]
+    fn enumerate_tree_node_v2(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        node: Va,
+        callback: &mut impl FnMut(Va) -> bool,
+        offsets: &v2::Offsets,
+    ) -> Result<(), VmiError> {
+        let RTL_BALANCED_NODE = &offsets._RTL_BALANCED_NODE;
 
-            if vpn < vad.starting_vpn {
-                vad_va = vad.left_child;
-            }
-            else if vpn > vad.ending_vpn {
-                vad_va = vad.right_child;
-            }
-            else {
-                return Ok(Some(vad_va));
-            }
+        let balanced_node = StructReader::new(
+            vmi,
+            registers.address_context(node),
+            RTL_BALANCED_NODE.effective_len(),
+        )?;
+
+        let left = Va(balanced_node.read(RTL_BALANCED_NODE.Left)?);
+        if !left.is_null() {
+            self.enumerate_tree_node_v2(vmi, registers, left, callback, offsets)?;
         }
 
-        Ok(None)
+        if !callback(node) {
+            return Ok(());
+        }
+
+        let right = Va(balanced_node.read(RTL_BALANCED_NODE.Right)?);
+        if !right.is_null() {
+            self.enumerate_tree_node_v2(vmi, registers, right, callback, offsets)?;
+        }
+
+        Ok(())
     }
 
-    /// Retrieves the virtual address of the Page Frame Number (PFN) database.
-    ///
-    /// The PFN database is a critical data structure in Windows memory management,
-    /// containing information about each physical page in the system.
-    ///
-    /// # Implementation Details
-    ///
-    /// The PFN database is located by reading the `MmPfnDatabase` symbol from
-    /// the kernel image.
-    fn pfn_database(
+    fn enumerate_tree_v1(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-    ) -> Result<Va, VmiError> {
-        let MmPfnDatabase = self.symbols.MmPfnDatabase;
+        root: Va,
+        mut callback: impl FnMut(Va) -> bool,
+        offsets: &v1::Offsets,
+    ) -> Result<(), VmiError> {
+        let MM_AVL_TABLE = &offsets._MM_AVL_TABLE;
+        let MMADDRESS_NODE = &offsets._MMADDRESS_NODE;
 
-        if let Some(mm_pfn_database) = self.mm_pfn_database.borrow().as_ref() {
-            return Ok(*mm_pfn_database);
+        // NumberGenericTableElements is a ULONG_PTR, which is the same size
+        // as a pointer.
+        let count = vmi.read_va(
+            registers.address_context(root + MM_AVL_TABLE.NumberGenericTableElements.offset),
+            registers.address_width(),
+        )?;
+
+        let count = MM_AVL_TABLE.NumberGenericTableElements.value_from(count.0);
+        if count == 0 {
+            return Ok(());
         }
 
-        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
-        let mm_pfn_database = vmi.read_va(
-            registers.address_context(kernel_image_base + MmPfnDatabase),
+        // Table->BalancedRoot.RightChild
+        let root = vmi.read_va(
+            registers.address_context(
+                root + MM_AVL_TABLE.BalancedRoot.offset + MMADDRESS_NODE.RightChild.offset,
+            ),
             registers.address_width(),
         )?;
-        *self.mm_pfn_database.borrow_mut() = Some(mm_pfn_database);
-        Ok(mm_pfn_database)
+
+        self.enumerate_tree_node_v1(vmi, registers, root, &mut callback, offsets)
     }
 
-    fn modify_pfn_reference_count(
+    fn enumerate_tree_v2(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        pfn: Gfn,
-        increment: i16,
-    ) -> Result<Option<u16>, VmiError> {
-        let MMPFN = &self.offsets.common._MMPFN;
-
-        // const ZeroedPageList: u16 = 0;
-        // const FreePageList: u16 = 1;
-        const StandbyPageList: u16 = 2; //this list and before make up available pages.
-        const ModifiedPageList: u16 = 3;
-        const ModifiedNoWritePageList: u16 = 4;
-        // const BadPageList: u16 = 5;
-        const ActiveAndValid: u16 = 6;
-        // const TransitionPage: u16 = 7;
-
-        let pfn = self.pfn_database(vmi, registers)? + u64::from(pfn) * MMPFN.len() as u64;
+        root: Va,
+        mut callback: impl FnMut(Va) -> bool,
+        offsets: &v2::Offsets,
+    ) -> Result<(), VmiError> {
+        self.enumerate_tree_node_v2(vmi, registers, root, &mut callback, offsets)
+    }
 
-        //
-        // In the _MMPFN structure, the fields are like this:
-        //
-        // ```c
-        // struct _MMPFN {
-        //     ...
-        //     union {
-        //         USHORT ReferenceCount;
-        //         struct {
-        //             UCHAR PageLocation : 3;
-        //             ...
-        //         } e1;
-        //         ...
-        //     } u3;
-        // };
-        // ```
-        //
-        // On the systems tested (Win7 - Win11), the `PageLocation` is right
-        // after `ReferenceCount`. We can read the value of both fields at once.
-        //
+    fn image_exported_symbols_generic<Pe>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+    ) -> Result<Vec<OsImageExportedSymbol>, VmiError>
+    where
+        Pe: ImageNtHeaders,
+    {
+        let mut data = [0u8; Amd64::PAGE_SIZE as usize];
+        vmi.read(registers.address_context(image_base), &mut data)?;
 
-        debug_assert_eq!(MMPFN.ReferenceCount.size, 2);
-        debug_assert_eq!(
-            MMPFN.ReferenceCount.offset + MMPFN.ReferenceCount.size,
-            MMPFN.PageLocation.offset
-        );
-        debug_assert_eq!(MMPFN.PageLocation.bit_position, 0);
-        debug_assert_eq!(MMPFN.PageLocation.bit_length, 3);
+        let pe = PeLite::<Pe>::parse(&data).map_err(|err| VmiError::Os(err.into()))?;
+        let entry = pe.data_directories[IMAGE_DIRECTORY_ENTRY_EXPORT];
 
-        let pfn_value =
-            vmi.read_u32(registers.address_context(pfn + MMPFN.ReferenceCount.offset))?;
-        let flags = (pfn_value >> 16) as u16;
-        let ref_count = (pfn_value & 0xFFFF) as u16;
+        let mut data = vec![0u8; entry.size.get(LE) as usize];
+        vmi.read(
+            registers.address_context(image_base + entry.virtual_address.get(LE) as u64),
+            &mut data,
+        )?;
 
-        let page_location = flags & 7;
+        let exports = pe.exports(&data).map_err(|err| VmiError::Os(err.into()))?;
+        Ok(exports
+            .iter()
+            .filter_map(|export| match export.target {
+                ExportTarget::Address(address) => Some(OsImageExportedSymbol {
+                    name: String::from_utf8_lossy(export.name?).to_string(),
+                    address: image_base + address as u64,
+                }),
+                _ => None,
+            })
+            .collect())
+    }
 
-        tracing::debug!(
-            %pfn,
-            ref_count,
-            flags = %Hex(flags),
-            page_location,
-            increment,
-            "Modifying PFN reference count"
-        );
+    fn image_size_generic<Pe>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+    ) -> Result<u64, VmiError>
+    where
+        Pe: ImageNtHeaders,
+    {
+        let mut data = [0u8; Amd64::PAGE_SIZE as usize];
+        vmi.read(registers.address_context(image_base), &mut data)?;
 
-        //
-        // Make sure the page is good (when coming from hibernate/standby pages
-        // can be in modified state).
-        //
+        let pe = PeLite::<Pe>::parse(&data).map_err(|err| VmiError::Os(err.into()))?;
+        Ok(pe.nt_headers.optional_header().size_of_image() as u64)
+    }
 
-        if !matches!(
-            page_location,
-            StandbyPageList | ModifiedPageList | ModifiedNoWritePageList | ActiveAndValid
-        ) {
-            tracing::warn!(
-                %pfn,
-                ref_count,
-                flags = %Hex(flags),
-                page_location,
-                increment,
-                "Page is not active and valid"
-            );
+    /// Returns the address and the number of entries of the exception
+    /// directory of a 64-bit PE image.
+    fn image_exception_directory(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+    ) -> Result<Option<(Va, u64)>, VmiError> {
+        if self.image_architecture(vmi, registers, image_base)? != OsArchitecture::Amd64 {
             return Ok(None);
         }
 
-        if ref_count == 0 {
-            tracing::warn!(
-                %pfn,
-                ref_count,
-                flags = %Hex(flags),
-                page_location,
-                increment,
-                "Page is not initialized"
-            );
-            return Ok(None);
-        }
+        let mut data = [0u8; Amd64::PAGE_SIZE as usize];
+        vmi.read(registers.address_context(image_base), &mut data)?;
 
-        let new_ref_count = match ref_count.checked_add_signed(increment) {
-            Some(new_ref_count) => new_ref_count,
-            None => {
-                tracing::warn!(
-                    %pfn,
-                    ref_count,
-                    flags = %Hex(flags),
-                    page_location,
-                    increment,
-                    "Page is at maximum reference count"
-                );
-                return Ok(None);
-            }
+        let pe = PeLite64::parse(&data).map_err(|err| VmiError::Os(err.into()))?;
+        let entry = match pe.data_directories.get(IMAGE_DIRECTORY_ENTRY_EXCEPTION) {
+            Some(entry) if entry.virtual_address.get(LE) != 0 => entry,
+            _ => return Ok(None),
         };
 
-        vmi.write_u16(
-            registers.address_context(pfn + MMPFN.ReferenceCount.offset),
-            new_ref_count,
+        let directory = image_base + entry.virtual_address.get(LE) as u64;
+        let count = entry.size.get(LE) as u64 / PeRuntimeFunction::SIZE as u64;
+
+        Ok(Some((directory, count)))
+    }
+
+    fn image_cfg_generic<Pe>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+    ) -> Result<Option<WindowsImageCfg>, VmiError>
+    where
+        Pe: ImageNtHeaders,
+    {
+        let mut data = [0u8; Amd64::PAGE_SIZE as usize];
+        vmi.read(registers.address_context(image_base), &mut data)?;
+
+        let pe = PeLite::<Pe>::parse(&data).map_err(|err| VmiError::Os(err.into()))?;
+        let entry = match pe.data_directories.get(IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG) {
+            Some(entry) if entry.virtual_address.get(LE) != 0 => entry,
+            _ => return Ok(None),
+        };
+
+        let mut data = vec![0u8; entry.size.get(LE).min(Amd64::PAGE_SIZE as u32) as usize];
+        vmi.read(
+            registers.address_context(image_base + entry.virtual_address.get(LE) as u64),
+            &mut data,
         )?;
 
-        Ok(Some(new_ref_count))
+        let config = pe
+            .guard_config(&data)
+            .map_err(|err| VmiError::Os(err.into()))?;
+
+        let read_function = |pointer: u64| -> Result<Va, VmiError> {
+            if pointer == 0 {
+                return Ok(Va(0));
+            }
+
+            vmi.read_va(
+                registers.address_context(Va(pointer)),
+                registers.address_width(),
+            )
+        };
+
+        let check_function = read_function(config.check_function_pointer)?;
+        let dispatch_function = read_function(config.dispatch_function_pointer)?;
+
+        //
+        // Each entry of the GuardCFFunctionTable is a 32-bit RVA followed by
+        // n bytes of metadata, where n is encoded in the upper bits of the
+        // GuardFlags field.
+        //
+
+        let metadata_size = (config.guard_flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK)
+            >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT;
+        let entry_size = size_of::<u32>() + metadata_size as usize;
+
+        //
+        // The table is part of the image, so it can't be larger than the
+        // image itself. This protects against corrupted or crafted counts.
+        //
+
+        let size_of_image = pe.nt_headers.optional_header().size_of_image() as usize;
+
+        let mut functions = Vec::new();
+        if config.function_table != 0 && config.function_count != 0 {
+            let table_size = usize::try_from(config.function_count)
+                .ok()
+                .and_then(|count| count.checked_mul(entry_size))
+                .filter(|&size| size <= size_of_image)
+                .ok_or(VmiError::CorruptedStruct("GuardCFFunctionTable"))?;
+
+            let mut table = vec![0u8; table_size];
+            vmi.read(
+                registers.address_context(Va(config.function_table)),
+                &mut table,
+            )?;
+
+            functions = table
+                .chunks_exact(entry_size)
+                .map(|entry| u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]))
+                .collect::<Vec<_>>();
+            functions.sort_unstable();
+        }
+
+        Ok(Some(WindowsImageCfg {
+            image_base,
+            guard_flags: config.guard_flags,
+            check_function,
+            dispatch_function,
+            functions,
+        }))
     }
 
-    /// Increments the reference count of a Page Frame Number (PFN).
+    // region: ETW
+
+    /// Enumerates the active ETW logger sessions.
     ///
-    /// This method is used to "lock" a physical page by increasing its
-    /// reference count, preventing it from being paged out or reallocated.
+    /// Supported on Windows 7 and on Windows 10 1607+. Returns
+    /// [`VmiError::NotSupported`] on other versions, or if the profile
+    /// lacks the symbols and structures described below.
     ///
-    /// Returns the new reference count if successful, or `None` if the
-    /// operation failed (e.g., if the page is not in a valid state).
+    /// Logger contexts that can't be read are skipped (and logged), so that
+    /// a single corrupted entry doesn't hide the other sessions.
     ///
     /// # Implementation Details
     ///
-    /// The method works by:
-    /// 1. Locating the `_MMPFN` structure for the given PFN within the `MmPfnDatabase`.
-    /// 2. Incrementing the `ReferenceCount` member of the `_MMPFN` structure.
-    ///
-    /// # Warning
+    /// The logger contexts are stored in an array of pointers. Unused slots
+    /// contain either `NULL` or `1` (`EtwpFreeLoggerContext`).
     ///
-    /// This function can potentially cause race conditions if the virtual machine
-    /// is not paused during its execution. It is strongly recommended to pause
-    /// the virtual machine before calling this function and resume it afterwards.
+    /// On Windows 10 1607+, the array belongs to the ETW state of the host
+    /// silo (`PspHostSiloGlobals.EtwSiloState`), which also holds the number
+    /// of its entries (`_ETW_SILODRIVERSTATE.MaxLoggers`).
     ///
-    /// Failure to pause the VM may result in inconsistent state or potential
-    /// crashes if the page is concurrently modified by the guest OS.
+    /// On Windows 7, the array is the `WmipLoggerContext` global, which has
+    /// a fixed number of entries (`MAXLOGGERS`, 64).
     ///
-    /// # Examples:
+    /// See [`etw_providers`] for the providers enabled in each session.
     ///
-    /// ```no_run
-    /// # use vmi::{
-    /// #     arch::amd64::{Amd64, Registers},
-    /// #     os::windows::WindowsOs,
-    /// #     Architecture, Gfn, VmiCore, VmiDriver, VmiError,
-    /// # };
-    /// #
-    /// # fn example<Driver>(
-    /// #     vmi: &VmiCore<Driver>,
-    /// #     os: &WindowsOs<Driver>,
-    /// #     registers: &Registers,
-    /// #     pfn: Gfn,
-    /// # ) -> Result<(), VmiError>
-    /// # where
-    /// #   Driver: VmiDriver<Architecture = Amd64>,
-    /// # {
-    /// let _pause_guard = vmi.pause_guard()?;
-    /// os.lock_pfn(vmi, registers, pfn)?;
-    /// // The VM will automatically resume when `_guard` goes out of scope
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub fn lock_pfn(
+    /// [`etw_providers`]: Self::etw_providers
+    pub fn etw_sessions(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        pfn: Gfn,
-    ) -> Result<Option<u16>, VmiError> {
-        self.modify_pfn_reference_count(vmi, registers, pfn, 1)
+    ) -> Result<Vec<WindowsEtwSession>, VmiError> {
+        const EtwpFreeLoggerContext: u64 = 1;
+
+        let address_width = registers.address_width();
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        let (logger_contexts, max_loggers) = match (
+            &self.offsets.silo,
+            self.symbols.PspHostSiloGlobals,
+            self.symbols.WmipLoggerContext,
+        ) {
+            (Some(silo), Some(PspHostSiloGlobals), _) => {
+                let ESERVERSILO_GLOBALS = &silo._ESERVERSILO_GLOBALS;
+                let ETW_SILODRIVERSTATE = &silo._ETW_SILODRIVERSTATE;
+
+                let etw_silo_state = vmi.read_va(
+                    registers.address_context(
+                        kernel_image_base
+                            + PspHostSiloGlobals
+                            + ESERVERSILO_GLOBALS.EtwSiloState.offset,
+                    ),
+                    address_width,
+                )?;
+
+                if etw_silo_state.is_null() {
+                    return Ok(Vec::new());
+                }
+
+                let state = StructReader::new(
+                    vmi,
+                    registers.address_context(etw_silo_state),
+                    ETW_SILODRIVERSTATE.effective_len(),
+                )?;
+
+                let logger_contexts = Va(state.read(ETW_SILODRIVERSTATE.EtwpLoggerContext)?);
+                let max_loggers = state.read(ETW_SILODRIVERSTATE.MaxLoggers)?;
+
+                // The number of loggers is configurable, but it is far below
+                // this sanity limit.
+                if max_loggers > 4096 {
+                    return Err(VmiError::CorruptedStruct("_ETW_SILODRIVERSTATE.MaxLoggers"));
+                }
+
+                (logger_contexts, max_loggers)
+            }
+            (_, _, Some(WmipLoggerContext)) => {
+                const MAXLOGGERS: u64 = 64;
+
+                (kernel_image_base + WmipLoggerContext, MAXLOGGERS)
+            }
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let mut result = Vec::new();
+
+        if logger_contexts.is_null() {
+            return Ok(result);
+        }
+
+        for index in 0..max_loggers {
+            let logger_context = vmi.read_va(
+                registers.address_context(logger_contexts + index * address_width as u64),
+                address_width,
+            )?;
+
+            if logger_context.0 <= EtwpFreeLoggerContext {
+                continue;
+            }
+
+            match self.etw_session(vmi, registers, logger_context) {
+                Ok(session) => result.push(session),
+                Err(err) => {
+                    tracing::warn!(%err, index, %logger_context, "failed to read ETW session");
+                }
+            }
+        }
+
+        Ok(result)
     }
 
-    /// Decrements the reference count of a Page Frame Number (PFN).
-    ///
-    /// This method is used to "unlock" a physical page by decreasing its
-    /// reference count, potentially allowing it to be paged out or reallocated
-    /// if the count reaches zero.
-    ///
-    /// Returns the new reference count if successful, or `None` if the
-    /// operation failed (e.g., if the page is not in a valid state).
-    ///
+    /// Reads an ETW logger session from a `_WMI_LOGGER_CONTEXT` structure.
+    pub fn etw_session(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        logger_context: Va,
+    ) -> Result<WindowsEtwSession, VmiError> {
+        let WMI_LOGGER_CONTEXT = &self.offsets.common._WMI_LOGGER_CONTEXT;
+
+        let context = StructReader::new(
+            vmi,
+            registers.address_context(logger_context),
+            WMI_LOGGER_CONTEXT.effective_len(),
+        )?;
+        let id = context.read(WMI_LOGGER_CONTEXT.LoggerId)? as u32;
+        let mode = context.read(WMI_LOGGER_CONTEXT.LoggerMode)? as u32;
+        let accept_new_events = context.read(WMI_LOGGER_CONTEXT.AcceptNewEvents)? as i32;
+        let enable_flags = context.read(WMI_LOGGER_CONTEXT.EnableFlags)? as u32;
+
+        let name = self.read_unicode_string(
+            vmi,
+            registers.address_context(logger_context + WMI_LOGGER_CONTEXT.LoggerName.offset),
+        )?;
+
+        Ok(WindowsEtwSession {
+            address: logger_context,
+            id,
+            name,
+            mode,
+            enable_flags,
+            enabled: accept_new_events != 0,
+        })
+    }
+
+    /// Enumerates the registered ETW providers and their enable parameters.
+    ///
+    /// Each provider reports the parameters of every session it is enabled
+    /// for, which can be matched to the sessions returned by
+    /// [`etw_sessions`] by the logger ID. Comparing the combined parameters
+    /// (`ProviderEnableInfo`) with the per-session ones reveals providers
+    /// that were disabled by tampering with the kernel memory.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the profile lacks the symbols
+    /// and structures described below.
+    ///
+    /// Providers that can't be read are skipped (and logged).
+    ///
     /// # Implementation Details
     ///
-    /// The method works by:
-    /// 1. Locating the `_MMPFN` structure for the given PFN within the `MmPfnDatabase`.
-    /// 2. Decrementing the `ReferenceCount` member of the `_MMPFN` structure.
+    /// Registered GUIDs are kept in a hash table of 64 buckets
+    /// (`_ETW_HASH_BUCKET`), each holding one list per GUID type. Providers
+    /// are in the first list (`EtwTraceGuidType`).
+    ///
+    /// On Windows 10 1607+, the hash table belongs to the ETW state of the
+    /// host silo (`_ETW_SILODRIVERSTATE.EtwpGuidHashTable`). On older
+    /// versions, it is the `EtwpGuidHashTable` global.
+    ///
+    /// [`etw_sessions`]: Self::etw_sessions
+    pub fn etw_providers(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<WindowsEtwProvider>, VmiError> {
+        const ETW_HASH_BUCKETS: u64 = 64;
+
+        let offsets = match &self.offsets.etw_provider {
+            Some(offsets) => offsets,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let ETW_HASH_BUCKET = &offsets._ETW_HASH_BUCKET;
+        let ETW_GUID_ENTRY = &offsets._ETW_GUID_ENTRY;
+
+        let address_width = registers.address_width();
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        let hash_table = match (
+            &self.offsets.silo,
+            self.symbols.PspHostSiloGlobals,
+            self.symbols.EtwpGuidHashTable,
+        ) {
+            (Some(silo), Some(PspHostSiloGlobals), _) => {
+                let ESERVERSILO_GLOBALS = &silo._ESERVERSILO_GLOBALS;
+                let ETW_SILODRIVERSTATE = &silo._ETW_SILODRIVERSTATE;
+
+                let EtwpGuidHashTable = match ETW_SILODRIVERSTATE.EtwpGuidHashTable {
+                    Some(EtwpGuidHashTable) => EtwpGuidHashTable,
+                    None => return Err(VmiError::NotSupported),
+                };
+
+                let etw_silo_state = vmi.read_va(
+                    registers.address_context(
+                        kernel_image_base
+                            + PspHostSiloGlobals
+                            + ESERVERSILO_GLOBALS.EtwSiloState.offset,
+                    ),
+                    address_width,
+                )?;
+
+                if etw_silo_state.is_null() {
+                    return Ok(Vec::new());
+                }
+
+                etw_silo_state + EtwpGuidHashTable.offset
+            }
+            (_, _, Some(EtwpGuidHashTable)) => kernel_image_base + EtwpGuidHashTable,
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let mut result = Vec::new();
+
+        for bucket in 0..ETW_HASH_BUCKETS {
+            // EtwTraceGuidType is the first list of the bucket.
+            let list_head = hash_table
+                + bucket * ETW_HASH_BUCKET.len() as u64
+                + ETW_HASH_BUCKET.ListHead.offset;
+
+            self.enumerate_list(vmi, registers, list_head, |entry| {
+                let entry = entry - ETW_GUID_ENTRY.GuidList.offset;
+
+                match self.etw_provider(vmi, registers, entry) {
+                    Ok(provider) => result.push(provider),
+                    Err(err) => tracing::warn!(%err, bucket, %entry, "failed to read ETW provider"),
+                }
+
+                true
+            })?;
+        }
+
+        Ok(result)
+    }
+
+    /// Reads an ETW provider from a `_ETW_GUID_ENTRY` structure.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the profile lacks the ETW
+    /// provider structures.
+    pub fn etw_provider(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        guid_entry: Va,
+    ) -> Result<WindowsEtwProvider, VmiError> {
+        let offsets = match &self.offsets.etw_provider {
+            Some(offsets) => offsets,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let ETW_GUID_ENTRY = &offsets._ETW_GUID_ENTRY;
+        let TRACE_ENABLE_INFO = &offsets._TRACE_ENABLE_INFO;
+
+        let mut guid = [0u8; 16];
+        vmi.read(
+            registers.address_context(guid_entry + ETW_GUID_ENTRY.Guid.offset),
+            &mut guid,
+        )?;
+
+        let guid0 = u32::from_le_bytes(guid[0..4].try_into().unwrap());
+        let guid1 = u16::from_le_bytes(guid[4..6].try_into().unwrap());
+        let guid2 = u16::from_le_bytes(guid[6..8].try_into().unwrap());
+        let guid3 = &guid[8..16];
+
+        let guid = format!(
+            "{:08x}-{:04x}-{:04x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+            guid0,
+            guid1,
+            guid2,
+            guid3[0],
+            guid3[1],
+            guid3[2],
+            guid3[3],
+            guid3[4],
+            guid3[5],
+            guid3[6],
+            guid3[7],
+        );
+
+        let enable_info = self.etw_enable_info(
+            vmi,
+            registers,
+            guid_entry + ETW_GUID_ENTRY.ProviderEnableInfo.offset,
+        )?;
+
+        let mut sessions = Vec::new();
+        let count = ETW_GUID_ENTRY.EnableInfo.size / TRACE_ENABLE_INFO.len() as u64;
+        for index in 0..count {
+            let info = self.etw_enable_info(
+                vmi,
+                registers,
+                guid_entry
+                    + ETW_GUID_ENTRY.EnableInfo.offset
+                    + index * TRACE_ENABLE_INFO.len() as u64,
+            )?;
+
+            if info.enabled {
+                sessions.push(info);
+            }
+        }
+
+        Ok(WindowsEtwProvider {
+            address: guid_entry,
+            guid,
+            enable_info,
+            sessions,
+        })
+    }
+
+    /// Reads the enable parameters of an ETW provider from
+    /// a `_TRACE_ENABLE_INFO` structure.
+    fn etw_enable_info(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        address: Va,
+    ) -> Result<WindowsEtwEnableInfo, VmiError> {
+        let offsets = match &self.offsets.etw_provider {
+            Some(offsets) => offsets,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let TRACE_ENABLE_INFO = &offsets._TRACE_ENABLE_INFO;
+
+        let info = StructReader::new(
+            vmi,
+            registers.address_context(address),
+            TRACE_ENABLE_INFO.effective_len(),
+        )?;
+
+        Ok(WindowsEtwEnableInfo {
+            enabled: info.read(TRACE_ENABLE_INFO.IsEnabled)? != 0,
+            level: info.read(TRACE_ENABLE_INFO.Level)? as u8,
+            logger_id: info.read(TRACE_ENABLE_INFO.LoggerId)? as u16,
+            enable_property: info.read(TRACE_ENABLE_INFO.EnableProperty)? as u32,
+            match_any_keyword: info.read(TRACE_ENABLE_INFO.MatchAnyKeyword)?,
+            match_all_keyword: info.read(TRACE_ENABLE_INFO.MatchAllKeyword)?,
+        })
+    }
+
+    // endregion: ETW
+
+    // region: File
+
+    /// Extracts the `FileName` from a `FILE_OBJECT` structure.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// UNICODE_STRING FileName = FileObject->FileName;
+    /// return FileName;
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// This operation might fail as the filename is allocated from paged pool.
+    pub fn file_object_to_filename(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        file_object: Va,
+    ) -> Result<String, VmiError> {
+        let FILE_OBJECT = &self.offsets.common._FILE_OBJECT;
+
+        // Note that filename is allocated from paged pool,
+        // so this read might fail.
+        self.read_unicode_string(
+            vmi,
+            registers.address_context(file_object + FILE_OBJECT.FileName.offset),
+        )
+    }
+
+    /// Constructs the full path of a file from its `FILE_OBJECT`.
+    ///
+    /// This function first reads the `DeviceObject` field of the `FILE_OBJECT`
+    /// structure. Then it reads the `ObjectNameInfo` of the `DeviceObject`
+    /// and its directory. Finally, it concatenates the device directory
+    /// name, device name, and file name.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PDEVICE_OBJECT DeviceObject = FileObject->DeviceObject;
+    ///
+    /// POBJECT_HEADER_NAME_INFO DeviceNameInfo = ObjectNameInfo(DeviceObject);
+    /// POBJECT_HEADER_NAME_INFO DeviceDirectoryNameInfo = DeviceNameInfo->Directory
+    ///     ? ObjectNameInfo(DeviceNameInfo->Directory)
+    ///     : NULL;
+    ///
+    /// if (DeviceDirectoryNameInfo->Name != NULL) {
+    ///     FullPath += '\\' + DeviceDirectoryNameInfo->Name;
+    /// }
+    ///
+    /// if (DeviceNameInfo->Name != NULL) {
+    ///     FullPath += '\\' + DeviceNameInfo->Name;
+    /// }
+    ///
+    /// FullPath += FileObject->FileName;
+    ///
+    /// return FullPath;
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the provided object is not a file object.
+    pub fn file_object_to_full_path(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        file_object: Va,
+    ) -> Result<String, VmiError> {
+        let file = match self.parse_file_object(vmi, registers, file_object)? {
+            WindowsObject::File(file) => file,
+            _ => panic!("Not a file object"),
+        };
+
+        let device = self.object_name(vmi, registers, file.device_object)?;
+        let directory = match &device {
+            Some(device) => self.object_name(vmi, registers, device.directory)?,
+            None => None,
+        };
+
+        let mut result = String::new();
+        if let Some(directory) = directory {
+            result.push('\\');
+            result.push_str(&directory.name);
+        }
+
+        if let Some(device) = device {
+            result.push('\\');
+            result.push_str(&device.name);
+        }
+
+        result.push_str(&file.filename);
+
+        Ok(result)
+    }
+
+    /// Extracts the filename from a `CONTROL_AREA` structure.
+    ///
+    /// This function first reads the `FilePointer` field of the `CONTROL_AREA`
+    /// structure, then reads the `FileName` field of the `FILE_OBJECT`
+    /// structure pointed by the `FilePointer`.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PFILE_OBJECT FileObject = ControlArea->FilePointer;
+    /// UNICODE_STRING FileName = FileObject->FileName;
+    /// return FileName;
+    /// ```
+    pub fn control_area_to_filename(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        control_area: Va,
+    ) -> Result<String, VmiError> {
+        let CONTROL_AREA = &self.offsets.common._CONTROL_AREA;
+
+        // The file pointer is in fact an `_EX_FAST_REF` structure,
+        // where the low bits are used to store the reference count.
+        let file_pointer = self.read_fast_ref(
+            vmi,
+            registers,
+            control_area + CONTROL_AREA.FilePointer.offset,
+        )?;
+
+        self.file_object_to_filename(vmi, registers, file_pointer)
+    }
+
+    // endregion: File
+
+    // region: Handle
+
+    /// Checks if the given handle is a kernel handle.
+    pub fn is_kernel_handle(
+        &self,
+        _vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        handle: u64,
+    ) -> Result<bool, VmiError> {
+        const KERNEL_HANDLE_MASK32: u64 = 0x8000_0000;
+        const KERNEL_HANDLE_MASK64: u64 = 0xffff_ffff_8000_0000;
+
+        match registers.effective_address_width() {
+            4 => Ok(handle & KERNEL_HANDLE_MASK32 == KERNEL_HANDLE_MASK32),
+            8 => Ok(handle & KERNEL_HANDLE_MASK64 == KERNEL_HANDLE_MASK64),
+            _ => panic!("Unsupported address width"),
+        }
+    }
+
+    /// Retrieves the handle table for a given process.
+    pub fn handle_table(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<WindowsHandleTable, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let HANDLE_TABLE = &self.offsets.common._HANDLE_TABLE;
+
+        let handle_table = vmi.read_va(
+            registers.address_context(process.0 + EPROCESS.ObjectTable.offset),
+            registers.address_width(),
+        )?;
+
+        let table_code = vmi.read_address(
+            registers.address_context(handle_table + HANDLE_TABLE.TableCode.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(WindowsHandleTable { table_code })
+    }
+
+    /// Looks up a specific handle table entry for a given process and handle.
+    pub fn handle_table_entry(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        handle: u64,
+    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
+        let mut process = process;
+        let mut handle = handle;
+
+        if self.is_kernel_handle(vmi, registers, handle)? {
+            process = self.system_process(vmi, registers)?;
+            handle &= 0x7fff_ffff;
+        }
+
+        let handle_table = self.handle_table(vmi, registers, process)?;
+        let entry_address =
+            self.handle_table_entry_lookup(vmi, registers, &handle_table, handle)?;
+        self.parse_handle_table_entry(vmi, registers, entry_address)
+    }
+
+    /// Performs a lookup in the handle table to find the address of a handle
+    /// table entry.
+    ///
+    /// Implements the multi-level handle table lookup algorithm used by
+    /// Windows. Returns the virtual address of the handle table entry.
+    pub fn handle_table_entry_lookup(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        handle_table: &WindowsHandleTable,
+        handle: u64,
+    ) -> Result<Va, VmiError> {
+        const SIZEOF_POINTER: u64 = 8;
+        const SIZEOF_HANDLE_TABLE_ENTRY: u64 = 16;
+
+        const LOWLEVEL_COUNT: u64 = 256; // (TABLE_PAGE_SIZE / sizeof(HANDLE_TABLE_ENTRY))
+        const MIDLEVEL_COUNT: u64 = 512; // (PAGE_SIZE / sizeof(PHANDLE_TABLE_ENTRY))
+
+        const LEVEL_CODE_MASK: u64 = 3;
+        const HANDLE_VALUE_INC: u64 = 4;
+
+        let level = handle_table.table_code & LEVEL_CODE_MASK;
+        let table = Va(handle_table.table_code - level);
+
+        //
+        // The 2 least significant bits of a handle are available to the
+        // application and are ignored by the system.
+        //
+
+        let mut index = handle & !0b11;
+
+        match level {
+            0 => Ok(table + index * (SIZEOF_HANDLE_TABLE_ENTRY / HANDLE_VALUE_INC)),
+
+            1 => {
+                let table2 = table;
+                let i = index % (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+
+                index -= i;
+                let j = index / (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+
+                let table1 = vmi.read_va(
+                    registers.address_context(table2 + j * SIZEOF_POINTER),
+                    registers.address_width(),
+                )?;
+
+                Ok(table1 + i * (SIZEOF_HANDLE_TABLE_ENTRY / HANDLE_VALUE_INC))
+            }
+
+            2 => {
+                let table3 = table;
+                let i = index % (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+
+                index -= i;
+                let mut k = index / (LOWLEVEL_COUNT * HANDLE_VALUE_INC);
+
+                let j = k % MIDLEVEL_COUNT;
+                k -= j;
+                k /= MIDLEVEL_COUNT;
+
+                let table2 = vmi.read_va(
+                    registers.address_context(table3 + k * SIZEOF_POINTER),
+                    registers.address_width(),
+                )?;
+                let table1 = vmi.read_va(
+                    registers.address_context(table2 + j * SIZEOF_POINTER),
+                    registers.address_width(),
+                )?;
+
+                Ok(table1 + i * (SIZEOF_HANDLE_TABLE_ENTRY / HANDLE_VALUE_INC))
+            }
+
+            _ => unreachable!(),
+        }
+    }
+
+    /// Enumerates the entries of a handle table.
+    ///
+    /// The callback receives the handle value and the address of its
+    /// handle table entry; the enumeration stops when it returns `false`.
+    /// Entries are not filtered, so free entries are enumerated as well.
+    ///
+    /// # Implementation Details
+    ///
+    /// The handle table is a tree of up to three levels. The level is
+    /// encoded in the low bits of the `TableCode`. Only the low-level
+    /// tables hold handle table entries; the upper levels hold pointers
+    /// to the tables of the level below, allocated in order as the table
+    /// grows. The first entry of each low-level table is reserved and is
+    /// skipped.
+    pub fn enumerate_handle_table(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        handle_table: &WindowsHandleTable,
+        callback: impl FnMut(u64, Va) -> bool,
+    ) -> Result<(), VmiError> {
+        const SIZEOF_POINTER: u64 = 8;
+        const SIZEOF_HANDLE_TABLE_ENTRY: u64 = 16;
+
+        const LOWLEVEL_COUNT: u64 = 256; // (TABLE_PAGE_SIZE / sizeof(HANDLE_TABLE_ENTRY))
+        const MIDLEVEL_COUNT: u64 = 512; // (PAGE_SIZE / sizeof(PHANDLE_TABLE_ENTRY))
+        const HIGHLEVEL_COUNT: u64 = 128; // (MAX_HANDLES / (LOWLEVEL_COUNT * MIDLEVEL_COUNT))
+
+        const LEVEL_CODE_MASK: u64 = 3;
+        const HANDLE_VALUE_INC: u64 = 4;
+
+        let level = handle_table.table_code & LEVEL_CODE_MASK;
+        let table = Va(handle_table.table_code - level);
+
+        // Reads the pointers to the tables of the level below, up to the
+        // first unallocated one.
+        let read_tables = |table: Va, count: u64| -> Result<Vec<Va>, VmiError> {
+            let mut result = Vec::new();
+
+            for index in 0..count {
+                let entry = vmi.read_va(
+                    registers.address_context(table + index * SIZEOF_POINTER),
+                    registers.address_width(),
+                )?;
+
+                if entry.is_null() {
+                    break;
+                }
+
+                result.push(entry);
+            }
+
+            Ok(result)
+        };
+
+        let mut callback = callback;
+
+        // Enumerates a low-level table, whose first entry corresponds to
+        // the handle table entry with the given index.
+        let mut enumerate_low = |table: Va, base: u64| -> bool {
+            for index in 1..LOWLEVEL_COUNT {
+                let handle = (base + index) * HANDLE_VALUE_INC;
+                let entry = table + index * SIZEOF_HANDLE_TABLE_ENTRY;
+
+                if !callback(handle, entry) {
+                    return false;
+                }
+            }
+
+            true
+        };
+
+        match level {
+            0 => {
+                enumerate_low(table, 0);
+            }
+
+            1 => {
+                for (j, table1) in read_tables(table, MIDLEVEL_COUNT)?.into_iter().enumerate() {
+                    if !enumerate_low(table1, j as u64 * LOWLEVEL_COUNT) {
+                        break;
+                    }
+                }
+            }
+
+            2 => {
+                'outer: for (k, table2) in
+                    read_tables(table, HIGHLEVEL_COUNT)?.into_iter().enumerate()
+                {
+                    for (j, table1) in read_tables(table2, MIDLEVEL_COUNT)?.into_iter().enumerate()
+                    {
+                        let base = (k as u64 * MIDLEVEL_COUNT + j as u64) * LOWLEVEL_COUNT;
+
+                        if !enumerate_low(table1, base) {
+                            break 'outer;
+                        }
+                    }
+                }
+            }
+
+            _ => return Err(VmiError::Other("Invalid handle table level")),
+        }
+
+        Ok(())
+    }
+
+    /// Converts a handle to the virtual address of the corresponding object.
+    ///
+    /// Uses the handle table entry lookup to find the object address for a
+    /// given handle.
+    pub fn handle_to_object_address(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        handle: u64,
+    ) -> Result<Option<Va>, VmiError> {
+        Ok(self
+            .handle_table_entry(vmi, registers, process, handle)?
+            .map(|entry| entry.object))
+    }
+
+    /// Retrieves the WindowsObject corresponding to a given handle in a
+    /// process.
+    pub fn handle_to_object(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        handle: u64,
+    ) -> Result<Option<WindowsObject>, VmiError> {
+        match self.handle_to_object_address(vmi, registers, process, handle)? {
+            Some(object) => self.object_from_address(vmi, registers, object),
+            None => Ok(None),
+        }
+    }
+
+    /// Parses a Windows object from its memory address.
+    ///
+    /// Determines the object type and calls the appropriate parsing method.
+    /// Currently supports File, Section and SymbolicLink object types.
+    pub fn object_from_address(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<Option<WindowsObject>, VmiError> {
+        match self.object_type(vmi, registers, object)? {
+            Some(WindowsObjectType::File) => {
+                Ok(Some(self.parse_file_object(vmi, registers, object)?))
+            }
+            Some(WindowsObjectType::Section) => self.parse_section_object(vmi, registers, object),
+            Some(WindowsObjectType::SymbolicLink) => {
+                Ok(Some(WindowsObject::SymbolicLink(WindowsSymbolicLink {
+                    target: self.symbolic_link_target(vmi, registers, object)?,
+                })))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses a `FILE_OBJECT` structure.
+    ///
+    /// Extracts the device object and filename from the `FILE_OBJECT`.
+    /// Returns a [`WindowsObject::File`] variant.
+    fn parse_file_object(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<WindowsObject, VmiError> {
+        let FILE_OBJECT = &self.offsets.common._FILE_OBJECT;
+
+        let device_object = vmi.read_va(
+            registers.address_context(object + FILE_OBJECT.DeviceObject.offset),
+            registers.address_width(),
+        )?;
+
+        let filename = self.file_object_to_filename(vmi, registers, object)?;
+        Ok(WindowsObject::File(WindowsFileObject {
+            device_object,
+            filename,
+        }))
+    }
+
+    /// Parses a Windows section object.
+    ///
+    /// Delegates to version-specific parsing methods based on the available
+    /// offsets. Currently supports `SECTION_OBJECT` and `SECTION` structures.
+    /// Returns a [`WindowsObject::Section`] variant.
+    fn parse_section_object(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<Option<WindowsObject>, VmiError> {
+        match &self.offsets.ext {
+            Some(OffsetsExt::V1(offsets)) => Ok(Some(
+                self.parse_section_object_v1(vmi, registers, object, offsets)?,
+            )),
+            Some(OffsetsExt::V2(offsets)) => Ok(Some(
+                self.parse_section_object_v2(vmi, registers, object, offsets)?,
+            )),
+            None => panic!("OffsetsExt not set"),
+        }
+    }
+
+    fn parse_section_object_v1(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+        offsets: &v1::Offsets,
+    ) -> Result<WindowsObject, VmiError> {
+        let SECTION_OBJECT = &offsets._SECTION_OBJECT;
+        let SEGMENT_OBJECT = &offsets._SEGMENT_OBJECT;
+        let MMSECTION_FLAGS = &self.offsets.common._MMSECTION_FLAGS;
+
+        let section = StructReader::new(
+            vmi,
+            registers.address_context(object),
+            SECTION_OBJECT.effective_len(),
+        )?;
+        let starting_vpn = section.read(SECTION_OBJECT.StartingVa)?;
+        let ending_vpn = section.read(SECTION_OBJECT.EndingVa)?;
+        let segment = section.read(SECTION_OBJECT.Segment)?;
+
+        let segment = StructReader::new(
+            vmi,
+            registers.address_context(segment.into()),
+            SEGMENT_OBJECT.effective_len(),
+        )?;
+        let size = segment.read(SEGMENT_OBJECT.SizeOfSegment)?;
+        let flags = segment.read(SEGMENT_OBJECT.MmSectionFlags)?;
+        let flags = vmi.read_u32(registers.address_context(flags.into()))? as u64;
+
+        let file = MMSECTION_FLAGS.File.value_from(flags) != 0;
+        let _image = MMSECTION_FLAGS.Image.value_from(flags) != 0;
+
+        let kind = if file {
+            let control_area = vmi.read_va(
+                registers.address_context(object + SEGMENT_OBJECT.ControlArea.offset),
+                registers.address_width(),
+            )?;
+
+            let path = self.control_area_to_filename(vmi, registers, control_area);
+
+            OsRegionKind::Mapped(OsMapped {
+                path: path.map(Some),
+            })
+        }
+        else {
+            OsRegionKind::Private
+        };
+
+        Ok(WindowsObject::Section(WindowsSectionObject {
+            region: OsRegion {
+                start: Va(starting_vpn << 12),
+                end: Va((ending_vpn + 1) << 12),
+                protection: MemoryAccess::default(),
+                kind,
+            },
+            size,
+        }))
+    }
+
+    fn parse_section_object_v2(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+        offsets: &v2::Offsets,
+    ) -> Result<WindowsObject, VmiError> {
+        let SECTION = &offsets._SECTION;
+        let MMSECTION_FLAGS = &self.offsets.common._MMSECTION_FLAGS;
+
+        let section = StructReader::new(
+            vmi,
+            registers.address_context(object),
+            SECTION.effective_len(),
+        )?;
+        let starting_vpn = section.read(SECTION.StartingVpn)?;
+        let ending_vpn = section.read(SECTION.EndingVpn)?;
+        let size = section.read(SECTION.SizeOfSection)?;
+        let flags = section.read(SECTION.Flags)?;
+
+        let file = MMSECTION_FLAGS.File.value_from(flags) != 0;
+        let _image = MMSECTION_FLAGS.Image.value_from(flags) != 0;
+
+        //
+        // We have to distinguish between FileObject and ControlArea.
+        // Here's an excerpt from _SECTION:
+        //
+        //     union {
+        //       union {
+        //         PCONTROL_AREA ControlArea;
+        //         PFILE_OBJECT FileObject;
+        //         struct {
+        //           ULONG_PTR RemoteImageFileObject : 1;
+        //           ULONG_PTR RemoteDataFileObject : 1;
+        //         };
+        //       };
+        //     };
+        //
+        // Based on information from Geoff Chappell's website, we can determine whether
+        // ControlArea is in fact FileObject by checking the lowest 2 bits of the
+        // pointer.
+        //
+        // ref: https://www.geoffchappell.com/studies/windows/km/ntoskrnl/inc/ntos/mi/section.htm
+        //
+
+        let kind = if file {
+            let control_area = vmi.read_va(
+                registers.address_context(object + SECTION.ControlArea.offset),
+                registers.address_width(),
+            )?;
+
+            let path = if u64::from(control_area) & 0x3 != 0 {
+                let file_object = control_area;
+                self.file_object_to_filename(vmi, registers, file_object)
+            }
+            else {
+                self.control_area_to_filename(vmi, registers, control_area)
+            };
+
+            OsRegionKind::Mapped(OsMapped {
+                path: path.map(Some),
+            })
+        }
+        else {
+            OsRegionKind::Private
+        };
+
+        Ok(WindowsObject::Section(WindowsSectionObject {
+            region: OsRegion {
+                start: Va(starting_vpn << 12),
+                end: Va((ending_vpn + 1) << 12),
+                protection: MemoryAccess::default(),
+                kind,
+            },
+            size,
+        }))
+    }
+
+    fn parse_handle_table_entry(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        entry: Va,
+    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
+        match &self.offsets.ext {
+            Some(OffsetsExt::V1(offsets)) => {
+                self.parse_handle_table_entry_v1(vmi, registers, entry, offsets)
+            }
+            Some(OffsetsExt::V2(offsets)) => {
+                self.parse_handle_table_entry_v2(vmi, registers, entry, offsets)
+            }
+            None => panic!("OffsetsExt not set"),
+        }
+    }
+
+    fn parse_handle_table_entry_v1(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        entry: Va,
+        offsets: &v1::Offsets,
+    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
+        const OBJ_PROTECT_CLOSE: u64 = 0x00000001;
+        const OBJ_INHERIT: u64 = 0x00000002;
+        const OBJ_AUDIT_OBJECT_CLOSE: u64 = 0x00000004;
+        const OBJ_HANDLE_ATTRIBUTES: u64 = OBJ_PROTECT_CLOSE | OBJ_INHERIT | OBJ_AUDIT_OBJECT_CLOSE;
+
+        let HANDLE_TABLE_ENTRY = &offsets._HANDLE_TABLE_ENTRY;
+        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
+
+        let handle_table_entry = StructReader::new(
+            vmi,
+            registers.address_context(entry),
+            HANDLE_TABLE_ENTRY.effective_len(),
+        )?;
+        let object = handle_table_entry.read(HANDLE_TABLE_ENTRY.Object)?;
+        let attributes = handle_table_entry.read(HANDLE_TABLE_ENTRY.ObAttributes)?;
+        let granted_access = handle_table_entry.read(HANDLE_TABLE_ENTRY.GrantedAccess)? as u32;
+
+        let object = Va(object & !OBJ_HANDLE_ATTRIBUTES);
+        let object = object + OBJECT_HEADER.Body.offset;
+
+        let attributes = (attributes & OBJ_HANDLE_ATTRIBUTES) as u32;
+
+        Ok(Some(WindowsHandleTableEntry {
+            object,
+            attributes,
+            granted_access,
+        }))
+    }
+
+    fn parse_handle_table_entry_v2(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        entry: Va,
+        offsets: &v2::Offsets,
+    ) -> Result<Option<WindowsHandleTableEntry>, VmiError> {
+        let HANDLE_TABLE_ENTRY = &offsets._HANDLE_TABLE_ENTRY;
+        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
+
+        #[repr(C)]
+        #[derive(Debug, Copy, Clone, FromBytes, IntoBytes)]
+        #[allow(non_camel_case_types, non_snake_case)]
+        struct _HANDLE_TABLE_ENTRY {
+            LowValue: u64,
+            HighValue: u64,
+        }
+
+        // Fetch the handle table entry
+        let handle_table_entry =
+            vmi.read_struct::<_HANDLE_TABLE_ENTRY>(registers.address_context(entry))?;
+
+        // Parse the handle table entry
+        let object_pointer_bits = HANDLE_TABLE_ENTRY
+            .ObjectPointerBits
+            .value_from(handle_table_entry.LowValue);
+
+        let attributes = HANDLE_TABLE_ENTRY
+            .Attributes
+            .value_from(handle_table_entry.LowValue) as u32;
+
+        let granted_access = HANDLE_TABLE_ENTRY
+            .GrantedAccessBits
+            .value_from(handle_table_entry.HighValue) as u32;
+
+        let object = Va(0xffff_0000_0000_0000 | object_pointer_bits << 4);
+        let object = object + OBJECT_HEADER.Body.offset;
+
+        Ok(Some(WindowsHandleTableEntry {
+            object,
+            attributes,
+            granted_access,
+        }))
+    }
+
+    // endregion: Handle
+
+    // region: Image
+
+    /// Retrieves the Control Flow Guard (CFG) information of a PE image.
+    ///
+    /// Returns `None` if the image has no load configuration directory.
+    ///
+    /// # Implementation Details
+    ///
+    /// The information is read from the load configuration directory of the
+    /// image. The pointers to the check and dispatch functions are resolved,
+    /// and the `GuardCFFunctionTable` is read to build the list of valid
+    /// call targets.
+    ///
+    /// Only the static function table is read. The returned information
+    /// therefore doesn't reflect valid call targets that are changed at
+    /// runtime (e.g., by `SetProcessValidCallTargets`). See
+    /// [`process_cfg_valid_target`] for the live CFG bitmap.
+    ///
+    /// [`process_cfg_valid_target`]: Self::process_cfg_valid_target
+    pub fn image_cfg(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+    ) -> Result<Option<WindowsImageCfg>, VmiError> {
+        match self.image_architecture(vmi, registers, image_base)? {
+            OsArchitecture::Unknown => Err(VmiError::Os(PeError::InvalidPeMagic.into())),
+            OsArchitecture::X86 => {
+                self.image_cfg_generic::<ImageNtHeaders32>(vmi, registers, image_base)
+            }
+            OsArchitecture::Amd64 => {
+                self.image_cfg_generic::<ImageNtHeaders64>(vmi, registers, image_base)
+            }
+        }
+    }
+
+    /// Retrieves the size of the image in memory, as declared by the
+    /// `SizeOfImage` field of the PE optional header.
+    pub fn image_size(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+    ) -> Result<u64, VmiError> {
+        match self.image_architecture(vmi, registers, image_base)? {
+            OsArchitecture::Unknown => Err(VmiError::Os(PeError::InvalidPeMagic.into())),
+            OsArchitecture::X86 => {
+                self.image_size_generic::<ImageNtHeaders32>(vmi, registers, image_base)
+            }
+            OsArchitecture::Amd64 => {
+                self.image_size_generic::<ImageNtHeaders64>(vmi, registers, image_base)
+            }
+        }
+    }
+
+    /// Checks whether kernel Control Flow Guard (kCFG) is active.
+    ///
+    /// # Implementation Details
+    ///
+    /// The kernel image must be instrumented with CFG. When kCFG is
+    /// disabled, the kernel leaves the `GuardCFDispatchFunctionPointer`
+    /// pointing to `_guard_dispatch_icall_nop` (`jmp rax`) and the
+    /// `GuardCFCheckFunctionPointer` pointing to `_guard_check_icall_nop`
+    /// (`ret`). kCFG is considered active if either of them points to
+    /// a different function.
+    pub fn cfg_enabled(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<bool, VmiError> {
+        const JMP_RAX: [u8; 2] = [0xff, 0xe0];
+        const RET: [u8; 1] = [0xc3];
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let cfg = match self.image_cfg(vmi, registers, kernel_image_base)? {
+            Some(cfg) if cfg.is_cf_instrumented() => cfg,
+            _ => return Ok(false),
+        };
+
+        if !cfg.dispatch_function.is_null() {
+            let mut code = [0u8; JMP_RAX.len()];
+            vmi.read(registers.address_context(cfg.dispatch_function), &mut code)?;
+
+            if code != JMP_RAX {
+                return Ok(true);
+            }
+        }
+
+        if !cfg.check_function.is_null() {
+            let mut code = [0u8; RET.len()];
+            vmi.read(registers.address_context(cfg.check_function), &mut code)?;
+
+            if code != RET {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Checks whether an address is a valid CFG call target in a process,
+    /// according to the CFG bitmap of the process.
+    ///
+    /// This is the check the user-mode CFG check function
+    /// (`LdrpValidateUserCallTarget`) performs, so it reflects call targets
+    /// changed at runtime. Only the native (64-bit) bitmap is consulted;
+    /// addresses outside the range covered by the bitmap are not valid
+    /// targets.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the profile lacks the symbols
+    /// and structures described below (e.g., before Windows 10). Fails if
+    /// the page of the bitmap is not present, which is the case when no
+    /// image was ever mapped in the corresponding range.
+    ///
+    /// # Implementation Details
+    ///
+    /// The bitmap is mapped at the same address in every process. The
+    /// address is taken from the first (native) `_MI_CFG_BITMAP_INFO` in
+    /// `MiState.Vs.CfgBitMap`.
+    ///
+    /// Every 16-byte aligned block of the address space is described by
+    /// two bits. An aligned address is valid if the first bit is set;
+    /// an unaligned address is valid only if both bits are set.
+    pub fn process_cfg_valid_target(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        va: Va,
+    ) -> Result<bool, VmiError> {
+        let (offsets, MiState) = match (&self.offsets.mi_state, self.symbols.MiState) {
+            (Some(offsets), Some(MiState)) => (offsets, MiState),
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let MI_SYSTEM_INFORMATION = &offsets._MI_SYSTEM_INFORMATION;
+        let MI_VISIBLE_STATE = &offsets._MI_VISIBLE_STATE;
+        let MI_CFG_BITMAP_INFO = &offsets._MI_CFG_BITMAP_INFO;
+
+        let CfgBitMap = match MI_VISIBLE_STATE.CfgBitMap {
+            Some(CfgBitMap) => CfgBitMap,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let info = StructReader::new(
+            vmi,
+            registers.address_context(
+                kernel_image_base + MiState + MI_SYSTEM_INFORMATION.Vs.offset + CfgBitMap.offset,
+            ),
+            MI_CFG_BITMAP_INFO.effective_len(),
+        )?;
+
+        let bitmap = Va(info.read(MI_CFG_BITMAP_INFO.BaseAddress)?);
+        let size = info.read(MI_CFG_BITMAP_INFO.RegionSize)?;
+
+        if bitmap.is_null() {
+            return Ok(false);
+        }
+
+        // Each 64-bit word of the bitmap covers 512 bytes.
+        let offset = (va.0 >> 9) * size_of::<u64>() as u64;
+        if offset >= size {
+            return Ok(false);
+        }
+
+        let root = self.process_translation_root(vmi, registers, process)?;
+        let word = vmi.read_u64((bitmap + offset, root))?;
+
+        let bit = (va.0 >> 3) & 0x3e;
+        let valid = match va.0 & 0xf {
+            0 => word & (1 << bit) != 0,
+            _ => word & (0b11 << bit) == 0b11 << bit,
+        };
+
+        Ok(valid)
+    }
+
+    /// Retrieves the exception directory entry (`RUNTIME_FUNCTION`) of
+    /// the function containing the given RVA of a PE image.
+    ///
+    /// Returns `None` if the image has no exception directory, or if the
+    /// RVA is not covered by any entry (e.g., a leaf function, which does
+    /// not need one). Only 64-bit images are supported; for 32-bit images
+    /// `None` is returned.
+    ///
+    /// # Implementation Details
+    ///
+    /// The entries of the exception directory are sorted by their
+    /// `BeginAddress`, so the matching entry is located by a binary search,
+    /// reading only the visited entries from the guest memory.
+    pub fn image_runtime_function(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+        rva: u32,
+    ) -> Result<Option<PeRuntimeFunction>, VmiError> {
+        let (directory, count) = match self.image_exception_directory(vmi, registers, image_base)? {
+            Some(directory) => directory,
+            None => return Ok(None),
+        };
+
+        let read_entry = |index: u64| -> Result<PeRuntimeFunction, VmiError> {
+            let mut data = [0u8; PeRuntimeFunction::SIZE];
+            vmi.read(
+                registers.address_context(directory + index * PeRuntimeFunction::SIZE as u64),
+                &mut data,
+            )?;
+
+            PeRuntimeFunction::parse(&data).map_err(|err| VmiError::Os(err.into()))
+        };
+
+        let mut low = 0;
+        let mut high = count;
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let entry = read_entry(middle)?;
+
+            if rva < entry.begin_address {
+                high = middle;
+            }
+            else if rva >= entry.end_address {
+                low = middle + 1;
+            }
+            else {
+                return Ok(Some(entry));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Retrieves the unwind information (`UNWIND_INFO`) of the function
+    /// containing the given RVA of a PE image.
+    ///
+    /// Returns `None` if no exception directory entry covers the RVA.
+    /// See [`image_runtime_function`] for details.
+    ///
+    /// Chained unwind information is not followed; the returned
+    /// [`PeUnwindInfo::chained`] entry can be passed to
+    /// [`image_unwind_info_at`] to continue.
+    ///
+    /// [`image_runtime_function`]: Self::image_runtime_function
+    /// [`image_unwind_info_at`]: Self::image_unwind_info_at
+    pub fn image_unwind_info(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+        rva: u32,
+    ) -> Result<Option<PeUnwindInfo>, VmiError> {
+        match self.image_runtime_function(vmi, registers, image_base, rva)? {
+            Some(function) => Ok(Some(self.image_unwind_info_at(
+                vmi,
+                registers,
+                image_base,
+                function.unwind_info_address,
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the unwind information (`UNWIND_INFO`) located at the given
+    /// RVA of a PE image.
+    ///
+    /// # Implementation Details
+    ///
+    /// The fixed-size header is read first, so that only the unwind codes
+    /// and the trailing data that are actually present are read.
+    pub fn image_unwind_info_at(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        image_base: Va,
+        unwind_info_rva: u32,
+    ) -> Result<PeUnwindInfo, VmiError> {
+        let address = image_base + unwind_info_rva as u64;
+
+        let mut header = [0u8; 4];
+        vmi.read(registers.address_context(address), &mut header)?;
+
+        //
+        // Version/Flags, SizeOfProlog, CountOfCodes, FrameRegister/Offset,
+        // followed by the unwind codes (padded to an even count) and either
+        // a chained RUNTIME_FUNCTION or the RVA of the exception handler.
+        //
+
+        let count_of_codes = header[2] as usize;
+        let size = header.len()
+            + count_of_codes.next_multiple_of(2) * size_of::<u16>()
+            + PeRuntimeFunction::SIZE;
+
+        let mut data = vec![0u8; size];
+        vmi.read(registers.address_context(address), &mut data)?;
+
+        PeUnwindInfo::parse(&data).map_err(|err| VmiError::Os(err.into()))
+    }
+
+    // endregion: Image
+
+    // region: Kernel
+
+    /// Locates the Windows kernel in memory based on the CPU registers.
+    /// This function is architecture-specific.
+    ///
+    /// On AMD64, the kernel is located by taking the `MSR_LSTAR` value and
+    /// reading the virtual memory page by page backwards until the `MZ` header
+    /// is found.
+    pub fn find_kernel(
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<WindowsKernelInformation>, VmiError> {
+        Driver::Architecture::find_kernel(vmi, registers)
+    }
+
+    /// Retrieves the kernel information string.
+    ///
+    /// # Implementation Details
+    ///
+    /// The kernel information string is located by reading the `NtBuildLabEx`
+    /// symbol from the kernel image.
+    pub fn kernel_information_string_ex(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<<Driver as VmiDriver>::Architecture as Architecture>::Registers,
+    ) -> Result<Option<String>, VmiError> {
+        let NtBuildLabEx = match self.symbols.NtBuildLabEx {
+            Some(offset) => offset,
+            None => return Ok(None),
+        };
+
+        if let Some(nt_build_lab_ex) = self.nt_build_lab_ex.borrow().as_ref() {
+            return Ok(Some(nt_build_lab_ex.clone()));
+        }
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let nt_build_lab_ex =
+            vmi.read_string(registers.address_context(kernel_image_base + NtBuildLabEx))?;
+        *self.nt_build_lab_ex.borrow_mut() = Some(nt_build_lab_ex.clone());
+
+        Ok(Some(nt_build_lab_ex))
+    }
+
+    /// Retrieves information about a kernel module from a pointer to
+    /// `KLDR_DATA_TABLE_ENTRY`
+    pub fn kernel_module(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        addr: Va, // _KLDR_DATA_TABLE_ENTRY*
+    ) -> Result<OsModule, VmiError> {
+        let KLDR_DATA_TABLE_ENTRY = &self.offsets.common._KLDR_DATA_TABLE_ENTRY;
+
+        let base_address = vmi.read_va(
+            registers.address_context(addr + KLDR_DATA_TABLE_ENTRY.DllBase.offset),
+            registers.address_width(),
+        )?;
+
+        let size = vmi
+            .read_u32(registers.address_context(addr + KLDR_DATA_TABLE_ENTRY.SizeOfImage.offset))?
+            as u64;
+
+        let name = self.read_unicode_string(
+            vmi,
+            registers.address_context(addr + KLDR_DATA_TABLE_ENTRY.BaseDllName.offset),
+        )?;
+
+        Ok(OsModule {
+            base_address,
+            size,
+            name,
+        })
+    }
+
+    /// Locates the kernel debugger data block (`KdDebuggerDataBlock`).
+    ///
+    /// Returns `None` if the block could not be found.
+    ///
+    /// # Implementation Details
+    ///
+    /// The kernel image is scanned for the `KDBG` owner tag of the
+    /// `DBGKD_DEBUG_DATA_HEADER64` header. A match is accepted only if
+    /// the `KernBase` field that follows the header points back to the
+    /// kernel image base. Pages of the image that are not present are
+    /// skipped.
+    ///
+    /// On 64-bit Windows 8 and later, the block is encoded unless a kernel
+    /// debugger is attached, in which case the signature will not be found.
+    pub fn kernel_debugger_data_block(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<Va>, VmiError> {
+        const KDBG_OWNER_TAG: &[u8; 4] = b"KDBG";
+        const KDBG_OWNER_TAG_OFFSET: u64 = 0x10;
+        const KDBG_KERN_BASE_OFFSET: u64 = 0x18;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let kernel_image_size = self.image_size(vmi, registers, kernel_image_base)?;
+
+        let mut page = [0u8; Amd64::PAGE_SIZE as usize];
+        for page_offset in (0..kernel_image_size).step_by(Amd64::PAGE_SIZE as usize) {
+            let page_address = kernel_image_base + page_offset;
+            if vmi
+                .read(registers.address_context(page_address), &mut page)
+                .is_err()
+            {
+                continue;
+            }
+
+            // The header is 8-byte aligned, so is the owner tag.
+            for offset in (0..page.len()).step_by(8) {
+                if &page[offset..offset + KDBG_OWNER_TAG.len()] != KDBG_OWNER_TAG {
+                    continue;
+                }
+
+                let tag_address = page_address + offset as u64;
+                if tag_address < kernel_image_base + KDBG_OWNER_TAG_OFFSET {
+                    continue;
+                }
+
+                let block = tag_address - KDBG_OWNER_TAG_OFFSET;
+                let kern_base =
+                    match vmi.read_u64(registers.address_context(block + KDBG_KERN_BASE_OFFSET)) {
+                        Ok(kern_base) => kern_base,
+                        Err(_) => continue,
+                    };
+
+                if kern_base == kernel_image_base.0 {
+                    return Ok(Some(block));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Retrieves the list of loaded kernel modules using the
+    /// `PsLoadedModuleList` pointer stored in the kernel debugger data
+    /// block.
+    ///
+    /// Returns `None` if the kernel debugger data block could not be found.
+    ///
+    /// Unlike [`VmiOs::modules`], this does not rely on the
+    /// `PsLoadedModuleList` symbol. Comparing the two lists can reveal
+    /// modules that were unlinked from one of them.
+    ///
+    /// # Implementation Details
+    ///
+    /// The block is read and decoded by [`kdbg`].
+    ///
+    /// [`kdbg`]: Self::kdbg
+    pub fn loaded_module_list_from_kdbg(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<Vec<OsModule>>, VmiError> {
+        let PsLoadedModuleList = match self.kdbg(vmi, registers)? {
+            Some(kdbg) => kdbg.ps_loaded_module_list,
+            None => return Ok(None),
+        };
+
+        Ok(Some(self.kernel_modules_from_list(
+            vmi,
+            registers,
+            PsLoadedModuleList,
+        )?))
+    }
+
+    /// Reads and decodes the kernel debugger data block
+    /// (`KdDebuggerDataBlock`).
+    ///
+    /// Returns `None` if the block could not be found or decoded.
+    ///
+    /// # Implementation Details
+    ///
+    /// The block is located by the `KdDebuggerDataBlock` symbol if it is
+    /// available, or by [`kernel_debugger_data_block`] otherwise.
+    ///
+    /// On 64-bit Windows 8 and later, the block is encoded unless a kernel
+    /// debugger is attached, which is indicated by `KdpDataBlockEncoded`.
+    /// Each 64-bit value of the block is encoded with the `KiWaitNever` and
+    /// `KiWaitAlways` values and the address of the `KdpDataBlockEncoded`
+    /// variable itself (see `KdCopyDataBlock`). An encoded block therefore
+    /// can't be decoded without the `KdpDataBlockEncoded` symbol. The header
+    /// of the decoded block must carry the `KDBG` owner tag.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// for (ULONG i = 0; i < Size / sizeof(ULONG64); i++) {
+    ///     Value = Block[i] ^ KiWaitNever;
+    ///     Value = _rotl64(Value, (UCHAR)KiWaitNever);
+    ///     Value = _byteswap_uint64(Value ^ (ULONG_PTR)&KdpDataBlockEncoded);
+    ///     Block[i] = Value ^ KiWaitAlways;
+    /// }
+    /// ```
+    ///
+    /// [`kernel_debugger_data_block`]: Self::kernel_debugger_data_block
+    pub fn kdbg(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<WindowsKdDebuggerData>, VmiError> {
+        const KDBG_OWNER_TAG: &[u8; 4] = b"KDBG";
+        const KDBG_HEADER_SIZE: usize = 0x18;
+        const KDBG_MAX_SIZE: usize = 0x1000;
+
+        const KDBG_KERN_BASE_OFFSET: usize = 0x18;
+        const KDBG_PS_LOADED_MODULE_LIST_OFFSET: usize = 0x48;
+        const KDBG_PS_ACTIVE_PROCESS_HEAD_OFFSET: usize = 0x50;
+        const KDBG_PSP_CID_TABLE_OFFSET: usize = 0x58;
+        const KDBG_OBP_ROOT_DIRECTORY_OBJECT_OFFSET: usize = 0x98;
+        const KDBG_MM_PFN_DATABASE_OFFSET: usize = 0xc0;
+        const KDBG_MM_HIGHEST_USER_ADDRESS_OFFSET: usize = 0x1c8;
+        const KDBG_NT_BUILD_LAB_OFFSET: usize = 0x208;
+        const KDBG_KI_PROCESSOR_BLOCK_OFFSET: usize = 0x218;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        let block = match self.symbols.KdDebuggerDataBlock {
+            Some(KdDebuggerDataBlock) => kernel_image_base + KdDebuggerDataBlock,
+            None => match self.kernel_debugger_data_block(vmi, registers)? {
+                Some(block) => block,
+                None => return Ok(None),
+            },
+        };
+
+        let encoded_flag = self
+            .symbols
+            .KdpDataBlockEncoded
+            .map(|KdpDataBlockEncoded| kernel_image_base + KdpDataBlockEncoded);
+
+        let wait_keys = self.wait_keys(vmi, registers)?;
+        let decode = |data: &mut [u8]| {
+            if let (Some(wait_keys), Some(encoded_flag)) = (wait_keys, encoded_flag) {
+                for chunk in data.chunks_exact_mut(size_of::<u64>()) {
+                    let value = u64::from_le_bytes(chunk.try_into().unwrap());
+                    let value = Self::decode_wait_pointer(value, encoded_flag, wait_keys);
+                    chunk.copy_from_slice(&value.to_le_bytes());
+                }
+            }
+        };
+
+        //
+        // The header is read first, to find out whether the block is encoded
+        // and how large it is.
+        //
+
+        let mut header = [0u8; KDBG_HEADER_SIZE];
+        vmi.read(registers.address_context(block), &mut header)?;
+
+        let encoded = match encoded_flag {
+            Some(encoded_flag) => vmi.read_u8(registers.address_context(encoded_flag))? != 0,
+            None => &header[0x10..0x14] != KDBG_OWNER_TAG,
+        };
+
+        if encoded {
+            if wait_keys.is_none() || encoded_flag.is_none() {
+                return Ok(None);
+            }
+
+            decode(&mut header);
+        }
+
+        if &header[0x10..0x14] != KDBG_OWNER_TAG {
+            return Ok(None);
+        }
+
+        let size =
+            u32::from_le_bytes([header[0x14], header[0x15], header[0x16], header[0x17]]) as usize;
+
+        if !(KDBG_HEADER_SIZE..=KDBG_MAX_SIZE).contains(&size) {
+            return Ok(None);
+        }
+
+        let mut data = vec![0u8; size.next_multiple_of(size_of::<u64>())];
+        vmi.read(registers.address_context(block), &mut data)?;
+
+        if encoded {
+            decode(&mut data);
+        }
+
+        data.truncate(size);
+
+        let field = |offset: usize| {
+            Va(data
+                .get(offset..offset + size_of::<u64>())
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                .unwrap_or_default())
+        };
+
+        let result = WindowsKdDebuggerData {
+            address: block,
+            encoded,
+            kernel_base: field(KDBG_KERN_BASE_OFFSET),
+            ps_loaded_module_list: field(KDBG_PS_LOADED_MODULE_LIST_OFFSET),
+            ps_active_process_head: field(KDBG_PS_ACTIVE_PROCESS_HEAD_OFFSET),
+            psp_cid_table: field(KDBG_PSP_CID_TABLE_OFFSET),
+            obp_root_directory_object: field(KDBG_OBP_ROOT_DIRECTORY_OBJECT_OFFSET),
+            mm_pfn_database: field(KDBG_MM_PFN_DATABASE_OFFSET),
+            mm_highest_user_address: field(KDBG_MM_HIGHEST_USER_ADDRESS_OFFSET),
+            nt_build_lab: field(KDBG_NT_BUILD_LAB_OFFSET),
+            ki_processor_block: field(KDBG_KI_PROCESSOR_BLOCK_OFFSET),
+            data,
+        };
+
+        Ok(Some(result))
+    }
+
+    /// Cross-validates the kernel debugger data block against the symbols
+    /// of the profile.
+    ///
+    /// Returns the names of the fields of the block that don't match the
+    /// address of the corresponding symbol. An empty result means that the
+    /// profile matches the running kernel.
+    pub fn kdbg_mismatches(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        kdbg: &WindowsKdDebuggerData,
+    ) -> Result<Vec<&'static str>, VmiError> {
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        let fields = [
+            ("KernBase", kdbg.kernel_base, kernel_image_base),
+            (
+                "PsLoadedModuleList",
+                kdbg.ps_loaded_module_list,
+                kernel_image_base + self.symbols.PsLoadedModuleList,
+            ),
+            (
+                "PsActiveProcessHead",
+                kdbg.ps_active_process_head,
+                kernel_image_base + self.symbols.PsActiveProcessHead,
+            ),
+            (
+                "ObpRootDirectoryObject",
+                kdbg.obp_root_directory_object,
+                kernel_image_base + self.symbols.ObpRootDirectoryObject,
+            ),
+            (
+                "MmPfnDatabase",
+                kdbg.mm_pfn_database,
+                kernel_image_base + self.symbols.MmPfnDatabase,
+            ),
+            (
+                "MmHighestUserAddress",
+                kdbg.mm_highest_user_address,
+                kernel_image_base + self.symbols.MmHighestUserAddress,
+            ),
+            (
+                "NtBuildLab",
+                kdbg.nt_build_lab,
+                kernel_image_base + self.symbols.NtBuildLab,
+            ),
+            (
+                "KiProcessorBlock",
+                kdbg.ki_processor_block,
+                kernel_image_base + self.symbols.KiProcessorBlock,
+            ),
+        ];
+
+        Ok(fields
+            .into_iter()
+            .filter(|&(_, kdbg_value, symbol_value)| kdbg_value != symbol_value)
+            .map(|(name, _, _)| name)
+            .collect())
+    }
+
+    /// Retrieves the boot loader parameter block.
+    ///
+    /// The loader block captures the boot options (relevant, e.g., for
+    /// detecting debugging or test-signing) and the modules loaded by the
+    /// boot loader, before `PsLoadedModuleList` is fully populated.
+    ///
+    /// Returns `None` once the kernel has freed the loader block, and
+    /// [`VmiError::NotSupported`] if the `KeLoaderBlock` symbol or the
+    /// loader block structures are not available.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `KeLoaderBlock` symbol points to the `_LOADER_PARAMETER_BLOCK`
+    /// structure. The kernel clears it when the loader block is freed.
+    pub fn loader_block(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<WindowsLoaderBlock>, VmiError> {
+        let KeLoaderBlock = match self.symbols.KeLoaderBlock {
+            Some(KeLoaderBlock) => self.kernel_image_base(vmi, registers)? + KeLoaderBlock,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let offsets = match &self.offsets.loader_block {
+            Some(offsets) => offsets,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let LOADER_PARAMETER_BLOCK = &offsets._LOADER_PARAMETER_BLOCK;
+        let BOOT_DRIVER_LIST_ENTRY = &offsets._BOOT_DRIVER_LIST_ENTRY;
+
+        let block = vmi.read_va(
+            registers.address_context(KeLoaderBlock),
+            registers.address_width(),
+        )?;
+
+        if block.is_null() {
+            return Ok(None);
+        }
+
+        let loader_block = StructReader::new(
+            vmi,
+            registers.address_context(block),
+            LOADER_PARAMETER_BLOCK.effective_len(),
+        )?;
+        let load_options = Va(loader_block.read(LOADER_PARAMETER_BLOCK.LoadOptions)?);
+        let arc_boot_device_name = Va(loader_block.read(LOADER_PARAMETER_BLOCK.ArcBootDeviceName)?);
+        let nls_data = Va(loader_block.read(LOADER_PARAMETER_BLOCK.NlsData)?);
+
+        let load_options = match load_options.is_null() {
+            false => vmi.read_string(registers.address_context(load_options))?,
+            true => String::new(),
+        };
+
+        let arc_boot_device_name = match arc_boot_device_name.is_null() {
+            false => vmi.read_string(registers.address_context(arc_boot_device_name))?,
+            true => String::new(),
+        };
+
+        let modules = self.kernel_modules_from_list(
+            vmi,
+            registers,
+            block + LOADER_PARAMETER_BLOCK.LoadOrderListHead.offset,
+        )?;
+
+        let mut boot_drivers = Vec::new();
+        self.enumerate_list(
+            vmi,
+            registers,
+            block + LOADER_PARAMETER_BLOCK.BootDriverListHead.offset,
+            |entry| {
+                let entry = entry - BOOT_DRIVER_LIST_ENTRY.Link.offset;
+
+                match self.boot_driver(vmi, registers, entry) {
+                    Ok(driver) => boot_drivers.push(driver),
+                    Err(err) => tracing::debug!(%entry, ?err, "failed to read boot driver"),
+                }
+
+                true
+            },
+        )?;
+
+        Ok(Some(WindowsLoaderBlock {
+            address: block,
+            load_options,
+            arc_boot_device_name,
+            modules,
+            boot_drivers,
+            nls_data,
+        }))
+    }
+
+    /// Reads a `_BOOT_DRIVER_LIST_ENTRY` structure.
+    fn boot_driver(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        entry: Va,
+    ) -> Result<WindowsBootDriver, VmiError> {
+        let offsets = match &self.offsets.loader_block {
+            Some(offsets) => offsets,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let BOOT_DRIVER_LIST_ENTRY = &offsets._BOOT_DRIVER_LIST_ENTRY;
+
+        let file_path = self.read_unicode_string(
+            vmi,
+            registers.address_context(entry + BOOT_DRIVER_LIST_ENTRY.FilePath.offset),
+        )?;
+
+        let registry_path = self.read_unicode_string(
+            vmi,
+            registers.address_context(entry + BOOT_DRIVER_LIST_ENTRY.RegistryPath.offset),
+        )?;
+
+        let loader_entry = vmi.read_va(
+            registers.address_context(entry + BOOT_DRIVER_LIST_ENTRY.LdrEntry.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(WindowsBootDriver {
+            file_path,
+            registry_path,
+            loader_entry: (!loader_entry.is_null()).then_some(loader_entry),
+        })
+    }
+
+    /// Retrieves the system service dispatch table (SSDT).
+    ///
+    /// Entries pointing outside of the kernel image are flagged as hooked.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `KeServiceDescriptorTable`
+    /// symbol is not available.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `KeServiceDescriptorTable` symbol points to
+    /// a `_KSERVICE_TABLE_DESCRIPTOR` structure, whose `Base` and `Limit`
+    /// fields describe the `KiServiceTable` array.
+    ///
+    /// On 64-bit Windows, each entry is a 32-bit value whose upper 28 bits
+    /// hold the signed offset of the routine from the table, and the lower
+    /// 4 bits hold the number of arguments passed on the stack. On 32-bit
+    /// Windows, each entry is the address of the routine.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PKSERVICE_TABLE_DESCRIPTOR Descriptor = &KeServiceDescriptorTable;
+    /// for (ULONG Index = 0; Index < Descriptor->Limit; Index++) {
+    ///     LONG Entry = ((PLONG)Descriptor->Base)[Index];
+    ///     PVOID Target = (PUCHAR)Descriptor->Base + (Entry >> 4);
+    /// }
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// The `_KSERVICE_TABLE_DESCRIPTOR` structure is not part of the public
+    /// symbols, but its layout has been stable since Windows XP.
+    pub fn ssdt(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<WindowsServiceTable, VmiError> {
+        let KeServiceDescriptorTable = match self.symbols.KeServiceDescriptorTable {
+            Some(KeServiceDescriptorTable) => {
+                self.kernel_image_base(vmi, registers)? + KeServiceDescriptorTable
+            }
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let kernel_image_size = self.image_size(vmi, registers, kernel_image_base)?;
+        let kernel_image_end = kernel_image_base + kernel_image_size;
+
+        let modules = self.kernel_modules_from_list(
+            vmi,
+            registers,
+            kernel_image_base + self.symbols.PsLoadedModuleList,
+        )?;
+
+        let result = self.service_table(
+            vmi,
+            registers,
+            KeServiceDescriptorTable,
+            &modules,
+            |target| kernel_image_base <= target && target < kernel_image_end,
+        )?;
+
+        match result {
+            Some(result) => Ok(result),
+            None => Err(VmiError::Other("KiServiceTable is not initialized")),
+        }
+    }
+
+    /// Retrieves the shadow system service dispatch table, which dispatches
+    /// the `win32k` system services.
+    ///
+    /// Entries pointing outside of the `win32k*.sys` modules are flagged
+    /// as hooked.
+    ///
+    /// Returns `None` if `win32k.sys` has not registered its table yet,
+    /// and [`VmiError::NotSupported`] if the `KeServiceDescriptorTableShadow`
+    /// symbol is not available.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `KeServiceDescriptorTableShadow` symbol points to an array of
+    /// `_KSERVICE_TABLE_DESCRIPTOR` structures. The first one mirrors
+    /// `KeServiceDescriptorTable`, the second one describes the
+    /// `W32pServiceTable` array. Entries are encoded in the same way as in
+    /// [`ssdt`].
+    ///
+    /// # Notes
+    ///
+    /// `W32pServiceTable` resides in session space, which is mapped only in
+    /// the address space of processes attached to a session. The `registers`
+    /// must therefore belong to such a process (e.g., `csrss.exe` or any GUI
+    /// process), otherwise reading the table fails.
+    ///
+    /// [`ssdt`]: Self::ssdt
+    pub fn shadow_ssdt(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<WindowsServiceTable>, VmiError> {
+        let KeServiceDescriptorTableShadow = match self.symbols.KeServiceDescriptorTableShadow {
+            Some(KeServiceDescriptorTableShadow) => {
+                self.kernel_image_base(vmi, registers)? + KeServiceDescriptorTableShadow
+            }
+            None => return Err(VmiError::NotSupported),
+        };
+
+        // sizeof(KSERVICE_TABLE_DESCRIPTOR) == 4 * sizeof(PVOID)
+        let descriptor_size = 4 * registers.address_width() as u64;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let modules = self.kernel_modules_from_list(
+            vmi,
+            registers,
+            kernel_image_base + self.symbols.PsLoadedModuleList,
+        )?;
+
+        let win32k = modules
+            .iter()
+            .filter(|module| module.name.to_ascii_lowercase().starts_with("win32k"))
+            .map(|module| (module.base_address, module.base_address + module.size))
+            .collect::<Vec<_>>();
+
+        self.service_table(
+            vmi,
+            registers,
+            KeServiceDescriptorTableShadow + descriptor_size,
+            &modules,
+            |target| {
+                win32k
+                    .iter()
+                    .any(|&(start, end)| start <= target && target < end)
+            },
+        )
+    }
+
+    /// Reads the system service dispatch table described by
+    /// a `_KSERVICE_TABLE_DESCRIPTOR` structure.
+    ///
+    /// Returns `None` if the table is not initialized.
+    fn service_table(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        descriptor: Va, // _KSERVICE_TABLE_DESCRIPTOR*
+        modules: &[OsModule],
+        is_expected: impl Fn(Va) -> bool,
+    ) -> Result<Option<WindowsServiceTable>, VmiError> {
+        // Guard against reading a garbage descriptor.
+        const MAX_SERVICE_COUNT: u32 = 0x1000;
+
+        let address_width = registers.address_width();
+
+        // typedef struct _KSERVICE_TABLE_DESCRIPTOR {
+        //     PULONG Base;
+        //     PULONG Count;
+        //     ULONG Limit;
+        //     PUCHAR Number;
+        // } KSERVICE_TABLE_DESCRIPTOR, *PKSERVICE_TABLE_DESCRIPTOR;
+        let base = vmi.read_va(registers.address_context(descriptor), address_width)?;
+        let limit =
+            vmi.read_u32(registers.address_context(descriptor + 2 * address_width as u64))?;
+
+        if base.is_null() || limit == 0 {
+            return Ok(None);
+        }
+
+        if limit > MAX_SERVICE_COUNT {
+            tracing::warn!(%descriptor, limit, "invalid service table limit");
+            return Err(VmiError::Other("invalid service table limit"));
+        }
+
+        let mut buffer = vec![0u8; limit as usize * 4];
+        vmi.read(registers.address_context(base), &mut buffer)?;
+
+        let mut entries = Vec::with_capacity(limit as usize);
+        for (index, entry) in buffer.chunks_exact(4).enumerate() {
+            let entry = u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]);
+
+            let target = match address_width {
+                8 => Va(base.0.wrapping_add_signed((entry as i32 >> 4) as i64)),
+                _ => Va(entry as u64),
+            };
+
+            let module = modules
+                .iter()
+                .find(|module| {
+                    module.base_address <= target && target < module.base_address + module.size
+                })
+                .map(|module| module.name.clone());
+
+            entries.push(WindowsServiceTableEntry {
+                index: index as u32,
+                target,
+                module,
+                hooked: !is_expected(target),
+            });
+        }
+
+        Ok(Some(WindowsServiceTable {
+            address: base,
+            entries,
+        }))
+    }
+
+    /// Retrieves the HAL dispatch table (`HalDispatchTable`).
+    ///
+    /// The table holds function pointers the kernel calls into the HAL
+    /// through (e.g., `HalQuerySystemInformation`). Overwriting one of them
+    /// is a classic way to gain kernel code execution or to hook the HAL,
+    /// so entries pointing outside of `ntoskrnl.exe` and `hal.dll` are
+    /// flagged as hooked.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `HalDispatchTable` symbol
+    /// is not available.
+    ///
+    /// # Implementation Details
+    ///
+    /// The layout of the table is taken from the `_HAL_DISPATCH` type of
+    /// the profile. If the type is not available, the documented layout of
+    /// the `HAL_DISPATCH` structure from `ntddk.h` is used.
+    ///
+    /// The resolved modules can be passed to a symbolizer (such as the
+    /// `ModuleSymbolizer` of `vmi-utils`) to name the functions.
+    pub fn hal_dispatch_table(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<WindowsHalDispatchTable, VmiError> {
+        let HalDispatchTable = match self.symbols.HalDispatchTable {
+            Some(HalDispatchTable) => self.kernel_image_base(vmi, registers)? + HalDispatchTable,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        self.hal_table(vmi, registers, HalDispatchTable, &self.hal_dispatch_layout)
+    }
+
+    /// Retrieves the private HAL dispatch table (`HalPrivateDispatchTable`).
+    ///
+    /// The table is the undocumented counterpart of the
+    /// [`hal_dispatch_table`], used e.g. for interrupt, timer and
+    /// processor management. Entries pointing outside of `ntoskrnl.exe`
+    /// and `hal.dll` are flagged as hooked.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `HalPrivateDispatchTable`
+    /// symbol or the `_HAL_PRIVATE_DISPATCH` type is not available.
+    ///
+    /// # Notes
+    ///
+    /// The layout of the table changes between Windows builds, so it is
+    /// taken from the `_HAL_PRIVATE_DISPATCH` type of the profile only.
+    ///
+    /// [`hal_dispatch_table`]: Self::hal_dispatch_table
+    pub fn hal_private_dispatch_table(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<WindowsHalDispatchTable, VmiError> {
+        let HalPrivateDispatchTable = match self.symbols.HalPrivateDispatchTable {
+            Some(HalPrivateDispatchTable) => {
+                self.kernel_image_base(vmi, registers)? + HalPrivateDispatchTable
+            }
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let layout = match &self.hal_private_dispatch_layout {
+            Some(layout) => layout,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        self.hal_table(vmi, registers, HalPrivateDispatchTable, layout)
+    }
+
+    /// Reads a HAL dispatch table with the given layout.
+    fn hal_table(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        address: Va,
+        layout: &[(String, u64)],
+    ) -> Result<WindowsHalDispatchTable, VmiError> {
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let modules = self.kernel_modules_from_list(
+            vmi,
+            registers,
+            kernel_image_base + self.symbols.PsLoadedModuleList,
+        )?;
+
+        //
+        // Since Windows 10 2004, the HAL is part of `ntoskrnl.exe` and
+        // `hal.dll` is only a stub, but older versions still dispatch into
+        // `hal.dll`. The kernel is always the first module in the list.
+        //
+
+        let expected = modules
+            .iter()
+            .enumerate()
+            .filter(|(index, module)| *index == 0 || module.name.eq_ignore_ascii_case("hal.dll"))
+            .map(|(_, module)| (module.base_address, module.base_address + module.size))
+            .collect::<Vec<_>>();
+
+        let version = vmi.read_u32(registers.address_context(address))?;
+
+        let mut entries = Vec::with_capacity(layout.len());
+        for (name, offset) in layout {
+            let target = vmi.read_va(
+                registers.address_context(address + *offset),
+                registers.address_width(),
+            )?;
+
+            let module = modules
+                .iter()
+                .find(|module| {
+                    module.base_address <= target && target < module.base_address + module.size
+                })
+                .map(|module| module.name.clone());
+
+            let hooked = !target.is_null()
+                && !expected
+                    .iter()
+                    .any(|&(start, end)| start <= target && target < end);
+
+            entries.push(WindowsHalDispatchEntry {
+                name: name.clone(),
+                offset: *offset,
+                target,
+                module,
+                hooked,
+            });
+        }
+
+        Ok(WindowsHalDispatchTable {
+            address,
+            version,
+            entries,
+        })
+    }
+
+    /// Retrieves the bugcheck the system is crashing with, if any.
+    ///
+    /// The kernel records the stop code and its parameters in
+    /// `KiBugCheckData` when `KeBugCheckEx` is called, before the crash
+    /// dump is written. Returns `None` if no bugcheck is in progress.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `KiBugCheckData` symbol is
+    /// not available.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// if (KiBugCheckData[0] == 0) {
+    ///     return NULL;
+    /// }
+    ///
+    /// return {
+    ///     (ULONG)KiBugCheckData[0],
+    ///     { KiBugCheckData[1], KiBugCheckData[2], KiBugCheckData[3], KiBugCheckData[4] }
+    /// };
+    /// ```
+    pub fn bugcheck_info(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<WindowsBugCheck>, VmiError> {
+        let KiBugCheckData = match self.symbols.KiBugCheckData {
+            Some(KiBugCheckData) => self.kernel_image_base(vmi, registers)? + KiBugCheckData,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        // ULONG_PTR KiBugCheckData[5];
+        let address_width = registers.address_width();
+        let mut data = [0u64; 5];
+        for (index, value) in data.iter_mut().enumerate() {
+            let address = KiBugCheckData + (index * address_width) as u64;
+            *value = vmi.read_address(registers.address_context(address), address_width)?;
+        }
+
+        if data[0] == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(WindowsBugCheck {
+            code: data[0] as u32,
+            parameters: [data[1], data[2], data[3], data[4]],
+        }))
+    }
+
+    /// Collects the kernel modules linked in the given list of
+    /// `KLDR_DATA_TABLE_ENTRY` structures.
+    fn kernel_modules_from_list(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        list_head: Va,
+    ) -> Result<Vec<OsModule>, VmiError> {
+        let mut result = Vec::new();
+
+        let KLDR_DATA_TABLE_ENTRY = &self.offsets.common._KLDR_DATA_TABLE_ENTRY;
+
+        self.enumerate_list(vmi, registers, list_head, |entry| {
+            let module_entry = entry - KLDR_DATA_TABLE_ENTRY.InLoadOrderLinks.offset;
+
+            if let Ok(module) = self.kernel_module(vmi, registers, module_entry) {
+                result.push(module)
+            }
+
+            true
+        })?;
+
+        Ok(result)
+    }
+
+    // endregion: Kernel
+
+    // region: Memory
+
+    /// Retrieves the large pool allocations.
+    ///
+    /// Allocations larger than a page are not carved from pool pages, so
+    /// they don't carry a `_POOL_HEADER` and can't be found by pool tag
+    /// scanning. Instead, the kernel tracks them in the big page table.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the kernel doesn't export the
+    /// big page table symbols.
+    ///
+    /// # Implementation Details
+    ///
+    /// `PoolBigPageTable` points to an array of `PoolBigPageTableSize`
+    /// `_POOL_TRACKER_BIG_PAGES` entries. Bit 0 of the `Va` field is set
+    /// for free entries (`POOL_BIG_TABLE_ENTRY_FREE`), which are skipped.
+    pub fn big_pool_allocations(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<WindowsBigPoolAllocation>, VmiError> {
+        const POOL_BIG_TABLE_ENTRY_FREE: u64 = 1;
+
+        // The table grows with the number of large allocations, but even on
+        // systems with terabytes of memory it stays far below this limit.
+        const MAX_TABLE_SIZE: u64 = 0x1000_0000;
+
+        let POOL_TRACKER_BIG_PAGES = &self.offsets.common._POOL_TRACKER_BIG_PAGES;
+
+        let (PoolBigPageTable, PoolBigPageTableSize) = match (
+            self.symbols.PoolBigPageTable,
+            self.symbols.PoolBigPageTableSize,
+        ) {
+            (Some(PoolBigPageTable), Some(PoolBigPageTableSize)) => {
+                (PoolBigPageTable, PoolBigPageTableSize)
+            }
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let address_width = registers.address_width();
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        let table = vmi.read_va(
+            registers.address_context(kernel_image_base + PoolBigPageTable),
+            address_width,
+        )?;
+        let table_size = vmi.read_address(
+            registers.address_context(kernel_image_base + PoolBigPageTableSize),
+            address_width,
+        )?;
+
+        if table.is_null() {
+            return Ok(Vec::new());
+        }
+
+        let entry_size = POOL_TRACKER_BIG_PAGES.len() as u64;
+        let size = table_size
+            .checked_mul(entry_size)
+            .filter(|&size| size <= MAX_TABLE_SIZE)
+            .ok_or(VmiError::CorruptedStruct("PoolBigPageTableSize"))?;
+
+        let mut data = vec![0u8; size as usize];
+        vmi.read(registers.address_context(table), &mut data)?;
+
+        let mut result = Vec::new();
+        for entry in data.chunks_exact(entry_size as usize) {
+            let read_field = |field: Field| {
+                let offset = field.offset as usize;
+                let mut value = [0u8; 8];
+                value[..field.size as usize]
+                    .copy_from_slice(&entry[offset..offset + field.size as usize]);
+                u64::from_le_bytes(value)
+            };
+
+            let va = read_field(POOL_TRACKER_BIG_PAGES.Va);
+            if va == 0 || va & POOL_BIG_TABLE_ENTRY_FREE != 0 {
+                continue;
+            }
+
+            let key = read_field(POOL_TRACKER_BIG_PAGES.Key) as u32;
+
+            result.push(WindowsBigPoolAllocation {
+                address: Va(va),
+                tag: key.to_le_bytes(),
+                size: read_field(POOL_TRACKER_BIG_PAGES.NumberOfBytes),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves information about a Virtual Address Descriptor (VAD) for a
+    /// given address.
+    ///
+    /// This method extracts details such as the starting and ending virtual
+    /// page numbers, VAD type, memory protection, and other flags
+    /// associated with the specified VAD.
+    pub fn vad(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        vad: Va,
+    ) -> Result<WindowsVad, VmiError> {
+        let MMVAD_FLAGS = &self.offsets.common._MMVAD_FLAGS;
+        let MMVAD_SHORT = &self.offsets.common._MMVAD_SHORT;
+
+        let mmvad = StructReader::new(
+            vmi,
+            registers.address_context(vad),
+            MMVAD_SHORT.effective_len(),
+        )?;
+        let starting_vpn_low = mmvad.read(MMVAD_SHORT.StartingVpn)?;
+        let ending_vpn_low = mmvad.read(MMVAD_SHORT.EndingVpn)?;
+        let starting_vpn_high = match MMVAD_SHORT.StartingVpnHigh {
+            Some(StartingVpnHigh) => mmvad.read(StartingVpnHigh)?,
+            None => 0,
+        };
+        let ending_vpn_high = match MMVAD_SHORT.EndingVpnHigh {
+            Some(EndingVpnHigh) => mmvad.read(EndingVpnHigh)?,
+            None => 0,
+        };
+
+        let starting_vpn = (starting_vpn_high << 32) | starting_vpn_low;
+        let ending_vpn = (ending_vpn_high << 32) | ending_vpn_low;
+
+        let vad_flags = mmvad.read(MMVAD_SHORT.VadFlags)?;
+        let vad_type = MMVAD_FLAGS.VadType.value_from(vad_flags) as u8;
+        let protection = MMVAD_FLAGS.Protection.value_from(vad_flags) as u8;
+        let private_memory = MMVAD_FLAGS.PrivateMemory.value_from(vad_flags) != 0;
+
+        // If `MMVAD_FLAGS.MemCommit` is present (Windows 7), then we fetch the
+        // value from it. Otherwise, we load the `VadFlags1` field from the VAD
+        // and fetch it from there.
+        let mem_commit = match MMVAD_FLAGS.MemCommit {
+            // `MemCommit` is present in `MMVAD_FLAGS`
+            Some(MemCommit) => MemCommit.value_from(vad_flags) != 0,
+
+            None => match (&self.offsets.ext, MMVAD_SHORT.VadFlags1) {
+                // `MemCommit` is present in `MMVAD_FLAGS1`
+                (Some(OffsetsExt::V2(offsets)), Some(VadFlags1)) => {
+                    let MMVAD_FLAGS1 = &offsets._MMVAD_FLAGS1;
+                    let vad_flags1 = mmvad.read(VadFlags1)?;
+                    MMVAD_FLAGS1.MemCommit.value_from(vad_flags1) != 0
+                }
+                _ => {
+                    panic!("Failed to read MemCommit from VAD");
+                }
+            },
+        };
+
+        let left_child = Va(mmvad.read(MMVAD_SHORT.Left)?);
+        let right_child = Va(mmvad.read(MMVAD_SHORT.Right)?);
+
+        Ok(WindowsVad {
+            starting_vpn,
+            ending_vpn,
+            vad_type,
+            protection,
+            private_memory,
+            mem_commit,
+            left_child,
+            right_child,
+        })
+    }
+
+    /// Locates the `VadRoot` for a given process. Returns the address of the
+    /// root node.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// // For Windows 7:
+    /// return Process->VadRoot->BalancedRoot;
+    ///
+    /// // For Windows 8.1 and later:
+    /// return Process->VadRoot->Root;
+    /// ```
+    pub fn vad_root(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Va, VmiError> {
+        match &self.offsets.ext {
+            Some(OffsetsExt::V1(offsets)) => self.vad_root_v1(vmi, registers, process, offsets),
+            Some(OffsetsExt::V2(offsets)) => self.vad_root_v2(vmi, registers, process, offsets),
+            None => panic!("OffsetsExt not set"),
+        }
+    }
+
+    fn vad_root_v1(
+        &self,
+        _vmi: &VmiCore<Driver>,
+        _registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        offsets: &v1::Offsets,
+    ) -> Result<Va, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let MM_AVL_TABLE = &offsets._MM_AVL_TABLE;
+
+        // The `_MM_AVL_TABLE::BalancedRoot` field is of `_MMADDRESS_NODE` type,
+        // which represents the root.
+        let vad_root = process.0 + EPROCESS.VadRoot.offset + MM_AVL_TABLE.BalancedRoot.offset;
+
+        Ok(vad_root)
+    }
+
+    fn vad_root_v2(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        offsets: &v2::Offsets,
+    ) -> Result<Va, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let RTL_AVL_TREE = &offsets._RTL_AVL_TREE;
+
+        // The `RTL_AVL_TREE::Root` field is of pointer type (`_RTL_BALANCED_NODE*`),
+        // thus we need to dereference it to get the actual node.
+        let vad_root = vmi.read_va(
+            registers
+                .address_context(process.0 + EPROCESS.VadRoot.offset + RTL_AVL_TREE.Root.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(vad_root)
+    }
+
+    /// Retrieves the `VadHint` for a given process. Returns the address of the
+    /// hint node in the VAD tree.
+    ///
+    /// The VAD hint is an optimization used by Windows to speed up VAD lookups.
+    /// This method returns the address of the hint node in the VAD tree.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// // For Windows 7:
+    /// return Process->VadRoot->NodeHint;
+    ///
+    /// // For Windows 8.1 and later:
+    /// return Process->VadRoot->Hint;
+    /// ```
+    pub fn vad_hint(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Va, VmiError> {
+        match &self.offsets.ext {
+            Some(OffsetsExt::V1(offsets)) => self.vad_hint_v1(vmi, registers, process, offsets),
+            Some(OffsetsExt::V2(offsets)) => self.vad_hint_v2(vmi, registers, process, offsets),
+            None => panic!("OffsetsExt not set"),
+        }
+    }
+
+    fn vad_hint_v1(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        offsets: &v1::Offsets,
+    ) -> Result<Va, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let MM_AVL_TABLE = &offsets._MM_AVL_TABLE;
+
+        let vad_hint = vmi.read_va(
+            registers.address_context(
+                process.0 + EPROCESS.VadRoot.offset + MM_AVL_TABLE.NodeHint.offset,
+            ),
+            registers.address_width(),
+        )?;
+
+        Ok(vad_hint)
+    }
+
+    fn vad_hint_v2(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        _offsets: &v2::Offsets,
+    ) -> Result<Va, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+
+        let VadHint = EPROCESS
+            .VadHint
+            .expect("VadHint is not present in common offsets");
+
+        let vad_hint = vmi.read_va(
+            registers.address_context(process.0 + VadHint.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(vad_hint)
+    }
+
+    /// Converts a VAD (Virtual Address Descriptor) to an [`OsRegion`] structure.
+    ///
+    /// This method extracts information from a VAD and creates an `OsRegion`
+    /// object, which includes details about the memory region's address range,
+    /// protection, and mapping type.
+    pub fn vad_to_region(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        vad: Va,
+    ) -> Result<OsRegion, VmiError> {
+        let MMVAD = &self.offsets.common._MMVAD;
+        let SUBSECTION = &self.offsets.common._SUBSECTION;
+
+        let mmvad = self.vad(vmi, registers, vad)?;
+        let start = Va(mmvad.starting_vpn << 12);
+        let end = Va((mmvad.ending_vpn + 1) << 12);
+
+        const MM_ZERO_ACCESS: u8 = 0; // this value is not used.
+        const MM_READONLY: u8 = 1;
+        const MM_EXECUTE: u8 = 2;
+        const MM_EXECUTE_READ: u8 = 3;
+        const MM_READWRITE: u8 = 4; // bit 2 is set if this is writable.
+        const MM_WRITECOPY: u8 = 5;
+        const MM_EXECUTE_READWRITE: u8 = 6;
+        const MM_EXECUTE_WRITECOPY: u8 = 7;
+
+        let protection = match mmvad.protection {
+            MM_ZERO_ACCESS => MemoryAccess::default(),
+            MM_READONLY => MemoryAccess::R,
+            MM_EXECUTE => MemoryAccess::X,
+            MM_EXECUTE_READ => MemoryAccess::RX,
+            MM_READWRITE => MemoryAccess::RW,
+            MM_WRITECOPY => MemoryAccess::RW, // REVIEW: is this correct?
+            MM_EXECUTE_READWRITE => MemoryAccess::RWX,
+            MM_EXECUTE_WRITECOPY => MemoryAccess::RWX, // REVIEW: is this correct?
+            _ => MemoryAccess::default(),
+        };
+
+        const VadImageMap: u8 = 2;
+
+        if mmvad.vad_type != VadImageMap {
+            return Ok(OsRegion {
+                start,
+                end,
+                protection,
+                kind: OsRegionKind::Private,
+            });
+        }
+
+        let subsection = vmi.read_va(
+            registers.address_context(vad + MMVAD.Subsection.offset),
+            registers.address_width(),
+        )?;
+
+        let control_area = vmi.read_va(
+            registers.address_context(subsection + SUBSECTION.ControlArea.offset),
+            registers.address_width(),
+        )?;
+
+        // Note that filename is allocated from paged pool,
+        // so this read might fail.
+        let path = self.control_area_to_filename(vmi, registers, control_area);
+
+        Ok(OsRegion {
+            start,
+            end,
+            protection,
+            kind: OsRegionKind::Mapped(OsMapped {
+                path: path.map(Some),
+            }),
+        })
+    }
+
+    /// Retrieves all memory regions associated with a process's VAD tree.
+    ///
+    /// This method traverses the entire VAD tree of a process and converts
+    /// each VAD into an [`OsRegion`], providing a comprehensive view of the
+    /// process's virtual address space.
+    pub fn vad_root_to_regions(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        vad_root: Va,
+    ) -> Result<Vec<OsRegion>, VmiError> {
+        let mut result = Vec::new();
+
+        self.enumerate_tree(vmi, registers, vad_root, |vad| {
+            match self.vad_to_region(vmi, registers, vad) {
+                Ok(region) => result.push(region),
+                Err(err) => tracing::warn!(?err, ?vad, "Failed to convert VAD to region"),
+            }
+
+            true
+        })?;
+
+        Ok(result)
+    }
+
+    /// Locates the VAD that encompasses a specific virtual address in a process.
+    ///
+    /// This method efficiently searches the VAD tree to find the VAD node that
+    /// corresponds to the given virtual address within the process's address
+    /// space. Its functionality is similar to the Windows kernel's internal
+    /// `MiLocateAddress()` function.
+    ///
+    /// Returns virtual address of the matching VAD if found, or `None` if the
+    /// address is not within any VAD.
+    ///
+    pub fn find_process_vad(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        address: Va,
+    ) -> Result<Option<Va>, VmiError> {
+        let mut vad_va = self.vad_hint(vmi, registers, process)?;
+
+        if vad_va.is_null() {
+            return Ok(None);
+        }
+
+        let vpn = address.0 >> 12;
+
+        let vad = self.vad(vmi, registers, vad_va)?;
+        if vpn >= vad.starting_vpn && vpn <= vad.ending_vpn {
+            return Ok(Some(vad_va));
+        }
+
+        vad_va = self.vad_root(vmi, registers, process)?;
+
+        while !vad_va.is_null() {
+            let vad = self.vad(vmi, registers, vad_va)?;
+
+            if vpn < vad.starting_vpn {
+                vad_va = vad.left_child;
+            }
+            else if vpn > vad.ending_vpn {
+                vad_va = vad.right_child;
+            }
+            else {
+                return Ok(Some(vad_va));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Finds a free range of user-mode virtual addresses in a process.
+    ///
+    /// Returns the lowest page-aligned address at which `size` bytes
+    /// (rounded up to whole pages) can be placed without overlapping any
+    /// VAD of the process, or `None` if the address space has no gap large
+    /// enough.
+    ///
+    /// # Implementation Details
+    ///
+    /// The VAD tree is walked to collect the ranges of all VADs, and the
+    /// gaps between them are searched in ascending order, bounded by the
+    /// lowest and highest user-mode addresses. Candidate addresses are
+    /// aligned to the 64KB allocation granularity, as `NtAllocateVirtualMemory`
+    /// would do.
+    ///
+    /// Returns an error if any of the VADs cannot be read, because the
+    /// range it covers cannot be proven to be free.
+    pub fn process_free_region(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        size: u64,
+    ) -> Result<Option<Va>, VmiError> {
+        const PAGE_SHIFT: u64 = 12;
+        const MM_ALLOCATION_GRANULARITY: u64 = 0x10000;
+
+        if size == 0 {
+            return Ok(None);
+        }
+
+        let page_count = size.div_ceil(1 << PAGE_SHIFT);
+
+        let mut vads = Vec::new();
+        let vad_root = self.vad_root(vmi, registers, process)?;
+        self.enumerate_tree(vmi, registers, vad_root, |vad_va| {
+            vads.push(vad_va);
+            true
+        })?;
+
+        let mut ranges = vads
+            .into_iter()
+            .map(|vad_va| {
+                let vad = self.vad(vmi, registers, vad_va)?;
+                Ok((vad.starting_vpn, vad.ending_vpn))
+            })
+            .collect::<Result<Vec<_>, VmiError>>()?;
+        ranges.sort_unstable();
+
+        let lowest_vpn = self.lowest_user_address(vmi, registers)?.0 >> PAGE_SHIFT;
+        let highest_vpn = self.highest_user_address(vmi, registers)?.0 >> PAGE_SHIFT;
+        let granularity = MM_ALLOCATION_GRANULARITY >> PAGE_SHIFT;
+
+        let mut candidate = lowest_vpn.next_multiple_of(granularity);
+        for (starting_vpn, ending_vpn) in ranges {
+            if candidate + page_count <= starting_vpn {
+                break;
+            }
+
+            candidate = candidate.max((ending_vpn + 1).next_multiple_of(granularity));
+        }
+
+        if candidate + page_count - 1 > highest_vpn {
+            return Ok(None);
+        }
+
+        Ok(Some(Va(candidate << PAGE_SHIFT)))
+    }
+
+    /// Scans the private executable memory of a process for PE images.
+    ///
+    /// Returns the images found in committed private memory that is
+    /// executable, i.e., images that are not backed by a file. Each image
+    /// is compared against the loader module list of the process, see
+    /// [`WindowsPrivateImage::linked`].
+    ///
+    /// # Implementation Details
+    ///
+    /// A VAD is scanned if its protection is executable, or if any of its
+    /// present pages is executable according to the page tables (the NX
+    /// bit is clear). The latter covers loaders that allocate memory as
+    /// read-write and change the protection to executable afterwards,
+    /// which doesn't update the initial protection stored in the VAD.
+    ///
+    /// The start of every page of the scanned VADs is checked for the
+    /// `MZ` signature, followed by a valid `PE` header. Pages that are not
+    /// present are skipped. Images whose headers were erased after loading
+    /// are not found.
+    ///
+    /// The page tables of each VAD are walked top-down, so the ranges
+    /// covered by non-present paging structures (e.g., of large
+    /// reservations) are skipped without examining their pages.
+    ///
+    /// # Notes
+    ///
+    /// If all executable pages of a read-write VAD are paged out, the VAD
+    /// is not scanned.
+    pub fn process_scan_pe_images(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<WindowsPrivateImage>, VmiError> {
+        const MM_EXECUTE: u8 = 2;
+        const MM_EXECUTE_READ: u8 = 3;
+        const MM_EXECUTE_READWRITE: u8 = 6;
+        const MM_EXECUTE_WRITECOPY: u8 = 7;
+
+        let root = self.process_translation_root(vmi, registers, process)?;
+
+        let mut regions = Vec::new();
+        let vad_root = self.vad_root(vmi, registers, process)?;
+        self.enumerate_tree(vmi, registers, vad_root, |vad_va| {
+            match self.vad(vmi, registers, vad_va) {
+                Ok(vad) => {
+                    if vad.private_memory && vad.mem_commit {
+                        let executable = matches!(
+                            vad.protection,
+                            MM_EXECUTE
+                                | MM_EXECUTE_READ
+                                | MM_EXECUTE_READWRITE
+                                | MM_EXECUTE_WRITECOPY
+                        );
+
+                        regions.push((vad.starting_vpn, vad.ending_vpn, executable));
+                    }
+                }
+                Err(err) => tracing::warn!(?err, ?vad_va, "Failed to read VAD"),
+            }
+
+            true
+        })?;
+        regions.sort_unstable();
+
+        let mut result = Vec::new();
+        let mut data = [0u8; Amd64::PAGE_SIZE as usize];
+
+        for (starting_vpn, ending_vpn, executable) in regions {
+            let region_start = Va(starting_vpn << Amd64::PAGE_SHIFT);
+            let region_end = Va((ending_vpn + 1) << Amd64::PAGE_SHIFT);
+
+            let pages =
+                Driver::Architecture::present_pages(self, vmi, root, region_start, region_end);
+
+            //
+            // The protection might have been changed to executable after
+            // the VAD was created.
+            //
+
+            if !executable && !pages.iter().any(|&(_, executable)| executable) {
+                continue;
+            }
+
+            for (address, _) in pages {
+                let mut signature = [0u8; 2];
+                if vmi.read((address, root), &mut signature).is_err() || signature != *b"MZ" {
+                    continue;
+                }
+
+                if vmi.read((address, root), &mut data).is_err() {
+                    continue;
+                }
+
+                let (architecture, size) = match optional_header_magic(data.as_ref()) {
+                    Ok(IMAGE_NT_OPTIONAL_HDR32_MAGIC) => match PeLite32::parse(&data) {
+                        Ok(pe) => (
+                            OsArchitecture::X86,
+                            pe.nt_headers.optional_header().size_of_image(),
+                        ),
+                        Err(_) => continue,
+                    },
+                    Ok(IMAGE_NT_OPTIONAL_HDR64_MAGIC) => match PeLite64::parse(&data) {
+                        Ok(pe) => (
+                            OsArchitecture::Amd64,
+                            pe.nt_headers.optional_header().size_of_image(),
+                        ),
+                        Err(_) => continue,
+                    },
+                    _ => continue,
+                };
+
+                result.push(WindowsPrivateImage {
+                    base_address: address,
+                    size: size as u64,
+                    architecture,
+                    region_start,
+                    region_end,
+                    linked: false,
+                });
+            }
+        }
+
+        if result.is_empty() {
+            return Ok(result);
+        }
+
+        //
+        // The module list might not be available (e.g., for minimal
+        // processes, or if the PEB is paged out). The images are then
+        // reported as unlinked.
+        //
+
+        match self.__process_user_modules(vmi, registers, process, root) {
+            Ok(modules) => {
+                for image in &mut result {
+                    image.linked = modules
+                        .iter()
+                        .any(|module| module.base_address == image.base_address);
+                }
+            }
+            Err(err) => tracing::warn!(?err, ?process, "Failed to read user modules"),
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the virtual address of the Page Frame Number (PFN) database.
+    ///
+    /// The PFN database is a critical data structure in Windows memory management,
+    /// containing information about each physical page in the system.
+    ///
+    /// # Implementation Details
+    ///
+    /// The PFN database is located by reading the `MmPfnDatabase` symbol from
+    /// the kernel image.
+    fn pfn_database(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Va, VmiError> {
+        let MmPfnDatabase = self.symbols.MmPfnDatabase;
+
+        if let Some(mm_pfn_database) = self.mm_pfn_database.borrow().as_ref() {
+            return Ok(*mm_pfn_database);
+        }
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let mm_pfn_database = vmi.read_va(
+            registers.address_context(kernel_image_base + MmPfnDatabase),
+            registers.address_width(),
+        )?;
+        *self.mm_pfn_database.borrow_mut() = Some(mm_pfn_database);
+        Ok(mm_pfn_database)
+    }
+
+    /// Retrieves information about a physical page from the PFN database.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `_MMPFN` structure for the given PFN is located at
+    /// `MmPfnDatabase + pfn * sizeof(_MMPFN)`. The `ShareCount` occupies the
+    /// low 54 bits of the `u2` union on Windows 10+ and the whole `u2` union
+    /// on Windows 7, so the value is masked to 54 bits.
+    pub fn pfn_info(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        pfn: Gfn,
+    ) -> Result<WindowsPfn, VmiError> {
+        const SHARE_COUNT_MASK: u64 = (1 << 54) - 1;
+
+        let MMPFN = &self.offsets.common._MMPFN;
+
+        let address = self.pfn_database(vmi, registers)? + u64::from(pfn) * MMPFN.len() as u64;
+
+        let mmpfn = StructReader::new(
+            vmi,
+            registers.address_context(address),
+            MMPFN.effective_len(),
+        )?;
+        let reference_count = mmpfn.read(MMPFN.ReferenceCount)? as u16;
+        let pte_address = mmpfn.read(MMPFN.PteAddress)?;
+        let share_count = mmpfn.read(MMPFN.u2)? & SHARE_COUNT_MASK;
+
+        let e1 = mmpfn.read(MMPFN.e1)?;
+        let e1_shift = (MMPFN.PageLocation.offset - MMPFN.e1.offset) * 8;
+        let page_location = MMPFN.PageLocation.value_from(e1 >> e1_shift) as u8;
+
+        let u4 = mmpfn.read(MMPFN.u4)?;
+        let u4_shift = (MMPFN.PteFrame.offset - MMPFN.u4.offset) * 8;
+        let pte_frame = MMPFN.PteFrame.value_from(u4 >> u4_shift);
+
+        Ok(WindowsPfn {
+            pfn,
+            address,
+            location: WindowsPageLocation::from(page_location),
+            reference_count,
+            share_count,
+            // The lowest bit might be used as a lock bit.
+            pte_address: Va(pte_address & !7),
+            pte_frame: Gfn(pte_frame),
+        })
+    }
+
+    /// Retrieves a snapshot of the system memory counters.
+    ///
+    /// Returns [`VmiError::NotSupported`] if none of the counters is
+    /// available.
+    ///
+    /// # Implementation Details
+    ///
+    /// On Windows 7, the counters are read from the `MmAvailablePages` and
+    /// `MmResidentAvailablePages` variables, and from the `Total` field of
+    /// the page list heads (`_MMPFNLIST`).
+    ///
+    /// On Windows 10+, the counters are read from the system memory
+    /// partition (`MiSystemPartition`): the available pages from its
+    /// `_MI_VISIBLE_PARTITION` (`Vp`), and the page lists from its
+    /// `_MI_PARTITION_PAGE_LISTS` (`PageLists`). If the profile doesn't
+    /// describe these structures, [`VmiError::NotSupported`] is returned.
+    ///
+    /// Lists that are split (by priority, by paging file, ...) are summed.
+    pub fn memory_statistics(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<WindowsMemoryStatistics, VmiError> {
+        // Same as `MI_PFN_PRIORITIES`.
+        const STANDBY_PRIORITIES: u64 = 8;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        let read_counter = |address: Va| -> Result<u64, VmiError> {
+            vmi.read_address(
+                registers.address_context(address),
+                registers.address_width(),
+            )
+        };
+
+        // Sums the `Total` fields of `count` consecutive `_MMPFNLIST`s.
+        let read_lists = |address: Va, count: u64, total: Field, len: usize| {
+            (0..count).try_fold(0, |sum, index| {
+                Ok::<_, VmiError>(sum + read_counter(address + index * len as u64 + total.offset)?)
+            })
+        };
+
+        let result = match &self.offsets.ext {
+            Some(OffsetsExt::V1(offsets)) => {
+                let MMPFNLIST = &offsets._MMPFNLIST;
+
+                let read_global = |symbol: Option<u64>, offset: u64| match symbol {
+                    Some(symbol) => read_counter(kernel_image_base + symbol + offset).map(Some),
+                    None => Ok(None),
+                };
+
+                WindowsMemoryStatistics {
+                    available: read_global(self.symbols.MmAvailablePages, 0)?,
+                    resident_available: read_global(self.symbols.MmResidentAvailablePages, 0)?,
+                    zeroed: read_global(self.symbols.MmZeroedPageListHead, MMPFNLIST.Total.offset)?,
+                    free: read_global(self.symbols.MmFreePageListHead, MMPFNLIST.Total.offset)?,
+                    standby: match self.symbols.MmStandbyPageListByPriority {
+                        Some(symbol) => Some(read_lists(
+                            kernel_image_base + symbol,
+                            STANDBY_PRIORITIES,
+                            MMPFNLIST.Total,
+                            MMPFNLIST.len(),
+                        )?),
+                        None => None,
+                    },
+                    modified: read_global(
+                        self.symbols.MmModifiedPageListHead,
+                        MMPFNLIST.Total.offset,
+                    )?,
+                    modified_no_write: read_global(
+                        self.symbols.MmModifiedNoWritePageListHead,
+                        MMPFNLIST.Total.offset,
+                    )?,
+                }
+            }
+            Some(OffsetsExt::V2(offsets)) => {
+                let MiSystemPartition = match self.symbols.MiSystemPartition {
+                    Some(MiSystemPartition) => kernel_image_base + MiSystemPartition,
+                    None => return Err(VmiError::NotSupported),
+                };
+
+                let partition = match &self.offsets.partition {
+                    Some(partition) => partition,
+                    None => return Err(VmiError::NotSupported),
+                };
+
+                let MMPFNLIST = &offsets._MMPFNLIST;
+                let MI_PARTITION = &partition._MI_PARTITION;
+                let MI_PARTITION_PAGE_LISTS = &partition._MI_PARTITION_PAGE_LISTS;
+                let MI_VISIBLE_PARTITION = &partition._MI_VISIBLE_PARTITION;
+
+                let vp = MiSystemPartition + MI_PARTITION.Vp.offset;
+                let page_lists = MiSystemPartition + MI_PARTITION.PageLists.offset;
+
+                let read_vp = |field: Option<Field>| match field {
+                    Some(field) => read_counter(vp + field.offset).map(Some),
+                    None => Ok(None),
+                };
+
+                // The field is either a single list or an array of lists.
+                let read_page_lists = |field: Option<Field>| match field {
+                    Some(field) => read_lists(
+                        page_lists + field.offset,
+                        field.size / MMPFNLIST.len() as u64,
+                        MMPFNLIST.Total,
+                        MMPFNLIST.len(),
+                    )
+                    .map(Some),
+                    None => Ok(None),
+                };
+
+                let modified = match (
+                    read_page_lists(MI_PARTITION_PAGE_LISTS.ModifiedPageListNoReservation)?,
+                    read_page_lists(MI_PARTITION_PAGE_LISTS.ModifiedPageListByReservation)?,
+                ) {
+                    (None, None) => None,
+                    (no_reservation, by_reservation) => {
+                        Some(no_reservation.unwrap_or(0) + by_reservation.unwrap_or(0))
+                    }
+                };
+
+                WindowsMemoryStatistics {
+                    available: read_vp(MI_VISIBLE_PARTITION.AvailablePages)?,
+                    resident_available: read_vp(MI_VISIBLE_PARTITION.ResidentAvailablePages)?,
+                    zeroed: read_page_lists(MI_PARTITION_PAGE_LISTS.ZeroedPageListHead)?,
+                    free: read_page_lists(MI_PARTITION_PAGE_LISTS.FreePageListHead)?,
+                    standby: read_page_lists(MI_PARTITION_PAGE_LISTS.StandbyPageListByPriority)?,
+                    modified,
+                    modified_no_write: read_page_lists(
+                        MI_PARTITION_PAGE_LISTS.ModifiedNoWritePageListHead,
+                    )?,
+                }
+            }
+            None => return Err(VmiError::NotSupported),
+        };
+
+        if result == WindowsMemoryStatistics::default() {
+            return Err(VmiError::NotSupported);
+        }
+
+        Ok(result)
+    }
+
+    fn modify_pfn_reference_count(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        pfn: Gfn,
+        increment: i16,
+    ) -> Result<Option<u16>, VmiError> {
+        let MMPFN = &self.offsets.common._MMPFN;
+
+        // const ZeroedPageList: u16 = 0;
+        // const FreePageList: u16 = 1;
+        const StandbyPageList: u16 = 2; //this list and before make up available pages.
+        const ModifiedPageList: u16 = 3;
+        const ModifiedNoWritePageList: u16 = 4;
+        // const BadPageList: u16 = 5;
+        const ActiveAndValid: u16 = 6;
+        // const TransitionPage: u16 = 7;
+
+        let pfn = self.pfn_database(vmi, registers)? + u64::from(pfn) * MMPFN.len() as u64;
+
+        //
+        // In the _MMPFN structure, the fields are like this:
+        //
+        // ```c
+        // struct _MMPFN {
+        //     ...
+        //     union {
+        //         USHORT ReferenceCount;
+        //         struct {
+        //             UCHAR PageLocation : 3;
+        //             ...
+        //         } e1;
+        //         ...
+        //     } u3;
+        // };
+        // ```
+        //
+        // On the systems tested (Win7 - Win11), the `PageLocation` is right
+        // after `ReferenceCount`. We can read the value of both fields at once.
+        //
+
+        debug_assert_eq!(MMPFN.ReferenceCount.size, 2);
+        debug_assert_eq!(
+            MMPFN.ReferenceCount.offset + MMPFN.ReferenceCount.size,
+            MMPFN.PageLocation.offset
+        );
+        debug_assert_eq!(MMPFN.PageLocation.bit_position, 0);
+        debug_assert_eq!(MMPFN.PageLocation.bit_length, 3);
+
+        let pfn_value =
+            vmi.read_u32(registers.address_context(pfn + MMPFN.ReferenceCount.offset))?;
+        let flags = (pfn_value >> 16) as u16;
+        let ref_count = (pfn_value & 0xFFFF) as u16;
+
+        let page_location = flags & 7;
+
+        tracing::debug!(
+            %pfn,
+            ref_count,
+            flags = %Hex(flags),
+            page_location,
+            increment,
+            "Modifying PFN reference count"
+        );
+
+        //
+        // Make sure the page is good (when coming from hibernate/standby pages
+        // can be in modified state).
+        //
+
+        if !matches!(
+            page_location,
+            StandbyPageList | ModifiedPageList | ModifiedNoWritePageList | ActiveAndValid
+        ) {
+            tracing::warn!(
+                %pfn,
+                ref_count,
+                flags = %Hex(flags),
+                page_location,
+                increment,
+                "Page is not active and valid"
+            );
+            return Ok(None);
+        }
+
+        if ref_count == 0 {
+            tracing::warn!(
+                %pfn,
+                ref_count,
+                flags = %Hex(flags),
+                page_location,
+                increment,
+                "Page is not initialized"
+            );
+            return Ok(None);
+        }
+
+        let new_ref_count = match ref_count.checked_add_signed(increment) {
+            Some(new_ref_count) => new_ref_count,
+            None => {
+                tracing::warn!(
+                    %pfn,
+                    ref_count,
+                    flags = %Hex(flags),
+                    page_location,
+                    increment,
+                    "Page is at maximum reference count"
+                );
+                return Ok(None);
+            }
+        };
+
+        vmi.write_u16(
+            registers.address_context(pfn + MMPFN.ReferenceCount.offset),
+            new_ref_count,
+        )?;
+
+        Ok(Some(new_ref_count))
+    }
+
+    /// Increments the reference count of a Page Frame Number (PFN).
+    ///
+    /// This method is used to "lock" a physical page by increasing its
+    /// reference count, preventing it from being paged out or reallocated.
+    ///
+    /// Returns the new reference count if successful, or `None` if the
+    /// operation failed (e.g., if the page is not in a valid state).
+    ///
+    /// # Implementation Details
+    ///
+    /// The method works by:
+    /// 1. Locating the `_MMPFN` structure for the given PFN within the `MmPfnDatabase`.
+    /// 2. Incrementing the `ReferenceCount` member of the `_MMPFN` structure.
+    ///
+    /// # Warning
+    ///
+    /// This function can potentially cause race conditions if the virtual machine
+    /// is not paused during its execution. It is strongly recommended to pause
+    /// the virtual machine before calling this function and resume it afterwards.
+    ///
+    /// Failure to pause the VM may result in inconsistent state or potential
+    /// crashes if the page is concurrently modified by the guest OS.
+    ///
+    /// # Examples:
+    ///
+    /// ```no_run
+    /// # use vmi::{
+    /// #     arch::amd64::{Amd64, Registers},
+    /// #     os::windows::WindowsOs,
+    /// #     Architecture, Gfn, VmiCore, VmiDriver, VmiError,
+    /// # };
+    /// #
+    /// # fn example<Driver>(
+    /// #     vmi: &VmiCore<Driver>,
+    /// #     os: &WindowsOs<Driver>,
+    /// #     registers: &Registers,
+    /// #     pfn: Gfn,
+    /// # ) -> Result<(), VmiError>
+    /// # where
+    /// #   Driver: VmiDriver<Architecture = Amd64>,
+    /// # {
+    /// let _pause_guard = vmi.pause_guard()?;
+    /// os.lock_pfn(vmi, registers, pfn)?;
+    /// // The VM will automatically resume when `_guard` goes out of scope
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn lock_pfn(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        pfn: Gfn,
+    ) -> Result<Option<u16>, VmiError> {
+        self.modify_pfn_reference_count(vmi, registers, pfn, 1)
+    }
+
+    /// Decrements the reference count of a Page Frame Number (PFN).
+    ///
+    /// This method is used to "unlock" a physical page by decreasing its
+    /// reference count, potentially allowing it to be paged out or reallocated
+    /// if the count reaches zero.
+    ///
+    /// Returns the new reference count if successful, or `None` if the
+    /// operation failed (e.g., if the page is not in a valid state).
+    ///
+    /// # Implementation Details
+    ///
+    /// The method works by:
+    /// 1. Locating the `_MMPFN` structure for the given PFN within the `MmPfnDatabase`.
+    /// 2. Decrementing the `ReferenceCount` member of the `_MMPFN` structure.
+    ///
+    /// # Warning
+    ///
+    /// This function can potentially cause race conditions if the virtual machine
+    /// is not paused during its execution. It is strongly recommended to pause
+    /// the virtual machine before calling this function and resume it afterwards.
+    ///
+    /// Failure to pause the VM may result in inconsistent state or potential
+    /// crashes if the page is concurrently modified by the guest OS.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use vmi_arch_amd64::{Amd64, Registers};
+    /// # use vmi_core::{Architecture, Gfn, VmiCore, VmiDriver, VmiError, VmiOs};
+    /// # use vmi_os_windows::WindowsOs;
+    /// #
+    /// # fn example<Driver>(
+    /// #     vmi: &VmiCore<Driver>,
+    /// #     os: &WindowsOs<Driver>,
+    /// #     registers: &Registers,
+    /// #     pfn: Gfn,
+    /// # ) -> Result<(), VmiError>
+    /// # where
+    /// #     Driver: VmiDriver<Architecture = Amd64>,
+    /// # {
+    /// let _pause_guard = vmi.pause_guard()?;
+    /// os.unlock_pfn(vmi, registers, pfn)?;
+    /// // The VM will automatically resume when `_guard` goes out of scope
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn unlock_pfn(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        pfn: Gfn,
+    ) -> Result<Option<u16>, VmiError> {
+        self.modify_pfn_reference_count(vmi, registers, pfn, -1)
+    }
+
+    /// Locks a physical page for the lifetime of the returned guard.
+    ///
+    /// The reference count of the page is incremented by [`lock_pfn`] and
+    /// decremented by [`unlock_pfn`] when the guard is dropped. If the page
+    /// is not in a valid state, the guard is returned without locking the
+    /// page, which can be checked with [`WindowsPfnGuard::is_locked`].
+    ///
+    /// # Warning
+    ///
+    /// The same caveats as for [`lock_pfn`] apply: the virtual machine
+    /// should be paused while the guard is created and dropped.
+    ///
+    /// [`lock_pfn`]: Self::lock_pfn
+    /// [`unlock_pfn`]: Self::unlock_pfn
+    pub fn pfn_guard<'a>(
+        &'a self,
+        vmi: &'a VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        pfn: Gfn,
+    ) -> Result<WindowsPfnGuard<'a, Driver>, VmiError> {
+        let reference_count = self.lock_pfn(vmi, registers, pfn)?;
+
+        Ok(WindowsPfnGuard {
+            os: self,
+            vmi,
+            registers: *registers,
+            pfn,
+            reference_count,
+        })
+    }
+
+    // endregion: Memory
+
+    // region: Misc
+
+    /// Retrieves the virtual address of the current Kernel Processor Control
+    /// Region (KPCR).
+    ///
+    /// The KPCR is a per-processor data structure in Windows that contains
+    /// critical information about the current processor state. This method
+    /// returns the virtual address of the KPCR for the current processor.
+    pub fn current_kpcr(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Va {
+        Driver::Architecture::current_kpcr(self, vmi, registers)
+    }
+
+    /// Retrieves the Interrupt Request Level (IRQL) of a virtual CPU.
+    ///
+    /// Code running at `DISPATCH_LEVEL` (2) or above must not touch pageable
+    /// memory. Handlers can use this value to decide whether it is safe to
+    /// walk structures that might be paged out.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the driver can't query the
+    /// task priority of the virtual CPU.
+    ///
+    /// # Architecture-specific
+    ///
+    /// - **AMD64**: The IRQL is the value of the `CR8` register, see
+    ///   [`VmiCore::task_priority`]. The `Irql` field of the KPCR is not
+    ///   used, because it is stale whenever `CR8` is written directly.
+    pub fn current_irql(&self, vmi: &VmiCore<Driver>, vcpu: VcpuId) -> Result<u8, VmiError> {
+        Driver::Architecture::current_irql(self, vmi, vcpu)
+    }
+
+    /// Checks whether maskable interrupts are enabled on the current
+    /// processor.
+    ///
+    /// # Implementation Details
+    ///
+    /// On AMD64, this is the state of the interrupt flag (`IF`) in the
+    /// `RFLAGS` register.
+    pub fn interrupts_enabled(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> bool {
+        Driver::Architecture::interrupts_enabled(self, vmi, registers)
+    }
+
+    /// Checks whether the guest believes it runs under a hypervisor.
+    ///
+    /// # Implementation Details
+    ///
+    /// The hypervisor-present bit of the guest CPUID policy is used if the
+    /// driver can query it. Otherwise, the guest is considered to run under
+    /// a hypervisor if the kernel uses the hypervisor enlightenments.
+    ///
+    /// See [`hypervisor_information`] for details.
+    ///
+    /// [`hypervisor_information`]: Self::hypervisor_information
+    pub fn hypervisor_present(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<bool, VmiError> {
+        let information = self.hypervisor_information(vmi, registers)?;
+
+        match information.cpuid_hypervisor_present {
+            Some(present) => Ok(present),
+            None => Ok(information.is_enlightened()),
+        }
+    }
+
+    /// Retrieves the guest's view of the hypervisor it runs under.
+    ///
+    /// This tells whether the Hyper-V enlightenment structures (e.g., the
+    /// hypercall page) are present and can be introspected.
+    ///
+    /// # Implementation Details
+    ///
+    /// The CPUID leaves `1` and `40000000h` are queried from the guest CPUID
+    /// policy of the first virtual CPU. The kernel state is read from the
+    /// `HvlHypervisorConnected`, `HvlEnlightenments`, `HvlpFlags` and
+    /// `HvlpHypercallCodeVa` variables. Each part that isn't available
+    /// (unsupported by the driver or missing symbol) is `None`.
+    pub fn hypervisor_information(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<WindowsHypervisorInformation, VmiError> {
+        const CPUID_HYPERVISOR_PRESENT: u32 = 1 << 31;
+        const CPUID_HYPERVISOR_VENDOR_LEAF: u32 = 0x4000_0000;
+
+        let mut result = WindowsHypervisorInformation::default();
+
+        match vmi.guest_cpuid(VcpuId(0), 1, 0) {
+            Ok(leaf) => {
+                let present = leaf.ecx & CPUID_HYPERVISOR_PRESENT != 0;
+                result.cpuid_hypervisor_present = Some(present);
+
+                // The hypervisor leaves might not be available even if the
+                // basic leaves are (e.g., Xen generates them on the fly).
+                if present {
+                    match vmi.guest_cpuid(VcpuId(0), CPUID_HYPERVISOR_VENDOR_LEAF, 0) {
+                        Ok(leaf) => {
+                            let vendor = [leaf.ebx, leaf.ecx, leaf.edx]
+                                .iter()
+                                .flat_map(|register| register.to_le_bytes())
+                                .collect::<Vec<_>>();
+
+                            result.vendor = Some(
+                                String::from_utf8_lossy(&vendor)
+                                    .trim_end_matches('\0')
+                                    .to_string(),
+                            );
+                        }
+                        Err(VmiError::NotSupported) => {}
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+            Err(VmiError::NotSupported) => {}
+            Err(err) => return Err(err),
+        }
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        if let Some(HvlHypervisorConnected) = self.symbols.HvlHypervisorConnected {
+            result.connected = Some(
+                vmi.read_u8(registers.address_context(kernel_image_base + HvlHypervisorConnected))?
+                    != 0,
+            );
+        }
+
+        if let Some(HvlEnlightenments) = self.symbols.HvlEnlightenments {
+            result.enlightenments = Some(
+                vmi.read_u32(registers.address_context(kernel_image_base + HvlEnlightenments))?,
+            );
+        }
+
+        if let Some(HvlpFlags) = self.symbols.HvlpFlags {
+            result.flags =
+                Some(vmi.read_u32(registers.address_context(kernel_image_base + HvlpFlags))?);
+        }
+
+        if let Some(HvlpHypercallCodeVa) = self.symbols.HvlpHypercallCodeVa {
+            let hypercall_page = vmi.read_va(
+                registers.address_context(kernel_image_base + HvlpHypercallCodeVa),
+                registers.address_width(),
+            )?;
+
+            if !hypercall_page.is_null() {
+                result.hypercall_page = Some(hypercall_page);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the addresses of the Kernel Processor Control Blocks (KPRCB)
+    /// of all processors.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `KiProcessorBlock` symbol is an array of pointers to the `_KPRCB`
+    /// structures, indexed by the processor number. The array is read until
+    /// a NULL entry is encountered or the number of virtual CPUs of the
+    /// virtual machine is reached.
+    pub fn processor_control_blocks(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<Va>, VmiError> {
+        let KiProcessorBlock =
+            self.kernel_image_base(vmi, registers)? + self.symbols.KiProcessorBlock;
+        let address_width = registers.address_width();
+
+        let mut result = Vec::new();
+        for index in 0..vmi.info()?.vcpus as u64 {
+            let prcb = vmi.read_va(
+                registers.address_context(KiProcessorBlock + index * address_width as u64),
+                address_width,
+            )?;
+
+            if prcb.is_null() {
+                break;
+            }
+
+            result.push(prcb);
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the active kernel timers of all processors, together with
+    /// their DPCs.
+    ///
+    /// Timers whose DPC routine points outside of the loaded kernel modules
+    /// are flagged with [`WindowsTimer::outside_modules`], as periodic
+    /// timers with a DPC in unbacked memory are a common way for rootkits
+    /// to execute code without a thread.
+    ///
+    /// # Implementation Details
+    ///
+    /// Each `_KPRCB` holds a `_KTIMER_TABLE`, whose `TimerEntries` buckets
+    /// are list heads of `_KTIMER` structures linked through
+    /// `TimerListEntry`. Windows 10 and later keep two arrays of buckets,
+    /// both are walked.
+    ///
+    /// On 64-bit Windows 8 and later, the `Dpc` pointer of the timer is
+    /// encoded with the `KiWaitNever` and `KiWaitAlways` values; it is
+    /// decoded when these symbols are available.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// Dpc = Timer->Dpc ^ KiWaitNever;
+    /// Dpc = _rotl64(Dpc, (UCHAR)KiWaitNever);
+    /// Dpc = _byteswap_uint64(Dpc ^ (ULONG_PTR)Timer);
+    /// Dpc = Dpc ^ KiWaitAlways;
+    /// ```
+    ///
+    /// # Notes
+    ///
+    /// Windows Vista and older kept the timers in the global
+    /// `KiTimerTableListHead` table, which is not supported.
+    pub fn timers(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<WindowsTimer>, VmiError> {
+        let KPRCB = &self.offsets.common._KPRCB;
+        let KTIMER_TABLE = &self.offsets.common._KTIMER_TABLE;
+        let KTIMER_TABLE_ENTRY = &self.offsets.common._KTIMER_TABLE_ENTRY;
+        let KTIMER = &self.offsets.common._KTIMER;
+
+        let wait_keys = self.wait_keys(vmi, registers)?;
+
+        let modules = self.modules(vmi, registers)?;
+        let entry_count = KTIMER_TABLE.TimerEntries.size / KTIMER_TABLE_ENTRY.len() as u64;
+
+        let mut result = Vec::new();
+        for (processor, prcb) in self
+            .processor_control_blocks(vmi, registers)?
+            .into_iter()
+            .enumerate()
+        {
+            let timer_entries = prcb + KPRCB.TimerTable.offset + KTIMER_TABLE.TimerEntries.offset;
+
+            for index in 0..entry_count {
+                let list_head = timer_entries
+                    + index * KTIMER_TABLE_ENTRY.len() as u64
+                    + KTIMER_TABLE_ENTRY.Entry.offset;
+
+                self.enumerate_list(vmi, registers, list_head, |entry| {
+                    let timer = entry - KTIMER.TimerListEntry.offset;
+
+                    match self.timer(vmi, registers, timer, processor as u32, wait_keys, &modules) {
+                        Ok(timer) => result.push(timer),
+                        Err(err) => tracing::debug!(%timer, %err, "failed to read timer"),
+                    }
+
+                    true
+                })?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Reads a `_KTIMER` and its DPC.
+    fn timer(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        timer: Va,
+        processor: u32,
+        wait_keys: Option<WindowsWaitKeys>,
+        modules: &[OsModule],
+    ) -> Result<WindowsTimer, VmiError> {
+        let KTIMER = &self.offsets.common._KTIMER;
+        let KDPC = &self.offsets.common._KDPC;
+
+        let ktimer = StructReader::new(
+            vmi,
+            registers.address_context(timer),
+            KTIMER.effective_len(),
+        )?;
+
+        let due_time = ktimer.read(KTIMER.DueTime)?;
+        let period = ktimer.read(KTIMER.Period)? as u32;
+        let mut dpc = ktimer.read(KTIMER.Dpc)?;
+
+        if let Some(wait_keys) = wait_keys {
+            if dpc != 0 {
+                dpc = Self::decode_wait_pointer(dpc, timer, wait_keys);
+            }
+        }
+
+        let dpc = Va(dpc);
+        let (routine, context) = match dpc.is_null() {
+            true => (Va(0), Va(0)),
+            false => {
+                let kdpc =
+                    StructReader::new(vmi, registers.address_context(dpc), KDPC.effective_len())?;
+
+                (
+                    Va(kdpc.read(KDPC.DeferredRoutine)?),
+                    Va(kdpc.read(KDPC.DeferredContext)?),
+                )
+            }
+        };
+
+        let module = modules
+            .iter()
+            .find(|module| {
+                module.base_address <= routine && routine < module.base_address + module.size
+            })
+            .map(|module| module.name.clone());
+
+        Ok(WindowsTimer {
+            timer,
+            processor,
+            due_time,
+            period,
+            dpc,
+            routine,
+            context,
+            outside_modules: !dpc.is_null() && module.is_none(),
+            module,
+        })
+    }
+
+    /// Reads the `KiWaitNever` and `KiWaitAlways` values, used by the kernel
+    /// to encode pointers (e.g., `KTIMER.Dpc` or the `KdDebuggerDataBlock`).
+    ///
+    /// Returns `None` if the symbols are not available.
+    fn wait_keys(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<WindowsWaitKeys>, VmiError> {
+        let (KiWaitNever, KiWaitAlways) =
+            match (self.symbols.KiWaitNever, self.symbols.KiWaitAlways) {
+                (Some(KiWaitNever), Some(KiWaitAlways)) => (KiWaitNever, KiWaitAlways),
+                _ => return Ok(None),
+            };
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+
+        Ok(Some(WindowsWaitKeys {
+            wait_never: vmi.read_u64(registers.address_context(kernel_image_base + KiWaitNever))?,
+            wait_always: vmi
+                .read_u64(registers.address_context(kernel_image_base + KiWaitAlways))?,
+        }))
+    }
+
+    /// Decodes a value encoded with the `KiWaitNever` and `KiWaitAlways`
+    /// values and the address it is stored at (or belongs to).
+    fn decode_wait_pointer(value: u64, address: Va, wait_keys: WindowsWaitKeys) -> u64 {
+        let WindowsWaitKeys {
+            wait_never,
+            wait_always,
+        } = wait_keys;
+
+        let value = (value ^ wait_never).rotate_left((wait_never & 0xff) as u32);
+        (value ^ address.0).swap_bytes() ^ wait_always
+    }
+
+    /// Retrieves the bounds of the DPC stack of a processor.
+    ///
+    /// Returns `None` if the processor doesn't exist.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `DpcStack` field of the `_KPRCB` points to the top of the stack.
+    /// The stack has the size of a regular kernel stack, which is taken
+    /// from the idle thread of the processor (`StackBase - StackLimit`).
+    ///
+    /// # Notes
+    ///
+    /// The KPRCB array is indexed by the processor number, which usually,
+    /// but not necessarily, matches the virtual CPU ID.
+    pub fn dpc_stack(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        vcpu: VcpuId,
+    ) -> Result<Option<WindowsStackBounds>, VmiError> {
+        let KPRCB = &self.offsets.common._KPRCB;
+
+        let prcb = match self
+            .processor_control_blocks(vmi, registers)?
+            .get(usize::from(vcpu.0))
+        {
+            Some(prcb) => *prcb,
+            None => return Ok(None),
+        };
+
+        let base = vmi.read_va(
+            registers.address_context(prcb + KPRCB.DpcStack.offset),
+            registers.address_width(),
+        )?;
+
+        if base.is_null() {
+            return Ok(None);
+        }
+
+        let size = self.kernel_stack_size(vmi, registers, prcb)?;
+
+        Ok(Some(WindowsStackBounds {
+            base,
+            limit: base - size,
+        }))
+    }
+
+    /// Retrieves the bounds of the interrupt stacks of a processor.
+    ///
+    /// Returns an empty vector if the processor doesn't exist.
+    ///
+    /// # Implementation Details
+    ///
+    /// On x86, the `IsrStack` field of the `_KPRCB` points to the top of
+    /// the stack on which interrupts are handled.
+    ///
+    /// On AMD64, interrupts are handled on the stack of the interrupted
+    /// thread, except for the vectors with an Interrupt Stack Table entry
+    /// (e.g., NMI, double fault or machine check). The stacks are read from
+    /// the IST of the TSS of the processor (`_KPCR.TssBase`).
+    ///
+    /// The stacks have the size of a regular kernel stack, which is taken
+    /// from the idle thread of the processor (`StackBase - StackLimit`).
+    ///
+    /// # Notes
+    ///
+    /// The KPRCB array is indexed by the processor number, which usually,
+    /// but not necessarily, matches the virtual CPU ID.
+    pub fn isr_stacks(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        vcpu: VcpuId,
+    ) -> Result<Vec<WindowsStackBounds>, VmiError> {
+        let KPRCB = &self.offsets.common._KPRCB;
+
+        let prcb = match self
+            .processor_control_blocks(vmi, registers)?
+            .get(usize::from(vcpu.0))
+        {
+            Some(prcb) => *prcb,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut stacks = Vec::new();
+
+        if let Some(IsrStack) = KPRCB.IsrStack {
+            let base = vmi.read_va(
+                registers.address_context(prcb + IsrStack.offset),
+                registers.address_width(),
+            )?;
+
+            if !base.is_null() {
+                stacks.push(base);
+            }
+        }
+
+        stacks.extend(Driver::Architecture::interrupt_stacks(
+            self, vmi, registers, prcb,
+        )?);
+
+        if stacks.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let size = self.kernel_stack_size(vmi, registers, prcb)?;
+
+        Ok(stacks
+            .into_iter()
+            .map(|base| WindowsStackBounds {
+                base,
+                limit: base - size,
+            })
+            .collect())
+    }
+
+    /// Retrieves the size of a regular kernel stack from the idle thread
+    /// of a processor.
+    fn kernel_stack_size(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        prcb: Va,
+    ) -> Result<u64, VmiError> {
+        let KPRCB = &self.offsets.common._KPRCB;
+
+        let idle_thread = vmi.read_va(
+            registers.address_context(prcb + KPRCB.IdleThread.offset),
+            registers.address_width(),
+        )?;
+
+        let bounds = self.thread_kernel_stack_bounds(vmi, registers, ThreadObject(idle_thread))?;
+
+        if bounds.base <= bounds.limit {
+            return Err(VmiError::CorruptedStruct("KTHREAD.StackBase"));
+        }
+
+        Ok(bounds.base.0 - bounds.limit.0)
+    }
+
+    /// Extracts information from an exception record at the specified address.
+    ///
+    /// This method reads and parses an `EXCEPTION_RECORD` structure from
+    /// memory, providing detailed information about an exception that has
+    /// occurred in the system. The returned [`WindowsExceptionRecord`]
+    /// contains data such as the exception code, flags, and related memory
+    /// addresses.
+    pub fn exception_record(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        address: Va,
+    ) -> Result<WindowsExceptionRecord, VmiError> {
+        #[repr(C)]
+        #[derive(Debug, Copy, Clone, FromBytes, IntoBytes)]
+        #[allow(non_camel_case_types, non_snake_case)]
+        struct _EXCEPTION_RECORD {
+            ExceptionCode: u32,
+            ExceptionFlags: u32,
+            ExceptionRecord: u64,
+            ExceptionAddress: u64,
+            NumberParameters: u64,
+            ExceptionInformation: [u64; 15],
+        }
+
+        let record = vmi.read_struct::<_EXCEPTION_RECORD>(registers.address_context(address))?;
+
+        Ok(WindowsExceptionRecord {
+            code: record.ExceptionCode,
+            flags: record.ExceptionFlags,
+            record: record.ExceptionRecord.into(),
+            address: record.ExceptionAddress.into(),
+            information: record.ExceptionInformation
+                [..u64::min(record.NumberParameters, 15) as usize]
+                .to_vec(),
+        })
+    }
+
+    /// Retrieves the last status value for the current thread.
+    ///
+    /// In Windows, the last status value is typically used to store error codes
+    /// or success indicators from system calls. This method reads this value
+    /// from the Thread Environment Block (TEB) of the current thread, providing
+    /// insight into the outcome of recent operations performed by the thread.
+    ///
+    /// Returns `None` if the TEB is not available.
+    ///
+    /// # Notes
+    ///
+    /// `LastStatusValue` is a `NTSTATUS` value, whereas `LastError` is a Win32
+    /// error code. The two values are related but not identical. You can obtain
+    /// the Win32 error code by calling
+    /// [`VmiOs::last_error`](crate::VmiOs::last_error).
+    ///
+    /// # Implementation Details
+    ///
+    /// ```c
+    /// return NtCurrentTeb()->LastStatusValue;
+    /// ```
+    pub fn last_status(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<u32>, VmiError> {
+        let KTHREAD = &self.offsets.common._KTHREAD;
+        let TEB = &self.offsets.common._TEB;
+
+        let current_thread = self.current_thread(vmi, registers)?;
+        let teb = vmi.read_va(
+            registers.address_context(current_thread.0 + KTHREAD.Teb.offset),
+            registers.address_width(),
+        )?;
+
+        if teb.is_null() {
+            return Ok(None);
+        }
+
+        let result = vmi.read_u32(registers.address_context(teb + TEB.LastStatusValue.offset))?;
+        Ok(Some(result))
+    }
+
+    /// Retrieves the time zone bias of the system.
+    ///
+    /// The bias is the difference between UTC and the local time, in
+    /// 100-nanosecond units (i.e., `UTC = local time + bias`).
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return SharedUserData->TimeZoneBias;
+    /// ```
+    pub fn time_zone_bias(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<i64, VmiError> {
+        let KUSER_SHARED_DATA = &self.offsets.common._KUSER_SHARED_DATA;
+
+        let address = Self::kuser_shared_data(registers) + KUSER_SHARED_DATA.TimeZoneBias.offset;
+        self.read_ksystem_time(vmi, registers, address)
+    }
+
+    /// Returns the kernel-mode address of the `KUSER_SHARED_DATA` structure.
+    fn kuser_shared_data(registers: &<Driver::Architecture as Architecture>::Registers) -> Va {
+        const KI_USER_SHARED_DATA_32: u64 = 0xffdf_0000;
+        const KI_USER_SHARED_DATA_64: u64 = 0xffff_f780_0000_0000;
+
+        match registers.address_width() {
+            4 => Va(KI_USER_SHARED_DATA_32),
+            _ => Va(KI_USER_SHARED_DATA_64),
+        }
+    }
+
+    /// Reads a `_KSYSTEM_TIME` structure.
+    ///
+    /// The kernel updates the structure without locking, writing `High2Time`
+    /// first and `High1Time` last. The value is consistent if both high
+    /// parts are equal, so the read is retried until they match.
+    fn read_ksystem_time(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        address: Va,
+    ) -> Result<i64, VmiError> {
+        const MAX_ATTEMPTS: usize = 16;
+
+        let KSYSTEM_TIME = &self.offsets.common._KSYSTEM_TIME;
+
+        for _ in 0..MAX_ATTEMPTS {
+            let time = StructReader::new(
+                vmi,
+                registers.address_context(address),
+                KSYSTEM_TIME.effective_len(),
+            )?;
+            let low_part = time.read(KSYSTEM_TIME.LowPart)? as u32;
+            let high1_time = time.read(KSYSTEM_TIME.High1Time)? as u32;
+            let high2_time = time.read(KSYSTEM_TIME.High2Time)? as u32;
+
+            if high1_time == high2_time {
+                return Ok(((u64::from(high1_time) << 32) | u64::from(low_part)) as i64);
+            }
+        }
+
+        Err(VmiError::Other("Inconsistent KSYSTEM_TIME"))
+    }
+
+    // endregion: Misc
+
+    // region: Network
+
+    /// Retrieves the TCP connections of the guest.
+    ///
+    /// Returns [`VmiError::NotSupported`] on kernels older than Windows 10.
+    ///
+    /// # Implementation Details
+    ///
+    /// `tcpip!PartitionTable` points to an array of `tcpip!PartitionCount`
+    /// `_PARTITION` structures. Each partition references a dynamic hash
+    /// table of `_TCP_ENDPOINT` structures, linked through their
+    /// `HashTableEntry` field.
+    ///
+    /// # Notes
+    ///
+    /// Listening sockets are tracked in the TCP port pool rather than in
+    /// the partition table, and are returned by [`tcp_listeners`] instead.
+    /// Endpoints that can't be read (e.g., because they are being torn
+    /// down) are skipped.
+    ///
+    /// [`tcp_listeners`]: Self::tcp_listeners
+    pub fn tcp_connections(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+    ) -> Result<Vec<WindowsTcpConnection>, VmiError> {
+        let offsets = match &self.offsets.ext {
+            Some(OffsetsExt::V2(offsets)) => offsets,
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        let layout = &tcpip.layout;
+
+        let partition_table = vmi.read_va(
+            registers.address_context(tcpip.base_address + tcpip.symbols.PartitionTable),
+            registers.address_width(),
+        )?;
+
+        let partition_count = vmi.read_u32(
+            registers.address_context(tcpip.base_address + tcpip.symbols.PartitionCount),
+        )?;
+
+        let mut result = Vec::new();
+
+        for index in 0..partition_count as u64 {
+            let partition = partition_table + index * layout.partition_size;
+
+            let endpoints = vmi.read_va(
+                registers.address_context(partition + layout.partition_endpoints),
+                registers.address_width(),
+            )?;
+
+            if endpoints.is_null() {
+                continue;
+            }
+
+            for entry in self.hash_table_entries(vmi, registers, endpoints, offsets)? {
+                let endpoint = entry - layout.tcp_endpoint_hash_table_entry;
+
+                match self.tcp_connection(vmi, registers, tcpip, endpoint) {
+                    Ok(Some(connection)) => result.push(connection),
+                    Ok(None) => {}
+                    Err(err) => tracing::debug!(%endpoint, ?err, "failed to read TCP endpoint"),
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the listening TCP sockets of the guest.
+    ///
+    /// # Implementation Details
+    ///
+    /// `tcpip!TcpPortPool` points to an `_INET_PORT_POOL` structure, which
+    /// is walked the same way as for [`udp_endpoints`]. The port assignment
+    /// entries reference `_TCP_LISTENER` structures, linked through their
+    /// `Next` field.
+    ///
+    /// # Notes
+    ///
+    /// Listeners that can't be read are skipped.
+    ///
+    /// [`udp_endpoints`]: Self::udp_endpoints
+    pub fn tcp_listeners(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+    ) -> Result<Vec<WindowsTcpListener>, VmiError> {
+        let entries = self.port_pool_entries(vmi, registers, tcpip, tcpip.symbols.TcpPortPool)?;
+
+        let mut result = Vec::new();
+
+        for entry in entries {
+            let listener = entry - tcpip.layout.tcp_listener_next;
+
+            match self.tcp_listener(vmi, registers, tcpip, listener) {
+                Ok(listener) => result.push(listener),
+                Err(err) => tracing::debug!(%listener, ?err, "failed to read TCP listener"),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the UDP endpoints of the guest.
+    ///
+    /// # Implementation Details
+    ///
+    /// `tcpip!UdpPortPool` points to an `_INET_PORT_POOL` structure. Its
+    /// `PortBitMap` has a bit set for every port in use. For each such port,
+    /// `PortAssignments[Port >> 8]->InPaBigPoolBase->Assignments[Port & 0xFF]`
+    /// references the first `_UDP_ENDPOINT` bound to the port, and further
+    /// endpoints are linked through their `Next` field.
+    ///
+    /// # Notes
+    ///
+    /// Endpoints that can't be read are skipped.
+    pub fn udp_endpoints(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+    ) -> Result<Vec<WindowsUdpEndpoint>, VmiError> {
+        let entries = self.port_pool_entries(vmi, registers, tcpip, tcpip.symbols.UdpPortPool)?;
+
+        let mut result = Vec::new();
+
+        for entry in entries {
+            let endpoint = entry - tcpip.layout.udp_endpoint_next;
+
+            match self.udp_endpoint(vmi, registers, tcpip, endpoint) {
+                Ok(endpoint) => result.push(endpoint),
+                Err(err) => tracing::debug!(%endpoint, ?err, "failed to read UDP endpoint"),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Collects the entries of an `_INET_PORT_POOL`.
+    ///
+    /// Returns the addresses of the `Next` fields of the structures bound
+    /// to the ports in use.
+    fn port_pool_entries(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        port_pool: u64, // tcpip!TcpPortPool or tcpip!UdpPortPool
+    ) -> Result<Vec<Va>, VmiError> {
+        let RTL_BITMAP = &self.offsets.common._RTL_BITMAP;
+
+        let port_pool = vmi.read_va(
+            registers.address_context(tcpip.base_address + port_pool),
+            registers.address_width(),
+        )?;
+
+        let bitmap = port_pool + tcpip.layout.port_pool_bitmap;
+
+        let size =
+            vmi.read_u32(registers.address_context(bitmap + RTL_BITMAP.SizeOfBitMap.offset))?;
+        let size = size.min(u16::MAX as u32 + 1) as usize;
+
+        let buffer = vmi.read_va(
+            registers.address_context(bitmap + RTL_BITMAP.Buffer.offset),
+            registers.address_width(),
+        )?;
+
+        let mut bits = vec![0u8; size.div_ceil(8)];
+        vmi.read(registers.address_context(buffer), &mut bits)?;
+
+        let mut result = Vec::new();
+
+        for port in 0..size {
+            if bits[port / 8] & (1 << (port % 8)) == 0 {
+                continue;
+            }
+
+            if let Err(err) = self.port_assignment_entries(
+                vmi,
+                registers,
+                tcpip,
+                port_pool,
+                port as u16,
+                &mut result,
+            ) {
+                tracing::debug!(port, ?err, "failed to read port assignment");
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Collects the entries bound to a port of an `_INET_PORT_POOL`.
+    fn port_assignment_entries(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        port_pool: Va,
+        port: u16,
+        result: &mut Vec<Va>,
+    ) -> Result<(), VmiError> {
+        // Guard against loops in corrupted lists.
+        const MAX_ENTRIES_PER_PORT: usize = 1024;
+
+        let layout = &tcpip.layout;
+        let address_width = registers.address_width() as u64;
+
+        let assignment = vmi.read_va(
+            registers.address_context(
+                port_pool + layout.port_pool_assignments + (port as u64 >> 8) * address_width,
+            ),
+            registers.address_width(),
+        )?;
+
+        if assignment.is_null() {
+            return Ok(());
+        }
+
+        let assignment_list = vmi.read_va(
+            registers.address_context(assignment + layout.port_assignment_list),
+            registers.address_width(),
+        )?;
+
+        if assignment_list.is_null() {
+            return Ok(());
+        }
+
+        let mut next = vmi.read_va(
+            registers.address_context(
+                assignment_list
+                    + layout.port_assignment_list_entries
+                    + (port as u64 & 0xFF) * layout.port_assignment_entry_size
+                    + layout.port_assignment_entry,
+            ),
+            registers.address_width(),
+        )?;
+
+        for _ in 0..MAX_ENTRIES_PER_PORT {
+            if next.is_null() {
+                break;
+            }
+
+            result.push(next);
+            next = vmi.read_va(registers.address_context(next), registers.address_width())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a `_TCP_ENDPOINT` structure.
+    ///
+    /// Returns `None` if the endpoint has no addresses assigned.
+    fn tcp_connection(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        endpoint: Va,
+    ) -> Result<Option<WindowsTcpConnection>, VmiError> {
+        let layout = &tcpip.layout;
+
+        let address_family = self.inet_address_family(
+            vmi,
+            registers,
+            tcpip,
+            endpoint + layout.tcp_endpoint_inet_af,
+        )?;
+
+        let addr_info = vmi.read_va(
+            registers.address_context(endpoint + layout.tcp_endpoint_addr_info),
+            registers.address_width(),
+        )?;
+
+        if addr_info.is_null() {
+            return Ok(None);
+        }
+
+        let local_address = vmi.read_va(
+            registers.address_context(addr_info + layout.addr_info_local),
+            registers.address_width(),
+        )?;
+
+        let remote_address = vmi.read_va(
+            registers.address_context(addr_info + layout.addr_info_remote),
+            registers.address_width(),
+        )?;
+
+        let local_address =
+            self.read_local_address(vmi, registers, tcpip, address_family, local_address)?;
+        let remote_address =
+            self.read_inet_address(vmi, registers, address_family, remote_address)?;
+
+        let (local_address, remote_address) = match (local_address, remote_address) {
+            (Some(local_address), Some(remote_address)) => (local_address, remote_address),
+            _ => return Ok(None),
+        };
+
+        let state =
+            vmi.read_u32(registers.address_context(endpoint + layout.tcp_endpoint_state))?;
+        let local_port =
+            vmi.read_u16(registers.address_context(endpoint + layout.tcp_endpoint_local_port))?;
+        let remote_port =
+            vmi.read_u16(registers.address_context(endpoint + layout.tcp_endpoint_remote_port))?;
+        let owner = self.endpoint_owner(vmi, registers, endpoint + layout.tcp_endpoint_owner)?;
+        let create_time =
+            vmi.read_u64(registers.address_context(endpoint + layout.tcp_endpoint_create_time))?;
+
+        Ok(Some(WindowsTcpConnection {
+            endpoint,
+            local_address,
+            local_port: u16::from_be(local_port),
+            remote_address,
+            remote_port: u16::from_be(remote_port),
+            state: WindowsTcpState::from(state),
+            owner,
+            create_time,
+        }))
+    }
+
+    /// Reads a `_TCP_LISTENER` structure.
+    fn tcp_listener(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        listener: Va,
+    ) -> Result<WindowsTcpListener, VmiError> {
+        let layout = &tcpip.layout;
+
+        let address_family = self.inet_address_family(
+            vmi,
+            registers,
+            tcpip,
+            listener + layout.tcp_listener_inet_af,
+        )?;
+
+        let local_address = vmi.read_va(
+            registers.address_context(listener + layout.tcp_listener_local_address),
+            registers.address_width(),
+        )?;
+
+        let local_address =
+            self.read_local_address(vmi, registers, tcpip, address_family, local_address)?;
+
+        let local_port =
+            vmi.read_u16(registers.address_context(listener + layout.tcp_listener_port))?;
+        let owner = self.endpoint_owner(vmi, registers, listener + layout.tcp_listener_owner)?;
+        let create_time =
+            vmi.read_u64(registers.address_context(listener + layout.tcp_listener_create_time))?;
+
+        Ok(WindowsTcpListener {
+            listener,
+            local_address,
+            local_port: u16::from_be(local_port),
+            owner,
+            create_time,
+        })
+    }
+
+    /// Reads a `_UDP_ENDPOINT` structure.
+    fn udp_endpoint(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        endpoint: Va,
+    ) -> Result<WindowsUdpEndpoint, VmiError> {
+        let layout = &tcpip.layout;
+
+        let address_family = self.inet_address_family(
+            vmi,
+            registers,
+            tcpip,
+            endpoint + layout.udp_endpoint_inet_af,
+        )?;
+
+        let local_address = vmi.read_va(
+            registers.address_context(endpoint + layout.udp_endpoint_local_address),
+            registers.address_width(),
+        )?;
+
+        let local_address =
+            self.read_local_address(vmi, registers, tcpip, address_family, local_address)?;
+
+        let local_port =
+            vmi.read_u16(registers.address_context(endpoint + layout.udp_endpoint_port))?;
+        let owner = self.endpoint_owner(vmi, registers, endpoint + layout.udp_endpoint_owner)?;
+        let create_time =
+            vmi.read_u64(registers.address_context(endpoint + layout.udp_endpoint_create_time))?;
+
+        Ok(WindowsUdpEndpoint {
+            endpoint,
+            local_address,
+            local_port: u16::from_be(local_port),
+            owner,
+            create_time,
+        })
+    }
+
+    /// Reads the address family from the `_INETAF` structure referenced
+    /// by an endpoint.
+    fn inet_address_family(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        inet_af: Va, // _INETAF**
+    ) -> Result<u16, VmiError> {
+        let inet_af = vmi.read_va(
+            registers.address_context(inet_af),
+            registers.address_width(),
+        )?;
+
+        vmi.read_u16(registers.address_context(inet_af + tcpip.layout.inet_af_address_family))
+    }
+
+    /// Reads the IP address referenced by a `_LOCAL_ADDRESS` structure.
+    ///
+    /// Returns `None` if the structure doesn't reference any address.
+    fn read_local_address(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tcpip: &WindowsTcpip,
+        address_family: u16,
+        local_address: Va, // _LOCAL_ADDRESS*
+    ) -> Result<Option<IpAddr>, VmiError> {
+        if local_address.is_null() {
+            return Ok(None);
+        }
+
+        let data = vmi.read_va(
+            registers.address_context(local_address + tcpip.layout.local_address_data),
+            registers.address_width(),
+        )?;
+
+        if data.is_null() {
+            return Ok(None);
+        }
+
+        let address = vmi.read_va(registers.address_context(data), registers.address_width())?;
+        self.read_inet_address(vmi, registers, address_family, address)
+    }
+
+    /// Reads an `IN_ADDR` or `IN6_ADDR` structure, depending on the
+    /// address family.
+    ///
+    /// Returns `None` if the address is null or the address family is
+    /// neither `AF_INET` nor `AF_INET6`.
+    fn read_inet_address(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        address_family: u16,
+        address: Va,
+    ) -> Result<Option<IpAddr>, VmiError> {
+        const AF_INET: u16 = 2;
+        const AF_INET6: u16 = 23;
+
+        if address.is_null() {
+            return Ok(None);
+        }
+
+        match address_family {
+            AF_INET => {
+                let mut buffer = [0u8; 4];
+                vmi.read(registers.address_context(address), &mut buffer)?;
+                Ok(Some(IpAddr::from(buffer)))
+            }
+            AF_INET6 => {
+                let mut buffer = [0u8; 16];
+                vmi.read(registers.address_context(address), &mut buffer)?;
+                Ok(Some(IpAddr::from(buffer)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Reads the PID of the process that owns an endpoint.
+    fn endpoint_owner(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        owner: Va, // _EPROCESS**
+    ) -> Result<Option<ProcessId>, VmiError> {
+        let process = vmi.read_va(registers.address_context(owner), registers.address_width())?;
+
+        if process.is_null() {
+            return Ok(None);
+        }
+
+        Ok(Some(self.process_id(
+            vmi,
+            registers,
+            ProcessObject(process),
+        )?))
+    }
+
+    /// Collects the entries of an `_RTL_DYNAMIC_HASH_TABLE`.
+    ///
+    /// Returns the addresses of the `_RTL_DYNAMIC_HASH_TABLE_ENTRY`
+    /// structures.
+    ///
+    /// # Implementation Details
+    ///
+    /// Tables with up to 128 buckets have `Directory` pointing directly to
+    /// the array of bucket list heads. Larger tables use a two-level
+    /// directory: `Directory` points to an array of pointers to second-level
+    /// arrays, the `N`-th of which holds `128 << N` buckets.
+    fn hash_table_entries(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        table: Va,
+        offsets: &v2::Offsets,
+    ) -> Result<Vec<Va>, VmiError> {
+        const HT_SECOND_LEVEL_DIR_MIN_SIZE: u64 = 128;
+
+        let RTL_DYNAMIC_HASH_TABLE = &offsets._RTL_DYNAMIC_HASH_TABLE;
+
+        let num_entries = vmi.read_u32(
+            registers.address_context(table + RTL_DYNAMIC_HASH_TABLE.NumEntries.offset),
+        )?;
+        if num_entries == 0 {
+            return Ok(Vec::new());
+        }
+
+        let table_size = vmi
+            .read_u32(registers.address_context(table + RTL_DYNAMIC_HASH_TABLE.TableSize.offset))?
+            as u64;
+
+        let directory = vmi.read_va(
+            registers.address_context(table + RTL_DYNAMIC_HASH_TABLE.Directory.offset),
+            registers.address_width(),
+        )?;
+
+        let list_entry_size = 2 * registers.address_width() as u64;
+
+        // (second-level array, number of buckets in it)
+        let mut bucket_arrays = Vec::new();
+        if table_size <= HT_SECOND_LEVEL_DIR_MIN_SIZE {
+            bucket_arrays.push((directory, table_size));
+        }
+        else {
+            let mut remaining = table_size;
+            let mut index = 0;
+
+            while remaining > 0 {
+                let size = HT_SECOND_LEVEL_DIR_MIN_SIZE << index;
+                let array = vmi.read_va(
+                    registers.address_context(directory + index * registers.address_width() as u64),
+                    registers.address_width(),
+                )?;
+
+                bucket_arrays.push((array, size.min(remaining)));
+                remaining = remaining.saturating_sub(size);
+                index += 1;
+            }
+        }
+
+        let mut result = Vec::new();
+        for (array, size) in bucket_arrays {
+            if array.is_null() {
+                continue;
+            }
+
+            for bucket in 0..size {
+                let list_head = array + bucket * list_entry_size;
+
+                self.enumerate_list(vmi, registers, list_head, |entry| {
+                    result.push(entry);
+                    result.len() <= num_entries as usize
+                })?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    // endregion: Network
+
+    // region: Object
+
+    /// Reads an `_EX_FAST_REF` pointer and returns the object it refers to.
+    ///
+    /// Fast references (e.g., `_EPROCESS.Token` or
+    /// `_CONTROL_AREA.FilePointer`) store a small reference count in the
+    /// low bits of the pointer, which must be masked off before the
+    /// pointer can be dereferenced.
+    ///
+    /// # Implementation Details
+    ///
+    /// The width of the reference count is taken from the
+    /// `_EX_FAST_REF.RefCnt` bitfield, which is 3 bits on 32-bit systems
+    /// and 4 bits on 64-bit systems.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return (PVOID)(FastRef->Value & ~MAX_FAST_REFS);
+    /// ```
+    pub fn read_fast_ref(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        fast_ref: Va, // _EX_FAST_REF*
+    ) -> Result<Va, VmiError> {
+        let EX_FAST_REF = &self.offsets.common._EX_FAST_REF;
+
+        debug_assert_eq!(EX_FAST_REF.RefCnt.offset, 0);
+        debug_assert_eq!(EX_FAST_REF.RefCnt.bit_position, 0);
+
+        let value = vmi.read_va(
+            registers.address_context(fast_ref + EX_FAST_REF.Value.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(value & !((1 << EX_FAST_REF.RefCnt.bit_length) - 1))
+    }
+
+    /// Retrieves the object header cookie used for obfuscating object types.
+    /// Returns `None` if the cookie is not present in the kernel image.
+    ///
+    /// # Notes
+    ///
+    /// Windows 10 introduced a security feature that obfuscates the type
+    /// of kernel objects by XORing the `TypeIndex` field in the object header
+    /// with a random cookie value. This method fetches that cookie, which is
+    /// essential for correctly interpreting object headers in memory.
+    ///
+    /// # Implementation Details
+    ///
+    /// The object header cookie is located by reading the `ObHeaderCookie`
+    /// symbol from the kernel image.
+    pub fn object_header_cookie(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<u8>, VmiError> {
+        if let Some(cookie) = *self.object_header_cookie.borrow() {
+            return Ok(Some(cookie));
+        }
+
+        let ObHeaderCookie = match self.symbols.ObHeaderCookie {
+            Some(cookie) => cookie,
+            None => return Ok(None),
+        };
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let cookie = vmi.read_u8(registers.address_context(kernel_image_base + ObHeaderCookie))?;
+        *self.object_header_cookie.borrow_mut() = Some(cookie);
+        Ok(Some(cookie))
+    }
+
+    /// Determines the type of a Windows kernel object.
+    ///
+    /// This method analyzes the object header of a given kernel object
+    /// and returns its type (e.g., Process, Thread, File). It handles the
+    /// obfuscation introduced by the object header cookie, ensuring accurate
+    /// type identification even on systems with this security feature enabled.
+    pub fn object_type(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<Option<WindowsObjectType>, VmiError> {
+        let index = self.object_type_index(vmi, registers, object)?;
+        self.object_type_by_index(vmi, registers, index)
+    }
+
+    /// Retrieves the de-obfuscated type index of a Windows kernel object.
+    ///
+    /// The returned value is an index into the `ObTypeIndexTable` and can be
+    /// passed to [`object_type_by_index`]. Only the object header is read,
+    /// which makes this method suitable for identifying objects found by
+    /// scanning memory (e.g., pool scanning).
+    ///
+    /// # Implementation Details
+    ///
+    /// On Windows 7, the `TypeIndex` field of the `_OBJECT_HEADER` is
+    /// stored as is. Starting with Windows 10, the stored value is XORed
+    /// with the second least significant byte of the `_OBJECT_HEADER`
+    /// address and with the `ObHeaderCookie` value.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// UCHAR index = ObjectHeader->TypeIndex;
+    ///
+    /// if (ObHeaderCookie) {
+    ///     index ^= (UCHAR)((ULONG_PTR)ObjectHeader >> 8) ^ ObHeaderCookie;
+    /// }
+    ///
+    /// return index;
+    /// ```
+    ///
+    /// [`object_type_by_index`]: Self::object_type_by_index
+    pub fn object_type_index(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<u8, VmiError> {
+        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
+
+        let object_header = object - OBJECT_HEADER.Body.offset;
+        let type_index =
+            vmi.read_u8(registers.address_context(object_header + OBJECT_HEADER.TypeIndex.offset))?;
+
+        let index = match self.object_header_cookie(vmi, registers)? {
+            Some(cookie) => {
+                //
+                // TypeIndex ^ 2nd least significate byte of OBJECT_HEADER address ^
+                // nt!ObHeaderCookie ref: https://medium.com/@ashabdalhalim/a-light-on-windows-10s-object-header-typeindex-value-e8f907e7073a
+                //
+
+                let salt = (u64::from(object_header) >> 8) as u8;
+                type_index ^ salt ^ cookie
+            }
+            None => type_index,
+        };
+
+        Ok(index)
+    }
+
+    /// Determines the object type from a de-obfuscated type index.
+    ///
+    /// Returns `None` if the type at the given index is not one of the
+    /// known [`WindowsObjectType`] variants.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `_OBJECT_TYPE` pointer is read from the `ObTypeIndexTable` at
+    /// the given index and the type is identified by its `Name` field.
+    pub fn object_type_by_index(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        index: u8,
+    ) -> Result<Option<WindowsObjectType>, VmiError> {
+        let ObTypeIndexTable = self.symbols.ObTypeIndexTable;
+
+        let index = index as u64;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let object_type = vmi.read_va(
+            registers.address_context(
+                kernel_image_base + ObTypeIndexTable + index * 8, // REVIEW: replace 8 with registers.address_width()?
+            ),
+            registers.address_width(),
+        )?;
+
+        if let Some(typ) = self.object_type_cache.borrow().get(&object_type) {
+            return Ok(Some(*typ));
+        }
+
+        let object_name = self.read_unicode_string(
+            vmi,
+            registers.address_context(object_type + self.offsets.common._OBJECT_TYPE.Name.offset),
+        )?;
+
+        let typ = match object_name.as_str() {
+            "ALPC Port" => WindowsObjectType::AlpcPort,
+            "DebugObject" => WindowsObjectType::DebugObject,
+            "Desktop" => WindowsObjectType::Desktop,
+            "Device" => WindowsObjectType::Device,
+            "Directory" => WindowsObjectType::Directory,
+            "Driver" => WindowsObjectType::Driver,
+            "Event" => WindowsObjectType::Event,
+            "File" => WindowsObjectType::File,
+            "Job" => WindowsObjectType::Job,
+            "Key" => WindowsObjectType::Key,
+            "Mutant" => WindowsObjectType::Mutant,
+            "Port" => WindowsObjectType::Port,
+            "Process" => WindowsObjectType::Process,
+            "Section" => WindowsObjectType::Section,
+            "SymbolicLink" => WindowsObjectType::SymbolicLink,
+            "Thread" => WindowsObjectType::Thread,
+            "Timer" => WindowsObjectType::Timer,
+            "Token" => WindowsObjectType::Token,
+            "Type" => WindowsObjectType::Type,
+            "WindowStation" => WindowsObjectType::WindowStation,
+            _ => return Ok(None),
+        };
+
+        self.object_type_cache.borrow_mut().insert(object_type, typ);
+
+        Ok(Some(typ))
+    }
+
+    /// Retrieves the object counters of all object types.
+    ///
+    /// The counters are maintained by the object manager for every type,
+    /// so comparing them against the objects that can be enumerated (e.g.,
+    /// processes in the `ActiveProcessLinks` list) reveals hidden objects.
+    ///
+    /// # Implementation Details
+    ///
+    /// The `ObTypeIndexTable` is walked from index 2 until the first empty
+    /// entry. The first two entries are reserved and never hold a type.
+    pub fn object_types(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<WindowsObjectTypeInfo>, VmiError> {
+        let ObTypeIndexTable = self.symbols.ObTypeIndexTable;
+        let OBJECT_TYPE = &self.offsets.common._OBJECT_TYPE;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let address_width = registers.address_width();
+
+        let mut result = Vec::new();
+
+        for index in 2..=u8::MAX {
+            let object_type = vmi.read_va(
+                registers.address_context(
+                    kernel_image_base + ObTypeIndexTable + index as u64 * address_width as u64,
+                ),
+                address_width,
+            )?;
+
+            if object_type.is_null() {
+                break;
+            }
+
+            let name = self.read_unicode_string(
+                vmi,
+                registers.address_context(object_type + OBJECT_TYPE.Name.offset),
+            )?;
+
+            let read_counter =
+                |field: Field| vmi.read_u32(registers.address_context(object_type + field.offset));
+
+            result.push(WindowsObjectTypeInfo {
+                address: object_type,
+                index,
+                name,
+                typ: self.object_type_by_index(vmi, registers, index)?,
+                total_objects: read_counter(OBJECT_TYPE.TotalNumberOfObjects)?,
+                total_handles: read_counter(OBJECT_TYPE.TotalNumberOfHandles)?,
+                high_water_objects: read_counter(OBJECT_TYPE.HighWaterNumberOfObjects)?,
+                high_water_handles: read_counter(OBJECT_TYPE.HighWaterNumberOfHandles)?,
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the object counters of a specific object type.
+    ///
+    /// Returns `None` if the type is not registered in the
+    /// `ObTypeIndexTable` (e.g., early during boot).
+    ///
+    /// See [`object_types`] for details.
+    ///
+    /// [`object_types`]: Self::object_types
+    pub fn object_type_info(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        typ: WindowsObjectType,
+    ) -> Result<Option<WindowsObjectTypeInfo>, VmiError> {
+        Ok(self
+            .object_types(vmi, registers)?
+            .into_iter()
+            .find(|info| info.typ == Some(typ)))
+    }
+
+    /// Retrieves the name of a named kernel object.
+    ///
+    /// Many Windows kernel objects (like mutexes, events, etc.) can have names.
+    /// This method extracts the name of such an object, if present. It also
+    /// provides information about the object's containing directory in the
+    /// object namespace.
+    pub fn object_name(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<Option<WindowsObjectName>, VmiError> {
+        let ObpInfoMaskToOffset = self.symbols.ObpInfoMaskToOffset;
+        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
+        let OBJECT_HEADER_NAME_INFO = &self.offsets.common._OBJECT_HEADER_NAME_INFO;
+
+        let object_header = object - OBJECT_HEADER.Body.offset;
+        let info_mask =
+            vmi.read_u8(registers.address_context(object_header + OBJECT_HEADER.InfoMask.offset))?;
+
+        bitflags::bitflags! {
+            struct InfoFlags: u8 {
+                const CREATOR_INFO = 0x01;
+                const NAME_INFO = 0x02;
+                const HANDLE_INFO = 0x04;
+                const QUOTA_INFO = 0x08;
+                const PROCESS_INFO = 0x10;
+            }
+        }
+
+        let info_flags = InfoFlags::from_bits_truncate(info_mask);
+        if !info_flags.contains(InfoFlags::NAME_INFO) {
+            return Ok(None);
+        }
+
+        // Offset = ObpInfoMaskToOffset[OBJECT_HEADER->InfoMask & (DesiredHeaderBit | (DesiredHeaderBit-1))]
+
+        let mask = info_mask & (InfoFlags::NAME_INFO.bits() | (InfoFlags::NAME_INFO.bits() - 1));
+        let mask = mask as u64;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let offset = vmi
+            .read_u8(registers.address_context(kernel_image_base + ObpInfoMaskToOffset + mask))?
+            as u64;
+
+        let object_header_name_info = object_header - offset;
+
+        let directory = vmi.read_va(
+            registers.address_context(
+                object_header_name_info + OBJECT_HEADER_NAME_INFO.Directory.offset,
+            ),
+            registers.address_width(),
+        )?;
+
+        let name = self.read_unicode_string(
+            vmi,
+            registers
+                .address_context(object_header_name_info + OBJECT_HEADER_NAME_INFO.Name.offset),
+        )?;
+
+        Ok(Some(WindowsObjectName { directory, name }))
+    }
+
+    /// Converts an `OBJECT_ATTRIBUTES` structure to an object name string.
+    ///
+    /// `OBJECT_ATTRIBUTES` is a structure used in many Windows system calls to
+    /// specify an object. This method interprets that structure and extracts
+    /// a meaningful name or path for the object. It handles both absolute and
+    /// relative object names, considering the root directory if specified.
+    ///
+    /// Returns `None` if the `_OBJECT_ATTRIBUTES::ObjectName` field is `NULL`.
+    pub fn object_attributes_to_object_name(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        object_attributes: Va,
+    ) -> Result<Option<String>, VmiError> {
+        let OBJECT_ATTRIBUTES = &self.offsets.common._OBJECT_ATTRIBUTES;
+
+        let object_name_address = vmi.read_va(
+            registers.address_context(object_attributes + OBJECT_ATTRIBUTES.ObjectName.offset),
+            registers.address_width(),
+        )?;
+
+        if object_name_address.is_null() {
+            return Ok(None);
+        }
+
+        let object_name =
+            self.read_unicode_string(vmi, registers.address_context(object_name_address))?;
+
+        let root_directory = vmi.read_va(
+            registers.address_context(object_attributes + OBJECT_ATTRIBUTES.RootDirectory.offset),
+            registers.address_width(),
+        )?;
+
+        if root_directory.is_null() {
+            return Ok(Some(object_name));
+        }
+
+        let object =
+            match self.handle_to_object(vmi, registers, process, u64::from(root_directory))? {
+                Some(object) => object,
+                None => return Ok(Some(object_name)),
+            };
+
+        let root_name = match object {
+            WindowsObject::File(file) => Some(file.filename),
+            WindowsObject::Section(section) => match section.region.kind {
+                OsRegionKind::Mapped(mapped) => mapped.path?,
+                _ => None,
+            },
+            WindowsObject::SymbolicLink(_) => None,
+        };
+
+        match root_name {
+            Some(root_name) => Ok(Some(format!("{root_name}\\{object_name}"))),
+            None => Ok(Some(object_name)),
+        }
+    }
+
+    /// Retrieves the root directory of the object namespace (`\`).
+    ///
+    /// # Implementation Details
+    ///
+    /// The root directory is read from the `ObpRootDirectoryObject` symbol.
+    pub fn root_directory(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Va, VmiError> {
+        let ObpRootDirectoryObject = self.symbols.ObpRootDirectoryObject;
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        vmi.read_va(
+            registers.address_context(kernel_image_base + ObpRootDirectoryObject),
+            registers.address_width(),
+        )
+    }
+
+    /// Retrieves the objects contained in an object directory.
+    ///
+    /// # Implementation Details
+    ///
+    /// An `_OBJECT_DIRECTORY` is a hash table of 37 buckets. Each bucket
+    /// is a singly linked list of `_OBJECT_DIRECTORY_ENTRY` structures,
+    /// linked through the `ChainLink` field.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// for (ULONG i = 0; i < NUMBER_HASH_BUCKETS; i++) {
+    ///     POBJECT_DIRECTORY_ENTRY Entry = Directory->HashBuckets[i];
+    ///
+    ///     while (Entry) {
+    ///         Callback(Entry->Object);
+    ///         Entry = Entry->ChainLink;
+    ///     }
+    /// }
+    /// ```
+    pub fn directory_objects(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        directory: Va,
+    ) -> Result<Vec<Va>, VmiError> {
+        const NUMBER_HASH_BUCKETS: u64 = 37;
+
+        // Limits the total number of objects of corrupted directories.
+        const MAX_ENTRIES: usize = 0x10000;
+
+        let OBJECT_DIRECTORY = &self.offsets.common._OBJECT_DIRECTORY;
+        let OBJECT_DIRECTORY_ENTRY = &self.offsets.common._OBJECT_DIRECTORY_ENTRY;
+
+        let address_width = registers.address_width();
+
+        let mut result = Vec::new();
+        for index in 0..NUMBER_HASH_BUCKETS {
+            let entry = vmi.read_va(
+                registers.address_context(
+                    directory + OBJECT_DIRECTORY.HashBuckets.offset + index * address_width as u64,
+                ),
+                address_width,
+            )?;
+
+            for entry in vmi.follow_chain(
+                registers.address_context(entry),
+                OBJECT_DIRECTORY_ENTRY.ChainLink.offset,
+                Va(0),
+                address_width,
+            ) {
+                if result.len() >= MAX_ENTRIES {
+                    break;
+                }
+
+                let object = vmi.read_va(
+                    registers.address_context(entry? + OBJECT_DIRECTORY_ENTRY.Object.offset),
+                    address_width,
+                )?;
+
+                result.push(object);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the target of a symbolic link object.
+    ///
+    /// Returns `None` if the link is resolved by a callback.
+    ///
+    /// # Implementation Details
+    ///
+    /// The target is read from the `LinkTarget` field of the
+    /// `_OBJECT_SYMBOLIC_LINK` structure. On Windows 10+, the field shares
+    /// a union with the `Callback` and `CallbackContext` pointers, which
+    /// are used instead if the `OBJECT_SYMBOLIC_LINK_USE_CALLBACK` bit
+    /// (`0x10`) of the `Flags` field is set.
+    pub fn symbolic_link_target(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        object: Va,
+    ) -> Result<Option<String>, VmiError> {
+        const OBJECT_SYMBOLIC_LINK_USE_CALLBACK: u32 = 0x10;
+
+        let OBJECT_SYMBOLIC_LINK = &self.offsets.common._OBJECT_SYMBOLIC_LINK;
+
+        if let Some(Flags) = OBJECT_SYMBOLIC_LINK.Flags {
+            let flags = vmi.read_u32(registers.address_context(object + Flags.offset))?;
+
+            if flags & OBJECT_SYMBOLIC_LINK_USE_CALLBACK != 0 {
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(self.read_unicode_string(
+            vmi,
+            registers.address_context(object + OBJECT_SYMBOLIC_LINK.LinkTarget.offset),
+        )?))
+    }
+
+    /// Looks up an object by its path, relative to an object directory.
+    ///
+    /// Path components are separated by backslashes and compared
+    /// case-insensitively. Every component except the last one must name
+    /// a directory or a symbolic link.
+    ///
+    /// Symbolic links are followed transparently, including the last
+    /// component. Their targets are resolved from the root directory.
+    ///
+    /// Returns `None` if any of the components is not found, if too many
+    /// symbolic links are encountered, or if a symbolic link is resolved by
+    /// a callback.
+    ///
+    /// # Notes
+    ///
+    /// The `\??` directory is resolved to the global `\GLOBAL??` directory.
+    /// Per-session DOS device maps are not taken into account.
+    pub fn lookup_object(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        directory: Va,
+        path: &str,
+    ) -> Result<Option<Va>, VmiError> {
+        // Same limit as `ObpLookupObjectName()`.
+        const MAX_REPARSE: usize = 32;
+
+        let root = self.root_directory(vmi, registers)?;
+
+        let mut components = split_object_path(path);
+        let mut current = directory;
+        let mut reparse_count = 0;
+
+        while let Some(component) = components.pop_front() {
+            if !matches!(
+                self.object_type(vmi, registers, current)?,
+                Some(WindowsObjectType::Directory)
+            ) {
+                return Ok(None);
+            }
+
+            let component = match component.as_str() {
+                "??" if current == root => "GLOBAL??",
+                component => component,
+            };
+
+            let mut found = None;
+            for object in self.directory_objects(vmi, registers, current)? {
+                let name = match self.object_name(vmi, registers, object)? {
+                    Some(name) => name.name,
+                    None => continue,
+                };
+
+                if name.eq_ignore_ascii_case(component) {
+                    found = Some(object);
+                    break;
+                }
+            }
+
+            let object = match found {
+                Some(object) => object,
+                None => return Ok(None),
+            };
+
+            if !matches!(
+                self.object_type(vmi, registers, object)?,
+                Some(WindowsObjectType::SymbolicLink)
+            ) {
+                current = object;
+                continue;
+            }
+
+            reparse_count += 1;
+            if reparse_count > MAX_REPARSE {
+                tracing::debug!(path, "too many symbolic links");
+                return Ok(None);
+            }
+
+            let target = match self.symbolic_link_target(vmi, registers, object)? {
+                Some(target) if !target.is_empty() => target,
+                _ => return Ok(None),
+            };
+
+            let mut reparsed = split_object_path(&target);
+            reparsed.extend(components);
+            components = reparsed;
+            current = root;
+        }
+
+        Ok(Some(current))
+    }
+
+    /// Retrieves the window stations of all sessions.
+    ///
+    /// Hidden window stations, created by malware to run UI invisibly, show
+    /// up here alongside the regular ones (`WinSta0`, `Service-0x0-3e7$`,
+    /// ...).
+    ///
+    /// # Implementation Details
+    ///
+    /// Window stations are named objects in the `\Windows\WindowStations`
+    /// directory for session 0, and in the
+    /// `\Sessions\<SessionId>\Windows\WindowStations` directory for other
+    /// sessions.
+    ///
+    /// Desktops are not part of the object namespace. They are linked from
+    /// the `win32k` window station structure, which is not covered by the
+    /// kernel profile.
+    pub fn window_stations(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<WindowsWindowStation>, VmiError> {
+        let root = self.root_directory(vmi, registers)?;
+
+        let mut directories = Vec::new();
+        if let Some(directory) =
+            self.lookup_object(vmi, registers, root, r"Windows\WindowStations")?
+        {
+            directories.push((directory, 0));
+        }
+
+        if let Some(sessions) = self.lookup_object(vmi, registers, root, "Sessions")? {
+            for session in self.directory_objects(vmi, registers, sessions)? {
+                let session_id = match self.object_name(vmi, registers, session)? {
+                    Some(name) => match name.name.parse::<u32>() {
+                        Ok(session_id) => session_id,
+                        Err(_) => continue,
+                    },
+                    None => continue,
+                };
+
+                if let Some(directory) =
+                    self.lookup_object(vmi, registers, session, r"Windows\WindowStations")?
+                {
+                    directories.push((directory, session_id));
+                }
+            }
+        }
+
+        let mut result = Vec::<WindowsWindowStation>::new();
+        for (directory, session_id) in directories {
+            for object in self.directory_objects(vmi, registers, directory)? {
+                if result.iter().any(|station| station.object == object) {
+                    continue;
+                }
+
+                if !matches!(
+                    self.object_type(vmi, registers, object)?,
+                    Some(WindowsObjectType::WindowStation)
+                ) {
+                    continue;
+                }
+
+                let name = match self.object_name(vmi, registers, object)? {
+                    Some(name) => name.name,
+                    None => continue,
+                };
+
+                result.push(WindowsWindowStation {
+                    object,
+                    name,
+                    session_id,
+                });
+            }
+        }
+
+        Ok(result)
+    }
+
+    // endregion: Object
+
+    // region: PEB
+
+    /// Retrieves the Process Environment Block (PEB) for a given process.
+    ///
+    /// The PEB contains crucial information about a process, including its
+    /// loaded modules, environment variables, and command line arguments.
+    ///
+    /// Returns `None` if the process has no PEB. This is the case for
+    /// minimal and pico processes (see [`process_is_minimal`] and
+    /// [`process_is_pico`]).
+    ///
+    /// [`process_is_minimal`]: Self::process_is_minimal
+    /// [`process_is_pico`]: Self::process_is_pico
+    pub fn process_peb(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Option<WindowsPeb>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
+
+        let address = match self.__process_peb_address(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        let current_directory = self
+            .__process_current_directory(vmi, registers, process, root)?
+            .unwrap_or_default();
+        let dll_path = self
+            .__process_dll_path(vmi, registers, process, root)?
+            .unwrap_or_default();
+        let image_path_name = self
+            .__process_image_path_name(vmi, registers, process, root)?
+            .unwrap_or_default();
+        let command_line = self
+            .__process_command_line(vmi, registers, process, root)?
+            .unwrap_or_default();
+
+        Ok(Some(WindowsPeb {
+            address: address.va,
+            current_directory,
+            dll_path,
+            image_path_name,
+            command_line,
+        }))
+    }
+
+    /// Internal method to get the address of the PEB.
+    ///
+    /// This method handles both native (non-WoW64) processes and WoW64
+    /// processes, returning the appropriate PEB address based on the
+    /// process architecture. Returns `None` if the process has no PEB.
+    fn __process_peb_address(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Option<WindowsWow64Va>, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+
+        let wow64 = vmi.read_va(
+            (process.0 + EPROCESS.WoW64Process.offset, root),
+            registers.address_width(),
+        )?;
+
+        if wow64.is_null() {
+            let peb64 = vmi.read_va(
+                (process.0 + EPROCESS.Peb.offset, root),
+                registers.address_width(),
+            )?;
+
+            if peb64.is_null() {
+                return Ok(None);
+            }
+
+            Ok(Some(WindowsWow64Va::native(peb64)))
+        }
+        else {
+            let peb32 = match &self.offsets.ext {
+                Some(OffsetsExt::V1(_)) => wow64,
+                Some(OffsetsExt::V2(v2)) => vmi.read_va(
+                    (wow64 + v2._EWOW64PROCESS.Peb.offset, root),
+                    registers.address_width(),
+                )?,
+                None => panic!("OffsetsExt not set"),
+            };
+
+            if peb32.is_null() {
+                return Ok(None);
+            }
+
+            Ok(Some(WindowsWow64Va::x86(peb32)))
+        }
+    }
+
+    /// Internal method to retrieve the address of
+    /// `RTL_USER_PROCESS_PARAMETERS`.
+    ///
+    /// This structure contains various process-specific parameters, including
+    /// the command line, current directory, and DLL search path.
+    fn __process_rtl_process_parameters(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Option<WindowsWow64Va>, VmiError> {
+        let address = match self.__process_peb_address(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        let result = match address.kind {
+            WindowsWow64Kind::Native => {
+                let PEB = &self.offsets.common._PEB;
+
+                let va = vmi.read_va(
+                    (address.va + PEB.ProcessParameters.offset, root),
+                    registers.address_width(),
+                )?;
+
+                WindowsWow64Va::native(va)
+            }
+            WindowsWow64Kind::X86 => {
+                const PEB32_ProcessParameters_offset: u64 = 0x10;
+
+                let va = vmi.read_va(
+                    (address.va + PEB32_ProcessParameters_offset, root),
+                    registers.address_width(),
+                )?;
+
+                WindowsWow64Va::x86(va)
+            }
+        };
+
+        if result.va.is_null() {
+            return Ok(None);
+        }
+
+        Ok(Some(result))
+    }
+
+    /// Retrieves the user-mode modules loaded in a process.
+    ///
+    /// The modules are listed in load order. For WoW64 processes, the
+    /// native modules (e.g., `ntdll.dll`, `wow64.dll`) are followed by the
+    /// modules of the 32-bit address space. Modules whose loader entries
+    /// fail to be read are skipped.
+    ///
+    /// Returns an empty list if the process has no PEB (e.g., minimal and
+    /// pico processes) or if the loader data is not initialized yet.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PPEB_LDR_DATA Ldr = NtCurrentPeb()->Ldr;
+    /// PLIST_ENTRY ListHead = &Ldr->InLoadOrderModuleList;
+    ///
+    /// for (PLIST_ENTRY Entry = ListHead->Flink; Entry != ListHead; Entry = Entry->Flink) {
+    ///     PLDR_DATA_TABLE_ENTRY Module = CONTAINING_RECORD(Entry, LDR_DATA_TABLE_ENTRY, InLoadOrderLinks);
+    ///     // ...
+    /// }
+    /// ```
+    pub fn process_user_modules(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<WindowsUserModule>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
+        self.__process_user_modules(vmi, registers, process, root)
+    }
+
+    /// Internal method to retrieve the user-mode modules of a process.
+    fn __process_user_modules(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Vec<WindowsUserModule>, VmiError> {
+        const PEB32_Ldr_offset: u64 = 0x0c;
+        const PEB_LDR_DATA32_InLoadOrderModuleList_offset: u64 = 0x0c;
+
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let PEB = &self.offsets.common._PEB;
+        let PEB_LDR_DATA = &self.offsets.common._PEB_LDR_DATA;
+
+        let mut result = Vec::new();
+
+        //
+        // The native PEB is present in WoW64 processes as well.
+        //
+
+        let peb = vmi.read_va(
+            (process.0 + EPROCESS.Peb.offset, root),
+            registers.address_width(),
+        )?;
+
+        if !peb.is_null() {
+            let ldr = vmi.read_va((peb + PEB.Ldr.offset, root), registers.address_width())?;
+
+            if !ldr.is_null() {
+                self.__user_module_list(
+                    vmi,
+                    root,
+                    ldr + PEB_LDR_DATA.InLoadOrderModuleList.offset,
+                    WindowsWow64Kind::Native,
+                    registers.address_width(),
+                    &mut result,
+                )?;
+            }
+        }
+
+        if let Some(WindowsWow64Va {
+            va: peb32,
+            kind: WindowsWow64Kind::X86,
+        }) = self.__process_peb_address(vmi, registers, process, root)?
+        {
+            let ldr = vmi.read_va32((peb32 + PEB32_Ldr_offset, root))?;
+
+            if !ldr.is_null() {
+                self.__user_module_list(
+                    vmi,
+                    root,
+                    ldr + PEB_LDR_DATA32_InLoadOrderModuleList_offset,
+                    WindowsWow64Kind::X86,
+                    4,
+                    &mut result,
+                )?;
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Internal method to walk a loader module list.
+    ///
+    /// The user-mode `_LDR_DATA_TABLE_ENTRY` shares the layout of its
+    /// leading fields with the `_KLDR_DATA_TABLE_ENTRY`, so the native
+    /// entries are read using the offsets of the latter. The 32-bit entries
+    /// of WoW64 processes are not described by the kernel symbols and are
+    /// read using fixed offsets.
+    fn __user_module_list(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        list_head: Va,
+        kind: WindowsWow64Kind,
+        address_width: usize,
+        result: &mut Vec<WindowsUserModule>,
+    ) -> Result<(), VmiError> {
+        const MAX_MODULES: usize = 0x1000;
+
+        const LDR_DATA_TABLE_ENTRY32_DllBase_offset: u64 = 0x18;
+        const LDR_DATA_TABLE_ENTRY32_SizeOfImage_offset: u64 = 0x20;
+        const LDR_DATA_TABLE_ENTRY32_FullDllName_offset: u64 = 0x24;
+        const LDR_DATA_TABLE_ENTRY32_BaseDllName_offset: u64 = 0x2c;
+
+        let LDR_DATA_TABLE_ENTRY = &self.offsets.common._KLDR_DATA_TABLE_ENTRY;
+
+        let read_module = |entry: Va| -> Result<WindowsUserModule, VmiError> {
+            match kind {
+                WindowsWow64Kind::Native => {
+                    let entry = entry - LDR_DATA_TABLE_ENTRY.InLoadOrderLinks.offset;
+
+                    Ok(WindowsUserModule {
+                        base_address: vmi.read_va(
+                            (entry + LDR_DATA_TABLE_ENTRY.DllBase.offset, root),
+                            address_width,
+                        )?,
+                        size: vmi
+                            .read_u32((entry + LDR_DATA_TABLE_ENTRY.SizeOfImage.offset, root))?
+                            as u64,
+                        name: self.read_unicode_string(
+                            vmi,
+                            (entry + LDR_DATA_TABLE_ENTRY.BaseDllName.offset, root),
+                        )?,
+                        full_name: self.read_unicode_string(
+                            vmi,
+                            (entry + LDR_DATA_TABLE_ENTRY.FullDllName.offset, root),
+                        )?,
+                        wow64: false,
+                    })
+                }
+                WindowsWow64Kind::X86 => Ok(WindowsUserModule {
+                    base_address: vmi
+                        .read_va32((entry + LDR_DATA_TABLE_ENTRY32_DllBase_offset, root))?,
+                    size: vmi.read_u32((entry + LDR_DATA_TABLE_ENTRY32_SizeOfImage_offset, root))?
+                        as u64,
+                    name: self.read_unicode_string32(
+                        vmi,
+                        (entry + LDR_DATA_TABLE_ENTRY32_BaseDllName_offset, root),
+                    )?,
+                    full_name: self.read_unicode_string32(
+                        vmi,
+                        (entry + LDR_DATA_TABLE_ENTRY32_FullDllName_offset, root),
+                    )?,
+                    wow64: true,
+                }),
+            }
+        };
+
+        let mut entry = vmi.read_va((list_head, root), address_width)?;
+
+        for _ in 0..MAX_MODULES {
+            if entry == list_head || entry.is_null() {
+                break;
+            }
+
+            match read_module(entry) {
+                Ok(module) => result.push(module),
+                Err(err) => tracing::debug!(%entry, %err, "failed to read loader entry"),
+            }
+
+            entry = vmi.read_va((entry, root), address_width)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the current working directory of a process.
+    ///
+    /// This method retrieves the full path of the current working directory
+    /// for the specified process.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
+    /// PUNICODE_STRING CurrentDirectory = ProcessParameters->CurrentDirectory;
+    /// return CurrentDirectory;
+    /// ```
+    ///
+    /// Returns `None` if the process has no PEB or process parameters (e.g.,
+    /// minimal and pico processes).
+    pub fn process_current_directory(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Option<String>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
+        self.__process_current_directory(vmi, registers, process, root)
+    }
+
+    /// Internal method to get the current directory, handling both 32-bit and
+    /// 64-bit processes.
+    fn __process_current_directory(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Option<String>, VmiError> {
+        let address = match self.__process_rtl_process_parameters(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        match address.kind {
+            WindowsWow64Kind::Native => self
+                .process_current_directory_native(vmi, root, address.va)
+                .map(Some),
+            WindowsWow64Kind::X86 => self
+                .process_current_directory_32bit(vmi, root, address.va)
+                .map(Some),
+        }
+    }
+
+    /// Retrieves the current directory for a native (non-WoW64) process.
+    fn process_current_directory_native(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        let CURDIR = &self.offsets.common._CURDIR;
+        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+
+        self.read_unicode_string(
+            vmi,
+            (
+                rtl_process_parameters
+                    + RTL_USER_PROCESS_PARAMETERS.CurrentDirectory.offset
+                    + CURDIR.DosPath.offset,
+                root,
+            ),
+        )
+    }
+
+    /// Retrieves the current directory for a 32-bit process running under
+    /// WoW64.
+    fn process_current_directory_32bit(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        const RTL_USER_PROCESS_PARAMETERS32_CurrentDirectory_offset: u64 = 0x24;
+
+        self.read_unicode_string32(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_CurrentDirectory_offset,
+                root,
+            ),
+        )
+    }
+
+    /// Gets the DLL search path for a process.
+    ///
+    /// This method retrieves the list of directories that the system searches
+    /// when loading DLLs for the specified process.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
+    /// PUNICODE_STRING DllPath = ProcessParameters->DllPath;
+    /// return DllPath;
+    /// ```
+    ///
+    /// Returns `None` if the process has no PEB or process parameters.
+    pub fn process_dll_path(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Option<String>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
+        self.__process_dll_path(vmi, registers, process, root)
+    }
+
+    /// Internal method to get the DLL path, handling both 32-bit and 64-bit
+    /// processes.
+    fn __process_dll_path(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Option<String>, VmiError> {
+        let address = match self.__process_rtl_process_parameters(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        match address.kind {
+            WindowsWow64Kind::Native => self
+                .process_dll_path_native(vmi, root, address.va)
+                .map(Some),
+            WindowsWow64Kind::X86 => self.process_dll_path_32bit(vmi, root, address.va).map(Some),
+        }
+    }
+
+    /// Retrieves the DLL search path for a native (non-WoW64) process.
+    fn process_dll_path_native(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+
+        self.read_unicode_string(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.DllPath.offset,
+                root,
+            ),
+        )
+    }
+
+    /// Retrieves the DLL search path for a 32-bit process running under WoW64.
+    fn process_dll_path_32bit(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        const RTL_USER_PROCESS_PARAMETERS32_DllPath_offset: u64 = 0x30;
+
+        self.read_unicode_string32(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_DllPath_offset,
+                root,
+            ),
+        )
+    }
+
+    /// Gets the full path of the executable image for a process.
+    ///
+    /// This method retrieves the full file system path of the main executable
+    /// that was used to create the specified process.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
+    /// PUNICODE_STRING ImagePathName = ProcessParameters->ImagePathName;
+    /// return ImagePathName;
+    /// ```
+    ///
+    /// Returns `None` if the process has no PEB or process parameters.
+    pub fn process_image_path_name(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Option<String>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
+        self.__process_image_path_name(vmi, registers, process, root)
+    }
+
+    /// Internal method to get the image path name, handling both 32-bit and
+    /// 64-bit processes.
+    fn __process_image_path_name(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Option<String>, VmiError> {
+        let address = match self.__process_rtl_process_parameters(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        match address.kind {
+            WindowsWow64Kind::Native => self
+                .process_image_path_name_native(vmi, root, address.va)
+                .map(Some),
+            WindowsWow64Kind::X86 => self
+                .process_image_path_name_32bit(vmi, root, address.va)
+                .map(Some),
+        }
+    }
+
+    /// Retrieves the image path name for a native (non-WoW64) process.
+    fn process_image_path_name_native(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+
+        self.read_unicode_string(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.ImagePathName.offset,
+                root,
+            ),
+        )
+    }
+
+    /// Retrieves the image path name for a 32-bit process running under WoW64.
+    fn process_image_path_name_32bit(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        const RTL_USER_PROCESS_PARAMETERS32_ImagePathName_offset: u64 = 0x38;
+
+        self.read_unicode_string32(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_ImagePathName_offset,
+                root,
+            ),
+        )
+    }
+
+    /// Gets the command line used to launch a process.
+    ///
+    /// This method retrieves the full command line string, including the
+    /// executable path and any arguments, used to start the specified process.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
+    /// PUNICODE_STRING CommandLine = ProcessParameters->CommandLine;
+    /// return CommandLine;
+    /// ```
+    ///
+    /// Returns `None` if the process has no PEB or process parameters.
+    pub fn process_command_line(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Option<String>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
+        self.__process_command_line(vmi, registers, process, root)
+    }
+
+    /// Internal method to get the command line, handling both 32-bit and 64-bit
+    /// processes.
+    fn __process_command_line(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        root: Pa,
+    ) -> Result<Option<String>, VmiError> {
+        let address = match self.__process_rtl_process_parameters(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+
+        match address.kind {
+            WindowsWow64Kind::Native => self
+                .process_command_line_native(vmi, root, address.va)
+                .map(Some),
+            WindowsWow64Kind::X86 => self
+                .process_command_line_32bit(vmi, root, address.va)
+                .map(Some),
+        }
+    }
+
+    /// Retrieves the command line for a native (non-WoW64) process.
+    fn process_command_line_native(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+
+        self.read_unicode_string(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.CommandLine.offset,
+                root,
+            ),
+        )
+    }
+
+    /// Retrieves the command line for a 32-bit process running under WoW64.
+    fn process_command_line_32bit(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<String, VmiError> {
+        const RTL_USER_PROCESS_PARAMETERS32_CommandLine_offset: u64 = 0x40;
+
+        self.read_unicode_string32(
+            vmi,
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_CommandLine_offset,
+                root,
+            ),
+        )
+    }
+
+    /// Gets the environment variables of a process.
     ///
-    /// # Warning
+    /// Returns the `KEY=VALUE` pairs in the order in which they appear in
+    /// the environment block. Entries without a `=` separator are returned
+    /// with an empty value. Hidden per-drive variables (e.g., `=C:=C:\`)
+    /// keep their leading `=` in the key.
     ///
-    /// This function can potentially cause race conditions if the virtual machine
-    /// is not paused during its execution. It is strongly recommended to pause
-    /// the virtual machine before calling this function and resume it afterwards.
+    /// # Implementation Details
     ///
-    /// Failure to pause the VM may result in inconsistent state or potential
-    /// crashes if the page is concurrently modified by the guest OS.
+    /// The environment block is a sequence of NUL-terminated wide strings,
+    /// terminated by an additional NUL character. When the
+    /// `EnvironmentSize` field is available (Windows Vista+), it limits
+    /// the amount of data read. Otherwise, the block is read page by page
+    /// until the terminator is found.
     ///
-    /// # Examples
+    /// # Equivalent C pseudo-code
     ///
-    /// ```no_run
-    /// # use vmi_arch_amd64::{Amd64, Registers};
-    /// # use vmi_core::{Architecture, Gfn, VmiCore, VmiDriver, VmiError, VmiOs};
-    /// # use vmi_os_windows::WindowsOs;
-    /// #
-    /// # fn example<Driver>(
-    /// #     vmi: &VmiCore<Driver>,
-    /// #     os: &WindowsOs<Driver>,
-    /// #     registers: &Registers,
-    /// #     pfn: Gfn,
-    /// # ) -> Result<(), VmiError>
-    /// # where
-    /// #     Driver: VmiDriver<Architecture = Amd64>,
-    /// # {
-    /// let _pause_guard = vmi.pause_guard()?;
-    /// os.unlock_pfn(vmi, registers, pfn)?;
-    /// // The VM will automatically resume when `_guard` goes out of scope
-    /// # Ok(())
-    /// # }
+    /// ```c
+    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
+    /// PWSTR Environment = ProcessParameters->Environment;
+    /// SIZE_T EnvironmentSize = ProcessParameters->EnvironmentSize;
+    /// return Environment;
     /// ```
-    pub fn unlock_pfn(
+    ///
+    /// Returns `None` if the process has no PEB or process parameters.
+    pub fn process_environment(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        pfn: Gfn,
-    ) -> Result<Option<u16>, VmiError> {
-        self.modify_pfn_reference_count(vmi, registers, pfn, -1)
-    }
+        process: ProcessObject,
+    ) -> Result<Option<Vec<(String, String)>>, VmiError> {
+        let root = self.process_translation_root(vmi, registers, process)?;
 
-    // endregion: Memory
+        let address = match self.__process_rtl_process_parameters(vmi, registers, process, root)? {
+            Some(address) => address,
+            None => return Ok(None),
+        };
 
-    // region: Misc
+        let (environment, environment_size) = match address.kind {
+            WindowsWow64Kind::Native => {
+                self.process_environment_native(vmi, registers, root, address.va)?
+            }
+            WindowsWow64Kind::X86 => self.process_environment_32bit(vmi, root, address.va)?,
+        };
 
-    /// Retrieves the virtual address of the current Kernel Processor Control
-    /// Region (KPCR).
-    ///
-    /// The KPCR is a per-processor data structure in Windows that contains
-    /// critical information about the current processor state. This method
-    /// returns the virtual address of the KPCR for the current processor.
-    pub fn current_kpcr(
+        if environment.is_null() {
+            return Ok(Some(Vec::new()));
+        }
+
+        let block = self.read_environment_block(vmi, (environment, root), environment_size)?;
+
+        let result = block
+            .split(|&c| c == 0)
+            .take_while(|entry| !entry.is_empty())
+            .map(|entry| {
+                let entry = String::from_utf16_lossy(entry);
+
+                // Skip the first character, so that hidden variables
+                // like `=C:=C:\` are split correctly.
+                match entry.char_indices().skip(1).find(|&(_, c)| c == '=') {
+                    Some((index, _)) => {
+                        (entry[..index].to_string(), entry[index + 1..].to_string())
+                    }
+                    None => (entry, String::new()),
+                }
+            })
+            .collect();
+
+        Ok(Some(result))
+    }
+
+    /// Retrieves the environment block address and size for a native
+    /// (non-WoW64) process.
+    fn process_environment_native(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-    ) -> Va {
-        Driver::Architecture::current_kpcr(self, vmi, registers)
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<(Va, Option<u64>), VmiError> {
+        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+
+        let environment = vmi.read_va(
+            (
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.Environment.offset,
+                root,
+            ),
+            registers.address_width(),
+        )?;
+
+        let environment_size = match &RTL_USER_PROCESS_PARAMETERS.EnvironmentSize {
+            Some(EnvironmentSize) => Some(
+                vmi.read_va(
+                    (rtl_process_parameters + EnvironmentSize.offset, root),
+                    registers.address_width(),
+                )?
+                .0,
+            ),
+            None => None,
+        };
+
+        Ok((environment, environment_size))
     }
 
-    /// Extracts information from an exception record at the specified address.
+    /// Retrieves the environment block address and size for a 32-bit
+    /// process running under WoW64.
+    fn process_environment_32bit(
+        &self,
+        vmi: &VmiCore<Driver>,
+        root: Pa,
+        rtl_process_parameters: Va,
+    ) -> Result<(Va, Option<u64>), VmiError> {
+        const RTL_USER_PROCESS_PARAMETERS32_Environment_offset: u64 = 0x48;
+        const RTL_USER_PROCESS_PARAMETERS32_EnvironmentSize_offset: u64 = 0x290;
+
+        let environment = vmi.read_u32((
+            rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_Environment_offset,
+            root,
+        ))?;
+
+        // The `EnvironmentSize` field is present only since Windows Vista,
+        // which matches the availability of the native field.
+        let environment_size = match &self
+            .offsets
+            .common
+            ._RTL_USER_PROCESS_PARAMETERS
+            .EnvironmentSize
+        {
+            Some(_) => Some(vmi.read_u32((
+                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_EnvironmentSize_offset,
+                root,
+            ))? as u64),
+            None => None,
+        };
+
+        Ok((Va(environment as u64), environment_size))
+    }
+
+    /// Reads the environment block as a sequence of UTF-16 characters.
     ///
-    /// This method reads and parses an `EXCEPTION_RECORD` structure from
-    /// memory, providing detailed information about an exception that has
-    /// occurred in the system. The returned [`WindowsExceptionRecord`]
-    /// contains data such as the exception code, flags, and related memory
-    /// addresses.
-    pub fn exception_record(
+    /// The result ends at the double NUL terminator, or at the end of the
+    /// block if the terminator is not found within `size` bytes.
+    fn read_environment_block(
         &self,
         vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        address: Va,
-    ) -> Result<WindowsExceptionRecord, VmiError> {
-        #[repr(C)]
-        #[derive(Debug, Copy, Clone, FromBytes, IntoBytes)]
-        #[allow(non_camel_case_types, non_snake_case)]
-        struct _EXCEPTION_RECORD {
-            ExceptionCode: u32,
-            ExceptionFlags: u32,
-            ExceptionRecord: u64,
-            ExceptionAddress: u64,
-            NumberParameters: u64,
-            ExceptionInformation: [u64; 15],
+        ctx: impl Into<AccessContext>,
+        size: Option<u64>,
+    ) -> Result<Vec<u16>, VmiError> {
+        const MAX_ENVIRONMENT_SIZE: u64 = 1024 * 1024;
+
+        let is_terminated = |block: &[u16]| block.windows(2).any(|window| window == [0, 0]);
+        let mut ctx = ctx.into();
+        let mut block = Vec::new();
+
+        match size {
+            Some(size @ 1..) => {
+                let mut buffer = vec![0u8; size.min(MAX_ENVIRONMENT_SIZE) as usize];
+                vmi.read(ctx, &mut buffer)?;
+                block.extend(
+                    buffer
+                        .chunks_exact(2)
+                        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]])),
+                );
+            }
+            _ => {
+                let mut buffer = vec![0u8; ctx.page_remaining(Amd64::PAGE_SIZE) as usize];
+
+                // The terminator may straddle a page boundary, so the whole
+                // block read so far is checked after each page.
+                loop {
+                    vmi.read(ctx, &mut buffer)?;
+                    block.extend(
+                        buffer
+                            .chunks_exact(2)
+                            .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]])),
+                    );
+
+                    if is_terminated(&block) || block.len() as u64 * 2 >= MAX_ENVIRONMENT_SIZE {
+                        break;
+                    }
+
+                    ctx += buffer.len() as u64;
+                    buffer.resize(Amd64::PAGE_SIZE as usize, 0);
+                }
+            }
         }
 
-        let record = vmi.read_struct::<_EXCEPTION_RECORD>(registers.address_context(address))?;
+        let end = block
+            .windows(2)
+            .position(|window| window == [0, 0])
+            .map(|position| position + 1)
+            .unwrap_or(block.len());
 
-        Ok(WindowsExceptionRecord {
-            code: record.ExceptionCode,
-            flags: record.ExceptionFlags,
-            record: record.ExceptionRecord.into(),
-            address: record.ExceptionAddress.into(),
-            information: record.ExceptionInformation
-                [..u64::min(record.NumberParameters, 15) as usize]
-                .to_vec(),
-        })
+        block.truncate(end);
+        Ok(block)
     }
 
-    /// Retrieves the last status value for the current thread.
-    ///
-    /// In Windows, the last status value is typically used to store error codes
-    /// or success indicators from system calls. This method reads this value
-    /// from the Thread Environment Block (TEB) of the current thread, providing
-    /// insight into the outcome of recent operations performed by the thread.
-    ///
-    /// Returns `None` if the TEB is not available.
+    // endregion: PEB
+
+    // region: Process
+
+    /// Extracts the `EPROCESS` structure from a `KTHREAD` structure.
     ///
-    /// # Notes
+    /// # Equivalent C pseudo-code
     ///
-    /// `LastStatusValue` is a `NTSTATUS` value, whereas `LastError` is a Win32
-    /// error code. The two values are related but not identical. You can obtain
-    /// the Win32 error code by calling
-    /// [`VmiOs::last_error`](crate::VmiOs::last_error).
+    /// ```c
+    /// return Thread->Process;
+    /// ```
+    pub fn process_from_thread(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<ProcessObject, VmiError> {
+        let KTHREAD = &self.offsets.common._KTHREAD;
+
+        let process = vmi.read_va(
+            registers.address_context(thread.0 + KTHREAD.Process.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(ProcessObject(process))
+    }
+
+    /// Extracts the `EPROCESS` structure from a `KAPC_STATE` structure.
     ///
-    /// # Implementation Details
+    /// # Equivalent C pseudo-code
     ///
     /// ```c
-    /// return NtCurrentTeb()->LastStatusValue;
+    /// return Thread->ApcState->Process;
     /// ```
-    pub fn last_status(
+    pub fn process_from_thread_apc_state(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-    ) -> Result<Option<u32>, VmiError> {
+        thread: ThreadObject,
+    ) -> Result<ProcessObject, VmiError> {
         let KTHREAD = &self.offsets.common._KTHREAD;
-        let TEB = &self.offsets.common._TEB;
+        let KAPC_STATE = &self.offsets.common._KAPC_STATE;
 
-        let current_thread = self.current_thread(vmi, registers)?;
-        let teb = vmi.read_va(
-            registers.address_context(current_thread.0 + KTHREAD.Teb.offset),
+        let process = vmi.read_va(
+            registers
+                .address_context(thread.0 + KTHREAD.ApcState.offset + KAPC_STATE.Process.offset),
             registers.address_width(),
         )?;
 
-        if teb.is_null() {
-            return Ok(None);
-        }
+        Ok(ProcessObject(process))
+    }
 
-        let result = vmi.read_u32(registers.address_context(teb + TEB.LastStatusValue.offset))?;
-        Ok(Some(result))
+    /// Checks whether the process is a minimal process.
+    ///
+    /// Minimal processes (e.g., `Registry`, `MemCompression` or the hosts of
+    /// pico processes) are created without a PEB and without the usual
+    /// user-mode address space layout. Reading user-mode structures of such
+    /// processes is not meaningful.
+    ///
+    /// Returns `false` on Windows versions that don't support minimal
+    /// processes.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return Process->Flags3.Minimal;
+    /// ```
+    pub fn process_is_minimal(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<bool, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+
+        let Minimal = match EPROCESS.Minimal {
+            Some(Minimal) => Minimal,
+            None => return Ok(false),
+        };
+
+        let flags = vmi.read_u32(registers.address_context(process.0 + Minimal.offset))?;
+
+        Ok(Minimal.value_from(u64::from(flags)) != 0)
     }
 
-    // endregion: Misc
+    /// Checks whether the process is a pico process (e.g., a WSL1 process).
+    ///
+    /// Pico processes are minimal processes managed by a pico provider
+    /// (`lxcore.sys`), which stores its per-process context in the
+    /// `PicoContext` field.
+    ///
+    /// Returns `false` on Windows versions that don't support pico
+    /// processes.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// return Process->PicoContext != NULL;
+    /// ```
+    pub fn process_is_pico(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<bool, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
 
-    // region: Object
+        let PicoContext = match EPROCESS.PicoContext {
+            Some(PicoContext) => PicoContext,
+            None => return Ok(false),
+        };
 
-    /// Retrieves the object header cookie used for obfuscating object types.
-    /// Returns `None` if the cookie is not present in the kernel image.
+        let pico_context = vmi.read_va(
+            registers.address_context(process.0 + PicoContext.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(!pico_context.is_null())
+    }
+
+    /// Retrieves the working set counters of a process.
     ///
-    /// # Notes
+    /// # Equivalent C pseudo-code
     ///
-    /// Windows 10 introduced a security feature that obfuscates the type
-    /// of kernel objects by XORing the `TypeIndex` field in the object header
-    /// with a random cookie value. This method fetches that cookie, which is
-    /// essential for correctly interpreting object headers in memory.
+    /// ```c
+    /// return (WindowsMemoryCounters) {
+    ///     .page_fault_count = Process->Vm.PageFaultCount,
+    ///     .working_set_size = Process->Vm.WorkingSetSize,
+    ///     .peak_working_set_size = Process->Vm.PeakWorkingSetSize,
+    /// };
+    /// ```
     ///
-    /// # Implementation Details
+    /// # Notes
     ///
-    /// The object header cookie is located by reading the `ObHeaderCookie`
-    /// symbol from the kernel image.
-    pub fn object_header_cookie(
+    /// Since Windows 10 1709, the counters are located in the `Instance`
+    /// field of the `_MMSUPPORT_FULL`.
+    pub fn process_memory_counters(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<WindowsMemoryCounters, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let MMSUPPORT = &self.offsets.common._MMSUPPORT;
+
+        let vm = StructReader::new(
+            vmi,
+            registers.address_context(process.0 + EPROCESS.Vm.offset),
+            MMSUPPORT.effective_len(),
+        )?;
+
+        Ok(WindowsMemoryCounters {
+            page_fault_count: vm.read(MMSUPPORT.PageFaultCount)? as u32,
+            working_set_size: vm.read(MMSUPPORT.WorkingSetSize)?,
+            peak_working_set_size: vm.read(MMSUPPORT.PeakWorkingSetSize)?,
+        })
+    }
+
+    /// Constructs an [`OsProcess`] from an `_EPROCESS`.
+    pub fn process_object_to_process(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-    ) -> Result<Option<u8>, VmiError> {
-        if let Some(cookie) = *self.object_header_cookie.borrow() {
-            return Ok(Some(cookie));
-        }
+        process: ProcessObject,
+    ) -> Result<OsProcess, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let KPROCESS = &self.offsets.common._KPROCESS;
 
-        let ObHeaderCookie = match self.symbols.ObHeaderCookie {
-            Some(cookie) => cookie,
-            None => return Ok(None),
-        };
+        let id =
+            vmi.read_u32(registers.address_context(process.0 + EPROCESS.UniqueProcessId.offset))?;
 
-        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
-        let cookie = vmi.read_u8(registers.address_context(kernel_image_base + ObHeaderCookie))?;
-        *self.object_header_cookie.borrow_mut() = Some(cookie);
-        Ok(Some(cookie))
+        let name =
+            vmi.read_string(registers.address_context(process.0 + EPROCESS.ImageFileName.offset))?;
+
+        let translation_root = vmi.read_address(
+            registers.address_context(process.0 + KPROCESS.DirectoryTableBase.offset),
+            registers.address_width(),
+        )?;
+
+        Ok(OsProcess {
+            id: id.into(),
+            object: process,
+            name,
+            translation_root: translation_root.into(),
+        })
     }
 
-    /// Determines the type of a Windows kernel object.
+    /// Retrieves the processes referenced by the CID handle table
+    /// (`PspCidTable`).
     ///
-    /// This method analyzes the object header of a given kernel object
-    /// and returns its type (e.g., Process, Thread, File). It handles the
-    /// obfuscation introduced by the object header cookie, ensuring accurate
-    /// type identification even on systems with this security feature enabled.
-    pub fn object_type(
+    /// Every process and thread is assigned its ID from the CID handle
+    /// table, independently of the `PsActiveProcessHead` list walked by
+    /// [`processes`]. A process that was unlinked from that list (a common
+    /// DKOM technique for hiding processes) is still found here, so
+    /// comparing the two results reveals hidden processes.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the `PspCidTable` symbol is not
+    /// available.
+    ///
+    /// # Implementation Details
+    ///
+    /// Unlike in the handle tables of processes, the entries of the CID
+    /// handle table point to the object bodies rather than to the object
+    /// headers. Free entries, entries that fail to be read, and entries of
+    /// objects other than processes (i.e., threads) are skipped.
+    ///
+    /// # Notes
+    ///
+    /// A rootkit can also remove the process from the CID handle table,
+    /// although it is less common, as it breaks the lookup of the process
+    /// by its ID.
+    ///
+    /// [`processes`]: VmiOs::processes
+    pub fn processes_via_handle_table(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-    ) -> Result<Option<WindowsObjectType>, VmiError> {
-        let ObTypeIndexTable = self.symbols.ObTypeIndexTable;
-        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
-
-        let object_header = object - OBJECT_HEADER.Body.offset;
-        let type_index =
-            vmi.read_u8(registers.address_context(object_header + OBJECT_HEADER.TypeIndex.offset))?;
-
-        let index = match self.object_header_cookie(vmi, registers)? {
-            Some(cookie) => {
-                //
-                // TypeIndex ^ 2nd least significate byte of OBJECT_HEADER address ^
-                // nt!ObHeaderCookie ref: https://medium.com/@ashabdalhalim/a-light-on-windows-10s-object-header-typeindex-value-e8f907e7073a
-                //
-
-                let salt = (u64::from(object_header) >> 8) as u8;
-                type_index ^ salt ^ cookie
-            }
-            None => type_index,
+    ) -> Result<Vec<OsProcess>, VmiError> {
+        let PspCidTable = match self.symbols.PspCidTable {
+            Some(PspCidTable) => self.kernel_image_base(vmi, registers)? + PspCidTable,
+            None => return Err(VmiError::NotSupported),
         };
 
-        let index = index as u64;
+        let HANDLE_TABLE = &self.offsets.common._HANDLE_TABLE;
+        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
 
-        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
-        let object_type = vmi.read_va(
-            registers.address_context(
-                kernel_image_base + ObTypeIndexTable + index * 8, // REVIEW: replace 8 with registers.address_width()?
-            ),
+        let handle_table = vmi.read_va(
+            registers.address_context(PspCidTable),
             registers.address_width(),
         )?;
 
-        if let Some(typ) = self.object_type_cache.borrow().get(&object_type) {
-            return Ok(Some(*typ));
-        }
+        let table_code = vmi.read_address(
+            registers.address_context(handle_table + HANDLE_TABLE.TableCode.offset),
+            registers.address_width(),
+        )?;
 
-        let object_name = self.read_unicode_string(
+        let mut result = Vec::new();
+        self.enumerate_handle_table(
             vmi,
-            registers.address_context(object_type + self.offsets.common._OBJECT_TYPE.Name.offset),
-        )?;
+            registers,
+            &WindowsHandleTable { table_code },
+            |_handle, entry| {
+                // Free entries have the object pointer cleared.
+                match vmi.read_u64(registers.address_context(entry)) {
+                    Ok(0) | Err(_) => return true,
+                    Ok(_) => {}
+                }
 
-        let typ = match object_name.as_str() {
-            "ALPC Port" => WindowsObjectType::AlpcPort,
-            "DebugObject" => WindowsObjectType::DebugObject,
-            "Device" => WindowsObjectType::Device,
-            "Directory" => WindowsObjectType::Directory,
-            "Driver" => WindowsObjectType::Driver,
-            "Event" => WindowsObjectType::Event,
-            "File" => WindowsObjectType::File,
-            "Job" => WindowsObjectType::Job,
-            "Key" => WindowsObjectType::Key,
-            "Mutant" => WindowsObjectType::Mutant,
-            "Port" => WindowsObjectType::Port,
-            "Process" => WindowsObjectType::Process,
-            "Section" => WindowsObjectType::Section,
-            "SymbolicLink" => WindowsObjectType::SymbolicLink,
-            "Thread" => WindowsObjectType::Thread,
-            "Timer" => WindowsObjectType::Timer,
-            "Token" => WindowsObjectType::Token,
-            "Type" => WindowsObjectType::Type,
-            _ => return Ok(None),
-        };
+                let object = match self.parse_handle_table_entry(vmi, registers, entry) {
+                    Ok(Some(entry)) => entry.object - OBJECT_HEADER.Body.offset,
+                    _ => return true,
+                };
 
-        self.object_type_cache.borrow_mut().insert(object_type, typ);
+                if !matches!(
+                    self.object_type(vmi, registers, object),
+                    Ok(Some(WindowsObjectType::Process))
+                ) {
+                    return true;
+                }
 
-        Ok(Some(typ))
+                if let Ok(process) = self.process_object_to_process(vmi, registers, object.into()) {
+                    result.push(process);
+                }
+
+                true
+            },
+        )?;
+
+        Ok(result)
     }
 
-    /// Retrieves the name of a named kernel object.
+    /// Retrieves the terminal services sessions.
     ///
-    /// Many Windows kernel objects (like mutexes, events, etc.) can have names.
-    /// This method extracts the name of such an object, if present. It also
-    /// provides information about the object's containing directory in the
-    /// object namespace.
-    pub fn object_name(
+    /// The sessions are sorted by their ID.
+    ///
+    /// # Implementation Details
+    ///
+    /// The sessions are found through the `EPROCESS.Session` pointers of
+    /// the processes in the `PsActiveProcessHead` list. Processes outside
+    /// of any session (e.g., `System`) have the pointer cleared. The
+    /// processes of each session are then enumerated by walking the
+    /// `ProcessList` of the session, linked through
+    /// `EPROCESS.SessionProcessLinks`, in the address space of the process
+    /// the session was found through.
+    ///
+    /// A session whose process list fails to be read is skipped.
+    pub fn sessions(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        object: Va,
-    ) -> Result<Option<WindowsObjectName>, VmiError> {
-        let ObpInfoMaskToOffset = self.symbols.ObpInfoMaskToOffset;
-        let OBJECT_HEADER = &self.offsets.common._OBJECT_HEADER;
-        let OBJECT_HEADER_NAME_INFO = &self.offsets.common._OBJECT_HEADER_NAME_INFO;
+    ) -> Result<Vec<WindowsSession>, VmiError> {
+        let EPROCESS = &self.offsets.common._EPROCESS;
+        let MM_SESSION_SPACE = &self.offsets.common._MM_SESSION_SPACE;
 
-        let object_header = object - OBJECT_HEADER.Body.offset;
-        let info_mask =
-            vmi.read_u8(registers.address_context(object_header + OBJECT_HEADER.InfoMask.offset))?;
+        let mut sessions = BTreeMap::<Va, WindowsSession>::new();
 
-        bitflags::bitflags! {
-            struct InfoFlags: u8 {
-                const CREATOR_INFO = 0x01;
-                const NAME_INFO = 0x02;
-                const HANDLE_INFO = 0x04;
-                const QUOTA_INFO = 0x08;
-                const PROCESS_INFO = 0x10;
-            }
-        }
+        for process in self.processes(vmi, registers)? {
+            let session = vmi.read_va(
+                registers.address_context(process.object.0 + EPROCESS.Session.offset),
+                registers.address_width(),
+            )?;
 
-        let info_flags = InfoFlags::from_bits_truncate(info_mask);
-        if !info_flags.contains(InfoFlags::NAME_INFO) {
-            return Ok(None);
-        }
+            if session.is_null() || sessions.contains_key(&session) {
+                continue;
+            }
 
-        // Offset = ObpInfoMaskToOffset[OBJECT_HEADER->InfoMask & (DesiredHeaderBit | (DesiredHeaderBit-1))]
+            //
+            // Session space is mapped only in the processes of the session.
+            //
 
-        let mask = info_mask & (InfoFlags::NAME_INFO.bits() | (InfoFlags::NAME_INFO.bits() - 1));
-        let mask = mask as u64;
+            let root = self.process_translation_root(vmi, registers, process.object)?;
+            let list_head = session + MM_SESSION_SPACE.ProcessList.offset;
 
-        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
-        let offset = vmi
-            .read_u8(registers.address_context(kernel_image_base + ObpInfoMaskToOffset + mask))?
-            as u64;
+            let walk = || -> Result<WindowsSession, VmiError> {
+                let id = vmi.read_u32((session + MM_SESSION_SPACE.SessionId.offset, root))?;
 
-        let object_header_name_info = object_header - offset;
+                let mut processes = Vec::new();
+                self.enumerate_list_in(vmi, root, list_head, registers.address_width(), |entry| {
+                    processes.push(ProcessObject(entry - EPROCESS.SessionProcessLinks.offset));
+                    true
+                })?;
 
-        let directory = vmi.read_va(
-            registers.address_context(
-                object_header_name_info + OBJECT_HEADER_NAME_INFO.Directory.offset,
-            ),
-            registers.address_width(),
-        )?;
+                Ok(WindowsSession {
+                    address: session,
+                    id,
+                    processes,
+                })
+            };
 
-        let name = self.read_unicode_string(
-            vmi,
-            registers
-                .address_context(object_header_name_info + OBJECT_HEADER_NAME_INFO.Name.offset),
-        )?;
+            match walk() {
+                Ok(result) => {
+                    sessions.insert(session, result);
+                }
+                Err(err) => {
+                    tracing::debug!(%session, pid = %process.id, %err, "failed to read session");
+                }
+            }
+        }
 
-        Ok(Some(WindowsObjectName { directory, name }))
+        let mut result = sessions.into_values().collect::<Vec<_>>();
+        result.sort_by_key(|session| session.id);
+        Ok(result)
     }
 
-    /// Converts an `OBJECT_ATTRIBUTES` structure to an object name string.
-    ///
-    /// `OBJECT_ATTRIBUTES` is a structure used in many Windows system calls to
-    /// specify an object. This method interprets that structure and extracts
-    /// a meaningful name or path for the object. It handles both absolute and
-    /// relative object names, considering the root directory if specified.
+    /// Walks a `LIST_ENTRY` list in the address space given by `root`.
     ///
-    /// Returns `None` if the `_OBJECT_ATTRIBUTES::ObjectName` field is `NULL`.
-    pub fn object_attributes_to_object_name(
+    /// Calls `callback` for each entry (excluding the list head) until it
+    /// returns `false`. Fails if the list has more than `MAX_ENTRIES`
+    /// entries, which happens when the list is corrupted or circular
+    /// without passing through the list head.
+    fn enumerate_list_in(
         &self,
         vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        object_attributes: Va,
-    ) -> Result<Option<String>, VmiError> {
-        let OBJECT_ATTRIBUTES = &self.offsets.common._OBJECT_ATTRIBUTES;
-
-        let object_name_address = vmi.read_va(
-            registers.address_context(object_attributes + OBJECT_ATTRIBUTES.ObjectName.offset),
-            registers.address_width(),
-        )?;
+        root: Pa,
+        list_head: Va,
+        address_width: usize,
+        mut callback: impl FnMut(Va) -> bool,
+    ) -> Result<(), VmiError> {
+        const MAX_ENTRIES: usize = 0x100000;
 
-        if object_name_address.is_null() {
-            return Ok(None);
-        }
+        let mut entry = vmi.read_va((list_head, root), address_width)?;
+        let mut count = 0;
 
-        let object_name =
-            self.read_unicode_string(vmi, registers.address_context(object_name_address))?;
+        while entry != list_head {
+            count += 1;
+            if count > MAX_ENTRIES {
+                return Err(VmiError::Other("Too many list entries"));
+            }
 
-        let root_directory = vmi.read_va(
-            registers.address_context(object_attributes + OBJECT_ATTRIBUTES.RootDirectory.offset),
-            registers.address_width(),
-        )?;
+            if !callback(entry) {
+                break;
+            }
 
-        if root_directory.is_null() {
-            return Ok(Some(object_name));
+            entry = vmi.read_va((entry, root), address_width)?;
         }
 
-        let object =
-            match self.handle_to_object(vmi, registers, process, u64::from(root_directory))? {
-                Some(object) => object,
-                None => return Ok(Some(object_name)),
-            };
+        Ok(())
+    }
 
-        let root_name = match object {
-            WindowsObject::File(file) => Some(file.filename),
-            WindowsObject::Section(section) => match section.region.kind {
-                OsRegionKind::Mapped(mapped) => mapped.path?,
-                _ => None,
-            },
-        };
+    /// Retrieves the user-mode images loaded across all processes.
+    ///
+    /// Each image is reported once, together with the processes it is
+    /// loaded in and its base address in each of them. Images are matched
+    /// by their full path, ignoring case, so an image relocated to
+    /// different base addresses (e.g., due to ASLR) is still reported
+    /// once. The images are sorted by path.
+    ///
+    /// Processes whose modules can't be enumerated (e.g., because the PEB
+    /// is paged out) are skipped.
+    ///
+    /// See [`process_user_modules`] for the modules of a single process.
+    ///
+    /// [`process_user_modules`]: Self::process_user_modules
+    pub fn all_loaded_images(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<WindowsLoadedImage>, VmiError> {
+        let mut images = BTreeMap::<String, WindowsLoadedImage>::new();
+
+        for process in self.processes(vmi, registers)? {
+            let modules = match self.process_user_modules(vmi, registers, process.object) {
+                Ok(modules) => modules,
+                Err(err) => {
+                    tracing::debug!(pid = %process.id, %err, "failed to read user modules");
+                    continue;
+                }
+            };
 
-        match root_name {
-            Some(root_name) => Ok(Some(format!("{root_name}\\{object_name}"))),
-            None => Ok(Some(object_name)),
+            for module in modules {
+                let image = images
+                    .entry(module.full_name.to_lowercase())
+                    .or_insert_with(|| WindowsLoadedImage {
+                        path: module.full_name.clone(),
+                        instances: Vec::new(),
+                    });
+
+                image.instances.push(WindowsLoadedImageInstance {
+                    process: process.object,
+                    process_id: process.id,
+                    base_address: module.base_address,
+                    size: module.size,
+                });
+            }
         }
-    }
 
-    // endregion: Object
-
-    // region: PEB
+        Ok(images.into_values().collect())
+    }
 
-    /// Retrieves the Process Environment Block (PEB) for a given process.
+    /// Retrieves the threads of a process.
     ///
-    /// The PEB contains crucial information about a process, including its
-    /// loaded modules, environment variables, and command line arguments.
-    pub fn process_peb(
+    /// # Implementation Details
+    ///
+    /// The threads are enumerated by walking the `KPROCESS.ThreadListHead`
+    /// list, linked through `KTHREAD.ThreadListEntry`.
+    pub fn process_threads(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
         process: ProcessObject,
-    ) -> Result<WindowsPeb, VmiError> {
-        let root = self.process_translation_root(vmi, registers, process)?;
+    ) -> Result<Vec<ThreadObject>, VmiError> {
+        let KPROCESS = &self.offsets.common._KPROCESS;
+        let KTHREAD = &self.offsets.common._KTHREAD;
 
-        let address = self.__process_peb_address(vmi, registers, process, root)?;
-        let current_directory = self.__process_current_directory(vmi, registers, process, root)?;
-        let dll_path = self.__process_dll_path(vmi, registers, process, root)?;
-        let image_path_name = self.__process_image_path_name(vmi, registers, process, root)?;
-        let command_line = self.__process_command_line(vmi, registers, process, root)?;
+        let mut result = Vec::new();
+        self.enumerate_list(
+            vmi,
+            registers,
+            process.0 + KPROCESS.ThreadListHead.offset,
+            |entry| {
+                result.push(ThreadObject(entry - KTHREAD.ThreadListEntry.offset));
+                true
+            },
+        )?;
 
-        Ok(WindowsPeb {
-            address: address.va,
-            current_directory,
-            dll_path,
-            image_path_name,
-            command_line,
-        })
+        Ok(result)
     }
 
-    /// Internal method to get the address of the PEB.
+    /// Resolves where the data of a virtual address in a process is located,
+    /// even if the page is not present in the page tables.
     ///
-    /// This method handles both native (non-WoW64) processes and WoW64
-    /// processes, returning the appropriate PEB address based on the
-    /// process architecture.
-    fn __process_peb_address(
+    /// Returns `None` if the address is not backed by anything (e.g., it is
+    /// not committed).
+    ///
+    /// # Implementation Details
+    ///
+    /// The non-present PTE is decoded the same way the page fault handler
+    /// does. Transition PTEs resolve to the physical page, software PTEs to
+    /// a paging file location or to a demand-zero page, and prototype PTEs
+    /// (found either in the PTE or through the VAD) are followed to the
+    /// shared physical page or to the subsection of the mapped file.
+    ///
+    /// Software PTEs are deobfuscated with the [`invalid_pte_mask`] before
+    /// they are decoded.
+    ///
+    /// [`invalid_pte_mask`]: Self::invalid_pte_mask
+    pub fn process_resolve_paged_va(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
         process: ProcessObject,
-        root: Pa,
-    ) -> Result<WindowsWow64Va, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
-
-        let wow64 = vmi.read_va(
-            (process.0 + EPROCESS.WoW64Process.offset, root),
-            registers.address_width(),
-        )?;
-
-        if wow64.is_null() {
-            let peb64 = vmi.read_va(
-                (process.0 + EPROCESS.Peb.offset, root),
-                registers.address_width(),
-            )?;
-
-            Ok(WindowsWow64Va::native(peb64))
-        }
-        else {
-            let peb32 = match &self.offsets.ext {
-                Some(OffsetsExt::V1(_)) => wow64,
-                Some(OffsetsExt::V2(v2)) => vmi.read_va(
-                    (wow64 + v2._EWOW64PROCESS.Peb.offset, root),
-                    registers.address_width(),
-                )?,
-                None => panic!("OffsetsExt not set"),
-            };
-
-            Ok(WindowsWow64Va::x86(peb32))
-        }
+        address: Va,
+    ) -> Result<Option<WindowsResolvedPage>, VmiError> {
+        Driver::Architecture::process_resolve_paged_va(self, vmi, registers, process, address)
     }
 
-    /// Internal method to retrieve the address of
-    /// `RTL_USER_PROCESS_PARAMETERS`.
+    /// Returns the number of the virtual store paging file.
     ///
-    /// This structure contains various process-specific parameters, including
-    /// the command line, current directory, and DLL search path.
-    fn __process_rtl_process_parameters(
+    /// The virtual store paging file backs the store of the memory manager.
+    /// Software PTEs whose `PageFileLow` field matches this number describe
+    /// pages compressed in the store, rather than pages in a paging file.
+    ///
+    /// Returns `None` if there is no virtual store paging file, or if the
+    /// profile doesn't describe the structures needed to find it (e.g., on
+    /// Windows 7, which has no store).
+    ///
+    /// # Implementation Details
+    ///
+    /// The paging files of the system partition
+    /// (`MiSystemPartition->Vp.PagingFiles`) are searched for the one with
+    /// the `VirtualStorePagefile` flag set. The result is cached once found.
+    pub fn virtual_store_pagefile(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        root: Pa,
-    ) -> Result<WindowsWow64Va, VmiError> {
-        let address = self.__process_peb_address(vmi, registers, process, root)?;
+    ) -> Result<Option<u8>, VmiError> {
+        if let Some(number) = *self.virtual_store_pagefile.borrow() {
+            return Ok(Some(number));
+        }
 
-        match address.kind {
-            WindowsWow64Kind::Native => {
-                let PEB = &self.offsets.common._PEB;
+        let partition = match &self.offsets.partition {
+            Some(partition) => partition,
+            None => return Ok(None),
+        };
 
-                let va = vmi.read_va(
-                    (address.va + PEB.ProcessParameters.offset, root),
-                    registers.address_width(),
-                )?;
+        let MI_PARTITION = &partition._MI_PARTITION;
+        let MI_VISIBLE_PARTITION = &partition._MI_VISIBLE_PARTITION;
+        let MMPAGING_FILE = &partition._MMPAGING_FILE;
 
-                Ok(WindowsWow64Va::native(va))
+        let (MiSystemPartition, PagingFiles, VirtualStorePagefile) = match (
+            self.symbols.MiSystemPartition,
+            MI_VISIBLE_PARTITION.PagingFiles,
+            MMPAGING_FILE.VirtualStorePagefile,
+        ) {
+            (Some(MiSystemPartition), Some(PagingFiles), Some(VirtualStorePagefile)) => {
+                (MiSystemPartition, PagingFiles, VirtualStorePagefile)
             }
-            WindowsWow64Kind::X86 => {
-                const PEB32_ProcessParameters_offset: u64 = 0x10;
+            _ => return Ok(None),
+        };
 
-                let va = vmi.read_va(
-                    (address.va + PEB32_ProcessParameters_offset, root),
-                    registers.address_width(),
-                )?;
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let paging_files =
+            kernel_image_base + MiSystemPartition + MI_PARTITION.Vp.offset + PagingFiles.offset;
+
+        let address_width = registers.address_width();
+        for index in 0..PagingFiles.size / address_width as u64 {
+            let paging_file = vmi.read_va(
+                registers.address_context(paging_files + index * address_width as u64),
+                address_width,
+            )?;
+
+            if paging_file.is_null() {
+                continue;
+            }
+
+            let flags =
+                vmi.read_u16(registers.address_context(paging_file + VirtualStorePagefile.offset))?;
 
-                Ok(WindowsWow64Va::x86(va))
+            if VirtualStorePagefile.value_from(u64::from(flags)) == 0 {
+                continue;
             }
+
+            let PageFileNumber = MMPAGING_FILE.PageFileNumber;
+            let number =
+                vmi.read_u16(registers.address_context(paging_file + PageFileNumber.offset))?;
+            let number = PageFileNumber.value_from(u64::from(number)) as u8;
+
+            *self.virtual_store_pagefile.borrow_mut() = Some(number);
+            return Ok(Some(number));
         }
+
+        Ok(None)
     }
 
-    /// Gets the current working directory of a process.
-    ///
-    /// This method retrieves the full path of the current working directory
-    /// for the specified process.
+    /// Returns the mask the kernel XORs into software PTEs.
     ///
-    /// # Equivalent C pseudo-code
+    /// Since Windows 10 1803, the kernel obfuscates software PTEs with
+    /// `MiState.Hardware.InvalidPteMask` (as a mitigation of L1 Terminal
+    /// Fault), so the mask must be XORed back before the `PageFileLow` and
+    /// `PageFileHigh` fields are decoded.
     ///
-    /// ```c
-    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
-    /// PUNICODE_STRING CurrentDirectory = ProcessParameters->CurrentDirectory;
-    /// return CurrentDirectory;
-    /// ```
-    pub fn process_current_directory(
+    /// Returns zero if the profile lacks the symbols and structures, as
+    /// older Windows versions don't obfuscate software PTEs.
+    pub fn invalid_pte_mask(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<String, VmiError> {
-        let root = self.process_translation_root(vmi, registers, process)?;
-        self.__process_current_directory(vmi, registers, process, root)
+    ) -> Result<u64, VmiError> {
+        if let Some(mask) = *self.invalid_pte_mask.borrow() {
+            return Ok(mask);
+        }
+
+        let (offsets, MiState) = match (&self.offsets.mi_state, self.symbols.MiState) {
+            (Some(offsets), Some(MiState)) => (offsets, MiState),
+            _ => return Ok(0),
+        };
+
+        let MI_SYSTEM_INFORMATION = &offsets._MI_SYSTEM_INFORMATION;
+        let MI_HARDWARE_STATE = &offsets._MI_HARDWARE_STATE;
+
+        let InvalidPteMask = match MI_HARDWARE_STATE.InvalidPteMask {
+            Some(InvalidPteMask) => InvalidPteMask,
+            None => return Ok(0),
+        };
+
+        let kernel_image_base = self.kernel_image_base(vmi, registers)?;
+        let mask = vmi.read_u64(registers.address_context(
+            kernel_image_base
+                + MiState
+                + MI_SYSTEM_INFORMATION.Hardware.offset
+                + InvalidPteMask.offset,
+        ))?;
+
+        *self.invalid_pte_mask.borrow_mut() = Some(mask);
+        Ok(mask)
     }
 
-    /// Internal method to get the current directory, handling both 32-bit and
-    /// 64-bit processes.
-    fn __process_current_directory(
+    /// Reads a page of a process that has been compressed by the memory
+    /// manager.
+    ///
+    /// Returns the decompressed content of the whole page containing the
+    /// address, or `None` if the page is not in the store.
+    ///
+    /// # Implementation Details
+    ///
+    /// The address is resolved by [`process_resolve_paged_va`]. If the
+    /// software PTE points to the virtual store paging file (see
+    /// [`virtual_store_pagefile`]), the page key is passed to the `locator`,
+    /// and the compressed data it points to is read and decompressed.
+    ///
+    /// # Notes
+    ///
+    /// This only works if `PageFileHigh` is not obfuscated, see
+    /// [`process_resolve_paged_va`].
+    ///
+    /// [`process_resolve_paged_va`]: Self::process_resolve_paged_va
+    /// [`virtual_store_pagefile`]: Self::virtual_store_pagefile
+    pub fn read_compressed_page(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
         process: ProcessObject,
-        root: Pa,
-    ) -> Result<String, VmiError> {
-        let address = self.__process_rtl_process_parameters(vmi, registers, process, root)?;
+        address: Va,
+        locator: &impl WindowsStoreLocator<Driver>,
+    ) -> Result<Option<Vec<u8>>, VmiError> {
+        let key = match self.process_resolve_paged_va(vmi, registers, process, address)? {
+            Some(WindowsResolvedPage::Store { key, .. }) => key,
+            _ => return Ok(None),
+        };
 
-        match address.kind {
-            WindowsWow64Kind::Native => {
-                self.process_current_directory_native(vmi, root, address.va)
+        let page = match locator.locate(vmi, registers, key)? {
+            Some(page) => page,
+            None => {
+                tracing::debug!(%address, key, "page not found in the store");
+                return Ok(None);
             }
-            WindowsWow64Kind::X86 => self.process_current_directory_32bit(vmi, root, address.va),
-        }
-    }
+        };
 
-    /// Retrieves the current directory for a native (non-WoW64) process.
-    fn process_current_directory_native(
-        &self,
-        vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        let CURDIR = &self.offsets.common._CURDIR;
-        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+        let mut result = vec![0u8; Driver::Architecture::PAGE_SIZE as usize];
 
-        self.read_unicode_string(
-            vmi,
-            (
-                rtl_process_parameters
-                    + RTL_USER_PROCESS_PARAMETERS.CurrentDirectory.offset
-                    + CURDIR.DosPath.offset,
-                root,
-            ),
-        )
-    }
+        if page.compressed_size as u64 >= Driver::Architecture::PAGE_SIZE {
+            vmi.read(page.ctx, &mut result)?;
+            return Ok(Some(result));
+        }
 
-    /// Retrieves the current directory for a 32-bit process running under
-    /// WoW64.
-    fn process_current_directory_32bit(
-        &self,
-        vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        const RTL_USER_PROCESS_PARAMETERS32_CurrentDirectory_offset: u64 = 0x24;
+        let mut compressed = vec![0u8; page.compressed_size as usize];
+        vmi.read(page.ctx, &mut compressed)?;
 
-        self.read_unicode_string32(
-            vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_CurrentDirectory_offset,
-                root,
-            ),
-        )
+        let size = page.format.decompress(&compressed, &mut result)?;
+        if size != result.len() {
+            tracing::warn!(%address, key, size, "compressed page is truncated");
+        }
+
+        Ok(Some(result))
     }
 
-    /// Gets the DLL search path for a process.
+    /// Reads memory of a process, including pages that are not present in
+    /// physical memory.
     ///
-    /// This method retrieves the list of directories that the system searches
-    /// when loading DLLs for the specified process.
+    /// Each page is read from the virtual machine first. If the page is not
+    /// present, its location is resolved by [`process_resolve_paged_va`]:
     ///
-    /// # Equivalent C pseudo-code
+    /// - Pages in transition are read from physical memory.
+    /// - Pages in a paging file are read from the copy of the paging file
+    ///   attached with [`VmiCore::set_pagefile`].
+    /// - Demand-zero pages are filled with zeros.
     ///
-    /// ```c
-    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
-    /// PUNICODE_STRING DllPath = ProcessParameters->DllPath;
-    /// return DllPath;
-    /// ```
-    pub fn process_dll_path(
+    /// Otherwise (e.g., for pages backed by a mapped file or compressed in
+    /// the store), the original page fault is returned.
+    ///
+    /// # Notes
+    ///
+    /// The paging file is read as it is on disk. A page that was evicted
+    /// after the copy was taken reads as stale data.
+    ///
+    /// [`process_resolve_paged_va`]: Self::process_resolve_paged_va
+    pub fn read_process_memory_paged(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
         process: ProcessObject,
-    ) -> Result<String, VmiError> {
+        address: Va,
+        buffer: &mut [u8],
+    ) -> Result<(), VmiError> {
         let root = self.process_translation_root(vmi, registers, process)?;
-        self.__process_dll_path(vmi, registers, process, root)
-    }
 
-    /// Internal method to get the DLL path, handling both 32-bit and 64-bit
-    /// processes.
-    fn __process_dll_path(
-        &self,
-        vmi: &VmiCore<Driver>,
-        registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        root: Pa,
-    ) -> Result<String, VmiError> {
-        let address = self.__process_rtl_process_parameters(vmi, registers, process, root)?;
+        let mut position = 0usize;
+        while position < buffer.len() {
+            let va = address + position as u64;
+            let in_page = Driver::Architecture::PAGE_SIZE - Driver::Architecture::va_offset(va);
+            let size = (buffer.len() - position).min(in_page as usize);
+            let chunk = &mut buffer[position..position + size];
+
+            match vmi.read((va, root), chunk) {
+                Ok(()) => {}
+                Err(VmiError::PageFault(pfs)) => {
+                    match self.process_resolve_paged_va(vmi, registers, process, va)? {
+                        Some(WindowsResolvedPage::Physical(pa)) => vmi.read(pa, chunk)?,
+                        Some(WindowsResolvedPage::PageFile { index, offset }) => {
+                            vmi.read_pagefile(index, offset, chunk)?
+                        }
+                        Some(WindowsResolvedPage::DemandZero) => chunk.fill(0),
+                        _ => return Err(VmiError::PageFault(pfs)),
+                    }
+                }
+                Err(err) => return Err(err),
+            }
 
-        match address.kind {
-            WindowsWow64Kind::Native => self.process_dll_path_native(vmi, root, address.va),
-            WindowsWow64Kind::X86 => self.process_dll_path_32bit(vmi, root, address.va),
+            position += size;
         }
+
+        Ok(())
     }
 
-    /// Retrieves the DLL search path for a native (non-WoW64) process.
-    fn process_dll_path_native(
+    /// Retrieves the `MemCompression` process, which holds the regions of
+    /// the store.
+    ///
+    /// Returns `None` if memory compression is disabled.
+    pub fn memory_compression_process(
         &self,
         vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
-
-        self.read_unicode_string(
-            vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.DllPath.offset,
-                root,
-            ),
-        )
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Option<OsProcess>, VmiError> {
+        Ok(self
+            .processes(vmi, registers)?
+            .into_iter()
+            .find(|process| process.name == "MemCompression"))
     }
 
-    /// Retrieves the DLL search path for a 32-bit process running under WoW64.
-    fn process_dll_path_32bit(
-        &self,
-        vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        const RTL_USER_PROCESS_PARAMETERS32_DllPath_offset: u64 = 0x30;
+    // endregion: Process
 
-        self.read_unicode_string32(
-            vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_DllPath_offset,
-                root,
-            ),
-        )
-    }
+    // region: Registry
 
-    /// Gets the full path of the executable image for a process.
-    ///
-    /// This method retrieves the full file system path of the main executable
-    /// that was used to create the specified process.
+    /// Retrieves the registry hives loaded by the configuration manager.
     ///
-    /// # Equivalent C pseudo-code
+    /// Returns [`VmiError::NotSupported`] if the `CmpHiveListHead` symbol
+    /// is not available.
     ///
-    /// ```c
-    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
-    /// PUNICODE_STRING ImagePathName = ProcessParameters->ImagePathName;
-    /// return ImagePathName;
-    /// ```
-    pub fn process_image_path_name(
+    /// # Implementation Details
+    ///
+    /// The hives are enumerated by walking the `CmpHiveListHead` list,
+    /// linked through the `_CMHIVE.HiveList` field. Entries whose `_HHIVE`
+    /// doesn't carry the `HHIVE_SIGNATURE` (`0xbee0bee0`) are skipped.
+    pub fn registry_hives(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<String, VmiError> {
-        let root = self.process_translation_root(vmi, registers, process)?;
-        self.__process_image_path_name(vmi, registers, process, root)
+    ) -> Result<Vec<WindowsRegistryHive>, VmiError> {
+        let CmpHiveListHead = match self.symbols.CmpHiveListHead {
+            Some(CmpHiveListHead) => self.kernel_image_base(vmi, registers)? + CmpHiveListHead,
+            None => return Err(VmiError::NotSupported),
+        };
+
+        let CMHIVE = &self.offsets.common._CMHIVE;
+
+        let mut result = Vec::new();
+        self.enumerate_list(vmi, registers, CmpHiveListHead, |entry| {
+            let hive = entry - CMHIVE.HiveList.offset;
+
+            match self.registry_hive(vmi, registers, hive) {
+                Ok(Some(hive)) => result.push(hive),
+                Ok(None) => tracing::debug!(%hive, "invalid hive signature"),
+                Err(err) => tracing::debug!(%hive, %err, "failed to read hive"),
+            }
+
+            true
+        })?;
+
+        Ok(result)
     }
 
-    /// Internal method to get the image path name, handling both 32-bit and
-    /// 64-bit processes.
-    fn __process_image_path_name(
+    /// Reads a `_CMHIVE`.
+    ///
+    /// Returns `None` if the hive doesn't carry the `HHIVE_SIGNATURE`.
+    fn registry_hive(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        root: Pa,
-    ) -> Result<String, VmiError> {
-        let address = self.__process_rtl_process_parameters(vmi, registers, process, root)?;
+        hive: Va,
+    ) -> Result<Option<WindowsRegistryHive>, VmiError> {
+        const HHIVE_SIGNATURE: u64 = 0xbee0bee0;
 
-        match address.kind {
-            WindowsWow64Kind::Native => self.process_image_path_name_native(vmi, root, address.va),
-            WindowsWow64Kind::X86 => self.process_image_path_name_32bit(vmi, root, address.va),
+        let HHIVE = &self.offsets.common._HHIVE;
+        let CMHIVE = &self.offsets.common._CMHIVE;
+
+        let hhive = StructReader::new(vmi, registers.address_context(hive), HHIVE.effective_len())?;
+        if hhive.read(HHIVE.Signature)? != HHIVE_SIGNATURE {
+            return Ok(None);
         }
-    }
 
-    /// Retrieves the image path name for a native (non-WoW64) process.
-    fn process_image_path_name_native(
-        &self,
-        vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+        let base_block = Va(hhive.read(HHIVE.BaseBlock)?);
 
-        self.read_unicode_string(
+        let file_full_path = self.read_unicode_string(
             vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.ImagePathName.offset,
-                root,
-            ),
-        )
-    }
-
-    /// Retrieves the image path name for a 32-bit process running under WoW64.
-    fn process_image_path_name_32bit(
-        &self,
-        vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        const RTL_USER_PROCESS_PARAMETERS32_ImagePathName_offset: u64 = 0x38;
+            registers.address_context(hive + CMHIVE.FileFullPath.offset),
+        )?;
 
-        self.read_unicode_string32(
+        let file_user_name = self.read_unicode_string(
             vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_ImagePathName_offset,
-                root,
-            ),
-        )
+            registers.address_context(hive + CMHIVE.FileUserName.offset),
+        )?;
+
+        let root_path =
+            match CMHIVE.HiveRootPath {
+                Some(HiveRootPath) => Some(self.read_unicode_string(
+                    vmi,
+                    registers.address_context(hive + HiveRootPath.offset),
+                )?),
+                None => None,
+            };
+
+        Ok(Some(WindowsRegistryHive {
+            hive,
+            base_block,
+            file_full_path,
+            file_user_name,
+            root_path,
+        }))
     }
 
-    /// Gets the command line used to launch a process.
+    /// Reads a value from the registry.
     ///
-    /// This method retrieves the full command line string, including the
-    /// executable path and any arguments, used to start the specified process.
+    /// The `path` is the path of the key followed by the name of the value,
+    /// e.g., `HKLM\SYSTEM\CurrentControlSet\Control\ComputerName\ComputerName\ComputerName`.
+    /// The path may start with `HKLM` (`HKEY_LOCAL_MACHINE`), `HKU`
+    /// (`HKEY_USERS`) or `\REGISTRY`. An empty value name (a trailing
+    /// backslash) refers to the default value of the key. Key and value
+    /// names are case-insensitive.
     ///
-    /// # Equivalent C pseudo-code
+    /// Returns `None` if the key or the value doesn't exist.
     ///
-    /// ```c
-    /// PRTL_USER_PROCESS_PARAMETERS ProcessParameters = NtCurrentPeb()->ProcessParameters;
-    /// PUNICODE_STRING CommandLine = ProcessParameters->CommandLine;
-    /// return CommandLine;
-    /// ```
-    pub fn process_command_line(
+    /// # Implementation Details
+    ///
+    /// The value is read from the cells of the hive in memory, without
+    /// touching the hive file:
+    /// 1. The hive whose `HiveRootPath` is the longest prefix of the path
+    ///    is selected (see [`registry_hives`]).
+    /// 2. Starting at the `RootCell` of its `_HBASE_BLOCK`, each component
+    ///    of the path is looked up in the subkey index cells (`li`, `lf`,
+    ///    `lh` and `ri`) of the current `_CM_KEY_NODE`, both in the stable
+    ///    and the volatile storage.
+    /// 3. The value is looked up in the value list of the final
+    ///    `_CM_KEY_NODE`, and its data is read from the `_CM_KEY_VALUE`,
+    ///    a separate data cell or a `_CM_BIG_DATA` list of segments.
+    ///
+    /// Keys that are symbolic links (e.g., `CurrentControlSet`) are
+    /// followed through their `SymbolicLinkValue`.
+    ///
+    /// Cell indices are translated to virtual addresses through the cell
+    /// map of the hive (`_HHIVE.Storage[].Map`), the same way as
+    /// `HvpGetCellMapped` does.
+    ///
+    /// # Notes
+    ///
+    /// Cells of hives that are not fully mapped (e.g., bins that were
+    /// never accessed since boot) might not be present in memory, in
+    /// which case an error is returned.
+    ///
+    /// [`registry_hives`]: Self::registry_hives
+    pub fn read_registry_value(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<String, VmiError> {
-        let root = self.process_translation_root(vmi, registers, process)?;
-        self.__process_command_line(vmi, registers, process, root)
+        path: &str,
+    ) -> Result<Option<WindowsRegistryValue>, VmiError> {
+        const MAX_SYMBOLIC_LINKS: usize = 8;
+
+        let (mut components, value_name) = match path.rsplit_once('\\') {
+            Some((key, value_name)) => (Self::registry_path_components(key)?, value_name),
+            None => return Ok(None),
+        };
+
+        let hives = self.registry_hives(vmi, registers)?;
+
+        'resolve: for _ in 0..=MAX_SYMBOLIC_LINKS {
+            //
+            // Find the hive mounted closest to the key.
+            //
+
+            let mut best = None;
+            for hive in &hives {
+                let root_path = match &hive.root_path {
+                    Some(root_path) => Self::registry_path_components(root_path)?,
+                    None => continue,
+                };
+
+                if root_path.is_empty()
+                    || root_path.len() > components.len()
+                    || !root_path
+                        .iter()
+                        .zip(&components)
+                        .all(|(a, b)| registry::name_eq(a, b))
+                {
+                    continue;
+                }
+
+                match best {
+                    Some((_, depth)) if depth >= root_path.len() => {}
+                    _ => best = Some((hive, root_path.len())),
+                }
+            }
+
+            let (hive, depth) = match best {
+                Some(best) => best,
+                None => return Ok(None),
+            };
+
+            let root_cell =
+                vmi.read_u32(registers.address_context(hive.base_block + HBASE_BLOCK_ROOT_CELL))?;
+
+            let mut key = self.registry_key_node(vmi, registers, hive.hive, root_cell)?;
+
+            for (index, name) in components[depth..].iter().enumerate() {
+                key = match self.registry_subkey(vmi, registers, hive.hive, &key, name)? {
+                    Some(key) => key,
+                    None => return Ok(None),
+                };
+
+                if !key.is_symbolic_link() {
+                    continue;
+                }
+
+                let target = match self.registry_key_value(
+                    vmi,
+                    registers,
+                    hive,
+                    &key,
+                    "SymbolicLinkValue",
+                )? {
+                    Some(WindowsRegistryValue::Link(target)) => target,
+                    _ => return Ok(None),
+                };
+
+                let mut resolved = Self::registry_path_components(&target)?;
+                resolved.extend_from_slice(&components[depth + index + 1..]);
+                components = resolved;
+                continue 'resolve;
+            }
+
+            return self.registry_key_value(vmi, registers, hive, &key, value_name);
+        }
+
+        tracing::debug!(path, "too many symbolic links");
+        Ok(None)
     }
 
-    /// Internal method to get the command line, handling both 32-bit and 64-bit
-    /// processes.
-    fn __process_command_line(
+    /// Splits a registry path into its components, replacing the
+    /// abbreviated root keys with their `\REGISTRY` equivalents.
+    fn registry_path_components(path: &str) -> Result<Vec<String>, VmiError> {
+        let mut components = path.split('\\').filter(|component| !component.is_empty());
+
+        let mut result = match components.next() {
+            Some(root) if root.eq_ignore_ascii_case("HKLM") => vec!["REGISTRY", "MACHINE"],
+            Some(root) if root.eq_ignore_ascii_case("HKEY_LOCAL_MACHINE") => {
+                vec!["REGISTRY", "MACHINE"]
+            }
+            Some(root) if root.eq_ignore_ascii_case("HKU") => vec!["REGISTRY", "USER"],
+            Some(root) if root.eq_ignore_ascii_case("HKEY_USERS") => vec!["REGISTRY", "USER"],
+            Some(root) if root.eq_ignore_ascii_case("REGISTRY") => vec!["REGISTRY"],
+            Some(_) => return Err(VmiError::Other("Unsupported registry root key")),
+            None => vec![],
+        };
+
+        result.extend(components);
+        Ok(result.into_iter().map(String::from).collect())
+    }
+
+    /// Looks up a subkey of a key by its name.
+    fn registry_subkey(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-        root: Pa,
-    ) -> Result<String, VmiError> {
-        let address = self.__process_rtl_process_parameters(vmi, registers, process, root)?;
+        hive: Va,
+        key: &KeyNode,
+        name: &str,
+    ) -> Result<Option<KeyNode>, VmiError> {
+        let hash = registry::key_name_hash(name);
+
+        // Stable and volatile subkeys.
+        for storage in 0..2 {
+            if key.subkey_counts[storage] == 0 || key.subkey_lists[storage] == HCELL_NIL {
+                continue;
+            }
 
-        match address.kind {
-            WindowsWow64Kind::Native => self.process_command_line_native(vmi, root, address.va),
-            WindowsWow64Kind::X86 => self.process_command_line_32bit(vmi, root, address.va),
+            let subkey = self.registry_subkey_in_index(
+                vmi,
+                registers,
+                hive,
+                key.subkey_lists[storage],
+                name,
+                hash,
+                true,
+            )?;
+
+            if subkey.is_some() {
+                return Ok(subkey);
+            }
         }
+
+        Ok(None)
     }
 
-    /// Retrieves the command line for a native (non-WoW64) process.
-    fn process_command_line_native(
+    /// Looks up a subkey in a subkey index cell.
+    ///
+    /// Root index (`ri`) cells refer to leaf cells only, so they are
+    /// followed only if `allow_root` is set.
+    #[expect(clippy::too_many_arguments)]
+    fn registry_subkey_in_index(
         &self,
         vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        let RTL_USER_PROCESS_PARAMETERS = &self.offsets.common._RTL_USER_PROCESS_PARAMETERS;
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        hive: Va,
+        index: u32,
+        name: &str,
+        hash: u32,
+        allow_root: bool,
+    ) -> Result<Option<KeyNode>, VmiError> {
+        let index = self.registry_cell(vmi, registers, hive, index)?;
+
+        match KeyIndex::parse(&index) {
+            Some(KeyIndex::Leaf(entries)) => {
+                for (cell, entry_hash) in entries {
+                    if entry_hash.is_some_and(|entry_hash| entry_hash != hash) {
+                        continue;
+                    }
+
+                    let key = self.registry_key_node(vmi, registers, hive, cell)?;
+                    if registry::name_eq(&key.name, name) {
+                        return Ok(Some(key));
+                    }
+                }
 
-        self.read_unicode_string(
-            vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS.CommandLine.offset,
-                root,
-            ),
-        )
+                Ok(None)
+            }
+            Some(KeyIndex::Root(leaves)) if allow_root => {
+                for leaf in leaves {
+                    let key = self
+                        .registry_subkey_in_index(vmi, registers, hive, leaf, name, hash, false)?;
+
+                    if key.is_some() {
+                        return Ok(key);
+                    }
+                }
+
+                Ok(None)
+            }
+            _ => Err(VmiError::Other("Invalid registry key index")),
+        }
     }
 
-    /// Retrieves the command line for a 32-bit process running under WoW64.
-    fn process_command_line_32bit(
+    /// Reads a `_CM_KEY_NODE` cell.
+    fn registry_key_node(
         &self,
         vmi: &VmiCore<Driver>,
-        root: Pa,
-        rtl_process_parameters: Va,
-    ) -> Result<String, VmiError> {
-        const RTL_USER_PROCESS_PARAMETERS32_CommandLine_offset: u64 = 0x40;
-
-        self.read_unicode_string32(
-            vmi,
-            (
-                rtl_process_parameters + RTL_USER_PROCESS_PARAMETERS32_CommandLine_offset,
-                root,
-            ),
-        )
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        hive: Va,
+        cell: u32,
+    ) -> Result<KeyNode, VmiError> {
+        let data = self.registry_cell(vmi, registers, hive, cell)?;
+        KeyNode::parse(&data).ok_or(VmiError::Other("Invalid registry key node"))
     }
 
-    // endregion: PEB
+    /// Looks up a value of a key by its name and reads its data.
+    fn registry_key_value(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        hive: &WindowsRegistryHive,
+        key: &KeyNode,
+        name: &str,
+    ) -> Result<Option<WindowsRegistryValue>, VmiError> {
+        if key.value_count == 0 || key.value_list == HCELL_NIL {
+            return Ok(None);
+        }
 
-    // region: Process
+        let list = self.registry_cell(vmi, registers, hive.hive, key.value_list)?;
+        let cells = registry::cell_list(&list, key.value_count as usize)
+            .ok_or(VmiError::Other("Invalid registry value list"))?;
 
-    /// Extracts the `EPROCESS` structure from a `KTHREAD` structure.
-    ///
-    /// # Equivalent C pseudo-code
-    ///
-    /// ```c
-    /// return Thread->Process;
-    /// ```
-    pub fn process_from_thread(
+        for cell in cells {
+            let value = self.registry_cell(vmi, registers, hive.hive, cell)?;
+            let value = KeyValue::parse(&value).ok_or(VmiError::Other("Invalid registry value"))?;
+
+            if !registry::name_eq(&value.name, name) {
+                continue;
+            }
+
+            let data = self.registry_value_data(vmi, registers, hive, &value)?;
+            return Ok(Some(WindowsRegistryValue::from_raw(value.value_type, data)));
+        }
+
+        Ok(None)
+    }
+
+    /// Reads the data of a `_CM_KEY_VALUE`.
+    fn registry_value_data(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        thread: ThreadObject,
-    ) -> Result<ProcessObject, VmiError> {
-        let KTHREAD = &self.offsets.common._KTHREAD;
+        hive: &WindowsRegistryHive,
+        value: &KeyValue,
+    ) -> Result<Vec<u8>, VmiError> {
+        if let Some(data) = value.inline_data() {
+            return Ok(data);
+        }
 
-        let process = vmi.read_va(
-            registers.address_context(thread.0 + KTHREAD.Process.offset),
-            registers.address_width(),
-        )?;
+        let length = value.data_length as usize;
+        if length == 0 {
+            return Ok(Vec::new());
+        }
 
-        Ok(ProcessObject(process))
+        let minor = vmi.read_u32(registers.address_context(hive.base_block + HBASE_BLOCK_MINOR))?;
+
+        if value.data_length <= CM_KEY_VALUE_BIG || minor < HSYS_WHISTLER_BETA1 {
+            let mut data = self.registry_cell(vmi, registers, hive.hive, value.data)?;
+            if data.len() < length {
+                return Err(VmiError::Other("Invalid registry value data"));
+            }
+
+            data.truncate(length);
+            return Ok(data);
+        }
+
+        //
+        // The data is split into segments of `CM_KEY_VALUE_BIG` bytes,
+        // referenced by a `_CM_BIG_DATA` cell.
+        //
+
+        let big_data = self.registry_cell(vmi, registers, hive.hive, value.data)?;
+        let big_data =
+            BigData::parse(&big_data).ok_or(VmiError::Other("Invalid registry big data"))?;
+
+        let list = self.registry_cell(vmi, registers, hive.hive, big_data.list)?;
+        let segments = registry::cell_list(&list, big_data.count as usize)
+            .ok_or(VmiError::Other("Invalid registry big data"))?;
+
+        let mut data = Vec::with_capacity(length);
+        for segment in segments {
+            let segment = self.registry_cell(vmi, registers, hive.hive, segment)?;
+            let size = (CM_KEY_VALUE_BIG as usize).min(length - data.len());
+
+            match segment.get(..size) {
+                Some(segment) => data.extend_from_slice(segment),
+                None => return Err(VmiError::Other("Invalid registry big data")),
+            }
+
+            if data.len() == length {
+                break;
+            }
+        }
+
+        Ok(data)
     }
 
-    /// Extracts the `EPROCESS` structure from a `KAPC_STATE` structure.
-    ///
-    /// # Equivalent C pseudo-code
-    ///
-    /// ```c
-    /// return Thread->ApcState->Process;
-    /// ```
-    pub fn process_from_thread_apc_state(
+    /// Reads the data of a hive cell.
+    fn registry_cell(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        thread: ThreadObject,
-    ) -> Result<ProcessObject, VmiError> {
-        let KTHREAD = &self.offsets.common._KTHREAD;
-        let KAPC_STATE = &self.offsets.common._KAPC_STATE;
+        hive: Va,
+        cell: u32,
+    ) -> Result<Vec<u8>, VmiError> {
+        const MAX_CELL_SIZE: u64 = 0x10_0000;
 
-        let process = vmi.read_va(
-            registers
-                .address_context(thread.0 + KTHREAD.ApcState.offset + KAPC_STATE.Process.offset),
-            registers.address_width(),
+        let address = self.registry_cell_address(vmi, registers, hive, cell)?;
+
+        // The size is negative for allocated cells.
+        let size = vmi.read_u32(registers.address_context(address))? as i32;
+        let size = size.unsigned_abs() as u64;
+
+        if !(HCELL_HEADER_SIZE..=MAX_CELL_SIZE).contains(&size) {
+            return Err(VmiError::Other("Invalid registry cell size"));
+        }
+
+        let mut data = vec![0; (size - HCELL_HEADER_SIZE) as usize];
+        vmi.read(
+            registers.address_context(address + HCELL_HEADER_SIZE),
+            &mut data,
         )?;
 
-        Ok(ProcessObject(process))
+        Ok(data)
     }
 
-    /// Constructs an [`OsProcess`] from an `_EPROCESS`.
-    pub fn process_object_to_process(
+    /// Translates a cell index to the virtual address of the cell.
+    ///
+    /// # Implementation Details
+    ///
+    /// The cell index consists of the storage type (1 bit, stable or
+    /// volatile), the directory index (10 bits), the table index (9 bits)
+    /// and the offset within the block (12 bits). The directory and table
+    /// indices select the `_HMAP_ENTRY` of the block in the cell map of
+    /// the storage.
+    ///
+    /// The returned address points to the header of the cell.
+    fn registry_cell_address(
         &self,
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
-        process: ProcessObject,
-    ) -> Result<OsProcess, VmiError> {
-        let EPROCESS = &self.offsets.common._EPROCESS;
-        let KPROCESS = &self.offsets.common._KPROCESS;
+        hive: Va,
+        cell: u32,
+    ) -> Result<Va, VmiError> {
+        let HHIVE = &self.offsets.common._HHIVE;
+        let DUAL = &self.offsets.common._DUAL;
+        let HMAP_ENTRY = &self.offsets.common._HMAP_ENTRY;
 
-        let id =
-            vmi.read_u32(registers.address_context(process.0 + EPROCESS.UniqueProcessId.offset))?;
+        if cell == HCELL_NIL {
+            return Err(VmiError::Other("Invalid registry cell index"));
+        }
 
-        let name =
-            vmi.read_string(registers.address_context(process.0 + EPROCESS.ImageFileName.offset))?;
+        let storage = (cell >> 31) as u64;
+        let directory_index = ((cell >> 21) & 0x3ff) as u64;
+        let table_index = ((cell >> 12) & 0x1ff) as u64;
+        let offset = (cell & 0xfff) as u64;
+
+        let address_width = registers.address_width();
+
+        let map = vmi.read_va(
+            registers.address_context(
+                hive + HHIVE.Storage.offset + storage * DUAL.len() as u64 + DUAL.Map.offset,
+            ),
+            address_width,
+        )?;
+
+        let table = vmi.read_va(
+            registers.address_context(map + directory_index * address_width as u64),
+            address_width,
+        )?;
 
-        let translation_root = vmi.read_address(
-            registers.address_context(process.0 + KPROCESS.DirectoryTableBase.offset),
-            registers.address_width(),
+        if table.is_null() {
+            return Err(VmiError::Other("Invalid registry cell index"));
+        }
+
+        let entry = StructReader::new(
+            vmi,
+            registers.address_context(table + table_index * HMAP_ENTRY.len() as u64),
+            HMAP_ENTRY.effective_len(),
         )?;
 
-        Ok(OsProcess {
-            id: id.into(),
-            object: process,
-            name,
-            translation_root: translation_root.into(),
-        })
+        let block = match (
+            HMAP_ENTRY.BlockAddress,
+            HMAP_ENTRY.BlockOffset,
+            HMAP_ENTRY.PermanentBinAddress,
+        ) {
+            (Some(BlockAddress), _, _) => entry.read(BlockAddress)?,
+            (None, Some(BlockOffset), Some(PermanentBinAddress)) => {
+                // The low bits of `PermanentBinAddress` hold flags.
+                (entry.read(PermanentBinAddress)? & !0xf) + entry.read(BlockOffset)?
+            }
+            _ => return Err(VmiError::NotSupported),
+        };
+
+        if block == 0 {
+            return Err(VmiError::Other("Registry cell is not mapped"));
+        }
+
+        Ok(Va(block + offset))
     }
 
-    // endregion: Process
+    // endregion: Registry
 
     // region: String
 
@@ -2879,11 +9661,44 @@ where
     /// This method reads a native `_ANSI_STRING` structure which contains
     /// an ASCII/ANSI string. The structure is read according to the current
     /// OS's architecture (32-bit or 64-bit).
+    ///
+    /// The string is decoded as UTF-8, with invalid sequences replaced.
+    /// With the `encoding` feature enabled, `read_ansi_string_encoded` can
+    /// be used for strings in a system codepage.
     pub fn read_ansi_string(
         &self,
         vmi: &VmiCore<Driver>,
         ctx: impl Into<AccessContext>,
     ) -> Result<String, VmiError> {
+        let buffer = self.read_ansi_string_bytes(vmi, ctx)?;
+        Ok(String::from_utf8_lossy(&buffer).into())
+    }
+
+    /// Reads string from an `_ANSI_STRING` structure and decodes it using
+    /// the given encoding.
+    ///
+    /// ANSI strings are stored in the system codepage of the guest (e.g.,
+    /// `windows-1252` for Western European or `Shift_JIS` for Japanese
+    /// locales), which is usually not UTF-8. Malformed sequences are
+    /// replaced with the replacement character.
+    #[cfg(feature = "encoding")]
+    pub fn read_ansi_string_encoded(
+        &self,
+        vmi: &VmiCore<Driver>,
+        ctx: impl Into<AccessContext>,
+        encoding: &'static encoding_rs::Encoding,
+    ) -> Result<String, VmiError> {
+        let buffer = self.read_ansi_string_bytes(vmi, ctx)?;
+        let (string, _) = encoding.decode_without_bom_handling(&buffer);
+        Ok(string.into_owned())
+    }
+
+    /// Reads the raw bytes of an `_ANSI_STRING` structure.
+    fn read_ansi_string_bytes(
+        &self,
+        vmi: &VmiCore<Driver>,
+        ctx: impl Into<AccessContext>,
+    ) -> Result<Vec<u8>, VmiError> {
         let mut ctx = ctx.into();
 
         //
@@ -2895,14 +9710,51 @@ where
 
         let string = StructReader::new(vmi, ctx, ANSI_STRING.effective_len())?;
         let string_length = string.read(ANSI_STRING.Length)?;
+        let string_maximum_length = string.read(ANSI_STRING.MaximumLength)?;
         let string_buffer = string.read(ANSI_STRING.Buffer)?;
 
         ctx.address = string_buffer;
+        Self::read_string_buffer(vmi, ctx, string_length, string_maximum_length, 1)
+    }
+
+    /// Reads the buffer of an `_ANSI_STRING` or `_UNICODE_STRING`
+    /// structure, after validating its fields.
+    ///
+    /// The fields of a string in guest memory can't be trusted, a corrupted
+    /// or maliciously crafted structure would otherwise cause a wild read.
+    /// Returns [`VmiError::CorruptedStruct`] if:
+    ///
+    /// - `Length` exceeds `MaximumLength`,
+    /// - `Length` is not a multiple of the character size,
+    /// - `Buffer` points into the first 64 KiB of the address space, which
+    ///   are never mapped on Windows.
+    ///
+    /// An empty string is returned without reading the buffer.
+    fn read_string_buffer(
+        vmi: &VmiCore<Driver>,
+        ctx: AccessContext,
+        length: u64,
+        maximum_length: u64,
+        char_size: u64,
+    ) -> Result<Vec<u8>, VmiError> {
+        const MM_LOWEST_USER_ADDRESS: u64 = 0x10000;
+
+        if length > maximum_length || length % char_size != 0 {
+            return Err(VmiError::CorruptedStruct("Invalid string length"));
+        }
 
-        let mut buffer = vec![0u8; string_length as usize];
+        if length == 0 {
+            return Ok(Vec::new());
+        }
+
+        if ctx.address < MM_LOWEST_USER_ADDRESS {
+            return Err(VmiError::CorruptedStruct("Invalid string buffer"));
+        }
+
+        let mut buffer = vec![0u8; length as usize];
         vmi.read(ctx, &mut buffer)?;
 
-        Ok(String::from_utf8_lossy(&buffer).into())
+        Ok(buffer)
     }
 
     /// Reads string from a 32-bit version of `_ANSI_STRING` structure.
@@ -2920,13 +9772,18 @@ where
         vmi.read(ctx, &mut buffer)?;
 
         let string_length = u16::from_le_bytes([buffer[0], buffer[1]]);
-        // let string_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
+        let string_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
         let string_buffer = u32::from_le_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]);
 
         ctx.address = string_buffer as u64;
 
-        let mut buffer = vec![0u8; string_length as usize];
-        vmi.read(ctx, &mut buffer)?;
+        let buffer = Self::read_string_buffer(
+            vmi,
+            ctx,
+            string_length as u64,
+            string_maximum_length as u64,
+            1,
+        )?;
 
         Ok(String::from_utf8_lossy(&buffer).into())
     }
@@ -2946,7 +9803,7 @@ where
         vmi.read(ctx, &mut buffer)?;
 
         let string_length = u16::from_le_bytes([buffer[0], buffer[1]]);
-        // let string_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
+        let string_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
         let string_buffer = u64::from_le_bytes([
             buffer[8], buffer[9], buffer[10], buffer[11], buffer[12], buffer[13], buffer[14],
             buffer[15],
@@ -2954,8 +9811,13 @@ where
 
         ctx.address = string_buffer;
 
-        let mut buffer = vec![0u8; string_length as usize];
-        vmi.read(ctx, &mut buffer)?;
+        let buffer = Self::read_string_buffer(
+            vmi,
+            ctx,
+            string_length as u64,
+            string_maximum_length as u64,
+            1,
+        )?;
 
         Ok(String::from_utf8_lossy(&buffer).into())
     }
@@ -2976,12 +9838,12 @@ where
 
         let string = StructReader::new(vmi, ctx, UNICODE_STRING.effective_len())?;
         let string_length = string.read(UNICODE_STRING.Length)?;
+        let string_maximum_length = string.read(UNICODE_STRING.MaximumLength)?;
         let string_buffer = string.read(UNICODE_STRING.Buffer)?;
 
         ctx.address = string_buffer;
 
-        let mut buffer = vec![0u8; string_length as usize];
-        vmi.read(ctx, &mut buffer)?;
+        let buffer = Self::read_string_buffer(vmi, ctx, string_length, string_maximum_length, 2)?;
 
         Ok(String::from_utf16_lossy(
             &buffer
@@ -3006,13 +9868,18 @@ where
         vmi.read(ctx, &mut buffer)?;
 
         let string_length = u16::from_le_bytes([buffer[0], buffer[1]]);
-        // let string_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
+        let string_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
         let string_buffer = u32::from_le_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]);
 
         ctx.address = string_buffer as u64;
 
-        let mut buffer = vec![0u8; string_length as usize];
-        vmi.read(ctx, &mut buffer)?;
+        let buffer = Self::read_string_buffer(
+            vmi,
+            ctx,
+            string_length as u64,
+            string_maximum_length as u64,
+            2,
+        )?;
 
         Ok(String::from_utf16_lossy(
             &buffer
@@ -3037,7 +9904,7 @@ where
         vmi.read(ctx, &mut buffer)?;
 
         let us_length = u16::from_le_bytes([buffer[0], buffer[1]]);
-        // let us_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
+        let us_maximum_length = u16::from_le_bytes([buffer[2], buffer[3]]);
         let us_buffer = u64::from_le_bytes([
             buffer[8], buffer[9], buffer[10], buffer[11], buffer[12], buffer[13], buffer[14],
             buffer[15],
@@ -3045,8 +9912,8 @@ where
 
         ctx.address = us_buffer;
 
-        let mut buffer = vec![0u8; us_length as usize];
-        vmi.read(ctx, &mut buffer)?;
+        let buffer =
+            Self::read_string_buffer(vmi, ctx, us_length as u64, us_maximum_length as u64, 2)?;
 
         Ok(String::from_utf16_lossy(
             &buffer
@@ -3056,8 +9923,343 @@ where
         ))
     }
 
+    /// Reads strings from an array of `count` consecutive `_UNICODE_STRING`
+    /// structures.
+    ///
+    /// The structures are read according to the current OS's architecture,
+    /// the same way as [`read_unicode_string`] does. An error is returned
+    /// if any of the strings can't be read or is corrupted.
+    ///
+    /// [`read_unicode_string`]: Self::read_unicode_string
+    pub fn read_unicode_string_array(
+        &self,
+        vmi: &VmiCore<Driver>,
+        ctx: impl Into<AccessContext>,
+        count: usize,
+    ) -> Result<Vec<String>, VmiError> {
+        let ctx = ctx.into();
+
+        let UNICODE_STRING = &self.offsets.common._UNICODE_STRING;
+
+        (0..count as u64)
+            .map(|index| self.read_unicode_string(vmi, ctx + index * UNICODE_STRING.len() as u64))
+            .collect()
+    }
+
     // endregion: String
 
+    // region: Thread
+
+    /// Retrieves the scheduling state of a thread.
+    pub fn thread_state(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<WindowsThreadState, VmiError> {
+        let KTHREAD = &self.offsets.common._KTHREAD;
+
+        let state = vmi.read_u8(registers.address_context(thread.0 + KTHREAD.State.offset))?;
+        Ok(WindowsThreadState::from(state))
+    }
+
+    /// Retrieves the reason a thread is waiting.
+    ///
+    /// The value is only meaningful if the thread is in the
+    /// [`WindowsThreadState::Waiting`] state. Otherwise, it holds the
+    /// reason of the last wait.
+    pub fn thread_wait_reason(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<WindowsWaitReason, VmiError> {
+        let KTHREAD = &self.offsets.common._KTHREAD;
+
+        let wait_reason =
+            vmi.read_u8(registers.address_context(thread.0 + KTHREAD.WaitReason.offset))?;
+        Ok(WindowsWaitReason::from(wait_reason))
+    }
+
+    /// Reconstructs the saved context of a thread that is not running.
+    ///
+    /// When a thread is switched out, its kernel-mode context is saved on its
+    /// kernel stack rather than in any virtual CPU. This method rebuilds
+    /// the registers from the `_KSWITCH_FRAME` stored at
+    /// `KTHREAD.KernelStack`, so that the returned registers can be used as
+    /// a starting point for walking the kernel stack of the thread.
+    ///
+    /// Only the instruction pointer, stack pointer and frame pointer (if
+    /// available) are restored; the remaining general-purpose registers are
+    /// zeroed. Control registers are copied from `registers`, except for the
+    /// translation root, which is set to the kernel translation root of the
+    /// thread's process.
+    ///
+    /// Returns an error if the thread is currently running.
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PKSWITCH_FRAME SwitchFrame = (PKSWITCH_FRAME)Thread->KernelStack;
+    ///
+    /// Context.Rip = SwitchFrame->Return;
+    /// Context.Rsp = (ULONG64)(SwitchFrame + 1);
+    /// Context.Rbp = SwitchFrame->Rbp;
+    /// ```
+    pub fn thread_saved_context(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<<Driver::Architecture as Architecture>::Registers, VmiError> {
+        if self.thread_state(vmi, registers, thread)? == WindowsThreadState::Running {
+            return Err(VmiError::Other("Thread is running"));
+        }
+
+        Driver::Architecture::thread_saved_context(self, vmi, registers, thread)
+    }
+
+    /// Reconstructs the user-mode context of a thread.
+    ///
+    /// This is the register state a debugger would obtain through
+    /// `GetThreadContext`: the state of the thread at the moment it
+    /// entered the kernel, even if it is currently executing kernel code.
+    /// If the thread is the current thread and the virtual CPU is executing
+    /// user-mode code, `registers` are returned unchanged.
+    ///
+    /// Otherwise, the registers are restored from the user-mode
+    /// `_KTRAP_FRAME` of the thread. Registers that are not saved in the
+    /// trap frame are zeroed. The translation root is set to the user
+    /// translation root of the thread's process.
+    ///
+    /// Returns an error if the thread has no user-mode context (e.g.,
+    /// system threads).
+    ///
+    /// # Equivalent C pseudo-code
+    ///
+    /// ```c
+    /// PKTRAP_FRAME TrapFrame = Thread->TrapFrame;
+    ///
+    /// if (TrapFrame == NULL || (TrapFrame->SegCs & 3) == 0) {
+    ///     TrapFrame = (PKTRAP_FRAME)Thread->InitialStack - 1;
+    /// }
+    ///
+    /// Context.Rip = TrapFrame->Rip;
+    /// Context.Rsp = TrapFrame->Rsp;
+    /// ...
+    /// ```
+    pub fn thread_user_context(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<<Driver::Architecture as Architecture>::Registers, VmiError> {
+        let is_current = self.current_thread(vmi, registers)? == thread;
+        Driver::Architecture::thread_user_context(self, vmi, registers, thread, is_current)
+    }
+
+    /// Retrieves the asynchronous procedure calls (APCs) queued to a thread.
+    ///
+    /// Returns the kernel-mode APCs followed by the user-mode APCs, in the
+    /// order in which they will be delivered.
+    ///
+    /// # Implementation Details
+    ///
+    /// The APCs are enumerated by walking the `KTHREAD.ApcState.ApcListHead`
+    /// lists, indexed by `KernelMode` (0) and `UserMode` (1), linked through
+    /// `KAPC.ApcListEntry`.
+    ///
+    /// APCs queued while the thread is attached to another process are
+    /// kept in `KTHREAD.SavedApcState` and are not included.
+    pub fn thread_apcs(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<Vec<WindowsApc>, VmiError> {
+        let KTHREAD = &self.offsets.common._KTHREAD;
+        let KAPC_STATE = &self.offsets.common._KAPC_STATE;
+        let KAPC = &self.offsets.common._KAPC;
+
+        // sizeof(LIST_ENTRY)
+        let list_entry_size = 2 * registers.address_width() as u64;
+        let apc_list_head = thread.0 + KTHREAD.ApcState.offset + KAPC_STATE.ApcListHead.offset;
+
+        let mut entries = Vec::new();
+        for (index, user_mode) in [false, true].into_iter().enumerate() {
+            let list_head = apc_list_head + index as u64 * list_entry_size;
+
+            self.enumerate_list(vmi, registers, list_head, |entry| {
+                entries.push((entry - KAPC.ApcListEntry.offset, user_mode));
+                true
+            })?;
+        }
+
+        let mut result = Vec::with_capacity(entries.len());
+        for (address, user_mode) in entries {
+            let apc = StructReader::new(
+                vmi,
+                registers.address_context(address),
+                KAPC.effective_len(),
+            )?;
+
+            result.push(WindowsApc {
+                address,
+                user_mode,
+                kernel_routine: Va(apc.read(KAPC.KernelRoutine)?),
+                rundown_routine: Va(apc.read(KAPC.RundownRoutine)?),
+                normal_routine: Va(apc.read(KAPC.NormalRoutine)?),
+                normal_context: Va(apc.read(KAPC.NormalContext)?),
+                system_argument1: Va(apc.read(KAPC.SystemArgument1)?),
+                system_argument2: Va(apc.read(KAPC.SystemArgument2)?),
+            });
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the kernel-only threads of the system.
+    ///
+    /// Returns the threads of the System process (kernel worker threads and
+    /// threads created by drivers), followed by the idle thread of each
+    /// processor. Idle threads belong to the Idle process, which is not
+    /// linked in the list of active processes.
+    ///
+    /// # Implementation Details
+    ///
+    /// The idle threads are read from `KPRCB.IdleThread` of each processor,
+    /// see [`processor_control_blocks`].
+    ///
+    /// [`processor_control_blocks`]: Self::processor_control_blocks
+    pub fn system_threads(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<Vec<ThreadObject>, VmiError> {
+        let KPRCB = &self.offsets.common._KPRCB;
+
+        let system_process = self.system_process(vmi, registers)?;
+        let mut result = self.process_threads(vmi, registers, system_process)?;
+
+        for prcb in self.processor_control_blocks(vmi, registers)? {
+            let idle_thread = ThreadObject(vmi.read_va(
+                registers.address_context(prcb + KPRCB.IdleThread.offset),
+                registers.address_width(),
+            )?);
+
+            if !idle_thread.is_null() && !result.contains(&idle_thread) {
+                result.push(idle_thread);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Retrieves the bounds of the kernel stack of a thread.
+    ///
+    /// A stack walk of the thread should not read past these bounds.
+    ///
+    /// # Implementation Details
+    ///
+    /// The bounds are read from the `StackBase` and `StackLimit` fields of
+    /// the `_KTHREAD`. When the thread runs on an expanded stack (e.g.,
+    /// during a callout to user mode), these fields describe the current
+    /// stack segment.
+    pub fn thread_kernel_stack_bounds(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<WindowsStackBounds, VmiError> {
+        let KTHREAD = &self.offsets.common._KTHREAD;
+
+        let kthread = StructReader::new(
+            vmi,
+            registers.address_context(thread.0),
+            KTHREAD.effective_len(),
+        )?;
+
+        Ok(WindowsStackBounds {
+            base: Va(kthread.read(KTHREAD.StackBase)?),
+            limit: Va(kthread.read(KTHREAD.StackLimit)?),
+        })
+    }
+
+    /// Walks the stack of a thread using the unwind information of the
+    /// images on the stack.
+    ///
+    /// If the thread is the current thread, the walk starts from
+    /// `registers`, otherwise it starts from the saved context of the
+    /// thread (see [`thread_saved_context`]). Kernel-mode walks are limited
+    /// to the kernel stack of the thread.
+    ///
+    /// See [`stack_trace_unwind`] for details.
+    ///
+    /// [`thread_saved_context`]: Self::thread_saved_context
+    /// [`stack_trace_unwind`]: Self::stack_trace_unwind
+    pub fn thread_stack_trace_unwind(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        thread: ThreadObject,
+    ) -> Result<Vec<WindowsStackFrame>, VmiError> {
+        let context = if self.current_thread(vmi, registers)? == thread {
+            *registers
+        }
+        else {
+            self.thread_saved_context(vmi, registers, thread)?
+        };
+
+        let highest_user_address = self.highest_user_address(vmi, registers)?;
+        let bounds = match Va(context.stack_pointer()) > highest_user_address {
+            true => Some(self.thread_kernel_stack_bounds(vmi, registers, thread)?),
+            false => None,
+        };
+
+        let process = self.process_from_thread(vmi, registers, thread)?;
+        self.stack_trace_unwind(vmi, registers, &context, process, bounds)
+    }
+
+    /// Walks a stack using the unwind information of the images on the
+    /// stack, starting from the given context.
+    ///
+    /// The context can be, e.g., the current registers, the saved context
+    /// of a thread or the user-mode context of a thread. The walk stops
+    /// when the stack pointer leaves `bounds` (if given), when the
+    /// instruction pointer does not belong to any image, or when a frame
+    /// cannot be unwound.
+    ///
+    /// Kernel-mode addresses are resolved to the loaded kernel modules,
+    /// user-mode addresses to the images mapped in `process`, which should
+    /// be the process the stack belongs to.
+    ///
+    /// # Implementation Details
+    ///
+    /// This is an equivalent of `RtlVirtualUnwind`. For each frame, the
+    /// `RUNTIME_FUNCTION` entry of the function is looked up in the
+    /// exception directory of the image, and the unwind codes of its
+    /// `UNWIND_INFO` (including chained unwind information) are applied
+    /// in reverse to restore the caller's registers. Functions without an
+    /// entry are leaf functions, whose return address is at the top of
+    /// the stack.
+    ///
+    /// # Notes
+    ///
+    /// Only 64-bit images carry unwind information. The first frame is
+    /// assumed not to be executing an epilog.
+    pub fn stack_trace_unwind(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        context: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+        bounds: Option<WindowsStackBounds>,
+    ) -> Result<Vec<WindowsStackFrame>, VmiError> {
+        Driver::Architecture::stack_trace_unwind(self, vmi, registers, context, process, bounds)
+    }
+
+    // endregion: Thread
+
     // region: User Address
 
     /// Returns the lowest user-mode address.
@@ -3204,24 +10406,10 @@ where
         vmi: &VmiCore<Driver>,
         registers: &<<Driver as VmiDriver>::Architecture as Architecture>::Registers,
     ) -> Result<Vec<OsModule>, VmiError> {
-        let mut result = Vec::new();
-
         let PsLoadedModuleList =
             self.kernel_image_base(vmi, registers)? + self.symbols.PsLoadedModuleList;
 
-        let KLDR_DATA_TABLE_ENTRY = &self.offsets.common._KLDR_DATA_TABLE_ENTRY;
-
-        self.enumerate_list(vmi, registers, PsLoadedModuleList, |entry| {
-            let module_entry = entry - KLDR_DATA_TABLE_ENTRY.InLoadOrderLinks.offset;
-
-            if let Ok(module) = self.kernel_module(vmi, registers, module_entry) {
-                result.push(module)
-            }
-
-            true
-        })?;
-
-        Ok(result)
+        self.kernel_modules_from_list(vmi, registers, PsLoadedModuleList)
     }
 
     fn system_process(
@@ -3476,6 +10664,23 @@ where
         self.vad_root_to_regions(vmi, registers, vad_root)
     }
 
+    fn process_modules(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<OsModule>, VmiError> {
+        Ok(self
+            .process_user_modules(vmi, registers, process)?
+            .into_iter()
+            .map(|module| OsModule {
+                base_address: module.base_address,
+                size: module.size,
+                name: module.name,
+            })
+            .collect())
+    }
+
     fn process_address_is_valid(
         &self,
         vmi: &VmiCore<Driver>,
@@ -3599,22 +10804,15 @@ where
         vmi: &VmiCore<Driver>,
         registers: &<Driver::Architecture as Architecture>::Registers,
         list_head: Va,
-        mut callback: impl FnMut(Va) -> bool,
+        callback: impl FnMut(Va) -> bool,
     ) -> Result<(), VmiError> {
-        let mut entry = vmi.read_va(
-            registers.address_context(list_head),
+        self.enumerate_list_in(
+            vmi,
+            registers.translation_root(list_head),
+            list_head,
             registers.address_width(),
-        )?;
-
-        while entry != list_head {
-            if !callback(entry) {
-                break;
-            }
-
-            entry = vmi.read_va(registers.address_context(entry), registers.address_width())?;
-        }
-
-        Ok(())
+            callback,
+        )
     }
 
     fn enumerate_tree(
@@ -3635,3 +10833,66 @@ where
         }
     }
 }
+
+/// Splits an object path into its components.
+fn split_object_path(path: &str) -> VecDeque<String> {
+    path.split('\\')
+        .filter(|component| !component.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Collects the function pointer fields of a HAL dispatch table type.
+///
+/// Returns `None` if the type is not present in the profile.
+fn hal_dispatch_layout(profile: &Profile, type_name: &str) -> Option<Vec<(String, u64)>> {
+    let udt = profile.find_struct(type_name)?;
+
+    Some(
+        udt.fields
+            .iter()
+            .filter(|(_, field)| matches!(field.type_, isr_core::types::Type::Pointer(_)))
+            .map(|(name, field)| (name.to_string(), field.offset))
+            .collect(),
+    )
+}
+
+/// Returns the layout of the `HAL_DISPATCH` structure, as documented in
+/// `ntddk.h`.
+///
+/// Each field occupies a pointer-sized slot, including the leading
+/// `Version` and the `Spare1` fields, which are skipped.
+fn hal_dispatch_layout_fallback(pointer_size: u64) -> Vec<(String, u64)> {
+    const HAL_DISPATCH_FIELDS: &[&str] = &[
+        "Version",
+        "HalQuerySystemInformation",
+        "HalSetSystemInformation",
+        "HalQueryBusSlots",
+        "Spare1",
+        "HalExamineMBR",
+        "HalIoReadPartitionTable",
+        "HalIoSetPartitionInformation",
+        "HalIoWritePartitionTable",
+        "HalReferenceHandlerForBus",
+        "HalReferenceBusHandler",
+        "HalDereferenceBusHandler",
+        "HalInitPnpDriver",
+        "HalInitPowerManagement",
+        "HalGetDmaAdapter",
+        "HalGetInterruptTranslator",
+        "HalStartMirroring",
+        "HalEndMirroring",
+        "HalMirrorPhysicalMemory",
+        "HalEndOfBoot",
+        "HalMirrorVerify",
+        "HalGetCachedAcpiTable",
+        "HalSetPciErrorHandlerCallback",
+    ];
+
+    HAL_DISPATCH_FIELDS
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| !matches!(**name, "Version" | "Spare1"))
+        .map(|(index, name)| (String::from(*name), index as u64 * pointer_size))
+        .collect()
+}
@@ -0,0 +1,420 @@
+use std::net::IpAddr;
+
+use isr_core::Profile;
+use isr_macros::symbols;
+use vmi_core::{os::ProcessId, Va, VmiError};
+
+symbols! {
+    /// `tcpip.sys` symbols used by the network enumeration.
+    #[derive(Debug)]
+    pub struct TcpipSymbols {
+        PartitionTable: u64,
+        PartitionCount: u64,
+        TcpPortPool: u64,
+        UdpPortPool: u64,
+    }
+}
+
+/// Layout of the undocumented `tcpip.sys` structures.
+///
+/// Unlike the kernel, `tcpip.sys` does not ship type information in its
+/// public symbols, and the layout of these structures changes between
+/// Windows builds. Layouts of the supported 64-bit builds are available
+/// through [`WindowsTcpipLayout::for_build`]; for other builds, the offsets
+/// have to be obtained for the specific build of the guest (e.g., by reverse
+/// engineering or from memory forensics frameworks).
+///
+/// All offsets are in bytes.
+#[derive(Debug, Clone)]
+pub struct WindowsTcpipLayout {
+    /// Size of the `_PARTITION` structure.
+    pub partition_size: u64,
+
+    /// Offset of `_PARTITION.Endpoints` (`_RTL_DYNAMIC_HASH_TABLE*`).
+    pub partition_endpoints: u64,
+
+    /// Offset of `_TCP_ENDPOINT.HashTableEntry`
+    /// (`_RTL_DYNAMIC_HASH_TABLE_ENTRY`).
+    pub tcp_endpoint_hash_table_entry: u64,
+
+    /// Offset of `_TCP_ENDPOINT.InetAF` (`_INETAF*`).
+    pub tcp_endpoint_inet_af: u64,
+
+    /// Offset of `_TCP_ENDPOINT.AddrInfo` (`_ADDRINFO*`).
+    pub tcp_endpoint_addr_info: u64,
+
+    /// Offset of `_TCP_ENDPOINT.State` (`ULONG`).
+    pub tcp_endpoint_state: u64,
+
+    /// Offset of `_TCP_ENDPOINT.LocalPort` (big-endian `USHORT`).
+    pub tcp_endpoint_local_port: u64,
+
+    /// Offset of `_TCP_ENDPOINT.RemotePort` (big-endian `USHORT`).
+    pub tcp_endpoint_remote_port: u64,
+
+    /// Offset of `_TCP_ENDPOINT.Owner` (`_EPROCESS*`).
+    pub tcp_endpoint_owner: u64,
+
+    /// Offset of `_TCP_ENDPOINT.CreateTime` (`LARGE_INTEGER`).
+    pub tcp_endpoint_create_time: u64,
+
+    /// Offset of `_TCP_LISTENER.InetAF` (`_INETAF*`).
+    pub tcp_listener_inet_af: u64,
+
+    /// Offset of `_TCP_LISTENER.LocalAddr` (`_LOCAL_ADDRESS*`).
+    pub tcp_listener_local_address: u64,
+
+    /// Offset of `_TCP_LISTENER.Port` (big-endian `USHORT`).
+    pub tcp_listener_port: u64,
+
+    /// Offset of `_TCP_LISTENER.Owner` (`_EPROCESS*`).
+    pub tcp_listener_owner: u64,
+
+    /// Offset of `_TCP_LISTENER.CreateTime` (`LARGE_INTEGER`).
+    pub tcp_listener_create_time: u64,
+
+    /// Offset of `_TCP_LISTENER.Next`.
+    ///
+    /// Listeners bound to the same port are linked through this field, and
+    /// the port assignment entries point to it.
+    pub tcp_listener_next: u64,
+
+    /// Offset of `_UDP_ENDPOINT.InetAF` (`_INETAF*`).
+    pub udp_endpoint_inet_af: u64,
+
+    /// Offset of `_UDP_ENDPOINT.LocalAddr` (`_LOCAL_ADDRESS*`).
+    pub udp_endpoint_local_address: u64,
+
+    /// Offset of `_UDP_ENDPOINT.Port` (big-endian `USHORT`).
+    pub udp_endpoint_port: u64,
+
+    /// Offset of `_UDP_ENDPOINT.Owner` (`_EPROCESS*`).
+    pub udp_endpoint_owner: u64,
+
+    /// Offset of `_UDP_ENDPOINT.CreateTime` (`LARGE_INTEGER`).
+    pub udp_endpoint_create_time: u64,
+
+    /// Offset of `_UDP_ENDPOINT.Next`.
+    ///
+    /// Endpoints bound to the same port are linked through this field, and
+    /// the port assignment entries point to it.
+    pub udp_endpoint_next: u64,
+
+    /// Offset of `_ADDRINFO.Local` (`_LOCAL_ADDRESS*`).
+    pub addr_info_local: u64,
+
+    /// Offset of `_ADDRINFO.Remote` (`IN_ADDR*` or `IN6_ADDR*`).
+    pub addr_info_remote: u64,
+
+    /// Offset of `_LOCAL_ADDRESS.pData` (pointer to `IN_ADDR*` or
+    /// `IN6_ADDR*`).
+    pub local_address_data: u64,
+
+    /// Offset of `_INETAF.AddressFamily` (`USHORT`).
+    pub inet_af_address_family: u64,
+
+    /// Offset of `_INET_PORT_POOL.PortBitMap` (`_RTL_BITMAP`).
+    pub port_pool_bitmap: u64,
+
+    /// Offset of `_INET_PORT_POOL.PortAssignments`
+    /// (`_PORT_ASSIGNMENT*[256]`).
+    pub port_pool_assignments: u64,
+
+    /// Offset of `_PORT_ASSIGNMENT.InPaBigPoolBase`
+    /// (`_PORT_ASSIGNMENT_LIST*`).
+    pub port_assignment_list: u64,
+
+    /// Offset of `_PORT_ASSIGNMENT_LIST.Assignments`
+    /// (`_PORT_ASSIGNMENT_ENTRY[256]`).
+    pub port_assignment_list_entries: u64,
+
+    /// Size of the `_PORT_ASSIGNMENT_ENTRY` structure.
+    pub port_assignment_entry_size: u64,
+
+    /// Offset of `_PORT_ASSIGNMENT_ENTRY.Entry`.
+    pub port_assignment_entry: u64,
+}
+
+impl WindowsTcpipLayout {
+    /// Layout of `tcpip.sys` 10.0.17763 (Windows 10 1809, Windows Server
+    /// 2019), 64-bit.
+    pub const WINDOWS_10_1809_X64: Self = Self {
+        partition_size: 0xc0,
+        partition_endpoints: 0x08,
+        tcp_endpoint_hash_table_entry: 0x28,
+        tcp_endpoint_inet_af: 0x10,
+        tcp_endpoint_addr_info: 0x18,
+        tcp_endpoint_state: 0x6c,
+        tcp_endpoint_local_port: 0x70,
+        tcp_endpoint_remote_port: 0x72,
+        tcp_endpoint_owner: 0x270,
+        tcp_endpoint_create_time: 0x280,
+        tcp_listener_inet_af: 0x28,
+        tcp_listener_local_address: 0x60,
+        tcp_listener_port: 0x72,
+        tcp_listener_owner: 0x30,
+        tcp_listener_create_time: 0x40,
+        tcp_listener_next: 0x78,
+        udp_endpoint_inet_af: 0x20,
+        udp_endpoint_local_address: 0x80,
+        udp_endpoint_port: 0x78,
+        udp_endpoint_owner: 0x28,
+        udp_endpoint_create_time: 0x58,
+        udp_endpoint_next: 0x70,
+        addr_info_local: 0x00,
+        addr_info_remote: 0x10,
+        local_address_data: 0x10,
+        inet_af_address_family: 0x18,
+        port_pool_bitmap: 0x90,
+        port_pool_assignments: 0xa0,
+        port_assignment_list: 0x18,
+        port_assignment_list_entries: 0x00,
+        port_assignment_entry_size: 0x18,
+        port_assignment_entry: 0x08,
+    };
+
+    /// Layout of `tcpip.sys` 10.0.19041 (Windows 10 2004 through 22H2),
+    /// 64-bit.
+    pub const WINDOWS_10_2004_X64: Self = Self {
+        partition_size: 0xc0,
+        partition_endpoints: 0x08,
+        tcp_endpoint_hash_table_entry: 0x28,
+        tcp_endpoint_inet_af: 0x10,
+        tcp_endpoint_addr_info: 0x18,
+        tcp_endpoint_state: 0x6c,
+        tcp_endpoint_local_port: 0x70,
+        tcp_endpoint_remote_port: 0x72,
+        tcp_endpoint_owner: 0x2d8,
+        tcp_endpoint_create_time: 0x2e8,
+        tcp_listener_inet_af: 0x28,
+        tcp_listener_local_address: 0x60,
+        tcp_listener_port: 0x72,
+        tcp_listener_owner: 0x30,
+        tcp_listener_create_time: 0x40,
+        tcp_listener_next: 0x78,
+        udp_endpoint_inet_af: 0x20,
+        udp_endpoint_local_address: 0x80,
+        udp_endpoint_port: 0xa0,
+        udp_endpoint_owner: 0x28,
+        udp_endpoint_create_time: 0x58,
+        udp_endpoint_next: 0x70,
+        addr_info_local: 0x00,
+        addr_info_remote: 0x10,
+        local_address_data: 0x10,
+        inet_af_address_family: 0x18,
+        port_pool_bitmap: 0x90,
+        port_pool_assignments: 0xa0,
+        port_assignment_list: 0x18,
+        port_assignment_list_entries: 0x00,
+        port_assignment_entry_size: 0x18,
+        port_assignment_entry: 0x08,
+    };
+
+    /// Returns the built-in layout for a 64-bit `tcpip.sys` build.
+    ///
+    /// The `build` is the third component of the `tcpip.sys` file version
+    /// (e.g., `19041` for `10.0.19041.3636`). Windows 10 20H2 through 22H2
+    /// ship `tcpip.sys` from the 19041 branch.
+    ///
+    /// Returns `None` if there is no built-in layout for the build.
+    pub fn for_build(build: u32) -> Option<Self> {
+        match build {
+            17763 => Some(Self::WINDOWS_10_1809_X64),
+            19041 => Some(Self::WINDOWS_10_2004_X64),
+            _ => None,
+        }
+    }
+}
+
+/// The `tcpip.sys` driver of the guest.
+///
+/// Required by [`WindowsOs::tcp_connections`],
+/// [`WindowsOs::tcp_listeners`] and [`WindowsOs::udp_endpoints`].
+///
+/// [`WindowsOs::tcp_connections`]: crate::WindowsOs::tcp_connections
+/// [`WindowsOs::tcp_listeners`]: crate::WindowsOs::tcp_listeners
+/// [`WindowsOs::udp_endpoints`]: crate::WindowsOs::udp_endpoints
+#[derive(Debug)]
+pub struct WindowsTcpip {
+    /// Base address of the `tcpip.sys` image.
+    pub(crate) base_address: Va,
+
+    /// Symbols of the `tcpip.sys` image.
+    pub(crate) symbols: TcpipSymbols,
+
+    /// Layout of the `tcpip.sys` structures.
+    pub(crate) layout: WindowsTcpipLayout,
+}
+
+impl WindowsTcpip {
+    /// Creates a new `WindowsTcpip` instance.
+    ///
+    /// The `profile` is created from the PDB of the `tcpip.sys` image
+    /// loaded at `base_address`, and the `layout` must match its build.
+    pub fn new(
+        profile: &Profile,
+        base_address: Va,
+        layout: WindowsTcpipLayout,
+    ) -> Result<Self, VmiError> {
+        Ok(Self {
+            base_address,
+            symbols: TcpipSymbols::new(profile)?,
+            layout,
+        })
+    }
+
+    /// Creates a new `WindowsTcpip` instance with the built-in layout for
+    /// the `tcpip.sys` build.
+    ///
+    /// Returns [`VmiError::NotSupported`] if there is no built-in layout
+    /// for the build. See [`WindowsTcpipLayout::for_build`].
+    pub fn with_build(profile: &Profile, base_address: Va, build: u32) -> Result<Self, VmiError> {
+        let layout = WindowsTcpipLayout::for_build(build).ok_or(VmiError::NotSupported)?;
+        Self::new(profile, base_address, layout)
+    }
+
+    /// Returns the base address of the `tcpip.sys` image.
+    pub fn base_address(&self) -> Va {
+        self.base_address
+    }
+
+    /// Returns the layout of the `tcpip.sys` structures.
+    pub fn layout(&self) -> &WindowsTcpipLayout {
+        &self.layout
+    }
+}
+
+/// State of a TCP connection.
+///
+/// Corresponds to the `MIB_TCP_STATE` values reported by `netstat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsTcpState {
+    /// `CLOSED`.
+    Closed,
+
+    /// `LISTENING`.
+    Listening,
+
+    /// `SYN_SENT`.
+    SynSent,
+
+    /// `SYN_RCVD`.
+    SynReceived,
+
+    /// `ESTABLISHED`.
+    Established,
+
+    /// `FIN_WAIT1`.
+    FinWait1,
+
+    /// `FIN_WAIT2`.
+    FinWait2,
+
+    /// `CLOSE_WAIT`.
+    CloseWait,
+
+    /// `CLOSING`.
+    Closing,
+
+    /// `LAST_ACK`.
+    LastAck,
+
+    /// `TIME_WAIT`.
+    TimeWait,
+
+    /// `DELETE_TCB`.
+    DeleteTcb,
+
+    /// Unknown state.
+    Unknown(u32),
+}
+
+impl From<u32> for WindowsTcpState {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Closed,
+            1 => Self::Listening,
+            2 => Self::SynSent,
+            3 => Self::SynReceived,
+            4 => Self::Established,
+            5 => Self::FinWait1,
+            6 => Self::FinWait2,
+            7 => Self::CloseWait,
+            8 => Self::Closing,
+            9 => Self::LastAck,
+            12 => Self::TimeWait,
+            13 => Self::DeleteTcb,
+            _ => Self::Unknown(value),
+        }
+    }
+}
+
+/// A TCP connection.
+#[derive(Debug, Clone)]
+pub struct WindowsTcpConnection {
+    /// Address of the `_TCP_ENDPOINT` structure.
+    pub endpoint: Va,
+
+    /// Local address.
+    pub local_address: IpAddr,
+
+    /// Local port.
+    pub local_port: u16,
+
+    /// Remote address.
+    pub remote_address: IpAddr,
+
+    /// Remote port.
+    pub remote_port: u16,
+
+    /// State of the connection.
+    pub state: WindowsTcpState,
+
+    /// PID of the owning process.
+    pub owner: Option<ProcessId>,
+
+    /// Creation time, in 100-nanosecond intervals since January 1, 1601.
+    pub create_time: u64,
+}
+
+/// A listening TCP socket.
+#[derive(Debug, Clone)]
+pub struct WindowsTcpListener {
+    /// Address of the `_TCP_LISTENER` structure.
+    pub listener: Va,
+
+    /// Local address.
+    ///
+    /// `None` if the listener is bound to all addresses.
+    pub local_address: Option<IpAddr>,
+
+    /// Local port.
+    pub local_port: u16,
+
+    /// PID of the owning process.
+    pub owner: Option<ProcessId>,
+
+    /// Creation time, in 100-nanosecond intervals since January 1, 1601.
+    pub create_time: u64,
+}
+
+/// A UDP endpoint.
+#[derive(Debug, Clone)]
+pub struct WindowsUdpEndpoint {
+    /// Address of the `_UDP_ENDPOINT` structure.
+    pub endpoint: Va,
+
+    /// Local address.
+    ///
+    /// `None` if the endpoint is bound to all addresses.
+    pub local_address: Option<IpAddr>,
+
+    /// Local port.
+    pub local_port: u16,
+
+    /// PID of the owning process.
+    pub owner: Option<ProcessId>,
+
+    /// Creation time, in 100-nanosecond intervals since January 1, 1601.
+    pub create_time: u64,
+}
@@ -12,11 +12,32 @@ symbols! {
     pub struct Symbols {
         PsActiveProcessHead: u64,
         PsInitialSystemProcess: u64,
+        PspCidTable: Option<u64>,
         PsLoadedModuleList: u64,
+        CmpHiveListHead: Option<u64>,
         KiDispatchException: Option<u64>,
         DbgkpSendErrorMessage: Option<u64>,
 
         KiKvaShadow: Option<u64>,
+        KeLoaderBlock: Option<u64>,
+        KeServiceDescriptorTable: Option<u64>,
+        KeServiceDescriptorTableShadow: Option<u64>,
+        KdDebuggerDataBlock: Option<u64>,
+        KdpDataBlockEncoded: Option<u64>,
+
+        HalDispatchTable: Option<u64>,
+        HalPrivateDispatchTable: Option<u64>,
+
+        KiBugCheckData: Option<u64>,
+
+        HvlEnlightenments: Option<u64>,
+        HvlHypervisorConnected: Option<u64>,
+        HvlpFlags: Option<u64>,
+        HvlpHypercallCodeVa: Option<u64>,
+
+        KiProcessorBlock: u64,
+        KiWaitAlways: Option<u64>,
+        KiWaitNever: Option<u64>,
         KiSystemCall32: u64,
         KiSystemCall64: u64,
         //KiSystemCall32Shadow: u64,
@@ -27,6 +48,15 @@ symbols! {
         MmPfnDatabase: u64,
         MmHighestUserAddress: u64,
 
+        MmAvailablePages: Option<u64>,
+        MmResidentAvailablePages: Option<u64>,
+        MmZeroedPageListHead: Option<u64>,
+        MmFreePageListHead: Option<u64>,
+        MmStandbyPageListByPriority: Option<u64>,
+        MmModifiedPageListHead: Option<u64>,
+        MmModifiedNoWritePageListHead: Option<u64>,
+        MiSystemPartition: Option<u64>,
+
         AlpcpSendMessage: Option<u64>,
 
         MiDeletePartialVad: Option<u64>,
@@ -64,11 +94,22 @@ symbols! {
         ExFreePoolWithTag: u64,
         MmGetSystemRoutineAddress: u64,
 
+        MiState: Option<u64>,
+        SmGlobals: Option<u64>,
+
         ObHeaderCookie: Option<u64>,
         ObTypeIndexTable: u64,
         ObpInfoMaskToOffset: u64,
+        ObpRootDirectoryObject: u64,
+
+        PoolBigPageTable: Option<u64>,
+        PoolBigPageTableSize: Option<u64>,
         ObpKernelHandleTable: u64,
 
+        WmipLoggerContext: Option<u64>,
+        EtwpGuidHashTable: Option<u64>,
+        PspHostSiloGlobals: Option<u64>,
+
         PspInsertProcess: Option<u64>,
         MmCleanProcessAddressSpace: Option<u64>,
     }
@@ -87,6 +128,11 @@ offsets! {
             Value: Field,
         }
 
+        struct _RTL_BITMAP {
+            SizeOfBitMap: Field,            // ULONG
+            Buffer: Field,                  // PULONG
+        }
+
         struct _UNICODE_STRING {
             Length: Field,
             MaximumLength: Field,
@@ -95,10 +141,64 @@ offsets! {
 
         struct _KPCR {
             Prcb: Field,
+            TssBase: Field,                 // PKTSS64
+        }
+
+        struct _KTSS64 {
+            Ist: Field,                     // ULONG64[8]
         }
 
         struct _KPRCB {
             CurrentThread: Field,
+            IdleThread: Field,
+            DpcStack: Field,                // PVOID
+            IsrStack: Option<Field>,        // PVOID (x86 only)
+            TimerTable: Field,              // _KTIMER_TABLE
+        }
+
+        struct _KTIMER_TABLE {
+            TimerEntries: Field,            // _KTIMER_TABLE_ENTRY[256] (Windows 7/8)
+                                            // _KTIMER_TABLE_ENTRY[2][256] (Windows 10+)
+        }
+
+        struct _KTIMER_TABLE_ENTRY {
+            Entry: Field,                   // _LIST_ENTRY
+        }
+
+        struct _KTIMER {
+            DueTime: Field,                 // _ULARGE_INTEGER
+            TimerListEntry: Field,          // _LIST_ENTRY
+            Dpc: Field,                     // _KDPC* (encoded on Windows 8+ x64)
+            Period: Field,                  // ULONG
+        }
+
+        struct _KDPC {
+            DeferredRoutine: Field,         // PKDEFERRED_ROUTINE
+            DeferredContext: Field,         // PVOID
+        }
+
+        struct _HHIVE {
+            Signature: Field,               // ULONG
+            BaseBlock: Field,               // _HBASE_BLOCK*
+            Storage: Field,                 // _DUAL[2]
+        }
+
+        struct _DUAL {
+            Map: Field,                     // _HMAP_DIRECTORY*
+        }
+
+        struct _HMAP_ENTRY {
+            BlockAddress: Option<Field>,        // ULONG_PTR (up to Windows 8.1)
+            BlockOffset: Option<Field>,         // ULONG_PTR (Windows 10+)
+            PermanentBinAddress: Option<Field>, // ULONG_PTR (Windows 10+)
+        }
+
+        struct _CMHIVE {
+            // Hive: Field,                 // _HHIVE (always at offset 0)
+            HiveList: Field,                // _LIST_ENTRY
+            FileFullPath: Field,            // _UNICODE_STRING
+            FileUserName: Field,            // _UNICODE_STRING
+            HiveRootPath: Option<Field>,    // _UNICODE_STRING (Windows Vista+)
         }
 
         #[isr(alias = "_LDR_DATA_TABLE_ENTRY")]
@@ -148,6 +248,30 @@ offsets! {
 
         struct _OBJECT_TYPE {
             Name: Field,
+            TotalNumberOfObjects: Field,
+            TotalNumberOfHandles: Field,
+            HighWaterNumberOfObjects: Field,
+            HighWaterNumberOfHandles: Field,
+        }
+
+        struct _POOL_TRACKER_BIG_PAGES {
+            Va: Field,                      // PVOID (bit 0 set if free)
+            Key: Field,                     // ULONG (pool tag)
+            NumberOfBytes: Field,           // SIZE_T
+        }
+
+        struct _OBJECT_DIRECTORY {
+            HashBuckets: Field,             // _OBJECT_DIRECTORY_ENTRY*[37]
+        }
+
+        struct _OBJECT_SYMBOLIC_LINK {
+            LinkTarget: Field,              // _UNICODE_STRING
+            Flags: Option<Field>,           // ULONG (Windows 10+)
+        }
+
+        struct _OBJECT_DIRECTORY_ENTRY {
+            ChainLink: Field,               // _OBJECT_DIRECTORY_ENTRY*
+            Object: Field,                  // PVOID
         }
 
         struct _MMSECTION_FLAGS {
@@ -164,27 +288,59 @@ offsets! {
             R10: Field,
             R11: Field,
 
+            Rbx: Field,
+            Rdi: Field,
+            Rsi: Field,
+            Rbp: Field,
+
             Rip: Field,
             Rsp: Field,
+            EFlags: Field,                  // ULONG
+            SegCs: Field,                   // USHORT
+            SegSs: Field,                   // USHORT
         }
 
         struct _KAPC_STATE {
+            ApcListHead: Field,             // _LIST_ENTRY[2]
             Process: Field,
         }
 
+        struct _KAPC {
+            ApcListEntry: Field,            // _LIST_ENTRY
+            KernelRoutine: Field,           // PKKERNEL_ROUTINE
+            RundownRoutine: Field,          // PKRUNDOWN_ROUTINE
+            NormalRoutine: Field,           // PKNORMAL_ROUTINE
+            NormalContext: Field,           // PVOID
+            SystemArgument1: Field,         // PVOID
+            SystemArgument2: Field,         // PVOID
+        }
+
         struct _KTHREAD {
+            InitialStack: Field,
+            StackLimit: Field,
+            StackBase: Field,
+            KernelStack: Field,
+            ThreadListEntry: Field,         // _LIST_ENTRY
             TrapFrame: Field,
             ApcState: Field,
             Teb: Field,
+            State: Field,                   // UCHAR (_KTHREAD_STATE)
+            WaitReason: Field,              // UCHAR (_KWAIT_REASON)
             Process: Field,
         }
 
+        struct _KSWITCH_FRAME {
+            Rbp: Option<Field>,             // ULONG64 (Windows 8+)
+            Return: Field,                  // ULONG64
+        }
+
         struct _ETHREAD {
             Cid: Field,
         }
 
         struct _KPROCESS {
             DirectoryTableBase: Field,
+            ThreadListHead: Field,          // _LIST_ENTRY
             UserDirectoryTableBase: Option<Field>,
         }
 
@@ -201,6 +357,42 @@ offsets! {
             VadRoot: Field,                 // _MM_AVL_TABLE (Windows 7, contains BalancedRoot at offset 0)
                                             // _RTL_AVL_TREE (Windows 10+)
             VadHint: Option<Field>,         // PVOID (Windows 10+, _MM_AVL_TABLE.NodeHint on Windows 7)
+            PicoContext: Option<Field>,     // PVOID (Windows 10+)
+            Minimal: Option<Bitfield>,      // ULONG bitfield inside Flags3 (Windows 10+)
+            Vm: Field,                      // _MMSUPPORT (up to Windows 10 1703)
+                                            // _MMSUPPORT_FULL (Windows 10 1709+, contains Instance at offset 0)
+            Session: Field,                 // PVOID (_MM_SESSION_SPACE*)
+            SessionProcessLinks: Field,     // _LIST_ENTRY
+        }
+
+        struct _MM_SESSION_SPACE {
+            SessionId: Field,               // ULONG
+            ProcessList: Field,             // _LIST_ENTRY
+        }
+
+        #[isr(alias = "_MMSUPPORT_INSTANCE")]
+        struct _MMSUPPORT {
+            PageFaultCount: Field,          // ULONG
+            PeakWorkingSetSize: Field,      // ULONG (Windows 7), ULONG_PTR (Windows 8+)
+            WorkingSetSize: Field,          // ULONG (Windows 7), ULONG_PTR (Windows 8+)
+        }
+
+        struct _KSYSTEM_TIME {
+            LowPart: Field,                 // ULONG
+            High1Time: Field,               // LONG
+            High2Time: Field,               // LONG
+        }
+
+        struct _KUSER_SHARED_DATA {
+            TimeZoneBias: Field,            // _KSYSTEM_TIME
+        }
+
+        struct _WMI_LOGGER_CONTEXT {
+            LoggerId: Field,                // ULONG
+            LoggerMode: Field,              // ULONG
+            AcceptNewEvents: Field,         // LONG
+            LoggerName: Field,              // _UNICODE_STRING
+            EnableFlags: Field,             // ULONG
         }
 
         struct _PEB {
@@ -209,6 +401,10 @@ offsets! {
             ProcessParameters: Field,       // _RTL_USER_PROCESS_PARAMETERS*
         }
 
+        struct _PEB_LDR_DATA {
+            InLoadOrderModuleList: Field,   // _LIST_ENTRY
+        }
+
         struct _TEB {
             LastErrorValue: Field,
             LastStatusValue: Field,
@@ -219,6 +415,8 @@ offsets! {
             DllPath: Field,                 // _UNICODE_STRING
             ImagePathName: Field,           // _UNICODE_STRING
             CommandLine: Field,             // _UNICODE_STRING
+            Environment: Field,             // PVOID
+            EnvironmentSize: Option<Field>, // SIZE_T (Windows Vista+)
         }
 
         struct _CURDIR {
@@ -243,6 +441,31 @@ offsets! {
 
             e1: Field,
             PageLocation: Bitfield,
+
+            PteAddress: Field,              // _MMPTE*
+            u2: Field,                      // contains ShareCount
+            u4: Field,                      // contains PteFrame
+            PteFrame: Bitfield,             // ULONG_PTR bitfield
+        }
+
+        //
+        // The layout of the invalid PTEs differs between Windows versions.
+        // For example, `PageFileLow` occupies bits 1-4 on Windows 7, but
+        // bits 12-15 on Windows 10+.
+        //
+
+        struct _MMPTE_SOFTWARE {
+            Protection: Bitfield,           // ULONG64 bitfield (5 bits)
+            Prototype: Bitfield,            // ULONG64 bitfield (1 bit)
+            Transition: Bitfield,           // ULONG64 bitfield (1 bit)
+            PageFileLow: Bitfield,          // ULONG64 bitfield (4 bits)
+            PageFileHigh: Bitfield,         // ULONG64 bitfield (32 bits)
+        }
+
+        struct _MMPTE_TRANSITION {
+            Prototype: Bitfield,            // ULONG64 bitfield (1 bit)
+            Transition: Bitfield,           // ULONG64 bitfield (1 bit)
+            PageFrameNumber: Bitfield,      // ULONG64 bitfield
         }
 
         struct _MMVAD_FLAGS {
@@ -280,10 +503,13 @@ offsets! {
         struct _MMVAD {
             // Core: Field,                 // _MMVAD_SHORT (always at offset 0)
             Subsection: Field,              // _SUBSECTION*
+            FirstPrototypePte: Field,       // _MMPTE*
         }
 
         struct _SUBSECTION {
             ControlArea: Field,
+            SubsectionBase: Field,          // _MMPTE*
+            StartingSector: Field,          // ULONG
         }
 
         struct _CONTROL_AREA {
@@ -311,6 +537,140 @@ offsets! {
     }
 }
 
+offsets! {
+    /// Offsets of the memory partitions (Windows 10+).
+    ///
+    /// Kept apart from the [`v2`] offsets, so that a profile without these
+    /// structures only disables the features that need them.
+    #[derive(Debug)]
+    pub struct PartitionOffsets {
+        struct _MI_PARTITION {
+            PageLists: Field,               // _MI_PARTITION_PAGE_LISTS
+            Vp: Field,                      // _MI_VISIBLE_PARTITION
+        }
+
+        struct _MI_PARTITION_PAGE_LISTS {
+            ZeroedPageListHead: Option<Field>,              // _MMPFNLIST
+            FreePageListHead: Option<Field>,                // _MMPFNLIST
+            StandbyPageListByPriority: Option<Field>,       // _MMPFNLIST[8]
+            ModifiedPageListNoReservation: Option<Field>,   // _MMPFNLIST[]
+            ModifiedPageListByReservation: Option<Field>,   // _MMPFNLIST[]
+            ModifiedNoWritePageListHead: Option<Field>,     // _MMPFNLIST or _MMPFNLIST[]
+        }
+
+        struct _MI_VISIBLE_PARTITION {
+            AvailablePages: Option<Field>,          // PFN_NUMBER
+            ResidentAvailablePages: Option<Field>,  // PFN_NUMBER
+            PagingFiles: Option<Field>,             // _MMPAGING_FILE*[16]
+        }
+
+        struct _MMPAGING_FILE {
+            PageFileNumber: Bitfield,               // USHORT bitfield (4 bits)
+            VirtualStorePagefile: Option<Bitfield>, // USHORT bitfield (1 bit)
+        }
+    }
+}
+
+offsets! {
+    /// Offsets of the state of the host server silo (Windows 10 1607+).
+    #[derive(Debug)]
+    pub struct SiloOffsets {
+        struct _ESERVERSILO_GLOBALS {
+            EtwSiloState: Field,            // _ETW_SILODRIVERSTATE*
+        }
+
+        struct _ETW_SILODRIVERSTATE {
+            MaxLoggers: Field,                // ULONG
+            EtwpLoggerContext: Field,         // _WMI_LOGGER_CONTEXT**
+            EtwpGuidHashTable: Option<Field>, // _ETW_HASH_BUCKET[64]
+        }
+    }
+}
+
+offsets! {
+    /// Offsets of the memory manager state (`MiState`, Windows 10+).
+    ///
+    /// Kept apart from the common offsets, so that a profile without these
+    /// structures only disables the features that depend on them.
+    #[derive(Debug)]
+    pub struct MiStateOffsets {
+        struct _MI_SYSTEM_INFORMATION {
+            Hardware: Field,                // _MI_HARDWARE_STATE
+            Vs: Field,                      // _MI_VISIBLE_STATE
+        }
+
+        struct _MI_HARDWARE_STATE {
+            InvalidPteMask: Option<Field>,  // ULONGLONG (Windows 10 1803+)
+        }
+
+        struct _MI_VISIBLE_STATE {
+            CfgBitMap: Option<Field>,       // _MI_CFG_BITMAP_INFO[]
+        }
+
+        struct _MI_CFG_BITMAP_INFO {
+            BaseAddress: Field,             // PVOID
+            RegionSize: Field,              // ULONG_PTR
+        }
+    }
+}
+
+offsets! {
+    /// Offsets of the ETW provider registrations.
+    ///
+    /// Kept apart from the common offsets, so that a profile without these
+    /// structures only disables [`WindowsOs::etw_providers`].
+    ///
+    /// [`WindowsOs::etw_providers`]: crate::WindowsOs::etw_providers
+    #[derive(Debug)]
+    pub struct EtwProviderOffsets {
+        struct _ETW_HASH_BUCKET {
+            ListHead: Field,                // _LIST_ENTRY[3]
+        }
+
+        struct _ETW_GUID_ENTRY {
+            GuidList: Field,                // _LIST_ENTRY
+            Guid: Field,                    // _GUID
+            ProviderEnableInfo: Field,      // _TRACE_ENABLE_INFO
+            EnableInfo: Field,              // _TRACE_ENABLE_INFO[8]
+        }
+
+        struct _TRACE_ENABLE_INFO {
+            IsEnabled: Field,               // ULONG
+            Level: Field,                   // UCHAR
+            LoggerId: Field,                // USHORT
+            EnableProperty: Field,          // ULONG
+            MatchAnyKeyword: Field,         // ULONGLONG
+            MatchAllKeyword: Field,         // ULONGLONG
+        }
+    }
+}
+
+offsets! {
+    /// Offsets of the boot loader parameter block.
+    ///
+    /// Kept apart from the common offsets, so that a profile without these
+    /// structures only disables [`WindowsOs::loader_block`].
+    ///
+    /// [`WindowsOs::loader_block`]: crate::WindowsOs::loader_block
+    #[derive(Debug)]
+    pub struct LoaderBlockOffsets {
+        struct _LOADER_PARAMETER_BLOCK {
+            LoadOrderListHead: Field,       // _LIST_ENTRY
+            BootDriverListHead: Field,      // _LIST_ENTRY
+            ArcBootDeviceName: Field,       // PCHAR
+            LoadOptions: Field,             // PCHAR
+            NlsData: Field,                 // _NLS_DATA_BLOCK*
+        }
+
+        struct _BOOT_DRIVER_LIST_ENTRY {
+            Link: Field,                    // _LIST_ENTRY
+            FilePath: Field,                // _UNICODE_STRING
+            RegistryPath: Field,            // _UNICODE_STRING
+            LdrEntry: Field,                // _KLDR_DATA_TABLE_ENTRY*
+        }
+    }
+}
+
 /// Extended offsets for Windows.
 pub enum OffsetsExt {
     /// First version of extended offsets.
@@ -331,6 +691,23 @@ pub struct Offsets {
 
     /// Extended offsets specific to the Windows version.
     pub ext: Option<OffsetsExt>,
+
+    /// Offsets of the memory partitions, if present in the profile.
+    pub partition: Option<PartitionOffsets>,
+
+    /// Offsets of the host server silo state, if present in the profile.
+    pub silo: Option<SiloOffsets>,
+
+    /// Offsets of the ETW provider registrations, if present in the
+    /// profile.
+    pub etw_provider: Option<EtwProviderOffsets>,
+
+    /// Offsets of the memory manager state, if present in the profile.
+    pub mi_state: Option<MiStateOffsets>,
+
+    /// Offsets of the boot loader parameter block, if present in the
+    /// profile.
+    pub loader_block: Option<LoaderBlockOffsets>,
 }
 
 impl Offsets {
@@ -347,6 +724,20 @@ impl Offsets {
             None
         };
 
-        Ok(Self { common, ext })
+        let partition = PartitionOffsets::new(profile).ok();
+        let silo = SiloOffsets::new(profile).ok();
+        let etw_provider = EtwProviderOffsets::new(profile).ok();
+        let mi_state = MiStateOffsets::new(profile).ok();
+        let loader_block = LoaderBlockOffsets::new(profile).ok();
+
+        Ok(Self {
+            common,
+            ext,
+            partition,
+            silo,
+            etw_provider,
+            mi_state,
+            loader_block,
+        })
     }
 }
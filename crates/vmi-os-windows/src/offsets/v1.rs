@@ -37,5 +37,9 @@ offsets! {
             RightChild: Field,
         }
 
+        struct _MMPFNLIST {
+            Total: Field,                   // PFN_NUMBER
+        }
+
     }
 }
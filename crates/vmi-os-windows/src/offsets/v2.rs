@@ -29,6 +29,12 @@ offsets! {
             Root: Field,                    // _RTL_BALANCED_NODE*
         }
 
+        struct _RTL_DYNAMIC_HASH_TABLE {
+            TableSize: Field,               // ULONG
+            NumEntries: Field,              // ULONG
+            Directory: Field,               // PVOID
+        }
+
         struct _RTL_BALANCED_NODE {
             Left: Field,                    // _RTL_BALANCED_NODE*
             Right: Field,                   // _RTL_BALANCED_NODE*
@@ -38,5 +44,9 @@ offsets! {
             MemCommit: Bitfield,            // ULONG bitfield (1 bit, might be in _MMVAD_FLAGS1)
         }
 
+        struct _MMPFNLIST {
+            Total: Field,                   // PFN_NUMBER
+        }
+
     }
 }
@@ -113,10 +113,11 @@ where
         // Note that the path is located after the `CvInfoPdb70` struct.
         //
 
-        let (info, pdb_path) = info_data.split_at(size_of::<CvInfoPdb70>());
-
-        let info = match CvInfoPdb70::ref_from_bytes(info) {
-            Ok(info) => info,
+        // The struct is copied out of the buffer, so that the buffer doesn't
+        // need to be aligned. The debug directory comes from the guest, so
+        // the data might also be too short.
+        let (info, pdb_path) = match CvInfoPdb70::read_from_prefix(&info_data) {
+            Ok(result) => result,
             Err(err) => {
                 tracing::warn!(?err, "Invalid CodeView Info address");
                 continue;
@@ -36,4 +36,16 @@ pub enum PeError {
     /// Invalid export table.
     #[error("Invalid export table")]
     InvalidExportTable,
+
+    /// Invalid load configuration directory.
+    #[error("Invalid load configuration directory")]
+    InvalidLoadConfig,
+
+    /// Invalid exception table.
+    #[error("Invalid exception table")]
+    InvalidExceptionTable,
+
+    /// Invalid unwind information.
+    #[error("Invalid unwind information")]
+    InvalidUnwindInfo,
 }
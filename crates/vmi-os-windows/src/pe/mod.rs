@@ -1,11 +1,13 @@
 pub(super) mod codeview;
 mod error;
+mod unwind;
 
 use object::{
     endian::LittleEndian as LE,
     pe::{
-        ImageDataDirectory, ImageDosHeader, ImageNtHeaders32, ImageNtHeaders64,
-        IMAGE_DIRECTORY_ENTRY_EXPORT, IMAGE_DOS_SIGNATURE, IMAGE_NT_SIGNATURE,
+        ImageDataDirectory, ImageDosHeader, ImageLoadConfigDirectory32, ImageLoadConfigDirectory64,
+        ImageNtHeaders32, ImageNtHeaders64, IMAGE_DIRECTORY_ENTRY_EXPORT, IMAGE_DOS_SIGNATURE,
+        IMAGE_NT_SIGNATURE,
     },
     read::{
         pe::{Export, ExportTable, ImageNtHeaders, ImageOptionalHeader},
@@ -13,7 +15,11 @@ use object::{
     },
 };
 
-pub use self::{codeview::CodeView, error::PeError};
+pub use self::{
+    codeview::CodeView,
+    error::PeError,
+    unwind::{PeRuntimeFunction, PeUnwindCode, PeUnwindInfo, PeUnwindOperation},
+};
 
 /// A lightweight Portable Executable (PE) file parser.
 ///
@@ -33,6 +39,31 @@ where
     pub data_directories: &'a [ImageDataDirectory],
 }
 
+/// Control Flow Guard information from the load configuration directory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PeGuardConfig {
+    /// The `GuardFlags` field.
+    pub guard_flags: u32,
+
+    /// The `GuardCFCheckFunctionPointer` field.
+    ///
+    /// Virtual address of the pointer to the CFG check function.
+    pub check_function_pointer: u64,
+
+    /// The `GuardCFDispatchFunctionPointer` field.
+    ///
+    /// Virtual address of the pointer to the CFG dispatch function.
+    pub dispatch_function_pointer: u64,
+
+    /// The `GuardCFFunctionTable` field.
+    ///
+    /// Virtual address of the table of valid call target RVAs.
+    pub function_table: u64,
+
+    /// The `GuardCFFunctionCount` field.
+    pub function_count: u64,
+}
+
 /// Type alias for 32-bit PE files.
 pub type PeLite32<'a> = PeLite<'a, ImageNtHeaders32>;
 
@@ -114,4 +145,52 @@ where
             .exports()
             .map_err(|_| PeError::InvalidExportTable)
     }
+
+    /// Extracts the Control Flow Guard information from the load
+    /// configuration directory.
+    ///
+    /// The `data` must contain the load configuration directory. Older
+    /// images have a shorter directory, in which case the missing fields
+    /// are treated as zero.
+    pub fn guard_config(&self, data: &[u8]) -> Result<PeGuardConfig, PeError> {
+        if self.nt_headers.is_type_64() {
+            let mut buffer = [0u8; size_of::<ImageLoadConfigDirectory64>()];
+            let length = data.len().min(buffer.len());
+            buffer[..length].copy_from_slice(&data[..length]);
+
+            let load_config = buffer
+                .as_ref()
+                .read_at::<ImageLoadConfigDirectory64>(0)
+                .map_err(|_| PeError::InvalidLoadConfig)?;
+
+            Ok(PeGuardConfig {
+                guard_flags: load_config.guard_flags.get(LE),
+                check_function_pointer: load_config.guard_cf_check_function_pointer.get(LE),
+                dispatch_function_pointer: load_config.guard_cf_dispatch_function_pointer.get(LE),
+                function_table: load_config.guard_cf_function_table.get(LE),
+                function_count: load_config.guard_cf_function_count.get(LE),
+            })
+        }
+        else {
+            let mut buffer = [0u8; size_of::<ImageLoadConfigDirectory32>()];
+            let length = data.len().min(buffer.len());
+            buffer[..length].copy_from_slice(&data[..length]);
+
+            let load_config = buffer
+                .as_ref()
+                .read_at::<ImageLoadConfigDirectory32>(0)
+                .map_err(|_| PeError::InvalidLoadConfig)?;
+
+            Ok(PeGuardConfig {
+                guard_flags: load_config.guard_flags.get(LE),
+                check_function_pointer: load_config.guard_cf_check_function_pointer.get(LE).into(),
+                dispatch_function_pointer: load_config
+                    .guard_cf_dispatch_function_pointer
+                    .get(LE)
+                    .into(),
+                function_table: load_config.guard_cf_function_table.get(LE).into(),
+                function_count: load_config.guard_cf_function_count.get(LE).into(),
+            })
+        }
+    }
 }
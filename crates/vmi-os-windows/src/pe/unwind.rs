@@ -0,0 +1,350 @@
+//! Exception directory (`.pdata`) and unwind information of x64 images.
+
+use object::{endian::LittleEndian as LE, pe::ImageRuntimeFunctionEntry, read::ReadRef as _};
+
+use super::PeError;
+
+/// The `UNW_FLAG_EHANDLER` flag.
+const UNW_FLAG_EHANDLER: u8 = 0x1;
+
+/// The `UNW_FLAG_UHANDLER` flag.
+const UNW_FLAG_UHANDLER: u8 = 0x2;
+
+/// The `UNW_FLAG_CHAININFO` flag.
+const UNW_FLAG_CHAININFO: u8 = 0x4;
+
+/// Size of the fixed part of the `UNWIND_INFO` structure.
+const UNWIND_INFO_HEADER_SIZE: usize = 4;
+
+/// An entry of the exception directory (`RUNTIME_FUNCTION`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeRuntimeFunction {
+    /// The `BeginAddress` field.
+    ///
+    /// RVA of the start of the function.
+    pub begin_address: u32,
+
+    /// The `EndAddress` field.
+    ///
+    /// RVA of the end of the function (exclusive).
+    pub end_address: u32,
+
+    /// The `UnwindData` field.
+    ///
+    /// RVA of the `UNWIND_INFO` structure of the function.
+    pub unwind_info_address: u32,
+}
+
+impl PeRuntimeFunction {
+    /// Size of a `RUNTIME_FUNCTION` entry.
+    pub const SIZE: usize = size_of::<ImageRuntimeFunctionEntry>();
+
+    /// Parses a `RUNTIME_FUNCTION` entry from raw bytes.
+    pub fn parse(data: &[u8]) -> Result<Self, PeError> {
+        let entry = data
+            .read_at::<ImageRuntimeFunctionEntry>(0)
+            .map_err(|_| PeError::InvalidExceptionTable)?;
+
+        Ok(Self {
+            begin_address: entry.begin_address.get(LE),
+            end_address: entry.end_address.get(LE),
+            unwind_info_address: entry.unwind_info_address_or_data.get(LE),
+        })
+    }
+
+    /// Checks whether the function contains the given RVA.
+    pub fn contains(&self, rva: u32) -> bool {
+        self.begin_address <= rva && rva < self.end_address
+    }
+}
+
+/// A decoded unwind operation (`UNWIND_CODE`).
+///
+/// Register numbers follow the x64 encoding: `0` is `RAX`, `1` is `RCX`,
+/// `2` is `RDX`, `3` is `RBX`, `4` is `RSP`, `5` is `RBP`, `6` is `RSI`,
+/// `7` is `RDI` and `8` to `15` are `R8` to `R15`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeUnwindOperation {
+    /// `UWOP_PUSH_NONVOL`: a non-volatile register was pushed.
+    PushNonvol {
+        /// The pushed register.
+        register: u8,
+    },
+
+    /// `UWOP_ALLOC_LARGE` or `UWOP_ALLOC_SMALL`: an area was allocated on
+    /// the stack.
+    Alloc {
+        /// The size of the allocation, in bytes.
+        size: u32,
+    },
+
+    /// `UWOP_SET_FPREG`: the frame pointer register was established.
+    SetFpreg,
+
+    /// `UWOP_SAVE_NONVOL` or `UWOP_SAVE_NONVOL_FAR`: a non-volatile
+    /// register was saved on the stack using a `MOV`.
+    SaveNonvol {
+        /// The saved register.
+        register: u8,
+
+        /// The offset of the saved value from the stack pointer.
+        offset: u32,
+    },
+
+    /// `UWOP_SAVE_XMM128` or `UWOP_SAVE_XMM128_FAR`: all 128 bits of
+    /// a non-volatile XMM register were saved on the stack.
+    SaveXmm128 {
+        /// The saved XMM register.
+        register: u8,
+
+        /// The offset of the saved value from the stack pointer.
+        offset: u32,
+    },
+
+    /// `UWOP_PUSH_MACHFRAME`: a hardware interrupt or exception frame was
+    /// pushed.
+    PushMachframe {
+        /// Whether the frame includes an error code.
+        error_code: bool,
+    },
+
+    /// `UWOP_EPILOG` (version 2): describes an epilog, has no effect on
+    /// unwinding the prolog.
+    Epilog,
+}
+
+/// An unwind operation together with its position in the prolog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PeUnwindCode {
+    /// The `CodeOffset` field.
+    ///
+    /// Offset from the start of the prolog to the end of the instruction
+    /// that performs the operation.
+    pub code_offset: u8,
+
+    /// The decoded operation.
+    pub operation: PeUnwindOperation,
+}
+
+/// The unwind information of a function (`UNWIND_INFO`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeUnwindInfo {
+    /// The `Version` field.
+    pub version: u8,
+
+    /// The `Flags` field (`UNW_FLAG_*`).
+    pub flags: u8,
+
+    /// The `SizeOfProlog` field.
+    pub size_of_prolog: u8,
+
+    /// The `FrameRegister` field.
+    ///
+    /// Zero if the function does not use a frame pointer.
+    pub frame_register: u8,
+
+    /// The `FrameOffset` field.
+    ///
+    /// Scaled offset from the stack pointer to the frame pointer, in units
+    /// of 16 bytes.
+    pub frame_offset: u8,
+
+    /// The unwind operations, in the reverse order of the prolog
+    /// instructions.
+    pub codes: Vec<PeUnwindCode>,
+
+    /// The function whose unwind information continues this one, if the
+    /// `UNW_FLAG_CHAININFO` flag is set.
+    pub chained: Option<PeRuntimeFunction>,
+
+    /// RVA of the exception handler, if the `UNW_FLAG_EHANDLER` or
+    /// `UNW_FLAG_UHANDLER` flag is set.
+    pub exception_handler: Option<u32>,
+}
+
+impl PeUnwindInfo {
+    /// Parses an `UNWIND_INFO` structure from raw bytes.
+    ///
+    /// The `data` must contain the unwind codes and the trailing chained
+    /// function entry or exception handler RVA, if present.
+    pub fn parse(data: &[u8]) -> Result<Self, PeError> {
+        let header = data
+            .get(..UNWIND_INFO_HEADER_SIZE)
+            .ok_or(PeError::InvalidUnwindInfo)?;
+
+        let version = header[0] & 0x7;
+        let flags = header[0] >> 3;
+        let size_of_prolog = header[1];
+        let count_of_codes = header[2] as usize;
+        let frame_register = header[3] & 0xf;
+        let frame_offset = header[3] >> 4;
+
+        if version != 1 && version != 2 {
+            return Err(PeError::InvalidUnwindInfo);
+        }
+
+        let slots = data
+            .get(UNWIND_INFO_HEADER_SIZE..UNWIND_INFO_HEADER_SIZE + count_of_codes * 2)
+            .ok_or(PeError::InvalidUnwindInfo)?
+            .chunks_exact(2)
+            .map(|slot| u16::from_le_bytes([slot[0], slot[1]]))
+            .collect::<Vec<_>>();
+
+        let codes = parse_unwind_codes(version, &slots)?;
+
+        //
+        // The array of unwind codes is padded to an even number of slots.
+        // It is followed either by the chained function entry, or by the
+        // RVA of the exception handler.
+        //
+
+        let trailer = UNWIND_INFO_HEADER_SIZE + count_of_codes.next_multiple_of(2) * 2;
+
+        let mut chained = None;
+        let mut exception_handler = None;
+
+        if flags & UNW_FLAG_CHAININFO != 0 {
+            let data = data.get(trailer..).ok_or(PeError::InvalidUnwindInfo)?;
+            chained = Some(PeRuntimeFunction::parse(data).map_err(|_| PeError::InvalidUnwindInfo)?);
+        }
+        else if flags & (UNW_FLAG_EHANDLER | UNW_FLAG_UHANDLER) != 0 {
+            let handler = data
+                .get(trailer..trailer + size_of::<u32>())
+                .ok_or(PeError::InvalidUnwindInfo)?;
+            exception_handler = Some(u32::from_le_bytes([
+                handler[0], handler[1], handler[2], handler[3],
+            ]));
+        }
+
+        Ok(Self {
+            version,
+            flags,
+            size_of_prolog,
+            frame_register,
+            frame_offset,
+            codes,
+            chained,
+            exception_handler,
+        })
+    }
+}
+
+/// Decodes the unwind operations from the array of `UNWIND_CODE` slots.
+///
+/// Some operations occupy more than one slot; the additional slots hold
+/// their operands.
+fn parse_unwind_codes(version: u8, slots: &[u16]) -> Result<Vec<PeUnwindCode>, PeError> {
+    const UWOP_PUSH_NONVOL: u8 = 0;
+    const UWOP_ALLOC_LARGE: u8 = 1;
+    const UWOP_ALLOC_SMALL: u8 = 2;
+    const UWOP_SET_FPREG: u8 = 3;
+    const UWOP_SAVE_NONVOL: u8 = 4;
+    const UWOP_SAVE_NONVOL_FAR: u8 = 5;
+    const UWOP_EPILOG: u8 = 6;
+    const UWOP_SPARE_CODE: u8 = 7;
+    const UWOP_SAVE_XMM128: u8 = 8;
+    const UWOP_SAVE_XMM128_FAR: u8 = 9;
+    const UWOP_PUSH_MACHFRAME: u8 = 10;
+
+    let operand = |index: usize| -> Result<u32, PeError> {
+        slots
+            .get(index)
+            .map(|&slot| slot as u32)
+            .ok_or(PeError::InvalidUnwindInfo)
+    };
+
+    let operand32 = |index: usize| -> Result<u32, PeError> {
+        Ok(operand(index)? | (operand(index + 1)? << 16))
+    };
+
+    let mut result = Vec::new();
+    let mut index = 0;
+
+    while index < slots.len() {
+        let slot = slots[index];
+        let code_offset = slot as u8;
+        let unwind_op = ((slot >> 8) & 0xf) as u8;
+        let op_info = (slot >> 12) as u8;
+
+        let (operation, slot_count) = match unwind_op {
+            UWOP_PUSH_NONVOL => (PeUnwindOperation::PushNonvol { register: op_info }, 1),
+            UWOP_ALLOC_LARGE if op_info == 0 => (
+                PeUnwindOperation::Alloc {
+                    size: operand(index + 1)? * 8,
+                },
+                2,
+            ),
+            UWOP_ALLOC_LARGE if op_info == 1 => (
+                PeUnwindOperation::Alloc {
+                    size: operand32(index + 1)?,
+                },
+                3,
+            ),
+            UWOP_ALLOC_SMALL => (
+                PeUnwindOperation::Alloc {
+                    size: op_info as u32 * 8 + 8,
+                },
+                1,
+            ),
+            UWOP_SET_FPREG => (PeUnwindOperation::SetFpreg, 1),
+            UWOP_SAVE_NONVOL => (
+                PeUnwindOperation::SaveNonvol {
+                    register: op_info,
+                    offset: operand(index + 1)? * 8,
+                },
+                2,
+            ),
+            UWOP_SAVE_NONVOL_FAR => (
+                PeUnwindOperation::SaveNonvol {
+                    register: op_info,
+                    offset: operand32(index + 1)?,
+                },
+                3,
+            ),
+            UWOP_EPILOG if version >= 2 => (PeUnwindOperation::Epilog, 1),
+            UWOP_SAVE_XMM128 => (
+                PeUnwindOperation::SaveXmm128 {
+                    register: op_info,
+                    offset: operand(index + 1)? * 16,
+                },
+                2,
+            ),
+            UWOP_SAVE_XMM128_FAR => (
+                PeUnwindOperation::SaveXmm128 {
+                    register: op_info,
+                    offset: operand32(index + 1)?,
+                },
+                3,
+            ),
+            UWOP_PUSH_MACHFRAME => (
+                PeUnwindOperation::PushMachframe {
+                    error_code: op_info != 0,
+                },
+                1,
+            ),
+
+            // Version 1 `UWOP_SAVE_XMM` / `UWOP_SAVE_XMM_FAR` and
+            // `UWOP_SPARE_CODE` have no effect on the integer registers,
+            // only their operand slots need to be skipped.
+            UWOP_EPILOG => {
+                index += 2;
+                continue;
+            }
+            UWOP_SPARE_CODE => {
+                index += 3;
+                continue;
+            }
+
+            _ => return Err(PeError::InvalidUnwindInfo),
+        };
+
+        result.push(PeUnwindCode {
+            code_offset,
+            operation,
+        });
+
+        index += slot_count;
+    }
+
+    Ok(result)
+}
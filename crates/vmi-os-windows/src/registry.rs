@@ -0,0 +1,325 @@
+//! Cells of the registry hive format.
+//!
+//! The layout of the cells is part of the on-disk hive format, and is
+//! therefore the same across Windows builds. Only the structures used to
+//! map the cells into memory (`_HHIVE`, `_HMAP_ENTRY`) come from the
+//! kernel symbols.
+
+/// Size of the header preceding the data of every cell.
+///
+/// The header holds the size of the cell; negative for allocated cells.
+pub(crate) const HCELL_HEADER_SIZE: u64 = 4;
+
+/// Offset of the `RootCell` field in the `_HBASE_BLOCK`.
+pub(crate) const HBASE_BLOCK_ROOT_CELL: u64 = 0x24;
+
+/// Offset of the `Minor` field in the `_HBASE_BLOCK`.
+pub(crate) const HBASE_BLOCK_MINOR: u64 = 0x18;
+
+/// The `HCELL_NIL` cell index.
+pub(crate) const HCELL_NIL: u32 = 0xffff_ffff;
+
+/// The `KEY_SYM_LINK` flag of the `_CM_KEY_NODE`.
+const KEY_SYM_LINK: u16 = 0x0010;
+
+/// The `KEY_COMP_NAME` flag of the `_CM_KEY_NODE`.
+const KEY_COMP_NAME: u16 = 0x0020;
+
+/// The `VALUE_COMP_NAME` flag of the `_CM_KEY_VALUE`.
+const VALUE_COMP_NAME: u16 = 0x0001;
+
+/// The `CM_KEY_VALUE_SPECIAL_SIZE` bit of the `DataLength` field.
+///
+/// When set, the data (at most 4 bytes) is stored directly in the `Data`
+/// field instead of a separate cell.
+const CM_KEY_VALUE_SPECIAL_SIZE: u32 = 0x8000_0000;
+
+/// Largest data size that is stored in a single cell.
+///
+/// Larger data is split into segments referenced by a `_CM_BIG_DATA`
+/// cell, if the hive is of version 1.4 or later.
+pub(crate) const CM_KEY_VALUE_BIG: u32 = 0x3fd8;
+
+/// Minor version of the hive format that introduced `_CM_BIG_DATA`.
+pub(crate) const HSYS_WHISTLER_BETA1: u32 = 4;
+
+const REG_SZ: u32 = 1;
+const REG_EXPAND_SZ: u32 = 2;
+const REG_BINARY: u32 = 3;
+const REG_DWORD: u32 = 4;
+const REG_LINK: u32 = 6;
+const REG_MULTI_SZ: u32 = 7;
+const REG_QWORD: u32 = 11;
+
+/// A value read from a registry hive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WindowsRegistryValue {
+    /// A `REG_SZ` value.
+    String(String),
+
+    /// A `REG_EXPAND_SZ` value.
+    ///
+    /// The environment variable references are not expanded.
+    ExpandString(String),
+
+    /// A `REG_BINARY` value.
+    Binary(Vec<u8>),
+
+    /// A `REG_DWORD` value.
+    Dword(u32),
+
+    /// A `REG_QWORD` value.
+    Qword(u64),
+
+    /// A `REG_LINK` value.
+    Link(String),
+
+    /// A `REG_MULTI_SZ` value.
+    MultiString(Vec<String>),
+
+    /// A value of any other type, or a value whose data doesn't match its
+    /// type (e.g., a `REG_DWORD` that isn't 4 bytes long).
+    Other {
+        /// The `REG_*` type of the value.
+        value_type: u32,
+
+        /// The raw data of the value.
+        data: Vec<u8>,
+    },
+}
+
+impl WindowsRegistryValue {
+    /// Interprets the raw data of a value according to its type.
+    pub fn from_raw(value_type: u32, data: Vec<u8>) -> Self {
+        match (value_type, data.len()) {
+            (REG_SZ, _) => Self::String(utf16_until_nul(&data)),
+            (REG_EXPAND_SZ, _) => Self::ExpandString(utf16_until_nul(&data)),
+            (REG_LINK, _) => Self::Link(utf16_until_nul(&data)),
+            (REG_BINARY, _) => Self::Binary(data),
+            (REG_DWORD, 4) => Self::Dword(u32::from_le_bytes([data[0], data[1], data[2], data[3]])),
+            (REG_QWORD, 8) => Self::Qword(u64::from_le_bytes([
+                data[0], data[1], data[2], data[3], data[4], data[5], data[6], data[7],
+            ])),
+            (REG_MULTI_SZ, _) => {
+                let mut strings = utf16(&data)
+                    .split(|&c| c == 0)
+                    .map(String::from_utf16_lossy)
+                    .collect::<Vec<_>>();
+
+                // The list is terminated by an empty string.
+                while strings.last().is_some_and(String::is_empty) {
+                    strings.pop();
+                }
+
+                Self::MultiString(strings)
+            }
+            _ => Self::Other { value_type, data },
+        }
+    }
+}
+
+/// A key node cell (`_CM_KEY_NODE`, signature `nk`).
+#[derive(Debug)]
+pub(crate) struct KeyNode {
+    pub flags: u16,
+    pub subkey_counts: [u32; 2],
+    pub subkey_lists: [u32; 2],
+    pub value_count: u32,
+    pub value_list: u32,
+    pub name: String,
+}
+
+impl KeyNode {
+    const SIGNATURE: &[u8] = b"nk";
+    const NAME_OFFSET: usize = 0x4c;
+
+    /// Parses the data of a key node cell.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..2)? != Self::SIGNATURE {
+            return None;
+        }
+
+        let flags = read_u16(data, 0x02)?;
+        let name_length = read_u16(data, 0x48)? as usize;
+        let name = data.get(Self::NAME_OFFSET..Self::NAME_OFFSET + name_length)?;
+
+        Some(Self {
+            flags,
+            subkey_counts: [read_u32(data, 0x14)?, read_u32(data, 0x18)?],
+            subkey_lists: [read_u32(data, 0x1c)?, read_u32(data, 0x20)?],
+            value_count: read_u32(data, 0x24)?,
+            value_list: read_u32(data, 0x28)?,
+            name: decode_name(name, flags & KEY_COMP_NAME != 0),
+        })
+    }
+
+    /// Checks whether the key is a symbolic link.
+    ///
+    /// The target of the link is stored in its `SymbolicLinkValue` value.
+    pub fn is_symbolic_link(&self) -> bool {
+        self.flags & KEY_SYM_LINK != 0
+    }
+}
+
+/// A key value cell (`_CM_KEY_VALUE`, signature `vk`).
+#[derive(Debug)]
+pub(crate) struct KeyValue {
+    pub data_length: u32,
+    pub data: u32,
+    pub value_type: u32,
+    pub name: String,
+}
+
+impl KeyValue {
+    const SIGNATURE: &[u8] = b"vk";
+    const NAME_OFFSET: usize = 0x14;
+
+    /// Parses the data of a key value cell.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..2)? != Self::SIGNATURE {
+            return None;
+        }
+
+        let name_length = read_u16(data, 0x02)? as usize;
+        let flags = read_u16(data, 0x10)?;
+        let name = data.get(Self::NAME_OFFSET..Self::NAME_OFFSET + name_length)?;
+
+        Some(Self {
+            data_length: read_u32(data, 0x04)?,
+            data: read_u32(data, 0x08)?,
+            value_type: read_u32(data, 0x0c)?,
+            name: decode_name(name, flags & VALUE_COMP_NAME != 0),
+        })
+    }
+
+    /// Returns the data stored directly in the value cell, if the value
+    /// is small enough.
+    pub fn inline_data(&self) -> Option<Vec<u8>> {
+        if self.data_length & CM_KEY_VALUE_SPECIAL_SIZE == 0 {
+            return None;
+        }
+
+        let length = (self.data_length & !CM_KEY_VALUE_SPECIAL_SIZE).min(4) as usize;
+        Some(self.data.to_le_bytes()[..length].to_vec())
+    }
+}
+
+/// A subkey index cell.
+#[derive(Debug)]
+pub(crate) enum KeyIndex {
+    /// A leaf (`li`, `lf` or `lh`) holding the key node cells, with the
+    /// name hash of each key, if available (`lh` only).
+    Leaf(Vec<(u32, Option<u32>)>),
+
+    /// A root index (`ri`) holding the cells of other index cells.
+    Root(Vec<u32>),
+}
+
+impl KeyIndex {
+    /// Parses the data of a subkey index cell.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        let signature = data.get(..2)?;
+        let count = read_u16(data, 0x02)? as usize;
+
+        let entries = |stride: usize| {
+            (0..count)
+                .map(|index| {
+                    let offset = 4 + index * stride;
+                    Some((read_u32(data, offset)?, read_u32(data, offset + 4)))
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+
+        match signature {
+            b"li" => Some(Self::Leaf(
+                entries(4)?
+                    .into_iter()
+                    .map(|(cell, _)| (cell, None))
+                    .collect(),
+            )),
+            b"lf" => Some(Self::Leaf(
+                entries(8)?
+                    .into_iter()
+                    .map(|(cell, _)| (cell, None))
+                    .collect(),
+            )),
+            b"lh" => Some(Self::Leaf(entries(8)?)),
+            b"ri" => Some(Self::Root(
+                entries(4)?.into_iter().map(|(cell, _)| cell).collect(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// A big data cell (`_CM_BIG_DATA`, signature `db`).
+#[derive(Debug)]
+pub(crate) struct BigData {
+    pub count: u16,
+    pub list: u32,
+}
+
+impl BigData {
+    /// Parses the data of a big data cell.
+    pub fn parse(data: &[u8]) -> Option<Self> {
+        if data.get(..2)? != b"db" {
+            return None;
+        }
+
+        Some(Self {
+            count: read_u16(data, 0x02)?,
+            list: read_u32(data, 0x04)?,
+        })
+    }
+}
+
+/// Computes the hash of a key name, as stored in `lh` index cells.
+pub(crate) fn key_name_hash(name: &str) -> u32 {
+    name.chars()
+        .flat_map(char::to_uppercase)
+        .fold(0u32, |hash, c| hash.wrapping_mul(37).wrapping_add(c as u32))
+}
+
+/// Compares two key or value names, ignoring case.
+pub(crate) fn name_eq(a: &str, b: &str) -> bool {
+    a.chars()
+        .flat_map(char::to_uppercase)
+        .eq(b.chars().flat_map(char::to_uppercase))
+}
+
+/// Parses the list of cell indices (e.g., the value list of a key).
+pub(crate) fn cell_list(data: &[u8], count: usize) -> Option<Vec<u32>> {
+    (0..count).map(|index| read_u32(data, index * 4)).collect()
+}
+
+/// Decodes a name, stored either as Latin-1 (compressed) or as UTF-16.
+fn decode_name(name: &[u8], compressed: bool) -> String {
+    if compressed {
+        name.iter().map(|&c| c as char).collect()
+    }
+    else {
+        String::from_utf16_lossy(&utf16(name))
+    }
+}
+
+fn utf16(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|chunk| u16::from_le_bytes([chunk[0], chunk[1]]))
+        .collect()
+}
+
+fn utf16_until_nul(data: &[u8]) -> String {
+    let data = utf16(data);
+    let length = data.iter().position(|&c| c == 0).unwrap_or(data.len());
+    String::from_utf16_lossy(&data[..length])
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
@@ -0,0 +1,464 @@
+use vmi_core::{
+    os::{ProcessObject, VmiOs},
+    AccessContext, Architecture, Registers as _, Va, VmiCore, VmiDriver, VmiError,
+};
+
+use crate::{arch::ArchAdapter, xpress, WindowsOs};
+
+/// Compression format of the pages in the store.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WindowsCompressionFormat {
+    /// Plain LZ77 (`COMPRESSION_FORMAT_XPRESS`).
+    Xpress,
+
+    /// LZ77 with Huffman encoding (`COMPRESSION_FORMAT_XPRESS_HUFF`).
+    XpressHuffman,
+}
+
+impl WindowsCompressionFormat {
+    /// Decompresses the data into the output buffer.
+    ///
+    /// Returns the number of bytes written to the output.
+    pub fn decompress(self, input: &[u8], output: &mut [u8]) -> Result<usize, VmiError> {
+        let result = match self {
+            Self::Xpress => xpress::decompress_plain(input, output),
+            Self::XpressHuffman => xpress::decompress_huffman(input, output),
+        };
+
+        result.map_err(VmiError::Other)
+    }
+}
+
+/// Location of a compressed page in the store.
+#[derive(Debug, Clone, Copy)]
+pub struct WindowsStorePage {
+    /// Location of the compressed data, typically in the address space of
+    /// the `MemCompression` process.
+    pub ctx: AccessContext,
+
+    /// Size of the compressed data.
+    ///
+    /// Pages that do not compress well are stored as-is, in which case the
+    /// size is equal to the page size.
+    pub compressed_size: u32,
+
+    /// Compression format of the data.
+    pub format: WindowsCompressionFormat,
+}
+
+/// Locates pages in the store of the memory manager.
+///
+/// Required by [`WindowsOs::read_compressed_page`].
+///
+/// Since Windows 10, the memory manager compresses pages removed from
+/// working sets instead of writing them to the paging file. The pages are
+/// tracked by the store manager (`SmGlobals`): the global B+tree of
+/// `SMKM_STORE_MGR` maps the page key to a store, the local B+tree of the
+/// store's `ST_DATA_MGR` maps it to a page record, and the page record
+/// points to a chunk in one of the regions mapped in the `MemCompression`
+/// process.
+///
+/// None of these structures are part of the public symbols, and their
+/// layout changes between Windows builds. [`WindowsSmKmStoreLocator`]
+/// implements the lookup for a given [`WindowsSmKmLayout`]; other
+/// implementations can be provided for builds it doesn't handle.
+///
+/// [`WindowsOs::read_compressed_page`]: crate::WindowsOs::read_compressed_page
+pub trait WindowsStoreLocator<Driver>
+where
+    Driver: VmiDriver,
+{
+    /// Locates the compressed page with the given key (`SM_PAGE_KEY`).
+    ///
+    /// Returns `None` if the key is not present in the store.
+    fn locate(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        key: u32,
+    ) -> Result<Option<WindowsStorePage>, VmiError>;
+}
+
+/// Layout of the undocumented store manager structures.
+///
+/// Used by [`WindowsSmKmStoreLocator`]. Like [`WindowsTcpipLayout`], the
+/// layout of these structures is not part of the public symbols and has to
+/// match the build of the guest. The layout of supported 64-bit builds is
+/// available through [`WindowsSmKmLayout::for_build`].
+///
+/// All offsets are in bytes.
+///
+/// [`WindowsTcpipLayout`]: crate::WindowsTcpipLayout
+#[derive(Debug, Clone)]
+pub struct WindowsSmKmLayout {
+    /// Offset of `SMKM_STORE_MGR.KeyToStoreTree` (`B_TREE`).
+    pub store_mgr_key_to_store_tree: u64,
+
+    /// Offset of `SMKM_STORE_MGR.StoreMetadataArray`
+    /// (`SMKM_STORE_METADATA*[]`, 32 entries per array).
+    pub store_mgr_metadata_array: u64,
+
+    /// Size of the `SMKM_STORE_METADATA` structure.
+    pub store_metadata_size: u64,
+
+    /// Offset of `SMKM_STORE_METADATA.SmkmStore` (`SMKM_STORE*`).
+    pub store_metadata_store: u64,
+
+    /// Mask of the store index in a `SMKM_FRONTEND_ENTRY`.
+    pub frontend_entry_store_index_mask: u32,
+
+    /// Offset of `SMKM_STORE.StStore.StDataMgr` (`ST_DATA_MGR`).
+    pub store_data_mgr: u64,
+
+    /// Offset of `SMKM_STORE.CompressedRegionPtrArray` (`PVOID*`).
+    pub store_region_array: u64,
+
+    /// Offset of `SMKM_STORE.StoreOwnerProcess` (`_EPROCESS*`).
+    pub store_owner_process: u64,
+
+    /// Offset of `ST_DATA_MGR.PagesTree` (`B_TREE`).
+    pub data_mgr_pages_tree: u64,
+
+    /// Offset of `ST_DATA_MGR.RegionSizeMask` (`ULONG`).
+    pub data_mgr_region_size_mask: u64,
+
+    /// Offset of `ST_DATA_MGR.RegionIndexMask` (`ULONG`).
+    pub data_mgr_region_index_mask: u64,
+
+    /// Offset of `B_TREE.Root` (`B_TREE_NODE*`).
+    pub tree_root: u64,
+
+    /// Offset of `B_TREE_NODE.Leaf` (bit 0 of a `UCHAR`).
+    pub tree_node_leaf: u64,
+
+    /// Offset of `B_TREE_NODE.NumEntries` (`USHORT`).
+    pub tree_node_entry_count: u64,
+
+    /// Offset of the entries of a `B_TREE_NODE`.
+    ///
+    /// Every entry starts with its `ULONG` key.
+    pub tree_node_entries: u64,
+
+    /// Size of an entry of an index node.
+    pub tree_index_entry_size: u64,
+
+    /// Offset of the child node pointer in an entry of an index node.
+    pub tree_index_entry_child: u64,
+
+    /// Size of an entry of a leaf node of the `KeyToStoreTree`
+    /// (`SM_PAGE_KEY` and `SMKM_FRONTEND_ENTRY`).
+    pub store_tree_leaf_entry_size: u64,
+
+    /// Size of an entry of a leaf node of the `PagesTree`
+    /// (`ULONG` key and `ST_PAGE_RECORD`).
+    pub pages_tree_leaf_entry_size: u64,
+
+    /// Offset of the value in an entry of a leaf node.
+    pub tree_leaf_entry_value: u64,
+
+    /// Offset of `ST_PAGE_RECORD.Key` (`ULONG`), which holds the region
+    /// index and the offset of the chunk in the region.
+    pub page_record_key: u64,
+
+    /// Offset of `ST_PAGE_RECORD.CompressedSize` (`USHORT`).
+    pub page_record_compressed_size: u64,
+}
+
+impl WindowsSmKmLayout {
+    /// Layout of Windows 10 2004 through 22H2 (build 19041), 64-bit.
+    pub const WINDOWS_10_2004_X64: Self = Self {
+        store_mgr_key_to_store_tree: 0x00,
+        store_mgr_metadata_array: 0x1c0,
+        store_metadata_size: 0x28,
+        store_metadata_store: 0x00,
+        frontend_entry_store_index_mask: 0x3ff,
+        store_data_mgr: 0x50,
+        store_region_array: 0x1848,
+        store_owner_process: 0x19a8,
+        data_mgr_pages_tree: 0x00,
+        data_mgr_region_size_mask: 0x248,
+        data_mgr_region_index_mask: 0x24c,
+        tree_root: 0x08,
+        tree_node_leaf: 0x00,
+        tree_node_entry_count: 0x02,
+        tree_node_entries: 0x08,
+        tree_index_entry_size: 0x10,
+        tree_index_entry_child: 0x08,
+        store_tree_leaf_entry_size: 0x08,
+        pages_tree_leaf_entry_size: 0x0c,
+        tree_leaf_entry_value: 0x04,
+        page_record_key: 0x00,
+        page_record_compressed_size: 0x04,
+    };
+
+    /// Returns the built-in layout for a 64-bit Windows build.
+    ///
+    /// Returns `None` if there is no built-in layout for the build.
+    pub fn for_build(build: u32) -> Option<Self> {
+        match build {
+            19041..=19045 => Some(Self::WINDOWS_10_2004_X64),
+            _ => None,
+        }
+    }
+}
+
+/// Locates pages in the store by walking the structures of the store
+/// manager.
+///
+/// # Implementation Details
+///
+/// The page key is looked up in the `KeyToStoreTree` of `nt!SmGlobals`,
+/// which yields the index of the store. The store is found in the
+/// two-level `StoreMetadataArray`, and the key is then looked up in the
+/// `PagesTree` of its `ST_DATA_MGR`, which yields the page record. The
+/// `Key` of the record holds the chunk offset (in 16-byte units, masked
+/// by `RegionSizeMask`) in the low bits and the region index (masked by
+/// `RegionIndexMask`) above them. The region index selects the region in
+/// `CompressedRegionPtrArray`, which is mapped in the address space of the
+/// store owner process (`MemCompression`).
+///
+/// # Notes
+///
+/// The pages are assumed to be compressed with
+/// [`WindowsCompressionFormat::Xpress`], which is what the memory manager
+/// uses.
+pub struct WindowsSmKmStoreLocator<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    os: &'a WindowsOs<Driver>,
+    sm_globals: Va,
+    layout: WindowsSmKmLayout,
+}
+
+impl<'a, Driver> WindowsSmKmStoreLocator<'a, Driver>
+where
+    Driver: VmiDriver,
+    Driver::Architecture: Architecture + ArchAdapter<Driver>,
+{
+    /// Maximum depth of the B+trees.
+    ///
+    /// Guards against loops in corrupted trees.
+    const MAX_TREE_DEPTH: usize = 16;
+
+    /// Creates a new store locator.
+    ///
+    /// Returns [`VmiError::NotSupported`] if the kernel profile doesn't
+    /// have the `SmGlobals` symbol.
+    pub fn new(
+        os: &'a WindowsOs<Driver>,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        layout: WindowsSmKmLayout,
+    ) -> Result<Self, VmiError> {
+        let SmGlobals = os.symbols.SmGlobals.ok_or(VmiError::NotSupported)?;
+        let sm_globals = os.kernel_image_base(vmi, registers)? + SmGlobals;
+
+        Ok(Self {
+            os,
+            sm_globals,
+            layout,
+        })
+    }
+
+    /// Returns the layout of the store manager structures.
+    pub fn layout(&self) -> &WindowsSmKmLayout {
+        &self.layout
+    }
+
+    /// Locates the `SMKM_STORE` that holds the page with the given key.
+    fn store(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        key: u32,
+    ) -> Result<Option<Va>, VmiError> {
+        let layout = &self.layout;
+        let address_width = registers.address_width();
+
+        let entry = match self.tree_lookup(
+            vmi,
+            registers,
+            self.sm_globals + layout.store_mgr_key_to_store_tree,
+            key,
+            layout.store_tree_leaf_entry_size,
+        )? {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+
+        let store_index = (vmi.read_u32(registers.address_context(entry))?
+            & layout.frontend_entry_store_index_mask) as u64;
+
+        let metadata_array = vmi.read_va(
+            registers.address_context(
+                self.sm_globals
+                    + layout.store_mgr_metadata_array
+                    + (store_index / 32) * address_width as u64,
+            ),
+            address_width,
+        )?;
+
+        if metadata_array.is_null() {
+            return Ok(None);
+        }
+
+        let store = vmi.read_va(
+            registers.address_context(
+                metadata_array
+                    + (store_index % 32) * layout.store_metadata_size
+                    + layout.store_metadata_store,
+            ),
+            address_width,
+        )?;
+
+        if store.is_null() {
+            return Ok(None);
+        }
+
+        Ok(Some(store))
+    }
+
+    /// Looks up a key in a `B_TREE`.
+    ///
+    /// Returns the address of the value of the matching leaf entry.
+    fn tree_lookup(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        tree: Va,
+        key: u32,
+        leaf_entry_size: u64,
+    ) -> Result<Option<Va>, VmiError> {
+        let layout = &self.layout;
+
+        let mut node = vmi.read_va(
+            registers.address_context(tree + layout.tree_root),
+            registers.address_width(),
+        )?;
+
+        for _ in 0..Self::MAX_TREE_DEPTH {
+            if node.is_null() {
+                return Ok(None);
+            }
+
+            let leaf =
+                vmi.read_u8(registers.address_context(node + layout.tree_node_leaf))? & 1 != 0;
+            let count = vmi
+                .read_u16(registers.address_context(node + layout.tree_node_entry_count))?
+                as u64;
+
+            let entry_size = if leaf {
+                leaf_entry_size
+            }
+            else {
+                layout.tree_index_entry_size
+            };
+
+            let entries = node + layout.tree_node_entries;
+            let mut buffer = vec![0u8; (count * entry_size) as usize];
+            vmi.read(registers.address_context(entries), &mut buffer)?;
+
+            let mut keys = buffer
+                .chunks_exact(entry_size as usize)
+                .map(|entry| u32::from_le_bytes([entry[0], entry[1], entry[2], entry[3]]));
+
+            if leaf {
+                return Ok(keys.position(|entry_key| entry_key == key).map(|index| {
+                    entries + index as u64 * entry_size + layout.tree_leaf_entry_value
+                }));
+            }
+
+            // The key of an index entry is the lowest key of its subtree.
+            let index = match keys.take_while(|&entry_key| entry_key <= key).count() {
+                0 => return Ok(None),
+                count => count as u64 - 1,
+            };
+
+            node = vmi.read_va(
+                registers
+                    .address_context(entries + index * entry_size + layout.tree_index_entry_child),
+                registers.address_width(),
+            )?;
+        }
+
+        Err(VmiError::CorruptedStruct("B_TREE"))
+    }
+}
+
+impl<Driver> WindowsStoreLocator<Driver> for WindowsSmKmStoreLocator<'_, Driver>
+where
+    Driver: VmiDriver,
+    Driver::Architecture: Architecture + ArchAdapter<Driver>,
+{
+    fn locate(
+        &self,
+        vmi: &VmiCore<Driver>,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        key: u32,
+    ) -> Result<Option<WindowsStorePage>, VmiError> {
+        let layout = &self.layout;
+        let address_width = registers.address_width();
+
+        let store = match self.store(vmi, registers, key)? {
+            Some(store) => store,
+            None => return Ok(None),
+        };
+
+        let data_mgr = store + layout.store_data_mgr;
+
+        let record = match self.tree_lookup(
+            vmi,
+            registers,
+            data_mgr + layout.data_mgr_pages_tree,
+            key,
+            layout.pages_tree_leaf_entry_size,
+        )? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+
+        let record_key =
+            vmi.read_u32(registers.address_context(record + layout.page_record_key))?;
+        let compressed_size =
+            vmi.read_u16(registers.address_context(record + layout.page_record_compressed_size))?;
+
+        let region_size_mask =
+            vmi.read_u32(registers.address_context(data_mgr + layout.data_mgr_region_size_mask))?;
+        let region_index_mask =
+            vmi.read_u32(registers.address_context(data_mgr + layout.data_mgr_region_index_mask))?;
+
+        let offset = ((record_key & region_size_mask) as u64) << 4;
+        let region_index =
+            ((record_key >> region_size_mask.count_ones()) & region_index_mask) as u64;
+
+        let region_array = vmi.read_va(
+            registers.address_context(store + layout.store_region_array),
+            address_width,
+        )?;
+
+        let region = vmi.read_va(
+            registers.address_context(region_array + region_index * address_width as u64),
+            address_width,
+        )?;
+
+        if region.is_null() {
+            return Ok(None);
+        }
+
+        let owner = vmi.read_va(
+            registers.address_context(store + layout.store_owner_process),
+            address_width,
+        )?;
+
+        let root = self
+            .os
+            .process_translation_root(vmi, registers, ProcessObject(owner))?;
+
+        Ok(Some(WindowsStorePage {
+            ctx: AccessContext::from((region + offset, root)),
+            compressed_size: compressed_size as u32,
+            format: WindowsCompressionFormat::Xpress,
+        }))
+    }
+}
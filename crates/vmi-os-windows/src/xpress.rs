@@ -0,0 +1,387 @@
+//! XPRESS decompression ([MS-XCA]).
+//!
+//! [MS-XCA]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-xca
+
+/// Error returned when the compressed data is malformed.
+const ERROR: &str = "malformed XPRESS data";
+
+/// Decompresses data in the plain LZ77 format (`COMPRESSION_FORMAT_XPRESS`).
+///
+/// Decompression stops when the input is exhausted or the output buffer is
+/// full. Returns the number of bytes written to the output.
+///
+/// See [MS-XCA] 2.4.4.
+///
+/// [MS-XCA]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-xca
+pub(crate) fn decompress_plain(input: &[u8], output: &mut [u8]) -> Result<usize, &'static str> {
+    let mut input_position = 0;
+    let mut output_position = 0;
+    let mut buffered_flags = 0u32;
+    let mut buffered_flag_count = 0;
+    let mut last_length_half_byte = None;
+
+    while output_position < output.len() {
+        if buffered_flag_count == 0 {
+            buffered_flags = read_u32(input, &mut input_position).ok_or(ERROR)?;
+            buffered_flag_count = 32;
+        }
+
+        buffered_flag_count -= 1;
+
+        if buffered_flags & (1 << buffered_flag_count) == 0 {
+            let literal = match input.get(input_position) {
+                Some(&literal) => literal,
+                None => break,
+            };
+
+            output[output_position] = literal;
+            input_position += 1;
+            output_position += 1;
+            continue;
+        }
+
+        if input_position == input.len() {
+            break;
+        }
+
+        let match_bytes = read_u16(input, &mut input_position).ok_or(ERROR)? as usize;
+        let mut match_length = match_bytes % 8;
+        let match_offset = match_bytes / 8 + 1;
+
+        if match_length == 7 {
+            match last_length_half_byte.take() {
+                None => {
+                    match_length = (*input.get(input_position).ok_or(ERROR)? % 16) as usize;
+                    last_length_half_byte = Some(input_position);
+                    input_position += 1;
+                }
+                Some(position) => {
+                    match_length = (input[position] / 16) as usize;
+                }
+            }
+
+            if match_length == 15 {
+                match_length = read_u8(input, &mut input_position).ok_or(ERROR)? as usize;
+
+                if match_length == 255 {
+                    match_length = read_u16(input, &mut input_position).ok_or(ERROR)? as usize;
+
+                    if match_length == 0 {
+                        match_length = read_u32(input, &mut input_position).ok_or(ERROR)? as usize;
+                    }
+
+                    match_length = match_length.checked_sub(15 + 7).ok_or(ERROR)?;
+                }
+
+                match_length += 15;
+            }
+
+            match_length += 7;
+        }
+
+        match_length += 3;
+
+        output_position = copy_match(output, output_position, match_offset, match_length)?;
+    }
+
+    Ok(output_position)
+}
+
+/// Decompresses data in the LZ77+Huffman format
+/// (`COMPRESSION_FORMAT_XPRESS_HUFF`).
+///
+/// Decompression stops when the end-of-stream symbol is reached or the
+/// output buffer is full. Returns the number of bytes written to the output.
+///
+/// See [MS-XCA] 2.2.4.
+///
+/// [MS-XCA]: https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-xca
+pub(crate) fn decompress_huffman(input: &[u8], output: &mut [u8]) -> Result<usize, &'static str> {
+    /// Maximum length of a Huffman code.
+    const MAX_BIT_LENGTH: u32 = 15;
+
+    /// Number of symbols (256 literals and 256 match symbols).
+    const SYMBOL_COUNT: usize = 512;
+
+    /// Size of the decoded output of a single block.
+    const BLOCK_SIZE: usize = 65536;
+
+    let mut input_position = 0;
+    let mut output_position = 0;
+
+    while output_position < output.len() {
+        //
+        // The block starts with a table of 4-bit code lengths of all
+        // symbols, followed by the Huffman-encoded data.
+        //
+
+        let lengths_table = input
+            .get(input_position..input_position + SYMBOL_COUNT / 2)
+            .ok_or(ERROR)?;
+        input_position += SYMBOL_COUNT / 2;
+
+        let mut lengths = [0u8; SYMBOL_COUNT];
+        for (index, &byte) in lengths_table.iter().enumerate() {
+            lengths[2 * index] = byte & 0xf;
+            lengths[2 * index + 1] = byte >> 4;
+        }
+
+        let table = decoding_table(&lengths)?;
+
+        let high = read_u16(input, &mut input_position).ok_or(ERROR)? as u32;
+        let low = read_u16(input, &mut input_position).ok_or(ERROR)? as u32;
+        let mut current_bits = (high << 16) | low;
+        let mut extra_bit_count = 16i32;
+
+        let block_end = output.len().min(output_position + BLOCK_SIZE);
+
+        while output_position < block_end {
+            let next_bits = current_bits >> (32 - MAX_BIT_LENGTH);
+            let symbol = table[next_bits as usize] as usize;
+            let bit_length = lengths[symbol] as u32;
+
+            consume_bits(
+                input,
+                &mut input_position,
+                &mut current_bits,
+                &mut extra_bit_count,
+                bit_length,
+            );
+
+            if symbol < 256 {
+                output[output_position] = symbol as u8;
+                output_position += 1;
+                continue;
+            }
+
+            // End-of-stream marker.
+            if symbol == 256 && input_position >= input.len() {
+                return Ok(output_position);
+            }
+
+            let symbol = symbol - 256;
+            let mut match_length = symbol % 16;
+            let match_offset_bit_length = (symbol / 16) as u32;
+
+            if match_length == 15 {
+                match_length = read_u8(input, &mut input_position).ok_or(ERROR)? as usize;
+
+                if match_length == 255 {
+                    match_length = read_u16(input, &mut input_position).ok_or(ERROR)? as usize;
+                    match_length = match_length.checked_sub(15).ok_or(ERROR)?;
+                }
+
+                match_length += 15;
+            }
+
+            match_length += 3;
+
+            let match_offset = match match_offset_bit_length {
+                0 => 0,
+                bit_length => (current_bits >> (32 - bit_length)) as usize,
+            } + (1 << match_offset_bit_length);
+
+            consume_bits(
+                input,
+                &mut input_position,
+                &mut current_bits,
+                &mut extra_bit_count,
+                match_offset_bit_length,
+            );
+
+            output_position = copy_match(output, output_position, match_offset, match_length)?;
+        }
+    }
+
+    Ok(output_position)
+}
+
+/// Builds the table mapping the next 15 bits of the input to a symbol.
+fn decoding_table(lengths: &[u8; 512]) -> Result<Vec<u16>, &'static str> {
+    const TABLE_SIZE: usize = 1 << 15;
+
+    let mut table = vec![0u16; TABLE_SIZE];
+    let mut current_entry = 0;
+
+    for bit_length in 1..=15 {
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != bit_length {
+                continue;
+            }
+
+            let entry_count = 1 << (15 - bit_length);
+            let entries = table
+                .get_mut(current_entry..current_entry + entry_count)
+                .ok_or(ERROR)?;
+            entries.fill(symbol as u16);
+            current_entry += entry_count;
+        }
+    }
+
+    if current_entry != TABLE_SIZE {
+        return Err(ERROR);
+    }
+
+    Ok(table)
+}
+
+/// Shifts `bit_length` bits out of the bit buffer and refills it from the
+/// input when necessary.
+///
+/// Reading past the end of the input yields zeros, which only affects the
+/// padding of the last symbol.
+fn consume_bits(
+    input: &[u8],
+    input_position: &mut usize,
+    current_bits: &mut u32,
+    extra_bit_count: &mut i32,
+    bit_length: u32,
+) {
+    if bit_length == 0 {
+        return;
+    }
+
+    *current_bits = current_bits.checked_shl(bit_length).unwrap_or(0);
+    *extra_bit_count -= bit_length as i32;
+
+    if *extra_bit_count < 0 {
+        let next = read_u16(input, input_position).unwrap_or(0) as u32;
+        *current_bits |= next << (-*extra_bit_count);
+        *extra_bit_count += 16;
+    }
+}
+
+/// Copies a match from the already decompressed output.
+///
+/// The copy is truncated at the end of the output buffer. Returns the new
+/// output position.
+fn copy_match(
+    output: &mut [u8],
+    output_position: usize,
+    match_offset: usize,
+    match_length: usize,
+) -> Result<usize, &'static str> {
+    if match_offset > output_position {
+        return Err(ERROR);
+    }
+
+    let end = output.len().min(output_position + match_length);
+
+    // The source and destination may overlap, so the bytes are copied one
+    // by one.
+    for position in output_position..end {
+        output[position] = output[position - match_offset];
+    }
+
+    Ok(end)
+}
+
+fn read_u8(input: &[u8], position: &mut usize) -> Option<u8> {
+    let value = *input.get(*position)?;
+    *position += 1;
+    Some(value)
+}
+
+fn read_u16(input: &[u8], position: &mut usize) -> Option<u16> {
+    let bytes = input.get(*position..*position + 2)?;
+    *position += 2;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(input: &[u8], position: &mut usize) -> Option<u32> {
+    let bytes = input.get(*position..*position + 4)?;
+    *position += 4;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the 256-byte table of 4-bit code lengths of a Huffman block.
+    fn lengths_table(lengths: &[(usize, u8)]) -> Vec<u8> {
+        let mut table = vec![0u8; 256];
+        for &(symbol, length) in lengths {
+            table[symbol / 2] |= length << (4 * (symbol % 2));
+        }
+        table
+    }
+
+    #[test]
+    fn plain_literals() {
+        // [MS-XCA] 3.1, example 1.
+        let mut input = vec![0x3f, 0x00, 0x00, 0x00];
+        input.extend(b'a'..=b'z');
+
+        let mut output = [0u8; 64];
+        let size = decompress_plain(&input, &mut output).unwrap();
+        assert_eq!(&output[..size], b"abcdefghijklmnopqrstuvwxyz");
+    }
+
+    #[test]
+    fn plain_long_match() {
+        // [MS-XCA] 3.1, example 2: "abc" repeated 100 times.
+        let input = [
+            0xff, 0xff, 0xff, 0x1f, 0x61, 0x62, 0x63, 0x17, 0x00, 0x0f, 0xff, 0x26, 0x01,
+        ];
+
+        let mut output = [0u8; 512];
+        let size = decompress_plain(&input, &mut output).unwrap();
+        assert_eq!(&output[..size], b"abc".repeat(100).as_slice());
+    }
+
+    #[test]
+    fn plain_truncated_output() {
+        let input = [
+            0xff, 0xff, 0xff, 0x1f, 0x61, 0x62, 0x63, 0x17, 0x00, 0x0f, 0xff, 0x26, 0x01,
+        ];
+
+        let mut output = [0u8; 10];
+        let size = decompress_plain(&input, &mut output).unwrap();
+        assert_eq!(&output[..size], b"abcabcabca");
+    }
+
+    #[test]
+    fn plain_match_before_start() {
+        // A match with offset 1 at the very beginning of the output.
+        let input = [0x00, 0x00, 0x00, 0x80, 0x00, 0x00];
+
+        let mut output = [0u8; 16];
+        assert_eq!(decompress_plain(&input, &mut output), Err(ERROR));
+    }
+
+    #[test]
+    fn huffman_literals() {
+        // 'a' is encoded as `0`, the end-of-stream symbol as `1`.
+        // The stream "aaa" + EOS is `0001`, padded with zeros.
+        let mut input = lengths_table(&[(b'a' as usize, 1), (256, 1)]);
+        input.extend([0x00, 0x10, 0x00, 0x00]);
+
+        let mut output = [0u8; 16];
+        let size = decompress_huffman(&input, &mut output).unwrap();
+        assert_eq!(&output[..size], b"aaa");
+    }
+
+    #[test]
+    fn huffman_matches() {
+        // Same code as above. Until the whole input is consumed, symbol 256
+        // is a match of length 3 at offset 1. The stream is 'a', 15 matches
+        // and EOS, whose bit causes the last 16 bits to be read.
+        let mut input = lengths_table(&[(b'a' as usize, 1), (256, 1)]);
+        input.extend([0xff, 0x7f, 0x00, 0x80, 0x00, 0x00]);
+
+        let mut output = [0u8; 64];
+        let size = decompress_huffman(&input, &mut output).unwrap();
+        assert_eq!(&output[..size], [b'a'; 46].as_slice());
+    }
+
+    #[test]
+    fn huffman_incomplete_code() {
+        // A single symbol with a 1-bit code doesn't fill the code space.
+        let mut input = lengths_table(&[(b'a' as usize, 1)]);
+        input.extend([0x00, 0x00, 0x00, 0x00]);
+
+        let mut output = [0u8; 16];
+        assert_eq!(decompress_huffman(&input, &mut output), Err(ERROR));
+    }
+}
@@ -0,0 +1,118 @@
+//! One-time access grants for protected memory.
+//!
+//! Access control hooks restrict the access to pages in a view and receive
+//! a memory access event whenever the guest touches them. To let a
+//! legitimate access through without disarming the protection, the
+//! instruction is executed in an unrestricted view with single-stepping
+//! enabled, and the restricted view is restored in the following
+//! singlestep event.
+//!
+//! [`AccessGrants`] keeps track of the VCPUs that are currently stepping
+//! over a granted access, so that the restricted view is restored on the
+//! right VCPU, and singlestep events caused by other components can be
+//! told apart.
+//!
+//! # Examples
+//!
+//! ```ignore
+//! fn memory_access(&mut self, vmi: &VmiContext<...>) -> Result<VmiEventResponse<Amd64>, VmiError> {
+//!     if self.is_legitimate(vmi)? {
+//!         return self.grants.grant(vmi.event(), vmi.default_view());
+//!     }
+//!
+//!     // Deny the access...
+//! }
+//!
+//! fn singlestep(&mut self, vmi: &VmiContext<...>) -> Result<VmiEventResponse<Amd64>, VmiError> {
+//!     if let Some(response) = self.grants.restore(vmi.event()) {
+//!         return Ok(response);
+//!     }
+//!
+//!     // Handle other singlestep events...
+//! }
+//! ```
+
+use std::collections::HashMap;
+
+use vmi_core::{Architecture, VcpuId, View, VmiError, VmiEvent, VmiEventResponse};
+
+/// Tracker of one-time access grants.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Debug, Default)]
+pub struct AccessGrants {
+    /// VCPUs stepping over a granted access.
+    ///
+    /// * Key: VCPU
+    /// * Value: The restricted view to restore after the step
+    pending: HashMap<VcpuId, View>,
+}
+
+impl AccessGrants {
+    /// Creates a new tracker with no pending grants.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Grants the access that caused the event once.
+    ///
+    /// The instruction is executed in the given unrestricted view, and the
+    /// view of the event is restored by [`restore`] afterwards.
+    ///
+    /// Returns an error if the event doesn't carry a view, as there would
+    /// be nothing to restore.
+    ///
+    /// [`restore`]: Self::restore
+    pub fn grant<Arch>(
+        &mut self,
+        event: &VmiEvent<Arch>,
+        view: View,
+    ) -> Result<VmiEventResponse<Arch>, VmiError>
+    where
+        Arch: Architecture,
+    {
+        let restricted = match event.view() {
+            Some(restricted) => restricted,
+            None => return Err(VmiError::Other("Event has no view")),
+        };
+
+        if let Some(previous) = self.pending.insert(event.vcpu_id(), restricted) {
+            tracing::warn!(
+                vcpu = %event.vcpu_id(),
+                ?previous,
+                "access granted while a previous grant is pending"
+            );
+        }
+
+        Ok(VmiEventResponse::allow_once(view))
+    }
+
+    /// Completes a pending grant on the VCPU of a singlestep event.
+    ///
+    /// Returns the response that switches the VCPU back to the restricted
+    /// view and disables single-stepping, or `None` if no grant is pending
+    /// on the VCPU (i.e., the singlestep event was caused by something
+    /// else).
+    pub fn restore<Arch>(&mut self, event: &VmiEvent<Arch>) -> Option<VmiEventResponse<Arch>>
+    where
+        Arch: Architecture,
+    {
+        let restricted = self.pending.remove(&event.vcpu_id())?;
+        Some(VmiEventResponse::toggle_singlestep().and_set_view(restricted))
+    }
+
+    /// Checks whether a grant is pending on the VCPU.
+    pub fn is_pending(&self, vcpu: VcpuId) -> bool {
+        self.pending.contains_key(&vcpu)
+    }
+
+    /// Returns the number of pending grants.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Checks whether no grant is pending.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
@@ -29,6 +29,11 @@ pub(super) type PendingBreakpoints<Key, Tag> = HashSet<Breakpoint<Key, Tag>>;
 /// If the breakpoint is global, it will be triggered regardless of the
 /// translation root, e.g., only [`AddressContext::va`] will be considered
 /// during breakpoint matching.
+///
+/// If the breakpoint is one-shot, it is reported only once and then removed
+/// by [`BreakpointManager::remove_expired`].
+///
+/// [`BreakpointManager::remove_expired`]: super::BreakpointManager::remove_expired
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Breakpoint<Key, Tag>
 where
@@ -38,6 +43,7 @@ where
     pub(super) ctx: AddressContext,
     pub(super) view: View,
     pub(super) global: bool,
+    pub(super) one_shot: bool,
     pub(super) key: Key,
     pub(super) tag: Tag,
 }
@@ -69,6 +75,16 @@ where
         self.global
     }
 
+    /// Returns whether the breakpoint is one-shot.
+    ///
+    /// A one-shot breakpoint is reported only once by
+    /// [`BreakpointManager::get_by_event`] and removed afterwards.
+    ///
+    /// [`BreakpointManager::get_by_event`]: super::BreakpointManager::get_by_event
+    pub fn one_shot(&self) -> bool {
+        self.one_shot
+    }
+
     /// Returns the key of the breakpoint.
     pub fn key(&self) -> Key {
         self.key
@@ -90,6 +106,7 @@ pub struct BreakpointBuilder {
     ctx: AddressContext,
     view: View,
     global: bool,
+    one_shot: bool,
 }
 
 #[doc(hidden)]
@@ -101,6 +118,7 @@ where
     ctx: AddressContext,
     view: View,
     global: bool,
+    one_shot: bool,
     key: Key,
 }
 
@@ -113,6 +131,7 @@ where
     ctx: AddressContext,
     view: View,
     global: bool,
+    one_shot: bool,
     tag: Tag,
 }
 
@@ -126,6 +145,7 @@ where
     ctx: AddressContext,
     view: View,
     global: bool,
+    one_shot: bool,
     key: Key,
     tag: Tag,
 }
@@ -145,6 +165,7 @@ impl BreakpointBuilder {
             ctx: ctx.into(),
             view,
             global: false,
+            one_shot: false,
         }
     }
 
@@ -160,6 +181,22 @@ impl BreakpointBuilder {
         }
     }
 
+    /// Sets the breakpoint as one-shot.
+    ///
+    /// A one-shot breakpoint is reported only once by
+    /// [`BreakpointManager::get_by_event`]. Because the breakpoint cannot be
+    /// removed while the event is being dispatched, the removal is deferred
+    /// until [`BreakpointManager::remove_expired`] is called.
+    ///
+    /// [`BreakpointManager::get_by_event`]: super::BreakpointManager::get_by_event
+    /// [`BreakpointManager::remove_expired`]: super::BreakpointManager::remove_expired
+    pub fn one_shot(self) -> Self {
+        Self {
+            one_shot: true,
+            ..self
+        }
+    }
+
     /// Sets the key of the breakpoint.
     pub fn with_key<Key>(self, key: Key) -> BreakpointBuilderWithKey<Key>
     where
@@ -169,6 +206,7 @@ impl BreakpointBuilder {
             ctx: self.ctx,
             view: self.view,
             global: self.global,
+            one_shot: self.one_shot,
             key,
         }
     }
@@ -182,6 +220,7 @@ impl BreakpointBuilder {
             ctx: self.ctx,
             view: self.view,
             global: self.global,
+            one_shot: self.one_shot,
             tag,
         }
     }
@@ -200,6 +239,7 @@ where
             ctx: self.ctx,
             view: self.view,
             global: self.global,
+            one_shot: self.one_shot,
             key: self.key,
             tag,
         }
@@ -219,6 +259,7 @@ where
             ctx: self.ctx,
             view: self.view,
             global: self.global,
+            one_shot: self.one_shot,
             key,
             tag: self.tag,
         }
@@ -235,6 +276,7 @@ where
             ctx: value.ctx,
             view: value.view,
             global: value.global,
+            one_shot: value.one_shot,
             key: Default::default(),
             tag: Default::default(),
         }
@@ -251,6 +293,7 @@ where
             ctx: value.ctx,
             view: value.view,
             global: value.global,
+            one_shot: value.one_shot,
             key: value.key,
             tag: Default::default(),
         }
@@ -267,6 +310,7 @@ where
             ctx: value.ctx,
             view: value.view,
             global: value.global,
+            one_shot: value.one_shot,
             key: Default::default(),
             tag: value.tag,
         }
@@ -283,6 +327,7 @@ where
             ctx: value.ctx,
             view: value.view,
             global: value.global,
+            one_shot: value.one_shot,
             key: value.key,
             tag: value.tag,
         }
@@ -114,6 +114,16 @@ where
     /// This map is kept in sync with `pending_breakpoints`.
     pending_ctx_by_view: HashMap<View, HashSet<AddressContext>>,
 
+    /// Stores one-shot breakpoints that were already reported by
+    /// `get_by_event` and are waiting to be removed.
+    ///
+    /// * Key: (AddressContext, Physical Address, Breakpoint), where the
+    ///   address context and the physical address are those of the event
+    ///   that triggered the breakpoint
+    ///
+    /// Entries are removed by `remove_expired`.
+    expired_breakpoints: HashSet<(AddressContext, Pa, Breakpoint<Key, Tag>)>,
+
     /// Controller used to insert and remove breakpoints.
     controller: Controller,
 }
@@ -164,6 +174,7 @@ where
             active_gfns_by_view: HashMap::new(),
             pending_breakpoints: HashMap::new(),
             pending_ctx_by_view: HashMap::new(),
+            expired_breakpoints: HashSet::new(),
             controller: Interface::new(),
         }
     }
@@ -235,6 +246,59 @@ where
         self.insert_active_breakpoint(vmi, breakpoint, pa)
     }
 
+    /// Inserts multiple breakpoints.
+    ///
+    /// Equivalent to calling [`insert`] for each breakpoint, except that the
+    /// address translation is performed once per page rather than once per
+    /// breakpoint. This speeds up inserting a large number of breakpoints
+    /// that cluster on a few pages (e.g., on every export of a DLL). As with
+    /// [`insert`], each `(view, GFN)` pair is monitored only once.
+    ///
+    /// Returns the result of the insertion for each breakpoint, in the order
+    /// of the input. A failure doesn't prevent the remaining breakpoints from
+    /// being inserted.
+    ///
+    /// [`insert`]: Self::insert
+    pub fn insert_batch(
+        &mut self,
+        vmi: &VmiCore<Interface::Driver>,
+        breakpoints: impl IntoIterator<Item = impl Into<Breakpoint<Key, Tag>>>,
+    ) -> Vec<Result<bool, VmiError>> {
+        //
+        // Translations of the pages seen so far.
+        // `None` means the page is not present.
+        //
+
+        let mut pages = HashMap::<AddressContext, Option<Pa>>::new();
+
+        breakpoints
+            .into_iter()
+            .map(|breakpoint| {
+                let breakpoint = breakpoint.into();
+                let ctx = breakpoint.ctx;
+
+                let offset = <Interface::Driver as VmiDriver>::Architecture::va_offset(ctx.va);
+                let page = AddressContext::new(ctx.va - offset, ctx.root);
+
+                let page_pa = match pages.get(&page) {
+                    Some(page_pa) => *page_pa,
+                    None => {
+                        let page_pa = match vmi.translate_address(page) {
+                            Ok(pa) => Some(pa),
+                            Err(VmiError::PageFault(_)) => None,
+                            Err(err) => return Err(err),
+                        };
+
+                        pages.insert(page, page_pa);
+                        page_pa
+                    }
+                };
+
+                self.insert_with_hint(vmi, breakpoint, page_pa.map(|pa| pa + offset))
+            })
+            .collect()
+    }
+
     /// Removes a breakpoint.
     ///
     /// When a translation for the virtual address is not present, the breakpoint
@@ -381,6 +445,12 @@ where
     }
 
     /// Returns an iterator over the breakpoints for the given event.
+    ///
+    /// One-shot breakpoints are reported only once. After being reported,
+    /// they are marked as expired and removed by the next call to
+    /// [`remove_expired`].
+    ///
+    /// [`remove_expired`]: Self::remove_expired
     pub fn get_by_event(
         &mut self,
         event: &VmiEvent<<Interface::Driver as VmiDriver>::Architecture>,
@@ -392,7 +462,84 @@ where
         let breakpoints_by_ctx = self.active_breakpoints.get(&(view, gfn))?;
         let breakpoints = breakpoints_by_ctx.get(&(key, ctx))?;
 
-        Some(breakpoints.iter().copied())
+        //
+        // Report one-shot breakpoints only if they were not reported before.
+        //
+
+        let expired_breakpoints = &mut self.expired_breakpoints;
+        let result = breakpoints
+            .iter()
+            .copied()
+            .filter(|breakpoint| {
+                !breakpoint.one_shot || expired_breakpoints.insert((ctx, pa, *breakpoint))
+            })
+            .collect::<Vec<_>>();
+
+        Some(result)
+    }
+
+    /// Removes one-shot breakpoints that were already reported by
+    /// [`get_by_event`].
+    ///
+    /// Breakpoints can't be removed while the event that triggered them is
+    /// being dispatched. This function should therefore be called after the
+    /// event handler has finished processing the event.
+    ///
+    /// Other breakpoints sharing the same `(key, ctx)` pair are kept intact.
+    ///
+    /// Returns `true` if any breakpoints were removed, `false` otherwise.
+    ///
+    /// [`get_by_event`]: Self::get_by_event
+    pub fn remove_expired(&mut self, vmi: &VmiCore<Interface::Driver>) -> Result<bool, VmiError> {
+        let mut result = false;
+
+        for (ctx, pa, breakpoint) in std::mem::take(&mut self.expired_breakpoints) {
+            let Breakpoint { view, key, .. } = breakpoint;
+            let gfn = <Interface::Driver as VmiDriver>::Architecture::gfn_from_pa(pa);
+
+            //
+            // The breakpoint might have been made pending by a page-out event
+            // since it was reported.
+            //
+
+            let breakpoints = match self
+                .active_breakpoints
+                .get_mut(&(view, gfn))
+                .and_then(|breakpoints_by_ctx| breakpoints_by_ctx.get_mut(&(key, ctx)))
+            {
+                Some(breakpoints) => breakpoints,
+                None => {
+                    result |= self.remove_pending_breakpoint(breakpoint);
+                    continue;
+                }
+            };
+
+            if !breakpoints.contains(&breakpoint) {
+                continue;
+            }
+
+            if breakpoints.len() > 1 {
+                //
+                // Other breakpoints are registered for this `(key, ctx)`.
+                // Remove only the expired one.
+                //
+
+                breakpoints.remove(&breakpoint);
+                result = true;
+                continue;
+            }
+
+            tracing::debug!(%ctx, %pa, %view, ?key, "removing one-shot breakpoint");
+
+            if self
+                .remove_active_breakpoint(vmi, ctx, pa, key, view)?
+                .is_some()
+            {
+                result = true;
+            }
+        }
+
+        Ok(result)
     }
 
     /// Checks if the given event was caused by a breakpoint.
@@ -436,6 +583,7 @@ where
         }
 
         self.pending_breakpoints.clear();
+        self.expired_breakpoints.clear();
 
         for (key, view, pa, ctx) in to_remove {
             if let Err(err) = self.remove_active_breakpoint(vmi, ctx, pa, key, view) {
@@ -574,6 +722,7 @@ where
             global,
             key,
             tag,
+            ..
         } = breakpoint;
         let gfn = <Interface::Driver as VmiDriver>::Architecture::gfn_from_pa(pa);
 
@@ -906,6 +1055,29 @@ where
         Some(breakpoints)
     }
 
+    /// Removes a single pending breakpoint.
+    ///
+    /// Returns `true` if the breakpoint was removed, `false` if it was not
+    /// found.
+    fn remove_pending_breakpoint(&mut self, breakpoint: Breakpoint<Key, Tag>) -> bool {
+        let Breakpoint { ctx, view, .. } = breakpoint;
+
+        let breakpoints = match self.pending_breakpoints.get_mut(&(view, ctx)) {
+            Some(breakpoints) => breakpoints,
+            None => return false,
+        };
+
+        if !breakpoints.remove(&breakpoint) {
+            return false;
+        }
+
+        if breakpoints.is_empty() {
+            self.remove_pending_breakpoints_by_address(ctx, view);
+        }
+
+        true
+    }
+
     fn register_global_breakpoint(&mut self, gfn: Gfn, view: View, ctx: &mut AddressContext) {
         match self.active_global_breakpoints.entry((view, ctx.va)) {
             Entry::Occupied(mut entry) => {
@@ -1141,3 +1313,193 @@ where
         Some((ctx, pa, view))
     }
 }
+
+#[cfg(all(test, feature = "arch-amd64"))]
+mod tests {
+    use vmi_arch_amd64::{Amd64, Cr3, EventInterrupt, EventReason, Interrupt, Registers};
+    use vmi_core::{MemoryAccess, VcpuId, VmiEventFlags};
+
+    use super::*;
+    use crate::{ptm::PageEntryUpdate, testing::MockDriver};
+
+    type Manager = BreakpointManager<BreakpointController<MockDriver>>;
+
+    const VIEW: View = View(1);
+    const MAX_GFN: Gfn = Gfn(0xff);
+
+    // The page tables at `ROOT` are zeroed, so `VA` is never mapped.
+    const ROOT: Pa = Pa(0x1000);
+    const VA: Va = Va(0x7ff6_1234_5678);
+
+    // The physical address `VA` is paged in at.
+    const PA: Pa = Pa(0x10678);
+    const ORIGINAL: u8 = 0x90;
+
+    fn setup() -> (VmiCore<MockDriver>, Manager) {
+        let vmi = MockDriver::core(MAX_GFN);
+        vmi.write_u8(PA, ORIGINAL).unwrap();
+
+        let mut bpm = Manager::new();
+        let breakpoint = Breakpoint::new((VA, ROOT), VIEW).with_tag("test");
+        assert!(bpm.insert(&vmi, breakpoint).unwrap());
+
+        (vmi, bpm)
+    }
+
+    fn page_in(pa: Pa) -> PageTableMonitorEvent {
+        PageTableMonitorEvent::PageIn(PageEntryUpdate::new(VIEW, (VA, ROOT), pa))
+    }
+
+    fn page_out(pa: Pa) -> PageTableMonitorEvent {
+        PageTableMonitorEvent::PageOut(PageEntryUpdate::new(VIEW, (VA, ROOT), pa))
+    }
+
+    /// Reads the byte at `PA` as seen through `VIEW`.
+    fn read_in_view(vmi: &VmiCore<MockDriver>) -> u8 {
+        let gfn = Amd64::gfn_from_pa(PA);
+        let gfn = vmi.driver().remapped(VIEW, gfn).unwrap_or(gfn);
+        vmi.read_u8(Amd64::pa_from_gfn(gfn) + Amd64::pa_offset(PA))
+            .unwrap()
+    }
+
+    /// Creates the event of a breakpoint hit at `VA` in `VIEW`.
+    fn breakpoint_event() -> VmiEvent<Amd64> {
+        let registers = Registers {
+            rip: VA.0,
+            cr3: Cr3(ROOT.0),
+            ..Default::default()
+        };
+
+        let reason = EventReason::Interrupt(EventInterrupt {
+            gfn: Amd64::gfn_from_pa(PA),
+            interrupt: Interrupt::breakpoint(1),
+        });
+
+        VmiEvent::new(
+            VcpuId(0),
+            VmiEventFlags::empty(),
+            Some(VIEW),
+            registers,
+            reason,
+        )
+    }
+
+    /// Returns the tags of the breakpoints reported for the event.
+    fn reported_tags(bpm: &mut Manager, event: &VmiEvent<Amd64>) -> Vec<&'static str> {
+        let mut tags = match bpm.get_by_event(event, ()) {
+            Some(breakpoints) => breakpoints
+                .into_iter()
+                .map(|breakpoint| breakpoint.tag())
+                .collect::<Vec<_>>(),
+            None => Vec::new(),
+        };
+
+        tags.sort();
+        tags
+    }
+
+    #[test]
+    fn unmapped_breakpoint_is_pending() {
+        let (vmi, bpm) = setup();
+
+        assert!(!bpm.contains_by_address((VA, ROOT), ()));
+        assert_eq!(read_in_view(&vmi), ORIGINAL);
+    }
+
+    #[test]
+    fn page_in_activates_pending_breakpoint() {
+        let (vmi, mut bpm) = setup();
+        let gfn = Amd64::gfn_from_pa(PA);
+
+        assert!(bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+
+        assert!(bpm.contains_by_address((VA, ROOT), ()));
+        assert_eq!(read_in_view(&vmi), Amd64::BREAKPOINT[0]);
+        assert_eq!(vmi.driver().access(VIEW, gfn), MemoryAccess::X);
+
+        // The original page is left intact.
+        assert_eq!(vmi.read_u8(PA).unwrap(), ORIGINAL);
+    }
+
+    #[test]
+    fn page_out_makes_breakpoint_pending() {
+        let (vmi, mut bpm) = setup();
+        let gfn = Amd64::gfn_from_pa(PA);
+
+        assert!(bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+        assert!(bpm.handle_ptm_event(&vmi, &page_out(PA)).unwrap());
+
+        assert!(!bpm.contains_by_address((VA, ROOT), ()));
+        assert_eq!(vmi.driver().remapped(VIEW, gfn), None);
+        assert_eq!(vmi.driver().access(VIEW, gfn), MemoryAccess::RWX);
+        assert_eq!(read_in_view(&vmi), ORIGINAL);
+
+        // The breakpoint is restored on the next page-in.
+        assert!(bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+        assert!(bpm.contains_by_address((VA, ROOT), ()));
+        assert_eq!(read_in_view(&vmi), Amd64::BREAKPOINT[0]);
+    }
+
+    #[test]
+    fn unrelated_events_are_ignored() {
+        let (vmi, mut bpm) = setup();
+        let other = Pa(0x20000);
+
+        // Nothing is active on the page yet.
+        assert!(!bpm.handle_ptm_event(&vmi, &page_out(PA)).unwrap());
+
+        assert!(bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+
+        // The breakpoint is no longer pending.
+        assert!(!bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+
+        // No breakpoint is active on the other page.
+        assert!(!bpm.handle_ptm_event(&vmi, &page_out(other)).unwrap());
+        assert!(bpm.contains_by_address((VA, ROOT), ()));
+    }
+
+    #[test]
+    fn one_shot_breakpoint_is_removed_after_dispatch() {
+        let (vmi, mut bpm) = setup();
+        let event = breakpoint_event();
+
+        let one_shot = Breakpoint::new((VA, ROOT), VIEW)
+            .one_shot()
+            .with_tag("one-shot");
+        assert!(bpm.insert(&vmi, one_shot).unwrap());
+        assert!(bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+
+        // The one-shot breakpoint is reported only once, but it stays in
+        // place until the event is dispatched.
+        assert_eq!(reported_tags(&mut bpm, &event), ["one-shot", "test"]);
+        assert_eq!(reported_tags(&mut bpm, &event), ["test"]);
+        assert_eq!(read_in_view(&vmi), Amd64::BREAKPOINT[0]);
+
+        // Only the one-shot breakpoint is removed.
+        assert!(bpm.remove_expired(&vmi).unwrap());
+        assert!(!bpm.remove_expired(&vmi).unwrap());
+        assert_eq!(reported_tags(&mut bpm, &event), ["test"]);
+        assert_eq!(read_in_view(&vmi), Amd64::BREAKPOINT[0]);
+    }
+
+    #[test]
+    fn last_one_shot_breakpoint_restores_page() {
+        let vmi = MockDriver::core(MAX_GFN);
+        vmi.write_u8(PA, ORIGINAL).unwrap();
+
+        let mut bpm = Manager::new();
+        let event = breakpoint_event();
+
+        let one_shot = Breakpoint::new((VA, ROOT), VIEW)
+            .one_shot()
+            .with_tag("one-shot");
+        assert!(bpm.insert(&vmi, one_shot).unwrap());
+        assert!(bpm.handle_ptm_event(&vmi, &page_in(PA)).unwrap());
+
+        assert_eq!(reported_tags(&mut bpm, &event), ["one-shot"]);
+        assert!(bpm.remove_expired(&vmi).unwrap());
+
+        assert!(!bpm.contains_by_address((VA, ROOT), ()));
+        assert_eq!(read_in_view(&vmi), ORIGINAL);
+    }
+}
@@ -1,4 +1,4 @@
-use vmi_core::{AccessContext, VmiCore, VmiDriver, VmiError};
+use vmi_core::{AccessContext, TranslationMechanism, Va, VmiCore, VmiDriver, VmiError};
 
 /// Representation of memory for hexdump.
 pub enum Representation {
@@ -105,3 +105,94 @@ where
 
     Ok(())
 }
+
+/// Print a hexdump of memory at the given address, annotating values that
+/// look like pointers.
+///
+/// The memory is printed as one 64-bit value per line. A value is
+/// considered a pointer if it is not a small integer and it translates to
+/// a physical address using the translation root of `ctx`. For such values,
+/// `describe` is called to obtain a description of the target address
+/// (e.g., `ntoskrnl.exe!PsLoadedModuleList` or `heap`), which is appended
+/// to the line as `-> description`.
+///
+/// No annotations are produced when `ctx` uses direct (physical) access.
+pub fn hexdump_annotated<Driver>(
+    vmi: &VmiCore<Driver>,
+    ctx: impl Into<AccessContext>,
+    count: usize,
+    mut describe: impl FnMut(Va) -> Option<String>,
+) -> Result<(), VmiError>
+where
+    Driver: VmiDriver,
+{
+    // Values below this threshold are most likely integers.
+    const MIN_POINTER: u64 = 0x10000;
+
+    let ctx = ctx.into();
+
+    let mut buf = vec![0u8; count];
+    vmi.read(ctx, &mut buf)?;
+
+    let is_pointer = |value: u64| -> bool {
+        if value < MIN_POINTER {
+            return false;
+        }
+
+        match ctx.mechanism {
+            TranslationMechanism::Paging { .. } => vmi
+                .translate_access_context(AccessContext {
+                    address: value,
+                    ..ctx
+                })
+                .is_ok(),
+            TranslationMechanism::Direct => false,
+        }
+    };
+
+    println!("--------------------|                  Value | 01234567 | Annotation");
+    for (index, chunk) in buf.chunks(8).enumerate() {
+        print!(" 0x{:016X} |", ctx.address + (index * 8) as u64);
+
+        let mut qword = [0u8; 8];
+        qword[..chunk.len()].copy_from_slice(chunk);
+        let value = u64::from_le_bytes(qword);
+
+        if chunk.len() == 8 {
+            print!("     0x{:016X}", value);
+        }
+        else {
+            print!("     {:>18}", "");
+        }
+
+        print!(" | ");
+
+        for &byte in chunk {
+            print!(
+                "{}",
+                if byte.is_ascii_graphic() {
+                    byte as char
+                }
+                else {
+                    '.'
+                }
+            );
+        }
+
+        for _ in chunk.len()..8 {
+            print!(" ");
+        }
+
+        print!(" |");
+
+        if chunk.len() == 8 && is_pointer(value) {
+            if let Some(description) = describe(Va(value)) {
+                print!(" -> {}", description);
+            }
+        }
+
+        println!();
+    }
+
+    Ok(())
+}
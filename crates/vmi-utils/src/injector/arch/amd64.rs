@@ -1,5 +1,5 @@
 use vmi_arch_amd64::Amd64;
-use vmi_core::{Va, VmiCore, VmiDriver, VmiError};
+use vmi_core::{Va, VmiDriver, VmiError, WriteBuffer};
 
 use super::ArchAdapter;
 
@@ -7,8 +7,8 @@ impl<Driver> ArchAdapter<Driver> for Amd64
 where
     Driver: VmiDriver<Architecture = Self>,
 {
-    fn copy_bytes_to_stack(
-        vmi: &VmiCore<Driver>,
+    fn copy_bytes_to_stack_buffered(
+        writer: &mut WriteBuffer<Driver>,
         registers: &mut Self::Registers,
         data: &[u8],
         alignment: usize,
@@ -17,7 +17,7 @@ where
         addr -= data.len() as u64;
         addr &= !(alignment as u64 - 1);
 
-        vmi.write((addr.into(), registers.cr3.into()), data)?;
+        writer.write((addr.into(), registers.cr3.into()), data)?;
 
         registers.rsp = addr;
         Ok(Va(addr))
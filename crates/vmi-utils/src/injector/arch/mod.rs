@@ -1,7 +1,7 @@
 #[cfg(feature = "arch-amd64")]
 pub mod amd64;
 
-use vmi_core::{Architecture, Va, VmiCore, VmiDriver, VmiError};
+use vmi_core::{Architecture, Va, VmiCore, VmiDriver, VmiError, WriteBuffer};
 use zerocopy::{Immutable, IntoBytes};
 
 use super::{Argument, ArgumentData};
@@ -11,13 +11,44 @@ pub trait ArchAdapter<Driver>: Architecture
 where
     Driver: VmiDriver<Architecture = Self>,
 {
+    /// Copies bytes to the stack through a write buffer.
+    ///
+    /// The bytes are written to the virtual machine once the buffer is
+    /// committed.
+    fn copy_bytes_to_stack_buffered(
+        writer: &mut WriteBuffer<Driver>,
+        registers: &mut Self::Registers,
+        data: &[u8],
+        alignment: usize,
+    ) -> Result<Va, VmiError>;
+
+    /// Copies a value to the stack through a write buffer.
+    ///
+    /// The value is written to the virtual machine once the buffer is
+    /// committed.
+    fn copy_to_stack_buffered<T>(
+        writer: &mut WriteBuffer<Driver>,
+        registers: &mut Self::Registers,
+        data: T,
+    ) -> Result<Va, VmiError>
+    where
+        T: IntoBytes + Immutable,
+    {
+        Self::copy_bytes_to_stack_buffered(writer, registers, data.as_bytes(), align_of::<T>())
+    }
+
     /// Copies bytes to the stack.
     fn copy_bytes_to_stack(
         vmi: &VmiCore<Driver>,
         registers: &mut Self::Registers,
         data: &[u8],
         alignment: usize,
-    ) -> Result<Va, VmiError>;
+    ) -> Result<Va, VmiError> {
+        let mut writer = vmi.write_buffered();
+        let result = Self::copy_bytes_to_stack_buffered(&mut writer, registers, data, alignment)?;
+        writer.commit()?;
+        Ok(result)
+    }
 
     /// Copies a value to the stack.
     fn copy_to_stack<T>(
@@ -39,12 +70,10 @@ where
         registers: &mut Self::Registers,
         argument: &Argument,
     ) -> Result<u64, VmiError> {
-        Ok(match &argument.data {
-            ArgumentData::Value(data) => *data,
-            ArgumentData::Reference(data) => {
-                Self::copy_bytes_to_stack(vmi, registers, data, argument.alignment as usize)?.0
-            }
-        })
+        let mut writer = vmi.write_buffered();
+        let result = Self::push_argument_buffered(&mut writer, registers, argument)?;
+        writer.commit()?;
+        Ok(result)
     }
 
     // TODO: Move somewhere else?
@@ -54,11 +83,49 @@ where
         vmi: &VmiCore<Driver>,
         registers: &mut Self::Registers,
         arguments: &[Argument],
+    ) -> Result<Vec<u64>, VmiError> {
+        let mut writer = vmi.write_buffered();
+        let result = Self::push_arguments_buffered(&mut writer, registers, arguments)?;
+        writer.commit()?;
+        Ok(result)
+    }
+
+    /// Pushes an argument through a write buffer.
+    ///
+    /// Referenced data is written to the virtual machine once the buffer is
+    /// committed.
+    fn push_argument_buffered(
+        writer: &mut WriteBuffer<Driver>,
+        registers: &mut Self::Registers,
+        argument: &Argument,
+    ) -> Result<u64, VmiError> {
+        Ok(match &argument.data {
+            ArgumentData::Value(data) => *data,
+            ArgumentData::Reference(data) => {
+                Self::copy_bytes_to_stack_buffered(
+                    writer,
+                    registers,
+                    data,
+                    argument.alignment as usize,
+                )?
+                .0
+            }
+        })
+    }
+
+    /// Pushes multiple arguments through a write buffer.
+    ///
+    /// Referenced data is written to the virtual machine once the buffer is
+    /// committed.
+    fn push_arguments_buffered(
+        writer: &mut WriteBuffer<Driver>,
+        registers: &mut Self::Registers,
+        arguments: &[Argument],
     ) -> Result<Vec<u64>, VmiError> {
         let mut values = Vec::with_capacity(arguments.len());
 
         for argument in arguments {
-            let value = Self::push_argument(vmi, registers, argument)?;
+            let value = Self::push_argument_buffered(writer, registers, argument)?;
             values.push(value);
         }
 
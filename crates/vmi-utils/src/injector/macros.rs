@@ -7,113 +7,6 @@ pub mod __private {
     pub mod zerocopy {
         pub use zerocopy::*;
     }
-
-    use vmi_core::{os::OsRegionKind, Va, VmiContext, VmiDriver, VmiError, VmiOs};
-
-    use super::super::RecipeContext;
-    use crate::injector::recipe::SymbolCache;
-
-    /// Looks up a symbol in the function cache. If the symbol is not found, it
-    /// retrieves the exported symbols from the specified image and caches them.
-    /// The filename is case-insensitive. Returns virtual address of the symbol
-    /// if found.
-    #[tracing::instrument(skip(ctx))]
-    pub fn lookup_symbol<Driver, Os, T>(
-        ctx: &mut RecipeContext<'_, Driver, Os, T>,
-        filename: &str,
-        symbol: &str,
-    ) -> Result<Option<Va>, VmiError>
-    where
-        Driver: VmiDriver,
-        Os: VmiOs<Driver>,
-    {
-        use std::collections::hash_map::Entry;
-
-        match ctx.cache.entry(filename.to_owned()) {
-            Entry::Occupied(entry) => match entry.into_mut().get(symbol).copied() {
-                Some(va) => {
-                    tracing::trace!(cache_hit = true, %va, "symbol found");
-                    Ok(Some(va))
-                }
-                None => {
-                    tracing::error!(cache_hit = true, "Symbol not found");
-                    Ok(None)
-                }
-            },
-            Entry::Vacant(entry) => {
-                let symbols = match exported_symbols(ctx.vmi, filename)? {
-                    Some(symbols) => symbols,
-                    None => {
-                        tracing::error!(cache_hit = false, "Image not found");
-                        return Ok(None);
-                    }
-                };
-
-                let va = match symbols.get(symbol).copied() {
-                    Some(va) => va,
-                    None => {
-                        tracing::error!(cache_hit = false, "Symbol not found");
-                        return Ok(None);
-                    }
-                };
-
-                tracing::trace!(cache_hit = false, %va, "symbol found");
-
-                entry.insert(symbols);
-                Ok(Some(va))
-            }
-        }
-    }
-
-    /// Finds a first mapped region with the specified filename in the current
-    /// process and retrieves the exported symbols from the image. The filename
-    /// is case-insensitive. Returns map of exported symbols and their virtual
-    /// addresses.
-    #[tracing::instrument(skip(vmi))]
-    pub fn exported_symbols<Driver, Os>(
-        vmi: &VmiContext<'_, Driver, Os>,
-        filename: &str,
-    ) -> Result<Option<SymbolCache>, VmiError>
-    where
-        Driver: VmiDriver,
-        Os: VmiOs<Driver>,
-    {
-        let current_process = vmi.os().current_process()?;
-        let regions = vmi.os().process_regions(current_process)?;
-
-        let image = match regions.iter().find(|region| {
-            let mapped = match &region.kind {
-                OsRegionKind::Mapped(mapped) => mapped,
-                _ => return false,
-            };
-
-            let path = match &mapped.path {
-                Ok(Some(path)) => path,
-                _ => return false,
-            };
-
-            path.to_ascii_lowercase().ends_with(filename)
-        }) {
-            Some(image) => image,
-            None => return Ok(None),
-        };
-
-        let symbols = vmi.os().image_exported_symbols(image.start)?;
-
-        tracing::trace!(
-            va = %image.start,
-            kind = ?image.kind,
-            symbols = symbols.len(),
-            "image found"
-        );
-
-        Ok(Some(
-            symbols
-                .into_iter()
-                .map(|symbol| (symbol.name, symbol.address))
-                .collect(),
-        ))
-    }
 }
 
 /// A macro for defining a recipe.
@@ -318,7 +211,7 @@ macro_rules! _private_recipe {
             // Note that the lookup can return a [`VmiError::PageFault`].
             //
 
-            let function = match __private::lookup_symbol(
+            let function = match $crate::injector::RecipeContext::lookup_symbol(
                 $ctx,
                 concat!(stringify!($image), ".dll"),
                 stringify!($function)
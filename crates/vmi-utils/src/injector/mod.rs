@@ -11,6 +11,10 @@
 //! The injector currently only supports Windows OS and AMD64 architecture.
 //! Injections into 32-bit processes are not currently supported.
 //!
+//! By default, the recipe runs on a hijacked thread of the target process.
+//! With `InjectorHandler::with_new_thread()`, the hijacked thread only
+//! creates a new thread, and the recipe runs on that thread instead.
+//!
 //! # Examples
 //!
 //!  Inject a `MessageBox()` call into a running process:
@@ -106,6 +110,8 @@ pub use self::recipe::{
     ImageSymbolCache, Recipe, RecipeContext, RecipeControlFlow, RecipeExecutor,
 };
 
+pub mod recipes;
+
 /// A handler for managing code injection into a running system.
 ///
 /// The handler monitors CPU events to hijack threads, inject code, and track execution.
@@ -148,4 +154,33 @@ where
 
     /// Whether the injection has completed.
     pub(super) finished: bool,
+
+    /// Executor for creating a new thread that runs the recipe, if the
+    /// recipe shouldn't run on the hijacked thread.
+    pub(super) spawn: Option<RecipeExecutor<Driver, Os, ThreadSpawn>>,
+}
+
+/// State of the creation of a new thread for the injection.
+#[derive(Debug, Default)]
+pub(super) struct ThreadSpawn {
+    /// Start address of the new thread.
+    /// Assigned in 1st step.
+    start_address: Va,
+
+    /// Address of the `ThreadHandle` output parameter.
+    /// Assigned in 1st step and used in 2nd step.
+    thread_handle_ptr: Va,
+
+    /// Address of the `ClientId` output parameter.
+    /// Assigned in 1st step and used in 2nd step.
+    client_id_ptr: Va,
+
+    /// Handle of the new thread.
+    /// Assigned in 2nd step and used until the last step.
+    thread_handle: u64,
+
+    /// ID of the new thread.
+    /// Assigned in 2nd step, `None` if the thread couldn't be created
+    /// or resumed.
+    thread_id: Option<ThreadId>,
 }
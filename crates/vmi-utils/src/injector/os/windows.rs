@@ -2,13 +2,17 @@ use isr_core::Profile;
 use isr_macros::{offsets, Field};
 use vmi_arch_amd64::{Amd64, ControlRegister, EventMonitor, EventReason, Interrupt, Registers};
 use vmi_core::{
-    os::ProcessId, Architecture as _, Hex, MemoryAccess, Registers as _, Va, View, VmiContext,
-    VmiCore, VmiDriver, VmiError, VmiEventResponse, VmiHandler,
+    os::{ProcessId, ThreadId},
+    Architecture as _, Hex, MemoryAccess, Registers as _, Va, View, VmiContext, VmiCore, VmiDriver,
+    VmiError, VmiEventResponse, VmiHandler,
 };
 use vmi_os_windows::{WindowsOs, WindowsOsExt as _};
 
 use super::{
-    super::{arch::ArchAdapter as _, CallBuilder, InjectorHandler, Recipe, RecipeExecutor},
+    super::{
+        arch::ArchAdapter as _, CallBuilder, InjectorHandler, Recipe, RecipeContext,
+        RecipeControlFlow, RecipeExecutor, ThreadSpawn,
+    },
     OsAdapter,
 };
 
@@ -49,7 +53,12 @@ where
             "preparing function call"
         );
 
-        let arguments = Amd64::push_arguments(vmi, registers, &builder.arguments)?;
+        // The whole frame (the referenced argument data, the stack arguments
+        // and the return address) is written at once, since it usually
+        // spans only a page or two.
+        let mut writer = vmi.write_buffered();
+
+        let arguments = Amd64::push_arguments_buffered(&mut writer, registers, &builder.arguments)?;
 
         tracing::trace!(
             rsp = %Hex(registers.rsp),
@@ -92,7 +101,7 @@ where
         // write parameters (5th onwards) into guest's stack
         for index in (4..nb_args).rev() {
             addr -= 0x8;
-            vmi.write_u64((addr.into(), registers.cr3.into()), arguments[index])?;
+            writer.write_u64((addr.into(), registers.cr3.into()), arguments[index])?;
 
             tracing::trace!(
                 index,
@@ -148,7 +157,8 @@ where
 
         // save the return address
         addr -= 0x8;
-        vmi.write_u64((addr.into(), registers.cr3.into()), registers.rip)?;
+        writer.write_u64((addr.into(), registers.cr3.into()), registers.rip)?;
+        writer.commit()?;
 
         // grow the stack
         registers.rsp = addr;
@@ -202,9 +212,34 @@ where
             view,
             bridge,
             finished: false,
+            spawn: None,
         })
     }
 
+    /// Runs the recipe on a newly created thread instead of the hijacked
+    /// thread.
+    ///
+    /// The hijacked thread is used only to create the new thread, and
+    /// resumes its original execution right afterwards. The recipe is then
+    /// executed when the new thread starts, and the thread exits once the
+    /// recipe is finished.
+    ///
+    /// # Implementation Details
+    ///
+    /// The thread is created suspended by `ntdll!RtlCreateUserThread` with
+    /// `ntdll!RtlExitUserThread` as its start routine. The page of the
+    /// start routine is made non-executable in the injection view before
+    /// the thread is resumed by `ntdll!NtResumeThread`, so that the start
+    /// of the new thread is caught the same way as the resumption of
+    /// a hijacked thread. Until the hijacked thread is released, the new
+    /// thread is held at the instruction it faulted on. Once the recipe is
+    /// finished, the original registers of the new thread are restored and
+    /// the start routine terminates the thread with `STATUS_SUCCESS`.
+    pub fn with_new_thread(mut self) -> Self {
+        self.spawn = Some(RecipeExecutor::new(spawn_recipe()));
+        self
+    }
+
     #[tracing::instrument(
         name = "injector",
         skip_all,
@@ -343,9 +378,14 @@ where
         let current_thread = vmi.os().current_thread()?;
         let current_thread = Va::from(current_thread);
 
-        let trap_frame = vmi.read_va(current_thread + KTHREAD_TrapFrame)?;
-        let sp_va = vmi.read_va(trap_frame + KTRAP_FRAME_Rsp)?;
-        let ip_va = vmi.read_va(trap_frame + KTRAP_FRAME_Rip)?;
+        //
+        // The trap frame is a kernel structure, the effective address width
+        // would be wrong if the event occurred in a WoW64 thread.
+        //
+
+        let trap_frame = vmi.read_va_native(current_thread + KTHREAD_TrapFrame)?;
+        let sp_va = vmi.read_va_native(trap_frame + KTRAP_FRAME_Rsp)?;
+        let ip_va = vmi.read_va_native(trap_frame + KTRAP_FRAME_Rip)?;
 
         //
         // Verify that the next instruction of this thread is in a user-mode
@@ -459,10 +499,23 @@ where
         }
 
         //
-        // Early exit if the current thread is not the target thread.
+        // Hold the new thread until the hijacked thread is released. The
+        // faulting instruction is retried until the new thread becomes
+        // the target thread.
         //
 
         let current_tid = vmi.os().current_thread_id()?;
+        if let Some(spawn) = &self.spawn {
+            if !spawn.done() && spawn.data().thread_id == Some(current_tid) {
+                tracing::trace!(%current_tid, "holding new thread");
+                return Ok(VmiEventResponse::default());
+            }
+        }
+
+        //
+        // Early exit if the current thread is not the target thread.
+        //
+
         if Some(current_tid) != self.tid {
             // Too noisy...
             // tracing::trace!(
@@ -515,6 +568,34 @@ where
             }
         }
 
+        //
+        // If the recipe should run on a new thread, create it first.
+        //
+
+        if let Some(spawn) = &mut self.spawn {
+            if !spawn.done() {
+                let created = spawn.data().thread_id.is_some();
+                let new_registers = spawn.execute(vmi)?;
+                self.sp_va = Some(Va(new_registers.rsp));
+
+                let start_address = spawn.data().start_address;
+                let thread_id = spawn.data().thread_id;
+                let done = spawn.done();
+
+                if !created && thread_id.is_some() {
+                    self.on_thread_created(vmi, start_address)?;
+                }
+
+                if done {
+                    self.on_thread_spawned(vmi, start_address, thread_id)?;
+                }
+
+                return Ok(VmiEventResponse::set_registers(
+                    new_registers.gp_registers(),
+                ));
+            }
+        }
+
         //
         // Execute the next step in the recipe.
         //
@@ -557,6 +638,284 @@ where
             new_registers.gp_registers(),
         ))
     }
+
+    /// Arms the trap on the start routine of the thread created for the
+    /// injection.
+    ///
+    /// The thread is still suspended at this point, it is resumed by the
+    /// call prepared by the current step of the spawn recipe.
+    fn on_thread_created(
+        &mut self,
+        vmi: &VmiContext<Driver, WindowsOs<Driver>>,
+        start_address: Va,
+    ) -> Result<(), VmiError> {
+        let ip_pa = vmi.translate_address(start_address)?;
+        let ip_gfn = Driver::Architecture::gfn_from_pa(ip_pa);
+        vmi.set_memory_access(ip_gfn, self.view, MemoryAccess::RW)?;
+
+        Ok(())
+    }
+
+    /// Prepares for the start of the thread created for the injection.
+    ///
+    /// The hijacked thread is released, and the new thread becomes
+    /// the target of the injection. If the thread couldn't be created,
+    /// the injection is finished.
+    fn on_thread_spawned(
+        &mut self,
+        vmi: &VmiContext<Driver, WindowsOs<Driver>>,
+        start_address: Va,
+        thread_id: Option<ThreadId>,
+    ) -> Result<(), VmiError> {
+        let ip_pa = vmi.translate_address(start_address)?;
+        let ip_gfn = Driver::Architecture::gfn_from_pa(ip_pa);
+
+        // The trap on the start routine stays armed if the hijacked thread
+        // was caught on the same page.
+        let memory_access = vmi.event().reason().as_memory_access();
+        let gfn = Driver::Architecture::gfn_from_pa(memory_access.pa);
+        if gfn != ip_gfn {
+            vmi.set_memory_access(gfn, self.view, MemoryAccess::RWX)?;
+        }
+
+        let thread_id = match thread_id {
+            Some(thread_id) => thread_id,
+            None => {
+                vmi.monitor_disable(EventMonitor::Singlestep)?;
+                vmi.switch_to_view(vmi.default_view())?;
+                vmi.destroy_view(self.view)?;
+
+                self.finished = true;
+                return Ok(());
+            }
+        };
+
+        //
+        // The new thread is caught when it starts executing its start
+        // routine. Its stack is not known yet.
+        //
+
+        tracing::debug!(%thread_id, %start_address, "thread created");
+
+        self.tid = Some(thread_id);
+        self.sp_va = None;
+        self.ip_va = Some(start_address);
+        self.ip_pa = Some(ip_pa);
+
+        Ok(())
+    }
+}
+
+/// Creates the recipe that creates a new thread in the current process.
+///
+/// The thread is created suspended and resumed in a separate step, so
+/// that the handler can arm the trap on its start routine in between.
+///
+/// # Equivalent C pseudo-code
+///
+/// ```c
+/// HANDLE thread_handle;
+/// CLIENT_ID client_id;
+/// NTSTATUS status = RtlCreateUserThread(NtCurrentProcess(),   // ProcessHandle
+///                                       NULL,                 // SecurityDescriptor
+///                                       TRUE,                 // CreateSuspended
+///                                       0,                    // StackZeroBits
+///                                       0,                    // StackReserve
+///                                       0,                    // StackCommit
+///                                       RtlExitUserThread,    // StartAddress
+///                                       NULL,                 // Parameter
+///                                       &thread_handle,       // ThreadHandle
+///                                       &client_id);          // ClientId
+///
+/// if (!NT_SUCCESS(status)) {
+///     return;
+/// }
+///
+/// status = NtResumeThread(thread_handle,                      // ThreadHandle
+///                         NULL);                              // PreviousSuspendCount
+///
+/// if (!NT_SUCCESS(status)) {
+///     NtTerminateThread(thread_handle,                        // ThreadHandle
+///                       STATUS_SUCCESS);                      // ExitStatus
+/// }
+///
+/// NtClose(thread_handle);
+/// ```
+fn spawn_recipe<Driver>() -> Recipe<Driver, WindowsOs<Driver>, ThreadSpawn>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    Recipe::<Driver, WindowsOs<Driver>, ThreadSpawn>::new(ThreadSpawn::default())
+        //
+        // Step 1:
+        // - Create the thread suspended
+        //
+        .step(|ctx| {
+            let start_address = match ctx.lookup_symbol("ntdll.dll", "RtlExitUserThread")? {
+                Some(start_address) => start_address,
+                None => return Err(VmiError::Other("RtlExitUserThread not found")),
+            };
+
+            let function_address = match ctx.lookup_symbol("ntdll.dll", "RtlCreateUserThread")? {
+                Some(function_address) => function_address,
+                None => return Err(VmiError::Other("RtlCreateUserThread not found")),
+            };
+
+            tracing::debug!(%start_address, "ntdll!RtlCreateUserThread()");
+
+            // typedef struct _CLIENT_ID {
+            //     HANDLE UniqueProcess;
+            //     HANDLE UniqueThread;
+            // } CLIENT_ID;
+            let client_id_ptr = Amd64::copy_to_stack(ctx.vmi, ctx.registers, [0u64; 2])?;
+            let thread_handle_ptr = Amd64::copy_to_stack(ctx.vmi, ctx.registers, 0u64)?;
+
+            ctx.data.start_address = start_address;
+            ctx.data.thread_handle_ptr = thread_handle_ptr;
+            ctx.data.client_id_ptr = client_id_ptr;
+
+            let call = CallBuilder::new(function_address)
+                .with_argument(WindowsOs::<Driver>::NtCurrentProcess64) // ProcessHandle
+                .with_argument(0u64) // SecurityDescriptor
+                .with_argument(1u64) // CreateSuspended
+                .with_argument(0u64) // StackZeroBits
+                .with_argument(0u64) // StackReserve
+                .with_argument(0u64) // StackCommit
+                .with_argument(start_address) // StartAddress
+                .with_argument(0u64) // Parameter
+                .with_argument(thread_handle_ptr) // ThreadHandle
+                .with_argument(client_id_ptr); // ClientId
+
+            ctx.vmi
+                .underlying_os()
+                .prepare_function_call(ctx.vmi, ctx.registers, call)?;
+
+            Ok(RecipeControlFlow::Continue)
+        })
+        //
+        // Step 2:
+        // - Verify the status and read the handle and the ID of the new thread
+        // - Resume the thread
+        //
+        .step(|ctx| {
+            let status = ctx.registers.rax as u32;
+
+            if (status as i32) < 0 {
+                tracing::error!(
+                    status = %Hex(status),
+                    "ntdll!RtlCreateUserThread() failed"
+                );
+
+                return Ok(RecipeControlFlow::Break);
+            }
+
+            let thread_handle = ctx.vmi.read_u64(ctx.data.thread_handle_ptr)?;
+            let thread_id = ctx.vmi.read_u64(ctx.data.client_id_ptr + 8)?;
+            ctx.data.thread_handle = thread_handle;
+            ctx.data.thread_id = Some(ThreadId(thread_id as u32));
+
+            tracing::debug!(
+                thread_handle = %Hex(thread_handle),
+                thread_id,
+                "ntdll!NtResumeThread()"
+            );
+
+            let function_address = match ctx.lookup_symbol("ntdll.dll", "NtResumeThread")? {
+                Some(function_address) => function_address,
+                None => return Err(VmiError::Other("NtResumeThread not found")),
+            };
+
+            let call = CallBuilder::new(function_address)
+                .with_argument(thread_handle) // ThreadHandle
+                .with_argument(0u64); // PreviousSuspendCount
+
+            ctx.vmi
+                .underlying_os()
+                .prepare_function_call(ctx.vmi, ctx.registers, call)?;
+
+            Ok(RecipeControlFlow::Continue)
+        })
+        //
+        // Step 3:
+        // - Verify the status
+        // - Terminate the thread if it couldn't be resumed
+        //
+        .step(|ctx| {
+            let status = ctx.registers.rax as u32;
+
+            if (status as i32) >= 0 {
+                close_thread_handle(ctx)?;
+                return Ok(RecipeControlFlow::Skip);
+            }
+
+            tracing::error!(
+                status = %Hex(status),
+                "ntdll!NtResumeThread() failed"
+            );
+
+            ctx.data.thread_id = None;
+
+            let function_address = match ctx.lookup_symbol("ntdll.dll", "NtTerminateThread")? {
+                Some(function_address) => function_address,
+                None => return Err(VmiError::Other("NtTerminateThread not found")),
+            };
+
+            let call = CallBuilder::new(function_address)
+                .with_argument(ctx.data.thread_handle) // ThreadHandle
+                .with_argument(0u64); // ExitStatus
+
+            ctx.vmi
+                .underlying_os()
+                .prepare_function_call(ctx.vmi, ctx.registers, call)?;
+
+            Ok(RecipeControlFlow::Continue)
+        })
+        //
+        // Step 4:
+        // - Close the handle of the terminated thread
+        //
+        .step(|ctx| {
+            close_thread_handle(ctx)?;
+            Ok(RecipeControlFlow::Continue)
+        })
+        //
+        // Step 5:
+        // - Verify the status
+        //
+        .step(|ctx| {
+            let status = ctx.registers.rax as u32;
+
+            if (status as i32) < 0 {
+                tracing::warn!(
+                    status = %Hex(status),
+                    "ntdll!NtClose() failed"
+                );
+            }
+
+            Ok(RecipeControlFlow::Break)
+        })
+}
+
+/// Prepares the call to `ntdll!NtClose` closing the handle of the new
+/// thread.
+fn close_thread_handle<Driver>(
+    ctx: &mut RecipeContext<'_, Driver, WindowsOs<Driver>, ThreadSpawn>,
+) -> Result<(), VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    let function_address = match ctx.lookup_symbol("ntdll.dll", "NtClose")? {
+        Some(function_address) => function_address,
+        None => return Err(VmiError::Other("NtClose not found")),
+    };
+
+    let call = CallBuilder::new(function_address).with_argument(ctx.data.thread_handle);
+
+    ctx.vmi
+        .underlying_os()
+        .prepare_function_call(ctx.vmi, ctx.registers, call)?;
+
+    Ok(())
 }
 
 impl<Driver, T> VmiHandler<Driver, WindowsOs<Driver>>
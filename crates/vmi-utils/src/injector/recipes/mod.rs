@@ -0,0 +1,12 @@
+//! Ready-made injection recipes.
+//!
+//! These recipes implement common injection building blocks on top of
+//! [`Recipe`]. They can be passed directly to the [`InjectorHandler`].
+//!
+//! [`Recipe`]: super::Recipe
+//! [`InjectorHandler`]: super::InjectorHandler
+
+#[cfg(all(feature = "arch-amd64", feature = "os-windows"))]
+mod windows;
+#[cfg(all(feature = "arch-amd64", feature = "os-windows"))]
+pub use self::windows::LoadLibraryRecipe;
@@ -0,0 +1,436 @@
+use vmi_arch_amd64::Amd64;
+use vmi_core::{Hex, Va, VmiDriver, VmiError};
+use vmi_os_windows::WindowsOs;
+
+use super::super::{
+    ArchAdapter as _, CallBuilder, OsAdapter as _, Recipe, RecipeContext, RecipeControlFlow,
+};
+
+/// Recipe for loading a DLL into the target process via `ntdll!LdrLoadDll`.
+///
+/// The recipe allocates a scratch buffer in the target process, marshals
+/// the DLL path into a `_UNICODE_STRING` inside of it, calls `LdrLoadDll`
+/// and then verifies that the module has been inserted into the loader
+/// list of the process by looking it up with `ntdll!LdrGetDllHandle`.
+/// The scratch buffer is released before the recipe finishes, whether
+/// the DLL has been loaded or not.
+///
+/// # Notes
+///
+/// The scratch buffer holds the `_UNICODE_STRING`, the output parameters
+/// of both calls and the path itself. Keeping them off the stack of the
+/// hijacked thread means the recipe doesn't depend on the pages below the
+/// stack pointer being mapped, and the loader can't overwrite the path
+/// while it is still being used. The path is at most 64 KiB long, the
+/// limit of `UNICODE_STRING`.
+///
+/// # Equivalent C pseudo-code
+///
+/// ```c
+/// struct {
+///     UNICODE_STRING path;
+///     HANDLE module_handle;
+///     HANDLE loaded_handle;
+///     WCHAR buffer[];
+/// } *scratch = NULL;
+///
+/// SIZE_T size = sizeof(*scratch) + sizeof(L"...\\library.dll");
+/// NTSTATUS status = NtAllocateVirtualMemory(
+///     NtCurrentProcess(),         // ProcessHandle
+///     &scratch,                   // BaseAddress
+///     0,                          // ZeroBits
+///     &size,                      // RegionSize
+///     MEM_COMMIT | MEM_RESERVE,   // AllocationType
+///     PAGE_READWRITE);            // Protect
+///
+/// if (!NT_SUCCESS(status)) {
+///     printf("ntdll!NtAllocateVirtualMemory() failed\n");
+///     return;
+/// }
+///
+/// wcscpy(scratch->buffer, L"...\\library.dll");
+/// RtlInitUnicodeString(&scratch->path, scratch->buffer);
+///
+/// status = LdrLoadDll(NULL,                       // PathToFile
+///                     NULL,                       // Flags
+///                     &scratch->path,             // ModuleFileName
+///                     &scratch->module_handle);   // ModuleHandle
+///
+/// if (!NT_SUCCESS(status)) {
+///     printf("ntdll!LdrLoadDll() failed\n");
+///     goto free;
+/// }
+///
+/// status = LdrGetDllHandle(NULL,                    // DllPath
+///                          NULL,                    // DllCharacteristics
+///                          &scratch->path,          // DllName
+///                          &scratch->loaded_handle); // DllHandle
+///
+/// if (!NT_SUCCESS(status) || scratch->loaded_handle != scratch->module_handle) {
+///     printf("module not found in the loader list\n");
+/// }
+///
+/// free:
+/// size = 0;
+/// NtFreeVirtualMemory(NtCurrentProcess(),         // ProcessHandle
+///                     &scratch,                   // BaseAddress
+///                     &size,                      // RegionSize
+///                     MEM_RELEASE);               // FreeType
+/// ```
+///
+/// # Examples
+///
+/// ```no_run
+/// use vmi::{
+///     arch::amd64::Amd64,
+///     os::windows::WindowsOs,
+///     utils::injector::{recipes::LoadLibraryRecipe, InjectorHandler},
+///     VmiDriver, VmiSession,
+/// };
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # use vmi::driver::xen::VmiXenDriver;
+/// # let vmi: VmiSession<VmiXenDriver<Amd64>, WindowsOs<VmiXenDriver<Amd64>>> = unimplemented!();
+/// # let profile = unimplemented!();
+/// # let pid = unimplemented!();
+/// #
+/// vmi.handle(|vmi| {
+///     InjectorHandler::new(
+///         vmi,
+///         &profile,
+///         pid,
+///         LoadLibraryRecipe::new("C:\\Windows\\System32\\version.dll").into_recipe(),
+///     )
+/// })?;
+/// #
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LoadLibraryRecipe {
+    /// Path of the DLL to load.
+    path: String,
+
+    /// Address of the `BaseAddress` parameter of `NtAllocateVirtualMemory`.
+    /// Assigned in 1st step and used in 2nd step.
+    base_address_ptr: Va,
+
+    /// Base address of the scratch buffer.
+    /// Assigned in 2nd step and used until the last step.
+    scratch: Va,
+
+    /// Base address of the loaded module.
+    /// Assigned in 3rd step and used in 4th step.
+    module_handle: Va,
+}
+
+/// Offset of the `_UNICODE_STRING` describing the path in the scratch buffer.
+const PATH_OFFSET: u64 = 0x00;
+
+/// Offset of the `ModuleHandle` output parameter in the scratch buffer.
+const MODULE_HANDLE_OFFSET: u64 = 0x10;
+
+/// Offset of the `DllHandle` output parameter in the scratch buffer.
+const LOADED_HANDLE_OFFSET: u64 = 0x18;
+
+/// Offset of the NUL-terminated UTF-16 path in the scratch buffer.
+const BUFFER_OFFSET: u64 = 0x20;
+
+/// `MEM_COMMIT` allocation type.
+const MEM_COMMIT: u64 = 0x1000;
+
+/// `MEM_RESERVE` allocation type.
+const MEM_RESERVE: u64 = 0x2000;
+
+/// `MEM_RELEASE` free type.
+const MEM_RELEASE: u64 = 0x8000;
+
+/// `PAGE_READWRITE` page protection.
+const PAGE_READWRITE: u64 = 0x04;
+
+impl LoadLibraryRecipe {
+    /// Creates a new recipe for loading the DLL at the given path.
+    pub fn new(path: impl AsRef<str>) -> Self {
+        Self {
+            path: path.as_ref().to_string(),
+
+            // Mutable fields.
+            base_address_ptr: Va::default(),
+            scratch: Va::default(),
+            module_handle: Va::default(),
+        }
+    }
+
+    /// Returns the path of the DLL to load.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Converts the data into an executable recipe.
+    pub fn into_recipe<Driver>(self) -> Recipe<Driver, WindowsOs<Driver>, Self>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        Recipe::<Driver, WindowsOs<Driver>, Self>::new(self)
+            //
+            // Step 1:
+            // - Allocate the scratch buffer
+            //
+            .step(|ctx| {
+                tracing::info!(
+                    path = ctx.data.path,
+                    "step 1: ntdll!NtAllocateVirtualMemory()"
+                );
+
+                let region_size = BUFFER_OFFSET + path_buffer(&ctx.data.path)?.len() as u64;
+                let base_address_ptr = Amd64::copy_to_stack(ctx.vmi, ctx.registers, 0u64)?;
+                let region_size_ptr = Amd64::copy_to_stack(ctx.vmi, ctx.registers, region_size)?;
+                ctx.data.base_address_ptr = base_address_ptr;
+
+                #[rustfmt::skip]
+                let arguments = [
+                    WindowsOs::<Driver>::NtCurrentProcess64,    // ProcessHandle
+                    base_address_ptr.0,                         // BaseAddress
+                    0,                                          // ZeroBits
+                    region_size_ptr.0,                          // RegionSize
+                    MEM_COMMIT | MEM_RESERVE,                   // AllocationType
+                    PAGE_READWRITE,                             // Protect
+                ];
+
+                call(ctx, "NtAllocateVirtualMemory", arguments)
+            })
+            //
+            // Step 2:
+            // - Verify the status of the allocation
+            // - Marshal the path into a `_UNICODE_STRING`
+            // - Load the DLL
+            //
+            .step(|ctx| {
+                let status = ctx.registers.rax as u32;
+
+                if !nt_success(status) {
+                    tracing::error!(
+                        status = %Hex(status),
+                        "step 2: ntdll!NtAllocateVirtualMemory() failed"
+                    );
+
+                    return Ok(RecipeControlFlow::Break);
+                }
+
+                let scratch = ctx.vmi.read_va64(ctx.data.base_address_ptr)?;
+                ctx.data.scratch = scratch;
+
+                tracing::info!(%scratch, "step 2: ntdll!LdrLoadDll()");
+
+                write_scratch(ctx)?;
+
+                #[rustfmt::skip]
+                let arguments = [
+                    0,                                  // PathToFile
+                    0,                                  // Flags
+                    (scratch + PATH_OFFSET).0,          // ModuleFileName
+                    (scratch + MODULE_HANDLE_OFFSET).0, // ModuleHandle
+                ];
+
+                call(ctx, "LdrLoadDll", arguments)
+            })
+            //
+            // Step 3:
+            // - Verify the status and the module handle
+            // - Look up the module in the loader list
+            //
+            .step(|ctx| {
+                let status = ctx.registers.rax as u32;
+                let scratch = ctx.data.scratch;
+
+                if !nt_success(status) {
+                    tracing::error!(
+                        status = %Hex(status),
+                        "step 3: ntdll!LdrLoadDll() failed"
+                    );
+
+                    free_scratch(ctx)?;
+                    return Ok(RecipeControlFlow::Skip);
+                }
+
+                let module_handle = ctx.vmi.read_va64(scratch + MODULE_HANDLE_OFFSET)?;
+                ctx.data.module_handle = module_handle;
+
+                tracing::info!(
+                    module_handle = %module_handle,
+                    "step 3: ntdll!LdrGetDllHandle()"
+                );
+
+                #[rustfmt::skip]
+                let arguments = [
+                    0,                                  // DllPath
+                    0,                                  // DllCharacteristics
+                    (scratch + PATH_OFFSET).0,          // DllName
+                    (scratch + LOADED_HANDLE_OFFSET).0, // DllHandle
+                ];
+
+                call(ctx, "LdrGetDllHandle", arguments)
+            })
+            //
+            // Step 4:
+            // - Verify that the module is present in the loader list
+            // - Free the scratch buffer
+            //
+            .step(|ctx| {
+                let status = ctx.registers.rax as u32;
+                let module_handle = ctx.data.module_handle;
+
+                if !nt_success(status) {
+                    tracing::error!(
+                        status = %Hex(status),
+                        %module_handle,
+                        "step 4: module not found in the loader list"
+                    );
+
+                    return free_scratch(ctx);
+                }
+
+                let loaded_handle = ctx.vmi.read_va64(ctx.data.scratch + LOADED_HANDLE_OFFSET)?;
+
+                if loaded_handle != module_handle {
+                    tracing::error!(
+                        %loaded_handle,
+                        %module_handle,
+                        "step 4: module handle mismatch"
+                    );
+
+                    return free_scratch(ctx);
+                }
+
+                tracing::info!(
+                    %module_handle,
+                    path = ctx.data.path,
+                    "step 4: module loaded"
+                );
+
+                free_scratch(ctx)
+            })
+            //
+            // Step 5:
+            // - Verify that the scratch buffer has been freed
+            //
+            .step(|ctx| {
+                let status = ctx.registers.rax as u32;
+
+                if !nt_success(status) {
+                    tracing::error!(
+                        status = %Hex(status),
+                        scratch = %ctx.data.scratch,
+                        "step 5: ntdll!NtFreeVirtualMemory() failed"
+                    );
+                }
+
+                Ok(RecipeControlFlow::Break)
+            })
+    }
+}
+
+/// Returns `true` if the `NTSTATUS` value indicates success.
+fn nt_success(status: u32) -> bool {
+    (status as i32) >= 0
+}
+
+/// Encodes the path as a NUL-terminated UTF-16 string.
+///
+/// Returns an error if the string doesn't fit into a `_UNICODE_STRING`.
+fn path_buffer(path: &str) -> Result<Vec<u8>, VmiError> {
+    let buffer = path
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(u16::to_le_bytes)
+        .collect::<Vec<_>>();
+
+    if u16::try_from(buffer.len()).is_err() {
+        return Err(VmiError::Other("DLL path is too long"));
+    }
+
+    Ok(buffer)
+}
+
+/// Writes the `_UNICODE_STRING` describing the path, zeroed output
+/// parameters and the path itself into the scratch buffer.
+fn write_scratch<Driver>(
+    ctx: &mut RecipeContext<'_, Driver, WindowsOs<Driver>, LoadLibraryRecipe>,
+) -> Result<(), VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    let scratch = ctx.data.scratch;
+    let buffer = path_buffer(&ctx.data.path)?;
+
+    // The length has been checked by `path_buffer()`.
+    let maximum_length = buffer.len() as u16;
+    let length = maximum_length - size_of::<u16>() as u16;
+
+    // typedef struct _UNICODE_STRING {
+    //     USHORT Length;
+    //     USHORT MaximumLength;
+    //     PWSTR  Buffer;
+    // } UNICODE_STRING;
+    let mut data = Vec::with_capacity(BUFFER_OFFSET as usize + buffer.len());
+    data.extend_from_slice(&(u64::from(length) | u64::from(maximum_length) << 16).to_le_bytes());
+    data.extend_from_slice(&(scratch + BUFFER_OFFSET).0.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // ModuleHandle
+    data.extend_from_slice(&0u64.to_le_bytes()); // DllHandle
+    data.extend_from_slice(&buffer);
+
+    ctx.vmi.write(scratch + PATH_OFFSET, &data)
+}
+
+/// Prepares the call to `ntdll!NtFreeVirtualMemory` releasing the scratch
+/// buffer.
+fn free_scratch<Driver>(
+    ctx: &mut RecipeContext<'_, Driver, WindowsOs<Driver>, LoadLibraryRecipe>,
+) -> Result<RecipeControlFlow, VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    tracing::info!(
+        scratch = %ctx.data.scratch,
+        "ntdll!NtFreeVirtualMemory()"
+    );
+
+    let base_address_ptr = Amd64::copy_to_stack(ctx.vmi, ctx.registers, ctx.data.scratch.0)?;
+    let region_size_ptr = Amd64::copy_to_stack(ctx.vmi, ctx.registers, 0u64)?;
+
+    #[rustfmt::skip]
+    let arguments = [
+        WindowsOs::<Driver>::NtCurrentProcess64,    // ProcessHandle
+        base_address_ptr.0,                         // BaseAddress
+        region_size_ptr.0,                          // RegionSize
+        MEM_RELEASE,                                // FreeType
+    ];
+
+    call(ctx, "NtFreeVirtualMemory", arguments)
+}
+
+/// Resolves the `ntdll.dll` export and prepares the registers for the call.
+fn call<Driver, const N: usize>(
+    ctx: &mut RecipeContext<'_, Driver, WindowsOs<Driver>, LoadLibraryRecipe>,
+    function: &str,
+    arguments: [u64; N],
+) -> Result<RecipeControlFlow, VmiError>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    let function_address = match ctx.lookup_symbol("ntdll.dll", function)? {
+        Some(function_address) => function_address,
+        None => return Err(VmiError::Other("ntdll.dll export not found")),
+    };
+
+    let call = arguments.into_iter().fold(
+        CallBuilder::new(function_address),
+        CallBuilder::with_argument,
+    );
+
+    ctx.vmi
+        .underlying_os()
+        .prepare_function_call(ctx.vmi, ctx.registers, call)?;
+
+    Ok(RecipeControlFlow::Continue)
+}
@@ -1,5 +1,8 @@
 //! VMI utilities
 
+#[cfg(feature = "access-grant")]
+pub mod access_grant;
+
 #[cfg(feature = "bpm")]
 pub mod bpm;
 
@@ -9,8 +12,35 @@ pub mod injector;
 #[cfg(feature = "interceptor")]
 pub mod interceptor;
 
+#[cfg(feature = "process-index")]
+pub mod process_index;
+
 #[cfg(feature = "ptm")]
 pub mod ptm;
 
+#[cfg(feature = "region-map")]
+pub mod region_map;
+
+#[cfg(feature = "scratch")]
+pub mod scratch;
+
+#[cfg(feature = "snapshot")]
+pub mod snapshot;
+
+#[cfg(feature = "struct-watcher")]
+pub mod struct_watcher;
+
+#[cfg(feature = "symbolizer")]
+pub mod symbolizer;
+
+#[cfg(feature = "tracer")]
+pub mod tracer;
+
+#[cfg(feature = "view")]
+pub mod view;
+
 mod hexdump;
-pub use self::hexdump::{hexdump, Representation};
+pub use self::hexdump::{hexdump, hexdump_annotated, Representation};
+
+#[cfg(all(test, feature = "arch-amd64"))]
+mod testing;
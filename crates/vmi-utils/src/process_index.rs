@@ -0,0 +1,391 @@
+//! Translation root to process lookup.
+//!
+//! Event handlers often need to know which process triggered an event, but
+//! the only readily available information is the translation root (e.g.,
+//! `CR3` on AMD64). Enumerating all processes on every event is expensive,
+//! so the [`ProcessIndex`] caches the mapping from translation roots to
+//! processes and rebuilds it only when it becomes stale.
+//!
+//! The index becomes stale when the configured refresh interval elapses,
+//! or when it is explicitly invalidated (e.g., from a process-creation
+//! event handler). Lookups of unknown translation roots also trigger
+//! a refresh, because they usually belong to a process created since the
+//! last refresh. These refreshes are rate-limited, so that a translation
+//! root that doesn't belong to any process (e.g., of a process that is
+//! being torn down) doesn't cause a process walk on every event.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use vmi_core::{
+    os::{ProcessId, ProcessObject, VmiOs},
+    Architecture, Pa, VmiCore, VmiDriver, VmiError,
+};
+
+/// A process known to the [`ProcessIndex`].
+#[derive(Debug, Clone)]
+pub struct ProcessIndexEntry {
+    /// The process object.
+    pub object: ProcessObject,
+
+    /// The PID of the process.
+    pub id: ProcessId,
+
+    /// The short name of the process.
+    pub name: String,
+}
+
+/// A cache of translation root to process mappings.
+#[derive(Debug)]
+pub struct ProcessIndex {
+    /// Processes indexed by their translation roots.
+    ///
+    /// A process may be present under two translation roots, if the kernel
+    /// uses separate user-mode and kernel-mode page tables.
+    entries: HashMap<Pa, ProcessIndexEntry>,
+
+    /// Interval after which the index is considered stale.
+    refresh_interval: Option<Duration>,
+
+    /// Minimum interval between refreshes triggered by lookup misses.
+    miss_refresh_interval: Duration,
+
+    /// Time of the last refresh.
+    last_refresh: Option<Instant>,
+}
+
+impl Default for ProcessIndex {
+    fn default() -> Self {
+        Self {
+            entries: HashMap::new(),
+            refresh_interval: None,
+            miss_refresh_interval: Self::DEFAULT_MISS_REFRESH_INTERVAL,
+            last_refresh: None,
+        }
+    }
+}
+
+impl ProcessIndex {
+    /// The default minimum interval between refreshes triggered by lookup
+    /// misses.
+    pub const DEFAULT_MISS_REFRESH_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Creates a new empty index.
+    ///
+    /// The index is refreshed on the first lookup.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the interval after which the index is considered stale.
+    ///
+    /// By default, the index is refreshed only when it is invalidated or
+    /// when a lookup misses.
+    pub fn with_refresh_interval(self, refresh_interval: Duration) -> Self {
+        Self {
+            refresh_interval: Some(refresh_interval),
+            ..self
+        }
+    }
+
+    /// Sets the minimum interval between refreshes triggered by lookup
+    /// misses.
+    ///
+    /// A lookup of an unknown translation root refreshes the index only if
+    /// at least this much time has passed since the last refresh. A zero
+    /// interval refreshes the index on every miss, [`Duration::MAX`]
+    /// disables refreshes on misses.
+    pub fn with_miss_refresh_interval(self, miss_refresh_interval: Duration) -> Self {
+        Self {
+            miss_refresh_interval,
+            ..self
+        }
+    }
+
+    /// Checks whether the index needs to be refreshed.
+    pub fn is_stale(&self) -> bool {
+        match (self.last_refresh, self.refresh_interval) {
+            (None, _) => true,
+            (Some(last_refresh), Some(refresh_interval)) => {
+                last_refresh.elapsed() >= refresh_interval
+            }
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Marks the index as stale, so that it is refreshed on the next
+    /// lookup.
+    ///
+    /// Call this from process-creation and process-exit event handlers.
+    pub fn invalidate(&mut self) {
+        self.last_refresh = None;
+    }
+
+    /// Rebuilds the index from the list of processes.
+    pub fn refresh<Driver, Os>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        os: &Os,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+        Os: VmiOs<Driver>,
+    {
+        let mut entries = HashMap::new();
+
+        for process in os.processes(vmi, registers)? {
+            let entry = ProcessIndexEntry {
+                object: process.object,
+                id: process.id,
+                name: process.name,
+            };
+
+            match os.process_user_translation_root(vmi, registers, process.object) {
+                Ok(root) if root != process.translation_root => {
+                    entries.insert(root, entry.clone());
+                }
+                Ok(_) | Err(VmiError::NotSupported) => {}
+                Err(err) => {
+                    tracing::debug!(?err, pid = %entry.id, "failed to read user translation root");
+                }
+            }
+
+            entries.insert(process.translation_root, entry);
+        }
+
+        tracing::trace!(processes = entries.len(), "refreshed process index");
+
+        self.entries = entries;
+        self.last_refresh = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Looks up the process with the given translation root, without
+    /// refreshing the index.
+    pub fn get(&self, root: Pa) -> Option<&ProcessIndexEntry> {
+        self.entries.get(&root)
+    }
+
+    /// Looks up the process with the given translation root.
+    ///
+    /// The index is refreshed first if it is stale, or if the translation
+    /// root is not found and the last refresh is older than the miss
+    /// refresh interval (see [`with_miss_refresh_interval`]).
+    ///
+    /// [`with_miss_refresh_interval`]: Self::with_miss_refresh_interval
+    pub fn lookup<Driver, Os>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        os: &Os,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        root: Pa,
+    ) -> Result<Option<&ProcessIndexEntry>, VmiError>
+    where
+        Driver: VmiDriver,
+        Os: VmiOs<Driver>,
+    {
+        if self.needs_refresh(root, Instant::now()) {
+            self.refresh(vmi, os, registers)?;
+        }
+
+        Ok(self.entries.get(&root))
+    }
+
+    /// Checks whether a lookup of the translation root at the given time
+    /// needs to refresh the index first.
+    fn needs_refresh(&self, root: Pa, now: Instant) -> bool {
+        if self.is_stale() {
+            return true;
+        }
+
+        if self.entries.contains_key(&root) {
+            return false;
+        }
+
+        match self.last_refresh {
+            Some(last_refresh) => {
+                now.saturating_duration_since(last_refresh) >= self.miss_refresh_interval
+            }
+            None => true,
+        }
+    }
+
+    /// Returns an iterator over the indexed translation roots and their
+    /// processes.
+    pub fn iter(&self) -> impl Iterator<Item = (Pa, &ProcessIndexEntry)> {
+        self.entries.iter().map(|(root, entry)| (*root, entry))
+    }
+
+    /// Returns the number of indexed translation roots.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Checks whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(all(test, feature = "arch-amd64"))]
+mod tests {
+    use vmi_arch_amd64::Registers;
+    use vmi_core::Gfn;
+
+    use super::*;
+    use crate::testing::{MockDriver, MockOs};
+
+    const ROOT_SYSTEM: Pa = Pa(0x1000);
+    const ROOT_PROCESS: Pa = Pa(0x2000);
+    const ROOT_NEW: Pa = Pa(0x3000);
+
+    fn setup() -> (VmiCore<MockDriver>, MockOs) {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let os = MockOs::new();
+        os.add_process(4, ROOT_SYSTEM);
+        os.add_process(100, ROOT_PROCESS);
+        (vmi, os)
+    }
+
+    fn lookup(
+        index: &mut ProcessIndex,
+        vmi: &VmiCore<MockDriver>,
+        os: &MockOs,
+        root: Pa,
+    ) -> Option<ProcessId> {
+        index
+            .lookup(vmi, os, &Registers::default(), root)
+            .unwrap()
+            .map(|entry| entry.id)
+    }
+
+    #[test]
+    fn first_lookup_refreshes() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new();
+        assert!(index.is_stale());
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_PROCESS),
+            Some(ProcessId(100))
+        );
+        assert_eq!(os.process_walks(), 1);
+        assert_eq!(index.len(), 2);
+        assert!(!index.is_stale());
+    }
+
+    #[test]
+    fn hit_does_not_refresh() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new();
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_PROCESS),
+            Some(ProcessId(100))
+        );
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+        assert_eq!(os.process_walks(), 1);
+    }
+
+    #[test]
+    fn miss_refresh_is_rate_limited() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new().with_miss_refresh_interval(Duration::from_secs(3600));
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+        assert_eq!(os.process_walks(), 1);
+
+        // The process was created after the last refresh, but the refresh
+        // is rate-limited, so the lookup misses.
+        os.add_process(200, ROOT_NEW);
+        assert_eq!(lookup(&mut index, &vmi, &os, ROOT_NEW), None);
+        assert_eq!(lookup(&mut index, &vmi, &os, ROOT_NEW), None);
+        assert_eq!(os.process_walks(), 1);
+    }
+
+    #[test]
+    fn miss_refreshes_after_interval() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new().with_miss_refresh_interval(Duration::ZERO);
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+
+        os.add_process(200, ROOT_NEW);
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_NEW),
+            Some(ProcessId(200))
+        );
+        assert_eq!(os.process_walks(), 2);
+
+        // Unknown roots refresh the index on every miss.
+        assert_eq!(lookup(&mut index, &vmi, &os, Pa(0x4000)), None);
+        assert_eq!(os.process_walks(), 3);
+    }
+
+    #[test]
+    fn miss_refresh_can_be_disabled() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new().with_miss_refresh_interval(Duration::MAX);
+
+        assert_eq!(lookup(&mut index, &vmi, &os, ROOT_NEW), None);
+        assert_eq!(lookup(&mut index, &vmi, &os, ROOT_NEW), None);
+        assert_eq!(os.process_walks(), 1);
+    }
+
+    #[test]
+    fn invalidate_forces_refresh() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new().with_miss_refresh_interval(Duration::MAX);
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+
+        os.add_process(200, ROOT_NEW);
+        index.invalidate();
+        assert!(index.is_stale());
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_NEW),
+            Some(ProcessId(200))
+        );
+        assert_eq!(os.process_walks(), 2);
+    }
+
+    #[test]
+    fn refresh_interval_makes_index_stale() {
+        let (vmi, os) = setup();
+        let mut index = ProcessIndex::new()
+            .with_refresh_interval(Duration::ZERO)
+            .with_miss_refresh_interval(Duration::MAX);
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+        assert!(index.is_stale());
+
+        assert_eq!(
+            lookup(&mut index, &vmi, &os, ROOT_SYSTEM),
+            Some(ProcessId(4))
+        );
+        assert_eq!(os.process_walks(), 2);
+    }
+}
@@ -49,7 +49,7 @@ impl<T> TagType for T where T: Debug + Copy + Eq + Hash {}
 /// Page Entry Update.
 ///
 /// Page entry update that represents a change in a page table entry.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct PageEntryUpdate {
     /// View in which the update occurred.
     pub view: View,
@@ -61,11 +61,27 @@ pub struct PageEntryUpdate {
     pub pa: Pa,
 }
 
+impl PageEntryUpdate {
+    /// Creates a new page entry update.
+    ///
+    /// Updates are normally created by the [`PageTableMonitor`]. Creating
+    /// them manually allows feeding synthetic events to the consumers of
+    /// the monitor (e.g., `BreakpointManager::handle_ptm_event`) without
+    /// a running virtual machine.
+    pub fn new(view: View, ctx: impl Into<AddressContext>, pa: Pa) -> Self {
+        Self {
+            view,
+            ctx: ctx.into(),
+            pa,
+        }
+    }
+}
+
 /// Page Table Monitor Event.
 ///
 /// Page table monitor event that represents a change in the page table
 /// hierarchy.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PageTableMonitorEvent {
     /// Page In.
     ///
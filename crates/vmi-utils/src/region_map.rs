@@ -0,0 +1,562 @@
+//! Labeling of guest virtual address ranges.
+//!
+//! Analysis output is much easier to read when raw addresses are annotated
+//! with what they point into, e.g., `module:ntdll.dll` or `private`.
+//! The [`RegionMap`] holds labeled address ranges and answers the question
+//! "what is at this address?" with [`RegionMap::label_for`].
+//!
+//! Ranges may overlap. A module is usually contained in a larger mapped
+//! region, and tools may add their own labels (e.g., `stack` or `heap`) on
+//! top of the regions enumerated by the OS. Lookups return the innermost,
+//! i.e., the smallest, range containing the address.
+//!
+//! The map can be populated from the memory regions of a process
+//! ([`RegionMap::insert_process_regions`]) and from the list of loaded
+//! kernel modules ([`RegionMap::insert_kernel_modules`]).
+//!
+//! # Implementation Details
+//!
+//! The ranges are kept in a few levels, each sorted by the start address
+//! and forming an implicit interval tree: the root of every subtree is the
+//! middle element of its slice, and each node stores the maximum end
+//! address within its subtree. A lookup skips every subtree whose maximum
+//! end address is below the queried address, which makes it
+//! `O(log n + k)` per level, where `k` is the number of ranges containing
+//! the address.
+//!
+//! An inserted range forms a new level. While a level is not more than
+//! twice as large as the level after it, the two are merged and the tree of
+//! the merged level is rebuilt. Each level is therefore less than half as
+//! large as the previous one, so there are at most `O(log n)` levels, and
+//! every range is merged `O(log n)` times over its lifetime.
+
+use vmi_core::{
+    os::{OsModule, OsRegion, OsRegionKind, ProcessObject, VmiOs},
+    Architecture, Va, VmiCore, VmiDriver, VmiError,
+};
+
+/// A labeled range of virtual addresses.
+#[derive(Debug, Clone)]
+pub struct LabeledRegion {
+    /// The start address of the range.
+    pub start: Va,
+
+    /// The end address of the range (exclusive).
+    pub end: Va,
+
+    /// The label of the range.
+    pub label: String,
+}
+
+impl LabeledRegion {
+    /// Checks whether the range contains the given address.
+    pub fn contains(&self, va: Va) -> bool {
+        self.start <= va && va < self.end
+    }
+
+    /// Returns the size of the range, in bytes.
+    pub fn size(&self) -> u64 {
+        self.end.0 - self.start.0
+    }
+}
+
+/// A node of the implicit interval tree.
+#[derive(Debug, Clone)]
+struct Node {
+    /// The labeled range.
+    region: LabeledRegion,
+
+    /// Insertion order, used to prefer the most recent of equally sized
+    /// ranges.
+    sequence: u64,
+
+    /// The maximum end address within the subtree rooted at this node.
+    max_end: Va,
+}
+
+/// A map of labeled virtual address ranges.
+#[derive(Debug, Default, Clone)]
+pub struct RegionMap {
+    /// Levels of nodes, each sorted by the start address.
+    ///
+    /// Each level is less than half as large as the previous one.
+    levels: Vec<Vec<Node>>,
+
+    /// Sequence number of the next inserted range.
+    next_sequence: u64,
+}
+
+impl RegionMap {
+    /// Creates a new empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a labeled range `[start, end)`.
+    ///
+    /// Empty ranges are ignored.
+    pub fn insert(&mut self, start: Va, end: Va, label: impl Into<String>) {
+        if start >= end {
+            return;
+        }
+
+        let node = self.node(start, end, label.into());
+        self.push_level(vec![node]);
+    }
+
+    /// Inserts multiple labeled ranges.
+    ///
+    /// This is more efficient than inserting the ranges one by one, because
+    /// the ranges are merged into the map at once.
+    pub fn extend(&mut self, regions: impl IntoIterator<Item = LabeledRegion>) {
+        let mut nodes = Vec::new();
+        for region in regions {
+            if region.start >= region.end {
+                continue;
+            }
+
+            nodes.push(self.node(region.start, region.end, region.label));
+        }
+
+        if nodes.is_empty() {
+            return;
+        }
+
+        // Stable sort keeps the insertion order of ranges with the same start.
+        nodes.sort_by_key(|node| node.region.start);
+        self.push_level(nodes);
+    }
+
+    /// Inserts the memory regions of a process.
+    ///
+    /// Private regions are labeled `private`. Mapped regions are labeled
+    /// `mapped:<path>` if the path of the backing file is known, and
+    /// `mapped` otherwise.
+    pub fn insert_regions(&mut self, regions: &[OsRegion]) {
+        self.extend(regions.iter().map(|region| {
+            let label = match &region.kind {
+                OsRegionKind::Private => String::from("private"),
+                OsRegionKind::Mapped(mapped) => match &mapped.path {
+                    Ok(Some(path)) => format!("mapped:{path}"),
+                    _ => String::from("mapped"),
+                },
+            };
+
+            LabeledRegion {
+                start: region.start,
+                end: region.end,
+                label,
+            }
+        }));
+    }
+
+    /// Inserts the ranges occupied by modules, labeled `module:<name>`.
+    ///
+    /// Modules whose range would wrap around the end of the address space
+    /// are skipped.
+    pub fn insert_modules(&mut self, modules: &[OsModule]) {
+        self.extend(modules.iter().filter_map(|module| {
+            Some(LabeledRegion {
+                start: module.base_address,
+                end: Va(module.base_address.0.checked_add(module.size)?),
+                label: format!("module:{}", module.name),
+            })
+        }));
+    }
+
+    /// Enumerates the memory regions of a process and inserts them.
+    ///
+    /// See [`insert_regions`] for the labels.
+    ///
+    /// [`insert_regions`]: Self::insert_regions
+    pub fn insert_process_regions<Driver, Os>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        os: &Os,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+        process: ProcessObject,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+        Os: VmiOs<Driver>,
+    {
+        let regions = os.process_regions(vmi, registers, process)?;
+        self.insert_regions(&regions);
+        Ok(())
+    }
+
+    /// Enumerates the loaded kernel modules and inserts them.
+    ///
+    /// See [`insert_modules`] for the labels.
+    ///
+    /// [`insert_modules`]: Self::insert_modules
+    pub fn insert_kernel_modules<Driver, Os>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        os: &Os,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+        Os: VmiOs<Driver>,
+    {
+        let modules = os.modules(vmi, registers)?;
+        self.insert_modules(&modules);
+        Ok(())
+    }
+
+    /// Removes all ranges with the given label.
+    ///
+    /// Returns the number of removed ranges.
+    pub fn remove_label(&mut self, label: &str) -> usize {
+        let len = self.len();
+
+        let mut nodes = std::mem::take(&mut self.levels)
+            .into_iter()
+            .flatten()
+            .filter(|node| node.region.label != label)
+            .collect::<Vec<_>>();
+
+        let removed = len - nodes.len();
+
+        //
+        // Rebuild the map as a single level.
+        //
+
+        if !nodes.is_empty() {
+            nodes.sort_by_key(|node| (node.region.start, node.sequence));
+            self.push_level(nodes);
+        }
+
+        removed
+    }
+
+    /// Returns the label of the innermost range containing the address.
+    pub fn label_for(&self, va: Va) -> Option<&str> {
+        self.region_for(va).map(|region| region.label.as_str())
+    }
+
+    /// Returns the innermost range containing the address.
+    ///
+    /// If several ranges of the same size contain the address, the most
+    /// recently inserted one is returned.
+    pub fn region_for(&self, va: Va) -> Option<&LabeledRegion> {
+        let mut result = None::<&Node>;
+        self.visit(va, &mut |node| {
+            let better = match result {
+                Some(current) => {
+                    let size = node.region.size();
+                    let current_size = current.region.size();
+                    size < current_size
+                        || (size == current_size && node.sequence > current.sequence)
+                }
+                None => true,
+            };
+
+            if better {
+                result = Some(node);
+            }
+        });
+
+        result.map(|node| &node.region)
+    }
+
+    /// Returns all ranges containing the address, ordered by their start
+    /// address.
+    pub fn regions_at(&self, va: Va) -> Vec<&LabeledRegion> {
+        let mut result = Vec::new();
+        self.visit(va, &mut |node| result.push(node));
+        Self::sorted(result)
+    }
+
+    /// Returns an iterator over all ranges, ordered by their start address.
+    pub fn iter(&self) -> impl Iterator<Item = &LabeledRegion> {
+        Self::sorted(self.levels.iter().flatten().collect()).into_iter()
+    }
+
+    /// Returns the number of ranges.
+    pub fn len(&self) -> usize {
+        self.levels.iter().map(Vec::len).sum()
+    }
+
+    /// Checks whether the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Removes all ranges.
+    pub fn clear(&mut self) {
+        self.levels.clear();
+    }
+
+    /// Creates a new node with the next sequence number.
+    fn node(&mut self, start: Va, end: Va, label: String) -> Node {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        Node {
+            region: LabeledRegion { start, end, label },
+            sequence,
+            max_end: end,
+        }
+    }
+
+    /// Sorts nodes by the start address, and nodes with the same start by
+    /// their insertion order, and returns their ranges.
+    fn sorted(mut nodes: Vec<&Node>) -> Vec<&LabeledRegion> {
+        nodes.sort_by_key(|node| (node.region.start, node.sequence));
+        nodes.into_iter().map(|node| &node.region).collect()
+    }
+
+    /// Adds a level of nodes sorted by the start address, merging it with
+    /// the last levels until each level is less than half as large as the
+    /// previous one.
+    fn push_level(&mut self, mut nodes: Vec<Node>) {
+        while let Some(last) = self.levels.pop() {
+            if last.len() > 2 * nodes.len() {
+                self.levels.push(last);
+                break;
+            }
+
+            nodes = Self::merge(last, nodes);
+        }
+
+        let len = nodes.len();
+        Self::rebuild_subtree(&mut nodes, 0, len);
+        self.levels.push(nodes);
+    }
+
+    /// Merges two levels into one sorted by the start address.
+    ///
+    /// Nodes of `older` precede nodes of `newer` with the same start.
+    fn merge(older: Vec<Node>, newer: Vec<Node>) -> Vec<Node> {
+        let mut result = Vec::with_capacity(older.len() + newer.len());
+        let mut older = older.into_iter().peekable();
+        let mut newer = newer.into_iter().peekable();
+
+        loop {
+            let node = match (older.peek(), newer.peek()) {
+                (Some(a), Some(b)) if a.region.start <= b.region.start => older.next(),
+                (Some(_), Some(_)) => newer.next(),
+                (Some(_), None) => older.next(),
+                (None, Some(_)) => newer.next(),
+                (None, None) => break,
+            };
+
+            result.extend(node);
+        }
+
+        result
+    }
+
+    /// Recomputes the maximum end addresses of the subtree spanning
+    /// `nodes[lo..hi]` and returns the maximum end address of the subtree.
+    fn rebuild_subtree(nodes: &mut [Node], lo: usize, hi: usize) -> Option<Va> {
+        if lo >= hi {
+            return None;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let left = Self::rebuild_subtree(nodes, lo, mid);
+        let right = Self::rebuild_subtree(nodes, mid + 1, hi);
+
+        let mut max_end = nodes[mid].region.end;
+        max_end = max_end.max(left.unwrap_or(max_end));
+        max_end = max_end.max(right.unwrap_or(max_end));
+
+        nodes[mid].max_end = max_end;
+        Some(max_end)
+    }
+
+    /// Calls `f` for every node whose range contains the address.
+    fn visit<'a>(&'a self, va: Va, f: &mut impl FnMut(&'a Node)) {
+        for nodes in &self.levels {
+            Self::visit_subtree(nodes, 0, nodes.len(), va, f);
+        }
+    }
+
+    /// Calls `f` for every node of the subtree spanning `nodes[lo..hi]`
+    /// whose range contains the address.
+    fn visit_subtree<'a>(
+        nodes: &'a [Node],
+        lo: usize,
+        hi: usize,
+        va: Va,
+        f: &mut impl FnMut(&'a Node),
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let node = &nodes[mid];
+
+        // No range in this subtree extends past the address.
+        if node.max_end <= va {
+            return;
+        }
+
+        Self::visit_subtree(nodes, lo, mid, va, f);
+
+        // Ranges to the right start after this one, so if this one starts
+        // after the address, none of them can contain it.
+        if node.region.start > va {
+            return;
+        }
+
+        if node.region.contains(va) {
+            f(node);
+        }
+
+        Self::visit_subtree(nodes, mid + 1, hi, va, f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels<'a>(regions: impl IntoIterator<Item = &'a LabeledRegion>) -> Vec<&'a str> {
+        regions
+            .into_iter()
+            .map(|region| region.label.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn empty_ranges_are_ignored() {
+        let mut map = RegionMap::new();
+        map.insert(Va(0x2000), Va(0x2000), "empty");
+        map.insert(Va(0x3000), Va(0x2000), "inverted");
+
+        assert!(map.is_empty());
+        assert_eq!(map.label_for(Va(0x2000)), None);
+    }
+
+    #[test]
+    fn lookup_at_boundaries() {
+        let mut map = RegionMap::new();
+        map.insert(Va(0x1000), Va(0x2000), "a");
+
+        assert_eq!(map.label_for(Va(0x0fff)), None);
+        assert_eq!(map.label_for(Va(0x1000)), Some("a"));
+        assert_eq!(map.label_for(Va(0x1fff)), Some("a"));
+        assert_eq!(map.label_for(Va(0x2000)), None);
+    }
+
+    #[test]
+    fn adjacent_ranges() {
+        let mut map = RegionMap::new();
+        map.insert(Va(0x1000), Va(0x2000), "a");
+        map.insert(Va(0x2000), Va(0x3000), "b");
+        map.insert(Va(0x3000), Va(0x4000), "c");
+
+        assert_eq!(map.label_for(Va(0x1fff)), Some("a"));
+        assert_eq!(map.label_for(Va(0x2000)), Some("b"));
+        assert_eq!(map.label_for(Va(0x2fff)), Some("b"));
+        assert_eq!(map.label_for(Va(0x3000)), Some("c"));
+        assert_eq!(map.label_for(Va(0x4000)), None);
+        assert_eq!(labels(map.regions_at(Va(0x2000))), ["b"]);
+    }
+
+    #[test]
+    fn overlapping_ranges_prefer_innermost() {
+        let mut map = RegionMap::new();
+        map.insert(Va(0x1000), Va(0x9000), "outer");
+        map.insert(Va(0x2000), Va(0x4000), "inner");
+        map.insert(Va(0x3000), Va(0x8000), "overlap");
+
+        assert_eq!(map.label_for(Va(0x1000)), Some("outer"));
+        assert_eq!(map.label_for(Va(0x2000)), Some("inner"));
+        assert_eq!(map.label_for(Va(0x3fff)), Some("inner"));
+        assert_eq!(map.label_for(Va(0x4000)), Some("overlap"));
+        assert_eq!(map.label_for(Va(0x8000)), Some("outer"));
+        assert_eq!(
+            labels(map.regions_at(Va(0x3000))),
+            ["outer", "inner", "overlap"]
+        );
+    }
+
+    #[test]
+    fn equal_ranges_prefer_most_recent() {
+        let mut map = RegionMap::new();
+        map.insert(Va(0x1000), Va(0x2000), "first");
+        map.insert(Va(0x1000), Va(0x2000), "second");
+
+        assert_eq!(map.label_for(Va(0x1800)), Some("second"));
+        assert_eq!(labels(map.iter()), ["first", "second"]);
+    }
+
+    #[test]
+    fn incremental_inserts_match_extend() {
+        let regions = (0..100u64)
+            .map(|i| LabeledRegion {
+                start: Va(0x1000 * (i % 10) + 0x100 * i),
+                end: Va(0x1000 * (i % 10) + 0x100 * i + 0x800 * (i % 7 + 1)),
+                label: format!("r{i}"),
+            })
+            .collect::<Vec<_>>();
+
+        let mut incremental = RegionMap::new();
+        for region in &regions {
+            incremental.insert(region.start, region.end, region.label.clone());
+        }
+
+        let mut batch = RegionMap::new();
+        batch.extend(regions.iter().cloned());
+
+        assert_eq!(incremental.len(), regions.len());
+        assert!(incremental.levels.len() <= 8);
+
+        for va in (0..0x10000).step_by(0x80).map(Va) {
+            let expected = regions.iter().filter(|region| region.contains(va)).count();
+
+            assert_eq!(incremental.regions_at(va).len(), expected);
+            assert_eq!(incremental.label_for(va), batch.label_for(va));
+        }
+    }
+
+    #[test]
+    fn top_of_address_space() {
+        let mut map = RegionMap::new();
+        map.insert(Va(u64::MAX - 0xfff), Va(u64::MAX), "top");
+
+        assert_eq!(map.label_for(Va(u64::MAX - 0x1000)), None);
+        assert_eq!(map.label_for(Va(u64::MAX - 0xfff)), Some("top"));
+        assert_eq!(map.label_for(Va(u64::MAX - 1)), Some("top"));
+        assert_eq!(map.label_for(Va(u64::MAX)), None);
+    }
+
+    #[test]
+    fn overflowing_modules_are_skipped() {
+        let module = |name: &str, base_address: u64, size: u64| OsModule {
+            base_address: Va(base_address),
+            size,
+            name: name.into(),
+        };
+
+        let mut map = RegionMap::new();
+        map.insert_modules(&[
+            module("wraps", u64::MAX - 0xfff, 0x2000),
+            module("fits", u64::MAX - 0x1fff, 0x1000),
+            module("ntdll.dll", 0x7ff0_0000, 0x1000),
+        ]);
+
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.label_for(Va(u64::MAX - 0x1800)), Some("module:fits"));
+        assert_eq!(map.label_for(Va(u64::MAX - 0x800)), None);
+        assert_eq!(map.label_for(Va(0x7ff0_0800)), Some("module:ntdll.dll"));
+    }
+
+    #[test]
+    fn remove_label() {
+        let mut map = RegionMap::new();
+        map.insert(Va(0x1000), Va(0x9000), "outer");
+        map.insert(Va(0x2000), Va(0x3000), "stack");
+        map.insert(Va(0x4000), Va(0x5000), "stack");
+
+        assert_eq!(map.remove_label("stack"), 2);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.label_for(Va(0x2000)), Some("outer"));
+    }
+}
@@ -0,0 +1,432 @@
+//! Scratch memory in the address space of a Windows process.
+//!
+//! A [`ScratchRegion`] is a range of user-mode virtual addresses in a target
+//! process that is backed by freshly allocated guest physical pages. It is
+//! meant to be used as a staging area for injected code and data.
+//!
+//! The free range is found by searching the VAD tree of the process for
+//! gaps, and the mapping is established only in a single view managed by
+//! a [`ViewManager`]: the page tables of the process are shadowed in that
+//! view and the new entries are written into the shadow pages. The guest
+//! does not see the allocation in any other view, and the VAD tree of the
+//! process is left untouched.
+//!
+//! # Notes
+//!
+//! Once a page table is shadowed, changes the guest makes to the original
+//! page table are not reflected in the view. The view should therefore be
+//! used only for short periods of time (e.g., while the injected code runs)
+//! and destroyed or restored afterwards.
+
+use vmi_arch_amd64::{Amd64, PageTableEntry, PageTableLevel, Registers};
+use vmi_core::{
+    os::{ProcessObject, VmiOs as _},
+    Architecture as _, Gfn, MemoryAccess, Va, View, VmiCore, VmiDriver, VmiError,
+};
+use vmi_os_windows::WindowsOs;
+
+use crate::view::ViewManager;
+
+const PRESENT: u64 = 1 << 0;
+const WRITE: u64 = 1 << 1;
+const USER: u64 = 1 << 2;
+const EXECUTE_DISABLE: u64 = 1 << 63;
+
+/// Flags of entries referencing newly created page tables.
+///
+/// The effective permissions are determined by the leaf entry.
+const TABLE_FLAGS: u64 = PRESENT | WRITE | USER;
+
+/// A range of virtual addresses in a process backed by scratch pages.
+#[derive(Debug)]
+pub struct ScratchRegion {
+    /// Start of the region.
+    address: Va,
+
+    /// Size of the region, rounded up to whole pages.
+    size: u64,
+
+    /// GFNs backing the region, in the order of virtual addresses.
+    pages: Vec<Gfn>,
+
+    /// GFNs of page tables created for the region.
+    tables: Vec<Gfn>,
+
+    /// Entries written into the page tables of the process, with their
+    /// previous values.
+    ///
+    /// * Table GFN (not remapped)
+    /// * Index of the entry
+    /// * Previous value of the entry
+    written: Vec<(Gfn, u64, PageTableEntry)>,
+}
+
+impl ScratchRegion {
+    /// Allocates a scratch region of at least `size` bytes in the address
+    /// space of the process.
+    ///
+    /// The region is placed in a gap between the VADs of the process and
+    /// mapped with the given `protection` in the `view` only. The backing
+    /// pages are zeroed.
+    ///
+    /// Returns [`VmiError::OutOfBounds`] if the process has no free range
+    /// large enough.
+    pub fn allocate<Driver>(
+        views: &mut ViewManager<'_, Driver>,
+        view: View,
+        os: &WindowsOs<Driver>,
+        registers: &Registers,
+        process: ProcessObject,
+        size: u64,
+        protection: MemoryAccess,
+    ) -> Result<Self, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let vmi = views.vmi();
+
+        let address = match os.process_free_region(vmi, registers, process, size)? {
+            Some(address) => address,
+            None => return Err(VmiError::OutOfBounds),
+        };
+
+        let mut roots = vec![Amd64::gfn_from_pa(
+            os.process_translation_root(vmi, registers, process)?,
+        )];
+
+        // With KVA shadowing enabled, the process has a separate user-mode
+        // PML4 whose user-mode entries mirror the kernel-mode PML4.
+        let user_root =
+            Amd64::gfn_from_pa(os.process_user_translation_root(vmi, registers, process)?);
+        if !roots.contains(&user_root) {
+            roots.push(user_root);
+        }
+
+        let mut flags = PRESENT | USER;
+        if protection.contains(MemoryAccess::W) {
+            flags |= WRITE;
+        }
+        if !protection.contains(MemoryAccess::X) {
+            flags |= EXECUTE_DISABLE;
+        }
+
+        let page_count = size.div_ceil(Amd64::PAGE_SIZE);
+        let result = Self::map(views, view, &roots, address, page_count, flags)?;
+
+        // No TLB flush is needed: the mapping only turns non-present
+        // entries into present ones, and the processor never caches
+        // non-present entries.
+
+        tracing::debug!(%view, %address, size = result.size, "allocated scratch region");
+        Ok(result)
+    }
+
+    /// Returns the start of the region.
+    pub fn address(&self) -> Va {
+        self.address
+    }
+
+    /// Returns the size of the region in bytes.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns the GFNs backing the region, in the order of virtual
+    /// addresses.
+    pub fn pages(&self) -> &[Gfn] {
+        &self.pages
+    }
+
+    /// Frees the pages allocated for the region.
+    ///
+    /// The view the region was mapped in should be restored or destroyed
+    /// before the region is freed, otherwise the shadowed page tables
+    /// would keep referencing the freed pages.
+    pub fn free<Driver>(self, vmi: &VmiCore<Driver>)
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        for gfn in self.pages.into_iter().chain(self.tables) {
+            if let Err(err) = vmi.free_gfn(gfn) {
+                tracing::error!(%gfn, ?err, "Failed to free scratch page");
+            }
+        }
+    }
+
+    /// Maps `page_count` pages starting at `address` in the view.
+    ///
+    /// If any of the pages cannot be mapped, the entries written so far
+    /// are rolled back before the allocated pages are freed, so that the
+    /// view never references the freed pages.
+    fn map<Driver>(
+        views: &mut ViewManager<'_, Driver>,
+        view: View,
+        roots: &[Gfn],
+        address: Va,
+        page_count: u64,
+        flags: u64,
+    ) -> Result<Self, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let mut result = Self {
+            address,
+            size: page_count * Amd64::PAGE_SIZE,
+            pages: Vec::new(),
+            tables: Vec::new(),
+            written: Vec::new(),
+        };
+
+        for index in 0..page_count {
+            let va = address + index * Amd64::PAGE_SIZE;
+
+            if let Err(err) = result.map_page(views, view, roots, va, flags) {
+                tracing::debug!(%va, ?err, "failed to map scratch page");
+                result.unmap(views, view);
+                result.free(views.vmi());
+                return Err(err);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Restores the entries written into the page tables of the process.
+    ///
+    /// Entries that cannot be restored are logged.
+    fn unmap<Driver>(&self, views: &mut ViewManager<'_, Driver>, view: View)
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        for &(table, index, previous) in self.written.iter().rev() {
+            let result = views
+                .shadow_page(view, table)
+                .and_then(|shadow| Self::write_table(views.vmi(), shadow, index, previous));
+
+            if let Err(err) = result {
+                tracing::warn!(%view, %table, index, ?err, "failed to restore page table entry");
+            }
+        }
+    }
+
+    /// Maps a single page at `va` in the view.
+    ///
+    /// Missing page tables are created along the way. Entries in the
+    /// existing page tables are written into their shadow pages.
+    fn map_page<Driver>(
+        &mut self,
+        views: &mut ViewManager<'_, Driver>,
+        view: View,
+        roots: &[Gfn],
+        va: Va,
+        flags: u64,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let mut tables = roots.to_vec();
+        let mut level = PageTableLevel::Pml4;
+
+        loop {
+            let index = Amd64::va_index_for(va, level);
+            let entry = self.read_entry(views, view, tables[0], index)?;
+
+            let next = match level.next() {
+                Some(next) => next,
+                None => {
+                    if entry.present() {
+                        return Err(VmiError::Other("page is already mapped"));
+                    }
+
+                    let gfn = self.allocate_page(views.vmi())?;
+                    self.pages.push(gfn);
+
+                    let entry = PageTableEntry(Amd64::pa_from_gfn(gfn).0 | flags);
+                    for &table in &tables {
+                        self.write_entry(views, view, table, index, entry)?;
+                    }
+
+                    return Ok(());
+                }
+            };
+
+            if entry.present() {
+                if entry.large() {
+                    return Err(VmiError::Other("address is mapped by a large page"));
+                }
+
+                tables = vec![entry.pfn()];
+                level = next;
+                continue;
+            }
+
+            let gfn = self.allocate_page(views.vmi())?;
+            self.tables.push(gfn);
+
+            let entry = PageTableEntry(Amd64::pa_from_gfn(gfn).0 | TABLE_FLAGS);
+            for &table in &tables {
+                self.write_entry(views, view, table, index, entry)?;
+            }
+
+            tables = vec![gfn];
+            level = next;
+        }
+    }
+
+    /// Reads a page table entry as seen in the view.
+    fn read_entry<Driver>(
+        &self,
+        views: &ViewManager<'_, Driver>,
+        view: View,
+        table: Gfn,
+        index: u64,
+    ) -> Result<PageTableEntry, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let table = views.remapping(view, table).unwrap_or(table);
+        let pa = Amd64::pa_from_gfn(table) + index * size_of::<PageTableEntry>() as u64;
+        Ok(PageTableEntry(views.vmi().read_u64(pa)?))
+    }
+
+    /// Writes a page table entry in the view.
+    ///
+    /// Page tables created for the region are written directly, other
+    /// page tables are shadowed first and the previous value of the entry
+    /// is recorded.
+    fn write_entry<Driver>(
+        &mut self,
+        views: &mut ViewManager<'_, Driver>,
+        view: View,
+        table: Gfn,
+        index: u64,
+        entry: PageTableEntry,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        if self.tables.contains(&table) {
+            return Self::write_table(views.vmi(), table, index, entry);
+        }
+
+        let previous = self.read_entry(views, view, table, index)?;
+        let shadow = views.shadow_page(view, table)?;
+        Self::write_table(views.vmi(), shadow, index, entry)?;
+        self.written.push((table, index, previous));
+        Ok(())
+    }
+
+    /// Writes an entry into the page table at `table`.
+    fn write_table<Driver>(
+        vmi: &VmiCore<Driver>,
+        table: Gfn,
+        index: u64,
+        entry: PageTableEntry,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let pa = Amd64::pa_from_gfn(table) + index * size_of::<PageTableEntry>() as u64;
+        vmi.write_u64(pa, entry.0)
+    }
+
+    /// Allocates a new zeroed page.
+    fn allocate_page<Driver>(&self, vmi: &VmiCore<Driver>) -> Result<Gfn, VmiError>
+    where
+        Driver: VmiDriver<Architecture = Amd64>,
+    {
+        let gfn = vmi.allocate_next_available_gfn()?;
+
+        let content = [0u8; Amd64::PAGE_SIZE as usize];
+        if let Err(err) = vmi.write(Amd64::pa_from_gfn(gfn), &content) {
+            let _ = vmi.free_gfn(gfn);
+            return Err(err);
+        }
+
+        Ok(gfn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use vmi_core::Pa;
+
+    use super::*;
+    use crate::testing::MockDriver;
+
+    const LARGE: u64 = 1 << 7;
+
+    fn write_entry(vmi: &VmiCore<MockDriver>, table: Gfn, index: u64, entry: u64) {
+        vmi.write_u64(Pa((table.0 << 12) + index * 8), entry)
+            .unwrap();
+    }
+
+    fn read_entry(views: &ViewManager<'_, MockDriver>, view: View, table: Gfn, index: u64) -> u64 {
+        let table = views.remapping(view, table).unwrap_or(table);
+        views
+            .vmi()
+            .read_u64(Pa((table.0 << 12) + index * 8))
+            .unwrap()
+    }
+
+    #[test]
+    fn map_rolls_back_existing_page_table() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let root = Gfn(0x10);
+
+        // PML4[0] -> PDPT[0] -> PD[0] -> PT, with PT[0x101] already mapped.
+        write_entry(&vmi, root, 0, 0x20 << 12 | TABLE_FLAGS);
+        write_entry(&vmi, Gfn(0x20), 0, 0x21 << 12 | TABLE_FLAGS);
+        write_entry(&vmi, Gfn(0x21), 0, 0x22 << 12 | TABLE_FLAGS);
+        write_entry(&vmi, Gfn(0x22), 0x101, 0x30 << 12 | PRESENT | USER);
+
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+
+        let flags = PRESENT | WRITE | USER;
+        let result = ScratchRegion::map(&mut views, view, &[root], Va(0x10_0000), 2, flags);
+        assert!(result.is_err());
+
+        // The first page was mapped into the shadowed PT and freed.
+        let page = Gfn(0x100);
+        assert!(views.remapping(view, Gfn(0x22)).is_some());
+        assert!(!vmi.driver().is_allocated(page));
+        assert_eq!(read_entry(&views, view, Gfn(0x22), 0x100), 0);
+        assert_eq!(
+            read_entry(&views, view, Gfn(0x22), 0x101),
+            0x30 << 12 | PRESENT | USER
+        );
+    }
+
+    #[test]
+    fn map_rolls_back_both_roots() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let roots = [Gfn(0x10), Gfn(0x11)];
+
+        // PML4[1] -> PDPT, with PDPT[0] mapped by a 1 GiB page.
+        write_entry(&vmi, roots[0], 1, 0x20 << 12 | TABLE_FLAGS);
+        write_entry(&vmi, Gfn(0x20), 0, PRESENT | USER | LARGE);
+
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+
+        // The second page is the first page covered by PML4[1].
+        let flags = PRESENT | WRITE | USER;
+        let result = ScratchRegion::map(&mut views, view, &roots, Va(0x7f_ffff_f000), 2, flags);
+        assert!(result.is_err());
+
+        // Besides the shadowed roots, the PDPT, PD, PT and the page were
+        // allocated, and all of them were freed.
+        let shadows = roots.map(|root| views.remapping(view, root).unwrap());
+        let freed = (0x100..0x106)
+            .map(Gfn)
+            .filter(|gfn| !shadows.contains(gfn))
+            .collect::<Vec<_>>();
+        assert_eq!(freed.len(), 4);
+        assert!(freed.iter().all(|&gfn| !vmi.driver().is_allocated(gfn)));
+
+        for root in roots {
+            assert_eq!(read_entry(&views, view, root, 0), 0);
+        }
+    }
+}
@@ -0,0 +1,162 @@
+//! Snapshots of guest physical memory.
+//!
+//! A [`MemorySnapshot`] holds a copy of selected guest physical pages taken
+//! at a point in time. Two snapshots of the same pages can be compared to
+//! find out which pages, and optionally which bytes, changed between them.
+//!
+//! A typical use is before/after analysis of a single action: take a
+//! snapshot, let the guest execute (e.g., a system call), take another
+//! snapshot and compare them with [`MemorySnapshot::diff`] or
+//! [`MemorySnapshot::diff_bytes`].
+
+use std::collections::BTreeMap;
+
+use vmi_core::{Gfn, VmiCore, VmiDriver, VmiError};
+
+/// A change of a single byte within a page.
+///
+/// Contains the offset within the page, the old value and the new value.
+pub type ByteChange = (usize, u8, u8);
+
+/// A copy of guest physical pages taken at a point in time.
+#[derive(Debug, Default, Clone)]
+pub struct MemorySnapshot {
+    pages: BTreeMap<Gfn, Box<[u8]>>,
+}
+
+impl MemorySnapshot {
+    /// Creates a new empty snapshot.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes a snapshot of the given pages.
+    ///
+    /// Pages that cannot be read, e.g., because they are not backed by guest
+    /// memory, are skipped.
+    pub fn capture<Driver>(
+        vmi: &VmiCore<Driver>,
+        gfns: impl IntoIterator<Item = Gfn>,
+    ) -> Result<Self, VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let mut result = Self::new();
+
+        for gfn in gfns {
+            //
+            // Pages that fail to be read are treated as invalid, same as
+            // the default `VmiDriver::gfn_valid` does. Checking the page
+            // upfront would read every valid page twice.
+            //
+
+            let page = match vmi.read_page(gfn) {
+                Ok(page) => page,
+                Err(_) => continue,
+            };
+
+            result.insert(gfn, &page);
+        }
+
+        Ok(result)
+    }
+
+    /// Takes a snapshot of the whole guest physical memory.
+    ///
+    /// Pages that cannot be read are skipped.
+    ///
+    /// # Notes
+    ///
+    /// The snapshot holds a copy of every page, so its size is comparable
+    /// to the amount of memory assigned to the virtual machine.
+    pub fn capture_all<Driver>(vmi: &VmiCore<Driver>) -> Result<Self, VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let max_gfn = vmi.info()?.max_gfn;
+        Self::capture(vmi, (0..=max_gfn.0).map(Gfn))
+    }
+
+    /// Inserts a copy of the page content into the snapshot.
+    ///
+    /// Replaces the previous content of the page, if any.
+    pub fn insert(&mut self, gfn: Gfn, content: &[u8]) {
+        self.pages.insert(gfn, content.into());
+    }
+
+    /// Returns the content of the page, if it is part of the snapshot.
+    pub fn get(&self, gfn: Gfn) -> Option<&[u8]> {
+        self.pages.get(&gfn).map(AsRef::as_ref)
+    }
+
+    /// Returns an iterator over the pages in the snapshot, ordered by GFN.
+    pub fn pages(&self) -> impl Iterator<Item = (Gfn, &[u8])> {
+        self.pages
+            .iter()
+            .map(|(gfn, content)| (*gfn, content.as_ref()))
+    }
+
+    /// Returns the number of pages in the snapshot.
+    pub fn len(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Checks whether the snapshot contains no pages.
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+
+    /// Returns the pages whose content differs between `self` (the older
+    /// snapshot) and `other` (the newer snapshot), ordered by GFN.
+    ///
+    /// Pages that are present in only one of the snapshots are ignored.
+    pub fn diff(&self, other: &MemorySnapshot) -> Vec<Gfn> {
+        self.changed_pages(other).map(|(gfn, _, _)| gfn).collect()
+    }
+
+    /// Returns the bytes that differ between `self` (the older snapshot)
+    /// and `other` (the newer snapshot), grouped by page and ordered by GFN.
+    ///
+    /// Each change holds the offset within the page, the old value and
+    /// the new value. Pages that are present in only one of the snapshots
+    /// are ignored.
+    ///
+    /// # Notes
+    ///
+    /// The result can be considerably larger than the result of [`diff`]
+    /// when many pages change.
+    ///
+    /// [`diff`]: Self::diff
+    pub fn diff_bytes(&self, other: &MemorySnapshot) -> Vec<(Gfn, Vec<ByteChange>)> {
+        self.changed_pages(other)
+            .map(|(gfn, old, new)| {
+                let changes = old
+                    .iter()
+                    .zip(new.iter())
+                    .enumerate()
+                    .filter(|(_, (old, new))| old != new)
+                    .map(|(offset, (&old, &new))| (offset, old, new))
+                    .collect();
+
+                (gfn, changes)
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the pages present in both snapshots whose
+    /// content differs.
+    fn changed_pages<'a>(
+        &'a self,
+        other: &'a MemorySnapshot,
+    ) -> impl Iterator<Item = (Gfn, &'a [u8], &'a [u8])> {
+        self.pages.iter().filter_map(|(gfn, old)| {
+            let new = other.pages.get(gfn)?;
+
+            if old == new {
+                return None;
+            }
+
+            Some((*gfn, old.as_ref(), new.as_ref()))
+        })
+    }
+}
@@ -0,0 +1,460 @@
+//! Field-level monitoring of writes to guest structures.
+//!
+//! The [`StructWatcher`] write-protects the pages of watched structures in
+//! a view. When the guest writes to such a page, the event handler passes
+//! the faulting physical address to [`StructWatcher::handle_write`], which
+//! snapshots the affected structures. After the write has been carried out
+//! (typically by singlestepping the instruction with the restriction lifted),
+//! [`StructWatcher::complete`] compares the structures against the snapshot
+//! and reports which named fields changed, together with their old and new
+//! values.
+//!
+//! Changes are attributed by comparing the contents rather than by the
+//! faulting address, so writes spanning several fields (e.g., `rep movs`)
+//! are reported for every field they modify.
+//!
+//! # Notes
+//!
+//! The watcher takes over the memory access permissions of the watched
+//! pages in its view. Writes to unrelated data sharing a page with
+//! a watched structure also generate events; [`StructWatcher::handle_write`]
+//! returns `false` for them.
+
+use std::collections::HashMap;
+
+use isr_macros::Field;
+use vmi_core::{
+    AddressContext, Architecture as _, Gfn, MemoryAccess, Pa, VcpuId, View, VmiCore, VmiDriver,
+    VmiError,
+};
+
+/// A named field of a watched structure.
+#[derive(Debug, Clone)]
+pub struct StructField {
+    /// Name of the field.
+    pub name: String,
+
+    /// Offset of the field from the beginning of the structure, in bytes.
+    pub offset: u64,
+
+    /// Size of the field, in bytes.
+    pub size: u64,
+}
+
+/// A modification of a field of a watched structure.
+#[derive(Debug, Clone)]
+pub struct StructFieldChange {
+    /// Address of the structure.
+    pub ctx: AddressContext,
+
+    /// Name of the structure.
+    pub structure: String,
+
+    /// Name of the field.
+    pub field: String,
+
+    /// Offset of the field from the beginning of the structure, in bytes.
+    pub offset: u64,
+
+    /// Content of the field before the write.
+    pub old: Vec<u8>,
+
+    /// Content of the field after the write.
+    pub new: Vec<u8>,
+}
+
+impl StructFieldChange {
+    /// Returns the old content as an integer, if the field is at most
+    /// 8 bytes long.
+    pub fn old_value(&self) -> Option<u64> {
+        as_integer(&self.old)
+    }
+
+    /// Returns the new content as an integer, if the field is at most
+    /// 8 bytes long.
+    pub fn new_value(&self) -> Option<u64> {
+        as_integer(&self.new)
+    }
+}
+
+/// A watched structure.
+struct WatchedStruct {
+    /// Name of the structure.
+    name: String,
+
+    /// Size of the structure, in bytes.
+    size: u64,
+
+    /// Fields of the structure, sorted by offset.
+    fields: Vec<StructField>,
+
+    /// GFNs backing the structure.
+    gfns: Vec<Gfn>,
+}
+
+/// State of a write-protected page.
+struct WatchedPage {
+    /// Access permissions the page had before it was write-protected.
+    original_access: MemoryAccess,
+
+    /// Number of watched structures on the page.
+    refcount: usize,
+}
+
+/// Snapshot of a structure taken before a write.
+struct PendingWrite {
+    /// Address of the structure.
+    ctx: AddressContext,
+
+    /// Content of the structure before the write.
+    content: Vec<u8>,
+}
+
+/// Watcher of writes to the fields of guest structures.
+pub struct StructWatcher {
+    /// View the pages are write-protected in.
+    view: View,
+
+    /// Watched structures.
+    structs: HashMap<AddressContext, WatchedStruct>,
+
+    /// Write-protected pages.
+    pages: HashMap<Gfn, WatchedPage>,
+
+    /// Snapshots taken by `handle_write`, waiting for `complete`.
+    pending: HashMap<VcpuId, Vec<PendingWrite>>,
+}
+
+impl StructWatcher {
+    /// Creates a new watcher that write-protects pages in the given view.
+    pub fn new(view: View) -> Self {
+        Self {
+            view,
+            structs: HashMap::new(),
+            pages: HashMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Returns the view the pages are write-protected in.
+    pub fn view(&self) -> View {
+        self.view
+    }
+
+    /// Starts watching a structure.
+    ///
+    /// The `fields` describe the layout of the structure, typically taken
+    /// from an ISR offsets struct, e.g.,
+    /// `[("Flags", EPROCESS.Flags), ("Token", EPROCESS.Token)]`.
+    /// The watched range spans from the beginning of the structure to the
+    /// end of the last field.
+    ///
+    /// The structure must be mapped in memory. If it is already watched,
+    /// its fields are replaced.
+    pub fn watch<Driver>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        ctx: impl Into<AddressContext>,
+        name: impl Into<String>,
+        fields: impl IntoIterator<Item = (impl Into<String>, Field)>,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let ctx = ctx.into();
+
+        let mut fields = fields
+            .into_iter()
+            .map(|(name, field)| StructField {
+                name: name.into(),
+                offset: field.offset,
+                size: field.size,
+            })
+            .collect::<Vec<_>>();
+        fields.sort_by_key(|field| field.offset);
+
+        let size = fields
+            .iter()
+            .map(|field| field.offset + field.size)
+            .max()
+            .unwrap_or(0);
+
+        if size == 0 {
+            return Err(VmiError::Other("structure has no fields"));
+        }
+
+        if self.structs.contains_key(&ctx) {
+            self.unwatch(vmi, ctx)?;
+        }
+
+        let gfns = self.translate(vmi, ctx, size)?;
+
+        for (index, &gfn) in gfns.iter().enumerate() {
+            if let Err(err) = self.acquire_page(vmi, gfn) {
+                //
+                // Roll back the pages protected so far, so that a failed
+                // call leaves no trace.
+                //
+
+                if let Err(err) = self.release_pages(vmi, &gfns[..index]) {
+                    tracing::warn!(%err, %ctx, "failed to roll back page protection");
+                }
+
+                return Err(err);
+            }
+        }
+
+        let name = name.into();
+        tracing::debug!(%ctx, name, size, "watching structure");
+
+        self.structs.insert(
+            ctx,
+            WatchedStruct {
+                name,
+                size,
+                fields,
+                gfns,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Stops watching a structure.
+    ///
+    /// The original access permissions of pages that no longer hold any
+    /// watched structure are restored.
+    pub fn unwatch<Driver>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        ctx: impl Into<AddressContext>,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let ctx = ctx.into();
+
+        let watched = match self.structs.remove(&ctx) {
+            Some(watched) => watched,
+            None => return Ok(()),
+        };
+
+        self.release_pages(vmi, &watched.gfns)?;
+
+        tracing::debug!(%ctx, name = watched.name, "stopped watching structure");
+        Ok(())
+    }
+
+    /// Checks whether the GFN holds a watched structure.
+    pub fn contains_gfn(&self, gfn: Gfn) -> bool {
+        self.pages.contains_key(&gfn)
+    }
+
+    /// Handles a write to a write-protected page.
+    ///
+    /// Call this from the memory access event handler, before the write is
+    /// carried out. The structures that may be affected by the write are
+    /// snapshotted, and the snapshot is kept until [`complete`] is called
+    /// for the same VCPU.
+    ///
+    /// Returns `true` if the page holds a watched structure.
+    ///
+    /// [`complete`]: Self::complete
+    pub fn handle_write<Driver>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        vcpu: VcpuId,
+        pa: Pa,
+    ) -> Result<bool, VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let gfn = Driver::Architecture::gfn_from_pa(pa);
+        if !self.pages.contains_key(&gfn) {
+            return Ok(false);
+        }
+
+        let mut pending = Vec::new();
+        for (&ctx, watched) in &self.structs {
+            if !watched.gfns.contains(&gfn) {
+                continue;
+            }
+
+            let mut content = vec![0u8; watched.size as usize];
+            vmi.read(ctx, &mut content)?;
+            pending.push(PendingWrite { ctx, content });
+        }
+
+        self.pending.entry(vcpu).or_default().extend(pending);
+        Ok(true)
+    }
+
+    /// Completes a write previously passed to [`handle_write`].
+    ///
+    /// Call this after the write has been carried out, e.g., from the
+    /// singlestep event handler. Returns the fields whose content changed.
+    /// Each change is also logged.
+    ///
+    /// [`handle_write`]: Self::handle_write
+    pub fn complete<Driver>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        vcpu: VcpuId,
+    ) -> Result<Vec<StructFieldChange>, VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let pending = match self.pending.remove(&vcpu) {
+            Some(pending) => pending,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut result = Vec::new();
+
+        for PendingWrite { ctx, content: old } in pending {
+            // The structure might have been unwatched in the meantime.
+            let watched = match self.structs.get(&ctx) {
+                Some(watched) => watched,
+                None => continue,
+            };
+
+            let mut new = vec![0u8; old.len()];
+            vmi.read(ctx, &mut new)?;
+
+            if old == new {
+                continue;
+            }
+
+            for field in &watched.fields {
+                let range = field.offset as usize..(field.offset + field.size) as usize;
+                if old[range.clone()] == new[range.clone()] {
+                    continue;
+                }
+
+                let change = StructFieldChange {
+                    ctx,
+                    structure: watched.name.clone(),
+                    field: field.name.clone(),
+                    offset: field.offset,
+                    old: old[range.clone()].to_vec(),
+                    new: new[range].to_vec(),
+                };
+
+                tracing::debug!(
+                    %ctx,
+                    structure = change.structure,
+                    field = change.field,
+                    old = ?change.old_value(),
+                    new = ?change.new_value(),
+                    "field modified"
+                );
+
+                result.push(change);
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Stops watching all structures and restores the original access
+    /// permissions of all write-protected pages.
+    pub fn clear<Driver>(&mut self, vmi: &VmiCore<Driver>) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        for (gfn, page) in self.pages.drain() {
+            vmi.set_memory_access(gfn, self.view, page.original_access)?;
+        }
+
+        self.structs.clear();
+        self.pending.clear();
+        Ok(())
+    }
+
+    /// Write-protects a page, or takes another reference to it if it is
+    /// already write-protected.
+    fn acquire_page<Driver>(&mut self, vmi: &VmiCore<Driver>, gfn: Gfn) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        if let Some(page) = self.pages.get_mut(&gfn) {
+            page.refcount += 1;
+            return Ok(());
+        }
+
+        let original_access = vmi.memory_access(gfn, self.view)?;
+        vmi.set_memory_access(gfn, self.view, original_access & !MemoryAccess::W)?;
+
+        self.pages.insert(
+            gfn,
+            WatchedPage {
+                original_access,
+                refcount: 1,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Drops a reference to each page, restoring the original access
+    /// permissions of pages that are no longer referenced.
+    fn release_pages<Driver>(&mut self, vmi: &VmiCore<Driver>, gfns: &[Gfn]) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        for gfn in gfns {
+            let page = match self.pages.get_mut(gfn) {
+                Some(page) => page,
+                None => continue,
+            };
+
+            page.refcount -= 1;
+            if page.refcount > 0 {
+                continue;
+            }
+
+            vmi.set_memory_access(*gfn, self.view, page.original_access)?;
+            self.pages.remove(gfn);
+        }
+
+        Ok(())
+    }
+
+    /// Translates the pages spanned by a structure.
+    fn translate<Driver>(
+        &self,
+        vmi: &VmiCore<Driver>,
+        ctx: AddressContext,
+        size: u64,
+    ) -> Result<Vec<Gfn>, VmiError>
+    where
+        Driver: VmiDriver,
+    {
+        let page_size = Driver::Architecture::PAGE_SIZE;
+        let first = ctx.va.0 & !(page_size - 1);
+        let last = (ctx.va.0 + size - 1) & !(page_size - 1);
+
+        let mut result = Vec::new();
+        let mut page = first;
+        while page <= last {
+            let page_ctx = AddressContext::new(page, ctx.root);
+            result.push(Driver::Architecture::gfn_from_pa(
+                vmi.translate_address(page_ctx)?,
+            ));
+            page += page_size;
+        }
+
+        Ok(result)
+    }
+}
+
+/// Interprets up to 8 bytes as a little-endian integer.
+fn as_integer(bytes: &[u8]) -> Option<u64> {
+    if bytes.len() > 8 {
+        return None;
+    }
+
+    let mut buffer = [0u8; 8];
+    buffer[..bytes.len()].copy_from_slice(bytes);
+    Some(u64::from_le_bytes(buffer))
+}
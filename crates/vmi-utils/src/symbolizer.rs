@@ -0,0 +1,334 @@
+//! Resolution of virtual addresses to `module!symbol+offset`.
+//!
+//! Stack walks, interrupt handler descriptions and annotated hexdumps all
+//! need to describe code addresses. The [`Symbolizer`] trait provides
+//! a common interface for that, so that the resolution logic is not
+//! reimplemented by every consumer.
+//!
+//! The [`ModuleSymbolizer`] is the default implementation. It maps
+//! addresses to modules by their ranges, and within a module to the
+//! nearest preceding symbol. Symbols are taken from the export directory
+//! of the module image, or from an ISR profile of the module (which covers
+//! the private symbols from the PDB as well). Results are kept in an LRU
+//! cache, because the same addresses tend to be resolved repeatedly (e.g.,
+//! return addresses during stack walks).
+//!
+//! To annotate a hexdump with symbols, pass
+//! `|va| symbolizer.symbolize(va).map(|address| address.to_string())`
+//! to [`hexdump_annotated`].
+//!
+//! [`hexdump_annotated`]: crate::hexdump_annotated
+
+use std::{cell::RefCell, num::NonZeroUsize};
+
+use isr_core::Profile;
+use lru::LruCache;
+use vmi_core::{
+    os::{OsModule, VmiOs},
+    Architecture, Va, VmiCore, VmiDriver, VmiError,
+};
+
+/// A virtual address resolved to a module and a symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolizedAddress {
+    /// The name of the module containing the address.
+    pub module: String,
+
+    /// The name of the nearest symbol at or before the address.
+    pub symbol: Option<String>,
+
+    /// The offset of the address from the symbol, or from the module base
+    /// if no symbol was found.
+    pub offset: u64,
+}
+
+impl std::fmt::Display for SymbolizedAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (&self.symbol, self.offset) {
+            (Some(symbol), 0) => write!(f, "{}!{symbol}", self.module),
+            (Some(symbol), offset) => write!(f, "{}!{symbol}+{offset:#x}", self.module),
+            (None, offset) => write!(f, "{}+{offset:#x}", self.module),
+        }
+    }
+}
+
+/// Resolves virtual addresses to modules and symbols.
+pub trait Symbolizer {
+    /// Resolves a virtual address.
+    ///
+    /// Returns `None` if the address does not belong to any known module.
+    fn symbolize(&self, va: Va) -> Option<SymbolizedAddress>;
+}
+
+/// A module known to the [`ModuleSymbolizer`].
+#[derive(Debug)]
+struct SymbolizerModule {
+    /// The base address of the module.
+    base_address: Va,
+
+    /// The size of the module.
+    size: u64,
+
+    /// The name of the module.
+    name: String,
+
+    /// Symbols of the module as `(rva, name)` pairs, sorted by the RVA.
+    ///
+    /// `None` if no symbols have been loaded for the module.
+    symbols: Option<Vec<(u64, String)>>,
+}
+
+/// Default number of resolved addresses kept in the cache.
+const DEFAULT_CACHE_CAPACITY: usize = 8192;
+
+/// Symbolizer backed by module ranges and their symbols.
+#[derive(Debug)]
+pub struct ModuleSymbolizer {
+    /// Known modules, sorted by their base address.
+    modules: Vec<SymbolizerModule>,
+
+    /// Cache of resolved addresses.
+    cache: RefCell<LruCache<Va, Option<SymbolizedAddress>>>,
+}
+
+impl Default for ModuleSymbolizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModuleSymbolizer {
+    /// Creates a new symbolizer without any modules.
+    ///
+    /// The cache holds up to 8192 resolved addresses.
+    pub fn new() -> Self {
+        Self::with_cache_capacity(DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Creates a new symbolizer without any modules, with a cache holding up
+    /// to `capacity` resolved addresses.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn with_cache_capacity(capacity: usize) -> Self {
+        Self {
+            modules: Vec::new(),
+            cache: RefCell::new(LruCache::new(NonZeroUsize::new(capacity).unwrap())),
+        }
+    }
+
+    /// Creates a new symbolizer for the given modules.
+    pub fn from_modules(modules: &[OsModule]) -> Self {
+        let mut result = Self::new();
+        for module in modules {
+            result.add_module(module.base_address, module.size, &module.name);
+        }
+        result
+    }
+
+    /// Adds a module.
+    ///
+    /// If a module with the same base address is already known, it is
+    /// replaced, together with its symbols.
+    pub fn add_module(&mut self, base_address: Va, size: u64, name: impl Into<String>) {
+        let module = SymbolizerModule {
+            base_address,
+            size,
+            name: name.into(),
+            symbols: None,
+        };
+
+        match self
+            .modules
+            .binary_search_by_key(&base_address, |module| module.base_address)
+        {
+            Ok(index) => self.modules[index] = module,
+            Err(index) => self.modules.insert(index, module),
+        }
+
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Removes the module with the given base address.
+    pub fn remove_module(&mut self, base_address: Va) {
+        if let Ok(index) = self
+            .modules
+            .binary_search_by_key(&base_address, |module| module.base_address)
+        {
+            self.modules.remove(index);
+            self.cache.borrow_mut().clear();
+        }
+    }
+
+    /// Adds symbols to the module with the given base address.
+    ///
+    /// The symbols are given as `(name, address)` pairs, where the address
+    /// is either absolute or relative to the module base.
+    ///
+    /// Returns [`VmiError::Other`] if no such module is known.
+    pub fn add_symbols(
+        &mut self,
+        base_address: Va,
+        symbols: impl IntoIterator<Item = (impl Into<String>, u64)>,
+    ) -> Result<(), VmiError> {
+        let module = match self
+            .modules
+            .binary_search_by_key(&base_address, |module| module.base_address)
+        {
+            Ok(index) => &mut self.modules[index],
+            Err(_) => return Err(VmiError::Other("module not found")),
+        };
+
+        let module_symbols = module.symbols.get_or_insert_with(Vec::new);
+        for (name, address) in symbols {
+            let rva = match address.checked_sub(module.base_address.0) {
+                Some(rva) if rva < module.size => rva,
+                _ => address,
+            };
+
+            if rva >= module.size {
+                continue;
+            }
+
+            module_symbols.push((rva, name.into()));
+        }
+
+        module_symbols.sort_by_key(|&(rva, _)| rva);
+        self.cache.borrow_mut().clear();
+        Ok(())
+    }
+
+    /// Adds the symbols from an ISR profile to the module with the given
+    /// base address.
+    ///
+    /// The profile must be created from the PDB of the module.
+    pub fn add_profile(&mut self, base_address: Va, profile: &Profile) -> Result<(), VmiError> {
+        self.add_symbols(
+            base_address,
+            profile.symbols().map(|(name, &rva)| (name, rva)),
+        )
+    }
+
+    /// Adds the loaded kernel modules.
+    pub fn load_modules<Driver, Os>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        os: &Os,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+        Os: VmiOs<Driver>,
+    {
+        for module in os.modules(vmi, registers)? {
+            self.add_module(module.base_address, module.size, module.name);
+        }
+
+        Ok(())
+    }
+
+    /// Loads the exported symbols of all modules that have no symbols yet.
+    ///
+    /// The export directories are read from the guest memory. Modules whose
+    /// export directory cannot be read (e.g., because it is paged out) are
+    /// skipped, and can be retried by calling this method again.
+    pub fn load_exported_symbols<Driver, Os>(
+        &mut self,
+        vmi: &VmiCore<Driver>,
+        os: &Os,
+        registers: &<Driver::Architecture as Architecture>::Registers,
+    ) -> Result<(), VmiError>
+    where
+        Driver: VmiDriver,
+        Os: VmiOs<Driver>,
+    {
+        let pending = self
+            .modules
+            .iter()
+            .filter(|module| module.symbols.is_none())
+            .map(|module| module.base_address)
+            .collect::<Vec<_>>();
+
+        for base_address in pending {
+            let symbols = match os.image_exported_symbols(vmi, registers, base_address) {
+                Ok(symbols) => symbols,
+                Err(err) => {
+                    tracing::debug!(%base_address, ?err, "failed to read exported symbols");
+                    continue;
+                }
+            };
+
+            self.add_symbols(
+                base_address,
+                symbols
+                    .into_iter()
+                    .map(|symbol| (symbol.name, symbol.address.0)),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of known modules.
+    pub fn len(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Checks whether no modules are known.
+    pub fn is_empty(&self) -> bool {
+        self.modules.is_empty()
+    }
+
+    /// Removes all modules and clears the cache.
+    pub fn clear(&mut self) {
+        self.modules.clear();
+        self.cache.borrow_mut().clear();
+    }
+
+    /// Resolves a virtual address without consulting the cache.
+    fn resolve(&self, va: Va) -> Option<SymbolizedAddress> {
+        let index = self
+            .modules
+            .partition_point(|module| module.base_address <= va)
+            .checked_sub(1)?;
+
+        let module = &self.modules[index];
+        let rva = va.0 - module.base_address.0;
+        if rva >= module.size {
+            return None;
+        }
+
+        let symbol = module.symbols.as_ref().and_then(|symbols| {
+            let index = symbols
+                .partition_point(|&(symbol_rva, _)| symbol_rva <= rva)
+                .checked_sub(1)?;
+            Some(&symbols[index])
+        });
+
+        Some(match symbol {
+            Some((symbol_rva, name)) => SymbolizedAddress {
+                module: module.name.clone(),
+                symbol: Some(name.clone()),
+                offset: rva - symbol_rva,
+            },
+            None => SymbolizedAddress {
+                module: module.name.clone(),
+                symbol: None,
+                offset: rva,
+            },
+        })
+    }
+}
+
+impl Symbolizer for ModuleSymbolizer {
+    fn symbolize(&self, va: Va) -> Option<SymbolizedAddress> {
+        if let Some(result) = self.cache.borrow_mut().get(&va) {
+            return result.clone();
+        }
+
+        let result = self.resolve(va);
+        self.cache.borrow_mut().put(va, result.clone());
+        result
+    }
+}
@@ -0,0 +1,558 @@
+//! Deterministic test harness.
+//!
+//! Provides an in-memory [`VmiDriver`] and a minimal [`VmiOs`] for unit
+//! tests that would otherwise require a running virtual machine. The guest
+//! memory is a sparse map of zero-initialized pages, and view remappings and
+//! memory access changes are recorded so that the tests can inspect them.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use vmi_arch_amd64::{Amd64, EventMonitor, Interrupt, Registers};
+use vmi_core::{
+    os::{
+        OsArchitecture, OsImageExportedSymbol, OsModule, OsProcess, OsRegion, ProcessId,
+        ProcessObject, ThreadId, ThreadObject, VmiOs,
+    },
+    Gfn, MemoryAccess, Pa, Va, VcpuId, View, VmiCore, VmiDriver, VmiError, VmiEvent,
+    VmiEventResponse, VmiInfo, VmiMappedPage,
+};
+
+const PAGE_SIZE: usize = 4096;
+
+/// In-memory VMI driver.
+pub(crate) struct MockDriver {
+    /// Content of the guest pages that were written to.
+    pages: RefCell<HashMap<Gfn, Vec<u8>>>,
+
+    /// Memory access of the GFNs whose access was changed.
+    access: RefCell<HashMap<(View, Gfn), MemoryAccess>>,
+
+    /// GFNs remapped in a view (original GFN to new GFN).
+    remapped: RefCell<HashMap<(View, Gfn), Gfn>>,
+
+    /// Views created by [`VmiDriver::create_view`].
+    views: RefCell<HashSet<View>>,
+
+    /// GFNs allocated by [`VmiDriver::allocate_gfn`] and not freed yet.
+    allocated: RefCell<HashSet<Gfn>>,
+
+    /// Highest allocated GFN.
+    max_gfn: Cell<Gfn>,
+}
+
+impl MockDriver {
+    /// Creates a new driver with `max_gfn + 1` pages of zeroed memory.
+    pub fn new(max_gfn: Gfn) -> Self {
+        Self {
+            pages: RefCell::new(HashMap::new()),
+            access: RefCell::new(HashMap::new()),
+            remapped: RefCell::new(HashMap::new()),
+            views: RefCell::new(HashSet::new()),
+            allocated: RefCell::new(HashSet::new()),
+            max_gfn: Cell::new(max_gfn),
+        }
+    }
+
+    /// Creates a new [`VmiCore`] with a mock driver.
+    ///
+    /// The caches are disabled, so that every read observes the latest
+    /// content of the memory.
+    pub fn core(max_gfn: Gfn) -> VmiCore<Self> {
+        let mut vmi = VmiCore::new(Self::new(max_gfn)).unwrap();
+        vmi.disable_gfn_cache();
+        vmi.disable_v2p_cache();
+        vmi
+    }
+
+    /// Returns the GFN the original GFN is remapped to in the view.
+    pub fn remapped(&self, view: View, gfn: Gfn) -> Option<Gfn> {
+        self.remapped.borrow().get(&(view, gfn)).copied()
+    }
+
+    /// Returns the memory access of the GFN in the view.
+    pub fn access(&self, view: View, gfn: Gfn) -> MemoryAccess {
+        self.access
+            .borrow()
+            .get(&(view, gfn))
+            .copied()
+            .unwrap_or(MemoryAccess::RWX)
+    }
+
+    /// Checks whether the view exists.
+    pub fn has_view(&self, view: View) -> bool {
+        self.views.borrow().contains(&view)
+    }
+
+    /// Checks whether the GFN was allocated and not freed yet.
+    pub fn is_allocated(&self, gfn: Gfn) -> bool {
+        self.allocated.borrow().contains(&gfn)
+    }
+
+    fn check_gfn(&self, gfn: Gfn) -> Result<(), VmiError> {
+        if gfn > self.max_gfn.get() {
+            return Err(VmiError::OutOfBounds);
+        }
+
+        Ok(())
+    }
+}
+
+impl VmiDriver for MockDriver {
+    type Architecture = Amd64;
+
+    fn info(&self) -> Result<VmiInfo, VmiError> {
+        Ok(VmiInfo {
+            page_size: PAGE_SIZE as u64,
+            page_shift: 12,
+            max_gfn: self.max_gfn.get(),
+            vcpus: 1,
+            tsc_frequency: None,
+            tsc_epoch: None,
+        })
+    }
+
+    fn pause(&self) -> Result<(), VmiError> {
+        Ok(())
+    }
+
+    fn resume(&self) -> Result<(), VmiError> {
+        Ok(())
+    }
+
+    fn registers(&self, _vcpu: VcpuId) -> Result<Registers, VmiError> {
+        Ok(Registers::default())
+    }
+
+    fn set_registers(&self, _vcpu: VcpuId, _registers: Registers) -> Result<(), VmiError> {
+        Ok(())
+    }
+
+    fn memory_access(&self, gfn: Gfn, view: View) -> Result<MemoryAccess, VmiError> {
+        self.check_gfn(gfn)?;
+        Ok(self.access(view, gfn))
+    }
+
+    fn set_memory_access(
+        &self,
+        gfn: Gfn,
+        view: View,
+        access: MemoryAccess,
+    ) -> Result<(), VmiError> {
+        self.check_gfn(gfn)?;
+        self.access.borrow_mut().insert((view, gfn), access);
+        Ok(())
+    }
+
+    fn read_page(&self, gfn: Gfn) -> Result<VmiMappedPage, VmiError> {
+        self.check_gfn(gfn)?;
+
+        let content = match self.pages.borrow().get(&gfn) {
+            Some(content) => content.clone(),
+            None => vec![0; PAGE_SIZE],
+        };
+
+        Ok(VmiMappedPage::new(content))
+    }
+
+    fn write_page(&self, gfn: Gfn, offset: u64, content: &[u8]) -> Result<VmiMappedPage, VmiError> {
+        self.check_gfn(gfn)?;
+
+        let offset = offset as usize;
+        if offset + content.len() > PAGE_SIZE {
+            return Err(VmiError::OutOfBounds);
+        }
+
+        let mut pages = self.pages.borrow_mut();
+        let page = pages.entry(gfn).or_insert_with(|| vec![0; PAGE_SIZE]);
+        page[offset..offset + content.len()].copy_from_slice(content);
+
+        Ok(VmiMappedPage::new(page.clone()))
+    }
+
+    fn allocate_gfn(&self, gfn: Gfn) -> Result<(), VmiError> {
+        if gfn > self.max_gfn.get() {
+            self.max_gfn.set(gfn);
+        }
+
+        self.allocated.borrow_mut().insert(gfn);
+        Ok(())
+    }
+
+    fn free_gfn(&self, gfn: Gfn) -> Result<(), VmiError> {
+        self.check_gfn(gfn)?;
+        self.pages.borrow_mut().remove(&gfn);
+        self.allocated.borrow_mut().remove(&gfn);
+        Ok(())
+    }
+
+    fn default_view(&self) -> View {
+        View(0)
+    }
+
+    fn create_view(&self, _default_access: MemoryAccess) -> Result<View, VmiError> {
+        let mut views = self.views.borrow_mut();
+        let view = View((1..).find(|&id| !views.contains(&View(id))).unwrap());
+        views.insert(view);
+        Ok(view)
+    }
+
+    fn destroy_view(&self, view: View) -> Result<(), VmiError> {
+        if !self.views.borrow_mut().remove(&view) {
+            return Err(VmiError::ViewNotFound);
+        }
+
+        self.access.borrow_mut().retain(|&(v, _), _| v != view);
+        self.remapped.borrow_mut().retain(|&(v, _), _| v != view);
+        Ok(())
+    }
+
+    fn switch_to_view(&self, view: View) -> Result<(), VmiError> {
+        if view != self.default_view() && !self.has_view(view) {
+            return Err(VmiError::ViewNotFound);
+        }
+
+        Ok(())
+    }
+
+    fn change_view_gfn(&self, view: View, old_gfn: Gfn, new_gfn: Gfn) -> Result<(), VmiError> {
+        self.check_gfn(old_gfn)?;
+        self.check_gfn(new_gfn)?;
+        self.remapped.borrow_mut().insert((view, old_gfn), new_gfn);
+        Ok(())
+    }
+
+    fn reset_view_gfn(&self, view: View, gfn: Gfn) -> Result<(), VmiError> {
+        self.remapped.borrow_mut().remove(&(view, gfn));
+        Ok(())
+    }
+
+    fn monitor_enable(&self, _option: EventMonitor) -> Result<(), VmiError> {
+        Ok(())
+    }
+
+    fn monitor_disable(&self, _option: EventMonitor) -> Result<(), VmiError> {
+        Ok(())
+    }
+
+    fn inject_interrupt(&self, _vcpu: VcpuId, _interrupt: Interrupt) -> Result<(), VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn events_pending(&self) -> usize {
+        0
+    }
+
+    fn event_processing_overhead(&self) -> Duration {
+        Duration::ZERO
+    }
+
+    fn wait_for_event(
+        &self,
+        _timeout: Duration,
+        _handler: impl FnMut(&VmiEvent<Amd64>) -> VmiEventResponse<Amd64>,
+    ) -> Result<(), VmiError> {
+        Err(VmiError::Timeout)
+    }
+
+    fn reset_state(&self) -> Result<(), VmiError> {
+        Ok(())
+    }
+}
+
+/// Operating system with a fixed list of processes.
+///
+/// All queries except [`VmiOs::processes`] fail with
+/// [`VmiError::NotSupported`].
+pub(crate) struct MockOs {
+    /// Processes returned by [`VmiOs::processes`].
+    processes: RefCell<Vec<OsProcess>>,
+
+    /// Number of [`VmiOs::processes`] calls.
+    process_walks: Cell<usize>,
+}
+
+impl MockOs {
+    /// Creates a new operating system without any processes.
+    pub fn new() -> Self {
+        Self {
+            processes: RefCell::new(Vec::new()),
+            process_walks: Cell::new(0),
+        }
+    }
+
+    /// Adds a process with the given PID and translation root.
+    pub fn add_process(&self, id: u32, translation_root: Pa) {
+        self.processes.borrow_mut().push(OsProcess {
+            id: ProcessId(id),
+            object: ProcessObject(Va(0xffff_8000_0000_0000 + u64::from(id) * 0x1000)),
+            name: format!("process{id}"),
+            translation_root,
+        });
+    }
+
+    /// Returns the number of times the processes were enumerated.
+    pub fn process_walks(&self) -> usize {
+        self.process_walks.get()
+    }
+}
+
+#[expect(unused_variables)]
+impl VmiOs<MockDriver> for MockOs {
+    fn kernel_image_base(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<Va, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn kernel_information_string(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<String, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn kpti_enabled(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<bool, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn modules(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<Vec<OsModule>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn system_process(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<ProcessObject, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn thread_id(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        thread: ThreadObject,
+    ) -> Result<ThreadId, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_id(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<ProcessId, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn current_thread(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<ThreadObject, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn current_thread_id(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<ThreadId, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn current_process(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<ProcessObject, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn current_process_id(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<ProcessId, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn processes(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<Vec<OsProcess>, VmiError> {
+        self.process_walks.set(self.process_walks.get() + 1);
+
+        Ok(self
+            .processes
+            .borrow()
+            .iter()
+            .map(|process| OsProcess {
+                id: process.id,
+                object: process.object,
+                name: process.name.clone(),
+                translation_root: process.translation_root,
+            })
+            .collect())
+    }
+
+    fn process_parent_process_id(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<ProcessId, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_architecture(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<OsArchitecture, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_translation_root(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<Pa, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_user_translation_root(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<Pa, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_filename(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<String, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_image_base(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<Va, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_regions(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<OsRegion>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_modules(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+    ) -> Result<Vec<OsModule>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn process_address_is_valid(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+        address: Va,
+    ) -> Result<Option<bool>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn find_process_region(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        process: ProcessObject,
+        address: Va,
+    ) -> Result<Option<OsRegion>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn image_architecture(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        image_base: Va,
+    ) -> Result<OsArchitecture, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn image_exported_symbols(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        image_base: Va,
+    ) -> Result<Vec<OsImageExportedSymbol>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn syscall_argument(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        index: u64,
+    ) -> Result<u64, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn function_argument(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+        index: u64,
+    ) -> Result<u64, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn function_return_value(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<u64, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+
+    fn last_error(
+        &self,
+        vmi: &VmiCore<MockDriver>,
+        registers: &Registers,
+    ) -> Result<Option<u32>, VmiError> {
+        Err(VmiError::NotSupported)
+    }
+}
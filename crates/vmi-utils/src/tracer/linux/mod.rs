@@ -0,0 +1,226 @@
+mod table;
+
+use isr_core::Profile;
+use vmi_arch_amd64::{Amd64, Registers};
+use vmi_core::{
+    os::VmiOs as _, Pa, Registers as _, Va, View, VmiCore, VmiDriver, VmiError, VmiEvent,
+    VmiSession,
+};
+use vmi_os_linux::{LinuxOs, Symbols};
+
+use crate::interceptor::Interceptor;
+
+/// Version of the Linux kernel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LinuxKernelVersion {
+    /// Major version number.
+    pub major: u32,
+
+    /// Minor version number.
+    pub minor: u32,
+
+    /// Patch level.
+    pub patch: u32,
+}
+
+impl LinuxKernelVersion {
+    /// Creates a new kernel version.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses the kernel version from the Linux banner string.
+    ///
+    /// The banner is expected to start with `Linux version X.Y.Z`, as
+    /// returned by [`LinuxOs::find_banner`]. Any suffix following the
+    /// version numbers (e.g., `-generic`) is ignored.
+    pub fn from_banner(banner: &str) -> Option<Self> {
+        let version = banner.strip_prefix("Linux version ")?;
+        let version = version.split_whitespace().next()?;
+
+        let mut numbers = version.split('.').map(|part| {
+            let end = part
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(part.len());
+
+            part[..end].parse::<u32>().ok()
+        });
+
+        let major = numbers.next()??;
+        let minor = numbers.next()??;
+        let patch = numbers.next().flatten().unwrap_or(0);
+
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+/// A decoded Linux system call.
+#[derive(Debug, Clone)]
+pub struct LinuxSyscall {
+    /// The system call number (`rax`).
+    pub number: u64,
+
+    /// The name of the system call, if known for the kernel version.
+    pub name: Option<&'static str>,
+
+    /// The system call arguments, as returned by
+    /// [`LinuxOs::syscall_argument`].
+    ///
+    /// [`LinuxOs::syscall_argument`]: vmi_core::os::VmiOs::syscall_argument
+    pub arguments: [u64; 6],
+}
+
+/// Linux system call tracer.
+///
+/// Places a breakpoint on the system call entry point of the kernel
+/// (`entry_SYSCALL_64`) and decodes the system call number and arguments
+/// according to the Linux x86-64 system call ABI.
+///
+/// The tracer uses the [`Interceptor`] to insert the breakpoint. As with the
+/// [`Interceptor`], the caller is responsible for enabling the breakpoint
+/// monitor and for stepping over the breakpoint after the event is handled.
+pub struct LinuxSyscallTracer<Driver>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    interceptor: Interceptor<Driver>,
+    view: View,
+    entry: Va,
+    entry_pa: Pa,
+    version: Option<LinuxKernelVersion>,
+}
+
+impl<Driver> LinuxSyscallTracer<Driver>
+where
+    Driver: VmiDriver<Architecture = Amd64>,
+{
+    /// Creates a new tracer with the breakpoint placed on the
+    /// `entry_SYSCALL_64` symbol.
+    ///
+    /// The kernel version used for mapping system call numbers to names is
+    /// parsed from the Linux banner.
+    pub fn new(
+        vmi: &VmiSession<Driver, LinuxOs<Driver>>,
+        registers: &Registers,
+        profile: &Profile,
+        view: View,
+    ) -> Result<Self, VmiError> {
+        let symbols = Symbols::new(profile)?;
+        let kaslr_offset = vmi.underlying_os().kaslr_offset(vmi.core(), registers)?;
+        let entry = Va(kaslr_offset + symbols.entry_SYSCALL_64);
+
+        Self::with_entry(vmi.core(), registers, entry, view)
+    }
+
+    /// Creates a new tracer with the breakpoint placed on the address
+    /// stored in the `MSR_LSTAR` register.
+    ///
+    /// # Notes
+    ///
+    /// On kernels with KPTI enabled, `MSR_LSTAR` may point to a trampoline
+    /// rather than to `entry_SYSCALL_64`.
+    pub fn from_msr_lstar(
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        view: View,
+    ) -> Result<Self, VmiError> {
+        Self::with_entry(vmi, registers, Va(registers.msr_lstar), view)
+    }
+
+    /// Creates a new tracer with the breakpoint placed on the given
+    /// system call entry point.
+    pub fn with_entry(
+        vmi: &VmiCore<Driver>,
+        registers: &Registers,
+        entry: Va,
+        view: View,
+    ) -> Result<Self, VmiError> {
+        let version = LinuxOs::find_banner(vmi, registers)?
+            .as_deref()
+            .and_then(LinuxKernelVersion::from_banner);
+
+        tracing::debug!(?version, %entry, "syscall entry");
+
+        let entry_pa = vmi.translate_address(registers.address_context(entry))?;
+
+        let mut interceptor = Interceptor::new();
+        interceptor.insert_breakpoint(vmi, entry_pa, view)?;
+
+        Ok(Self {
+            interceptor,
+            view,
+            entry,
+            entry_pa,
+            version,
+        })
+    }
+
+    /// Returns the address of the system call entry point.
+    pub fn entry(&self) -> Va {
+        self.entry
+    }
+
+    /// Returns the detected kernel version.
+    pub fn version(&self) -> Option<LinuxKernelVersion> {
+        self.version
+    }
+
+    /// Returns the name of the system call with the given number.
+    ///
+    /// Returns `None` if the number is unknown or the system call is not
+    /// available in the detected kernel version.
+    pub fn syscall_name(&self, number: u64) -> Option<&'static str> {
+        table::syscall_name(number, self.version)
+    }
+
+    /// Decodes the system call if the event was caused by the tracer's
+    /// breakpoint.
+    pub fn handle_event(
+        &self,
+        vmi: &VmiSession<Driver, LinuxOs<Driver>>,
+        event: &VmiEvent<Amd64>,
+    ) -> Result<Option<LinuxSyscall>, VmiError> {
+        if !self.interceptor.contains_breakpoint(event) {
+            return Ok(None);
+        }
+
+        let registers = event.registers();
+        if Va(registers.instruction_pointer()) != self.entry {
+            return Ok(None);
+        }
+
+        let mut arguments = [0; 6];
+        for (index, argument) in arguments.iter_mut().enumerate() {
+            *argument =
+                vmi.underlying_os()
+                    .syscall_argument(vmi.core(), registers, index as u64)?;
+        }
+
+        let number = registers.rax;
+        let syscall = LinuxSyscall {
+            number,
+            name: self.syscall_name(number),
+            arguments,
+        };
+
+        tracing::trace!(
+            vcpu = %event.vcpu_id(),
+            number,
+            name = syscall.name,
+            "syscall"
+        );
+
+        Ok(Some(syscall))
+    }
+
+    /// Removes the breakpoint from the system call entry point.
+    pub fn remove(&mut self, vmi: &VmiCore<Driver>) -> Result<(), VmiError> {
+        self.interceptor
+            .remove_breakpoint_by_force(vmi, self.entry_pa, self.view)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,425 @@
+//! Linux x86-64 system call table.
+//!
+//! System call numbers on x86-64 are append-only, so the table maps each
+//! number to its name along with the kernel version that introduced it.
+
+use super::LinuxKernelVersion;
+
+/// A single entry in the system call table.
+struct Entry {
+    number: u64,
+    name: &'static str,
+    since: LinuxKernelVersion,
+}
+
+macro_rules! table {
+    ($(($number:literal, $name:literal, $major:literal, $minor:literal, $patch:literal)),* $(,)?) => {
+        &[
+            $(
+                Entry {
+                    number: $number,
+                    name: $name,
+                    since: LinuxKernelVersion::new($major, $minor, $patch),
+                },
+            )*
+        ]
+    };
+}
+
+/// The x86-64 system call table, sorted by system call number.
+///
+/// System calls that predate 2.6.16 are listed as introduced in 2.6.16.
+#[rustfmt::skip]
+static TABLE: &[Entry] = table![
+    (0, "read", 2, 6, 16),
+    (1, "write", 2, 6, 16),
+    (2, "open", 2, 6, 16),
+    (3, "close", 2, 6, 16),
+    (4, "stat", 2, 6, 16),
+    (5, "fstat", 2, 6, 16),
+    (6, "lstat", 2, 6, 16),
+    (7, "poll", 2, 6, 16),
+    (8, "lseek", 2, 6, 16),
+    (9, "mmap", 2, 6, 16),
+    (10, "mprotect", 2, 6, 16),
+    (11, "munmap", 2, 6, 16),
+    (12, "brk", 2, 6, 16),
+    (13, "rt_sigaction", 2, 6, 16),
+    (14, "rt_sigprocmask", 2, 6, 16),
+    (15, "rt_sigreturn", 2, 6, 16),
+    (16, "ioctl", 2, 6, 16),
+    (17, "pread64", 2, 6, 16),
+    (18, "pwrite64", 2, 6, 16),
+    (19, "readv", 2, 6, 16),
+    (20, "writev", 2, 6, 16),
+    (21, "access", 2, 6, 16),
+    (22, "pipe", 2, 6, 16),
+    (23, "select", 2, 6, 16),
+    (24, "sched_yield", 2, 6, 16),
+    (25, "mremap", 2, 6, 16),
+    (26, "msync", 2, 6, 16),
+    (27, "mincore", 2, 6, 16),
+    (28, "madvise", 2, 6, 16),
+    (29, "shmget", 2, 6, 16),
+    (30, "shmat", 2, 6, 16),
+    (31, "shmctl", 2, 6, 16),
+    (32, "dup", 2, 6, 16),
+    (33, "dup2", 2, 6, 16),
+    (34, "pause", 2, 6, 16),
+    (35, "nanosleep", 2, 6, 16),
+    (36, "getitimer", 2, 6, 16),
+    (37, "alarm", 2, 6, 16),
+    (38, "setitimer", 2, 6, 16),
+    (39, "getpid", 2, 6, 16),
+    (40, "sendfile", 2, 6, 16),
+    (41, "socket", 2, 6, 16),
+    (42, "connect", 2, 6, 16),
+    (43, "accept", 2, 6, 16),
+    (44, "sendto", 2, 6, 16),
+    (45, "recvfrom", 2, 6, 16),
+    (46, "sendmsg", 2, 6, 16),
+    (47, "recvmsg", 2, 6, 16),
+    (48, "shutdown", 2, 6, 16),
+    (49, "bind", 2, 6, 16),
+    (50, "listen", 2, 6, 16),
+    (51, "getsockname", 2, 6, 16),
+    (52, "getpeername", 2, 6, 16),
+    (53, "socketpair", 2, 6, 16),
+    (54, "setsockopt", 2, 6, 16),
+    (55, "getsockopt", 2, 6, 16),
+    (56, "clone", 2, 6, 16),
+    (57, "fork", 2, 6, 16),
+    (58, "vfork", 2, 6, 16),
+    (59, "execve", 2, 6, 16),
+    (60, "exit", 2, 6, 16),
+    (61, "wait4", 2, 6, 16),
+    (62, "kill", 2, 6, 16),
+    (63, "uname", 2, 6, 16),
+    (64, "semget", 2, 6, 16),
+    (65, "semop", 2, 6, 16),
+    (66, "semctl", 2, 6, 16),
+    (67, "shmdt", 2, 6, 16),
+    (68, "msgget", 2, 6, 16),
+    (69, "msgsnd", 2, 6, 16),
+    (70, "msgrcv", 2, 6, 16),
+    (71, "msgctl", 2, 6, 16),
+    (72, "fcntl", 2, 6, 16),
+    (73, "flock", 2, 6, 16),
+    (74, "fsync", 2, 6, 16),
+    (75, "fdatasync", 2, 6, 16),
+    (76, "truncate", 2, 6, 16),
+    (77, "ftruncate", 2, 6, 16),
+    (78, "getdents", 2, 6, 16),
+    (79, "getcwd", 2, 6, 16),
+    (80, "chdir", 2, 6, 16),
+    (81, "fchdir", 2, 6, 16),
+    (82, "rename", 2, 6, 16),
+    (83, "mkdir", 2, 6, 16),
+    (84, "rmdir", 2, 6, 16),
+    (85, "creat", 2, 6, 16),
+    (86, "link", 2, 6, 16),
+    (87, "unlink", 2, 6, 16),
+    (88, "symlink", 2, 6, 16),
+    (89, "readlink", 2, 6, 16),
+    (90, "chmod", 2, 6, 16),
+    (91, "fchmod", 2, 6, 16),
+    (92, "chown", 2, 6, 16),
+    (93, "fchown", 2, 6, 16),
+    (94, "lchown", 2, 6, 16),
+    (95, "umask", 2, 6, 16),
+    (96, "gettimeofday", 2, 6, 16),
+    (97, "getrlimit", 2, 6, 16),
+    (98, "getrusage", 2, 6, 16),
+    (99, "sysinfo", 2, 6, 16),
+    (100, "times", 2, 6, 16),
+    (101, "ptrace", 2, 6, 16),
+    (102, "getuid", 2, 6, 16),
+    (103, "syslog", 2, 6, 16),
+    (104, "getgid", 2, 6, 16),
+    (105, "setuid", 2, 6, 16),
+    (106, "setgid", 2, 6, 16),
+    (107, "geteuid", 2, 6, 16),
+    (108, "getegid", 2, 6, 16),
+    (109, "setpgid", 2, 6, 16),
+    (110, "getppid", 2, 6, 16),
+    (111, "getpgrp", 2, 6, 16),
+    (112, "setsid", 2, 6, 16),
+    (113, "setreuid", 2, 6, 16),
+    (114, "setregid", 2, 6, 16),
+    (115, "getgroups", 2, 6, 16),
+    (116, "setgroups", 2, 6, 16),
+    (117, "setresuid", 2, 6, 16),
+    (118, "getresuid", 2, 6, 16),
+    (119, "setresgid", 2, 6, 16),
+    (120, "getresgid", 2, 6, 16),
+    (121, "getpgid", 2, 6, 16),
+    (122, "setfsuid", 2, 6, 16),
+    (123, "setfsgid", 2, 6, 16),
+    (124, "getsid", 2, 6, 16),
+    (125, "capget", 2, 6, 16),
+    (126, "capset", 2, 6, 16),
+    (127, "rt_sigpending", 2, 6, 16),
+    (128, "rt_sigtimedwait", 2, 6, 16),
+    (129, "rt_sigqueueinfo", 2, 6, 16),
+    (130, "rt_sigsuspend", 2, 6, 16),
+    (131, "sigaltstack", 2, 6, 16),
+    (132, "utime", 2, 6, 16),
+    (133, "mknod", 2, 6, 16),
+    (134, "uselib", 2, 6, 16),
+    (135, "personality", 2, 6, 16),
+    (136, "ustat", 2, 6, 16),
+    (137, "statfs", 2, 6, 16),
+    (138, "fstatfs", 2, 6, 16),
+    (139, "sysfs", 2, 6, 16),
+    (140, "getpriority", 2, 6, 16),
+    (141, "setpriority", 2, 6, 16),
+    (142, "sched_setparam", 2, 6, 16),
+    (143, "sched_getparam", 2, 6, 16),
+    (144, "sched_setscheduler", 2, 6, 16),
+    (145, "sched_getscheduler", 2, 6, 16),
+    (146, "sched_get_priority_max", 2, 6, 16),
+    (147, "sched_get_priority_min", 2, 6, 16),
+    (148, "sched_rr_get_interval", 2, 6, 16),
+    (149, "mlock", 2, 6, 16),
+    (150, "munlock", 2, 6, 16),
+    (151, "mlockall", 2, 6, 16),
+    (152, "munlockall", 2, 6, 16),
+    (153, "vhangup", 2, 6, 16),
+    (154, "modify_ldt", 2, 6, 16),
+    (155, "pivot_root", 2, 6, 16),
+    (156, "_sysctl", 2, 6, 16),
+    (157, "prctl", 2, 6, 16),
+    (158, "arch_prctl", 2, 6, 16),
+    (159, "adjtimex", 2, 6, 16),
+    (160, "setrlimit", 2, 6, 16),
+    (161, "chroot", 2, 6, 16),
+    (162, "sync", 2, 6, 16),
+    (163, "acct", 2, 6, 16),
+    (164, "settimeofday", 2, 6, 16),
+    (165, "mount", 2, 6, 16),
+    (166, "umount2", 2, 6, 16),
+    (167, "swapon", 2, 6, 16),
+    (168, "swapoff", 2, 6, 16),
+    (169, "reboot", 2, 6, 16),
+    (170, "sethostname", 2, 6, 16),
+    (171, "setdomainname", 2, 6, 16),
+    (172, "iopl", 2, 6, 16),
+    (173, "ioperm", 2, 6, 16),
+    (174, "create_module", 2, 6, 16),
+    (175, "init_module", 2, 6, 16),
+    (176, "delete_module", 2, 6, 16),
+    (177, "get_kernel_syms", 2, 6, 16),
+    (178, "query_module", 2, 6, 16),
+    (179, "quotactl", 2, 6, 16),
+    (180, "nfsservctl", 2, 6, 16),
+    (181, "getpmsg", 2, 6, 16),
+    (182, "putpmsg", 2, 6, 16),
+    (183, "afs_syscall", 2, 6, 16),
+    (184, "tuxcall", 2, 6, 16),
+    (185, "security", 2, 6, 16),
+    (186, "gettid", 2, 6, 16),
+    (187, "readahead", 2, 6, 16),
+    (188, "setxattr", 2, 6, 16),
+    (189, "lsetxattr", 2, 6, 16),
+    (190, "fsetxattr", 2, 6, 16),
+    (191, "getxattr", 2, 6, 16),
+    (192, "lgetxattr", 2, 6, 16),
+    (193, "fgetxattr", 2, 6, 16),
+    (194, "listxattr", 2, 6, 16),
+    (195, "llistxattr", 2, 6, 16),
+    (196, "flistxattr", 2, 6, 16),
+    (197, "removexattr", 2, 6, 16),
+    (198, "lremovexattr", 2, 6, 16),
+    (199, "fremovexattr", 2, 6, 16),
+    (200, "tkill", 2, 6, 16),
+    (201, "time", 2, 6, 16),
+    (202, "futex", 2, 6, 16),
+    (203, "sched_setaffinity", 2, 6, 16),
+    (204, "sched_getaffinity", 2, 6, 16),
+    (205, "set_thread_area", 2, 6, 16),
+    (206, "io_setup", 2, 6, 16),
+    (207, "io_destroy", 2, 6, 16),
+    (208, "io_getevents", 2, 6, 16),
+    (209, "io_submit", 2, 6, 16),
+    (210, "io_cancel", 2, 6, 16),
+    (211, "get_thread_area", 2, 6, 16),
+    (212, "lookup_dcookie", 2, 6, 16),
+    (213, "epoll_create", 2, 6, 16),
+    (214, "epoll_ctl_old", 2, 6, 16),
+    (215, "epoll_wait_old", 2, 6, 16),
+    (216, "remap_file_pages", 2, 6, 16),
+    (217, "getdents64", 2, 6, 16),
+    (218, "set_tid_address", 2, 6, 16),
+    (219, "restart_syscall", 2, 6, 16),
+    (220, "semtimedop", 2, 6, 16),
+    (221, "fadvise64", 2, 6, 16),
+    (222, "timer_create", 2, 6, 16),
+    (223, "timer_settime", 2, 6, 16),
+    (224, "timer_gettime", 2, 6, 16),
+    (225, "timer_getoverrun", 2, 6, 16),
+    (226, "timer_delete", 2, 6, 16),
+    (227, "clock_settime", 2, 6, 16),
+    (228, "clock_gettime", 2, 6, 16),
+    (229, "clock_getres", 2, 6, 16),
+    (230, "clock_nanosleep", 2, 6, 16),
+    (231, "exit_group", 2, 6, 16),
+    (232, "epoll_wait", 2, 6, 16),
+    (233, "epoll_ctl", 2, 6, 16),
+    (234, "tgkill", 2, 6, 16),
+    (235, "utimes", 2, 6, 16),
+    (236, "vserver", 2, 6, 16),
+    (237, "mbind", 2, 6, 16),
+    (238, "set_mempolicy", 2, 6, 16),
+    (239, "get_mempolicy", 2, 6, 16),
+    (240, "mq_open", 2, 6, 16),
+    (241, "mq_unlink", 2, 6, 16),
+    (242, "mq_timedsend", 2, 6, 16),
+    (243, "mq_timedreceive", 2, 6, 16),
+    (244, "mq_notify", 2, 6, 16),
+    (245, "mq_getsetattr", 2, 6, 16),
+    (246, "kexec_load", 2, 6, 16),
+    (247, "waitid", 2, 6, 16),
+    (248, "add_key", 2, 6, 16),
+    (249, "request_key", 2, 6, 16),
+    (250, "keyctl", 2, 6, 16),
+    (251, "ioprio_set", 2, 6, 16),
+    (252, "ioprio_get", 2, 6, 16),
+    (253, "inotify_init", 2, 6, 16),
+    (254, "inotify_add_watch", 2, 6, 16),
+    (255, "inotify_rm_watch", 2, 6, 16),
+    (256, "migrate_pages", 2, 6, 16),
+    (257, "openat", 2, 6, 16),
+    (258, "mkdirat", 2, 6, 16),
+    (259, "mknodat", 2, 6, 16),
+    (260, "fchownat", 2, 6, 16),
+    (261, "futimesat", 2, 6, 16),
+    (262, "newfstatat", 2, 6, 16),
+    (263, "unlinkat", 2, 6, 16),
+    (264, "renameat", 2, 6, 16),
+    (265, "linkat", 2, 6, 16),
+    (266, "symlinkat", 2, 6, 16),
+    (267, "readlinkat", 2, 6, 16),
+    (268, "fchmodat", 2, 6, 16),
+    (269, "faccessat", 2, 6, 16),
+    (270, "pselect6", 2, 6, 16),
+    (271, "ppoll", 2, 6, 16),
+    (272, "unshare", 2, 6, 16),
+    (273, "set_robust_list", 2, 6, 17),
+    (274, "get_robust_list", 2, 6, 17),
+    (275, "splice", 2, 6, 17),
+    (276, "tee", 2, 6, 17),
+    (277, "sync_file_range", 2, 6, 17),
+    (278, "vmsplice", 2, 6, 17),
+    (279, "move_pages", 2, 6, 18),
+    (280, "utimensat", 2, 6, 22),
+    (281, "epoll_pwait", 2, 6, 19),
+    (282, "signalfd", 2, 6, 22),
+    (283, "timerfd_create", 2, 6, 25),
+    (284, "eventfd", 2, 6, 22),
+    (285, "fallocate", 2, 6, 23),
+    (286, "timerfd_settime", 2, 6, 25),
+    (287, "timerfd_gettime", 2, 6, 25),
+    (288, "accept4", 2, 6, 28),
+    (289, "signalfd4", 2, 6, 27),
+    (290, "eventfd2", 2, 6, 27),
+    (291, "epoll_create1", 2, 6, 27),
+    (292, "dup3", 2, 6, 27),
+    (293, "pipe2", 2, 6, 27),
+    (294, "inotify_init1", 2, 6, 27),
+    (295, "preadv", 2, 6, 30),
+    (296, "pwritev", 2, 6, 30),
+    (297, "rt_tgsigqueueinfo", 2, 6, 31),
+    (298, "perf_event_open", 2, 6, 31),
+    (299, "recvmmsg", 2, 6, 33),
+    (300, "fanotify_init", 2, 6, 36),
+    (301, "fanotify_mark", 2, 6, 36),
+    (302, "prlimit64", 2, 6, 36),
+    (303, "name_to_handle_at", 2, 6, 39),
+    (304, "open_by_handle_at", 2, 6, 39),
+    (305, "clock_adjtime", 2, 6, 39),
+    (306, "syncfs", 2, 6, 39),
+    (307, "sendmmsg", 3, 0, 0),
+    (308, "setns", 3, 0, 0),
+    (309, "getcpu", 2, 6, 19),
+    (310, "process_vm_readv", 3, 2, 0),
+    (311, "process_vm_writev", 3, 2, 0),
+    (312, "kcmp", 3, 5, 0),
+    (313, "finit_module", 3, 8, 0),
+    (314, "sched_setattr", 3, 14, 0),
+    (315, "sched_getattr", 3, 14, 0),
+    (316, "renameat2", 3, 15, 0),
+    (317, "seccomp", 3, 17, 0),
+    (318, "getrandom", 3, 17, 0),
+    (319, "memfd_create", 3, 17, 0),
+    (320, "kexec_file_load", 3, 17, 0),
+    (321, "bpf", 3, 18, 0),
+    (322, "execveat", 3, 19, 0),
+    (323, "userfaultfd", 4, 3, 0),
+    (324, "membarrier", 4, 3, 0),
+    (325, "mlock2", 4, 4, 0),
+    (326, "copy_file_range", 4, 5, 0),
+    (327, "preadv2", 4, 6, 0),
+    (328, "pwritev2", 4, 6, 0),
+    (329, "pkey_mprotect", 4, 9, 0),
+    (330, "pkey_alloc", 4, 9, 0),
+    (331, "pkey_free", 4, 9, 0),
+    (332, "statx", 4, 11, 0),
+    (333, "io_pgetevents", 4, 18, 0),
+    (334, "rseq", 4, 18, 0),
+    (335, "uretprobe", 6, 11, 0),
+    (424, "pidfd_send_signal", 5, 1, 0),
+    (425, "io_uring_setup", 5, 1, 0),
+    (426, "io_uring_enter", 5, 1, 0),
+    (427, "io_uring_register", 5, 1, 0),
+    (428, "open_tree", 5, 2, 0),
+    (429, "move_mount", 5, 2, 0),
+    (430, "fsopen", 5, 2, 0),
+    (431, "fsconfig", 5, 2, 0),
+    (432, "fsmount", 5, 2, 0),
+    (433, "fspick", 5, 2, 0),
+    (434, "pidfd_open", 5, 3, 0),
+    (435, "clone3", 5, 3, 0),
+    (436, "close_range", 5, 9, 0),
+    (437, "openat2", 5, 6, 0),
+    (438, "pidfd_getfd", 5, 6, 0),
+    (439, "faccessat2", 5, 8, 0),
+    (440, "process_madvise", 5, 10, 0),
+    (441, "epoll_pwait2", 5, 11, 0),
+    (442, "mount_setattr", 5, 12, 0),
+    (443, "quotactl_fd", 5, 14, 0),
+    (444, "landlock_create_ruleset", 5, 13, 0),
+    (445, "landlock_add_rule", 5, 13, 0),
+    (446, "landlock_restrict_self", 5, 13, 0),
+    (447, "memfd_secret", 5, 14, 0),
+    (448, "process_mrelease", 5, 15, 0),
+    (449, "futex_waitv", 5, 16, 0),
+    (450, "set_mempolicy_home_node", 5, 17, 0),
+    (451, "cachestat", 6, 5, 0),
+    (452, "fchmodat2", 6, 6, 0),
+    (453, "map_shadow_stack", 6, 6, 0),
+    (454, "futex_wake", 6, 7, 0),
+    (455, "futex_wait", 6, 7, 0),
+    (456, "futex_requeue", 6, 7, 0),
+    (457, "statmount", 6, 8, 0),
+    (458, "listmount", 6, 8, 0),
+    (459, "lsm_get_self_attr", 6, 8, 0),
+    (460, "lsm_set_self_attr", 6, 8, 0),
+    (461, "lsm_list_modules", 6, 8, 0),
+    (462, "mseal", 6, 10, 0),
+];
+
+/// Returns the name of the system call with the given number.
+///
+/// If the kernel version is known, system calls introduced in later
+/// versions are not reported.
+pub fn syscall_name(number: u64, version: Option<LinuxKernelVersion>) -> Option<&'static str> {
+    let index = TABLE
+        .binary_search_by_key(&number, |entry| entry.number)
+        .ok()?;
+    let entry = &TABLE[index];
+
+    match version {
+        Some(version) if version < entry.since => None,
+        _ => Some(entry.name),
+    }
+}
@@ -0,0 +1,10 @@
+//! System call tracing.
+//!
+//! Provides tracers that intercept the system call entry point of the guest
+//! kernel and decode the system call number and arguments according to the
+//! calling convention of the operating system.
+
+#[cfg(all(feature = "arch-amd64", feature = "os-linux"))]
+mod linux;
+#[cfg(all(feature = "arch-amd64", feature = "os-linux"))]
+pub use self::linux::{LinuxKernelVersion, LinuxSyscall, LinuxSyscallTracer};
@@ -0,0 +1,596 @@
+//! Management of multiple memory views.
+//!
+//! Keeps track of the views created through the [`ViewManager`], the GFN
+//! remappings and the memory access permissions applied to each of them,
+//! and the shadow pages backing those remappings.
+//!
+//! Because every change is recorded, a view can be cloned into a new view,
+//! restored to its initial state, or destroyed together with its shadow
+//! pages. When the [`ViewManager`] is dropped, all views it created are
+//! destroyed and all shadow pages are freed.
+//!
+//! Shadow pages released by [`ViewManager::restore`], or by remapping
+//! a shadowed GFN elsewhere, are kept in a pool and reused by subsequent
+//! calls to [`ViewManager::shadow_page`]. Shadow pages of a view destroyed
+//! by [`ViewManager::destroy_view`] are freed.
+
+use std::collections::HashMap;
+
+use vmi_core::{Architecture as _, Gfn, MemoryAccess, View, VmiCore, VmiDriver, VmiError};
+
+/// State of a single view.
+struct ViewState {
+    /// Default access permissions the view was created with.
+    default_access: MemoryAccess,
+
+    /// GFN remappings applied to the view.
+    ///
+    /// * Key: Original GFN
+    /// * Value: GFN the original GFN is mapped to
+    remappings: HashMap<Gfn, Gfn>,
+
+    /// Memory access permissions applied to the view.
+    ///
+    /// * Key: GFN
+    /// * Value: Access permissions
+    access: HashMap<Gfn, MemoryAccess>,
+
+    /// Shadow pages allocated for the view.
+    ///
+    /// * Key: Original GFN
+    /// * Value: Shadow GFN
+    shadow_pages: HashMap<Gfn, Gfn>,
+}
+
+/// Manager of views and their GFN remappings.
+pub struct ViewManager<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    vmi: &'a VmiCore<Driver>,
+    views: HashMap<View, ViewState>,
+
+    /// Shadow GFNs that were released and can be reused.
+    free_gfns: Vec<Gfn>,
+}
+
+impl<'a, Driver> ViewManager<'a, Driver>
+where
+    Driver: VmiDriver,
+{
+    /// Creates a new view manager.
+    pub fn new(vmi: &'a VmiCore<Driver>) -> Self {
+        Self {
+            vmi,
+            views: HashMap::new(),
+            free_gfns: Vec::new(),
+        }
+    }
+
+    /// Returns the VMI core the view manager operates on.
+    pub fn vmi(&self) -> &'a VmiCore<Driver> {
+        self.vmi
+    }
+
+    /// Returns an iterator over the views managed by the view manager.
+    pub fn views(&self) -> impl Iterator<Item = View> + '_ {
+        self.views.keys().copied()
+    }
+
+    /// Checks whether the view is managed by the view manager.
+    pub fn contains_view(&self, view: View) -> bool {
+        self.views.contains_key(&view)
+    }
+
+    /// Returns the GFN the given GFN is remapped to in the view.
+    pub fn remapping(&self, view: View, gfn: Gfn) -> Option<Gfn> {
+        self.views.get(&view)?.remappings.get(&gfn).copied()
+    }
+
+    /// Returns the memory access permissions set for the GFN in the view.
+    ///
+    /// Returns `None` if the permissions were not changed through the
+    /// view manager.
+    pub fn memory_access(&self, view: View, gfn: Gfn) -> Option<MemoryAccess> {
+        self.views.get(&view)?.access.get(&gfn).copied()
+    }
+
+    /// Creates a new view with the specified default access permissions.
+    pub fn create_view(&mut self, default_access: MemoryAccess) -> Result<View, VmiError> {
+        let view = self.vmi.create_view(default_access)?;
+
+        self.views.insert(
+            view,
+            ViewState {
+                default_access,
+                remappings: HashMap::new(),
+                access: HashMap::new(),
+                shadow_pages: HashMap::new(),
+            },
+        );
+
+        tracing::debug!(%view, ?default_access, "created view");
+        Ok(view)
+    }
+
+    /// Creates a new view as a clone of an existing managed view.
+    ///
+    /// The new view has the same default access permissions, GFN
+    /// remappings and memory access permissions as the source view.
+    /// Shadow pages are copied, so that each view owns its shadow pages
+    /// and the views can be restored or destroyed independently.
+    ///
+    /// If any of the changes cannot be applied, the new view is destroyed.
+    pub fn clone_view(&mut self, source: View) -> Result<View, VmiError> {
+        let (default_access, mut remappings, access, shadow_pages) = match self.views.get(&source) {
+            Some(state) => (
+                state.default_access,
+                state.remappings.clone(),
+                state.access.clone(),
+                state.shadow_pages.clone(),
+            ),
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        let view = self.create_view(default_access)?;
+
+        let result = (|| {
+            for (gfn, source_shadow_gfn) in shadow_pages {
+                let shadow_gfn = self.allocate_shadow_page(source_shadow_gfn)?;
+
+                if let Some(state) = self.views.get_mut(&view) {
+                    state.shadow_pages.insert(gfn, shadow_gfn);
+                }
+
+                remappings.insert(gfn, shadow_gfn);
+            }
+
+            self.apply(view, remappings, access)
+        })();
+
+        if let Err(err) = result {
+            self.destroy_view(view)?;
+            return Err(err);
+        }
+
+        tracing::debug!(%source, %view, "cloned view");
+        Ok(view)
+    }
+
+    /// Remaps the GFN to another GFN in the view.
+    ///
+    /// If the GFN was backed by a shadow page, the shadow page is released.
+    pub fn remap(&mut self, view: View, gfn: Gfn, new_gfn: Gfn) -> Result<(), VmiError> {
+        if let Some(shadow_gfn) = self.remap_gfn(view, gfn, new_gfn)? {
+            self.free_gfns.push(shadow_gfn);
+        }
+
+        Ok(())
+    }
+
+    /// Resets the remapping of the GFN in the view.
+    ///
+    /// If the GFN was backed by a shadow page, the shadow page is released.
+    pub fn reset(&mut self, view: View, gfn: Gfn) -> Result<(), VmiError> {
+        let state = match self.views.get_mut(&view) {
+            Some(state) => state,
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        if state.remappings.remove(&gfn).is_none() {
+            return Ok(());
+        }
+
+        self.vmi.reset_view_gfn(view, gfn)?;
+
+        if let Some(shadow_gfn) = state.shadow_pages.remove(&gfn) {
+            self.free_gfns.push(shadow_gfn);
+        }
+
+        Ok(())
+    }
+
+    /// Sets the memory access permissions for the GFN in the view.
+    pub fn set_memory_access(
+        &mut self,
+        view: View,
+        gfn: Gfn,
+        access: MemoryAccess,
+    ) -> Result<(), VmiError> {
+        let state = match self.views.get_mut(&view) {
+            Some(state) => state,
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        self.vmi.set_memory_access(gfn, view, access)?;
+        state.access.insert(gfn, access);
+        Ok(())
+    }
+
+    /// Remaps the GFN to a shadow page in the view.
+    ///
+    /// The shadow page is initialized with the contents of the original
+    /// page. If the GFN is already backed by a shadow page in the view,
+    /// the existing shadow page is returned.
+    pub fn shadow_page(&mut self, view: View, gfn: Gfn) -> Result<Gfn, VmiError> {
+        let state = match self.views.get(&view) {
+            Some(state) => state,
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        if let Some(shadow_gfn) = state.shadow_pages.get(&gfn) {
+            return Ok(*shadow_gfn);
+        }
+
+        let shadow_gfn = self.allocate_shadow_page(gfn)?;
+
+        if let Err(err) = self.remap(view, gfn, shadow_gfn) {
+            self.free_gfns.push(shadow_gfn);
+            return Err(err);
+        }
+
+        if let Some(state) = self.views.get_mut(&view) {
+            state.shadow_pages.insert(gfn, shadow_gfn);
+        }
+
+        tracing::debug!(%view, %gfn, %shadow_gfn, "created shadow page");
+        Ok(shadow_gfn)
+    }
+
+    /// Applies a batch of GFN remappings and memory access permissions to
+    /// the view.
+    ///
+    /// The operation is transactional: if any of the changes cannot be
+    /// applied, the changes already applied by this call are reverted
+    /// and the error is returned. Reverting is best-effort; changes that
+    /// cannot be reverted are logged, and the original error is returned
+    /// nevertheless.
+    pub fn apply(
+        &mut self,
+        view: View,
+        remappings: impl IntoIterator<Item = (Gfn, Gfn)>,
+        access: impl IntoIterator<Item = (Gfn, MemoryAccess)>,
+    ) -> Result<(), VmiError> {
+        let state = match self.views.get(&view) {
+            Some(state) => state,
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        let default_access = state.default_access;
+        let mut applied_remappings = Vec::new();
+        let mut applied_access = Vec::new();
+
+        let result = (|| {
+            for (gfn, new_gfn) in remappings {
+                let previous = self.remapping(view, gfn);
+                let released = self.remap_gfn(view, gfn, new_gfn)?;
+                applied_remappings.push((gfn, previous, released));
+            }
+
+            for (gfn, access) in access {
+                let previous = self.memory_access(view, gfn);
+                self.set_memory_access(view, gfn, access)?;
+                applied_access.push((gfn, previous));
+            }
+
+            Ok(())
+        })();
+
+        let err = match result {
+            Ok(()) => {
+                let released = applied_remappings
+                    .into_iter()
+                    .filter_map(|(_, _, released)| released);
+                self.free_gfns.extend(released);
+                return Ok(());
+            }
+            Err(err) => err,
+        };
+
+        tracing::debug!(%view, ?err, "rolling back view changes");
+
+        let vmi = self.vmi;
+        let state = self.views.get_mut(&view).expect("view exists");
+
+        for (gfn, previous) in applied_access.into_iter().rev() {
+            let access = previous.unwrap_or(default_access);
+            if let Err(err) = vmi.set_memory_access(gfn, view, access) {
+                tracing::warn!(%view, %gfn, %err, "failed to roll back memory access");
+                continue;
+            }
+
+            match previous {
+                Some(access) => state.access.insert(gfn, access),
+                None => state.access.remove(&gfn),
+            };
+        }
+
+        for (gfn, previous, released) in applied_remappings.into_iter().rev() {
+            let result = match previous {
+                Some(new_gfn) => vmi.change_view_gfn(view, gfn, new_gfn),
+                None => vmi.reset_view_gfn(view, gfn),
+            };
+
+            if let Err(err) = result {
+                tracing::warn!(%view, %gfn, %err, "failed to roll back GFN remapping");
+                self.free_gfns.extend(released);
+                continue;
+            }
+
+            match previous {
+                Some(new_gfn) => state.remappings.insert(gfn, new_gfn),
+                None => state.remappings.remove(&gfn),
+            };
+
+            if let Some(shadow_gfn) = released {
+                state.shadow_pages.insert(gfn, shadow_gfn);
+            }
+        }
+
+        Err(err)
+    }
+
+    /// Restores the view to its initial state.
+    ///
+    /// All GFN remappings are reset, memory access permissions are set back
+    /// to the default access permissions of the view, and the shadow pages
+    /// of the view are released.
+    ///
+    /// Every GFN is attempted, even if some of them fail. The failed ones
+    /// are kept (together with their shadow pages), so that the restore can
+    /// be retried, and the first error is returned.
+    pub fn restore(&mut self, view: View) -> Result<(), VmiError> {
+        let state = match self.views.get_mut(&view) {
+            Some(state) => state,
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        let mut first_error = None;
+
+        state.access.retain(|&gfn, _| {
+            match self.vmi.set_memory_access(gfn, view, state.default_access) {
+                Ok(()) => false,
+                Err(err) => {
+                    tracing::warn!(%view, %gfn, %err, "failed to restore memory access");
+                    first_error.get_or_insert(err);
+                    true
+                }
+            }
+        });
+
+        state
+            .remappings
+            .retain(|&gfn, _| match self.vmi.reset_view_gfn(view, gfn) {
+                Ok(()) => false,
+                Err(err) => {
+                    tracing::warn!(%view, %gfn, %err, "failed to reset GFN remapping");
+                    first_error.get_or_insert(err);
+                    true
+                }
+            });
+
+        // Shadow pages of GFNs that are still remapped remain in use.
+        let remappings = &state.remappings;
+        state.shadow_pages.retain(|gfn, shadow_gfn| {
+            if remappings.contains_key(gfn) {
+                return true;
+            }
+
+            self.free_gfns.push(*shadow_gfn);
+            false
+        });
+
+        if let Some(err) = first_error {
+            return Err(err);
+        }
+
+        tracing::debug!(%view, "restored view");
+        Ok(())
+    }
+
+    /// Destroys the view.
+    ///
+    /// The view is restored to its initial state before being destroyed,
+    /// and its shadow pages are freed.
+    pub fn destroy_view(&mut self, view: View) -> Result<(), VmiError> {
+        let shadow_gfns = match self.views.get(&view) {
+            Some(state) => state.shadow_pages.values().copied().collect::<Vec<_>>(),
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        self.restore(view)?;
+        self.vmi.destroy_view(view)?;
+        self.views.remove(&view);
+
+        // Shadow pages that fail to be freed stay in the pool, so that
+        // freeing them is retried when the view manager is dropped.
+        let mut first_error = None;
+        self.free_gfns.retain(|&gfn| {
+            if !shadow_gfns.contains(&gfn) {
+                return true;
+            }
+
+            match self.vmi.free_gfn(gfn) {
+                Ok(()) => false,
+                Err(err) => {
+                    tracing::warn!(%view, %gfn, %err, "failed to free shadow page");
+                    first_error.get_or_insert(err);
+                    true
+                }
+            }
+        });
+
+        if let Some(err) = first_error {
+            return Err(err);
+        }
+
+        tracing::debug!(%view, "destroyed view");
+        Ok(())
+    }
+
+    /// Remaps the GFN to another GFN in the view.
+    ///
+    /// If the GFN was backed by a different shadow page, the shadow page is
+    /// removed from the view and returned, so that the caller can release
+    /// it.
+    fn remap_gfn(&mut self, view: View, gfn: Gfn, new_gfn: Gfn) -> Result<Option<Gfn>, VmiError> {
+        let state = match self.views.get_mut(&view) {
+            Some(state) => state,
+            None => return Err(VmiError::ViewNotFound),
+        };
+
+        self.vmi.change_view_gfn(view, gfn, new_gfn)?;
+        state.remappings.insert(gfn, new_gfn);
+
+        match state.shadow_pages.get(&gfn) {
+            Some(&shadow_gfn) if shadow_gfn != new_gfn => Ok(state.shadow_pages.remove(&gfn)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Allocates a shadow page initialized with the contents of a page.
+    ///
+    /// Released shadow pages are reused before new GFNs are allocated.
+    fn allocate_shadow_page(&mut self, source: Gfn) -> Result<Gfn, VmiError> {
+        let shadow_gfn = match self.free_gfns.pop() {
+            Some(shadow_gfn) => shadow_gfn,
+            None => self.vmi.allocate_next_available_gfn()?,
+        };
+
+        if let Err(err) = self.copy_page(source, shadow_gfn) {
+            self.free_gfns.push(shadow_gfn);
+            return Err(err);
+        }
+
+        Ok(shadow_gfn)
+    }
+
+    /// Copies the contents of a page to another page.
+    fn copy_page(&self, source: Gfn, destination: Gfn) -> Result<(), VmiError> {
+        let mut content = vec![0u8; Driver::Architecture::PAGE_SIZE as usize];
+        self.vmi
+            .read(Driver::Architecture::pa_from_gfn(source), &mut content)?;
+        self.vmi
+            .write(Driver::Architecture::pa_from_gfn(destination), &content)?;
+        Ok(())
+    }
+}
+
+impl<Driver> Drop for ViewManager<'_, Driver>
+where
+    Driver: VmiDriver,
+{
+    fn drop(&mut self) {
+        let default_view = self.vmi.default_view();
+        if let Err(err) = self.vmi.switch_to_view(default_view) {
+            tracing::error!(?err, "Failed to switch to the default view");
+        }
+
+        let views = self.views.keys().copied().collect::<Vec<_>>();
+        for view in views {
+            if let Err(err) = self.destroy_view(view) {
+                tracing::error!(%view, ?err, "Failed to destroy view");
+            }
+        }
+
+        for gfn in self.free_gfns.drain(..) {
+            if let Err(err) = self.vmi.free_gfn(gfn) {
+                tracing::error!(%gfn, ?err, "Failed to free shadow page");
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "arch-amd64"))]
+mod tests {
+    use vmi_core::Pa;
+
+    use super::*;
+    use crate::testing::MockDriver;
+
+    const GFN: Gfn = Gfn(0x10);
+    const OTHER_GFN: Gfn = Gfn(0x20);
+    const INVALID_GFN: Gfn = Gfn(0x1000);
+
+    #[test]
+    fn apply_rolls_back_on_error() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+
+        let result = views.apply(
+            view,
+            [(GFN, OTHER_GFN), (OTHER_GFN, INVALID_GFN)],
+            [(GFN, MemoryAccess::R)],
+        );
+        assert!(matches!(result, Err(VmiError::OutOfBounds)));
+
+        assert_eq!(views.remapping(view, GFN), None);
+        assert_eq!(vmi.driver().remapped(view, GFN), None);
+        assert_eq!(views.memory_access(view, GFN), None);
+    }
+
+    #[test]
+    fn apply_restores_shadow_page_on_error() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+        let shadow_gfn = views.shadow_page(view, GFN).unwrap();
+
+        let result = views.apply(view, [(GFN, OTHER_GFN), (OTHER_GFN, INVALID_GFN)], []);
+        assert!(result.is_err());
+
+        assert_eq!(views.remapping(view, GFN), Some(shadow_gfn));
+        assert_eq!(vmi.driver().remapped(view, GFN), Some(shadow_gfn));
+        assert_eq!(views.shadow_page(view, GFN).unwrap(), shadow_gfn);
+    }
+
+    #[test]
+    fn remap_releases_shadow_page() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+        let shadow_gfn = views.shadow_page(view, GFN).unwrap();
+
+        views.remap(view, GFN, OTHER_GFN).unwrap();
+        assert_eq!(vmi.driver().remapped(view, GFN), Some(OTHER_GFN));
+
+        // The released shadow page is reused for a fresh copy.
+        vmi.write_u64(Pa(OTHER_GFN.0 << 12), 0x1234).unwrap();
+        let new_shadow_gfn = views.shadow_page(view, OTHER_GFN).unwrap();
+        assert_eq!(new_shadow_gfn, shadow_gfn);
+        assert_eq!(vmi.read_u64(Pa(new_shadow_gfn.0 << 12)).unwrap(), 0x1234);
+    }
+
+    #[test]
+    fn destroy_view_frees_shadow_pages() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+        let shadow_gfn = views.shadow_page(view, GFN).unwrap();
+        assert!(vmi.driver().is_allocated(shadow_gfn));
+
+        views.destroy_view(view).unwrap();
+        assert!(!views.contains_view(view));
+        assert!(!vmi.driver().has_view(view));
+        assert!(!vmi.driver().is_allocated(shadow_gfn));
+    }
+
+    #[test]
+    fn clone_view_owns_shadow_pages() {
+        let vmi = MockDriver::core(Gfn(0xff));
+        let mut views = ViewManager::new(&vmi);
+        let view = views.create_view(MemoryAccess::RWX).unwrap();
+        let shadow_gfn = views.shadow_page(view, GFN).unwrap();
+        vmi.write_u64(Pa(shadow_gfn.0 << 12), 0x1234).unwrap();
+
+        let clone = views.clone_view(view).unwrap();
+        let clone_shadow_gfn = views.remapping(clone, GFN).unwrap();
+        assert_ne!(clone_shadow_gfn, shadow_gfn);
+        assert_eq!(vmi.read_u64(Pa(clone_shadow_gfn.0 << 12)).unwrap(), 0x1234);
+
+        views.destroy_view(view).unwrap();
+        assert!(!vmi.driver().is_allocated(shadow_gfn));
+        assert!(vmi.driver().is_allocated(clone_shadow_gfn));
+    }
+}
@@ -14,8 +14,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         panic!("Domain not found");
     };
 
-    // Setup VMI.
-    let driver = VmiXenDriver::<Amd64>::new(domain_id)?;
+    // Setup VMI. Views are not needed, so the example also works with
+    // domains created without altp2m.
+    let driver = VmiXenDriver::<Amd64>::builder(domain_id)
+        .with_altp2m(false)
+        .build()?;
     let vmi = VmiCore::new(driver)?;
 
     // Get the interrupt descriptor table for each VCPU and print it.
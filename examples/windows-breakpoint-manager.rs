@@ -298,7 +298,7 @@ where
             }
         };
 
-        let process = vmi.os().current_process()?;
+        let process = vmi.current_process()?;
         let process_id = vmi.os().process_id(process)?;
         let process_name = vmi.os().process_filename(process)?;
         tracing::Span::current()
@@ -364,7 +364,7 @@ where
 
         let ObjectAttributes = Va(vmi.os().function_argument(2)?);
 
-        let process = vmi.os().current_process()?;
+        let process = vmi.current_process()?;
         let path = match vmi
             .os()
             .object_attributes_to_object_name(process, ObjectAttributes)?
@@ -403,7 +403,7 @@ where
 
         let FileHandle = vmi.os().function_argument(0)?;
 
-        let process = vmi.os().current_process()?;
+        let process = vmi.current_process()?;
 
         let object = match vmi.os().handle_to_object_address(process, FileHandle)? {
             Some(object) => object,